@@ -0,0 +1,53 @@
+// benchmark_intersect.rs - Mide cuántas intersecciones rayo-bloque por segundo se calculan
+//
+// cargo run --release --example benchmark_intersect
+use std::hint::black_box;
+use std::time::Instant;
+
+use project2_graphics::block::Block;
+use project2_graphics::material::Material;
+use project2_graphics::ray_intersect::RayIntersect;
+use project2_graphics::rng::Rng;
+use raylib::prelude::*;
+
+const RAYS: usize = 1_000_000;
+
+fn main() {
+    let block = Block::new_cubic(Vector3::zero(), 1.0, Material::black());
+
+    // Rayos precalculados (así se mide solo la intersección): salen de un
+    // plano frente al bloque hacia puntos cerca de él, aciertan y fallan
+    let mut rng = Rng::new(42, 0);
+    let rays: Vec<(Vector3, Vector3)> = (0..RAYS)
+        .map(|_| {
+            let origin = Vector3::new(
+                rng.next_f32() * 8.0 - 4.0,
+                rng.next_f32() * 8.0 - 4.0,
+                -6.0,
+            );
+            let target = Vector3::new(
+                rng.next_f32() * 2.0 - 1.0,
+                rng.next_f32() * 2.0 - 1.0,
+                rng.next_f32() * 2.0 - 1.0,
+            );
+            (origin, (target - origin).normalized())
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut hits = 0;
+    for (origin, dir) in &rays {
+        if black_box(block.ray_intersect(black_box(origin), black_box(dir))).is_intersecting {
+            hits += 1;
+        }
+    }
+    let seconds = start.elapsed().as_secs_f64();
+
+    println!(
+        "{} rayos en {:.1} ms: {:.1} millones/s ({} impactos)",
+        RAYS,
+        seconds * 1000.0,
+        RAYS as f64 / seconds / 1e6,
+        hits
+    );
+}
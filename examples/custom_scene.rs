@@ -0,0 +1,114 @@
+// custom_scene.rs - Pirámide armada con las funciones de escena, vista en una ventana
+//
+// cargo run --example custom_scene (desde la raíz, para encontrar textures/)
+// Flechas izquierda/derecha: girar alrededor; arriba/abajo: altura; ESC: salir
+use std::f32::consts::{FRAC_PI_3, TAU};
+
+use project2_graphics::block_types::BlockType;
+use project2_graphics::camera::CameraConfig;
+use project2_graphics::light::Light;
+use project2_graphics::render_settings::RenderSettings;
+use project2_graphics::renderer::Renderer;
+use project2_graphics::scene::{Scene, load_minecraft_textures};
+use project2_graphics::textures::TextureManager;
+use raylib::prelude::*;
+
+const WINDOW_WIDTH: i32 = 800;
+const WINDOW_HEIGHT: i32 = 600;
+/// Resolución interna; se escala a la ventana
+const RENDER_WIDTH: u32 = 320;
+const RENDER_HEIGHT: u32 = 240;
+
+/// Pirámide escalonada de arena sobre un piso de césped, con un bloque de
+/// sol en la punta
+fn build_pyramid() -> Scene {
+    let mut scene = Scene::default();
+    for x in -4..=4 {
+        for z in -4..=4 {
+            let position = Vector3::new(x as f32, 0.0, z as f32);
+            scene.place_block(BlockType::Grass.to_block(position, 1.0));
+        }
+    }
+    for level in 0..3 {
+        let half = 2 - level;
+        for x in -half..=half {
+            for z in -half..=half {
+                let position = Vector3::new(x as f32, (level + 1) as f32, z as f32);
+                scene.place_block(BlockType::Sand.to_block(position, 1.0));
+            }
+        }
+    }
+    scene.place_block(BlockType::Sun.to_block(Vector3::new(0.0, 4.0, 0.0), 1.0));
+    scene
+}
+
+fn main() {
+    let scene = build_pyramid();
+    // El bloque de sol trae su propia luz; se suma una de relleno
+    let mut lights: Vec<Light> = scene.iter().filter_map(|block| block.emission).collect();
+    lights.push(Light::new(
+        Vector3::new(-6.0, 8.0, -6.0),
+        Vector3::new(0.9, 0.9, 1.0),
+        2.5,
+    ));
+
+    let (mut rl, thread) = raylib::init()
+        .size(WINDOW_WIDTH, WINDOW_HEIGHT)
+        .title("Pirámide - project2-graphics")
+        .build();
+    rl.set_target_fps(60);
+
+    let mut textures = TextureManager::new();
+    if let Err(errors) = load_minecraft_textures(&mut rl, &thread, &mut textures) {
+        // Sin texturas los bloques usan su color base
+        for err in errors {
+            eprintln!("{}", err);
+        }
+    }
+
+    let settings = RenderSettings {
+        shadows: true,
+        ..RenderSettings::default()
+    };
+    let mut renderer = Renderer::new(RENDER_WIDTH, RENDER_HEIGHT, settings);
+    let (mut angle, mut height) = (0.8f32, 5.0f32);
+
+    while !rl.window_should_close() {
+        let dt = rl.get_frame_time();
+        if rl.is_key_down(KeyboardKey::KEY_LEFT) {
+            angle -= dt;
+        }
+        if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+            angle += dt;
+        }
+        if rl.is_key_down(KeyboardKey::KEY_UP) {
+            height = (height + 4.0 * dt).min(15.0);
+        }
+        if rl.is_key_down(KeyboardKey::KEY_DOWN) {
+            height = (height - 4.0 * dt).max(1.0);
+        }
+        angle = angle.rem_euclid(TAU);
+
+        // Órbita alrededor del centro de la pirámide
+        let eye = Vector3::new(10.0 * angle.cos(), height, 10.0 * angle.sin());
+        let forward = (Vector3::new(0.0, 1.5, 0.0) - eye).normalized();
+        let camera = CameraConfig::new(
+            eye,
+            forward.z.atan2(forward.x),
+            forward.y.asin(),
+            RENDER_WIDTH as usize,
+            RENDER_HEIGHT as usize,
+            FRAC_PI_3,
+            RENDER_WIDTH as f32 / RENDER_HEIGHT as f32,
+        );
+        renderer.render(&scene, &lights, &camera, &textures);
+        renderer.framebuffer.swap();
+
+        let mut d = rl.begin_drawing(&thread);
+        d.clear_background(Color::BLACK);
+        let source = Rectangle::new(0.0, 0.0, RENDER_WIDTH as f32, RENDER_HEIGHT as f32);
+        let dest = Rectangle::new(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32);
+        renderer.framebuffer.present_scaled(&mut d, &thread, source, dest);
+        d.draw_fps(10, 10);
+    }
+}
@@ -0,0 +1,115 @@
+// headless_render.rs - Escena chica armada en código, con texturas procedurales, a un PPM
+//
+// cargo run --example headless_render [salida.ppm]
+use std::f32::consts::FRAC_PI_3;
+use std::time::Instant;
+
+use project2_graphics::block::Block;
+use project2_graphics::block_types::BlockType;
+use project2_graphics::camera::CameraConfig;
+use project2_graphics::framebuffer::{ExportError, write_ppm};
+use project2_graphics::light::Light;
+use project2_graphics::material::Material;
+use project2_graphics::render_settings::RenderSettings;
+use project2_graphics::renderer::Renderer;
+use project2_graphics::scene::{Scene, mark_interior_faces};
+use project2_graphics::textures::TextureManager;
+use raylib::prelude::*;
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 192;
+
+/// Material difuso que toma el color de una textura
+fn textured(texture: &str) -> Material {
+    Material::new(
+        Vector3::one(),
+        [0.9, 0.1],
+        10.0,
+        0.0,
+        0.0,
+        1.0,
+        Some(texture.to_string()),
+        None,
+    )
+}
+
+fn main() -> Result<(), ExportError> {
+    let output = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "headless_render.ppm".to_string());
+
+    // Texturas procedurales: no hace falta ningún archivo
+    let mut textures = TextureManager::new();
+    textures.insert_procedural("proc/checker", 16, 16, |x, y| {
+        if (x / 4 + y / 4) % 2 == 0 {
+            Vector3::new(0.85, 0.85, 0.8)
+        } else {
+            Vector3::new(0.25, 0.3, 0.35)
+        }
+    });
+    textures.insert_procedural("proc/bricks", 32, 32, |x, y| {
+        // Hileras de 8 texels desplazadas media pieza una de otra
+        let offset = if (y / 8) % 2 == 0 { 0 } else { 8 };
+        if y % 8 == 0 || (x + offset) % 16 == 0 {
+            Vector3::new(0.8, 0.78, 0.72)
+        } else {
+            Vector3::new(0.6, 0.22, 0.16)
+        }
+    });
+
+    // Piso de 7x7, una columna de ladrillos, un espejo y un vidrio
+    let floor = textured("proc/checker");
+    let bricks = textured("proc/bricks");
+    let mut blocks = Vec::new();
+    for x in -3..=3 {
+        for z in -3..=3 {
+            let position = Vector3::new(x as f32, 0.0, z as f32);
+            blocks.push(Block::new_cubic(position, 1.0, floor.clone()));
+        }
+    }
+    for y in 1..=3 {
+        blocks.push(Block::new_cubic(Vector3::new(0.0, y as f32, 0.0), 1.0, bricks.clone()));
+    }
+    blocks.push(BlockType::Reflect.to_block(Vector3::new(-2.0, 1.0, 1.0), 1.0));
+    blocks.push(BlockType::Glass.to_block(Vector3::new(2.0, 1.0, -1.0), 1.0));
+    mark_interior_faces(&mut blocks);
+    let scene = Scene::new(blocks);
+
+    let lights = [
+        Light::new(Vector3::new(-4.0, 6.0, -4.0), Vector3::new(1.0, 0.95, 0.85), 3.0),
+        Light::new(Vector3::new(4.0, 5.0, 3.0), Vector3::new(0.6, 0.7, 1.0), 2.0),
+    ];
+
+    // Cámara mirando al centro de la columna
+    let eye = Vector3::new(5.0, 4.0, -6.0);
+    let forward = (Vector3::new(0.0, 1.5, 0.0) - eye).normalized();
+    let camera = CameraConfig::new(
+        eye,
+        forward.z.atan2(forward.x),
+        forward.y.asin(),
+        WIDTH as usize,
+        HEIGHT as usize,
+        FRAC_PI_3,
+        WIDTH as f32 / HEIGHT as f32,
+    );
+
+    let settings = RenderSettings {
+        shadows: true,
+        ambient_occlusion: true,
+        ..RenderSettings::default()
+    };
+    let mut renderer = Renderer::new(WIDTH, HEIGHT, settings);
+    let start = Instant::now();
+    let pixels = renderer.render(&scene, &lights, &camera, &textures);
+    let elapsed = start.elapsed();
+
+    write_ppm(&output, WIDTH, HEIGHT, pixels)?;
+    println!(
+        "{}x{} en {:.1} ms -> {}",
+        WIDTH,
+        HEIGHT,
+        elapsed.as_secs_f32() * 1000.0,
+        output
+    );
+    Ok(())
+}
@@ -0,0 +1,73 @@
+// adaptive.rs - Muestreo adaptativo: más rayos solo donde la imagen varía
+use raylib::prelude::*;
+
+use crate::post::luminance;
+
+/// Parámetros del muestreo adaptativo. Reemplaza a las muestras fijas por
+/// pixel: una pasada con un rayo por pixel y después muestras extra en los
+/// pixeles cuyo vecindario varía mucho (bordes, agua, sombras suaves).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AdaptiveSampling {
+    pub enabled: bool,
+    /// Varianza del brillo del vecindario 3x3 a partir de la cual un pixel
+    /// recibe muestras extra; cada múltiplo del umbral suma una. Con el
+    /// valor por defecto la isla queda a un 5% del error de 4 muestras fijas
+    /// con menos de la mitad de los rayos (ver el test del renderer).
+    pub threshold: f32,
+    /// Muestras máximas de un pixel, contando la primera
+    pub max_spp: u32,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.001,
+            max_spp: 8,
+        }
+    }
+}
+
+/// Muestras que debería tener cada pixel según la primera pasada `hdr` (un
+/// rayo por pixel). La varianza se mide sobre el brillo comprimido a
+/// [0, 1), así un pixel muy brillante no domina el vecindario; el cielo
+/// liso queda en 1 y los bordes suben hasta `max_spp`.
+pub fn sample_targets(
+    hdr: &[Vector3],
+    width: usize,
+    height: usize,
+    settings: &AdaptiveSampling,
+) -> Vec<u32> {
+    let brightness: Vec<f32> = hdr
+        .iter()
+        .map(|&color| {
+            let l = luminance(color);
+            l / (1.0 + l)
+        })
+        .collect();
+    let max_spp = settings.max_spp.max(1);
+    let threshold = settings.threshold.max(f32::EPSILON);
+
+    let mut targets = vec![1; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = 0.0;
+            let mut sum_squares = 0.0;
+            let mut count = 0.0;
+            for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                    let value = brightness[ny * width + nx];
+                    sum += value;
+                    sum_squares += value * value;
+                    count += 1.0;
+                }
+            }
+            let mean = sum / count;
+            let variance = (sum_squares / count - mean * mean).max(0.0);
+            targets[y * width + x] = ((variance / threshold).ceil() as u32).clamp(1, max_spp);
+        }
+    }
+    targets
+}
@@ -0,0 +1,87 @@
+// animation.rs - Animaciones simples sobre los bloques de la escena
+use crate::material::Material;
+use crate::scene::index_after_removal;
+
+/// Hace que el material de un bloque oscile entre dos estados
+/// (ej: magma que se calienta y se enfría).
+#[derive(Debug, Clone)]
+pub struct MaterialAnimation {
+    /// Índice del bloque animado dentro de la escena
+    pub block_index: usize,
+    /// Material en el punto mínimo del ciclo
+    pub from: Material,
+    /// Material en el punto máximo del ciclo
+    pub to: Material,
+    /// Duración de un ciclo completo en segundos
+    pub period: f32,
+}
+
+impl MaterialAnimation {
+    pub fn new(block_index: usize, from: Material, to: Material, period: f32) -> Self {
+        Self {
+            block_index,
+            from,
+            to,
+            period,
+        }
+    }
+
+    /// Factor de mezcla en [0, 1] para un tiempo dado (onda coseno suave)
+    #[inline]
+    pub fn factor(&self, time: f32) -> f32 {
+        let phase = time * std::f32::consts::TAU / self.period.max(1e-3);
+        0.5 - 0.5 * phase.cos()
+    }
+
+    /// Ajusta el índice tras quitar el bloque `removed` de la escena, con
+    /// `last` el índice que tenía el último (ver `index_after_removal`).
+    /// Devuelve false si la animación era de ese bloque y debe descartarse.
+    pub fn on_block_removed(&mut self, removed: usize, last: usize) -> bool {
+        match index_after_removal(self.block_index, removed, last) {
+            Some(index) => {
+                self.block_index = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Material interpolado para un tiempo dado
+    pub fn material(&self, time: f32) -> Material {
+        Material::lerp(&self.from, &self.to, self.factor(time))
+    }
+}
+
+/// Materiales de las animaciones evaluados para un frame. El renderer los
+/// calcula una vez por frame con el tiempo de los ajustes y el trazado los
+/// usa en lugar del material guardado en el bloque, así la escena no se
+/// modifica (ni se clona) en cada frame de la interfaz.
+#[derive(Debug, Clone, Default)]
+pub struct AnimatedMaterials {
+    /// (índice del bloque, material), ordenado por índice
+    by_block: Vec<(usize, Material)>,
+}
+
+impl AnimatedMaterials {
+    pub fn evaluate(animations: &[MaterialAnimation], time: f32) -> Self {
+        let mut by_block: Vec<_> = animations
+            .iter()
+            .map(|animation| (animation.block_index, animation.material(time)))
+            .collect();
+        by_block.sort_by_key(|(index, _)| *index);
+        Self { by_block }
+    }
+
+    /// Material animado del bloque `block_index`, si tiene uno
+    #[inline]
+    pub fn get(&self, block_index: usize) -> Option<&Material> {
+        self.by_block
+            .binary_search_by_key(&block_index, |(index, _)| *index)
+            .ok()
+            .map(|i| &self.by_block[i].1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_block.is_empty()
+    }
+}
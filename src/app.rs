@@ -0,0 +1,1681 @@
+// app.rs - Modo interactivo: la ventana, el loop principal y su estado
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use raylib::prelude::*;
+
+use project2_graphics::animation::MaterialAnimation;
+use project2_graphics::block::Block;
+use project2_graphics::block_inspector::BlockInspector;
+use project2_graphics::block_registry::{BLOCKS_PATH, BlockRegistry, RegisteredBlock};
+use project2_graphics::block_shape::{BlockShape, MAX_SNOW_LAYERS};
+use project2_graphics::block_types::BlockType;
+use project2_graphics::camera::CameraConfig;
+use project2_graphics::chest::{ChestPanel, is_chest, toggle_lid};
+use project2_graphics::config::{CONFIG_PATH, Config, ConfigError};
+use project2_graphics::console::Console;
+use project2_graphics::console_commands::{self, CommandContext};
+use project2_graphics::debug_stats::{DebugStats, RenderStats};
+use project2_graphics::error::AppError;
+use project2_graphics::events::{GamepadState, handle_camera_input, handle_gamepad_input};
+use project2_graphics::frame_limiter::FrameLimiter;
+use project2_graphics::framebuffer::{ExportError, Framebuffer, ScreenMapping, color_to_u32};
+use project2_graphics::gbuffer::{ViewMode, depth_range};
+use project2_graphics::headless::SceneSource;
+use project2_graphics::hotbar::Hotbar;
+use project2_graphics::hud::{Hud, draw_hud};
+use project2_graphics::input_session::InputSession;
+use project2_graphics::keybinds::{Action, InputMap, KEYBINDS_PATH};
+use project2_graphics::light::Light;
+use project2_graphics::light_editor::LightEditor;
+use project2_graphics::logger;
+use project2_graphics::material_editor::MaterialEditor;
+use project2_graphics::notifications::{Notifications, Severity};
+use project2_graphics::pause_menu::{PauseAction, PauseMenu};
+use project2_graphics::post_menu::PostProcessMenu;
+use project2_graphics::quality::QualityPreset;
+use project2_graphics::recorder::FrameRecorder;
+use project2_graphics::render_thread::{RenderJob, RenderThread};
+use project2_graphics::renderer::finish_frame;
+use project2_graphics::scene::{
+    Scene, SceneKind, collect_lights, create_optimized_scene, create_scene_animations,
+    default_lights, load_minecraft_textures,
+};
+use project2_graphics::scene_file::{
+    SCENE_PATH, load_block_definitions, load_scene, load_scene_checked, save_scene,
+};
+use project2_graphics::screenshot::{
+    HdrCapture, read_window, save_hdr_capture, save_screenshot, save_screenshot_pixels,
+};
+use project2_graphics::settings::{SETTINGS_PATH, Settings};
+use project2_graphics::shared_scene::SharedScene;
+use project2_graphics::snell::{BlockPick, pick_block};
+use project2_graphics::textures::{TextureError, TextureManager};
+use project2_graphics::tile_heatmap::TileTime;
+use project2_graphics::time_panel::TimePanel;
+use project2_graphics::tnt::Explosives;
+use project2_graphics::view_file::{SavedView, load_view, save_view, view_path};
+use project2_graphics::wireframe::{
+    WireframeMode, draw_block_boxes, draw_box_outline, draw_grid_cells,
+};
+
+/// Abre la ventana y corre el modo interactivo hasta que se cierra. `args`
+/// es la línea de comandos completa (opciones de `Config` y de la sesión de
+/// entrada, `--from-screenshot`, `--fixed-resolution`, `--strict`).
+pub fn run(args: &[String]) -> Result<(), AppError> {
+    let strict = args.iter().any(|arg| arg == "--strict");
+    // Opciones de arranque de config.toml; la línea de comandos las pisa
+    let mut config = Config::load(CONFIG_PATH);
+    config.apply_args(args)?;
+    // --from-screenshot <captura> arranca con la vista guardada junto a una
+    // captura: cámara, FOV, escena y ajustes
+    let saved_view = match args.iter().position(|arg| arg == "--from-screenshot") {
+        Some(i) => {
+            let path = args.get(i + 1).ok_or_else(|| {
+                ConfigError::MissingValue("--from-screenshot <captura o su vista>".to_string())
+            })?;
+            Some(load_view(view_path(Path::new(path)))?)
+        }
+        None => None,
+    };
+    if let Some(view) = &saved_view {
+        config.scene = view.scene.clone();
+        config.fov_degrees = view.fov_degrees;
+    }
+    // --strict también cubre los materiales de blocks.txt y de la escena
+    if strict {
+        let issues = file_material_issues(&config);
+        for issue in &issues {
+            error!("Advertencia de material en {}", issue);
+        }
+        if !issues.is_empty() {
+            return Err(AppError::StrictMaterials(issues.len()));
+        }
+    }
+    // Sesión de entrada: --record <archivo> graba las acciones de cada frame
+    // y --replay <archivo> las reproduce en lugar del teclado
+    let session = InputSession::from_args(args)?;
+
+    // Inicialización de ventana y Raylib
+    let mut builder = raylib::init();
+    builder
+        .size(config.window_width, config.window_height)
+        .title("Minecraft Raytracer")
+        .log_level(TraceLogLevel::LOG_INFO)
+        .resizable();
+    // vsync espera además al refresco del monitor
+    if config.vsync {
+        builder.vsync();
+    }
+    let (mut rl, thread) = builder.build();
+    // ESC abre el menú de pausa en lugar de cerrar la ventana
+    rl.set_exit_key(None);
+
+    // Texturas
+    let mut texture_manager = TextureManager::new();
+    // Si faltan, los bloques usan su color base; se avisa en pantalla
+    let texture_errors = load_minecraft_textures(&mut rl, &thread, &mut texture_manager)
+        .err()
+        .unwrap_or_default();
+
+    // --fixed-resolution mantiene la resolución interna al redimensionar
+    let resize_framebuffer = !args.iter().any(|arg| arg == "--fixed-resolution");
+    let mut app = App::new(
+        config,
+        saved_view,
+        session,
+        texture_manager,
+        &texture_errors,
+        resize_framebuffer,
+    );
+
+    // === Loop principal ===
+    while !rl.window_should_close() {
+        let mut frame = app.begin_frame(&rl);
+        if app.handle_menus(&mut rl, &mut frame) == Flow::Quit {
+            break;
+        }
+        app.handle_input(&rl, &frame);
+        let camera = app.camera_config();
+        frame.hovered = app.pick_center(&camera);
+        app.handle_panels(&mut rl, &frame, &camera);
+        app.edit_blocks(&rl, &mut frame, &camera);
+        app.update_explosives(&mut frame);
+        app.present_finished_frame(&mut frame);
+        app.submit_render_job(&frame, &camera);
+        app.update_debug_stats(&mut frame);
+        app.draw(&mut rl, &thread, &frame);
+    }
+
+    app.finish();
+    Ok(())
+}
+
+/// Si el loop sigue después de los menús
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    Continue,
+    Quit,
+}
+
+/// Lo que se calcula al principio de cada vuelta del loop y leen sus pasos
+struct Frame {
+    /// Tamaño de ventana actual (0 si está minimizada)
+    window_width: i32,
+    window_height: i32,
+    minimized: bool,
+    /// El framebuffer cambió de tamaño en este frame
+    resized: bool,
+    /// Ventana <-> framebuffer para el mouse en este frame
+    mapping: ScreenMapping,
+    /// Ajustes al empezar el frame, para saber si hay que trazar en la pausa
+    settings_before: Settings,
+    /// Se reemplazó la escena o una explosión la cambió
+    scene_changed: bool,
+    /// Segundos simulados de este frame (los de la sesión reproducida)
+    dt: f32,
+    replaying: bool,
+    /// La consola tiene el teclado
+    typing: bool,
+    paused: bool,
+    /// Menús y edición no reciben teclado ni mouse (pausa o consola)
+    input_locked: bool,
+    /// Bloque bajo la mira
+    hovered: Option<BlockPick>,
+    /// Estadísticas del frame terminado en esta vuelta, si llegó uno
+    render_stats: Option<RenderStats>,
+}
+
+/// Estado del modo interactivo entre frames
+struct App {
+    config: Config,
+    resize_framebuffer: bool,
+    /// Límite de FPS (0 = sin límite); se cambia con F12
+    frame_limiter: FrameLimiter,
+    session: InputSession,
+    input: InputMap,
+    gamepad: GamepadState,
+    settings: Settings,
+
+    camera_pos: Vector3,
+    camera_yaw: f32,
+    camera_pitch: f32,
+    fov: f32,
+    /// Pose del último frame enviado, para el desenfoque de movimiento
+    previous_camera: Option<CameraConfig>,
+
+    scene_kind: SceneKind,
+    /// Nombre de la escena para las vistas de las capturas (ver `SceneKind::name`)
+    scene_name: String,
+    scene: SharedScene,
+    /// Hay ediciones sin guardar (bloques o materiales)
+    scene_dirty: bool,
+    animations: Vec<MaterialAnimation>,
+    /// TNT encendidas, destellos y sacudida de cámara
+    explosives: Explosives,
+    /// Luces que no vienen de bloques; el editor de luces las modifica
+    fixed_lights: Vec<Light>,
+    lights: Arc<Vec<Light>>,
+    texture_manager: Arc<TextureManager>,
+    /// Tipos de bloque propios: los de blocks.txt (si existe) y los de la
+    /// paleta de cada escena que se carga; la barra los lista al final
+    block_registry: BlockRegistry,
+    hotbar: Hotbar,
+
+    /// Framebuffer que se presenta; el hilo de render traza en el suyo y se
+    /// intercambian al terminar cada frame
+    framebuffer: Framebuffer,
+    render_thread: RenderThread,
+    use_multithreading: bool,
+    view_mode: ViewMode,
+    /// Hay que trazar un frame nuevo en cuanto el hilo quede libre
+    render_requested: bool,
+    adapted_ev: f32,
+    /// Tiempo desde el último frame resuelto, para la exposición automática
+    exposure_dt: f32,
+    render_time: Duration,
+    /// Costo del filtro de ruido en el último frame resuelto (None = apagado)
+    denoise_time: Option<Duration>,
+    /// Rayos del último frame terminado (crece con las muestras por pixel)
+    frame_rays: u64,
+    /// Frames promediados en el último frame terminado (acumulación)
+    accumulated_frames: u32,
+    /// Tiempos por tile del último frame renderizado (mapa de calor con K)
+    tile_times: Vec<TileTime>,
+
+    material_editor: MaterialEditor,
+    light_editor: LightEditor,
+    block_inspector: BlockInspector,
+    time_panel: TimePanel,
+    chest_panel: ChestPanel,
+    post_menu: PostProcessMenu,
+    pause_menu: PauseMenu,
+    console: Console,
+    notifications: Notifications,
+    show_help: bool,
+    show_debug: bool,
+    wireframe: WireframeMode,
+    debug_stats: DebugStats,
+
+    screenshot_requested: bool,
+    /// Captura HDR (Shift + captura): el buffer lineal del próximo frame final
+    hdr_screenshot_requested: bool,
+    /// Frame nuevo que se graba desde la ventana (con el HUD) al dibujarla
+    window_capture_pending: bool,
+    recorder: FrameRecorder,
+
+    frame_count: u32,
+    /// Segundos simulados (suma de los dt de cada frame)
+    sim_time: f32,
+    last_fps_update: Instant,
+}
+
+impl App {
+    fn new(
+        config: Config,
+        saved_view: Option<SavedView>,
+        session: InputSession,
+        texture_manager: TextureManager,
+        texture_errors: &[TextureError],
+        resize_framebuffer: bool,
+    ) -> Self {
+        // Cámara
+        let mut camera_pos = Vector3::new(0.0, 2.0, -6.0);
+        let mut camera_yaw = 0.0_f32;
+        let mut camera_pitch = -0.2_f32;
+        if let Some(view) = &saved_view {
+            camera_pos = view.camera_pos;
+            camera_yaw = view.camera_yaw;
+            camera_pitch = view.camera_pitch;
+        }
+
+        // Escena y recursos compartidos
+        let (scene_kind, scene) = initial_scene(&config);
+        let scene_name = match scene_kind {
+            SceneKind::Saved => config.scene.clone(),
+            kind => kind.name().to_string(),
+        };
+        let scene = SharedScene::new(scene);
+        let fixed_lights = default_lights();
+        let lights = Arc::new(collect_lights(&scene, &fixed_lights));
+        let texture_manager = Arc::new(texture_manager);
+
+        // Teclas configurables; con un archivo inválido se usan las de siempre
+        let input = InputMap::load(KEYBINDS_PATH).unwrap_or_else(|err| {
+            warn!("Error en las teclas: {}", err);
+            InputMap::default()
+        });
+        info!("Controles:\n{}", input.controls_text());
+        info!(
+            "Ventana: {}x{}, resolución interna: {}x{}, FOV {}°",
+            config.window_width,
+            config.window_height,
+            config.render_width,
+            config.render_height,
+            config.fov_degrees
+        );
+
+        // La vista de --from-screenshot reemplaza a settings.toml
+        let mut settings = match saved_view {
+            Some(view) => view.settings,
+            None => Settings::load(SETTINGS_PATH),
+        };
+        if let Some(preset) = config.quality {
+            preset.apply(&mut settings);
+        }
+        let framebuffer = Framebuffer::new(config.render_width as u32, config.render_height as u32);
+        let render_thread = RenderThread::spawn(Arc::clone(&texture_manager), config.threads);
+        let recorder = FrameRecorder::new(&settings.record_dir, settings.record_every_nth);
+
+        let mut app = Self {
+            frame_limiter: FrameLimiter::new(config.fps_cap),
+            fov: config.fov(),
+            resize_framebuffer,
+            session,
+            input,
+            gamepad: GamepadState::new(),
+            settings,
+            camera_pos,
+            camera_yaw,
+            camera_pitch,
+            previous_camera: None,
+            scene_kind,
+            scene_name,
+            animations: create_scene_animations(&scene),
+            scene,
+            scene_dirty: false,
+            explosives: Explosives::new(),
+            fixed_lights,
+            lights,
+            texture_manager,
+            block_registry: BlockRegistry::new(),
+            hotbar: Hotbar::new(),
+            framebuffer,
+            render_thread,
+            use_multithreading: true,
+            view_mode: ViewMode::Final,
+            render_requested: true,
+            adapted_ev: 0.0,
+            exposure_dt: 0.0,
+            render_time: Duration::ZERO,
+            denoise_time: None,
+            frame_rays: 0,
+            accumulated_frames: 1,
+            tile_times: Vec::new(),
+            material_editor: MaterialEditor::new(),
+            light_editor: LightEditor::new(),
+            block_inspector: BlockInspector::new(),
+            time_panel: TimePanel::new(),
+            chest_panel: ChestPanel::new(),
+            post_menu: PostProcessMenu::new(),
+            pause_menu: PauseMenu::new(),
+            console: Console::new(),
+            notifications: Notifications::new(),
+            show_help: false,
+            show_debug: false,
+            wireframe: WireframeMode::default(),
+            debug_stats: DebugStats::default(),
+            screenshot_requested: false,
+            hdr_screenshot_requested: false,
+            window_capture_pending: false,
+            recorder,
+            frame_count: 0,
+            sim_time: 0.0,
+            last_fps_update: Instant::now(),
+            config,
+        };
+        app.report_texture_errors(texture_errors);
+        if Path::new(BLOCKS_PATH).exists() {
+            match load_block_definitions(BLOCKS_PATH, &mut app.block_registry) {
+                Ok(count) => info!("{} tipos de bloque propios en {}", count, BLOCKS_PATH),
+                Err(err) => app
+                    .notifications
+                    .warning(format!("No se cargó {}: {}", BLOCKS_PATH, err)),
+            }
+        }
+        register_palette(&mut app.block_registry, &app.scene, &mut app.hotbar);
+        app
+    }
+
+    /// Cada falla va solo a la terminal; el HUD muestra el resumen
+    fn report_texture_errors(&mut self, errors: &[TextureError]) {
+        if errors.is_empty() {
+            return;
+        }
+        for err in errors {
+            error!(target: logger::NOTIFICATION_TARGET, "{}", err);
+        }
+        self.notifications.notify(
+            format!(
+                "No se cargaron {} texturas (ver terminal): se usan los colores base",
+                errors.len()
+            ),
+            Severity::Error,
+            10.0,
+        );
+    }
+
+    /// Tamaño de la ventana, resolución interna y acciones del frame
+    fn begin_frame(&mut self, rl: &RaylibHandle) -> Frame {
+        let window_width = rl.get_screen_width();
+        let window_height = rl.get_screen_height();
+        let minimized = window_width <= 0 || window_height <= 0 || rl.is_window_minimized();
+        // Resolución interna: la ventana con la escala inicial de la
+        // configuración (o fija con --fixed-resolution), a la mitad con
+        // `half_resolution`
+        let (base_width, base_height) = if self.resize_framebuffer {
+            let (scale_x, scale_y) = self.config.render_scale();
+            (
+                (window_width as f32 / scale_x) as i32,
+                (window_height as f32 / scale_y) as i32,
+            )
+        } else {
+            (self.config.render_width, self.config.render_height)
+        };
+        let divisor = if self.settings.render.half_resolution {
+            2
+        } else {
+            1
+        };
+        let target_size = (
+            (base_width / divisor).max(1) as u32,
+            (base_height / divisor).max(1) as u32,
+        );
+        let resized =
+            !minimized && target_size != (self.framebuffer.width, self.framebuffer.height);
+        if resized {
+            self.framebuffer.resize(target_size.0, target_size.1);
+        }
+        let mapping = ScreenMapping::new(
+            self.settings.present_mode,
+            self.framebuffer.width,
+            self.framebuffer.height,
+            window_width.max(0) as u32,
+            window_height.max(0) as u32,
+        );
+
+        // Acciones del frame (teclado o sesión reproducida). La simulación
+        // avanza con su dt para que una reproducción sea idéntica.
+        self.session
+            .update(rl, &mut self.input, &mut self.notifications);
+        let dt = self.input.frame().dt;
+        self.sim_time += dt;
+        self.notifications.show_logged();
+        self.notifications.update(rl.get_frame_time());
+
+        Frame {
+            window_width,
+            window_height,
+            minimized,
+            resized,
+            mapping,
+            settings_before: self.settings.clone(),
+            scene_changed: false,
+            dt,
+            replaying: self.session.is_replaying(),
+            typing: false,
+            paused: false,
+            input_locked: false,
+            hovered: None,
+            render_stats: None,
+        }
+    }
+
+    /// Consola y menú de pausa, que se quedan con el teclado mientras están
+    /// abiertos. Devuelve `Flow::Quit` si se eligió salir.
+    fn handle_menus(&mut self, rl: &mut RaylibHandle, frame: &mut Frame) -> Flow {
+        // Consola (~): mientras está abierta el teclado es suyo, incluso en el
+        // frame en que se cierra con ESC (para no abrir también la pausa)
+        self.input.set_enabled(true);
+        if !self.pause_menu.is_open() && self.input.is_pressed(Action::ToggleConsole) {
+            self.console.toggle(rl);
+        }
+        frame.typing = self.console.is_open();
+        if let Some(line) = self.console.handle_input(rl) {
+            self.run_command(&line, frame);
+        }
+        self.input.set_enabled(!frame.typing);
+
+        // Menú de pausa (ESC)
+        let action = self.pause_menu.handle_input(
+            rl,
+            &self.input,
+            &mut self.settings.render,
+            self.scene_dirty,
+        );
+        match action {
+            Some(PauseAction::SwitchScene) => self.switch_scene(frame),
+            Some(PauseAction::SaveScene)
+                if save_current_scene(&self.scene, &mut self.notifications) =>
+            {
+                self.scene_dirty = false;
+            }
+            Some(PauseAction::SaveSettings) => {
+                if !frame.minimized {
+                    (self.config.window_width, self.config.window_height) =
+                        (frame.window_width, frame.window_height);
+                }
+                self.config.fps_cap = self.frame_limiter.cap();
+                save_settings(&self.settings, &self.config, &mut self.notifications);
+            }
+            Some(PauseAction::SaveAndQuit)
+                if save_current_scene(&self.scene, &mut self.notifications) =>
+            {
+                return Flow::Quit;
+            }
+            Some(PauseAction::Quit) => return Flow::Quit,
+            _ => {}
+        }
+        frame.paused = self.pause_menu.is_open();
+        // Menús y edición solo reciben teclado y mouse fuera de la pausa y
+        // con la consola cerrada
+        frame.input_locked = frame.paused || frame.typing;
+        Flow::Continue
+    }
+
+    /// Corre una línea de la consola y completa lo que pidió el comando
+    fn run_command(&mut self, line: &str, frame: &mut Frame) {
+        let mut context = CommandContext {
+            settings: &mut self.settings,
+            use_multithreading: &mut self.use_multithreading,
+            camera_pos: &mut self.camera_pos,
+            camera_yaw: &mut self.camera_yaw,
+            camera_pitch: &mut self.camera_pitch,
+            fov: &mut self.fov,
+            scene: &mut self.scene,
+            scene_name: &mut self.scene_name,
+            fixed_lights: &mut self.fixed_lights,
+            scene_replaced: false,
+            scene_edited: false,
+            screenshot_requested: false,
+        };
+        let output = console_commands::execute(&mut context, line);
+        let (replaced, edited) = (context.scene_replaced, context.scene_edited);
+        self.screenshot_requested |= context.screenshot_requested;
+        self.console.print(&output);
+        if replaced {
+            self.scene_kind = SceneKind::from_name(&self.scene_name);
+            self.animations = create_scene_animations(&self.scene);
+            self.explosives.clear();
+            register_palette(&mut self.block_registry, &self.scene, &mut self.hotbar);
+            self.material_editor.close();
+            self.block_inspector.close();
+            self.chest_panel.close();
+            self.scene_dirty = false;
+            frame.scene_changed = true;
+        }
+        self.scene_dirty |= edited;
+        self.lights = Arc::new(collect_lights(&self.scene, &self.fixed_lights));
+    }
+
+    /// Pasa a la siguiente escena incorporada que se pueda construir
+    fn switch_scene(&mut self, frame: &mut Frame) {
+        // La isla siempre se puede construir, así que el ciclo termina
+        let mut next = self.scene_kind.next();
+        let blocks = loop {
+            match next.build() {
+                Ok(blocks) => break blocks,
+                Err(err) => {
+                    self.notifications.warning(format!(
+                        "No se pudo cargar {}: {}",
+                        next.label(),
+                        err
+                    ));
+                    next = next.next();
+                }
+            }
+        };
+        self.scene_kind = next;
+        self.scene_name = self.scene_kind.name().to_string();
+        self.scene.replace(blocks);
+        self.animations = create_scene_animations(&self.scene);
+        self.explosives.clear();
+        register_palette(&mut self.block_registry, &self.scene, &mut self.hotbar);
+        self.lights = Arc::new(collect_lights(&self.scene, &self.fixed_lights));
+        self.material_editor.close();
+        self.chest_panel.close();
+        self.scene_dirty = false;
+        frame.scene_changed = true;
+        self.notifications
+            .info(format!("Escena: {}", self.scene_kind.label()));
+    }
+
+    /// Cámara y teclas que cambian ajustes, vistas y capturas
+    fn handle_input(&mut self, rl: &RaylibHandle, frame: &Frame) {
+        let input = &self.input;
+        let notifications = &mut self.notifications;
+        let settings = &mut self.settings;
+
+        // Movimiento de cámara (las flechas son de los menús mientras están abiertos)
+        let menu_open = self.material_editor.is_open()
+            || self.light_editor.is_open()
+            || self.post_menu.is_open();
+        if !menu_open && !frame.input_locked {
+            handle_camera_input(
+                input,
+                &mut self.camera_pos,
+                &mut self.camera_yaw,
+                &mut self.camera_pitch,
+            );
+        }
+        // Los sticks no se graban: durante una reproducción se ignoran
+        let gamepad_active = self.gamepad.update(rl, notifications);
+        if gamepad_active && !menu_open && !frame.input_locked && !frame.replaying {
+            handle_gamepad_input(
+                rl,
+                &settings.gamepad,
+                &mut self.camera_pos,
+                &mut self.camera_yaw,
+                &mut self.camera_pitch,
+                frame.dt,
+            );
+        }
+
+        // Toggle multihilo
+        if input.is_pressed(Action::ToggleMultithread) {
+            self.use_multithreading = !self.use_multithreading;
+            notifications.info(format!(
+                "Multihilo: {}",
+                if self.use_multithreading { "ON" } else { "OFF" }
+            ));
+        }
+
+        // Operador de mapeo tonal y exposición
+        if input.is_pressed(Action::CycleToneMapping) {
+            settings.tone_map.operator = settings.tone_map.operator.next();
+            notifications.info(format!(
+                "Mapeo tonal: {}",
+                settings.tone_map.operator.label()
+            ));
+        }
+        if input.is_pressed(Action::ExposureUp) {
+            settings.tone_map.adjust_exposure(1.0);
+        }
+        if input.is_pressed(Action::ExposureDown) {
+            settings.tone_map.adjust_exposure(-1.0);
+        }
+        if input.is_pressed(Action::ToggleAutoExposure) {
+            settings.auto_exposure.enabled = !settings.auto_exposure.enabled;
+            notifications.info(format!(
+                "Exposición automática: {}",
+                if settings.auto_exposure.enabled {
+                    "ON"
+                } else {
+                    "OFF"
+                }
+            ));
+        }
+
+        // Efectos del raytracer: F1 sombras, F2 reflejos, F9 refracción, F4 AO
+        let render_toggles = [
+            (Action::ToggleShadows, &mut settings.render.shadows),
+            (Action::ToggleReflections, &mut settings.render.reflections),
+            (Action::ToggleRefractions, &mut settings.render.refractions),
+            (
+                Action::ToggleAmbientOcclusion,
+                &mut settings.render.ambient_occlusion,
+            ),
+        ];
+        let mut render_changed = false;
+        for (action, enabled) in render_toggles {
+            if input.is_pressed(action) {
+                *enabled = !*enabled;
+                render_changed = true;
+            }
+        }
+        if render_changed {
+            notifications.info(settings.render.summary());
+        }
+
+        // Presets de calidad: desde una configuración personalizada empieza
+        // por el primero
+        if input.is_pressed(Action::CycleQualityPreset) {
+            let preset =
+                QualityPreset::detect(settings).map_or(QualityPreset::Fast, QualityPreset::next);
+            preset.apply(settings);
+            notifications.info(format!("Calidad: {}", preset.label()));
+        }
+
+        // Toggle bloom
+        if input.is_pressed(Action::ToggleBloom) {
+            settings.bloom.enabled = !settings.bloom.enabled;
+            notifications.info(format!(
+                "Bloom: {}",
+                if settings.bloom.enabled { "ON" } else { "OFF" }
+            ));
+        }
+
+        // Toggle FXAA
+        if input.is_pressed(Action::ToggleFxaa) {
+            settings.fxaa = !settings.fxaa;
+            notifications.info(format!(
+                "FXAA: {}",
+                if settings.fxaa { "ON" } else { "OFF" }
+            ));
+        }
+
+        // Filtro de ruido
+        if input.is_pressed(Action::ToggleDenoise) {
+            settings.denoise.enabled = !settings.denoise.enabled;
+            notifications.info(format!(
+                "Filtro de ruido: {}",
+                if settings.denoise.enabled {
+                    "ON"
+                } else {
+                    "OFF"
+                }
+            ));
+        }
+
+        // Modo dibujo animado
+        if input.is_pressed(Action::ToggleToon) {
+            settings.render.toon.enabled = !settings.render.toon.enabled;
+            notifications.info(format!(
+                "Modo dibujo: {}",
+                if settings.render.toon.enabled {
+                    "ON"
+                } else {
+                    "OFF"
+                }
+            ));
+        }
+
+        // Modo de presentación
+        if input.is_pressed(Action::CyclePresentMode) {
+            settings.present_mode = settings.present_mode.next();
+            notifications.info(format!("Presentación: {}", settings.present_mode.label()));
+        }
+
+        // Grabación de frames
+        if input.is_pressed(Action::ToggleRecording) {
+            if self.recorder.is_recording() {
+                stop_recording(&mut self.recorder, &settings.record_dir, notifications);
+            } else {
+                match self.recorder.start() {
+                    Ok(()) => {
+                        notifications.info(format!("Grabando frames en {}", settings.record_dir))
+                    }
+                    Err(e) => {
+                        notifications.error(format!("No se pudo iniciar la grabación: {}", e))
+                    }
+                }
+            }
+        }
+
+        // Captura: se guarda el próximo frame ya post-procesado; con Shift,
+        // su radiancia antes del mapeo tonal
+        if input.is_pressed(Action::Screenshot) {
+            if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT)
+            {
+                self.hdr_screenshot_requested = true;
+            } else {
+                self.screenshot_requested = true;
+            }
+        }
+
+        if input.is_pressed(Action::CycleFpsCap) {
+            self.frame_limiter.cycle();
+            notifications.info(format!("Límite de FPS: {}", self.frame_limiter.label()));
+        }
+
+        // Menú de post-procesado
+        if input.is_pressed(Action::TogglePostMenu) {
+            self.post_menu.toggle();
+        }
+        if !frame.input_locked {
+            self.post_menu.handle_input(rl, &mut settings.post);
+        }
+
+        // Toggle tramado
+        if input.is_pressed(Action::ToggleDither) {
+            settings.dither = !settings.dither;
+            notifications.info(format!(
+                "Tramado: {}",
+                if settings.dither { "ON" } else { "OFF" }
+            ));
+        }
+
+        // Ayuda con todas las teclas
+        if input.is_pressed(Action::ToggleHelp) {
+            self.show_help = !self.show_help;
+        }
+
+        // Pantalla de depuración con estadísticas
+        if input.is_pressed(Action::ToggleDebugStats) {
+            self.show_debug = !self.show_debug;
+        }
+        // Cajas de alambre: de los bloques, de la grilla o ninguna
+        if input.is_pressed(Action::ToggleWireframe) {
+            self.wireframe = self.wireframe.next();
+            notifications.info(format!("Alambre: {}", self.wireframe.label()));
+        }
+
+        // Vista de depuración (profundidad, normales, UV, ID de bloque)
+        if input.is_pressed(Action::CycleViewMode) {
+            self.view_mode = self.view_mode.next();
+            notifications.info(format!("Vista: {}", self.view_mode.label()));
+        }
+    }
+
+    /// Cámara con la pose y el framebuffer actuales
+    fn camera_config(&self) -> CameraConfig {
+        CameraConfig::new(
+            self.camera_pos,
+            self.camera_yaw,
+            self.camera_pitch,
+            self.framebuffer.width as usize,
+            self.framebuffer.height as usize,
+            self.fov,
+            self.framebuffer.width as f32 / self.framebuffer.height as f32,
+        )
+    }
+
+    /// Bloque bajo la mira
+    fn pick_center(&self, camera: &CameraConfig) -> Option<BlockPick> {
+        let center_dir = camera.get_ray_direction(camera.width / 2, camera.height / 2);
+        pick_block(&camera.pos, &center_dir, &self.scene)
+    }
+
+    /// Inspector, editores de material y de luces, hora del día y barra
+    fn handle_panels(&mut self, rl: &mut RaylibHandle, frame: &Frame, camera: &CameraConfig) {
+        let paused = frame.paused;
+        let input_locked = frame.input_locked;
+
+        // Inspector: clic central sobre un bloque muestra su material y UV.
+        // El rayo sale del pixel del framebuffer bajo el mouse.
+        if !input_locked
+            && !frame.replaying
+            && rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_MIDDLE)
+        {
+            let mouse = rl.get_mouse_position();
+            match frame.mapping.window_to_buffer(mouse.x, mouse.y) {
+                Some((x, y)) => {
+                    let dir = camera.get_ray_direction(x as usize, y as usize);
+                    self.block_inspector.inspect(
+                        &camera.pos,
+                        &dir,
+                        &self.scene,
+                        &self.texture_manager,
+                    );
+                }
+                None => self.block_inspector.close(),
+            }
+        }
+        if !input_locked {
+            self.block_inspector
+                .handle_input(rl, &mut self.notifications);
+        }
+
+        // Editor de materiales: M selecciona el bloque bajo la mira
+        if !paused && self.input.is_pressed(Action::ToggleMaterialEditor) {
+            if self.material_editor.is_open() {
+                self.material_editor.close();
+            } else {
+                match frame.hovered {
+                    Some(pick) => {
+                        self.material_editor.open(pick.index);
+                        if self.light_editor.is_open() {
+                            self.light_editor.toggle();
+                        }
+                    }
+                    None => self.notifications.warning("No hay bloque bajo la mira"),
+                }
+            }
+        }
+        if !input_locked
+            && self
+                .material_editor
+                .handle_input(rl, &mut self.scene, &mut self.notifications)
+        {
+            self.scene_dirty = true;
+        }
+
+        // Editor de luces: los cambios se aplican a la lista del próximo frame
+        if !paused && self.input.is_pressed(Action::ToggleLightEditor) {
+            self.light_editor.toggle();
+            if self.light_editor.is_open() {
+                self.material_editor.close();
+            }
+        }
+        if !input_locked && self.light_editor.is_open() {
+            let mut edited = self.lights.as_ref().clone();
+            if self.light_editor.handle_input(
+                rl,
+                &mut edited,
+                frame.window_width - 270,
+                10,
+                &mut self.notifications,
+            ) {
+                if apply_light_edits(&mut self.scene, &mut self.fixed_lights, &edited) {
+                    self.scene_dirty = true;
+                }
+                self.lights = Arc::new(collect_lights(&self.scene, &self.fixed_lights));
+            }
+        }
+
+        // Hora del día: el panel mueve la hora y el ciclo avanza solo si no
+        // está en pausa (ni el juego ni el ciclo)
+        if !paused && self.input.is_pressed(Action::ToggleTimePanel) {
+            self.time_panel.toggle();
+        }
+        if !input_locked {
+            self.time_panel.handle_input(
+                rl,
+                &mut self.settings.day,
+                frame.window_width,
+                frame.window_height,
+            );
+        }
+        if !paused {
+            self.settings.day.advance(frame.dt);
+        }
+        self.settings.render.sky = self.settings.day.sky();
+        if !paused {
+            self.settings.render.time = self.sim_time;
+        }
+
+        // Tipo de bloque a colocar
+        if !input_locked {
+            self.hotbar.handle_input(rl);
+        }
+    }
+
+    /// Edición de bloques: clic izquierdo quita (o enciende una TNT, o abre
+    /// y cierra un cofre), clic derecho coloca. Con un editor abierto no se
+    /// edita (los índices cambiarían y los clics son de los sliders).
+    fn edit_blocks(&mut self, rl: &RaylibHandle, frame: &mut Frame, camera: &CameraConfig) {
+        let panel_open = self.material_editor.is_open()
+            || self.light_editor.is_open()
+            || self.time_panel.is_open();
+        if !panel_open
+            && !frame.input_locked
+            && !frame.replaying
+            && let Some(pick) = frame.hovered
+        {
+            let mut edited = false;
+            let clicked_tnt = self.scene.get(pick.index).is_some_and(Explosives::is_tnt);
+            let clicked_chest = self.scene.get(pick.index).is_some_and(is_chest);
+            if clicked_tnt && rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.explosives.ignite(&self.scene, pick.index);
+            } else if clicked_chest && rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                let mut chest = self.scene[pick.index].clone();
+                let opened = toggle_lid(&mut chest);
+                self.scene.replace_block(pick.index, chest);
+                match opened {
+                    Some(true) => self.chest_panel.open(pick.index),
+                    Some(false) if self.chest_panel.chest() == Some(pick.index) => {
+                        self.chest_panel.close();
+                    }
+                    _ => {}
+                }
+                edited = true;
+            } else if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                let removed = self.scene.remove_block(pick.index);
+                self.on_block_removed(pick.index);
+                self.explosives
+                    .on_block_removed(pick.index, self.scene.len());
+                if removed.emission.is_some() {
+                    self.lights = Arc::new(collect_lights(&self.scene, &self.fixed_lights));
+                }
+                edited = true;
+            } else if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
+                edited = place_against_face(
+                    &mut self.scene,
+                    &mut self.lights,
+                    &self.fixed_lights,
+                    &pick,
+                    self.hotbar.selected_type(),
+                    camera.pos,
+                    &mut self.notifications,
+                );
+            }
+            if edited {
+                frame.hovered = None;
+                self.scene_dirty = true;
+            }
+        }
+
+        // TNT bajo la mira con la tecla (se graba, así que también en una
+        // reproducción)
+        if !frame.input_locked && self.input.is_pressed(Action::Detonate) {
+            let lit = frame
+                .hovered
+                .is_some_and(|pick| self.explosives.ignite(&self.scene, pick.index));
+            if !lit {
+                self.notifications
+                    .warning("No hay TNT apagada bajo la mira");
+            }
+        }
+    }
+
+    /// Corre los índices de las animaciones y del cofre abierto después de
+    /// quitar `index` (el último bloque pasó a ocupar su lugar)
+    fn on_block_removed(&mut self, index: usize) {
+        let last = self.scene.len();
+        self.animations
+            .retain_mut(|animation| animation.on_block_removed(index, last));
+        self.chest_panel.on_block_removed(index, last);
+    }
+
+    /// Mechas y explosiones: quitan bloques como el clic izquierdo, así que
+    /// corren los índices de las animaciones y del editor
+    fn update_explosives(&mut self, frame: &mut Frame) {
+        if frame.paused || !self.explosives.is_active() {
+            return;
+        }
+        let step = self
+            .explosives
+            .update(&self.scene, frame.dt, self.settings.tnt_radius);
+        for (index, block) in step.glowing.iter().cloned() {
+            self.scene.replace_block(index, block);
+        }
+        for index in step.removal_order() {
+            self.scene.remove_block(index);
+            self.on_block_removed(index);
+        }
+        for blast in &step.blasts {
+            self.notifications
+                .info(format!("¡Explosión! {} bloques", blast.removed.len()));
+        }
+        if !step.blasts.is_empty() {
+            self.lights = Arc::new(collect_lights(&self.scene, &self.fixed_lights));
+            self.material_editor.close();
+            frame.hovered = None;
+            self.scene_dirty = true;
+            frame.scene_changed = true;
+        }
+    }
+
+    /// Frame terminado por el hilo de render: se resuelve con los ajustes
+    /// actuales y pasa a ser el presentado. Si la ventana cambió de tamaño
+    /// mientras se trazaba, se descarta.
+    fn present_finished_frame(&mut self, frame: &mut Frame) {
+        // Con el menú de pausa abierto se reutiliza el último frame, salvo que
+        // cambien la escena, los ajustes o el tamaño del framebuffer
+        self.render_requested |= !self.skip_render(frame);
+        self.exposure_dt += frame.dt;
+
+        let Some(mut finished) = self.render_thread.poll() else {
+            return;
+        };
+        let size = (finished.framebuffer.width, finished.framebuffer.height);
+        if size == (self.framebuffer.width, self.framebuffer.height) {
+            let settings = &self.settings;
+            self.framebuffer.swap_traced(&mut finished.framebuffer);
+            if finished.view_mode == ViewMode::Final {
+                // Exposición automática: la manual queda como compensación
+                let mut tone_map = settings.tone_map;
+                if settings.auto_exposure.enabled {
+                    let target_ev = settings.auto_exposure.target_ev(self.framebuffer.hdr());
+                    self.adapted_ev =
+                        settings
+                            .auto_exposure
+                            .adapt(self.adapted_ev, target_ev, self.exposure_dt);
+                    tone_map.exposure_ev += self.adapted_ev;
+                }
+                if self.hdr_screenshot_requested {
+                    self.hdr_screenshot_requested = false;
+                    report_hdr_capture(
+                        save_hdr_capture(&self.framebuffer, settings.hdr_capture, tone_map),
+                        &mut self.console,
+                        &mut self.notifications,
+                    );
+                }
+                self.denoise_time = finish_frame(&mut self.framebuffer, settings, tone_map);
+            } else {
+                self.framebuffer.resolve_debug_view(
+                    finished.view_mode,
+                    depth_range(
+                        finished.camera.pos,
+                        &self.scene,
+                        settings.render.max_distance,
+                    ),
+                    &settings.render,
+                );
+            }
+            self.exposure_dt = 0.0;
+
+            // Capturas: el frame post-procesado, sin overlays ni HUD. Con
+            // `capture_hud` se toma la ventana después de dibujarla.
+            if self.settings.capture_hud {
+                self.window_capture_pending = true;
+            } else {
+                self.recorder.capture(&self.framebuffer);
+                if self.screenshot_requested {
+                    self.screenshot_requested = false;
+                    let view = self.saved_view();
+                    report_screenshot(
+                        save_screenshot(&self.framebuffer),
+                        &mut self.console,
+                        &mut self.notifications,
+                        &view,
+                    );
+                }
+            }
+
+            self.draw_overlays(&finished.camera, frame.hovered);
+
+            self.render_time = finished.elapsed;
+            self.frame_rays = finished.stats.rays.total();
+            self.accumulated_frames = finished.accumulated_frames;
+            self.tile_times = std::mem::take(&mut finished.stats.tile_times);
+            frame.render_stats = Some(finished.stats);
+        }
+        self.render_thread.recycle(finished.framebuffer);
+    }
+
+    /// Con el menú de pausa abierto se reutiliza el último frame, salvo que
+    /// cambien la escena, los ajustes o el tamaño del framebuffer
+    fn skip_render(&self, frame: &Frame) -> bool {
+        frame.minimized
+            || (frame.paused
+                && !frame.scene_changed
+                && !frame.resized
+                && self.settings == frame.settings_before)
+    }
+
+    /// Overlays alineados con los pixeles renderizados (no se graban), con
+    /// la cámara del frame
+    fn draw_overlays(&mut self, camera: &CameraConfig, hovered: Option<BlockPick>) {
+        let framebuffer = &mut self.framebuffer;
+        let scene = &self.scene;
+        match self.wireframe {
+            WireframeMode::Off => {}
+            WireframeMode::Blocks => draw_block_boxes(
+                framebuffer,
+                camera,
+                scene,
+                depth_range(camera.pos, scene, self.settings.render.max_distance),
+            ),
+            WireframeMode::Grid => {
+                if let (Some(grid), Some(bvh)) = (scene.grid(), scene.bvh()) {
+                    draw_grid_cells(framebuffer, camera, grid, bvh);
+                }
+            }
+        }
+        if let Some(block) = self
+            .material_editor
+            .block_index()
+            .and_then(|i| scene.get(i))
+        {
+            let (min, max) = block.bounds();
+            draw_box_outline(framebuffer, camera, min, max, color_to_u32(Color::YELLOW));
+        } else if let Some(block) = hovered.and_then(|pick| scene.get(pick.index)) {
+            let (min, max) = block.bounds();
+            draw_box_outline(framebuffer, camera, min, max, color_to_u32(Color::WHITE));
+        }
+        framebuffer.draw_cross(
+            (framebuffer.width / 2) as i32,
+            (framebuffer.height / 2) as i32,
+            3,
+            color_to_u32(Color::WHITE),
+        );
+        framebuffer.swap();
+    }
+
+    /// Nuevo trabajo con la pose y los ajustes de este frame; las luces
+    /// fijas se tiñen según la hora y el editor trabaja sobre las originales
+    fn submit_render_job(&mut self, frame: &Frame, camera: &CameraConfig) {
+        if !self.render_requested || frame.minimized || self.render_thread.is_busy() {
+            return;
+        }
+        let mut job_lights = collect_lights(
+            &self.scene,
+            &self.settings.day.tint_lights(&self.fixed_lights),
+        );
+        job_lights.extend(self.explosives.flash_lights());
+        let mut camera = camera.clone();
+        camera.pos += self.explosives.camera_offset();
+        self.render_thread.submit(RenderJob {
+            scene: self.scene.snapshot(),
+            lights: job_lights,
+            previous_camera: self.previous_camera.replace(camera.clone()),
+            camera,
+            settings: self.settings.render,
+            view_mode: self.view_mode,
+            multithreaded: self.use_multithreading,
+            record_gbuffer: self.settings.needs_gbuffer(),
+            animations: self.animations.clone(),
+        });
+        self.render_requested = false;
+    }
+
+    /// Estadísticas de depuración (solo si se muestran)
+    fn update_debug_stats(&mut self, frame: &mut Frame) {
+        if !self.show_debug {
+            return;
+        }
+        let stats = &mut self.debug_stats;
+        stats.camera_pos = self.camera_pos;
+        stats.yaw = self.camera_yaw;
+        stats.pitch = self.camera_pitch;
+        stats.target = frame.hovered.map(|pick| {
+            let name = self
+                .scene
+                .get(pick.index)
+                .and_then(BlockType::identify)
+                .map_or("Personalizado", |block_type| block_type.display_name());
+            (name, pick.distance)
+        });
+        if let Some(render) = frame.render_stats.take() {
+            stats.render = render;
+        }
+        stats.texture_memory = self.texture_manager.memory_usage();
+        stats.count_blocks(&self.scene);
+        stats.grid = self.scene.grid_counters();
+        stats.bvh = self.scene.bvh_counters();
+    }
+
+    /// Vista actual para el archivo que acompaña a una captura
+    fn saved_view(&self) -> SavedView {
+        SavedView {
+            camera_pos: self.camera_pos,
+            camera_yaw: self.camera_yaw,
+            camera_pitch: self.camera_pitch,
+            fov_degrees: self.fov.to_degrees(),
+            scene: self.scene_name.clone(),
+            settings: self.settings.clone(),
+        }
+    }
+
+    /// Presenta el framebuffer con el HUD y toma la captura de la ventana
+    /// si hay una pendiente
+    fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, frame: &Frame) {
+        self.frame_count += 1;
+        let now = Instant::now();
+        let fps_text = if now.duration_since(self.last_fps_update).as_secs() >= 1 {
+            self.last_fps_update = now;
+            let fps = self.frame_count;
+            self.frame_count = 0;
+            format!("FPS: {}", fps)
+        } else {
+            format!("FPS: {}", rl.get_fps())
+        };
+
+        let settings = &self.settings;
+        let pos_text = format!(
+            "Pos: ({:.1}, {:.1}, {:.1})",
+            self.camera_pos.x, self.camera_pos.y, self.camera_pos.z
+        );
+        let mode_text = format!(
+            "Modo: {} | {}{} | {} rayos/frame",
+            if self.use_multithreading {
+                "Multi-hilo"
+            } else {
+                "Single-hilo"
+            },
+            if settings.render.adaptive.enabled {
+                format!("adaptativo hasta {} spp", settings.render.adaptive.max_spp)
+            } else {
+                format!("{} spp", settings.render.samples_per_pixel)
+            },
+            if self.accumulated_frames > 1 {
+                format!(" x{} acumulados", self.accumulated_frames)
+            } else {
+                String::new()
+            },
+            self.frame_rays
+        );
+        // El render sin la espera del límite; el frame completo la incluye
+        let render_time_text = format!(
+            "Render: {:.1}ms | Frame: {:.1}ms | Límite: {}{}",
+            self.render_time.as_secs_f32() * 1000.0,
+            rl.get_frame_time() * 1000.0,
+            self.frame_limiter.label(),
+            match self.denoise_time {
+                Some(time) if settings.denoise.enabled => {
+                    format!(" | Filtro de ruido: {:.1}ms", time.as_secs_f32() * 1000.0)
+                }
+                _ => String::new(),
+            }
+        );
+        // Bloque bajo la mira (se oculta si la mira apunta al cielo)
+        let target_text = frame
+            .hovered
+            .and_then(|pick| target_readout(&self.scene, &pick));
+        // Mapa de calor del tiempo por tile mientras se mantiene la tecla
+        let show_tile_heatmap = self.input.is_down(Action::ShowTileHeatmap);
+        self.frame_limiter.apply(rl, self.skip_render(frame));
+
+        let mut d = rl.begin_drawing(thread);
+        d.clear_background(Color::BLACK);
+        if frame.minimized {
+            return;
+        }
+
+        // Escalar según el modo de presentación (puede haber cambiado
+        // en este frame, así que se recalcula la correspondencia)
+        let framebuffer = &mut self.framebuffer;
+        let mapping = ScreenMapping::new(
+            settings.present_mode,
+            framebuffer.width,
+            framebuffer.height,
+            frame.window_width as u32,
+            frame.window_height as u32,
+        );
+        let source = Rectangle::new(
+            0.0,
+            0.0,
+            framebuffer.width as f32,
+            framebuffer.height as f32,
+        );
+        framebuffer.present_scaled(&mut d, thread, source, mapping.dest);
+        draw_hud(
+            &mut d,
+            &Hud {
+                mapping: &mapping,
+                framebuffer,
+                window_width: frame.window_width,
+                window_height: frame.window_height,
+                fps_text: &fps_text,
+                pos_text: &pos_text,
+                mode_text: &mode_text,
+                render_time_text: &render_time_text,
+                target_text: target_text.as_deref(),
+                tile_times: show_tile_heatmap.then_some(&self.tile_times[..]),
+                scene: &self.scene,
+                scene_label: self.scene_kind.label(),
+                view_mode: self.view_mode,
+                settings,
+                adapted_ev: self.adapted_ev,
+                input: &self.input,
+                lights: &self.lights,
+                material_editor: &self.material_editor,
+                light_editor: &self.light_editor,
+                post_menu: &self.post_menu,
+                hotbar: &self.hotbar,
+                texture_manager: &self.texture_manager,
+                block_inspector: &self.block_inspector,
+                time_panel: &self.time_panel,
+                chest_panel: &self.chest_panel,
+                recorder: &self.recorder,
+                session: &self.session,
+                debug_stats: self.show_debug.then_some(&self.debug_stats),
+                notifications: &self.notifications,
+                show_help: self.show_help,
+                console: &self.console,
+                pause_menu: &self.pause_menu,
+            },
+        );
+
+        // Captura con el HUD: la ventana completa, a su resolución
+        if self.window_capture_pending {
+            self.window_capture_pending = false;
+            let (width, height, pixels) = read_window(&mut d, thread);
+            self.recorder.capture_pixels(width, height, &pixels);
+            if self.screenshot_requested {
+                self.screenshot_requested = false;
+                let view = self.saved_view();
+                report_screenshot(
+                    save_screenshot_pixels(width, height, &pixels),
+                    &mut self.console,
+                    &mut self.notifications,
+                    &view,
+                );
+            }
+        }
+    }
+
+    /// Espera el frame en curso, cierra la grabación y la sesión y guarda
+    /// las preferencias
+    fn finish(mut self) {
+        self.render_thread.shutdown();
+        if self.recorder.is_recording() {
+            stop_recording(
+                &mut self.recorder,
+                &self.settings.record_dir,
+                &mut self.notifications,
+            );
+        }
+        self.session.finish();
+
+        if let Err(err) = self.settings.save(SETTINGS_PATH) {
+            error!("No se pudieron guardar las preferencias: {}", err);
+        }
+    }
+}
+
+/// Escena de `config.scene`; si no se puede cargar se avisa y se usa la isla
+fn initial_scene(config: &Config) -> (SceneKind, Scene) {
+    let loaded = match config.scene_source() {
+        SceneSource::Builtin(kind) => kind.build().map(|scene| (kind, scene)),
+        SceneSource::File(path) => load_scene(&path).map(|scene| (SceneKind::Saved, scene)),
+    };
+    loaded.unwrap_or_else(|err| {
+        warn!("No se pudo cargar la escena '{}': {}", config.scene, err);
+        (SceneKind::Island, create_optimized_scene())
+    })
+}
+
+/// Advertencias de material (con archivo y línea) de `blocks.txt` y de la
+/// escena de arranque si es un archivo. Los errores de lectura se informan
+/// después, al cargarlos.
+fn file_material_issues(config: &Config) -> Vec<String> {
+    let mut registry = BlockRegistry::new();
+    let mut issues = Vec::new();
+    if Path::new(BLOCKS_PATH).exists()
+        && let Ok((definitions, found)) = load_scene_checked(BLOCKS_PATH, &registry)
+    {
+        for definition in definitions.palette {
+            let _ = registry.register(definition);
+        }
+        issues.extend(
+            found
+                .iter()
+                .map(|issue| format!("{}:{}", BLOCKS_PATH, issue)),
+        );
+    }
+    if let SceneSource::File(path) = config.scene_source()
+        && let Ok((_, found)) = load_scene_checked(&path, &registry)
+    {
+        issues.extend(found.iter().map(|issue| format!("{}:{}", path, issue)));
+    }
+    issues
+}
+
+/// Guarda `settings.toml` y `config.toml` e informa el resultado
+fn save_settings(settings: &Settings, config: &Config, notifications: &mut Notifications) {
+    let result = settings
+        .save(SETTINGS_PATH)
+        .and_then(|()| config.save(CONFIG_PATH));
+    match result {
+        Ok(()) => notifications.info(format!(
+            "Ajustes guardados en {} y {}",
+            SETTINGS_PATH, CONFIG_PATH
+        )),
+        Err(err) => notifications.error(format!("No se pudieron guardar los ajustes: {}", err)),
+    }
+}
+
+/// Coloca un bloque del tipo elegido pegado a la cara golpeada. Se rechaza si
+/// la celda está ocupada o si el bloque quedaría sobre la cámara.
+fn place_against_face(
+    scene: &mut SharedScene,
+    lights: &mut Arc<Vec<Light>>,
+    fixed_lights: &[Light],
+    pick: &BlockPick,
+    block_type: &RegisteredBlock,
+    camera_pos: Vector3,
+    notifications: &mut Notifications,
+) -> bool {
+    let Some(hit_block) = scene.get(pick.index) else {
+        return false;
+    };
+    // El bloque nuevo es un cubo del lado más largo del golpeado, pegado a
+    // la cara (en un cubo, la celda vecina)
+    let extents = hit_block.extents;
+    let size = extents.x.max(extents.y).max(extents.z);
+    let normal = Vector3::new(
+        pick.normal.x.round(),
+        pick.normal.y.round(),
+        pick.normal.z.round(),
+    );
+    let mut position = hit_block.position + normal * ((extents + Vector3::one() * size) * 0.5);
+
+    // Las formas chicas se apoyan en la cara clickeada (la antorcha nunca en
+    // un techo, la planta solo en un piso) y la escalera sube alejándose de
+    // la cámara. El tronco queda a lo largo de la cara. Una losa sobre la
+    // mitad libre de otra completa su celda y la nieve sobre nieve suma una
+    // capa.
+    let look = position - camera_pos;
+    let Some(mut shape) = block_type.default_shape().placed_against(&normal, &look) else {
+        notifications.warning(match block_type.default_shape() {
+            BlockShape::Torch(_) => "La antorcha no se puede colgar del techo",
+            BlockShape::SnowLayer { .. } => "La nieve solo se coloca sobre un piso",
+            _ => "Las plantas solo se colocan sobre un piso",
+        });
+        return false;
+    };
+    if let BlockShape::SnowLayer { .. } = shape
+        && let BlockShape::SnowLayer { layers } = hit_block.shape
+        && normal.y == 1.0
+        && layers < MAX_SNOW_LAYERS
+    {
+        let layers = layers + 1;
+        let snow = hit_block
+            .clone()
+            .with_shape(BlockShape::SnowLayer { layers });
+        scene.replace_block(pick.index, snow);
+        return true;
+    }
+    if let (BlockShape::Slab { .. }, BlockShape::Slab { top }) = (shape, hit_block.shape)
+        && normal.y == if top { -1.0 } else { 1.0 }
+    {
+        position = hit_block.position;
+        shape = BlockShape::Slab { top: !top };
+    }
+
+    // Margen para que el plano cercano no quede dentro del bloque nuevo
+    let reach = size * 0.5 + 0.2;
+    let offset = camera_pos - position;
+    if offset.x.abs() < reach && offset.y.abs() < reach && offset.z.abs() < reach {
+        notifications.warning("No se puede colocar un bloque sobre la cámara");
+        return false;
+    }
+
+    let block = block_type
+        .to_block(position, size)
+        .with_shape(shape)
+        .with_orientation(block_type.placed_orientation(&normal, &look));
+    let emissive = block.emission.is_some();
+    if scene.place_block(block).is_none() {
+        return false;
+    }
+    // La escena guarda la definición de los tipos propios que usa
+    if let RegisteredBlock::Custom(definition) = block_type {
+        scene.add_to_palette(definition);
+    }
+    if emissive {
+        *lights = Arc::new(collect_lights(scene, fixed_lights));
+    }
+    true
+}
+
+/// Suma al registro los tipos propios de la paleta de la escena y actualiza
+/// la barra
+fn register_palette(registry: &mut BlockRegistry, scene: &Scene, hotbar: &mut Hotbar) {
+    for definition in &scene.palette {
+        if let Err(err) = registry.register(definition.clone()) {
+            warn!("{}", err);
+        }
+    }
+    hotbar.set_custom_types(registry);
+}
+
+/// Informa el resultado de guardar una captura en la consola y en los avisos.
+/// Junto a la imagen queda su vista (cámara, escena y ajustes) para volver
+/// a armarla con `--from-screenshot` o `view load`.
+fn report_screenshot(
+    result: Result<PathBuf, ExportError>,
+    console: &mut Console,
+    notifications: &mut Notifications,
+    view: &SavedView,
+) {
+    match result {
+        Ok(path) => {
+            let message = format!("Captura guardada en {}", path.display());
+            console.print(&message);
+            notifications.info(message);
+            if let Err(err) = save_view(view_path(&path), view) {
+                let message = format!("No se pudo guardar la vista de la captura: {}", err);
+                console.print(&format!("Error: {}", message));
+                notifications.warning(message);
+            }
+        }
+        Err(e) => {
+            let message = format!("No se pudo guardar la captura: {}", e);
+            console.print(&format!("Error: {}", message));
+            notifications.error(message);
+        }
+    }
+}
+
+/// Informa en la consola y en pantalla dónde quedó la captura HDR y la
+/// luminancia más alta del frame
+fn report_hdr_capture(
+    result: Result<HdrCapture, ExportError>,
+    console: &mut Console,
+    notifications: &mut Notifications,
+) {
+    match result {
+        Ok(capture) => {
+            let paths: Vec<String> = capture
+                .paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            let message = format!(
+                "Captura HDR guardada en {} (pico de luminancia {:.2})",
+                paths.join(", "),
+                capture.peak_luminance
+            );
+            console.print(&message);
+            notifications.info(message);
+        }
+        Err(e) => {
+            let message = format!("No se pudo guardar la captura HDR: {}", e);
+            console.print(&format!("Error: {}", message));
+            notifications.error(message);
+        }
+    }
+}
+
+/// Texto junto a la mira para el bloque apuntado: coordenadas de grilla y
+/// distancia al impacto
+fn target_readout(scene: &[Block], pick: &BlockPick) -> Option<String> {
+    let block = scene.get(pick.index)?;
+    let p = block.position;
+    Some(format!("({}, {}, {})  {:.1} m", p.x, p.y, p.z, pick.distance))
+}
+
+/// Guarda la escena en `SCENE_PATH` e informa el resultado
+fn save_current_scene(scene: &Scene, notifications: &mut Notifications) -> bool {
+    match save_scene(SCENE_PATH, scene) {
+        Ok(()) => {
+            notifications.info(format!("Escena guardada en {}", SCENE_PATH));
+            true
+        }
+        Err(err) => {
+            notifications.error(format!("No se pudo guardar la escena: {}", err));
+            false
+        }
+    }
+}
+
+/// Termina la grabación (esperando a que se escriba la cola) e informa el resultado
+fn stop_recording(
+    recorder: &mut FrameRecorder,
+    output_dir: &str,
+    notifications: &mut Notifications,
+) {
+    if let Some(report) = recorder.stop() {
+        let message = format!(
+            "Grabación terminada: {} frames en {} ({} descartados, {} con error)",
+            report.written, output_dir, report.dropped, report.failed
+        );
+        if report.dropped > 0 || report.failed > 0 {
+            notifications.warning(message);
+        } else {
+            notifications.info(message);
+        }
+    }
+}
+
+/// Devuelve las luces editadas a su origen. `edited` tiene el orden de
+/// `collect_lights`: las de bloques emisivos guardan color e intensidad (la
+/// posición sigue siendo la del bloque), las de primitivas se ignoran y el
+/// resto reemplaza a las fijas. Devuelve true si cambió algún bloque de la
+/// escena.
+fn apply_light_edits(
+    scene: &mut SharedScene,
+    fixed_lights: &mut [Light],
+    edited: &[Light],
+) -> bool {
+    let emissive: Vec<usize> = scene
+        .iter()
+        .enumerate()
+        .filter(|(_, block)| block.emission.is_some())
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut scene_changed = false;
+    for (&index, light) in emissive.iter().zip(edited) {
+        let current = scene[index].emission.expect("bloque emisivo");
+        if current.color != light.color || current.intensity != light.intensity {
+            let mut block = scene[index].clone();
+            let emission = block.emission.as_mut().expect("bloque emisivo");
+            emission.color = light.color;
+            emission.intensity = light.intensity;
+            scene.replace_block(index, block);
+            scene_changed = true;
+        }
+    }
+    let skipped = emissive.len() + scene.primitive_lights().count();
+    for (fixed, light) in fixed_lights.iter_mut().zip(edited.iter().skip(skipped)) {
+        *fixed = *light;
+    }
+    scene_changed
+}
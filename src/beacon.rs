@@ -0,0 +1,236 @@
+// beacon.rs - Haces de luz verticales de los faros
+use raylib::prelude::*;
+
+use crate::block_types::BlockType;
+use crate::scene::Scene;
+
+/// Radio del haz (un poco más angosto que el bloque)
+pub const BEAM_RADIUS: f32 = 0.3;
+
+/// Alto del haz sobre la cara superior del faro
+pub const BEAM_HEIGHT: f32 = 40.0;
+
+/// Altura a la que el brillo del haz cae a la mitad
+const BEAM_HALF_HEIGHT: f32 = 8.0;
+
+/// Brillo por unidad de camino dentro del haz
+const BEAM_DENSITY: f32 = 0.6;
+
+/// Haces que se evalúan como mucho por rayo: con muchos faros se toman los
+/// primeros de la escena, así el costo por rayo queda acotado
+pub const MAX_BEAMS_PER_RAY: usize = 4;
+
+/// Apertura del cono de la luz hacia arriba de un faro, en radianes
+pub const BEACON_SPOT_ANGLE: f32 = 0.5;
+
+/// Haz vertical de un faro: un cilindro desde `base` hacia arriba
+#[derive(Debug, Clone, Copy)]
+pub struct Beam {
+    /// Centro de la base (la cara superior del faro)
+    pub base: Vector3,
+    pub radius: f32,
+    pub height: f32,
+    pub color: Vector3,
+}
+
+/// Haces de los faros de la escena, en el orden de sus bloques. Se
+/// calculan una vez por frame, no por rayo.
+pub fn beacon_beams(scene: &Scene) -> Vec<Beam> {
+    scene
+        .iter()
+        .filter(|block| block.emission.is_some())
+        .filter(|block| BlockType::identify(block) == Some(BlockType::Beacon))
+        .map(|block| Beam {
+            base: block.position + Vector3::new(0.0, block.extents.y * 0.5, 0.0),
+            radius: BEAM_RADIUS * block.extents.x.min(block.extents.z),
+            height: BEAM_HEIGHT,
+            color: block.material.emission_color.unwrap_or(block.material.diffuse),
+        })
+        .collect()
+}
+
+/// Tramo `(t_entrada, t_salida)` del rayo dentro del cilindro vertical de
+/// centro de base `base`, radio `radius` y alto `height`, recortado a
+/// `[0, max_t]`. `dir` debe estar normalizado; None si no lo atraviesa.
+pub fn cylinder_segment(
+    origin: &Vector3,
+    dir: &Vector3,
+    max_t: f32,
+    base: &Vector3,
+    radius: f32,
+    height: f32,
+) -> Option<(f32, f32)> {
+    // Círculo en el plano XZ
+    let ox = origin.x - base.x;
+    let oz = origin.z - base.z;
+    let a = dir.x * dir.x + dir.z * dir.z;
+    let c = ox * ox + oz * oz - radius * radius;
+    let (mut t0, mut t1) = if a < 1e-8 {
+        // Rayo vertical: adentro o afuera del círculo en todo el recorrido
+        if c > 0.0 {
+            return None;
+        }
+        (f32::NEG_INFINITY, f32::INFINITY)
+    } else {
+        let b = ox * dir.x + oz * dir.z;
+        let discriminant = b * b - a * c;
+        if discriminant <= 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        ((-b - root) / a, (-b + root) / a)
+    };
+
+    // Tapas: entre la base y la altura del haz
+    if dir.y.abs() < 1e-8 {
+        if origin.y < base.y || origin.y > base.y + height {
+            return None;
+        }
+    } else {
+        let bottom = (base.y - origin.y) / dir.y;
+        let top = (base.y + height - origin.y) / dir.y;
+        t0 = t0.max(bottom.min(top));
+        t1 = t1.min(bottom.max(top));
+    }
+
+    t0 = t0.max(0.0);
+    t1 = t1.min(max_t);
+    (t1 > t0).then_some((t0, t1))
+}
+
+/// Largo del camino del rayo dentro del cilindro (0.0 si no lo cruza)
+pub fn cylinder_path_length(
+    origin: &Vector3,
+    dir: &Vector3,
+    max_t: f32,
+    base: &Vector3,
+    radius: f32,
+    height: f32,
+) -> f32 {
+    cylinder_segment(origin, dir, max_t, base, radius, height)
+        .map_or(0.0, |(t0, t1)| t1 - t0)
+}
+
+/// Luz que suman los haces a un rayo que recorre `max_t` (hasta el primer
+/// impacto o el cielo): proporcional al camino dentro de cada haz y más
+/// tenue cuanto más alto cruza
+pub fn beam_glow(origin: &Vector3, dir: &Vector3, max_t: f32, beams: &[Beam]) -> Vector3 {
+    let mut glow = Vector3::zero();
+    for beam in beams.iter().take(MAX_BEAMS_PER_RAY) {
+        let Some((t0, t1)) =
+            cylinder_segment(origin, dir, max_t, &beam.base, beam.radius, beam.height)
+        else {
+            continue;
+        };
+        let middle = origin.y + dir.y * (t0 + t1) * 0.5 - beam.base.y;
+        let fade = 1.0 / (1.0 + middle.max(0.0) / BEAM_HALF_HEIGHT);
+        glow += beam.color * ((t1 - t0) * BEAM_DENSITY * fade);
+    }
+    glow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPS: f32 = 1e-4;
+
+    fn beam_at(x: f32, z: f32) -> Beam {
+        Beam {
+            base: Vector3::new(x, 0.0, z),
+            radius: 0.5,
+            height: 10.0,
+            color: Vector3::one(),
+        }
+    }
+
+    fn path(origin: Vector3, dir: Vector3, max_t: f32) -> f32 {
+        cylinder_path_length(
+            &origin,
+            &dir.normalized(),
+            max_t,
+            &Vector3::zero(),
+            0.5,
+            10.0,
+        )
+    }
+
+    #[test]
+    fn horizontal_rays_cross_a_chord() {
+        let dir = Vector3::new(1.0, 0.0, 0.0);
+        // Por el eje: el diámetro
+        let length = path(Vector3::new(-5.0, 2.0, 0.0), dir, 100.0);
+        assert!((length - 1.0).abs() < EPS, "{}", length);
+        // Corrido del eje: la cuerda 2·sqrt(r² - d²)
+        let length = path(Vector3::new(-5.0, 2.0, 0.3), dir, 100.0);
+        assert!((length - 0.8).abs() < EPS, "{}", length);
+        // Fuera del radio, debajo de la base o sobre la punta: nada
+        assert_eq!(path(Vector3::new(-5.0, 2.0, 0.6), dir, 100.0), 0.0);
+        assert_eq!(path(Vector3::new(-5.0, -0.1, 0.0), dir, 100.0), 0.0);
+        assert_eq!(path(Vector3::new(-5.0, 10.1, 0.0), dir, 100.0), 0.0);
+        // Mirando para el otro lado
+        assert_eq!(path(Vector3::new(-5.0, 2.0, 0.0), -dir, 100.0), 0.0);
+    }
+
+    #[test]
+    fn vertical_and_slanted_rays_stop_at_the_caps() {
+        // Desde abajo por adentro del haz: todo el alto
+        let length = path(
+            Vector3::new(0.1, -3.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            100.0,
+        );
+        assert!((length - 10.0).abs() < EPS, "{}", length);
+        // Desde adentro hacia abajo: hasta la base
+        let length = path(
+            Vector3::new(0.0, 4.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+            100.0,
+        );
+        assert!((length - 4.0).abs() < EPS, "{}", length);
+        // Entra por la pared y sale por la tapa de arriba
+        let dir = Vector3::new(1.0, 1.0, 0.0);
+        let length = path(Vector3::new(-0.5, 9.0, 0.0), dir, 100.0);
+        assert!((length - 2f32.sqrt()).abs() < EPS, "{}", length);
+    }
+
+    #[test]
+    fn path_is_clipped_to_the_first_hit() {
+        let origin = Vector3::new(-5.0, 2.0, 0.0);
+        let dir = Vector3::new(1.0, 0.0, 0.0);
+        // Un bloque antes del haz lo tapa entero y uno adentro lo corta
+        assert_eq!(path(origin, dir, 4.0), 0.0);
+        assert!((path(origin, dir, 4.75) - 0.25).abs() < EPS);
+        assert!((path(origin, dir, 5.5) - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn glow_fades_with_height_and_caps_the_beams() {
+        let dir = Vector3::new(1.0, 0.0, 0.0);
+        let beams = [beam_at(0.0, 0.0)];
+        let low = beam_glow(&Vector3::new(-5.0, 1.0, 0.0), &dir, 100.0, &beams);
+        let high = beam_glow(&Vector3::new(-5.0, 9.0, 0.0), &dir, 100.0, &beams);
+        assert!(low.x > high.x && high.x > 0.0, "{:?} {:?}", low, high);
+
+        // Haces en fila sobre el rayo: solo cuentan los primeros
+        let row = (0..MAX_BEAMS_PER_RAY + 3)
+            .map(|i| beam_at(i as f32 * 2.0, 0.0))
+            .collect::<Vec<_>>();
+        let all = beam_glow(&Vector3::new(-5.0, 1.0, 0.0), &dir, 100.0, &row);
+        assert!((all.x - low.x * MAX_BEAMS_PER_RAY as f32).abs() < EPS);
+    }
+
+    #[test]
+    fn beams_start_on_top_of_each_beacon() {
+        let scene = Scene::new(vec![
+            BlockType::Stone.to_block(Vector3::new(0.0, 0.0, 0.0), 1.0),
+            BlockType::Beacon.to_block(Vector3::new(3.0, 1.0, -2.0), 1.0),
+            BlockType::Torch.to_block(Vector3::new(5.0, 1.0, 0.0), 1.0),
+        ]);
+        let beams = beacon_beams(&scene);
+        assert_eq!(beams.len(), 1);
+        assert_eq!(beams[0].base, Vector3::new(3.0, 1.5, -2.0));
+        assert!((beams[0].radius - BEAM_RADIUS).abs() < EPS);
+        assert_eq!(beams[0].height, BEAM_HEIGHT);
+    }
+}
@@ -1,25 +1,124 @@
 // block.rs
 use raylib::prelude::*;
+use crate::block_shape::{BlockShape, HingedBox};
 use crate::material::Material;
+use crate::orientation::Orientation;
+use crate::plant::intersect_cross;
 use crate::ray_intersect::{Intersect, RayIntersect};
 use crate::light::Light;
 
-#[derive(Debug, Clone)]
+/// Caras de un bloque, en el orden usado por `face_overrides`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Face {
+    East,   // +X
+    West,   // -X
+    Top,    // +Y
+    Bottom, // -Y
+    South,  // +Z
+    North,  // -Z
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [
+        Face::East,
+        Face::West,
+        Face::Top,
+        Face::Bottom,
+        Face::South,
+        Face::North,
+    ];
+
+    /// Índice de la cara (0..6)
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Determina la cara a partir de la normal del impacto
+    pub fn from_normal(normal: &Vector3) -> Self {
+        if normal.x.abs() > 0.9 {
+            if normal.x > 0.0 { Face::East } else { Face::West }
+        } else if normal.y.abs() > 0.9 {
+            if normal.y > 0.0 { Face::Top } else { Face::Bottom }
+        } else if normal.z > 0.0 {
+            Face::South
+        } else {
+            Face::North
+        }
+    }
+
+    /// Normal unitaria hacia afuera
+    pub fn normal(self) -> Vector3 {
+        match self {
+            Face::East => Vector3::new(1.0, 0.0, 0.0),
+            Face::West => Vector3::new(-1.0, 0.0, 0.0),
+            Face::Top => Vector3::new(0.0, 1.0, 0.0),
+            Face::Bottom => Vector3::new(0.0, -1.0, 0.0),
+            Face::South => Vector3::new(0.0, 0.0, 1.0),
+            Face::North => Vector3::new(0.0, 0.0, -1.0),
+        }
+    }
+
+    /// Nombre usado en los archivos de escena
+    pub fn name(self) -> &'static str {
+        match self {
+            Face::East => "east",
+            Face::West => "west",
+            Face::Top => "top",
+            Face::Bottom => "bottom",
+            Face::South => "south",
+            Face::North => "north",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|face| face.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
     pub position: Vector3,
-    pub size: f32,
+    /// Largo en cada eje, antes de girar el bloque (un cubo de lado `s`
+    /// tiene las tres iguales a `s`)
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub extents: Vector3,
     pub material: Material,
     pub emission: Option<Light>,
+    pub face_overrides: Option<Box<[Option<Material>; 6]>>,
+    /// Bits por cara (ver `Face::index`) que están pegadas a un bloque del
+    /// mismo medio transparente. Se calcula al construir la escena.
+    pub interior_faces: u8,
+    /// Parte de la celda que ocupa (cubo entero por defecto)
+    pub shape: BlockShape,
+    /// Rotación de la forma, las caras y las UV (ej: un tronco acostado)
+    pub orientation: Orientation,
 }
 
 impl Block {
-    pub fn new(position: Vector3, size: f32, material: Material) -> Self {
-        Self { position, size, material, emission: None }
+    pub fn new(position: Vector3, extents: Vector3, material: Material) -> Self {
+        Self {
+            position,
+            extents,
+            material,
+            emission: None,
+            face_overrides: None,
+            interior_faces: 0,
+            shape: BlockShape::Cube,
+            orientation: Orientation::UPRIGHT,
+        }
+    }
+
+    /// Cubo de lado `size`
+    pub fn new_cubic(position: Vector3, size: f32, material: Material) -> Self {
+        Self::new(position, Vector3::one() * size, material)
     }
 
     pub fn new_emissive(
         position: Vector3,
-        size: f32,
+        extents: Vector3,
         material: Material,
         color: Vector3,
         intensity: f32,
@@ -27,21 +126,117 @@ impl Block {
         let light = Light::new(position, color, intensity);
         Self {
             position,
-            size,
+            extents,
             material,
             emission: Some(light),
+            face_overrides: None,
+            interior_faces: 0,
+            shape: BlockShape::Cube,
+            orientation: Orientation::UPRIGHT,
+        }
+    }
+
+    /// Cambia la forma del bloque. La luz de un bloque emisivo se mueve al
+    /// punto de emisión de la nueva forma (ej: la punta de la antorcha).
+    pub fn with_shape(mut self, shape: BlockShape) -> Self {
+        if let Some(light) = self.emission.as_mut() {
+            let anchor = self.position + self.shape.light_anchor() * self.extents;
+            light.position = light.position - anchor
+                + self.position
+                + shape.light_anchor() * self.extents;
+        }
+        self.shape = shape;
+        self
+    }
+
+    /// Cambia el largo del bloque en cada eje. La luz de un bloque emisivo
+    /// conserva su lugar relativo dentro del bloque.
+    pub fn with_extents(mut self, extents: Vector3) -> Self {
+        if let Some(light) = self.emission.as_mut() {
+            let offset = light.position - self.position;
+            light.position = self.position + offset / self.extents * extents;
+        }
+        self.extents = extents;
+        self
+    }
+
+    /// Si el largo es el mismo en los tres ejes
+    pub fn is_cubic(&self) -> bool {
+        self.extents.x == self.extents.y && self.extents.y == self.extents.z
+    }
+
+    /// Gira el bloque. Las caras de `face_overrides` son las del bloque sin
+    /// girar (la tapa de un tronco acostado queda mirando al costado).
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Caja (mínimo, máximo) que ocupa el bloque en el mundo
+    #[inline]
+    pub fn bounds(&self) -> (Vector3, Vector3) {
+        let (min, max) = self.shape.bounds();
+        let (min, max) = self.orientation.rotate_box(min * self.extents, max * self.extents);
+        (self.position + min, self.position + max)
+    }
+
+    /// Reemplaza el material de una sola cara (ej: tapa emisiva, lado espejo).
+    pub fn with_face_material(mut self, face: Face, material: Material) -> Self {
+        let overrides = self.face_overrides.get_or_insert_with(Default::default);
+        overrides[face.index()] = Some(material);
+        self
+    }
+
+    /// Indica si la cara está marcada como interior
+    #[inline]
+    pub fn is_interior_face(&self, face: Face) -> bool {
+        self.interior_faces & (1 << face.index()) != 0
+    }
+
+    /// Material efectivo de la cara golpeada
+    #[inline]
+    fn material_for_face(&self, normal: &Vector3) -> &Material {
+        match &self.face_overrides {
+            Some(faces) => faces[Face::from_normal(normal).index()]
+                .as_ref()
+                .unwrap_or(&self.material),
+            None => &self.material,
         }
     }
 
-    /// Calcula UV básicos según la cara golpeada y el punto local.
+    /// Corta el rayo con una caja girada sobre su bisagra (ej: la tapa de
+    /// un cofre abierto): lleva el rayo al marco de la caja cerrada, donde
+    /// vuelve a estar alineada. Devuelve la distancia y el punto (en
+    /// coordenadas del bloque sin girar) y la normal de la caja cerrada.
+    fn intersect_hinged(
+        &self,
+        lid: &HingedBox,
+        origin: &Vector3,
+        dir: &Vector3,
+    ) -> Option<(f32, Vector3, Vector3)> {
+        // Celda de lado 1: la transformación es lineal, así que la
+        // distancia sobre el rayo no cambia
+        let local_origin = self.orientation.to_local(*origin - self.position) / self.extents;
+        let local_dir = self.orientation.to_local(*dir) / self.extents;
+        let closed_origin = lid.hinge + lid.close(local_origin - lid.hinge);
+        let closed_dir = lid.close(local_dir);
+        let (distance, normal) = intersect_box(&lid.min, &lid.max, &closed_origin, &closed_dir)?;
+        let closed_point = (closed_origin + closed_dir * distance) * self.extents;
+        Some((distance, closed_point, normal))
+    }
+
+    /// Calcula UV básicos según la cara golpeada y el punto local, ambos
+    /// ya llevados a las coordenadas del bloque sin girar.
     /// Retorna (u,v) en 0..1.
-    fn calc_uv(&self, point: &Vector3, normal: &Vector3) -> (f32, f32) {
-        let local = *point - self.position;
-        let half = self.size * 0.5;
-        // Convertir a rango [0,size]
-        let lx = (local.x + half) / self.size;
-        let ly = (local.y + half) / self.size;
-        let lz = (local.z + half) / self.size;
+    fn calc_uv(&self, local: &Vector3, normal: &Vector3) -> (f32, f32) {
+        let half = self.extents * 0.5;
+        // La textura mide lo que el lado más largo: en un cubo cada cara es
+        // la textura entera y en un bloque chato se ve una franja sin
+        // estirarse (como en las formas que no llenan la celda)
+        let unit = self.extents.x.max(self.extents.y).max(self.extents.z);
+        let lx = (local.x + half.x) / unit;
+        let ly = (local.y + half.y) / unit;
+        let lz = (local.z + half.z) / unit;
 
         if normal.x.abs() > 0.9 {
             // caras +/- X : usar Z vertical = y, horizontal = z
@@ -58,53 +253,491 @@ impl Block {
 
 impl<'a> RayIntersect<'a> for Block {
     fn ray_intersect(&'a self, origin: &Vector3, dir: &Vector3) -> Intersect<'a> {
-        // AABB centered on position
-        let half = self.size * 0.5;
-        let min = self.position - Vector3::new(half, half, half);
-        let max = self.position + Vector3::new(half, half, half);
+        if let BlockShape::CrossPlant(plant) = self.shape {
+            let Some((distance, normal, u, v)) =
+                intersect_cross(plant, &self.position, self.extents, origin, dir)
+            else {
+                return Intersect::empty();
+            };
+            let point = *origin + *dir * distance;
+            return Intersect::new(&self.material, distance, normal, point, u, v);
+        }
+
+        // La figura puede ser varias cajas (ej: escalera): gana la más cercana
+        let mut nearest: Option<(f32, Vector3)> = None;
+        for (min, max) in self.shape.boxes().iter() {
+            let (min, max) = self.orientation.rotate_box(*min * self.extents, *max * self.extents);
+            let (min, max) = (self.position + min, self.position + max);
+            if let Some((distance, normal)) = intersect_box(&min, &max, origin, dir)
+                && nearest.is_none_or(|(best, _)| distance < best)
+            {
+                nearest = Some((distance, normal));
+            }
+        }
+        // Tapa abierta: punto y normal de la tapa cerrada, para la textura
+        let mut lid_hit = None;
+        if let Some(lid) = self.shape.hinged_lid()
+            && let Some((distance, closed_point, closed_normal)) =
+                self.intersect_hinged(&lid, origin, dir)
+            && nearest.is_none_or(|(best, _)| distance < best)
+        {
+            let normal = self.orientation.to_world(lid.open(closed_normal) / self.extents);
+            nearest = Some((distance, normal.normalized()));
+            lid_hit = Some((closed_point, closed_normal));
+        }
+        let Some((distance, normal)) = nearest else {
+            return Intersect::empty();
+        };
+        let point = *origin + *dir * distance;
 
-        // Handle possible zero components in dir by using large values (slab method safe)
-        let invx = if dir.x.abs() > 1e-8 { 1.0 / dir.x } else { f32::INFINITY };
-        let invy = if dir.y.abs() > 1e-8 { 1.0 / dir.y } else { f32::INFINITY };
-        let invz = if dir.z.abs() > 1e-8 { 1.0 / dir.z } else { f32::INFINITY };
+        // Textura y material de cara salen del bloque sin girar
+        let (local_point, local_normal) = lid_hit.unwrap_or_else(|| {
+            (
+                self.orientation.to_local(point - self.position),
+                self.orientation.to_local(normal),
+            )
+        });
+        let (u, v) = self.calc_uv(&local_point, &local_normal);
 
-        let mut tmin = (min.x - origin.x) * invx;
-        let mut tmax = (max.x - origin.x) * invx;
-        if tmin > tmax { std::mem::swap(&mut tmin, &mut tmax); }
+        let material = self.material_for_face(&local_normal);
+        let mut hit = Intersect::new(material, distance, normal, point, u, v);
+        hit.interior_face = self.interior_faces != 0 && self.is_interior_face(Face::from_normal(&normal));
+        hit
+    }
+}
 
-        let mut tymin = (min.y - origin.y) * invy;
-        let mut tymax = (max.y - origin.y) * invy;
-        if tymin > tymax { std::mem::swap(&mut tymin, &mut tymax); }
+/// Corta el rayo con la caja alineada a los ejes (min, max) por el método
+/// de las losas. Retorna la distancia y la normal de la cara golpeada.
+fn intersect_box(
+    min: &Vector3,
+    max: &Vector3,
+    origin: &Vector3,
+    dir: &Vector3,
+) -> Option<(f32, Vector3)> {
+    // Handle possible zero components in dir by using large values (slab method safe)
+    let invx = if dir.x.abs() > 1e-8 { 1.0 / dir.x } else { f32::INFINITY };
+    let invy = if dir.y.abs() > 1e-8 { 1.0 / dir.y } else { f32::INFINITY };
+    let invz = if dir.z.abs() > 1e-8 { 1.0 / dir.z } else { f32::INFINITY };
 
-        if (tmin > tymax) || (tymin > tmax) { return Intersect::empty(); }
-        if tymin > tmin { tmin = tymin; }
-        if tymax < tmax { tmax = tymax; }
+    // Eje (0 = X, 1 = Y, 2 = Z) de la losa que fija la entrada y la salida
+    let (mut near_axis, mut far_axis) = (0, 0);
 
-        let mut tzmin = (min.z - origin.z) * invz;
-        let mut tzmax = (max.z - origin.z) * invz;
-        if tzmin > tzmax { std::mem::swap(&mut tzmin, &mut tzmax); }
+    let mut tmin = (min.x - origin.x) * invx;
+    let mut tmax = (max.x - origin.x) * invx;
+    if tmin > tmax { std::mem::swap(&mut tmin, &mut tmax); }
 
-        if (tmin > tzmax) || (tzmin > tmax) { return Intersect::empty(); }
-        if tzmin > tmin { tmin = tzmin; }
-        if tzmax < tmax { tmax = tzmax; }
+    let mut tymin = (min.y - origin.y) * invy;
+    let mut tymax = (max.y - origin.y) * invy;
+    if tymin > tymax { std::mem::swap(&mut tymin, &mut tymax); }
 
-        if tmin < 0.0 && tmax < 0.0 { return Intersect::empty(); }
+    if (tmin > tymax) || (tymin > tmax) { return None; }
+    if tymin > tmin { tmin = tymin; near_axis = 1; }
+    if tymax < tmax { tmax = tymax; far_axis = 1; }
 
-        let distance = if tmin >= 0.0 { tmin } else { tmax };
-        let point = *origin + *dir * distance;
+    let mut tzmin = (min.z - origin.z) * invz;
+    let mut tzmax = (max.z - origin.z) * invz;
+    if tzmin > tzmax { std::mem::swap(&mut tzmin, &mut tzmax); }
+
+    if (tmin > tzmax) || (tzmin > tmax) { return None; }
+    if tzmin > tmin { tmin = tzmin; near_axis = 2; }
+    if tzmax < tmax { tmax = tzmax; far_axis = 2; }
 
-        // Determine approximate normal
-        let epsilon = 1e-4;
-        let mut normal = Vector3::zero();
-        if (point.x - min.x).abs() < epsilon { normal = Vector3::new(-1.0, 0.0, 0.0); }
-        else if (point.x - max.x).abs() < epsilon { normal = Vector3::new(1.0, 0.0, 0.0); }
-        else if (point.y - min.y).abs() < epsilon { normal = Vector3::new(0.0, -1.0, 0.0); }
-        else if (point.y - max.y).abs() < epsilon { normal = Vector3::new(0.0, 1.0, 0.0); }
-        else if (point.z - min.z).abs() < epsilon { normal = Vector3::new(0.0, 0.0, -1.0); }
-        else if (point.z - max.z).abs() < epsilon { normal = Vector3::new(0.0, 0.0, 1.0); }
+    if tmin < 0.0 && tmax < 0.0 { return None; }
 
-        let (u, v) = self.calc_uv(&point, &normal);
+    let distance = if tmin >= 0.0 { tmin } else { tmax };
 
-        Intersect::new(&self.material, distance, normal, point, u, v)
+    // La normal sale de la losa que fijó la distancia, no de comparar el
+    // punto con las caras: así no depende de un epsilon (que falla lejos
+    // de la cámara) y nunca es cero. Al entrar apunta contra el rayo; con
+    // el origen adentro, la cara de salida apunta a favor.
+    let normal = if tmin >= 0.0 {
+        axis_normal(near_axis, -axis_component(dir, near_axis).signum())
+    } else {
+        axis_normal(far_axis, axis_component(dir, far_axis).signum())
+    };
+    debug_assert!((normal.length() - 1.0).abs() < 1e-6, "normal no unitaria: {:?}", normal);
+    Some((distance, normal))
+}
+
+/// Componente `axis` (0 = X, 1 = Y, 2 = Z) de un vector
+fn axis_component(v: &Vector3, axis: usize) -> f32 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+/// Normal unitaria sobre el eje `axis` con el signo de `sign` (±1)
+fn axis_normal(axis: usize, sign: f32) -> Vector3 {
+    match axis {
+        0 => Vector3::new(sign, 0.0, 0.0),
+        1 => Vector3::new(0.0, sign, 0.0),
+        _ => Vector3::new(0.0, 0.0, sign),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_types::BlockType;
+    use crate::rng::Rng;
+
+    const EPS: f32 = 1e-4;
+
+    fn unit_block() -> Block {
+        Block::new_cubic(Vector3::zero(), 1.0, BlockType::Stone.material())
+    }
+
+    fn assert_near(actual: f32, expected: f32, what: &str) {
+        assert!(
+            (actual - expected).abs() < EPS,
+            "{}: {} en vez de {}",
+            what,
+            actual,
+            expected
+        );
+    }
+
+    fn assert_vec_near(actual: Vector3, expected: Vector3, what: &str) {
+        assert!(
+            (actual - expected).length() < EPS,
+            "{}: {:?} en vez de {:?}",
+            what,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn every_face_gives_its_normal_and_uv() {
+        let block = unit_block();
+        // Punto de cada cara (corrido del centro para que la UV no sea
+        // simétrica) y la UV que le corresponde
+        let cases = [
+            (Face::East, Vector3::new(0.5, 0.2, 0.1), (0.6, 0.3)),
+            (Face::West, Vector3::new(-0.5, 0.2, 0.1), (0.6, 0.3)),
+            (Face::Top, Vector3::new(0.1, 0.5, 0.2), (0.6, 0.7)),
+            (Face::Bottom, Vector3::new(0.1, -0.5, 0.2), (0.6, 0.3)),
+            (Face::South, Vector3::new(0.1, 0.2, 0.5), (0.4, 0.3)),
+            (Face::North, Vector3::new(0.1, 0.2, -0.5), (0.6, 0.3)),
+        ];
+        for (face, point, (u, v)) in cases {
+            let origin = point + face.normal() * 2.0;
+            let hit = block.ray_intersect(&origin, &-face.normal());
+            assert!(hit.is_intersecting, "{:?}", face);
+            assert_near(hit.distance, 2.0, face.name());
+            assert_vec_near(hit.normal, face.normal(), face.name());
+            assert_vec_near(hit.point, point, face.name());
+            assert_near(hit.u, u, face.name());
+            assert_near(hit.v, v, face.name());
+        }
+    }
+
+    /// UV del impacto sobre `point` llegando desde afuera por `normal`
+    fn uv_at(block: &Block, point: Vector3, normal: Vector3) -> (f32, f32) {
+        let hit = block.ray_intersect(&(point + normal * 2.0), &-normal);
+        assert!(hit.is_intersecting, "{:?}", point);
+        assert_vec_near(hit.normal, normal, "normal del mundo");
+        (hit.u, hit.v)
+    }
+
+    #[test]
+    fn texture_origin_sits_on_a_fixed_corner_of_each_face() {
+        // Esquina de cada cara donde cae el (0, 0) de la textura: en los
+        // costados siempre arriba, así la textura nunca queda cabeza abajo
+        let block = unit_block();
+        let corners = [
+            (Face::East, Vector3::new(0.5, 0.45, -0.45)),
+            (Face::West, Vector3::new(-0.5, 0.45, -0.45)),
+            (Face::Top, Vector3::new(-0.45, 0.5, -0.45)),
+            (Face::Bottom, Vector3::new(-0.45, -0.5, 0.45)),
+            (Face::South, Vector3::new(0.45, 0.45, 0.5)),
+            (Face::North, Vector3::new(-0.45, 0.45, -0.5)),
+        ];
+        for (face, corner) in corners {
+            let (u, v) = uv_at(&block, corner, face.normal());
+            assert!(u < 0.1 && v < 0.1, "{}: ({}, {})", face.name(), u, v);
+            // La esquina opuesta es el (1, 1)
+            let opposite = corner - (corner - face.normal() * 0.5) * 2.0;
+            let (u, v) = uv_at(&block, opposite, face.normal());
+            assert!(
+                u > 0.9 && v > 0.9,
+                "{} opuesta: ({}, {})",
+                face.name(),
+                u,
+                v
+            );
+        }
+    }
+
+    #[test]
+    fn uv_matrix_across_faces_and_extents() {
+        // Por cara: eje de u y de v y si corren al revés del eje, la misma
+        // convención que en el cubo de lado 1
+        let layouts = [
+            (Face::East, (2, false), (1, true)),
+            (Face::West, (2, false), (1, true)),
+            (Face::Top, (0, false), (2, false)),
+            (Face::Bottom, (0, false), (2, true)),
+            (Face::South, (0, true), (1, true)),
+            (Face::North, (0, false), (1, true)),
+        ];
+        let extents = [
+            Vector3::new(1.0, 1.0, 1.0),
+            Vector3::new(2.0, 2.0, 2.0),
+            Vector3::new(1.0, 0.5, 1.0),
+            Vector3::new(0.25, 0.75, 0.25),
+            Vector3::new(1.0, 0.125, 0.5),
+            Vector3::new(0.0625, 1.0, 1.0),
+        ];
+        let position = Vector3::new(3.0, -2.0, 5.0);
+        let fractions = [(0.1, 0.2), (0.5, 0.5), (0.9, 0.3), (0.25, 0.95)];
+
+        for extents in extents {
+            let block = Block::new(position, extents, BlockType::Stone.material());
+            let half = extents * 0.5;
+            let unit = extents.x.max(extents.y).max(extents.z);
+            for (face, (u_axis, u_flip), (v_axis, v_flip)) in layouts {
+                let normal = face.normal();
+                for (fu, fv) in fractions {
+                    // Punto de la cara a esas fracciones de su ancho y alto
+                    let mut local = [0.0; 3];
+                    let normal_axis = 3 - u_axis - v_axis;
+                    local[normal_axis] =
+                        axis_component(&normal, normal_axis) * axis_component(&half, normal_axis);
+                    local[u_axis] = (fu - 0.5) * axis_component(&extents, u_axis);
+                    local[v_axis] = (fv - 0.5) * axis_component(&extents, v_axis);
+                    let point = position + Vector3::new(local[0], local[1], local[2]);
+
+                    // Sin estirar: la textura mide `unit` en cada cara
+                    let span = |axis: usize, fraction: f32, flip: bool| {
+                        let along = fraction * axis_component(&extents, axis) / unit;
+                        if flip { 1.0 - along } else { along }
+                    };
+                    let what = format!("{:?} {:?} ({}, {})", extents, face, fu, fv);
+                    let (u, v) = uv_at(&block, point, normal);
+                    let expected_u = span(u_axis, fu, u_flip);
+                    let expected_v = span(v_axis, fv, v_flip);
+                    assert_near(u, expected_u, &format!("{} u", what));
+                    assert_near(v, expected_v, &format!("{} v", what));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn oriented_block_keeps_the_uv_of_its_local_face() {
+        // Acostado hacia +X: la tapa local queda en la cara East del mundo
+        // y el punto local (lx, 0.5, lz) cae en (0.5, -lx, lz)
+        let block = unit_block().with_orientation(Orientation::new(Face::East, 0));
+        let (u, v) = uv_at(&block, Vector3::new(0.5, 0.2, 0.1), Face::East.normal());
+        assert_near(u, 0.3, "tapa acostada u");
+        assert_near(v, 0.6, "tapa acostada v");
+
+        // Un cuarto de vuelta sobre Y: la cara North local mira al West y
+        // el punto local (lx, ly, -0.5) cae en (-0.5, ly, -lx)
+        let block = unit_block().with_orientation(Orientation::new(Face::Top, 1));
+        let (u, v) = uv_at(&block, Vector3::new(-0.5, 0.2, 0.1), Face::West.normal());
+        assert_near(u, 0.4, "costado girado u");
+        assert_near(v, 0.3, "costado girado v");
+
+        // La tapa gira con el bloque: (lx, 0.5, lz) cae en (lz, 0.5, -lx)
+        let (u, v) = uv_at(&block, Vector3::new(0.1, 0.5, 0.2), Face::Top.normal());
+        assert_near(u, 0.3, "tapa girada u");
+        assert_near(v, 0.6, "tapa girada v");
+    }
+
+    #[test]
+    fn edges_and_corners_hit_with_an_axis_normal() {
+        let block = unit_block();
+        let targets = [
+            Vector3::new(0.5, 0.5, 0.0),
+            Vector3::new(-0.5, 0.0, 0.5),
+            Vector3::new(0.0, -0.5, -0.5),
+            Vector3::new(0.5, 0.5, 0.5),
+            Vector3::new(-0.5, -0.5, 0.5),
+            Vector3::new(0.5, -0.5, -0.5),
+        ];
+        for target in targets {
+            // Se llega desde afuera en diagonal, apuntando justo al borde
+            let origin = target * 6.0;
+            let dir = (target - origin).normalized();
+            let hit = block.ray_intersect(&origin, &dir);
+            assert!(hit.is_intersecting, "{:?}", target);
+            assert_near(
+                hit.distance,
+                (target - origin).length(),
+                "distancia al borde",
+            );
+            assert_vec_near(hit.point, target, "punto del borde");
+            // La normal es la de una de las caras que se juntan ahí
+            let face = Face::from_normal(&hit.normal);
+            assert_vec_near(hit.normal, face.normal(), "normal de eje");
+            assert!(hit.normal.dot(dir) < 0.0);
+            assert_near(
+                axis_component(&target, face.index() / 2).abs(),
+                0.5,
+                "cara del borde",
+            );
+        }
+    }
+
+    #[test]
+    fn origin_inside_hits_the_exit_face() {
+        let block = unit_block();
+        let hit = block.ray_intersect(&Vector3::zero(), &Vector3::new(1.0, 0.0, 0.0));
+        assert!(hit.is_intersecting);
+        assert_near(hit.distance, 0.5, "salida por +X");
+        assert_vec_near(hit.normal, Vector3::new(1.0, 0.0, 0.0), "normal de salida");
+
+        // En diagonal sale por la cara más cercana en la dirección del rayo
+        let dir = Vector3::new(1.0, 1.0, 0.0).normalized();
+        let hit = block.ray_intersect(&Vector3::new(0.1, 0.0, 0.0), &dir);
+        assert_near(hit.distance, 0.4 * 2f32.sqrt(), "salida diagonal");
+        assert_vec_near(hit.normal, Vector3::new(1.0, 0.0, 0.0), "normal diagonal");
+        assert!(hit.distance > 0.0);
+    }
+
+    #[test]
+    fn misses_return_no_hit() {
+        let block = unit_block();
+        let misses = [
+            // Paralelo a Z, fuera de las losas de X e Y
+            (Vector3::new(2.0, 2.0, -3.0), Vector3::new(0.0, 0.0, 1.0)),
+            // Paralelo a X, pasa justo por encima
+            (Vector3::new(-3.0, 0.6, 0.0), Vector3::new(1.0, 0.0, 0.0)),
+            // El bloque queda detrás
+            (Vector3::new(0.0, 0.0, -2.0), Vector3::new(0.0, 0.0, -1.0)),
+            // Oblicuo que pasa al costado
+            (
+                Vector3::new(0.0, 0.0, -2.0),
+                Vector3::new(1.0, 0.0, 0.1).normalized(),
+            ),
+        ];
+        for (origin, dir) in misses {
+            let hit = block.ray_intersect(&origin, &dir);
+            assert!(!hit.is_intersecting, "{:?} -> {:?}", origin, dir);
+            assert!(hit.distance.is_infinite());
+        }
+    }
+
+    #[test]
+    fn distances_match_the_analytic_slab() {
+        // Caja de 2x1x4 corrida del origen: la cara -Z está en z = -4
+        let block = Block::new(
+            Vector3::new(3.0, 1.0, -2.0),
+            Vector3::new(2.0, 1.0, 4.0),
+            BlockType::Stone.material(),
+        );
+        let hit = block.ray_intersect(&Vector3::new(3.0, 1.0, -10.0), &Vector3::new(0.0, 0.0, 1.0));
+        assert_near(hit.distance, 6.0, "frente -Z");
+        assert_vec_near(hit.normal, Vector3::new(0.0, 0.0, -1.0), "normal -Z");
+
+        // Desde arriba en 45°: baja 1 hasta y = 1.5 y avanza 1 en X
+        let dir = Vector3::new(1.0, -1.0, 0.0).normalized();
+        let hit = block.ray_intersect(&Vector3::new(2.0, 2.5, -2.0), &dir);
+        assert_near(hit.distance, 2f32.sqrt(), "diagonal a la tapa");
+        assert_vec_near(hit.point, Vector3::new(3.0, 1.5, -2.0), "punto en la tapa");
+        assert_vec_near(hit.normal, Vector3::new(0.0, 1.0, 0.0), "normal +Y");
+    }
+
+    #[test]
+    fn random_rays_land_on_the_box_surface() {
+        let block = unit_block();
+        let mut rng = Rng::new(929, 1);
+        let mut signed = || rng.next_f32() * 2.0 - 1.0;
+        for _ in 0..2000 {
+            // Origen afuera (a distancia 3) apuntando a un punto adentro,
+            // así el rayo siempre tiene que pegar
+            let origin = Vector3::new(signed(), signed(), signed()).normalized() * 3.0;
+            let target = Vector3::new(signed(), signed(), signed()) * 0.45;
+            let dir = (target - origin).normalized();
+            let hit = block.ray_intersect(&origin, &dir);
+            assert!(hit.is_intersecting, "{:?} -> {:?}", origin, target);
+
+            let point = hit.point;
+            let outside = [point.x, point.y, point.z]
+                .iter()
+                .map(|c| c.abs() - 0.5)
+                .fold(f32::MIN, f32::max);
+            assert!(
+                outside.abs() < EPS,
+                "punto fuera de la superficie: {:?}",
+                point
+            );
+            assert_vec_near(point, origin + dir * hit.distance, "punto y distancia");
+            assert!(hit.normal.dot(dir) < 0.0, "la normal mira hacia el rayo");
+            let axis = Face::from_normal(&hit.normal).index() / 2;
+            assert_near(
+                axis_component(&point, axis).abs(),
+                0.5,
+                "normal de la cara golpeada",
+            );
+        }
+    }
+
+    /// La normal de `intersect_box` siempre es de un eje, unitaria y
+    /// contra el rayo (o a favor, con el origen adentro)
+    fn assert_axis_normal(normal: Vector3, what: &str) {
+        assert!(
+            Face::ALL.iter().any(|face| face.normal() == normal),
+            "{}: normal {:?}",
+            what,
+            normal
+        );
+    }
+
+    #[test]
+    fn grazing_rays_never_give_a_zero_normal() {
+        let (min, max) = (Vector3::one() * -0.5, Vector3::one() * 0.5);
+        let grazing = [
+            // En el plano de la tapa, a lo largo de X
+            (Vector3::new(-3.0, 0.5, 0.0), Vector3::new(1.0, 0.0, 0.0)),
+            // Por la arista superior de la cara -Z
+            (Vector3::new(-3.0, 0.5, -0.5), Vector3::new(1.0, 0.0, 0.0)),
+            // Justo por una esquina, en diagonal
+            (
+                Vector3::new(-3.0, -3.0, 0.5),
+                Vector3::new(1.0, 1.0, 0.0).normalized(),
+            ),
+            // Rozando la cara +X desde arriba
+            (Vector3::new(0.5, 3.0, 0.2), Vector3::new(0.0, -1.0, 0.0)),
+        ];
+        for (origin, dir) in grazing {
+            if let Some((distance, normal)) = intersect_box(&min, &max, &origin, &dir) {
+                assert!(distance.is_finite() && distance >= 0.0, "{:?}", origin);
+                assert_axis_normal(normal, "rasante");
+            }
+        }
+    }
+
+    #[test]
+    fn far_edge_hits_keep_an_axis_normal() {
+        // A más de 40 unidades el error de redondeo del punto supera 1e-4:
+        // comparar el punto con las caras dejaba la normal en cero
+        let block = Block::new_cubic(
+            Vector3::new(40.3, 17.7, 35.1),
+            1.0,
+            BlockType::Stone.material(),
+        );
+        let (min, max) = block.bounds();
+        let origin = Vector3::new(0.1, 0.2, -0.3);
+        let targets = [
+            Vector3::new(min.x, max.y, block.position.z),
+            Vector3::new(min.x, min.y, min.z),
+            Vector3::new(block.position.x, max.y, min.z),
+            Vector3::new(max.x, max.y, min.z),
+        ];
+        for target in targets {
+            // Apenas hacia adentro para que el impacto sea seguro
+            let target = target.lerp(block.position, 1e-3);
+            let dir = (target - origin).normalized();
+            let hit = block.ray_intersect(&origin, &dir);
+            assert!(hit.is_intersecting, "{:?}", target);
+            assert!(hit.distance > 40.0);
+            assert_axis_normal(hit.normal, "arista lejana");
+            assert!(hit.normal.dot(dir) <= 0.0);
+        }
     }
 }
@@ -0,0 +1,249 @@
+// block_inspector.rs - Panel con los datos del bloque bajo el mouse (clic central)
+use raylib::prelude::*;
+
+use crate::block::{Block, Face};
+use crate::block_types::BlockType;
+use crate::material::Material;
+use crate::notifications::Notifications;
+use crate::ray_intersect::RayIntersect;
+use crate::snell::pick_block;
+use crate::textures::TextureManager;
+
+/// Copia de lo que se vio en el clic; no guarda referencias a la escena, así
+/// que sigue siendo válida aunque la escena cambie después.
+#[derive(Debug, Clone)]
+struct BlockInfo {
+    index: usize,
+    position: Vector3,
+    extents: Vector3,
+    block_type: Option<BlockType>,
+    face: Face,
+    /// La cara tiene un material propio (`face_overrides`)
+    face_override: bool,
+    material: Material,
+    point: Vector3,
+    u: f32,
+    v: f32,
+    /// Tamaño de la textura, si está cargada
+    texture_size: Option<(u32, u32)>,
+}
+
+/// Estado del inspector: el último bloque inspeccionado, hasta que se cierre
+#[derive(Default)]
+pub struct BlockInspector {
+    info: Option<BlockInfo>,
+}
+
+impl BlockInspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.info.is_some()
+    }
+
+    pub fn close(&mut self) {
+        self.info = None;
+    }
+
+    /// Inspecciona el bloque que toca el rayo. Si no toca nada, cierra el panel.
+    pub fn inspect(
+        &mut self,
+        origin: &Vector3,
+        dir: &Vector3,
+        scene: &[Block],
+        texture_manager: &TextureManager,
+    ) {
+        self.info = pick_block(origin, dir, scene).and_then(|pick| {
+            let block = &scene[pick.index];
+            let hit = block.ray_intersect(origin, dir);
+            let material = hit.material?;
+            let face = Face::from_normal(&hit.normal);
+            Some(BlockInfo {
+                index: pick.index,
+                position: block.position,
+                extents: block.extents,
+                block_type: BlockType::identify(block),
+                face,
+                face_override: block
+                    .face_overrides
+                    .as_ref()
+                    .is_some_and(|faces| faces[face.index()].is_some()),
+                material: material.clone(),
+                point: hit.point,
+                u: hit.u,
+                v: hit.v,
+                texture_size: material
+                    .texture
+                    .as_deref()
+                    .and_then(|path| texture_manager.size_of(path)),
+            })
+        });
+    }
+
+    /// Líneas de texto del panel (también es lo que se copia)
+    pub fn lines(&self) -> Vec<String> {
+        let Some(info) = &self.info else {
+            return Vec::new();
+        };
+        let m = &info.material;
+        let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+        let e = info.extents;
+        let size = if e.x == e.y && e.y == e.z {
+            format!("{:.2}", e.x)
+        } else {
+            format!("{:.2} x {:.2} x {:.2}", e.x, e.y, e.z)
+        };
+        let mut lines = vec![
+            format!(
+                "Bloque #{} en ({:.2}, {:.2}, {:.2}) tamaño {}",
+                info.index, info.position.x, info.position.y, info.position.z, size
+            ),
+            format!(
+                "Tipo: {}",
+                info.block_type
+                    .as_ref()
+                    .map_or("personalizado", |block_type| block_type.display_name())
+            ),
+            format!(
+                "Cara: {}{}",
+                face_name(info.face),
+                if info.face_override {
+                    " (material propio)"
+                } else {
+                    ""
+                }
+            ),
+            format!(
+                "Punto: ({:.3}, {:.3}, {:.3})",
+                info.point.x, info.point.y, info.point.z
+            ),
+            format!("UV: ({:.4}, {:.4})", info.u, info.v),
+        ];
+        if let Some((width, height)) = info.texture_size {
+            // Mismo mapeo que el muestreo bilineal de la textura
+            lines.push(format!(
+                "Texel: ({:.2}, {:.2}) de {}x{}",
+                info.u.clamp(0.0, 1.0) * (width - 1) as f32,
+                info.v.clamp(0.0, 1.0) * (height - 1) as f32,
+                width,
+                height
+            ));
+        }
+        lines.extend([
+            format!("Textura: {}", optional(&m.texture)),
+            format!("Normal map: {}", optional(&m.normal_map_id)),
+            format!(
+                "Difuso: ({:.3}, {:.3}, {:.3}) albedo [{:.2}, {:.2}]",
+                m.diffuse.x, m.diffuse.y, m.diffuse.z, m.albedo[0], m.albedo[1]
+            ),
+            format!(
+                "Especular {:.1} | reflejo {:.2} | transparencia {:.2}",
+                m.specular, m.reflectivity, m.transparency
+            ),
+            format!(
+                "IOR {:.3}{} | translucidez {:.2}",
+                m.refractive_index,
+                m.refractive_index_rgb
+                    .map_or(String::new(), |[r, g, b]| format!(
+                        " (RGB {:.3}/{:.3}/{:.3})",
+                        r, g, b
+                    )),
+                m.translucency
+            ),
+            match m.emission_color {
+                Some(color) => format!(
+                    "Emisión ({:.2}, {:.2}, {:.2}) x {:.2}{}",
+                    color.x,
+                    color.y,
+                    color.z,
+                    m.emission_strength,
+                    if m.unlit { " sin iluminar" } else { "" }
+                ),
+                None => "Emisión: -".to_string(),
+            },
+        ]);
+        if m.absorption != Vector3::zero() || m.waves > 0.0 {
+            lines.push(format!(
+                "Absorción ({:.2}, {:.2}, {:.2}) | olas {:.2}",
+                m.absorption.x, m.absorption.y, m.absorption.z, m.waves
+            ));
+        }
+        if m.roughness > 0.0 || m.frost > 0.0 {
+            lines.push(format!("Aspereza {:.2} | escarcha {:.2}", m.roughness, m.frost));
+        }
+        if m.aberration_strength > 0.0 || m.distortion_strength > 0.0 {
+            lines.push(format!(
+                "Aberración {:.2} | distorsión {:.2}",
+                m.aberration_strength, m.distortion_strength
+            ));
+        }
+        if m.caustics {
+            lines.push("Proyecta cáusticas".to_string());
+        }
+        if m.fresnel_reflection {
+            lines.push("Reflejo de Fresnel".to_string());
+        }
+        lines
+    }
+
+    /// C copia los datos al portapapeles (y los imprime); Retroceso cierra
+    pub fn handle_input(&mut self, rl: &mut RaylibHandle, notifications: &mut Notifications) {
+        if !self.is_open() {
+            return;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_C) {
+            let text = self.lines().join("\n");
+            println!("{}", text);
+            match rl.set_clipboard_text(&text) {
+                Ok(()) => notifications.info("Datos del bloque copiados al portapapeles"),
+                Err(err) => {
+                    notifications.error(format!("No se pudo copiar al portapapeles: {}", err))
+                }
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            self.close();
+        }
+    }
+
+    /// Dibuja el panel con la esquina inferior izquierda en (x, bottom)
+    pub fn draw(&self, d: &mut RaylibDrawHandle, x: i32, bottom: i32) {
+        if !self.is_open() {
+            return;
+        }
+        let mut lines = self.lines();
+        lines.push("C: copiar | Retroceso: cerrar".to_string());
+        let line_height = 16;
+        let width = lines
+            .iter()
+            .map(|line| d.measure_text(line, 14))
+            .max()
+            .unwrap_or(0);
+        let height = line_height * lines.len() as i32 + 10;
+        let y = bottom - height;
+        d.draw_rectangle(x, y, width + 16, height, Color::new(0, 0, 0, 170));
+        let last = lines.len() - 1;
+        for (i, line) in lines.iter().enumerate() {
+            let color = match i {
+                0 => Color::YELLOW,
+                i if i == last => Color::LIGHTGRAY,
+                _ => Color::WHITE,
+            };
+            d.draw_text(line, x + 8, y + 5 + line_height * i as i32, 14, color);
+        }
+    }
+}
+
+fn face_name(face: Face) -> &'static str {
+    match face {
+        Face::East => "este (+X)",
+        Face::West => "oeste (-X)",
+        Face::Top => "arriba (+Y)",
+        Face::Bottom => "abajo (-Y)",
+        Face::South => "sur (+Z)",
+        Face::North => "norte (-Z)",
+    }
+}
@@ -0,0 +1,157 @@
+// block_registry.rs - Tipos de bloque por nombre: los del enum más los definidos en archivos
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::block_shape::BlockShape;
+use crate::block_types::{BlockType, normalize_name};
+use crate::material::Material;
+use crate::orientation::Orientation;
+
+/// Archivo opcional con tipos de bloque propios (directivas `blockdef` del
+/// formato de escena)
+pub const BLOCKS_PATH: &str = "blocks.txt";
+
+/// Tipo de bloque definido sin recompilar: un nombre y un bloque modelo en el
+/// origen con lado 1 (material, forma, orientación, caras y luz)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockDefinition {
+    pub name: String,
+    pub template: Block,
+}
+
+impl BlockDefinition {
+    pub fn new(name: &str, material: Material) -> Self {
+        Self {
+            name: name.to_string(),
+            template: Block::new_cubic(Vector3::zero(), 1.0, material),
+        }
+    }
+
+    /// Copia del modelo en una posición, escalada por `size`; la luz
+    /// conserva su desplazamiento relativo
+    pub fn to_block(&self, position: Vector3, size: f32) -> Block {
+        let mut block = self.template.clone();
+        block.position = position;
+        block.extents *= size;
+        if let Some(light) = block.emission.as_mut() {
+            light.position = position + light.position * size;
+        }
+        block
+    }
+}
+
+/// Entrada del registro: un tipo del enum o uno definido en un archivo
+#[derive(Debug, Clone)]
+pub enum RegisteredBlock {
+    Builtin(BlockType),
+    Custom(Box<BlockDefinition>),
+}
+
+impl RegisteredBlock {
+    pub fn name(&self) -> &str {
+        match self {
+            RegisteredBlock::Builtin(block_type) => block_type.name(),
+            RegisteredBlock::Custom(definition) => &definition.name,
+        }
+    }
+
+    /// Nombre para mostrar: los propios no tienen otro que el suyo
+    pub fn display_name(&self) -> &str {
+        match self {
+            RegisteredBlock::Builtin(block_type) => block_type.display_name(),
+            RegisteredBlock::Custom(definition) => &definition.name,
+        }
+    }
+
+    pub fn material(&self) -> Material {
+        match self {
+            RegisteredBlock::Builtin(block_type) => block_type.material(),
+            RegisteredBlock::Custom(definition) => definition.template.material.clone(),
+        }
+    }
+
+    pub fn default_shape(&self) -> BlockShape {
+        match self {
+            RegisteredBlock::Builtin(block_type) => block_type.default_shape(),
+            RegisteredBlock::Custom(definition) => definition.template.shape,
+        }
+    }
+
+    /// Ver `BlockType::placed_orientation`; los propios usan la del modelo
+    pub fn placed_orientation(&self, normal: &Vector3, look: &Vector3) -> Orientation {
+        match self {
+            RegisteredBlock::Builtin(block_type) => block_type.placed_orientation(normal, look),
+            RegisteredBlock::Custom(definition) => definition.template.orientation,
+        }
+    }
+
+    pub fn to_block(&self, position: Vector3, size: f32) -> Block {
+        match self {
+            RegisteredBlock::Builtin(block_type) => block_type.to_block(position, size),
+            RegisteredBlock::Custom(definition) => definition.to_block(position, size),
+        }
+    }
+}
+
+/// Tipos de bloque por nombre: empieza con todos los del enum y suma los
+/// que se registran al cargar `blocks.txt` o la paleta de una escena
+#[derive(Debug, Clone)]
+pub struct BlockRegistry {
+    entries: Vec<RegisteredBlock>,
+}
+
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockRegistry {
+    pub fn new() -> Self {
+        Self {
+            entries: BlockType::all()
+                .iter()
+                .cloned()
+                .map(RegisteredBlock::Builtin)
+                .collect(),
+        }
+    }
+
+    /// Registra un tipo propio; uno con el mismo nombre se reemplaza. Los
+    /// nombres (y alias) de los tipos del enum no se pueden pisar.
+    pub fn register(&mut self, definition: BlockDefinition) -> Result<(), String> {
+        if BlockType::from_name(&definition.name).is_some() {
+            return Err(format!("'{}' es un tipo de bloque incorporado", definition.name));
+        }
+        let wanted = normalize_name(&definition.name);
+        match self.entries.iter_mut().find(|entry| normalize_name(entry.name()) == wanted) {
+            Some(entry) => *entry = RegisteredBlock::Custom(Box::new(definition)),
+            None => self.entries.push(RegisteredBlock::Custom(Box::new(definition))),
+        }
+        Ok(())
+    }
+
+    /// Busca por nombre con las mismas reglas que `BlockType::from_name`
+    pub fn get(&self, name: &str) -> Option<&RegisteredBlock> {
+        if let Some(block_type) = BlockType::from_name(name) {
+            return self.entries.iter().find(|entry| {
+                matches!(entry, RegisteredBlock::Builtin(builtin) if *builtin == block_type)
+            });
+        }
+        let wanted = normalize_name(name);
+        self.entries.iter().find(|entry| normalize_name(entry.name()) == wanted)
+    }
+
+    /// Todos los tipos: primero los del enum, después los propios
+    pub fn entries(&self) -> &[RegisteredBlock] {
+        &self.entries
+    }
+
+    /// Solo los tipos propios
+    pub fn custom(&self) -> impl Iterator<Item = &BlockDefinition> {
+        self.entries.iter().filter_map(|entry| match entry {
+            RegisteredBlock::Custom(definition) => Some(definition.as_ref()),
+            RegisteredBlock::Builtin(_) => None,
+        })
+    }
+}
@@ -0,0 +1,642 @@
+// block_shape.rs - Formas de bloque que no llenan toda la celda
+use raylib::prelude::*;
+
+use crate::plant::Plant;
+
+/// Lado de la celda en el que se apoya un bloque chico (ej: antorcha)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Attachment {
+    /// Sobre el bloque de abajo
+    Floor,
+    /// Contra la pared -Z
+    North,
+    /// Contra la pared +Z
+    South,
+    /// Contra la pared +X
+    East,
+    /// Contra la pared -X
+    West,
+}
+
+impl Attachment {
+    pub const ALL: [Attachment; 5] = [
+        Attachment::Floor,
+        Attachment::North,
+        Attachment::South,
+        Attachment::East,
+        Attachment::West,
+    ];
+
+    /// Nombre usado en los archivos de escena
+    pub fn name(self) -> &'static str {
+        match self {
+            Attachment::Floor => "floor",
+            Attachment::North => "north",
+            Attachment::South => "south",
+            Attachment::East => "east",
+            Attachment::West => "west",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|attachment| attachment.name() == name)
+    }
+
+    /// Apoyo de un bloque colocado contra la cara de normal `normal` del
+    /// bloque vecino. None para la cara de abajo: no se cuelga del techo.
+    pub fn from_face_normal(normal: &Vector3) -> Option<Self> {
+        if normal.y > 0.5 {
+            Some(Attachment::Floor)
+        } else if normal.y < -0.5 {
+            None
+        } else if normal.x.abs() > normal.z.abs() {
+            // La pared queda del lado opuesto a la normal
+            Some(if normal.x > 0.0 {
+                Attachment::West
+            } else {
+                Attachment::East
+            })
+        } else {
+            Some(if normal.z > 0.0 {
+                Attachment::North
+            } else {
+                Attachment::South
+            })
+        }
+    }
+
+    /// Dirección horizontal hacia la pared (cero en el piso)
+    fn wall_direction(self) -> Vector3 {
+        match self {
+            Attachment::Floor => Vector3::zero(),
+            Attachment::North => Vector3::new(0.0, 0.0, -1.0),
+            Attachment::South => Vector3::new(0.0, 0.0, 1.0),
+            Attachment::East => Vector3::new(1.0, 0.0, 0.0),
+            Attachment::West => Vector3::new(-1.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Dirección horizontal (ej: hacia dónde sube una escalera)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Hacia -Z
+    North,
+    /// Hacia +Z
+    South,
+    /// Hacia +X
+    East,
+    /// Hacia -X
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+    ];
+
+    /// Nombre usado en los archivos de escena
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::East => "east",
+            Direction::West => "west",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|direction| direction.name() == name)
+    }
+
+    /// Dirección horizontal más cercana a `v` (se ignora la componente Y)
+    pub fn from_horizontal(v: &Vector3) -> Self {
+        if v.x.abs() > v.z.abs() {
+            if v.x > 0.0 { Direction::East } else { Direction::West }
+        } else if v.z > 0.0 {
+            Direction::South
+        } else {
+            Direction::North
+        }
+    }
+
+    /// Vector unitario de la dirección
+    pub fn vector(self) -> Vector3 {
+        match self {
+            Direction::North => Vector3::new(0.0, 0.0, -1.0),
+            Direction::South => Vector3::new(0.0, 0.0, 1.0),
+            Direction::East => Vector3::new(1.0, 0.0, 0.0),
+            Direction::West => Vector3::new(-1.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Eje horizontal (ej: el perpendicular a un panel de vidrio)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Axis {
+    X,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 2] = [Axis::X, Axis::Z];
+
+    /// Nombre usado en los archivos de escena
+    pub fn name(self) -> &'static str {
+        match self {
+            Axis::X => "x",
+            Axis::Z => "z",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|axis| axis.name() == name)
+    }
+
+    /// Eje horizontal dominante de `v`
+    pub fn from_horizontal(v: &Vector3) -> Self {
+        if v.x.abs() > v.z.abs() { Axis::X } else { Axis::Z }
+    }
+
+    /// Vector unitario del eje
+    pub fn vector(self) -> Vector3 {
+        match self {
+            Axis::X => Vector3::new(1.0, 0.0, 0.0),
+            Axis::Z => Vector3::new(0.0, 0.0, 1.0),
+        }
+    }
+}
+
+/// Forma del bloque dentro de su celda
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockShape {
+    /// Cubo que llena la celda
+    #[default]
+    Cube,
+    /// Palo fino de 0.15 x 0.6 x 0.15 en el piso o contra una pared
+    Torch(Attachment),
+    /// Media celda: la mitad de abajo o, con `top`, la de arriba
+    Slab { top: bool },
+    /// Escalón en L: media celda de abajo entera más la mitad de arriba del
+    /// lado `facing`, hacia donde sube
+    Stair { facing: Direction },
+    /// Dos láminas verticales cruzadas en el centro (flores, pasto alto)
+    CrossPlant(Plant),
+    /// Panel delgado centrado en la celda, perpendicular a `axis`
+    Pane { axis: Axis },
+    /// Capas de nieve apiladas desde el piso, de 1/8 de celda cada una
+    /// (1 a `MAX_SNOW_LAYERS`)
+    SnowLayer { layers: u8 },
+    /// Poste central de cerca con un travesaño hacia cada cerca vecina. El
+    /// bit `i` de `rails` corresponde a `Direction::ALL[i]`; se recalcula
+    /// con los vecinos (ver `scene::connect_fences`).
+    Fence { rails: u8 },
+    /// Cofre de 0.875 de lado con el frente (y el pestillo) hacia el norte
+    /// local; `open` levanta la tapa sobre su bisagra trasera (ver
+    /// `HingedBox`)
+    Chest { open: bool },
+}
+
+/// Cantidad máxima de cajas que forman una figura (la cerca: poste y cuatro
+/// travesaños)
+const MAX_SHAPE_BOXES: usize = 5;
+
+/// Cajas (mínimo, máximo) que forman una figura, en coordenadas de celda
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeBoxes {
+    boxes: [(Vector3, Vector3); MAX_SHAPE_BOXES],
+    len: usize,
+}
+
+impl ShapeBoxes {
+    fn one(min: Vector3, max: Vector3) -> Self {
+        ShapeBoxes {
+            boxes: [(min, max); MAX_SHAPE_BOXES],
+            len: 1,
+        }
+    }
+
+    fn push(&mut self, min: Vector3, max: Vector3) {
+        self.boxes[self.len] = (min, max);
+        self.len += 1;
+    }
+}
+
+impl std::ops::Deref for ShapeBoxes {
+    type Target = [(Vector3, Vector3)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.boxes[..self.len]
+    }
+}
+
+/// Lado de la antorcha en planta y su altura (fracción de la celda)
+const TORCH_WIDTH: f32 = 0.15;
+const TORCH_HEIGHT: f32 = 0.6;
+
+/// Grosor de un panel (fracción de la celda)
+const PANE_THICKNESS: f32 = 0.125;
+
+/// Capas de nieve que entran en una celda
+pub const MAX_SNOW_LAYERS: u8 = 8;
+
+/// Lado del poste de la cerca, y ancho y altura (desde el centro de la
+/// celda) de sus travesaños
+const FENCE_POST_WIDTH: f32 = 0.25;
+const FENCE_RAIL_WIDTH: f32 = 0.125;
+const FENCE_RAIL_BOTTOM: f32 = -0.05;
+const FENCE_RAIL_TOP: f32 = 0.3;
+
+/// Medio lado del cofre, altura (desde el centro de la celda) donde la
+/// tapa se apoya sobre el cuerpo y grosor de la tapa
+const CHEST_HALF_WIDTH: f32 = 0.4375;
+const CHEST_LID_BOTTOM: f32 = 0.125;
+const CHEST_LID_THICKNESS: f32 = 0.25;
+
+/// Medio ancho y alto del pestillo del frente
+const CHEST_LATCH_HALF_WIDTH: f32 = 0.0625;
+const CHEST_LATCH_HEIGHT: f32 = 0.25;
+
+/// Ángulo de la tapa abierta, en radianes
+pub const CHEST_LID_OPEN_ANGLE: f32 = 1.2;
+
+/// Caja que gira sobre una bisagra horizontal (la tapa abierta del cofre):
+/// la caja `min..max` en su lugar cerrado, girada `angle` radianes
+/// alrededor del eje X que pasa por `hinge`. Las coordenadas son las de
+/// `BlockShape::boxes`.
+#[derive(Debug, Clone, Copy)]
+pub struct HingedBox {
+    pub min: Vector3,
+    pub max: Vector3,
+    pub hinge: Vector3,
+    pub angle: f32,
+}
+
+impl HingedBox {
+    /// Gira un vector de la caja cerrada a la abierta (el frente sube)
+    pub fn open(&self, v: Vector3) -> Vector3 {
+        let (sin, cos) = self.angle.sin_cos();
+        Vector3::new(v.x, v.y * cos - v.z * sin, v.y * sin + v.z * cos)
+    }
+
+    /// Inversa de `open`
+    pub fn close(&self, v: Vector3) -> Vector3 {
+        let (sin, cos) = self.angle.sin_cos();
+        Vector3::new(v.x, v.y * cos + v.z * sin, -v.y * sin + v.z * cos)
+    }
+
+    /// Caja alineada a los ejes que envuelve la caja abierta
+    pub fn bounds(&self) -> (Vector3, Vector3) {
+        let mut bounds = (Vector3::one() * f32::INFINITY, Vector3::one() * f32::NEG_INFINITY);
+        for i in 0..8 {
+            let corner = Vector3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+            let p = self.hinge + self.open(corner - self.hinge);
+            bounds.0 = Vector3::new(bounds.0.x.min(p.x), bounds.0.y.min(p.y), bounds.0.z.min(p.z));
+            bounds.1 = Vector3::new(bounds.1.x.max(p.x), bounds.1.y.max(p.y), bounds.1.z.max(p.z));
+        }
+        bounds
+    }
+}
+
+impl BlockShape {
+    /// Si la forma ocupa la celda entera (tapa por completo la cara vecina)
+    pub fn is_full(self) -> bool {
+        self == BlockShape::Cube
+    }
+
+    /// Cajas que forman la figura en una celda de lado 1 centrada en el
+    /// origen. Casi todas son una sola caja; la escalera son dos, el cofre
+    /// tres (dos con la tapa abierta, que va en `hinged_lid`) y la cerca de
+    /// una a cinco.
+    pub fn boxes(self) -> ShapeBoxes {
+        match self {
+            BlockShape::Cube => {
+                ShapeBoxes::one(Vector3::new(-0.5, -0.5, -0.5), Vector3::new(0.5, 0.5, 0.5))
+            }
+            BlockShape::Torch(attachment) => {
+                let half = TORCH_WIDTH * 0.5;
+                // En la pared la antorcha sube y se pega al borde de la celda
+                let (bottom, shift) = match attachment {
+                    Attachment::Floor => (-0.5, 0.0),
+                    _ => (-0.3, 0.5 - half),
+                };
+                let center = attachment.wall_direction() * shift;
+                ShapeBoxes::one(
+                    Vector3::new(center.x - half, bottom, center.z - half),
+                    Vector3::new(center.x + half, bottom + TORCH_HEIGHT, center.z + half),
+                )
+            }
+            BlockShape::Slab { top } => {
+                let bottom = if top { 0.0 } else { -0.5 };
+                ShapeBoxes::one(
+                    Vector3::new(-0.5, bottom, -0.5),
+                    Vector3::new(0.5, bottom + 0.5, 0.5),
+                )
+            }
+            BlockShape::Stair { facing } => {
+                // Mitad trasera de altura completa y mitad delantera de media
+                // altura, sin solaparse: la contrahuella queda en el centro
+                let back = facing.vector();
+                // Mitad de la celda hacia `side` (±1 en un eje, 0 en los otros)
+                let half = |side: Vector3, top: f32| {
+                    (
+                        Vector3::new(side.x.max(0.0), 0.0, side.z.max(0.0)) * 0.5
+                            - Vector3::new(0.5, 0.5, 0.5),
+                        Vector3::new(side.x.min(0.0) + 1.0, 0.0, side.z.min(0.0) + 1.0) * 0.5
+                            + Vector3::new(0.0, top, 0.0),
+                    )
+                };
+                let ((back_min, back_max), (front_min, front_max)) =
+                    (half(back, 0.5), half(-back, 0.0));
+                let mut boxes = ShapeBoxes::one(back_min, back_max);
+                boxes.push(front_min, front_max);
+                boxes
+            }
+            // Para ocupar lugar en la celda cuenta la caja que envuelve las
+            // láminas; el rayo las corta aparte (ver `plant::intersect_cross`)
+            BlockShape::CrossPlant(plant) => ShapeBoxes::one(
+                Vector3::new(-0.5, -0.5, -0.5),
+                Vector3::new(0.5, plant.height() - 0.5, 0.5),
+            ),
+            BlockShape::Pane { axis } => {
+                // Celda entera salvo en el eje del panel
+                let thin = Vector3::one() * 0.5 - axis.vector() * (0.5 - PANE_THICKNESS * 0.5);
+                ShapeBoxes::one(-thin, thin)
+            }
+            BlockShape::SnowLayer { layers } => {
+                let height = layers.clamp(1, MAX_SNOW_LAYERS) as f32 / MAX_SNOW_LAYERS as f32;
+                ShapeBoxes::one(
+                    Vector3::new(-0.5, -0.5, -0.5),
+                    Vector3::new(0.5, height - 0.5, 0.5),
+                )
+            }
+            BlockShape::Fence { rails } => {
+                let post = FENCE_POST_WIDTH * 0.5;
+                let mut boxes = ShapeBoxes::one(
+                    Vector3::new(-post, -0.5, -post),
+                    Vector3::new(post, 0.5, post),
+                );
+                for (i, direction) in Direction::ALL.into_iter().enumerate() {
+                    if rails & (1 << i) == 0 {
+                        continue;
+                    }
+                    // Del borde del poste al borde de la celda, angosto en
+                    // el eje de costado
+                    let along = direction.vector();
+                    let side =
+                        Vector3::new(along.z.abs(), 0.0, along.x.abs()) * (FENCE_RAIL_WIDTH * 0.5);
+                    let (a, b) = (
+                        along * post - side + Vector3::new(0.0, FENCE_RAIL_BOTTOM, 0.0),
+                        along * 0.5 + side + Vector3::new(0.0, FENCE_RAIL_TOP, 0.0),
+                    );
+                    boxes.push(
+                        Vector3::new(a.x.min(b.x), a.y, a.z.min(b.z)),
+                        Vector3::new(a.x.max(b.x), b.y, a.z.max(b.z)),
+                    );
+                }
+                boxes
+            }
+            BlockShape::Chest { open } => {
+                let half = CHEST_HALF_WIDTH;
+                let mut boxes = ShapeBoxes::one(
+                    Vector3::new(-half, -0.5, -half),
+                    Vector3::new(half, CHEST_LID_BOTTOM, half),
+                );
+                // Pestillo: sobresale del frente hasta el borde de la celda
+                let latch = CHEST_LATCH_HALF_WIDTH;
+                let latch_bottom = CHEST_LID_BOTTOM - CHEST_LATCH_HEIGHT * 0.5;
+                boxes.push(
+                    Vector3::new(-latch, latch_bottom, -0.5),
+                    Vector3::new(latch, latch_bottom + CHEST_LATCH_HEIGHT, -half),
+                );
+                if !open {
+                    let (min, max) = Self::chest_lid();
+                    boxes.push(min, max);
+                }
+                boxes
+            }
+        }
+    }
+
+    /// Tapa del cofre cerrada (mínimo, máximo)
+    fn chest_lid() -> (Vector3, Vector3) {
+        let half = CHEST_HALF_WIDTH;
+        (
+            Vector3::new(-half, CHEST_LID_BOTTOM, -half),
+            Vector3::new(half, CHEST_LID_BOTTOM + CHEST_LID_THICKNESS, half),
+        )
+    }
+
+    /// Parte girada de la figura, fuera de `boxes`: la tapa de un cofre
+    /// abierto, con la bisagra en el borde trasero de arriba
+    pub fn hinged_lid(self) -> Option<HingedBox> {
+        match self {
+            BlockShape::Chest { open: true } => {
+                let (min, max) = Self::chest_lid();
+                Some(HingedBox {
+                    min,
+                    max,
+                    hinge: Vector3::new(0.0, max.y, max.z),
+                    angle: CHEST_LID_OPEN_ANGLE,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Caja (mínimo, máximo) que envuelve toda la forma en una celda de
+    /// lado 1 centrada en el origen (la tapa abierta de un cofre puede
+    /// salir de la celda)
+    pub fn bounds(self) -> (Vector3, Vector3) {
+        let union = |(min, max): (Vector3, Vector3), (b_min, b_max): (Vector3, Vector3)| {
+            (
+                Vector3::new(min.x.min(b_min.x), min.y.min(b_min.y), min.z.min(b_min.z)),
+                Vector3::new(max.x.max(b_max.x), max.y.max(b_max.y), max.z.max(b_max.z)),
+            )
+        };
+        let boxes = self.boxes();
+        let bounds = boxes.iter().skip(1).copied().fold(boxes[0], union);
+        match self.hinged_lid() {
+            Some(lid) => union(bounds, lid.bounds()),
+            None => bounds,
+        }
+    }
+
+    /// Si dos formas de la misma celda se pisan (dos losas, una arriba y
+    /// otra abajo, pueden compartirla)
+    pub fn overlaps(self, other: BlockShape) -> bool {
+        let others = other.boxes();
+        self.boxes().iter().any(|(a_min, a_max)| {
+            others.iter().any(|(b_min, b_max)| {
+                a_min.x < b_max.x
+                    && b_min.x < a_max.x
+                    && a_min.y < b_max.y
+                    && b_min.y < a_max.y
+                    && a_min.z < b_max.z
+                    && b_min.z < a_max.z
+            })
+        })
+    }
+
+    /// Si la cara de normal `normal`, en el borde de la celda, coincide
+    /// entera con la cara opuesta de `other` en la celda vecina: dos cubos,
+    /// o dos paneles paralelos que siguen en esa dirección
+    pub fn joins(self, other: BlockShape, normal: &Vector3) -> bool {
+        match (self, other) {
+            (BlockShape::Cube, BlockShape::Cube) => true,
+            (BlockShape::Pane { axis }, BlockShape::Pane { axis: other_axis }) => {
+                axis == other_axis && axis.vector().dot(*normal).abs() < 0.5
+            }
+            _ => false,
+        }
+    }
+
+    /// Variante de esta forma para un bloque colocado contra la cara de
+    /// normal `normal` de un vecino: la antorcha se apoya en esa cara y la
+    /// losa va arriba si se coloca desde abajo. La escalera sube en la
+    /// dirección `look` en que mira la cámara, el panel queda de frente a
+    /// ella y las plantas y la nieve solo van sobre un piso (la nieve, de a
+    /// una capa). None si no se puede colocar.
+    pub fn placed_against(self, normal: &Vector3, look: &Vector3) -> Option<BlockShape> {
+        match self {
+            BlockShape::Cube => Some(BlockShape::Cube),
+            BlockShape::Torch(_) => Attachment::from_face_normal(normal).map(BlockShape::Torch),
+            BlockShape::Slab { .. } => Some(BlockShape::Slab {
+                top: normal.y < -0.5,
+            }),
+            BlockShape::Stair { .. } => Some(BlockShape::Stair {
+                facing: Direction::from_horizontal(look),
+            }),
+            BlockShape::CrossPlant(_) => (normal.y > 0.5).then_some(self),
+            BlockShape::Pane { .. } => Some(BlockShape::Pane {
+                axis: Axis::from_horizontal(look),
+            }),
+            BlockShape::SnowLayer { .. } => {
+                (normal.y > 0.5).then_some(BlockShape::SnowLayer { layers: 1 })
+            }
+            // Los travesaños salen de los vecinos al agregarla
+            BlockShape::Fence { .. } => Some(BlockShape::Fence { rails: 0 }),
+            BlockShape::Chest { .. } => Some(BlockShape::Chest { open: false }),
+        }
+    }
+
+    /// Punto de donde sale la luz de un bloque emisivo con esta forma, en
+    /// las mismas coordenadas que `bounds`: el centro del cubo o la punta
+    /// de la antorcha
+    pub fn light_anchor(self) -> Vector3 {
+        match self {
+            BlockShape::Cube
+            | BlockShape::Stair { .. }
+            | BlockShape::CrossPlant(_)
+            | BlockShape::Pane { .. }
+            | BlockShape::SnowLayer { .. }
+            | BlockShape::Fence { .. }
+            | BlockShape::Chest { .. } => Vector3::zero(),
+            BlockShape::Slab { top } => Vector3::new(0.0, if top { 0.25 } else { -0.25 }, 0.0),
+            BlockShape::Torch(_) => {
+                let (min, max) = self.bounds();
+                Vector3::new((min.x + max.x) * 0.5, max.y, (min.z + max.z) * 0.5)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+    use crate::block_types::BlockType;
+    use crate::ray_intersect::{Intersect, RayIntersect};
+
+    const UP: Vector3 = Vector3::new(0.0, 1.0, 0.0);
+
+    fn stair(facing: Direction) -> Block {
+        Block::new_cubic(Vector3::zero(), 1.0, BlockType::Cobble.material())
+            .with_shape(BlockShape::Stair { facing })
+    }
+
+    /// Impacto esperado: distancia y normal
+    fn assert_hit(hit: &Intersect, distance: f32, normal: Vector3, what: &str) {
+        assert!(hit.is_intersecting, "{}: sin impacto", what);
+        assert!(
+            (hit.distance - distance).abs() < 1e-4,
+            "{}: distancia {} en vez de {}",
+            what,
+            hit.distance,
+            distance
+        );
+        assert!(
+            (hit.normal - normal).length() < 1e-4,
+            "{}: normal {:?} en vez de {:?}",
+            what,
+            hit.normal,
+            normal
+        );
+    }
+
+    #[test]
+    fn stair_surfaces_for_every_facing() {
+        for facing in Direction::ALL {
+            let block = stair(facing);
+            let back = facing.vector();
+            let name = facing.name();
+
+            // Huella del escalón (mitad delantera, arriba en y = 0) y la
+            // parte alta de atrás (y = 0.5)
+            let hit = block.ray_intersect(&(-back * 0.25 + UP * 3.0), &-UP);
+            assert_hit(&hit, 3.0, UP, &format!("{} huella", name));
+            let hit = block.ray_intersect(&(back * 0.25 + UP * 3.0), &-UP);
+            assert_hit(&hit, 2.5, UP, &format!("{} parte alta", name));
+
+            // Contrahuella en el centro de la celda y frente de la mitad de
+            // abajo; la V sigue a la del bloque entero
+            let hit = block.ray_intersect(&(-back * 3.0 + UP * 0.25), &back);
+            assert_hit(&hit, 3.0, -back, &format!("{} contrahuella", name));
+            assert!((hit.v - 0.25).abs() < 1e-4, "{} v {}", name, hit.v);
+            let hit = block.ray_intersect(&(-back * 3.0 - UP * 0.25), &back);
+            assert_hit(&hit, 2.5, -back, &format!("{} frente de abajo", name));
+            assert!((hit.v - 0.75).abs() < 1e-4, "{} v {}", name, hit.v);
+        }
+    }
+
+    #[test]
+    fn inner_corner_hits_the_right_sub_surface() {
+        for facing in Direction::ALL {
+            let block = stair(facing);
+            let back = facing.vector();
+            let dir = (back - UP).normalized();
+            let name = facing.name();
+
+            // Baja en diagonal hacia el rincón: entra por encima de la
+            // huella y toca la contrahuella a y = 0.1...
+            let hit = block.ray_intersect(&(-back * 0.3 + UP * 0.4), &dir);
+            assert_hit(
+                &hit,
+                0.3 * 2f32.sqrt(),
+                -back,
+                &format!("{} rincón alto", name),
+            );
+            // ...o cae en la huella a 0.1 de la contrahuella
+            let hit = block.ray_intersect(&(-back * 0.4 + UP * 0.3), &dir);
+            assert_hit(
+                &hit,
+                0.3 * 2f32.sqrt(),
+                UP,
+                &format!("{} rincón bajo", name),
+            );
+        }
+    }
+}
@@ -1,9 +1,27 @@
-use crate::block::Block;
-use crate::material::Material;
+use std::mem::discriminant;
+
+use crate::beacon::BEACON_SPOT_ANGLE;
+use crate::block::{Block, Face};
+use crate::block_shape::{Attachment, Axis, BlockShape, Direction};
+use crate::chest::{CHEST_FRONT_TEXTURE, CHEST_SIDE_TEXTURE, CHEST_TOP_TEXTURE};
+use crate::light::Spot;
+use crate::material::{Material, MaterialWarning};
+use crate::ore::Specks;
+use crate::orientation::Orientation;
+use crate::plant::Plant;
+use crate::textures::TextureManager;
+use crate::tnt::{TNT_SIDE_TEXTURE, TNT_TOP_TEXTURE};
 use raylib::prelude::*;
 
+/// Textura procedural de las tapas del tronco
+const LOG_RINGS_TEXTURE: &str = "procedural/log_rings";
+
+/// Lado en texels de la textura de anillos
+const LOG_RINGS_SIZE: u32 = 16;
+
 /// Enum que define los tipos de bloques disponibles
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlockType {
     Grass,
     Dirt,
@@ -17,9 +35,131 @@ pub enum BlockType {
     CherryLeaves,
     Sun,
     Magma,
+    Prism,
+    Water,
+    Lava,
+    Torch,
+    StoneSlab,
+    WoodPlankSlab,
+    CobbleStairs,
+    WoodStairs,
+    Flower,
+    TallGrass,
+    GlassPane,
+    Ice,
+    Tnt,
+    Snow,
+    WoodFence,
+    DiamondOre,
+    RedstoneOre,
+    Beacon,
+    Chest,
+    WarpedGlass,
 }
 
 impl BlockType {
+    /// Nombre para mostrar en la interfaz
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BlockType::Grass => "Pasto",
+            BlockType::Dirt => "Tierra",
+            BlockType::Stone => "Piedra",
+            BlockType::Cobble => "Adoquín",
+            BlockType::WoodLog => "Tronco",
+            BlockType::Leaves => "Hojas",
+            BlockType::Sand => "Arena",
+            BlockType::Glass => "Vidrio",
+            BlockType::Reflect => "Espejo",
+            BlockType::CherryLeaves => "Hojas de cerezo",
+            BlockType::Sun => "Sol",
+            BlockType::Magma => "Magma",
+            BlockType::Prism => "Prisma",
+            BlockType::Water => "Agua",
+            BlockType::Lava => "Lava",
+            BlockType::Torch => "Antorcha",
+            BlockType::StoneSlab => "Losa de piedra",
+            BlockType::WoodPlankSlab => "Losa de madera",
+            BlockType::CobbleStairs => "Escalera de adoquín",
+            BlockType::WoodStairs => "Escalera de madera",
+            BlockType::Flower => "Flor",
+            BlockType::TallGrass => "Pasto alto",
+            BlockType::GlassPane => "Panel de vidrio",
+            BlockType::Ice => "Hielo",
+            BlockType::Tnt => "TNT",
+            BlockType::Snow => "Nieve",
+            BlockType::WoodFence => "Cerca de madera",
+            BlockType::DiamondOre => "Mineral de diamante",
+            BlockType::RedstoneOre => "Mineral de redstone",
+            BlockType::Beacon => "Faro",
+            BlockType::Chest => "Cofre",
+            BlockType::WarpedGlass => "Vidrio deformado",
+        }
+    }
+
+    /// Nombre estable en snake_case para archivos, consola y paletas. El
+    /// match es exhaustivo: un tipo nuevo no compila sin su nombre.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockType::Grass => "grass",
+            BlockType::Dirt => "dirt",
+            BlockType::Stone => "stone",
+            BlockType::Cobble => "cobble",
+            BlockType::WoodLog => "wood_log",
+            BlockType::Leaves => "leaves",
+            BlockType::Sand => "sand",
+            BlockType::Glass => "glass",
+            BlockType::Reflect => "reflect",
+            BlockType::CherryLeaves => "cherry_leaves",
+            BlockType::Sun => "sun",
+            BlockType::Magma => "magma",
+            BlockType::Prism => "prism",
+            BlockType::Water => "water",
+            BlockType::Lava => "lava",
+            BlockType::Torch => "torch",
+            BlockType::StoneSlab => "stone_slab",
+            BlockType::WoodPlankSlab => "wood_plank_slab",
+            BlockType::CobbleStairs => "cobble_stairs",
+            BlockType::WoodStairs => "wood_stairs",
+            BlockType::Flower => "flower",
+            BlockType::TallGrass => "tall_grass",
+            BlockType::GlassPane => "glass_pane",
+            BlockType::Ice => "ice",
+            BlockType::Tnt => "tnt",
+            BlockType::Snow => "snow",
+            BlockType::WoodFence => "wood_fence",
+            BlockType::DiamondOre => "diamond_ore",
+            BlockType::RedstoneOre => "redstone_ore",
+            BlockType::Beacon => "beacon",
+            BlockType::Chest => "chest",
+            BlockType::WarpedGlass => "warped_glass",
+        }
+    }
+
+    /// Tipo a partir de su nombre, su nombre para mostrar o un alias. No
+    /// distingue mayúsculas ni separadores: "wood_log", "WoodLog" y
+    /// "wood log" son el mismo.
+    pub fn from_name(name: &str) -> Option<BlockType> {
+        let wanted = normalize_name(name);
+        Self::all()
+            .iter()
+            .find(|block_type| {
+                normalize_name(block_type.name()) == wanted
+                    || normalize_name(block_type.display_name()) == wanted
+            })
+            .or_else(|| {
+                BLOCK_ALIASES
+                    .iter()
+                    .find(|(alias, _)| normalize_name(alias) == wanted)
+                    .map(|(_, block_type)| block_type)
+            })
+            .cloned()
+    }
+
+    /// Todos los tipos, en el orden de `ALL_BLOCK_TYPES`
+    pub fn all() -> &'static [BlockType] {
+        &ALL_BLOCK_TYPES
+    }
+
     /// Devuelve el material asociado a cada tipo de bloque
     pub fn material(&self) -> Material {
         match self {
@@ -34,6 +174,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Dirt => Material {
                 diffuse: Vector3::new(0.4, 0.3, 0.2),
@@ -46,6 +198,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Stone => Material {
                 diffuse: Vector3::new(0.5, 0.5, 0.5),
@@ -58,6 +222,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Cobble => Material {
                 diffuse: Vector3::new(0.6, 0.6, 0.6),
@@ -70,6 +246,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::WoodLog => Material {
                 diffuse: Vector3::new(0.4, 0.3, 0.1),
@@ -82,6 +270,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Leaves => Material {
                 diffuse: Vector3::new(0.2, 0.6, 0.2),
@@ -94,6 +294,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.4,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::CherryLeaves => Material {
                 diffuse: Vector3::new(0.98, 0.88, 0.94),
@@ -106,6 +318,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.4,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Sand => Material {
                 diffuse: Vector3::new(0.96, 0.87, 0.7),
@@ -118,6 +342,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Glass => Material {
                 diffuse: Vector3::new(0.9, 0.9, 1.0),
@@ -130,6 +366,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: true,
+                fresnel_reflection: false,
             },
             BlockType::Reflect => Material {
                 diffuse: Vector3::new(0.9, 0.9, 0.95),
@@ -142,6 +390,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: None,
                 emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Sun => Material {
                 diffuse: Vector3::new(1.0, 0.9, 0.6),
@@ -154,6 +414,18 @@ impl BlockType {
                 normal_map_id: None,
                 emission_color: Some(Vector3::new(1.0, 0.9, 0.9)),
                 emission_strength: 10.0,
+                translucency: 0.0,
+                unlit: true,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
             },
             BlockType::Magma => Material {
                 diffuse: Vector3::new(0.7, 0.28, 0.1),
@@ -166,28 +438,729 @@ impl BlockType {
                 normal_map_id: None,                  
                 emission_color: Some(Vector3::new(0.75, 0.32, 0.12)), 
                 emission_strength: 2.0,               
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            BlockType::Prism => Material {
+                diffuse: Vector3::new(0.95, 0.97, 1.0),
+                albedo: [0.1, 0.9],
+                specular: 300.0,
+                reflectivity: 0.1,
+                transparency: 0.85,
+                refractive_index: 1.5,
+                texture: None,
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                // Leve separación por canal: bordes con franjas de color
+                refractive_index_rgb: Some([1.47, 1.50, 1.55]),
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            // Sin textura: el color sale del fondo teñido por la absorción.
+            // La reflectividad la da Fresnel (ver trace_ray_multi_light)
+            BlockType::Water => Material {
+                diffuse: Vector3::new(0.12, 0.4, 0.5),
+                albedo: [0.3, 0.7],
+                specular: 150.0,
+                reflectivity: 0.0,
+                transparency: 0.85,
+                refractive_index: 1.33,
+                texture: None,
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                // Se come el rojo primero: el fondo se ve azul verdoso
+                absorption: Vector3::new(0.6, 0.15, 0.1),
+                waves: 0.08,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: true,
+                fresnel_reflection: true,
+            },
+            // Unlit: solo emisión. La textura la enmascara, así la costra
+            // oscura del magma no brilla
+            BlockType::Lava => Material {
+                diffuse: Vector3::new(1.0, 0.35, 0.05),
+                albedo: [0.0, 0.0],
+                specular: 0.0,
+                reflectivity: 0.0,
+                transparency: 0.0,
+                refractive_index: 1.0,
+                texture: Some("textures/magma.png".to_string()),
+                normal_map_id: None,
+                emission_color: Some(Vector3::new(1.0, 0.35, 0.05)),
+                emission_strength: 2.5,
+                translucency: 0.0,
+                unlit: true,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            BlockType::StoneSlab => BlockType::Stone.material(),
+            BlockType::DiamondOre => ore_material(Vector3::new(0.35, 0.9, 0.85), 0.2),
+            BlockType::RedstoneOre => ore_material(Vector3::new(0.9, 0.08, 0.05), 0.6),
+            BlockType::Beacon => Material {
+                diffuse: Vector3::new(0.55, 0.95, 1.0),
+                albedo: [0.6, 0.4],
+                specular: 80.0,
+                reflectivity: 0.1,
+                transparency: 0.0,
+                refractive_index: 1.0,
+                texture: None,
+                normal_map_id: None,
+                emission_color: Some(Vector3::new(0.55, 0.95, 1.0)),
+                emission_strength: 1.5,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            // Madera: el color lo trae la textura (la tapa y el frente van
+            // como caras propias, ver `to_block`)
+            BlockType::Chest => Material {
+                diffuse: Vector3::one(),
+                albedo: [0.85, 0.15],
+                specular: 10.0,
+                reflectivity: 0.0,
+                transparency: 0.0,
+                refractive_index: 1.0,
+                texture: Some(CHEST_SIDE_TEXTURE.to_string()),
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            BlockType::CobbleStairs => BlockType::Cobble.material(),
+            BlockType::WoodStairs | BlockType::WoodFence => BlockType::WoodPlankSlab.material(),
+            // Vidrio con índice casi 1: un panel fino no deforma lo de atrás
+            // Hielo: refracta menos que el vidrio, absorbe un poco de rojo
+            // (se ve celeste en lo profundo), refleja borroso y tiene
+            // escarcha en los bordes
+            BlockType::Ice => Material {
+                diffuse: Vector3::new(0.78, 0.9, 1.0),
+                albedo: [0.5, 0.5],
+                specular: 300.0,
+                reflectivity: 0.25,
+                transparency: 0.6,
+                refractive_index: 1.31,
+                texture: None,
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::new(0.3, 0.1, 0.02),
+                waves: 0.0,
+                roughness: 0.15,
+                frost: 0.7,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            BlockType::GlassPane => {
+                let mut material = BlockType::Glass.material();
+                material.refractive_index = 1.02;
+                material
+            }
+            // Vidrio deformado: lo de atrás se curva hacia los bordes y se
+            // separa en colores (el vidrio común queda limpio)
+            BlockType::WarpedGlass => {
+                let mut material = BlockType::Glass.material();
+                material.aberration_strength = 1.0;
+                material.distortion_strength = 1.0;
+                material
+            }
+            // Plantas: la textura procedural trae el color (la flor) o solo
+            // el sombreado que tiñe el difuso (el pasto)
+            BlockType::Flower | BlockType::TallGrass => {
+                let (plant, diffuse) = match self {
+                    BlockType::Flower => (Plant::Flower, Vector3::one()),
+                    _ => (Plant::TallGrass, Vector3::new(0.4, 0.75, 0.3)),
+                };
+                Material {
+                    diffuse,
+                    albedo: [0.9, 0.1],
+                    specular: 3.0,
+                    reflectivity: 0.0,
+                    transparency: 0.0,
+                    refractive_index: 1.0,
+                    texture: Some(plant.texture_name().to_string()),
+                    normal_map_id: None,
+                    emission_color: None,
+                    emission_strength: 0.0,
+                    translucency: 0.3,
+                    unlit: false,
+                    refractive_index_rgb: None,
+                    absorption: Vector3::zero(),
+                    waves: 0.0,
+                    roughness: 0.0,
+                    frost: 0.0,
+                    specks: None,
+                    aberration_strength: 0.0,
+                    distortion_strength: 0.0,
+                    caustics: false,
+                    fresnel_reflection: false,
+                }
+            }
+            // TNT: la textura trae el rojo y la franja blanca
+            BlockType::Tnt => Material {
+                diffuse: Vector3::one(),
+                albedo: [0.9, 0.1],
+                specular: 5.0,
+                reflectivity: 0.0,
+                transparency: 0.0,
+                refractive_index: 1.0,
+                texture: Some(TNT_SIDE_TEXTURE.to_string()),
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            // Nieve: casi todo difuso y muy claro, apenas azulada
+            BlockType::Snow => Material {
+                diffuse: Vector3::new(0.95, 0.97, 1.0),
+                albedo: [0.95, 0.05],
+                specular: 10.0,
+                reflectivity: 0.0,
+                transparency: 0.0,
+                refractive_index: 1.0,
+                texture: None,
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            // Tablones: sin textura propia, color de madera clara
+            BlockType::WoodPlankSlab => Material {
+                diffuse: Vector3::new(0.72, 0.55, 0.33),
+                albedo: [0.9, 0.1],
+                specular: 8.0,
+                reflectivity: 0.0,
+                transparency: 0.0,
+                refractive_index: 1.0,
+                texture: None,
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+            // El palo; la llama va como material de la cara de arriba
+            BlockType::Torch => Material {
+                diffuse: Vector3::new(0.42, 0.28, 0.14),
+                albedo: [0.9, 0.1],
+                specular: 5.0,
+                reflectivity: 0.0,
+                transparency: 0.0,
+                refractive_index: 1.0,
+                texture: None,
+                normal_map_id: None,
+                emission_color: None,
+                emission_strength: 0.0,
+                translucency: 0.0,
+                unlit: false,
+                refractive_index_rgb: None,
+                absorption: Vector3::zero(),
+                waves: 0.0,
+                roughness: 0.0,
+                frost: 0.0,
+                specks: None,
+                aberration_strength: 0.0,
+                distortion_strength: 0.0,
+                caustics: false,
+                fresnel_reflection: false,
+            },
+        }
+    }
+
+    /// Tapas del tronco: los anillos en lugar de la corteza
+    fn log_rings() -> Material {
+        let mut rings = BlockType::WoodLog.material();
+        rings.diffuse = Vector3::one();
+        rings.texture = Some(LOG_RINGS_TEXTURE.to_string());
+        rings
+    }
+
+    /// Llama de la punta de la antorcha: sin sombreado, solo emisión
+    fn torch_flame() -> Material {
+        let mut flame = Material::new_emissive(
+            Vector3::new(1.0, 0.75, 0.35),
+            [0.0, 0.0],
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            None,
+            None,
+            Some(Vector3::new(1.0, 0.75, 0.35)),
+            3.0,
+        );
+        flame.unlit = true;
+        flame
+    }
+
+    /// Deduce el tipo de un bloque de la escena a partir de su forma y su
+    /// material: misma textura y color base o, si ninguno coincide en ambos
+    /// (color editado), la misma textura. None si el material no corresponde a
+    /// ningún tipo (por ejemplo, editado a mano sin textura).
+    pub fn identify(block: &Block) -> Option<BlockType> {
+        let material = &block.material;
+        let same_texture = |reference: &Material| reference.texture == material.texture;
+        // La variante no importa (una losa arriba sigue siendo una losa)
+        let candidates = ALL_BLOCK_TYPES.into_iter().filter(|block_type| {
+            discriminant(&block_type.default_shape()) == discriminant(&block.shape)
+        });
+        candidates
+            .clone()
+            .find(|block_type| {
+                let reference = block_type.material();
+                same_texture(&reference)
+                    && reference.diffuse == material.diffuse
+                    && reference.specks == material.specks
+                    && reference.aberration_strength == material.aberration_strength
+                    && reference.distortion_strength == material.distortion_strength
+            })
+            .or_else(|| {
+                candidates.clone().find(|block_type| {
+                    material.texture.is_some() && same_texture(&block_type.material())
+                })
+            })
+    }
+
+    /// Forma con la que se crea el bloque (otra variante, ej: la antorcha
+    /// en una pared, se elige después con `Block::with_shape`)
+    pub fn default_shape(&self) -> BlockShape {
+        match self {
+            BlockType::Torch => BlockShape::Torch(Attachment::Floor),
+            BlockType::StoneSlab | BlockType::WoodPlankSlab => BlockShape::Slab { top: false },
+            BlockType::CobbleStairs | BlockType::WoodStairs => BlockShape::Stair {
+                facing: Direction::North,
             },
+            BlockType::Flower => BlockShape::CrossPlant(Plant::Flower),
+            BlockType::TallGrass => BlockShape::CrossPlant(Plant::TallGrass),
+            BlockType::GlassPane => BlockShape::Pane { axis: Axis::Z },
+            BlockType::Snow => BlockShape::SnowLayer { layers: 1 },
+            BlockType::WoodFence => BlockShape::Fence { rails: 0 },
+            BlockType::Chest => BlockShape::Chest { open: false },
+            _ => BlockShape::Cube,
+        }
+    }
+
+    /// Orientación al colocarlo contra la cara de normal `normal`: el tronco
+    /// queda a lo largo de ella (acostado si se clickea una pared) y el
+    /// cofre gira con el frente hacia la cámara, que mira en la dirección
+    /// `look`; el resto no gira
+    pub fn placed_orientation(&self, normal: &Vector3, look: &Vector3) -> Orientation {
+        match self {
+            BlockType::WoodLog => Orientation::new(Face::from_normal(normal), 0),
+            // Cuartos de vuelta que llevan el norte local a cada dirección
+            BlockType::Chest => match Direction::from_horizontal(&-*look) {
+                Direction::North => Orientation::UPRIGHT,
+                Direction::West => Orientation::new(Face::Top, 1),
+                Direction::South => Orientation::new(Face::Top, 2),
+                Direction::East => Orientation::new(Face::Top, 3),
+            },
+            _ => Orientation::UPRIGHT,
         }
     }
 
     /// Crea un bloque de este tipo en una posición dada
     pub fn to_block(&self, position: Vector3, size: f32) -> Block {
-        match self {
+        let block = match self {
             BlockType::Sun => Block::new_emissive(
                 position,
-                size,
+                Vector3::one() * size,
                 self.material(),
                 Vector3::new(1.0, 0.9, 0.9),
                 8.0,
             ),
             BlockType::Magma => Block::new_emissive(
                 position,
-                size,
+                Vector3::one() * size,
                 self.material(),
                 Vector3::new(0.75, 0.32, 0.12),
                 0.5,
             ),
-            _ => Block::new(position, size, self.material()),
+            BlockType::Lava => {
+                let mut block = Block::new_emissive(
+                    position,
+                    Vector3::one() * size,
+                    self.material(),
+                    Vector3::new(1.0, 0.4, 0.1),
+                    2.0,
+                );
+                // Luz medio bloque sobre la superficie: desde el centro no
+                // iluminaría el piso que rodea una pileta hundida
+                if let Some(light) = block.emission.as_mut() {
+                    light.position.y += size;
+                }
+                block
+            }
+            // Luz cálida en la punta que alcanza poco: ilumina un cuarto,
+            // no la isla entera
+            BlockType::Torch => {
+                let mut block = Block::new_emissive(
+                    position,
+                    Vector3::one() * size,
+                    self.material(),
+                    Vector3::new(1.0, 0.7, 0.4),
+                    8.0,
+                )
+                .with_face_material(Face::Top, Self::torch_flame());
+                if let Some(light) = block.emission.as_mut() {
+                    light.attenuation = Some(0.8);
+                }
+                block
+            }
+            // Foco fuerte hacia arriba desde la tapa; el haz visible lo
+            // agrega el trazado (ver beacon.rs)
+            BlockType::Beacon => {
+                let mut block = Block::new_emissive(
+                    position,
+                    Vector3::one() * size,
+                    self.material(),
+                    Vector3::new(0.55, 0.95, 1.0),
+                    12.0,
+                );
+                if let Some(light) = block.emission.as_mut() {
+                    light.position.y += size * 0.6;
+                    light.spot = Some(Spot::new(Vector3::new(0.0, 1.0, 0.0), BEACON_SPOT_ANGLE));
+                }
+                block
+            }
+            BlockType::Chest => {
+                let mut top = self.material();
+                top.texture = Some(CHEST_TOP_TEXTURE.to_string());
+                let mut front = self.material();
+                front.texture = Some(CHEST_FRONT_TEXTURE.to_string());
+                Block::new_cubic(position, size, self.material())
+                    .with_face_material(Face::Top, top)
+                    .with_face_material(Face::North, front)
+            }
+            // Anillos arriba y abajo; al acostarlo quedan hacia los costados
+            BlockType::WoodLog => Block::new_cubic(position, size, self.material())
+                .with_face_material(Face::Top, Self::log_rings())
+                .with_face_material(Face::Bottom, Self::log_rings()),
+            BlockType::Tnt => {
+                let mut top = self.material();
+                top.texture = Some(TNT_TOP_TEXTURE.to_string());
+                Block::new_cubic(position, size, self.material())
+                    .with_face_material(Face::Top, top.clone())
+                    .with_face_material(Face::Bottom, top)
+            }
+            _ => Block::new_cubic(position, size, self.material()),
+        };
+        block.with_shape(self.default_shape())
+    }
+}
+
+/// Piedra con vetas de mineral del color `speck_color` que brillan con
+/// `glow` (0.0 = solo se ven con luz). Base de los minerales y de los que se
+/// definan en `blocks.txt` con la directiva `specks`.
+pub fn ore_material(speck_color: Vector3, glow: f32) -> Material {
+    let mut material = BlockType::Stone.material();
+    material.specks = Some(Specks::new(speck_color, glow));
+    material
+}
+
+/// Otros nombres que acepta `BlockType::from_name`
+const BLOCK_ALIASES: &[(&str, BlockType)] = &[
+    ("wood", BlockType::WoodPlankSlab),
+    ("planks", BlockType::WoodPlankSlab),
+    ("log", BlockType::WoodLog),
+    ("cobblestone", BlockType::Cobble),
+    ("mirror", BlockType::Reflect),
+    ("stairs", BlockType::CobbleStairs),
+    ("pane", BlockType::GlassPane),
+    ("fence", BlockType::WoodFence),
+];
+
+/// Minúsculas y sin separadores, para comparar nombres
+pub(crate) fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Todos los tipos de bloque
+pub const ALL_BLOCK_TYPES: [BlockType; 32] = [
+    BlockType::Grass,
+    BlockType::Dirt,
+    BlockType::Stone,
+    BlockType::Cobble,
+    BlockType::WoodLog,
+    BlockType::Leaves,
+    BlockType::Sand,
+    BlockType::Glass,
+    BlockType::Reflect,
+    BlockType::CherryLeaves,
+    BlockType::Sun,
+    BlockType::Magma,
+    BlockType::Prism,
+    BlockType::Water,
+    BlockType::Lava,
+    BlockType::Torch,
+    BlockType::StoneSlab,
+    BlockType::WoodPlankSlab,
+    BlockType::CobbleStairs,
+    BlockType::WoodStairs,
+    BlockType::Flower,
+    BlockType::TallGrass,
+    BlockType::GlassPane,
+    BlockType::Ice,
+    BlockType::Tnt,
+    BlockType::Snow,
+    BlockType::WoodFence,
+    BlockType::DiamondOre,
+    BlockType::RedstoneOre,
+    BlockType::Beacon,
+    BlockType::Chest,
+    BlockType::WarpedGlass,
+];
+
+/// Valida el material de cada tipo de bloque.
+/// Devuelve (tipo de bloque, advertencia) por cada problema encontrado.
+pub fn validate_block_materials() -> Vec<(BlockType, MaterialWarning)> {
+    ALL_BLOCK_TYPES
+        .into_iter()
+        .flat_map(|block_type| {
+            block_type
+                .material()
+                .validate()
+                .into_iter()
+                .map(move |warning| (block_type.clone(), warning))
+        })
+        .collect()
+}
+
+/// Registra la textura de anillos de las tapas del tronco: corteza en el
+/// borde y anillos cuadrados alternando tono hacia el centro
+pub fn insert_log_textures(tex_mgr: &mut TextureManager) {
+    let size = LOG_RINGS_SIZE;
+    tex_mgr.insert_procedural(LOG_RINGS_TEXTURE, size, size, |x, y| {
+        let center = (size as f32 - 1.0) * 0.5;
+        let ring = (x as f32 - center).abs().max((y as f32 - center).abs()) as u32;
+        if ring + 1 >= size / 2 {
+            Vector3::new(0.3, 0.2, 0.14)
+        } else if ring.is_multiple_of(2) {
+            Vector3::new(0.72, 0.52, 0.36)
+        } else {
+            Vector3::new(0.6, 0.42, 0.28)
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_materials_are_valid() {
+        let warnings = validate_block_materials();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    /// Posición de cada tipo en `ALL_BLOCK_TYPES`. El match es exhaustivo:
+    /// un tipo nuevo no compila los tests hasta que se agrega a la lista
+    fn listed_position(block_type: &BlockType) -> usize {
+        match block_type {
+            BlockType::Grass => 0,
+            BlockType::Dirt => 1,
+            BlockType::Stone => 2,
+            BlockType::Cobble => 3,
+            BlockType::WoodLog => 4,
+            BlockType::Leaves => 5,
+            BlockType::Sand => 6,
+            BlockType::Glass => 7,
+            BlockType::Reflect => 8,
+            BlockType::CherryLeaves => 9,
+            BlockType::Sun => 10,
+            BlockType::Magma => 11,
+            BlockType::Prism => 12,
+            BlockType::Water => 13,
+            BlockType::Lava => 14,
+            BlockType::Torch => 15,
+            BlockType::StoneSlab => 16,
+            BlockType::WoodPlankSlab => 17,
+            BlockType::CobbleStairs => 18,
+            BlockType::WoodStairs => 19,
+            BlockType::Flower => 20,
+            BlockType::TallGrass => 21,
+            BlockType::GlassPane => 22,
+            BlockType::Ice => 23,
+            BlockType::Tnt => 24,
+            BlockType::Snow => 25,
+            BlockType::WoodFence => 26,
+            BlockType::DiamondOre => 27,
+            BlockType::RedstoneOre => 28,
+            BlockType::Beacon => 29,
+            BlockType::Chest => 30,
+            BlockType::WarpedGlass => 31,
+        }
+    }
+
+    #[test]
+    fn every_block_type_round_trips_through_its_name() {
+        let all = BlockType::all();
+        for (i, block_type) in all.iter().enumerate() {
+            assert_eq!(
+                listed_position(block_type),
+                i,
+                "{:?} fuera de lugar",
+                block_type
+            );
+
+            let name = block_type.name();
+            assert_eq!(BlockType::from_name(name).as_ref(), Some(block_type));
+            assert_eq!(
+                BlockType::from_name(&name.to_uppercase()).as_ref(),
+                Some(block_type)
+            );
+            assert_eq!(
+                BlockType::from_name(block_type.display_name()).as_ref(),
+                Some(block_type)
+            );
+            // Los nombres no se repiten
+            assert!(
+                all[..i].iter().all(|other| other.name() != name),
+                "'{}' repetido",
+                name
+            );
+        }
+        assert_eq!(all.len(), listed_position(&BlockType::WarpedGlass) + 1);
+
+        assert_eq!(BlockType::from_name("wood"), Some(BlockType::WoodPlankSlab));
+        assert_eq!(BlockType::from_name("Wood Log"), Some(BlockType::WoodLog));
+        assert_eq!(BlockType::from_name("COBBLESTONE"), Some(BlockType::Cobble));
+        assert_eq!(BlockType::from_name("unobtainium"), None);
+    }
+
+    /// Los anillos del tronco quedan sobre las dos caras del eje hacia el
+    /// que apunta su arriba local, con cualquier giro alrededor de ese eje
+    #[test]
+    fn log_rings_follow_every_orientation() {
+        use crate::ray_intersect::RayIntersect;
+
+        let look = Vector3::new(0.0, 0.0, 1.0);
+        for up in Face::ALL {
+            assert_eq!(
+                BlockType::WoodLog.placed_orientation(&up.normal(), &look),
+                Orientation::new(up, 0)
+            );
+            for turns in 0..4 {
+                let block = BlockType::WoodLog
+                    .to_block(Vector3::zero(), 1.0)
+                    .with_orientation(Orientation::new(up, turns));
+                for face in Face::ALL {
+                    let normal = face.normal();
+                    // Un poco corrido del centro, lejos de las aristas
+                    let offset = Vector3::new(0.1, 0.2, 0.15);
+                    let offset = offset - normal * offset.dot(normal);
+                    let hit = block.ray_intersect(&(normal * 3.0 + offset), &-normal);
+                    assert!(hit.is_intersecting, "{:?} {} desde {:?}", up, turns, face);
+                    assert_eq!(hit.normal, normal);
+
+                    let texture = hit.material.unwrap().texture.as_deref();
+                    let on_axis = normal.dot(up.normal()).abs() > 0.5;
+                    assert_eq!(
+                        texture == Some(LOG_RINGS_TEXTURE),
+                        on_axis,
+                        "arriba {:?}, {} giros: cara {:?} con textura {:?}",
+                        up,
+                        turns,
+                        face,
+                        texture
+                    );
+                }
+            }
         }
     }
 }
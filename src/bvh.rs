@@ -0,0 +1,613 @@
+// bvh.rs - Jerarquía de cajas para lo que no entra en la grilla: bloques más
+// grandes que una celda o corridos, y las primitivas. Editar reajusta las
+// cajas en el lugar; cada tantas ediciones, o si las cajas se pisan
+// demasiado, el árbol se arma de nuevo.
+use std::collections::HashMap;
+
+use raylib::prelude::*;
+
+/// Caja (mínimo, máximo)
+pub type Aabb = (Vector3, Vector3);
+
+/// Objeto de la BVH: un bloque o una primitiva de la escena, por índice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BvhItem {
+    Block(u32),
+    Primitive(u32),
+}
+
+/// Reajustes y reconstrucciones del árbol, para la pantalla de depuración
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BvhCounters {
+    /// Ediciones resueltas corrigiendo las cajas del camino a la raíz
+    pub refits: u64,
+    /// Veces que se armó de cero
+    pub rebuilds: u64,
+}
+
+/// Ediciones seguidas que se reajustan antes de armar el árbol de nuevo
+pub const REBUILD_AFTER_EDITS: u32 = 32;
+
+/// Cuánto puede crecer el solapamiento promedio de las cajas hermanas
+/// (ver `Bvh::average_overlap`) desde la última reconstrucción
+pub const MAX_OVERLAP_GROWTH: f32 = 0.25;
+
+/// Objetos por hoja al armar el árbol; los reajustes pueden sumar más
+const LEAF_SIZE: usize = 2;
+
+/// Margen de las cajas, para que el redondeo no deje afuera un impacto justo
+/// en el borde
+const BOX_PADDING: f32 = 1e-3;
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Leaf(Vec<(BvhItem, Aabb)>),
+    /// Dos hijos y cuánto se pisan sus cajas
+    Inner([usize; 2], f32),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// None si la hoja quedó vacía
+    bounds: Option<Aabb>,
+    parent: Option<usize>,
+    kind: NodeKind,
+}
+
+/// BVH con la forma fija entre reconstrucciones: insertar suma el objeto a
+/// la hoja que menos crece, quitarlo lo saca de la suya, y en los dos casos
+/// se corrigen las cajas hasta la raíz. Los objetos sin caja (planos
+/// infinitos) van aparte y se visitan con todos los rayos.
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    /// La raíz es el primero
+    nodes: Vec<Node>,
+    /// Hoja de cada objeto
+    leaves: HashMap<BvhItem, usize>,
+    unbounded: Vec<BvhItem>,
+    /// Suma del solapamiento de los nodos internos
+    overlap_sum: f32,
+    inner_nodes: usize,
+    /// Solapamiento promedio al terminar la última reconstrucción
+    built_overlap: f32,
+    edits: u32,
+    counters: BvhCounters,
+}
+
+/// Dos árboles son iguales si tienen los mismos objetos con las mismas
+/// cajas, armados como sea
+impl PartialEq for Bvh {
+    fn eq(&self, other: &Self) -> bool {
+        let items = |bvh: &Bvh| {
+            let mut items: Vec<_> = bvh
+                .items()
+                .map(|(item, bounds)| (item, bounds.map(bits)))
+                .collect();
+            items.sort_by_key(|(item, _)| *item);
+            items
+        };
+        items(self) == items(other)
+    }
+}
+
+impl Bvh {
+    /// Arma el árbol con `items` (None = sin caja)
+    pub fn build(items: impl IntoIterator<Item = (BvhItem, Option<Aabb>)>) -> Self {
+        let mut bvh = Self::default();
+        let mut bounded = Vec::new();
+        for (item, bounds) in items {
+            match bounds {
+                Some(bounds) => bounded.push((item, pad(bounds))),
+                None => bvh.unbounded.push(item),
+            }
+        }
+        bvh.unbounded.sort_unstable();
+        bounded.sort_by_key(|(item, _)| *item);
+        if !bounded.is_empty() {
+            bvh.build_node(bounded, None);
+        }
+        bvh.built_overlap = bvh.average_overlap();
+        bvh
+    }
+
+    /// Arma el árbol de cero con los objetos que tiene, conservando los
+    /// contadores
+    pub fn rebuild(&mut self) {
+        let items: Vec<_> = self
+            .items()
+            .map(|(item, bounds)| (item, bounds.map(unpad)))
+            .collect();
+        self.rebuild_with(items);
+    }
+
+    /// Arma el árbol de cero con otros objetos, conservando los contadores
+    pub fn rebuild_with(&mut self, items: impl IntoIterator<Item = (BvhItem, Option<Aabb>)>) {
+        let counters = self.counters;
+        *self = Self::build(items);
+        self.counters = BvhCounters {
+            rebuilds: counters.rebuilds + 1,
+            ..counters
+        };
+    }
+
+    /// Suma un objeto a la hoja cuya caja menos crece
+    pub fn insert(&mut self, item: BvhItem, bounds: Option<Aabb>) {
+        self.place(item, bounds);
+        self.edited();
+    }
+
+    /// Saca un objeto; su hoja puede quedar vacía hasta la próxima
+    /// reconstrucción
+    pub fn remove(&mut self, item: BvhItem) {
+        self.take(item);
+        self.edited();
+    }
+
+    /// El objeto cambió de caja: se corrige en su hoja (o pasa a la lista sin
+    /// caja, o sale de ella)
+    pub fn update(&mut self, item: BvhItem, bounds: Option<Aabb>) {
+        match (self.leaves.get(&item).copied(), bounds) {
+            (Some(leaf), Some(bounds)) => {
+                if let NodeKind::Leaf(items) = &mut self.nodes[leaf].kind
+                    && let Some(entry) = items.iter_mut().find(|(other, _)| *other == item)
+                {
+                    entry.1 = pad(bounds);
+                }
+                self.refit(leaf);
+            }
+            _ => {
+                self.take(item);
+                self.place(item, bounds);
+            }
+        }
+        self.edited();
+    }
+
+    /// Cambia el índice de un objeto sin tocar su caja (el último bloque
+    /// que pasa al lugar de uno quitado)
+    pub fn rename(&mut self, from: BvhItem, to: BvhItem) {
+        if let Some(leaf) = self.leaves.remove(&from) {
+            if let NodeKind::Leaf(items) = &mut self.nodes[leaf].kind {
+                for entry in items.iter_mut().filter(|(item, _)| *item == from) {
+                    entry.0 = to;
+                }
+            }
+            self.leaves.insert(to, leaf);
+        } else if let Some(at) = self.unbounded.iter().position(|item| *item == from) {
+            self.unbounded.remove(at);
+            self.place(to, None);
+        }
+    }
+
+    pub fn counters(&self) -> BvhCounters {
+        self.counters
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty() && self.unbounded.is_empty()
+    }
+
+    /// Promedio, entre los nodos internos, de cuánto se pisan las cajas de
+    /// sus dos hijos: el área de la intersección sobre la de la caja menor
+    /// (0 si están separadas, 1 si una contiene a la otra). Baja con una
+    /// reconstrucción y sube con reajustes que agrandan las hojas.
+    pub fn average_overlap(&self) -> f32 {
+        if self.inner_nodes == 0 {
+            0.0
+        } else {
+            self.overlap_sum / self.inner_nodes as f32
+        }
+    }
+
+    /// Objetos que puede tocar el rayo antes de `max_t`: primero los que no
+    /// tienen caja y después los de las hojas que cruza, sin orden entre sí
+    pub fn visit(
+        &self,
+        origin: &Vector3,
+        dir: &Vector3,
+        max_t: f32,
+        mut visit: impl FnMut(BvhItem),
+    ) {
+        self.unbounded.iter().copied().for_each(&mut visit);
+        self.visit_nodes(
+            |bounds| ray_hits_box(origin, dir, max_t, bounds),
+            |item, bounds| {
+                if ray_hits_box(origin, dir, max_t, bounds) {
+                    visit(item);
+                }
+            },
+        );
+    }
+
+    /// Objetos cuya caja toca la caja `min`-`max` (los que no tienen caja
+    /// no cuentan)
+    pub fn visit_box(&self, min: Vector3, max: Vector3, mut visit: impl FnMut(BvhItem)) {
+        let query = (min, max);
+        self.visit_nodes(
+            |bounds| boxes_touch(bounds, &query),
+            |item, bounds| {
+                if boxes_touch(bounds, &query) {
+                    visit(item);
+                }
+            },
+        );
+    }
+
+    /// Cajas de las hojas que no están vacías
+    pub fn leaf_bounds(&self) -> impl Iterator<Item = Aabb> + '_ {
+        self.nodes.iter().filter_map(|node| match node.kind {
+            NodeKind::Leaf(_) => node.bounds,
+            NodeKind::Inner(..) => None,
+        })
+    }
+
+    /// Todos los objetos con su caja (con el margen), los sin caja al final
+    fn items(&self) -> impl Iterator<Item = (BvhItem, Option<Aabb>)> + '_ {
+        let bounded = self.nodes.iter().flat_map(|node| match &node.kind {
+            NodeKind::Leaf(items) => items.as_slice(),
+            NodeKind::Inner(..) => &[],
+        });
+        bounded
+            .map(|(item, bounds)| (*item, Some(*bounds)))
+            .chain(self.unbounded.iter().map(|item| (*item, None)))
+    }
+
+    /// Recorre desde la raíz los nodos que acepta `enter`, pasando cada
+    /// objeto de sus hojas a `visit`
+    fn visit_nodes(&self, enter: impl Fn(&Aabb) -> bool, mut visit: impl FnMut(BvhItem, &Aabb)) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.as_ref().is_some_and(&enter) {
+                continue;
+            }
+            match &node.kind {
+                NodeKind::Leaf(items) => {
+                    items.iter().for_each(|(item, bounds)| visit(*item, bounds))
+                }
+                NodeKind::Inner(children, _) => stack.extend(children),
+            }
+        }
+    }
+
+    /// Arma el subárbol de `items` (ya con margen) y devuelve su índice:
+    /// se parten por la mitad sobre el eje más largo de sus centros
+    fn build_node(&mut self, mut items: Vec<(BvhItem, Aabb)>, parent: Option<usize>) -> usize {
+        let index = self.nodes.len();
+        let bounds = items.iter().map(|(_, bounds)| *bounds).reduce(union);
+        self.nodes.push(Node {
+            bounds,
+            parent,
+            kind: NodeKind::Leaf(Vec::new()),
+        });
+        if items.len() <= LEAF_SIZE {
+            for (item, _) in &items {
+                self.leaves.insert(*item, index);
+            }
+            self.nodes[index].kind = NodeKind::Leaf(items);
+            return index;
+        }
+
+        let center = |(min, max): &Aabb| (*min + *max) * 0.5;
+        let (lo, hi) = items
+            .iter()
+            .map(|(_, bounds)| (center(bounds), center(bounds)))
+            .reduce(union)
+            .expect("hay objetos");
+        let extent = hi - lo;
+        let axis_of = |v: Vector3| {
+            if extent.x >= extent.y && extent.x >= extent.z {
+                v.x
+            } else if extent.y >= extent.z {
+                v.y
+            } else {
+                v.z
+            }
+        };
+        items.sort_by(|a, b| axis_of(center(&a.1)).total_cmp(&axis_of(center(&b.1))));
+        let second = items.split_off(items.len() / 2);
+        let children = [
+            self.build_node(items, Some(index)),
+            self.build_node(second, Some(index)),
+        ];
+        let overlap = overlap(
+            self.nodes[children[0]].bounds,
+            self.nodes[children[1]].bounds,
+        );
+        self.nodes[index].kind = NodeKind::Inner(children, overlap);
+        self.overlap_sum += overlap;
+        self.inner_nodes += 1;
+        index
+    }
+
+    /// Pone el objeto en la hoja que menos crece, sin contar la edición
+    fn place(&mut self, item: BvhItem, bounds: Option<Aabb>) {
+        let Some(bounds) = bounds.map(pad) else {
+            let at = self.unbounded.partition_point(|other| *other < item);
+            self.unbounded.insert(at, item);
+            return;
+        };
+        if self.nodes.is_empty() {
+            self.nodes.push(Node {
+                bounds: None,
+                parent: None,
+                kind: NodeKind::Leaf(Vec::new()),
+            });
+        }
+        let mut index = 0;
+        while let NodeKind::Inner(children, _) = self.nodes[index].kind {
+            let growth = |child: usize| {
+                let current = self.nodes[child].bounds;
+                let grown = current.map_or(bounds, |current| union(current, bounds));
+                area(&grown) - current.as_ref().map_or(0.0, area)
+            };
+            index = if growth(children[1]) < growth(children[0]) {
+                children[1]
+            } else {
+                children[0]
+            };
+        }
+        if let NodeKind::Leaf(items) = &mut self.nodes[index].kind {
+            items.push((item, bounds));
+        }
+        self.leaves.insert(item, index);
+        self.refit(index);
+    }
+
+    /// Saca el objeto de su hoja (o de la lista sin caja), sin contar la edición
+    fn take(&mut self, item: BvhItem) {
+        if let Some(leaf) = self.leaves.remove(&item) {
+            if let NodeKind::Leaf(items) = &mut self.nodes[leaf].kind {
+                items.retain(|(other, _)| *other != item);
+            }
+            self.refit(leaf);
+        } else {
+            self.unbounded.retain(|other| *other != item);
+        }
+    }
+
+    /// Corrige la caja de la hoja y las de sus antecesores, con el
+    /// solapamiento de cada nodo interno del camino
+    fn refit(&mut self, leaf: usize) {
+        let mut index = Some(leaf);
+        while let Some(current) = index {
+            let bounds = match &self.nodes[current].kind {
+                NodeKind::Leaf(items) => items.iter().map(|(_, bounds)| *bounds).reduce(union),
+                NodeKind::Inner(children, previous) => {
+                    let (a, b) = (
+                        self.nodes[children[0]].bounds,
+                        self.nodes[children[1]].bounds,
+                    );
+                    let (children, overlap) = (*children, overlap(a, b));
+                    self.overlap_sum += overlap - previous;
+                    self.nodes[current].kind = NodeKind::Inner(children, overlap);
+                    match (a, b) {
+                        (Some(a), Some(b)) => Some(union(a, b)),
+                        (a, b) => a.or(b),
+                    }
+                }
+            };
+            self.nodes[current].bounds = bounds;
+            index = self.nodes[current].parent;
+        }
+    }
+
+    /// Cuenta una edición: se reconstruye si ya van `REBUILD_AFTER_EDITS`
+    /// o si el solapamiento creció más de `MAX_OVERLAP_GROWTH`
+    fn edited(&mut self) {
+        self.edits += 1;
+        if self.edits >= REBUILD_AFTER_EDITS
+            || self.average_overlap() > self.built_overlap + MAX_OVERLAP_GROWTH
+        {
+            self.rebuild();
+        } else {
+            self.counters.refits += 1;
+        }
+    }
+}
+
+fn union(a: Aabb, b: Aabb) -> Aabb {
+    (
+        Vector3::new(a.0.x.min(b.0.x), a.0.y.min(b.0.y), a.0.z.min(b.0.z)),
+        Vector3::new(a.1.x.max(b.1.x), a.1.y.max(b.1.y), a.1.z.max(b.1.z)),
+    )
+}
+
+fn pad((min, max): Aabb) -> Aabb {
+    let padding = Vector3::one() * BOX_PADDING;
+    (min - padding, max + padding)
+}
+
+fn unpad((min, max): Aabb) -> Aabb {
+    let padding = Vector3::one() * BOX_PADDING;
+    (min + padding, max - padding)
+}
+
+/// Área de la superficie de la caja (no se anula con cajas planas)
+fn area((min, max): &Aabb) -> f32 {
+    let d = *max - *min;
+    2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+}
+
+/// Cuánto se pisan dos cajas hermanas (ver `Bvh::average_overlap`)
+fn overlap(a: Option<Aabb>, b: Option<Aabb>) -> f32 {
+    let (Some(a), Some(b)) = (a, b) else {
+        return 0.0;
+    };
+    if !boxes_touch(&a, &b) {
+        return 0.0;
+    }
+    let shared = (
+        Vector3::new(a.0.x.max(b.0.x), a.0.y.max(b.0.y), a.0.z.max(b.0.z)),
+        Vector3::new(a.1.x.min(b.1.x), a.1.y.min(b.1.y), a.1.z.min(b.1.z)),
+    );
+    let smaller = area(&a).min(area(&b));
+    if smaller > 0.0 {
+        (area(&shared) / smaller).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+fn boxes_touch(a: &Aabb, b: &Aabb) -> bool {
+    a.0.x <= b.1.x
+        && b.0.x <= a.1.x
+        && a.0.y <= b.1.y
+        && b.0.y <= a.1.y
+        && a.0.z <= b.1.z
+        && b.0.z <= a.1.z
+}
+
+/// Si el rayo entra en la caja entre 0 y `max_t`
+fn ray_hits_box(origin: &Vector3, dir: &Vector3, max_t: f32, (min, max): &Aabb) -> bool {
+    let (mut t_near, mut t_far) = (0.0f32, max_t);
+    for (o, d, min, max) in [
+        (origin.x, dir.x, min.x, max.x),
+        (origin.y, dir.y, min.y, max.y),
+        (origin.z, dir.z, min.z, max.z),
+    ] {
+        if d == 0.0 {
+            if o < min || o > max {
+                return false;
+            }
+            continue;
+        }
+        let (t0, t1) = ((min - o) / d, (max - o) / d);
+        t_near = t_near.max(t0.min(t1));
+        t_far = t_far.min(t0.max(t1));
+        if t_near > t_far {
+            return false;
+        }
+    }
+    true
+}
+
+/// Para comparar cajas bit a bit
+fn bits((min, max): Aabb) -> [u32; 6] {
+    [min.x, min.y, min.z, max.x, max.y, max.z].map(f32::to_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn unit_box(x: f32, y: f32, z: f32) -> Option<Aabb> {
+        let center = Vector3::new(x, y, z);
+        Some((center - Vector3::one() * 0.5, center + Vector3::one() * 0.5))
+    }
+
+    fn row(count: u32) -> Bvh {
+        Bvh::build((0..count).map(|i| (BvhItem::Block(i), unit_box(i as f32 * 2.0, 0.0, 0.0))))
+    }
+
+    fn visited(bvh: &Bvh, origin: Vector3, dir: Vector3) -> Vec<BvhItem> {
+        let mut items = Vec::new();
+        bvh.visit(&origin, &dir, f32::INFINITY, |item| items.push(item));
+        items.sort_unstable();
+        items
+    }
+
+    #[test]
+    fn rays_visit_the_boxes_they_cross() {
+        let mut bvh = row(8);
+        bvh.insert(BvhItem::Primitive(0), None);
+        // A lo largo de la fila: todas, más el plano
+        let along = visited(
+            &bvh,
+            Vector3::new(-5.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+        );
+        assert_eq!(along.len(), 9);
+        // Cruzando solo la caja 3 (x = 6)
+        let across = visited(
+            &bvh,
+            Vector3::new(6.0, 5.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+        );
+        assert_eq!(across, [BvhItem::Block(3), BvhItem::Primitive(0)]);
+        // Sin alcance suficiente no llega a la fila
+        let mut short = Vec::new();
+        bvh.visit(
+            &Vector3::new(6.0, 5.0, 0.0),
+            &Vector3::new(0.0, -1.0, 0.0),
+            2.0,
+            |item| short.push(item),
+        );
+        assert_eq!(short, [BvhItem::Primitive(0)]);
+
+        let mut near = Vec::new();
+        bvh.visit_box(
+            Vector3::new(3.9, -0.1, -0.1),
+            Vector3::new(4.1, 0.1, 0.1),
+            |item| near.push(item),
+        );
+        assert_eq!(near, [BvhItem::Block(2)]);
+    }
+
+    #[test]
+    fn edits_in_place_match_a_fresh_build() {
+        let mut items: Vec<(BvhItem, Option<Aabb>)> = (0..12)
+            .map(|i| (BvhItem::Block(i), unit_box(i as f32 * 2.0, 0.0, 0.0)))
+            .collect();
+        let mut bvh = Bvh::build(items.clone());
+        let mut rng = Rng::new(5, 0);
+        for i in 0..20u32 {
+            let index = (rng.next_f32() * items.len() as f32) as usize % items.len();
+            let moved = unit_box(rng.next_f32() * 20.0, rng.next_f32(), 0.0);
+            if i % 3 == 0 {
+                let (item, _) = items.swap_remove(index);
+                bvh.remove(item);
+            } else {
+                items[index].1 = moved;
+                bvh.update(items[index].0, moved);
+            }
+            assert_eq!(
+                bvh,
+                Bvh::build(items.clone()),
+                "después de la edición {}",
+                i
+            );
+
+            // Un rayo encuentra lo mismo que en un árbol armado de cero
+            let origin = Vector3::new(rng.next_f32() * 20.0, 3.0, 0.2);
+            let dir = Vector3::new(rng.next_f32() - 0.5, -1.0, 0.0);
+            assert_eq!(
+                visited(&bvh, origin, dir),
+                visited(&Bvh::build(items.clone()), origin, dir)
+            );
+        }
+    }
+
+    #[test]
+    fn rebuilds_after_many_edits_or_too_much_overlap() {
+        let mut bvh = row(8);
+        for _ in 1..REBUILD_AFTER_EDITS {
+            bvh.update(BvhItem::Block(0), unit_box(0.0, 0.01, 0.0));
+        }
+        assert_eq!(
+            bvh.counters(),
+            BvhCounters {
+                refits: REBUILD_AFTER_EDITS as u64 - 1,
+                rebuilds: 0
+            }
+        );
+        bvh.update(BvhItem::Block(0), unit_box(0.0, 0.0, 0.0));
+        assert_eq!(bvh.counters().rebuilds, 1);
+
+        // Dos hojas separadas que pasan a pisarse: se rearma enseguida
+        let mut pair = row(4);
+        assert_eq!(pair.average_overlap(), 0.0);
+        pair.update(BvhItem::Block(2), unit_box(0.2, 0.0, 0.0));
+        assert_eq!(
+            pair.counters(),
+            BvhCounters {
+                refits: 0,
+                rebuilds: 1
+            }
+        );
+    }
+}
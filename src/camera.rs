@@ -91,3 +91,146 @@ impl Camera {
         )
     }
 }
+
+/// Distancia del plano cercano para recortar las líneas de los overlays
+pub const NEAR_PLANE: f32 = 0.05;
+
+/// Cámara del raytracer: posición, base ortonormal y resolución de la
+/// imagen. Genera el rayo de cada pixel.
+#[derive(Clone)]
+pub struct CameraConfig {
+    pub pos: Vector3,
+    pub forward: Vector3,
+    pub right: Vector3,
+    pub up: Vector3,
+    pub width: usize,
+    pub height: usize,
+    pub fov_tan: f32,
+    pub aspect_ratio: f32,
+    /// Punto dentro de cada pixel por el que pasa el rayo, en [0, 1)
+    pub jitter: (f32, f32),
+}
+
+impl CameraConfig {
+    pub fn new(
+        pos: Vector3,
+        yaw: f32,
+        pitch: f32,
+        width: usize,
+        height: usize,
+        fov: f32,
+        aspect_ratio: f32,
+    ) -> Self {
+        let forward = Vector3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        )
+        .normalized();
+        let right = forward.cross(Vector3::new(0.0, 1.0, 0.0)).normalized();
+        let up = right.cross(forward).normalized();
+        Self {
+            pos,
+            forward,
+            right,
+            up,
+            width,
+            height,
+            fov_tan: (fov / 2.0).tan(),
+            aspect_ratio,
+            jitter: (0.5, 0.5),
+        }
+    }
+
+    /// Misma cámara con los rayos desplazados dentro del pixel (ver
+    /// `headless::sample_offset`)
+    pub fn with_jitter(&self, jitter: (f32, f32)) -> Self {
+        Self {
+            jitter,
+            ..self.clone()
+        }
+    }
+
+    /// Misma cámara con otra resolución (la relación de aspecto se recalcula)
+    pub fn with_size(&self, width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            aspect_ratio: width as f32 / height as f32,
+            ..self.clone()
+        }
+    }
+
+    /// Pose intermedia entre esta cámara (`t` = 0) y `to` (`t` = 1): la
+    /// posición en línea recta y la base interpolada y vuelta a
+    /// ortonormalizar. La resolución y el campo de visión son los de `to`.
+    pub fn lerp_pose(&self, to: &CameraConfig, t: f32) -> Self {
+        let forward = self.forward.lerp(to.forward, t).normalized();
+        let right = self.right.lerp(to.right, t);
+        let right = (right - forward * right.dot(forward)).normalized();
+        Self {
+            pos: self.pos.lerp(to.pos, t),
+            forward,
+            right,
+            up: right.cross(forward).normalized(),
+            ..to.clone()
+        }
+    }
+
+    /// Si la cámara se movió o giró respecto de `previous` lo suficiente
+    /// como para que se note entre dos frames
+    pub fn moved_from(&self, previous: &CameraConfig) -> bool {
+        const MIN_MOVE: f32 = 1e-4;
+        const MIN_TURN_COS: f32 = 1.0 - 1e-7;
+        (self.pos - previous.pos).length() > MIN_MOVE
+            || self.forward.dot(previous.forward) < MIN_TURN_COS
+            || self.right.dot(previous.right) < MIN_TURN_COS
+    }
+
+    /// Recorta el segmento `a`-`b` a la parte delante del plano cercano.
+    /// None si queda entero detrás.
+    pub fn clip_to_near(&self, a: Vector3, b: Vector3) -> Option<(Vector3, Vector3)> {
+        let depth_a = (a - self.pos).dot(self.forward) - NEAR_PLANE;
+        let depth_b = (b - self.pos).dot(self.forward) - NEAR_PLANE;
+        match (depth_a >= 0.0, depth_b >= 0.0) {
+            (true, true) => Some((a, b)),
+            (false, false) => None,
+            (a_in, _) => {
+                let crossing = a.lerp(b, depth_a / (depth_a - depth_b));
+                Some(if a_in { (a, crossing) } else { (crossing, b) })
+            }
+        }
+    }
+
+    /// Proyecta un punto del mundo a coordenadas de pixel (inversa de
+    /// `get_ray_direction`). None si está detrás de la cámara.
+    pub fn project(&self, point: Vector3) -> Option<(f32, f32)> {
+        let v = point - self.pos;
+        let depth = v.dot(self.forward);
+        if depth <= 1e-3 {
+            return None;
+        }
+        let px = v.dot(self.right) / depth / (self.fov_tan * self.aspect_ratio);
+        let py = v.dot(self.up) / depth / self.fov_tan;
+        Some((
+            (px + 1.0) * 0.5 * self.width as f32,
+            (1.0 - py) * 0.5 * self.height as f32,
+        ))
+    }
+
+    #[inline]
+    pub fn get_ray_direction(&self, x: usize, y: usize) -> Vector3 {
+        self.get_jittered_ray_direction(x, y, self.jitter)
+    }
+
+    /// Rayo por el punto `jitter` (en [0, 1)) del pixel, sin cambiar el de
+    /// la cámara (varias muestras del mismo pixel)
+    #[inline]
+    pub fn get_jittered_ray_direction(&self, x: usize, y: usize, jitter: (f32, f32)) -> Vector3 {
+        let px = (2.0 * ((x as f32 + jitter.0) / self.width as f32) - 1.0)
+            * self.fov_tan
+            * self.aspect_ratio;
+        let py = (1.0 - 2.0 * ((y as f32 + jitter.1) / self.height as f32)) * self.fov_tan;
+        (self.forward + self.right * px + self.up * py).normalized()
+    }
+}
@@ -0,0 +1,106 @@
+// caustics.rs - Cáusticas falsas: la luz que cruza agua o vidrio forma
+// ondas brillantes sobre lo que hay debajo
+use raylib::prelude::*;
+
+use crate::material::Material;
+
+/// Ondas del patrón: (dirección x, dirección z, número de onda, velocidad).
+/// Más finas y rápidas que las olas de la superficie (`ripple_normal`).
+const CAUSTIC_WAVES: [(f32, f32, f32, f32); 3] = [
+    (1.0, 0.0, 3.1, 1.1),
+    (0.5, 0.866, 4.3, 1.7),
+    (-0.643, 0.766, 5.9, 2.3),
+];
+
+/// Intensidad del patrón de cáusticas en el punto `(x, z)` del mundo al
+/// tiempo `time`, en [0, 2]. Las líneas brillantes caen donde las ondas se
+/// cancelan; el promedio sobre una zona grande es ~1, así el patrón
+/// redistribuye la luz sin aclarar ni oscurecer la sombra en conjunto.
+pub fn caustic_pattern(x: f32, z: f32, time: f32) -> f32 {
+    let sum: f32 = CAUSTIC_WAVES
+        .iter()
+        .map(|&(dx, dz, k, speed)| (k * (x * dx + z * dz) + speed * time).sin())
+        .sum();
+    let ridge = 1.0 - (sum / CAUSTIC_WAVES.len() as f32).abs();
+    2.0 * ridge * ridge
+}
+
+/// Factor por el que se multiplica la luz que atraviesa `material` hacia el
+/// punto `point` (además de su transparencia). El patrón pesa según la
+/// transparencia del material y según lo vertical que sea la luz
+/// (`light_dir.y`): con el sol bajo las ondas se estirarían de más.
+pub fn caustic_factor(material: &Material, point: &Vector3, light_dir: &Vector3, time: f32) -> f32 {
+    let weight = material.transparency * light_dir.y.abs();
+    1.0 + (caustic_pattern(point.x, point.z, time) - 1.0) * weight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_types::BlockType;
+
+    /// Puntos de una zona de 11x12 unidades, sin alinearse con las ondas
+    fn samples(time: f32) -> impl Iterator<Item = f32> {
+        (0..300 * 300)
+            .map(move |i| caustic_pattern((i / 300) as f32 * 0.037, (i % 300) as f32 * 0.041, time))
+    }
+
+    #[test]
+    fn pattern_stays_in_range_and_averages_one() {
+        for time in [0.0, 1.3, 37.5] {
+            let (mut sum, mut min, mut max) = (0.0, f32::MAX, f32::MIN);
+            for value in samples(time) {
+                sum += value;
+                min = min.min(value);
+                max = max.max(value);
+            }
+            let mean = sum / (300 * 300) as f32;
+            assert!(min >= 0.0 && max <= 2.0, "rango [{}, {}]", min, max);
+            // Hay líneas brillantes y zonas oscuras
+            assert!(min < 0.1 && max > 1.9, "rango [{}, {}]", min, max);
+            assert!((mean - 1.0).abs() < 0.05, "promedio {}", mean);
+        }
+    }
+
+    #[test]
+    fn pattern_moves_with_time() {
+        assert_eq!(
+            caustic_pattern(1.5, -2.0, 4.0),
+            caustic_pattern(1.5, -2.0, 4.0)
+        );
+        let moved = samples(0.0)
+            .zip(samples(0.5))
+            .filter(|(a, b)| (a - b).abs() > 0.1)
+            .count();
+        assert!(moved > 300 * 300 / 4, "solo cambiaron {} puntos", moved);
+    }
+
+    #[test]
+    fn factor_scales_with_transparency_and_light_height() {
+        let point = Vector3::new(0.3, 0.0, 0.7);
+        let overhead = Vector3::new(0.0, 1.0, 0.0);
+        let pattern = caustic_pattern(point.x, point.z, 2.0);
+
+        let mut clear = BlockType::Water.material();
+        clear.transparency = 1.0;
+        assert!((caustic_factor(&clear, &point, &overhead, 2.0) - pattern).abs() < 1e-6);
+        // Luz rasante u opaco: sin patrón
+        let grazing = Vector3::new(1.0, 0.0, 0.0);
+        assert_eq!(caustic_factor(&clear, &point, &grazing, 2.0), 1.0);
+        clear.transparency = 0.0;
+        assert_eq!(caustic_factor(&clear, &point, &overhead, 2.0), 1.0);
+    }
+
+    #[test]
+    fn only_transparent_blocks_make_caustics() {
+        for block_type in BlockType::all() {
+            let material = block_type.material();
+            if material.caustics {
+                assert!(material.transparency > 0.0, "{:?} es opaco", block_type);
+            }
+        }
+        assert!(BlockType::Water.material().caustics);
+        assert!(BlockType::Glass.material().caustics);
+        assert!(!BlockType::Ice.material().caustics);
+    }
+}
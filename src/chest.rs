@@ -0,0 +1,155 @@
+// chest.rs - Cofre: texturas, tapa que se abre con un clic y el panel de
+// inventario (por ahora vacío)
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::block_shape::BlockShape;
+use crate::block_types::BlockType;
+use crate::scene::index_after_removal;
+use crate::textures::TextureManager;
+
+/// Texturas procedurales del costado, la tapa y el frente con el pestillo
+pub const CHEST_SIDE_TEXTURE: &str = "procedural/chest_side";
+pub const CHEST_TOP_TEXTURE: &str = "procedural/chest_top";
+pub const CHEST_FRONT_TEXTURE: &str = "procedural/chest_front";
+
+/// Lado en texels de las texturas del cofre
+const CHEST_TEXTURE_SIZE: u32 = 16;
+
+/// Fila de la textura donde la tapa se apoya sobre el cuerpo
+const SEAM_ROW: u32 = 6;
+
+/// Casillas del inventario: filas y columnas
+const INVENTORY_ROWS: i32 = 3;
+const INVENTORY_COLUMNS: i32 = 9;
+const SLOT_SIZE: i32 = 36;
+const SLOT_GAP: i32 = 4;
+const PANEL_PADDING: i32 = 12;
+const TITLE_HEIGHT: i32 = 28;
+const FOOTER_HEIGHT: i32 = 22;
+
+/// Si el bloque es un cofre
+pub fn is_chest(block: &Block) -> bool {
+    BlockType::identify(block) == Some(BlockType::Chest)
+}
+
+/// Abre o cierra la tapa. Devuelve el estado nuevo (true = abierto), o
+/// None si el bloque no es un cofre.
+pub fn toggle_lid(block: &mut Block) -> Option<bool> {
+    let BlockShape::Chest { open } = block.shape else {
+        return None;
+    };
+    block.shape = BlockShape::Chest { open: !open };
+    Some(!open)
+}
+
+/// Panel del inventario del cofre abierto: una grilla de casillas vacías
+/// hasta que haya ítems
+#[derive(Debug, Default)]
+pub struct ChestPanel {
+    /// Índice en la escena del cofre abierto
+    open_chest: Option<usize>,
+}
+
+impl ChestPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open_chest.is_some()
+    }
+
+    /// Índice del cofre cuyo inventario se muestra
+    pub fn chest(&self) -> Option<usize> {
+        self.open_chest
+    }
+
+    pub fn open(&mut self, index: usize) {
+        self.open_chest = Some(index);
+    }
+
+    pub fn close(&mut self) {
+        self.open_chest = None;
+    }
+
+    /// Al quitar el bloque `removed` el último (`last`) pasa a su índice;
+    /// si era el cofre abierto, el panel se cierra
+    pub fn on_block_removed(&mut self, removed: usize, last: usize) {
+        self.open_chest = self
+            .open_chest
+            .and_then(|index| index_after_removal(index, removed, last));
+    }
+
+    /// Dibuja el panel centrado en la ventana
+    pub fn draw(&self, d: &mut RaylibDrawHandle, window_width: i32, window_height: i32) {
+        if !self.is_open() {
+            return;
+        }
+        let grid_width = INVENTORY_COLUMNS * (SLOT_SIZE + SLOT_GAP) - SLOT_GAP;
+        let grid_height = INVENTORY_ROWS * (SLOT_SIZE + SLOT_GAP) - SLOT_GAP;
+        let width = grid_width + PANEL_PADDING * 2;
+        let height = TITLE_HEIGHT + grid_height + FOOTER_HEIGHT + PANEL_PADDING * 2;
+        let x = (window_width - width) / 2;
+        let y = (window_height - height) / 2;
+
+        d.draw_rectangle(x, y, width, height, Color::new(0, 0, 0, 190));
+        d.draw_text("Cofre", x + PANEL_PADDING, y + PANEL_PADDING, 20, Color::WHITE);
+
+        let grid_y = y + PANEL_PADDING + TITLE_HEIGHT;
+        for row in 0..INVENTORY_ROWS {
+            for column in 0..INVENTORY_COLUMNS {
+                let slot_x = x + PANEL_PADDING + column * (SLOT_SIZE + SLOT_GAP);
+                let slot_y = grid_y + row * (SLOT_SIZE + SLOT_GAP);
+                d.draw_rectangle(slot_x, slot_y, SLOT_SIZE, SLOT_SIZE, Color::new(60, 60, 60, 220));
+                d.draw_rectangle_lines(slot_x, slot_y, SLOT_SIZE, SLOT_SIZE, Color::GRAY);
+            }
+        }
+
+        d.draw_text(
+            "Vacío: todavía no hay ítems. Clic izquierdo en el cofre para cerrarlo",
+            x + PANEL_PADDING,
+            grid_y + grid_height + 6,
+            14,
+            Color::LIGHTGRAY,
+        );
+    }
+}
+
+/// Registra las texturas procedurales del cofre: tablas de madera con un
+/// marco oscuro, la junta de la tapa en los costados y el pestillo de
+/// metal en el frente
+pub fn insert_chest_textures(tex_mgr: &mut TextureManager) {
+    let size = CHEST_TEXTURE_SIZE;
+    let wood = Vector3::new(0.62, 0.42, 0.2);
+    let dark_wood = Vector3::new(0.5, 0.33, 0.15);
+    let frame = Vector3::new(0.3, 0.18, 0.08);
+    let metal = Vector3::new(0.75, 0.75, 0.72);
+
+    // Tablas horizontales de 4 texels con una línea más oscura entre ellas
+    let planks = move |x: u32, y: u32| {
+        let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+        if border {
+            frame
+        } else if y % 4 == 3 {
+            dark_wood
+        } else {
+            wood
+        }
+    };
+
+    tex_mgr.insert_procedural(CHEST_TOP_TEXTURE, size, size, planks);
+    tex_mgr.insert_procedural(CHEST_SIDE_TEXTURE, size, size, move |x, y| {
+        if y == SEAM_ROW { frame } else { planks(x, y) }
+    });
+    tex_mgr.insert_procedural(CHEST_FRONT_TEXTURE, size, size, move |x, y| {
+        let latch = (7..9).contains(&x) && (SEAM_ROW - 2..SEAM_ROW + 2).contains(&y);
+        if latch {
+            metal
+        } else if y == SEAM_ROW {
+            frame
+        } else {
+            planks(x, y)
+        }
+    });
+}
@@ -0,0 +1,320 @@
+// config.rs - Opciones de arranque (ventana, resolución, FOV, hilos) en config.toml
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+use crate::frame_limiter::DEFAULT_FPS_CAP;
+use crate::headless::{SceneSource, parse_scene, parse_size};
+use crate::quality::QualityPreset;
+use crate::settings::parse_key_values;
+
+/// Archivo de configuración de arranque; se crea con los valores por defecto
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// Lado máximo aceptado para la ventana y la resolución interna
+const MAX_SIZE: i32 = 16384;
+/// Rango de FOV vertical aceptado, en grados
+const FOV_RANGE: std::ops::RangeInclusive<f32> = 10.0..=170.0;
+
+/// Errores de las opciones de arranque: línea de comandos y archivos de
+/// configuración (`config.toml`, `settings.toml`, teclas, sesiones grabadas)
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Falta el valor de una opción de la línea de comandos
+    MissingValue(String),
+    /// Valor que no se pudo interpretar; `what` describe lo que se esperaba
+    InvalidValue { what: &'static str, value: String },
+    /// Opción de la línea de comandos que no existe
+    UnknownOption(String),
+    /// Dos opciones que no se pueden usar juntas
+    Conflict(&'static str, &'static str),
+    /// No se pudo leer o escribir un archivo
+    Io { path: PathBuf, source: io::Error },
+    /// Línea inválida de un archivo (numeradas desde 1)
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl ConfigError {
+    /// Valor inválido para lo que describe `what`
+    pub fn invalid(what: &'static str, value: impl Into<String>) -> Self {
+        ConfigError::InvalidValue {
+            what,
+            value: value.into(),
+        }
+    }
+
+    /// Error de E/S sobre `path`
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        ConfigError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingValue(flag) => write!(f, "falta el valor de {}", flag),
+            ConfigError::InvalidValue { what, value } => {
+                write!(f, "valor inválido para {}: '{}'", what, value)
+            }
+            ConfigError::UnknownOption(flag) => write!(f, "opción desconocida '{}'", flag),
+            ConfigError::Conflict(a, b) => write!(f, "{} y {} no se pueden combinar", a, b),
+            ConfigError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ConfigError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Lo que se decide al arrancar y no cambia con las preferencias de
+/// `settings.toml`. La línea de comandos pisa los valores del archivo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub window_width: i32,
+    pub window_height: i32,
+    /// Resolución interna del raytracer; al redimensionar se mantiene la
+    /// proporción con la ventana
+    pub render_width: i32,
+    pub render_height: i32,
+    /// FOV vertical en grados
+    pub fov_degrees: f32,
+    pub vsync: bool,
+    /// 0 = sin límite
+    pub fps_cap: u32,
+    /// Hilos del render multihilo; 0 = los que tenga la máquina
+    pub threads: usize,
+    /// Escena inicial: isla, invierno, galeria, primitivas o un archivo de escena
+    pub scene: String,
+    /// Preset de calidad que se aplica al arrancar, si hay
+    pub quality: Option<QualityPreset>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            window_width: 800,
+            window_height: 600,
+            render_width: 400,
+            render_height: 300,
+            fov_degrees: 60.0,
+            vsync: false,
+            fps_cap: DEFAULT_FPS_CAP,
+            threads: 0,
+            scene: "isla".to_string(),
+            quality: None,
+        }
+    }
+}
+
+impl Config {
+    /// Carga la configuración; si el archivo no existe lo crea con los
+    /// valores por defecto. Claves desconocidas o valores inválidos se
+    /// ignoran con una advertencia.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let mut config = Config::default();
+        let Ok(text) = fs::read_to_string(path.as_ref()) else {
+            if let Err(err) = config.save(path.as_ref()) {
+                warn!("No se pudo crear la configuración: {}", err);
+            }
+            return config;
+        };
+
+        for (line_number, key, value) in parse_key_values(&text) {
+            let ok = match key.as_str() {
+                "window_width" => value.parse().map(|w| config.window_width = w).is_ok(),
+                "window_height" => value.parse().map(|h| config.window_height = h).is_ok(),
+                "render_width" => value.parse().map(|w| config.render_width = w).is_ok(),
+                "render_height" => value.parse().map(|h| config.render_height = h).is_ok(),
+                "fov_degrees" => value.parse().map(|fov| config.fov_degrees = fov).is_ok(),
+                "vsync" => value.parse().map(|on| config.vsync = on).is_ok(),
+                "fps_cap" => value.parse().map(|cap| config.fps_cap = cap).is_ok(),
+                "threads" => value.parse().map(|n| config.threads = n).is_ok(),
+                "scene" => {
+                    config.scene = value.clone();
+                    true
+                }
+                "quality" if value == "-" => {
+                    config.quality = None;
+                    true
+                }
+                "quality" => QualityPreset::from_name(&value)
+                    .map(|preset| config.quality = Some(preset))
+                    .is_some(),
+                _ => false,
+            };
+            if !ok {
+                warn!(
+                    "{}:{}: ignorando '{} = {}'",
+                    path.as_ref().display(),
+                    line_number,
+                    key,
+                    value
+                );
+            }
+        }
+
+        config.validate();
+        config
+    }
+
+    /// Opciones de la línea de comandos, que pisan las del archivo:
+    /// `--window AxB`, `--resolution AxB`, `--fov GRADOS`, `--vsync`,
+    /// `--fps-cap N`, `--threads N`, `--scene ESCENA` y `--preset NOMBRE`
+    pub fn apply_args(&mut self, args: &[String]) -> Result<(), ConfigError> {
+        let value = |flag: &str| -> Result<Option<&String>, ConfigError> {
+            match args.iter().position(|arg| arg == flag) {
+                Some(i) => args
+                    .get(i + 1)
+                    .map(Some)
+                    .ok_or_else(|| ConfigError::MissingValue(flag.to_string())),
+                None => Ok(None),
+            }
+        };
+        let size = |value: &str| -> Result<(i32, i32), ConfigError> {
+            let (width, height) = parse_size(value)?;
+            Ok((width as i32, height as i32))
+        };
+
+        if let Some(window) = value("--window")? {
+            (self.window_width, self.window_height) = size(window)?;
+        }
+        if let Some(resolution) = value("--resolution")? {
+            (self.render_width, self.render_height) = size(resolution)?;
+        }
+        if let Some(fov) = value("--fov")? {
+            self.fov_degrees = fov.parse().map_err(|_| ConfigError::invalid("--fov", fov))?;
+        }
+        if args.iter().any(|arg| arg == "--vsync") {
+            self.vsync = true;
+        }
+        if let Some(cap) = value("--fps-cap")? {
+            self.fps_cap = cap
+                .parse()
+                .map_err(|_| ConfigError::invalid("--fps-cap", cap))?;
+        }
+        if let Some(threads) = value("--threads")? {
+            self.threads = threads
+                .parse()
+                .map_err(|_| ConfigError::invalid("--threads", threads))?;
+        }
+        if let Some(scene) = value("--scene")? {
+            self.scene = scene.clone();
+        }
+        if let Some(name) = value("--preset")? {
+            self.quality = Some(
+                QualityPreset::from_name(name)
+                    .ok_or_else(|| ConfigError::invalid("--preset", name))?,
+            );
+        }
+
+        self.validate();
+        Ok(())
+    }
+
+    /// Vuelve a los valores por defecto los tamaños y el FOV fuera de rango
+    fn validate(&mut self) {
+        let defaults = Config::default();
+        let valid_size = |width: i32, height: i32| {
+            (1..=MAX_SIZE).contains(&width) && (1..=MAX_SIZE).contains(&height)
+        };
+        if !valid_size(self.window_width, self.window_height) {
+            warn!(
+                "Tamaño de ventana inválido {}x{}, se usa {}x{}",
+                self.window_width,
+                self.window_height,
+                defaults.window_width,
+                defaults.window_height
+            );
+            (self.window_width, self.window_height) =
+                (defaults.window_width, defaults.window_height);
+        }
+        if !valid_size(self.render_width, self.render_height) {
+            warn!(
+                "Resolución interna inválida {}x{}, se usa {}x{}",
+                self.render_width,
+                self.render_height,
+                defaults.render_width,
+                defaults.render_height
+            );
+            (self.render_width, self.render_height) =
+                (defaults.render_width, defaults.render_height);
+        }
+        if !FOV_RANGE.contains(&self.fov_degrees) {
+            warn!(
+                "FOV inválido {}° (rango {}..{}), se usa {}°",
+                self.fov_degrees,
+                FOV_RANGE.start(),
+                FOV_RANGE.end(),
+                defaults.fov_degrees
+            );
+            self.fov_degrees = defaults.fov_degrees;
+        }
+    }
+
+    /// FOV vertical en radianes, como lo espera `CameraConfig`
+    pub fn fov(&self) -> f32 {
+        self.fov_degrees.to_radians()
+    }
+
+    /// Pixeles de ventana por pixel del framebuffer en cada eje
+    pub fn render_scale(&self) -> (f32, f32) {
+        (
+            self.window_width as f32 / self.render_width as f32,
+            self.window_height as f32 / self.render_height as f32,
+        )
+    }
+
+    /// Escena inicial como la entiende el modo sin ventana
+    pub fn scene_source(&self) -> SceneSource {
+        parse_scene(&self.scene)
+    }
+
+    /// Guarda la configuración en formato `clave = valor`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let text = format!(
+            "# Configuración de arranque del raytracer\n\
+             window_width = {}\n\
+             window_height = {}\n\
+             render_width = {}\n\
+             render_height = {}\n\
+             fov_degrees = {}\n\
+             vsync = {}\n\
+             fps_cap = {}\n\
+             threads = {}\n\
+             scene = \"{}\"\n\
+             quality = \"{}\"\n",
+            self.window_width,
+            self.window_height,
+            self.render_width,
+            self.render_height,
+            self.fov_degrees,
+            self.vsync,
+            self.fps_cap,
+            self.threads,
+            self.scene,
+            self.quality.map_or("-", QualityPreset::name),
+        );
+        fs::write(path.as_ref(), text).map_err(|err| ConfigError::io(path, err))
+    }
+}
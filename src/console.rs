@@ -0,0 +1,351 @@
+// console.rs - Consola de comandos estilo Quake (~): edición de línea,
+// historial, tokenizador y registro de comandos
+use std::collections::VecDeque;
+
+use raylib::prelude::*;
+
+/// Líneas de salida que se conservan; las más viejas se descartan
+const MAX_OUTPUT_LINES: usize = 200;
+/// Entradas del historial que se conservan
+const MAX_HISTORY: usize = 50;
+const FONT_SIZE: i32 = 14;
+const LINE_HEIGHT: i32 = 16;
+
+/// Error de un comando: argumentos mal escritos (se muestra el uso) o una
+/// falla al ejecutarlo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    Usage,
+    Failed(String),
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Failed(message)
+    }
+}
+
+/// Lo que devuelve un comando: texto para la salida o un error
+pub type CommandResult = Result<String, CommandError>;
+
+/// Un comando registrado. `C` es el estado que los comandos pueden modificar.
+pub struct Command<C> {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+    pub handler: fn(&mut C, &[String]) -> CommandResult,
+}
+
+/// Comandos disponibles, por nombre. Cada subsistema agrega los suyos con
+/// `register`.
+pub struct CommandRegistry<C> {
+    commands: Vec<Command<C>>,
+}
+
+impl<C> Default for CommandRegistry<C> {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+}
+
+impl<C> CommandRegistry<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Agrega un comando. `usage` es la sintaxis completa, ej: "teleport <x> <y> <z>"
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        description: &'static str,
+        handler: fn(&mut C, &[String]) -> CommandResult,
+    ) {
+        debug_assert!(
+            self.find(name).is_none(),
+            "comando '{}' registrado dos veces",
+            name
+        );
+        self.commands.push(Command {
+            name,
+            usage,
+            description,
+            handler,
+        });
+    }
+
+    fn find(&self, name: &str) -> Option<&Command<C>> {
+        self.commands
+            .iter()
+            .find(|command| command.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Ayuda de un comando o la lista de todos
+    fn help(&self, name: Option<&str>) -> String {
+        match name.map(|name| self.find(name)) {
+            Some(Some(command)) => format!("{} - {}", command.usage, command.description),
+            Some(None) => format!("Comando desconocido '{}'", name.unwrap_or_default()),
+            None => std::iter::once("Comandos:".to_string())
+                .chain(
+                    self.commands
+                        .iter()
+                        .map(|command| format!("  {} - {}", command.usage, command.description)),
+                )
+                .chain(std::iter::once("  help [comando] - Esta ayuda".to_string()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    /// Ejecuta una línea y devuelve el texto para la salida
+    pub fn execute(&self, context: &mut C, line: &str) -> String {
+        let tokens = match tokenize(line) {
+            Ok(tokens) => tokens,
+            Err(err) => return format!("Error: {}", err),
+        };
+        let Some((name, args)) = tokens.split_first() else {
+            return String::new();
+        };
+        if name.eq_ignore_ascii_case("help") {
+            return self.help(args.first().map(String::as_str));
+        }
+        let Some(command) = self.find(name) else {
+            return format!(
+                "Comando desconocido '{}'. Usa 'help' para ver la lista.",
+                name
+            );
+        };
+        match (command.handler)(context, args) {
+            Ok(output) => output,
+            Err(CommandError::Usage) => format!("Uso: {}", command.usage),
+            Err(CommandError::Failed(message)) => format!("Error: {}", message),
+        }
+    }
+}
+
+/// Separa una línea en palabras. Las comillas dobles agrupan texto con
+/// espacios (ej: rutas) y `\"` escribe una comilla.
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quoted = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if quoted => match chars.next() {
+                Some(escaped) => current.push(escaped),
+                None => return Err("'\\' al final de la línea".to_string()),
+            },
+            '"' => {
+                quoted = !quoted;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if quoted {
+        return Err("falta cerrar las comillas".to_string());
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// Estado de la consola: línea en edición, historial y salida
+#[derive(Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    /// Posición del cursor en caracteres dentro de `input`
+    cursor: usize,
+    history: Vec<String>,
+    /// Entrada del historial que se está mostrando (None = línea nueva)
+    history_index: Option<usize>,
+    output: VecDeque<String>,
+    /// Líneas desplazadas hacia arriba desde el final de la salida
+    scroll: usize,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Abre o cierra la consola. Descarta los caracteres pendientes para que
+    /// la tecla que la abrió no quede escrita.
+    pub fn toggle(&mut self, rl: &mut RaylibHandle) {
+        self.open = !self.open;
+        while rl.get_char_pressed().is_some() {}
+    }
+
+    /// Agrega texto a la salida (una entrada por línea)
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.output.len() == MAX_OUTPUT_LINES {
+                self.output.pop_front();
+            }
+            self.output.push_back(line.to_string());
+        }
+        self.scroll = 0;
+    }
+
+    /// Edita la línea con el teclado. Devuelve la línea enviada con Enter.
+    pub fn handle_input(&mut self, rl: &mut RaylibHandle) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+        let pressed = |key| rl.is_key_pressed(key) || rl.is_key_pressed_repeat(key);
+
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.open = false;
+            return None;
+        }
+        if pressed(KeyboardKey::KEY_BACKSPACE) && self.cursor > 0 {
+            self.cursor -= 1;
+            self.input.remove(self.byte_index(self.cursor));
+        }
+        if pressed(KeyboardKey::KEY_DELETE) && self.cursor < self.input.chars().count() {
+            self.input.remove(self.byte_index(self.cursor));
+        }
+        if pressed(KeyboardKey::KEY_LEFT) {
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+        if pressed(KeyboardKey::KEY_RIGHT) {
+            self.cursor = (self.cursor + 1).min(self.input.chars().count());
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_HOME) {
+            self.cursor = 0;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_END) {
+            self.cursor = self.input.chars().count();
+        }
+        if pressed(KeyboardKey::KEY_UP) {
+            self.browse_history(true);
+        }
+        if pressed(KeyboardKey::KEY_DOWN) {
+            self.browse_history(false);
+        }
+        if pressed(KeyboardKey::KEY_PAGE_UP) {
+            self.scroll = (self.scroll + 5).min(self.output.len().saturating_sub(1));
+        }
+        if pressed(KeyboardKey::KEY_PAGE_DOWN) {
+            self.scroll = self.scroll.saturating_sub(5);
+        }
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel > 0.0 {
+            self.scroll = (self.scroll + 1).min(self.output.len().saturating_sub(1));
+        } else if wheel < 0.0 {
+            self.scroll = self.scroll.saturating_sub(1);
+        }
+
+        while let Some(c) = rl.get_char_pressed() {
+            // La tecla de la consola la cierra desde el loop principal
+            if c.is_control() || c == '`' || c == '~' {
+                continue;
+            }
+            self.input.insert(self.byte_index(self.cursor), c);
+            self.cursor += 1;
+        }
+
+        if !rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return None;
+        }
+        let line = std::mem::take(&mut self.input);
+        self.cursor = 0;
+        self.history_index = None;
+        self.print(&format!("> {}", line));
+        if line.trim().is_empty() {
+            return None;
+        }
+        if self.history.last() != Some(&line) {
+            if self.history.len() == MAX_HISTORY {
+                self.history.remove(0);
+            }
+            self.history.push(line.clone());
+        }
+        Some(line)
+    }
+
+    /// Posición en bytes del carácter `index` de la línea
+    fn byte_index(&self, index: usize) -> usize {
+        self.input
+            .char_indices()
+            .nth(index)
+            .map_or(self.input.len(), |(i, _)| i)
+    }
+
+    /// Flecha arriba recorre el historial hacia atrás, abajo hacia adelante
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        self.history_index = match (self.history_index, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < self.history.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .history_index
+            .map(|i| self.history[i].clone())
+            .unwrap_or_default();
+        self.cursor = self.input.chars().count();
+    }
+
+    /// Dibuja la consola sobre la parte de arriba de la ventana
+    pub fn draw(&self, d: &mut RaylibDrawHandle, window_width: i32, window_height: i32) {
+        if !self.open {
+            return;
+        }
+        let height = (window_height * 2 / 5).max(LINE_HEIGHT * 4);
+        d.draw_rectangle(0, 0, window_width, height, Color::new(0, 0, 0, 200));
+        d.draw_line(0, height, window_width, height, Color::GRAY);
+
+        // Salida: las últimas líneas que entran, desplazadas por `scroll`
+        let input_y = height - LINE_HEIGHT - 6;
+        let visible = ((input_y - 6) / LINE_HEIGHT).max(0) as usize;
+        let end = self.output.len() - self.scroll.min(self.output.len());
+        let start = end.saturating_sub(visible);
+        for (i, line) in self.output.range(start..end).enumerate() {
+            let color = if line.starts_with("Error") {
+                Color::RED
+            } else if line.starts_with('>') {
+                Color::LIGHTGRAY
+            } else {
+                Color::WHITE
+            };
+            d.draw_text(line, 8, 6 + LINE_HEIGHT * i as i32, FONT_SIZE, color);
+        }
+        if self.scroll > 0 {
+            let text = format!("-- {} líneas más abajo --", self.scroll);
+            let width = d.measure_text(&text, FONT_SIZE);
+            d.draw_text(&text, window_width - width - 8, 6, FONT_SIZE, Color::GRAY);
+        }
+
+        // Línea en edición con el cursor
+        let prompt = format!("> {}", self.input);
+        d.draw_text(&prompt, 8, input_y, FONT_SIZE, Color::YELLOW);
+        let before: String = self.input.chars().take(self.cursor).collect();
+        let cursor_x = 8 + d.measure_text(&format!("> {}", before), FONT_SIZE);
+        d.draw_rectangle(cursor_x, input_y, 2, FONT_SIZE, Color::YELLOW);
+    }
+}
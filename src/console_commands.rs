@@ -0,0 +1,438 @@
+// console_commands.rs - Comandos de la consola: render, cámara, escena y luces
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::block_types::BlockType;
+use crate::console::{CommandError, CommandRegistry, CommandResult};
+use crate::day_cycle::SkyModel;
+use crate::events::clamp_pitch;
+use crate::fog::parse_fog_color;
+use crate::light::Light;
+use crate::quality::QualityPreset;
+use crate::render_settings::RenderSettings;
+use crate::scene_file::{load_scene, save_scene};
+use crate::screenshot::HdrCaptureFormat;
+use crate::settings::Settings;
+use crate::shared_scene::SharedScene;
+use crate::tonemap::ToneMapping;
+use crate::view_file::{load_view, view_path};
+
+/// Estado del loop principal que los comandos pueden modificar. Los cambios
+/// que el loop tiene que completar (reiniciar animaciones, capturar el
+/// frame...) se piden con las banderas.
+pub struct CommandContext<'a> {
+    pub settings: &'a mut Settings,
+    pub use_multithreading: &'a mut bool,
+    pub camera_pos: &'a mut Vector3,
+    pub camera_yaw: &'a mut f32,
+    pub camera_pitch: &'a mut f32,
+    /// FOV vertical en radianes
+    pub fov: &'a mut f32,
+    pub scene: &'a mut SharedScene,
+    /// Escena actual como la acepta `--scene` (ver `SceneKind::name`)
+    pub scene_name: &'a mut String,
+    pub fixed_lights: &'a mut Vec<Light>,
+    /// Se reemplazó la escena entera
+    pub scene_replaced: bool,
+    /// Se agregaron bloques a la escena
+    pub scene_edited: bool,
+    pub screenshot_requested: bool,
+}
+
+/// Ejecuta una línea de la consola con los comandos de todos los
+/// subsistemas. El registro se arma en cada llamada porque los comandos
+/// toman prestado el estado solo durante esa línea.
+pub fn execute(context: &mut CommandContext, line: &str) -> String {
+    let mut registry = CommandRegistry::new();
+    register_render_commands(&mut registry);
+    register_camera_commands(&mut registry);
+    register_scene_commands(&mut registry);
+    register_light_commands(&mut registry);
+    registry.execute(context, line)
+}
+
+fn register_render_commands(registry: &mut CommandRegistry<CommandContext>) {
+    registry.register(
+        "set",
+        "set <opción> <valor>",
+        "Cambia una opción de render (sin argumentos lista las opciones)",
+        set_command,
+    );
+    registry.register(
+        "screenshot",
+        "screenshot",
+        "Guarda el próximo frame en screenshots/",
+        |context, args| {
+            if !args.is_empty() {
+                return Err(CommandError::Usage);
+            }
+            context.screenshot_requested = true;
+            Ok(String::new())
+        },
+    );
+}
+
+fn register_camera_commands(registry: &mut CommandRegistry<CommandContext>) {
+    registry.register(
+        "teleport",
+        "teleport <x> <y> <z> [yaw° pitch°]",
+        "Mueve la cámara (y opcionalmente la orienta)",
+        |context, args| {
+            if args.len() != 3 && args.len() != 5 {
+                return Err(CommandError::Usage);
+            }
+            let numbers = parse_numbers(args)?;
+            *context.camera_pos = Vector3::new(numbers[0], numbers[1], numbers[2]);
+            if let [_, _, _, yaw, pitch] = numbers[..] {
+                *context.camera_yaw = yaw.to_radians();
+                *context.camera_pitch = pitch.to_radians();
+                clamp_pitch(context.camera_pitch);
+            }
+            Ok(format!(
+                "Cámara en ({}, {}, {})",
+                numbers[0], numbers[1], numbers[2]
+            ))
+        },
+    );
+    registry.register(
+        "view",
+        "view load <captura>",
+        "Restaura la cámara, la escena y los ajustes de una captura (su archivo de vista)",
+        |context, args| {
+            let [action, path] = args else {
+                return Err(CommandError::Usage);
+            };
+            if action != "load" {
+                return Err(CommandError::Usage);
+            }
+            let view = load_view(view_path(Path::new(path))).map_err(|err| err.to_string())?;
+            if view.scene != *context.scene_name {
+                let (_, scene) = view.build_scene().map_err(|err| err.to_string())?;
+                context.scene.replace(scene);
+                *context.scene_name = view.scene.clone();
+                context.scene_replaced = true;
+            }
+            *context.camera_pos = view.camera_pos;
+            *context.camera_yaw = view.camera_yaw;
+            *context.camera_pitch = view.camera_pitch;
+            clamp_pitch(context.camera_pitch);
+            *context.fov = view.fov_degrees.to_radians();
+            *context.settings = view.settings;
+            Ok(format!("Vista de {} restaurada", path))
+        },
+    );
+}
+
+fn register_scene_commands(registry: &mut CommandRegistry<CommandContext>) {
+    registry.register(
+        "place",
+        "place <bloque> <x> <y> <z>",
+        "Coloca un bloque de 1x1x1 (ej: place stone 2 1 3)",
+        |context, args| {
+            let [name, coords @ ..] = args else {
+                return Err(CommandError::Usage);
+            };
+            if coords.len() != 3 {
+                return Err(CommandError::Usage);
+            }
+            let block_type = BlockType::from_name(name).ok_or_else(|| {
+                let names: Vec<&str> = BlockType::all().iter().map(BlockType::name).collect();
+                format!("bloque desconocido '{}'. Tipos: {}", name, names.join(", "))
+            })?;
+            let numbers = parse_numbers(coords)?;
+            let position = Vector3::new(numbers[0], numbers[1], numbers[2]);
+            if context
+                .scene
+                .place_block(block_type.to_block(position, 1.0))
+                .is_none()
+            {
+                return Err(format!(
+                    "la celda ({}, {}, {}) está ocupada",
+                    numbers[0], numbers[1], numbers[2]
+                )
+                .into());
+            }
+            context.scene_edited = true;
+            Ok(format!("Colocado: {}", block_type.display_name()))
+        },
+    );
+    registry.register(
+        "scene",
+        "scene <load|save> <archivo>",
+        "Carga o guarda la escena en texto",
+        |context, args| {
+            let [action, path] = args else {
+                return Err(CommandError::Usage);
+            };
+            match action.as_str() {
+                "load" => {
+                    let scene = load_scene(path).map_err(|err| err.to_string())?;
+                    let count = scene.object_count();
+                    context.scene.replace(scene);
+                    *context.scene_name = path.clone();
+                    context.scene_replaced = true;
+                    Ok(format!("{} objetos cargados de {}", count, path))
+                }
+                "save" => {
+                    save_scene(path, context.scene).map_err(|err| err.to_string())?;
+                    Ok(format!("Escena guardada en {}", path))
+                }
+                _ => Err(CommandError::Usage),
+            }
+        },
+    );
+}
+
+fn register_light_commands(registry: &mut CommandRegistry<CommandContext>) {
+    registry.register(
+        "light",
+        "light <add x y z r g b intensidad | list | remove n>",
+        "Administra las luces fijas de la escena",
+        |context, args| {
+            let Some((action, rest)) = args.split_first() else {
+                return Err(CommandError::Usage);
+            };
+            match action.as_str() {
+                "add" if rest.len() == 7 => {
+                    let n = parse_numbers(rest)?;
+                    context.fixed_lights.push(Light::new(
+                        Vector3::new(n[0], n[1], n[2]),
+                        Vector3::new(n[3], n[4], n[5]),
+                        n[6],
+                    ));
+                    Ok(format!("Luz {} agregada", context.fixed_lights.len()))
+                }
+                "list" if rest.is_empty() => Ok(context
+                    .fixed_lights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, light)| {
+                        format!(
+                            "{}: pos ({}, {}, {}) color ({}, {}, {}) intensidad {}",
+                            i + 1,
+                            light.position.x,
+                            light.position.y,
+                            light.position.z,
+                            light.color.x,
+                            light.color.y,
+                            light.color.z,
+                            light.intensity
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")),
+                "remove" if rest.len() == 1 => {
+                    let index = rest[0]
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&n| n >= 1 && n <= context.fixed_lights.len())
+                        .ok_or_else(|| format!("no hay luz '{}'", rest[0]))?;
+                    context.fixed_lights.remove(index - 1);
+                    Ok(format!("Luz {} quitada", index))
+                }
+                _ => Err(CommandError::Usage),
+            }
+        },
+    );
+}
+
+/// Opciones de `set`, con el nombre de `settings.toml` cuando existe
+const SET_OPTIONS: &str = "shadows, soft_shadows, light_radius, reflections, refractions, ambient_occlusion (ao), ao_jitter, \
+    light_portals, portal_strength, max_depth, russian_roulette (rr), bilinear_filtering, \
+    half_resolution, max_distance, \
+    ray_epsilon, min_specular, ambient_strength, light_attenuation, tile_size, \
+    samples_per_pixel (spp), accumulate, adaptive_sampling, adaptive_threshold, adaptive_max_spp, motion_blur, \
+    motion_blur_shutter, motion_blur_samples, god_rays, god_ray_steps, god_ray_density, god_ray_distance, \
+    sky_model, turbidity, star_density, star_brightness, toon, toon_bands, toon_outline, \
+    cost_view_max, fog_density, fog_start, fog_color (horizon o r,g,b), preset (fast/balanced/pretty), tone_mapping, exposure_ev, \
+    auto_exposure, bloom, denoise, denoise_strength, denoise_passes, fxaa, dither, capture_hud, \
+    hdr_capture (hdr/brackets), multithread, time_of_day, day_speed, day_paused, tnt_radius";
+
+fn set_command(context: &mut CommandContext, args: &[String]) -> CommandResult {
+    let [name, value] = args else {
+        return if args.is_empty() {
+            Ok(format!("Opciones: {}", SET_OPTIONS))
+        } else {
+            Err(CommandError::Usage)
+        };
+    };
+    let settings = &mut *context.settings;
+    match name.as_str() {
+        "shadows" => settings.render.shadows = parse_bool(value)?,
+        "soft_shadows" => settings.render.soft_shadows = parse_bool(value)?,
+        "light_radius" => settings.render.light_radius = parse_number(value)?,
+        "reflections" => settings.render.reflections = parse_bool(value)?,
+        "refractions" => settings.render.refractions = parse_bool(value)?,
+        "ambient_occlusion" | "ao" => settings.render.ambient_occlusion = parse_bool(value)?,
+        "ao_jitter" => settings.render.ao_jitter = parse_bool(value)?,
+        "light_portals" => settings.render.light_portals = parse_bool(value)?,
+        "portal_strength" => settings.render.portal_strength = parse_number(value)?,
+        "max_depth" => {
+            settings.render.max_depth = value
+                .parse()
+                .map_err(|_| format!("profundidad inválida '{}'", value))?
+        }
+        "russian_roulette" | "rr" => settings.render.russian_roulette = parse_bool(value)?,
+        "bilinear_filtering" => settings.render.bilinear_filtering = parse_bool(value)?,
+        "half_resolution" => settings.render.half_resolution = parse_bool(value)?,
+        "max_distance" => settings.render.max_distance = parse_number(value)?,
+        "ray_epsilon" => settings.render.ray_epsilon = parse_number(value)?,
+        "min_specular" => settings.render.min_specular = parse_number(value)?,
+        "ambient_strength" => settings.render.ambient_strength = parse_number(value)?,
+        "light_attenuation" => settings.render.light_attenuation = parse_number(value)?,
+        "tile_size" => {
+            settings.render.tile_size = value
+                .parse()
+                .ok()
+                .filter(|&size| size > 0)
+                .ok_or_else(|| format!("tamaño de tile inválido '{}'", value))?
+        }
+        "samples_per_pixel" | "spp" => {
+            settings.render.samples_per_pixel = value
+                .parse()
+                .ok()
+                .filter(|&samples| RenderSettings::is_valid_samples_per_pixel(samples))
+                .ok_or_else(|| format!("muestras por pixel inválidas '{}' (1, 2, 4 u 8)", value))?
+        }
+        "accumulate" => settings.render.accumulate = parse_bool(value)?,
+        "adaptive_sampling" => settings.render.adaptive.enabled = parse_bool(value)?,
+        "adaptive_threshold" => {
+            settings.render.adaptive.threshold = Some(parse_number(value)?)
+                .filter(|&threshold| threshold > 0.0)
+                .ok_or_else(|| format!("umbral inválido '{}'", value))?
+        }
+        "adaptive_max_spp" => {
+            settings.render.adaptive.max_spp = value
+                .parse()
+                .ok()
+                .filter(|&samples| samples > 0)
+                .ok_or_else(|| format!("muestras máximas inválidas '{}'", value))?
+        }
+        "motion_blur" => settings.render.motion_blur.enabled = parse_bool(value)?,
+        "motion_blur_shutter" => {
+            settings.render.motion_blur.shutter = Some(parse_number(value)?)
+                .filter(|shutter| (0.0..=1.0).contains(shutter))
+                .ok_or_else(|| format!("obturador inválido '{}' (0 a 1)", value))?
+        }
+        "motion_blur_samples" => {
+            settings.render.motion_blur.samples = value
+                .parse()
+                .ok()
+                .filter(|&samples| samples > 0)
+                .ok_or_else(|| format!("cantidad de pasadas inválida '{}'", value))?
+        }
+        "god_rays" => settings.render.god_rays = parse_bool(value)?,
+        "god_ray_steps" => {
+            settings.render.god_ray_steps = value
+                .parse()
+                .ok()
+                .filter(|&steps| steps > 0)
+                .ok_or_else(|| format!("cantidad de pasos inválida '{}'", value))?
+        }
+        "god_ray_density" => settings.render.god_ray_density = parse_number(value)?,
+        "god_ray_distance" => settings.render.god_ray_distance = parse_number(value)?,
+        "sky_model" => {
+            settings.render.sky_model = SkyModel::from_name(value)
+                .ok_or_else(|| format!("cielo desconocido '{}' (procedural o gradient)", value))?
+        }
+        "turbidity" => {
+            settings.render.turbidity = Some(parse_number(value)?)
+                .filter(|&turbidity| turbidity > 0.0)
+                .ok_or_else(|| format!("turbidez inválida '{}'", value))?
+        }
+        "star_density" => {
+            settings.render.star_density = Some(parse_number(value)?)
+                .filter(|density| (0.0..=1.0).contains(density))
+                .ok_or_else(|| format!("densidad de estrellas inválida '{}' (0 a 1)", value))?
+        }
+        "star_brightness" => settings.render.star_brightness = parse_number(value)?,
+        "toon" => settings.render.toon.enabled = parse_bool(value)?,
+        "toon_bands" => {
+            settings.render.toon.bands = value
+                .parse()
+                .ok()
+                .filter(|&bands| bands > 0)
+                .ok_or_else(|| format!("cantidad de bandas inválida '{}'", value))?
+        }
+        "toon_outline" => {
+            settings.render.toon.outline_thickness = value
+                .parse()
+                .map_err(|_| format!("grosor de contorno inválido '{}'", value))?
+        }
+        "cost_view_max" => {
+            settings.render.cost_view_max = value
+                .parse()
+                .ok()
+                .filter(|&max| max > 0)
+                .ok_or_else(|| format!("máximo de pruebas inválido '{}'", value))?
+        }
+        "fog_density" => {
+            settings.render.fog.density = Some(parse_number(value)?)
+                .filter(|&density| density >= 0.0)
+                .ok_or_else(|| format!("densidad de niebla inválida '{}'", value))?
+        }
+        "fog_start" => settings.render.fog.start = parse_number(value)?,
+        "fog_color" => {
+            settings.render.fog.color = parse_fog_color(value)
+                .ok_or_else(|| format!("color de niebla inválido '{}' (horizon o r,g,b)", value))?
+        }
+        "preset" => QualityPreset::from_name(value)
+            .ok_or_else(|| format!("preset desconocido '{}'", value))?
+            .apply(settings),
+        "tone_mapping" => {
+            settings.tone_map.operator = ToneMapping::from_name(value)
+                .ok_or_else(|| format!("mapeo tonal desconocido '{}'", value))?
+        }
+        "exposure_ev" => settings.tone_map.exposure_ev = parse_number(value)?,
+        "auto_exposure" => settings.auto_exposure.enabled = parse_bool(value)?,
+        "bloom" => settings.bloom.enabled = parse_bool(value)?,
+        "denoise" => settings.denoise.enabled = parse_bool(value)?,
+        "denoise_strength" => settings.denoise.strength = parse_number(value)?,
+        "denoise_passes" => {
+            settings.denoise.passes = value
+                .parse()
+                .map_err(|_| format!("cantidad de pasadas inválida '{}'", value))?
+        }
+        "fxaa" => settings.fxaa = parse_bool(value)?,
+        "dither" => settings.dither = parse_bool(value)?,
+        "capture_hud" => settings.capture_hud = parse_bool(value)?,
+        "hdr_capture" => {
+            settings.hdr_capture = HdrCaptureFormat::from_name(value)
+                .ok_or_else(|| format!("formato de captura HDR desconocido '{}'", value))?
+        }
+        "multithread" => *context.use_multithreading = parse_bool(value)?,
+        "time_of_day" => settings.day.set_hour(parse_number(value)?),
+        "day_speed" => {
+            let speed = parse_number(value)?;
+            if speed <= 0.0 {
+                return Err(format!("la velocidad debe ser positiva, no {}", speed).into());
+            }
+            settings.day.speed = speed;
+        }
+        "day_paused" => settings.day.paused = parse_bool(value)?,
+        "tnt_radius" => settings.tnt_radius = parse_number(value)?.clamp(0.5, 16.0),
+        _ => {
+            return Err(format!("opción desconocida '{}'. Opciones: {}", name, SET_OPTIONS).into());
+        }
+    }
+    Ok(format!("{} = {}", name, value))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" | "1" | "si" | "sí" => Ok(true),
+        "off" | "false" | "0" | "no" => Ok(false),
+        _ => Err(format!("se esperaba on/off, no '{}'", value)),
+    }
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .map_err(|_| format!("número inválido '{}'", value))
+}
+
+fn parse_numbers(values: &[String]) -> Result<Vec<f32>, String> {
+    values.iter().map(|value| parse_number(value)).collect()
+}
@@ -0,0 +1,312 @@
+// day_cycle.rs - Ciclo día/noche: hora del día, cielo y tono de las luces
+use raylib::prelude::*;
+
+use crate::light::Light;
+
+/// Multiplicadores de velocidad disponibles en el panel de hora
+pub const DAY_SPEEDS: [f32; 4] = [0.5, 1.0, 10.0, 60.0];
+
+/// Horas de juego que pasan por minuto real a velocidad 1x (un día en 24 min)
+const HOURS_PER_MINUTE: f32 = 1.0;
+
+/// Cielo que ve el trazado: gradiente de horizonte a cenit más el disco del
+/// sol y el de la luna. Se recalcula desde `DayCycle` cada frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    pub horizon: Vector3,
+    pub zenith: Vector3,
+    /// Dirección hacia el sol (la luna está en la opuesta)
+    pub sun_direction: Vector3,
+    /// Color de cada disco; cero cuando está bajo el horizonte
+    pub sun_color: Vector3,
+    pub moon_color: Vector3,
+    /// Multiplicador de la luz ambiente de las superficies
+    pub ambient: f32,
+}
+
+impl Default for Sky {
+    /// Cielo del mediodía
+    fn default() -> Self {
+        DayCycle::default().sky()
+    }
+}
+
+/// Cómo se pinta el cielo de los rayos que no chocan con nada
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SkyModel {
+    /// Cielo analítico: gradiente según la altura del sol, disco con halo y
+    /// horizonte cálido al amanecer y al atardecer (ver `snell::sky_color`)
+    Procedural,
+    /// Gradiente de horizonte a cenit de la curva del día (el original)
+    Gradient,
+}
+
+impl SkyModel {
+    /// Nombre usado en el archivo de configuración
+    pub fn name(self) -> &'static str {
+        match self {
+            SkyModel::Procedural => "procedural",
+            SkyModel::Gradient => "gradient",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "procedural" => Some(SkyModel::Procedural),
+            "gradient" => Some(SkyModel::Gradient),
+            _ => None,
+        }
+    }
+}
+
+/// Radio angular de los discos, como coseno del ángulo
+const SUN_DISC_COS: f32 = 0.9994;
+const MOON_DISC_COS: f32 = 0.9996;
+
+impl Sky {
+    /// Color del cielo para un rayo que no golpeó nada
+    #[inline]
+    pub fn color(&self, dir: &Vector3) -> Vector3 {
+        let mut color = self.gradient(dir);
+
+        if dir.dot(self.sun_direction) > SUN_DISC_COS {
+            color += self.sun_color;
+        }
+        color + self.moon(dir)
+    }
+
+    /// Degradé del horizonte al cenit, sin los discos del sol y la luna
+    /// (la luz difusa del cielo)
+    #[inline]
+    pub fn gradient(&self, dir: &Vector3) -> Vector3 {
+        let t = (dir.y * 0.5 + 0.5).clamp(0.0, 1.0); // Mapear [-1,1] a [0,1]
+        self.horizon * (1.0 - t) + self.zenith * t
+    }
+
+    /// Disco de la luna en la dirección `dir` (cero fuera de él)
+    #[inline]
+    pub fn moon(&self, dir: &Vector3) -> Vector3 {
+        if -dir.dot(self.sun_direction) > MOON_DISC_COS {
+            self.moon_color
+        } else {
+            Vector3::zero()
+        }
+    }
+
+    /// El mismo cielo con el sol fijo en `direction` (el de un archivo de
+    /// escena); los discos se encienden según su nueva altura
+    pub fn with_sun_direction(self, direction: Vector3) -> Self {
+        let sun_direction = direction.normalized();
+        let (sun_color, moon_color) = disc_colors(sun_direction);
+        Self {
+            sun_direction,
+            sun_color,
+            moon_color,
+            ..self
+        }
+    }
+}
+
+/// Color del disco del sol y el de la luna para el sol en `sun_direction`:
+/// se apagan suavemente al cruzar el horizonte
+fn disc_colors(sun_direction: Vector3) -> (Vector3, Vector3) {
+    let sun_height = (sun_direction.y * 10.0).clamp(0.0, 1.0);
+    let moon_height = (-sun_direction.y * 10.0).clamp(0.0, 1.0);
+    (
+        Vector3::new(3.0, 2.8, 2.3) * sun_height,
+        Vector3::new(0.6, 0.65, 0.8) * moon_height,
+    )
+}
+
+/// Un punto de la curva del día; entre puntos se interpola linealmente
+struct SkyKey {
+    hour: f32,
+    horizon: Vector3,
+    zenith: Vector3,
+    /// Color (con intensidad) que multiplica a las luces fijas
+    light_tint: Vector3,
+    ambient: f32,
+}
+
+const fn key(
+    hour: f32,
+    horizon: [f32; 3],
+    zenith: [f32; 3],
+    tint: [f32; 3],
+    ambient: f32,
+) -> SkyKey {
+    SkyKey {
+        hour,
+        horizon: Vector3 {
+            x: horizon[0],
+            y: horizon[1],
+            z: horizon[2],
+        },
+        zenith: Vector3 {
+            x: zenith[0],
+            y: zenith[1],
+            z: zenith[2],
+        },
+        light_tint: Vector3 {
+            x: tint[0],
+            y: tint[1],
+            z: tint[2],
+        },
+        ambient,
+    }
+}
+
+/// Curva del día. De 9h a 15h se usa el cielo original de la escena.
+const SKY_KEYS: [SkyKey; 8] = [
+    key(
+        0.0,
+        [0.05, 0.07, 0.15],
+        [0.01, 0.02, 0.06],
+        [0.15, 0.18, 0.3],
+        0.3,
+    ),
+    key(
+        5.0,
+        [0.12, 0.12, 0.22],
+        [0.03, 0.05, 0.12],
+        [0.2, 0.22, 0.35],
+        0.35,
+    ),
+    key(
+        6.5,
+        [0.95, 0.55, 0.35],
+        [0.25, 0.35, 0.6],
+        [0.9, 0.65, 0.45],
+        0.7,
+    ),
+    key(
+        9.0,
+        [0.98, 0.92, 0.88],
+        [0.2, 0.4, 0.8],
+        [1.0, 1.0, 1.0],
+        1.0,
+    ),
+    key(
+        15.0,
+        [0.98, 0.92, 0.88],
+        [0.2, 0.4, 0.8],
+        [1.0, 1.0, 1.0],
+        1.0,
+    ),
+    key(
+        17.5,
+        [0.95, 0.5, 0.3],
+        [0.25, 0.3, 0.55],
+        [0.9, 0.6, 0.4],
+        0.7,
+    ),
+    key(
+        19.0,
+        [0.12, 0.12, 0.22],
+        [0.03, 0.05, 0.12],
+        [0.2, 0.22, 0.35],
+        0.35,
+    ),
+    key(
+        24.0,
+        [0.05, 0.07, 0.15],
+        [0.01, 0.02, 0.06],
+        [0.15, 0.18, 0.3],
+        0.3,
+    ),
+];
+
+/// Hora del día y cómo avanza. Se guarda en las preferencias.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DayCycle {
+    /// Hora en [0, 24)
+    pub hour: f32,
+    /// Multiplicador de velocidad (ver `DAY_SPEEDS`)
+    pub speed: f32,
+    /// Con el ciclo en pausa la hora solo cambia desde el panel
+    pub paused: bool,
+}
+
+impl Default for DayCycle {
+    /// Mediodía y en pausa, para que la escena se vea como siempre
+    fn default() -> Self {
+        Self {
+            hour: 12.0,
+            speed: 1.0,
+            paused: true,
+        }
+    }
+}
+
+impl DayCycle {
+    /// Avanza la hora `dt` segundos reales (si no está en pausa)
+    pub fn advance(&mut self, dt: f32) {
+        if !self.paused {
+            self.set_hour(self.hour + dt / 60.0 * HOURS_PER_MINUTE * self.speed);
+        }
+    }
+
+    pub fn set_hour(&mut self, hour: f32) {
+        self.hour = hour.rem_euclid(24.0);
+    }
+
+    /// Hora como texto, ej: "06:30"
+    pub fn clock(&self) -> String {
+        let minutes = (self.hour * 60.0) as u32;
+        format!("{:02}:{:02}", minutes / 60 % 24, minutes % 60)
+    }
+
+    /// Punto de la curva del día para la hora actual
+    fn sample(&self) -> (Vector3, Vector3, Vector3, f32) {
+        let hour = self.hour.rem_euclid(24.0);
+        let i = SKY_KEYS
+            .windows(2)
+            .position(|pair| hour <= pair[1].hour)
+            .unwrap_or(SKY_KEYS.len() - 2);
+        let (a, b) = (&SKY_KEYS[i], &SKY_KEYS[i + 1]);
+        let t = ((hour - a.hour) / (b.hour - a.hour)).clamp(0.0, 1.0);
+        (
+            a.horizon.lerp(b.horizon, t),
+            a.zenith.lerp(b.zenith, t),
+            a.light_tint.lerp(b.light_tint, t),
+            a.ambient + (b.ambient - a.ambient) * t,
+        )
+    }
+
+    /// Dirección del sol: sale por el este (+X) a las 6h, culmina a las 12h
+    /// algo inclinado hacia el sur (+Z) y se pone por el oeste a las 18h
+    pub fn sun_direction(&self) -> Vector3 {
+        let angle = (self.hour - 6.0) / 12.0 * std::f32::consts::PI;
+        Vector3::new(angle.cos(), angle.sin(), 0.35).normalized()
+    }
+
+    pub fn sky(&self) -> Sky {
+        let (horizon, zenith, _, ambient) = self.sample();
+        let sun_direction = self.sun_direction();
+        let (sun_color, moon_color) = disc_colors(sun_direction);
+        Sky {
+            horizon,
+            zenith,
+            sun_direction,
+            sun_color,
+            moon_color,
+            ambient,
+        }
+    }
+
+    /// Las luces fijas teñidas por la hora; las de los bloques no cambian
+    pub fn tint_lights(&self, lights: &[Light]) -> Vec<Light> {
+        let (_, _, tint, _) = self.sample();
+        lights
+            .iter()
+            .map(|light| {
+                let mut light = *light;
+                light.color *= tint;
+                light
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,198 @@
+// debug_stats.rs - Pantalla de depuración (F3): datos del frame ya reunidos
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::block_types::{ALL_BLOCK_TYPES, BlockType};
+use crate::bvh::BvhCounters;
+use crate::snell::RayCounts;
+use crate::tile_heatmap::TileTime;
+use crate::voxel_grid::GridCounters;
+
+/// Lo que el renderer informa de cada frame
+#[derive(Debug, Clone, Default)]
+pub struct RenderStats {
+    pub rays: RayCounts,
+    pub threads: usize,
+    pub tiles: usize,
+    /// Tiempo de cada tile (vacío en el camino de un solo hilo)
+    pub tile_times: Vec<TileTime>,
+}
+
+impl RenderStats {
+    pub fn tiles_per_thread(&self) -> usize {
+        if self.threads == 0 {
+            0
+        } else {
+            self.tiles.div_ceil(self.threads)
+        }
+    }
+}
+
+/// Datos de la pantalla de depuración. El loop principal los llena y el
+/// overlay solo les da formato.
+#[derive(Debug, Clone, Default)]
+pub struct DebugStats {
+    pub camera_pos: Vector3,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Nombre del tipo de bloque bajo la mira y su distancia
+    pub target: Option<(&'static str, f32)>,
+    pub render: RenderStats,
+    /// Bytes de texturas (CPU, GPU)
+    pub texture_memory: (usize, usize),
+    /// Bloques de la escena por tipo; los materiales que no corresponden a
+    /// ningún tipo se cuentan aparte
+    pub block_counts: Vec<(&'static str, usize)>,
+    pub custom_blocks: usize,
+    /// Ediciones en el lugar y reconstrucciones de la grilla de bloques
+    pub grid: GridCounters,
+    /// Reajustes y reconstrucciones de la BVH
+    pub bvh: BvhCounters,
+}
+
+impl DebugStats {
+    /// Cuenta los bloques de la escena por tipo
+    pub fn count_blocks(&mut self, scene: &[Block]) {
+        let mut counts = [0usize; ALL_BLOCK_TYPES.len()];
+        self.custom_blocks = 0;
+        for block in scene {
+            match BlockType::identify(block) {
+                Some(block_type) => {
+                    if let Some(i) = ALL_BLOCK_TYPES.iter().position(|t| *t == block_type) {
+                        counts[i] += 1;
+                    }
+                }
+                None => self.custom_blocks += 1,
+            }
+        }
+        self.block_counts = ALL_BLOCK_TYPES
+            .iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(block_type, count)| (block_type.display_name(), count))
+            .collect();
+    }
+
+    /// Líneas de texto del overlay
+    pub fn lines(&self) -> Vec<String> {
+        let (yaw, pitch) = (self.yaw.to_degrees(), self.pitch.to_degrees());
+        let mut lines = vec![
+            format!(
+                "Cámara: ({:.2}, {:.2}, {:.2}) bloque ({}, {}, {})",
+                self.camera_pos.x,
+                self.camera_pos.y,
+                self.camera_pos.z,
+                self.camera_pos.x.round() as i32,
+                self.camera_pos.y.round() as i32,
+                self.camera_pos.z.round() as i32
+            ),
+            format!(
+                "Yaw {:.1}° Pitch {:.1}° mirando al {}",
+                yaw.rem_euclid(360.0),
+                pitch,
+                cardinal(self.yaw)
+            ),
+            match self.target {
+                Some((name, distance)) => format!("Mira: {} a {:.2}", name, distance),
+                None => "Mira: cielo".to_string(),
+            },
+        ];
+
+        let rays = &self.render.rays;
+        lines.push(format!(
+            "Rayos: {} (primarios {}, sombra {}, reflejo {}, refracción {}, AO {})",
+            rays.total(),
+            rays.primary,
+            rays.shadow,
+            rays.reflection,
+            rays.refraction,
+            rays.ambient_occlusion
+        ));
+        lines.push(format!(
+            "Rebotes: profundidad media {:.2} | cortados por ruleta {}",
+            rays.average_bounce_depth(),
+            rays.roulette
+        ));
+        lines.push(format!(
+            "Pruebas de intersección: {} ({:.1} por rayo)",
+            rays.intersection_tests,
+            rays.intersection_tests as f32 / rays.total().max(1) as f32
+        ));
+        lines.push(format!(
+            "Grilla: {} ediciones en el lugar ({} celdas), {} reconstrucciones",
+            self.grid.updates, self.grid.cells_touched, self.grid.rebuilds
+        ));
+        lines.push(format!(
+            "BVH: {} reajustes, {} reconstrucciones",
+            self.bvh.refits, self.bvh.rebuilds
+        ));
+        lines.push(if self.render.threads > 0 {
+            format!(
+                "Hilos: {} | Tiles: {} ({} por hilo)",
+                self.render.threads,
+                self.render.tiles,
+                self.render.tiles_per_thread()
+            )
+        } else {
+            "Hilos: 1 (sin tiles)".to_string()
+        });
+
+        let (cpu, gpu) = self.texture_memory;
+        lines.push(format!(
+            "Texturas: {:.1} MB CPU | {:.1} MB GPU",
+            cpu as f32 / (1024.0 * 1024.0),
+            gpu as f32 / (1024.0 * 1024.0)
+        ));
+
+        let mut counts: Vec<String> = self
+            .block_counts
+            .iter()
+            .map(|(name, count)| format!("{} {}", name, count))
+            .collect();
+        if self.custom_blocks > 0 {
+            counts.push(format!("Otros {}", self.custom_blocks));
+        }
+        // Varias líneas cortas para que no se salga de la ventana
+        for chunk in counts.chunks(5) {
+            lines.push(chunk.join(" | "));
+        }
+        lines
+    }
+
+    /// Dibuja las líneas sobre un fondo oscuro
+    pub fn draw(&self, d: &mut RaylibDrawHandle, x: i32, y: i32) {
+        let lines = self.lines();
+        let line_height = 16;
+        let width = lines
+            .iter()
+            .map(|line| d.measure_text(line, 14))
+            .max()
+            .unwrap_or(0);
+        d.draw_rectangle(
+            x,
+            y,
+            width + 16,
+            line_height * lines.len() as i32 + 10,
+            Color::new(0, 0, 0, 160),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            d.draw_text(
+                line,
+                x + 8,
+                y + 5 + line_height * i as i32,
+                14,
+                Color::WHITE,
+            );
+        }
+    }
+}
+
+/// Punto cardinal hacia el que mira la cámara: yaw 0 es +X (este) y
+/// +Z es el sur, como en `Face`
+fn cardinal(yaw: f32) -> &'static str {
+    const NAMES: [&str; 8] = [
+        "este", "sureste", "sur", "suroeste", "oeste", "noroeste", "norte", "noreste",
+    ];
+    let octant = (yaw.rem_euclid(std::f32::consts::TAU) / std::f32::consts::FRAC_PI_4).round();
+    NAMES[octant as usize % 8]
+}
@@ -0,0 +1,146 @@
+// denoise.rs - Filtro de ruido guiado por la geometría (à-trous)
+use std::time::{Duration, Instant};
+
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::gbuffer::GBufferSample;
+use crate::post::luminance;
+
+/// Pesos del spline B3 para los desplazamientos 0, ±1 y ±2 del núcleo 5x5
+const KERNEL: [f32; 3] = [3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// Diferencia de profundidad tolerada entre vecinos, relativa a la
+/// distancia del pixel y a la separación de la pasada
+const DEPTH_TOLERANCE: f32 = 0.02;
+
+/// Exponente del coseno entre normales: alto para que las caras de un
+/// bloque no se mezclen con las de al lado
+const NORMAL_POWER: f32 = 64.0;
+
+/// Diferencia de luminancia tolerada con fuerza 1 en la primera pasada
+const LUMINANCE_TOLERANCE: f32 = 0.25;
+
+/// Parámetros del filtro de ruido
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DenoiseSettings {
+    pub enabled: bool,
+    /// Cuánta diferencia de brillo se promedia entre vecinos: más suaviza
+    /// más ruido pero también más detalle de las texturas
+    pub strength: f32,
+    /// Pasadas del filtro; cada una duplica la separación de los vecinos
+    pub passes: u32,
+}
+
+impl Default for DenoiseSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strength: 1.0,
+            passes: 3,
+        }
+    }
+}
+
+/// Filtra el ruido del buffer HDR (sombras suaves, AO, reflejos ásperos)
+/// antes del mapeo tonal. Cada pasada promedia un núcleo 5x5 con huecos
+/// (à-trous) y solo mezcla vecinos con profundidad y normal parecidas, así
+/// los bordes de los bloques quedan nítidos; el peso por luminancia protege
+/// en parte el detalle de las texturas. Necesita el G-buffer del frame (el
+/// renderer lo guarda con `record_gbuffer`). Devuelve cuánto tardó, o None
+/// si está apagado.
+pub fn apply_denoise(
+    framebuffer: &mut Framebuffer,
+    settings: &DenoiseSettings,
+) -> Option<Duration> {
+    if !settings.enabled || settings.passes == 0 || settings.strength <= 0.0 {
+        return None;
+    }
+    let start = Instant::now();
+    let width = framebuffer.width as usize;
+    let height = framebuffer.height as usize;
+    let (hdr, gbuffer) = framebuffer.hdr_with_gbuffer();
+
+    let mut current = hdr.to_vec();
+    let mut next = vec![Vector3::zero(); current.len()];
+    for pass in 0..settings.passes {
+        let step = 1usize << pass;
+        // La tolerancia de brillo se achica en cada pasada para no borronear
+        // lo que las primeras ya dejaron parejo
+        let luminance_tolerance = LUMINANCE_TOLERANCE * settings.strength / step as f32;
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                next[index] = filter_pixel(
+                    &current,
+                    gbuffer,
+                    (x, y),
+                    (width, height),
+                    step,
+                    luminance_tolerance,
+                );
+            }
+        }
+        std::mem::swap(&mut current, &mut next);
+    }
+    hdr.copy_from_slice(&current);
+    Some(start.elapsed())
+}
+
+/// Promedio ponderado de los vecinos de `(x, y)` separados `step` pixeles
+fn filter_pixel(
+    colors: &[Vector3],
+    gbuffer: &[GBufferSample],
+    (x, y): (usize, usize),
+    (width, height): (usize, usize),
+    step: usize,
+    luminance_tolerance: f32,
+) -> Vector3 {
+    let index = y * width + x;
+    let center = &gbuffer[index];
+    let color = colors[index];
+    // El cielo no tiene ruido ni geometría con la que comparar
+    if !center.distance.is_finite() {
+        return color;
+    }
+    let center_luminance = luminance(color);
+    let depth_tolerance = DEPTH_TOLERANCE * center.distance * step as f32;
+
+    let mut sum = Vector3::zero();
+    let mut total = 0.0;
+    for dy in -2i32..=2 {
+        let ny = y as i64 + (dy as i64) * step as i64;
+        if ny < 0 || ny >= height as i64 {
+            continue;
+        }
+        for dx in -2i32..=2 {
+            let nx = x as i64 + (dx as i64) * step as i64;
+            if nx < 0 || nx >= width as i64 {
+                continue;
+            }
+            let neighbor_index = ny as usize * width + nx as usize;
+            let neighbor = &gbuffer[neighbor_index];
+            if !neighbor.distance.is_finite() {
+                continue;
+            }
+            let neighbor_color = colors[neighbor_index];
+
+            let kernel = KERNEL[dx.unsigned_abs() as usize] * KERNEL[dy.unsigned_abs() as usize];
+            let depth = (-(neighbor.distance - center.distance).abs() / depth_tolerance).exp();
+            let normal = center
+                .normal
+                .dot(neighbor.normal)
+                .max(0.0)
+                .powf(NORMAL_POWER);
+            let brightness =
+                (-(luminance(neighbor_color) - center_luminance).abs() / luminance_tolerance).exp();
+            let weight = kernel * depth * normal * brightness;
+            sum += neighbor_color * weight;
+            total += weight;
+        }
+    }
+    // El propio pixel siempre pesa, así `total` nunca es cero
+    sum / total
+}
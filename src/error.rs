@@ -0,0 +1,97 @@
+// error.rs - Error de la aplicación que agrupa los errores de cada módulo
+use std::fmt;
+
+use crate::config::ConfigError;
+use crate::framebuffer::ExportError;
+use crate::scene_file::SceneError;
+use crate::textures::TextureError;
+
+/// Cualquier error que puede cortar un modo de la aplicación. El modo sin
+/// ventana lo convierte en código de salida con `exit_code` y el interactivo
+/// lo muestra como notificación.
+#[derive(Debug)]
+pub enum AppError {
+    Config(ConfigError),
+    Scene(SceneError),
+    /// Texturas que no se pudieron cargar (se intentan todas antes de fallar)
+    Textures(Vec<TextureError>),
+    Export(ExportError),
+    /// Advertencias de material con `--strict` (cuántas hubo)
+    StrictMaterials(usize),
+}
+
+impl AppError {
+    /// Código de salida del proceso: 1 para `--strict`, 2 para opciones
+    /// inválidas y uno distinto por etapa para el resto, así los scripts
+    /// saben qué falló
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Config(_) => 2,
+            AppError::Scene(_) => 3,
+            AppError::Textures(_) => 4,
+            AppError::Export(_) => 5,
+            AppError::StrictMaterials(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Config(err) => err.fmt(f),
+            AppError::Scene(err) => err.fmt(f),
+            AppError::Textures(errors) => {
+                let list: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+                write!(f, "{}", list.join("; "))
+            }
+            AppError::Export(err) => err.fmt(f),
+            AppError::StrictMaterials(count) => {
+                write!(f, "{} advertencias de material con --strict", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Config(err) => Some(err),
+            AppError::Scene(err) => Some(err),
+            AppError::Textures(errors) => errors
+                .first()
+                .map(|err| err as &(dyn std::error::Error + 'static)),
+            AppError::Export(err) => Some(err),
+            AppError::StrictMaterials(_) => None,
+        }
+    }
+}
+
+impl From<ConfigError> for AppError {
+    fn from(err: ConfigError) -> Self {
+        AppError::Config(err)
+    }
+}
+
+impl From<SceneError> for AppError {
+    fn from(err: SceneError) -> Self {
+        AppError::Scene(err)
+    }
+}
+
+impl From<TextureError> for AppError {
+    fn from(err: TextureError) -> Self {
+        AppError::Textures(vec![err])
+    }
+}
+
+impl From<Vec<TextureError>> for AppError {
+    fn from(errors: Vec<TextureError>) -> Self {
+        AppError::Textures(errors)
+    }
+}
+
+impl From<ExportError> for AppError {
+    fn from(err: ExportError) -> Self {
+        AppError::Export(err)
+    }
+}
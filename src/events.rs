@@ -1,7 +1,10 @@
 use raylib::prelude::*;
 
+use crate::keybinds::{Action, InputMap};
+use crate::notifications::{Notifications, Severity};
+
 pub fn handle_camera_input(
-    rl: &RaylibHandle,
+    input: &InputMap,
     pos: &mut Vector3,
     yaw: &mut f32,
     pitch: &mut f32,
@@ -13,43 +16,47 @@ pub fn handle_camera_input(
     let forward = Vector3::new(yaw.cos(), 0.0, yaw.sin());
     let right = Vector3::new(-yaw.sin(), 0.0, yaw.cos());
 
-    // Movimiento con WASD
-    if rl.is_key_down(KeyboardKey::KEY_W) {
+    // Movimiento (WASD por defecto)
+    if input.is_down(Action::MoveForward) {
         *pos += forward * move_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_S) {
+    if input.is_down(Action::MoveBack) {
         *pos -= forward * move_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_A) {
+    if input.is_down(Action::StrafeLeft) {
         *pos -= right * move_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_D) {
+    if input.is_down(Action::StrafeRight) {
         *pos += right * move_speed;
     }
 
     // Subir / Bajar
-    if rl.is_key_down(KeyboardKey::KEY_SPACE) {
+    if input.is_down(Action::MoveUp) {
         pos.y += move_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) {
+    if input.is_down(Action::MoveDown) {
         pos.y -= move_speed;
     }
 
     // Rotación con flechas
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+    if input.is_down(Action::TurnRight) {
         *yaw += rot_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_LEFT) {
+    if input.is_down(Action::TurnLeft) {
         *yaw -= rot_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_UP) {
+    if input.is_down(Action::LookUp) {
         *pitch += rot_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_DOWN) {
+    if input.is_down(Action::LookDown) {
         *pitch -= rot_speed;
     }
 
-    // Limitar pitch para no voltear de más
+    clamp_pitch(pitch);
+}
+
+/// Limitar pitch para no voltear de más
+pub fn clamp_pitch(pitch: &mut f32) {
     let limit = std::f32::consts::FRAC_PI_2 - 0.1;
     if *pitch > limit {
         *pitch = limit;
@@ -58,3 +65,116 @@ pub fn handle_camera_input(
         *pitch = -limit;
     }
 }
+
+/// Control que se lee (el primero conectado)
+pub const GAMEPAD: i32 = 0;
+
+/// Segundos que se muestra el aviso de conexión del control
+const GAMEPAD_NOTICE_SECONDS: f32 = 3.0;
+
+/// Sensibilidad del control
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GamepadSettings {
+    /// Por debajo de este valor el stick se considera en reposo
+    pub dead_zone: f32,
+    /// Exponente de respuesta: 1 es lineal, más alto da precisión cerca del centro
+    pub response_curve: f32,
+    /// Unidades por segundo con el stick a fondo
+    pub move_speed: f32,
+    /// Radianes por segundo con el stick a fondo
+    pub look_speed: f32,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.15,
+            response_curve: 2.0,
+            move_speed: 6.0,
+            look_speed: 1.8,
+        }
+    }
+}
+
+impl GamepadSettings {
+    /// Aplica zona muerta y curva de respuesta a un eje en -1..1
+    fn shape(&self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.dead_zone {
+            return 0.0;
+        }
+        let scaled = ((magnitude - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0);
+        scaled.powf(self.response_curve) * value.signum()
+    }
+}
+
+/// Detecta conexión y desconexión del control
+#[derive(Default)]
+pub struct GamepadState {
+    name: Option<String>,
+}
+
+impl GamepadState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Revisa si el control sigue conectado y avisa al conectarlo o
+    /// desconectarlo. Devuelve true si está disponible.
+    pub fn update(&mut self, rl: &RaylibHandle, notifications: &mut Notifications) -> bool {
+        let available = rl.is_gamepad_available(GAMEPAD);
+        if available && self.name.is_none() {
+            let name = rl
+                .get_gamepad_name(GAMEPAD)
+                .unwrap_or_else(|| "Control".to_string());
+            notifications.notify(
+                format!("Control conectado: {}", name),
+                Severity::Info,
+                GAMEPAD_NOTICE_SECONDS,
+            );
+            self.name = Some(name);
+        } else if !available && self.name.is_some() {
+            notifications.notify(
+                "Control desconectado",
+                Severity::Warning,
+                GAMEPAD_NOTICE_SECONDS,
+            );
+            self.name = None;
+        }
+        available
+    }
+}
+
+/// Stick izquierdo mueve, stick derecho gira y los gatillos suben/bajan.
+/// Las velocidades se escalan por `dt` para no depender de los FPS.
+pub fn handle_gamepad_input(
+    rl: &RaylibHandle,
+    settings: &GamepadSettings,
+    pos: &mut Vector3,
+    yaw: &mut f32,
+    pitch: &mut f32,
+    dt: f32,
+) {
+    let axis = |axis| settings.shape(rl.get_gamepad_axis_movement(GAMEPAD, axis));
+
+    let forward = Vector3::new(yaw.cos(), 0.0, yaw.sin());
+    let right = Vector3::new(-yaw.sin(), 0.0, yaw.cos());
+    let step = settings.move_speed * dt;
+
+    // El eje Y del stick es negativo hacia adelante
+    *pos += forward * (-axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y) * step);
+    *pos += right * (axis(GamepadAxis::GAMEPAD_AXIS_LEFT_X) * step);
+
+    // Los gatillos van de -1 (suelto) a 1 (a fondo)
+    let trigger = |axis| settings.shape((rl.get_gamepad_axis_movement(GAMEPAD, axis) + 1.0) * 0.5);
+    pos.y += (trigger(GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER)
+        - trigger(GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER))
+        * step;
+
+    let turn = settings.look_speed * dt;
+    *yaw += axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_X) * turn;
+    *pitch -= axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_Y) * turn;
+    clamp_pitch(pitch);
+}
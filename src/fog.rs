@@ -0,0 +1,128 @@
+// fog.rs - Niebla exponencial por distancia
+use raylib::prelude::*;
+
+use crate::day_cycle::Sky;
+
+/// Niebla que tiñe las superficies lejanas hacia un color. Con densidad 0
+/// no cambia nada.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Fog {
+    /// Cuánto se espesa por unidad de distancia
+    pub density: f32,
+    /// Distancia desde la que empieza; más cerca no hay niebla
+    pub start: f32,
+    /// Color de la niebla; None usa el horizonte del cielo de la hora
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::option_vec3"))]
+    pub color: Option<Vector3>,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            density: 0.0,
+            start: 0.0,
+            color: None,
+        }
+    }
+}
+
+impl Fog {
+    pub fn new(density: f32, start: f32, color: Option<Vector3>) -> Self {
+        Self {
+            density,
+            start,
+            color,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.density > 0.0
+    }
+
+    /// Fracción de niebla a `distance` del origen del rayo:
+    /// `1 - exp(-densidad * (distancia - inicio))`
+    pub fn amount(&self, distance: f32) -> f32 {
+        let inside = (distance - self.start).max(0.0);
+        1.0 - (-self.density * inside).exp()
+    }
+
+    /// Mezcla `color`, visto a `distance`, con el color de la niebla
+    pub fn apply(&self, color: Vector3, distance: f32, sky: &Sky) -> Vector3 {
+        if !self.is_enabled() {
+            return color;
+        }
+        let fog_color = self.color.unwrap_or(sky.horizon);
+        color.lerp(fog_color, self.amount(distance))
+    }
+}
+
+/// Lee el color de niebla de `settings.toml` o de la consola: "horizon"
+/// (el del cielo) o "r,g,b"
+pub fn parse_fog_color(text: &str) -> Option<Option<Vector3>> {
+    if text.eq_ignore_ascii_case("horizon") {
+        return Some(None);
+    }
+    let parts: Vec<f32> = text
+        .split(',')
+        .map(|part| part.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match parts[..] {
+        [r, g, b] => Some(Some(Vector3::new(r, g, b))),
+        _ => None,
+    }
+}
+
+/// Inverso de `parse_fog_color`
+pub fn fog_color_to_string(color: Option<Vector3>) -> String {
+    match color {
+        Some(color) => format!("{},{},{}", color.x, color.y, color.z),
+        None => String::from("horizon"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_follows_the_exponential_from_the_start() {
+        let fog = Fog::new(0.1, 10.0, None);
+        assert_eq!(fog.amount(0.0), 0.0);
+        assert_eq!(fog.amount(10.0), 0.0);
+        assert!((fog.amount(20.0) - (1.0 - (-1.0f32).exp())).abs() < 1e-6);
+        assert!(fog.amount(30.0) > fog.amount(20.0));
+        assert!(fog.amount(1e6) <= 1.0);
+    }
+
+    #[test]
+    fn zero_density_is_a_no_op() {
+        let fog = Fog::new(0.0, 0.0, Some(Vector3::new(1.0, 0.0, 0.0)));
+        let color = Vector3::new(0.2, 0.4, 0.6);
+        assert!(!fog.is_enabled());
+        assert_eq!(fog.apply(color, 1e4, &Sky::default()), color);
+    }
+
+    #[test]
+    fn default_color_is_the_horizon() {
+        let sky = Sky::default();
+        let color = Vector3::new(0.2, 0.4, 0.6);
+        let far = Fog::new(1.0, 0.0, None).apply(color, 100.0, &sky);
+        assert!((far - sky.horizon).length() < 1e-4, "{:?}", far);
+        let red = Vector3::new(1.0, 0.0, 0.0);
+        let far = Fog::new(1.0, 0.0, Some(red)).apply(color, 100.0, &sky);
+        assert!((far - red).length() < 1e-4, "{:?}", far);
+    }
+
+    #[test]
+    fn color_text_round_trips() {
+        for color in [None, Some(Vector3::new(0.5, 0.25, 1.0))] {
+            assert_eq!(parse_fog_color(&fog_color_to_string(color)), Some(color));
+        }
+        assert_eq!(parse_fog_color("Horizon"), Some(None));
+        assert_eq!(parse_fog_color("1,2"), None);
+        assert_eq!(parse_fog_color("rojo"), None);
+    }
+}
@@ -0,0 +1,55 @@
+// frame_limiter.rs - Límite de FPS (--fps-cap, F12) aplicado con raylib
+use raylib::prelude::*;
+
+/// Límites que recorre la tecla; 0 es sin límite
+pub const FPS_CAPS: [u32; 4] = [30, 60, 120, 0];
+pub const DEFAULT_FPS_CAP: u32 = 60;
+
+/// Sin límite, los frames que no renderizan (pausa, ventana minimizada) solo
+/// atienden la entrada; se esperan igual a este ritmo para no ocupar un
+/// núcleo entero dando vueltas.
+const IDLE_FPS: u32 = 30;
+
+/// Límite elegido y el que tiene raylib ahora, para llamarlo solo al cambiar
+pub struct FrameLimiter {
+    cap: u32,
+    applied: Option<u32>,
+}
+
+impl FrameLimiter {
+    pub fn new(cap: u32) -> Self {
+        Self { cap, applied: None }
+    }
+
+    /// Límite elegido (0 = sin límite)
+    pub fn cap(&self) -> u32 {
+        self.cap
+    }
+
+    /// Pasa al siguiente límite de `FPS_CAPS`. Un límite de la
+    /// configuración que no está en la lista sigue con el primero.
+    pub fn cycle(&mut self) {
+        let next = FPS_CAPS
+            .iter()
+            .position(|&cap| cap == self.cap)
+            .map_or(0, |i| (i + 1) % FPS_CAPS.len());
+        self.cap = FPS_CAPS[next];
+    }
+
+    pub fn label(&self) -> String {
+        match self.cap {
+            0 => "sin límite".to_string(),
+            cap => format!("{} FPS", cap),
+        }
+    }
+
+    /// Ajusta el objetivo de raylib para el frame en curso. Llamar antes de
+    /// dibujar: la espera ocurre al terminar el dibujo.
+    pub fn apply(&mut self, rl: &mut RaylibHandle, idle: bool) {
+        let target = if self.cap == 0 && idle { IDLE_FPS } else { self.cap };
+        if self.applied != Some(target) {
+            rl.set_target_fps(target);
+            self.applied = Some(target);
+        }
+    }
+}
@@ -1,9 +1,160 @@
 use raylib::prelude::*;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::gbuffer::{GBufferSample, ViewMode, debug_color, sample_count_color};
+use crate::material::{color_to_vector3, vector3_to_color};
+use crate::post::PostProcess;
+use crate::render_settings::RenderSettings;
+use crate::tonemap::{ToneMapSettings, tone_map};
+
+/// Por qué no se pudo exportar una imagen (capturas, grabación, render sin
+/// ventana)
+#[derive(Debug)]
+pub enum ExportError {
+    /// No se pudo crear o escribir el archivo (o su directorio)
+    Io { path: PathBuf, source: io::Error },
+    /// raylib no pudo codificar la imagen en el formato de la extensión
+    Encode(PathBuf),
+}
+
+impl ExportError {
+    /// Error de E/S sobre `path`
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        ExportError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ExportError::Encode(path) => {
+                write!(f, "raylib no pudo exportar la imagen {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Io { source, .. } => Some(source),
+            ExportError::Encode(_) => None,
+        }
+    }
+}
+
+/// Umbrales de la matriz de Bayer 4x4, en fracciones de un nivel de 8 bits
+const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.5 / 16.0, 8.5 / 16.0, 2.5 / 16.0, 10.5 / 16.0],
+    [12.5 / 16.0, 4.5 / 16.0, 14.5 / 16.0, 6.5 / 16.0],
+    [3.5 / 16.0, 11.5 / 16.0, 1.5 / 16.0, 9.5 / 16.0],
+    [15.5 / 16.0, 7.5 / 16.0, 13.5 / 16.0, 5.5 / 16.0],
+];
+
+/// Ajustes que cambian el color de toda la imagen sin tocar el HDR
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageLook {
+    pub tone_map: ToneMapSettings,
+    pub dither: bool,
+    pub post: PostProcess,
+}
+
+/// Región rectangular del buffer que cambió y debe subirse a la GPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DirtyRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[inline]
+    pub fn area(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// Se solapan o se tocan por un borde (unirlas no agrega pixeles sueltos)
+    fn touches(&self, other: &DirtyRect) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    fn union(&self, other: &DirtyRect) -> DirtyRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        DirtyRect::new(x, y, right - x, bottom - y)
+    }
+}
+
+/// Une los rectángulos que se solapan o tocan hasta que no quede ningún par
+/// unible (por ejemplo, tiles vecinos de una misma fila)
+pub fn merge_dirty_rects(rects: &mut Vec<DirtyRect>) {
+    rects.retain(|r| r.area() > 0);
+    let mut merged = true;
+    while merged {
+        merged = false;
+        'outer: for i in 0..rects.len() {
+            for j in (i + 1)..rects.len() {
+                if rects[i].touches(&rects[j]) {
+                    rects[i] = rects[i].union(&rects[j]);
+                    rects.swap_remove(j);
+                    merged = true;
+                    break 'outer;
+                }
+            }
+        }
+    }
+}
 
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
+    /// Buffer trasero: aquí escriben el render, el post-procesado y los overlays
     buffer: Vec<u32>,
+    /// Buffer delantero: el último frame terminado, que es el que se sube a
+    /// la GPU. `swap` intercambia ambos al completar un frame.
+    front: Vec<u32>,
+    /// Regiones escritas en el buffer trasero durante el frame en curso
+    dirty: Vec<DirtyRect>,
+    /// Regiones del buffer delantero que la textura todavía no tiene
+    front_dirty: Vec<DirtyRect>,
+    /// Líneas dibujadas encima de la imagen (cruz, contornos) en el frame en
+    /// curso y en el presentado: el próximo frame tiene que borrarlas
+    overlays: Vec<DirtyRect>,
+    presented_overlays: Vec<DirtyRect>,
+    /// Ajustes de color del frame presentado (ver `set_look`)
+    look: Option<ImageLook>,
+    /// Color lineal sin recortar que escriben los renders; `resolve` lo
+    /// convierte a `buffer` aplicando el mapeo tonal.
+    hdr: Vec<Vector3>,
+    /// Geometría del rayo primario; se escribe en las vistas de depuración
+    /// y, si el filtro de ruido o el modo dibujo están activos, también en
+    /// la final
+    gbuffer: Vec<GBufferSample>,
+    /// Rayos que recibió cada pixel en el último frame final (vista de
+    /// muestras por pixel)
+    sample_counts: Vec<u32>,
     texture: Option<Texture2D>,
 }
 
@@ -13,13 +164,113 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; (width * height) as usize],
+            front: vec![0; (width * height) as usize],
+            dirty: Vec::new(),
+            front_dirty: Vec::new(),
+            overlays: Vec::new(),
+            presented_overlays: Vec::new(),
+            look: None,
+            hdr: vec![Vector3::zero(); (width * height) as usize],
+            gbuffer: vec![GBufferSample::MISS; (width * height) as usize],
+            sample_counts: vec![1; (width * height) as usize],
             texture: None,
         }
     }
 
+    /// Cambia el tamaño del buffer. La textura de GPU se recrea en el
+    /// siguiente `present_scaled`. Tamaños 0 se ignoran (ventana minimizada).
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 || (width == self.width && height == self.height) {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![0; (width * height) as usize];
+        self.front = vec![0; (width * height) as usize];
+        self.dirty.clear();
+        self.front_dirty.clear();
+        self.overlays.clear();
+        self.presented_overlays.clear();
+        self.look = None;
+        self.hdr = vec![Vector3::zero(); (width * height) as usize];
+        self.gbuffer = vec![GBufferSample::MISS; (width * height) as usize];
+        self.sample_counts = vec![1; (width * height) as usize];
+        self.texture = None;
+    }
+
+    /// Marca una región del buffer trasero como modificada en este frame.
+    /// Quien escribe pixeles (renders, overlays) marca lo que tocó; solo eso
+    /// se vuelve a subir a la GPU.
+    pub fn mark_dirty(&mut self, rect: DirtyRect) {
+        let x = rect.x.min(self.width);
+        let y = rect.y.min(self.height);
+        let clipped = DirtyRect::new(
+            x,
+            y,
+            rect.width.min(self.width - x),
+            rect.height.min(self.height - y),
+        );
+        if clipped.area() > 0 {
+            self.dirty.push(clipped);
+        }
+    }
+
+    /// Marca todo el buffer como modificado (pasadas que tocan cada pixel)
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.clear();
+        self.dirty
+            .push(DirtyRect::new(0, 0, self.width, self.height));
+    }
+
+    /// Ajustes de color del frame en curso. Fuera de lo trazado el HDR no
+    /// cambió, así que la imagen tampoco, salvo que cambien estos ajustes:
+    /// en ese caso se marca todo.
+    pub fn set_look(&mut self, look: ImageLook) {
+        if self.look != Some(look) {
+            self.mark_all_dirty();
+            self.look = Some(look);
+        }
+    }
+
+    /// Publica el frame terminado del buffer trasero como buffer delantero.
+    /// Las regiones modificadas se copian también al nuevo trasero, así ambos
+    /// buffers coinciden fuera de lo que se vuelva a dibujar.
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.buffer, &mut self.front);
+
+        merge_dirty_rects(&mut self.dirty);
+        let width = self.width as usize;
+        for rect in &self.dirty {
+            for y in rect.y..rect.y + rect.height {
+                let start = y as usize * width + rect.x as usize;
+                let end = start + rect.width as usize;
+                self.buffer[start..end].copy_from_slice(&self.front[start..end]);
+            }
+        }
+        self.front_dirty.append(&mut self.dirty);
+        self.presented_overlays = std::mem::take(&mut self.overlays);
+    }
+
+    /// Intercambia el HDR, el G-buffer y las muestras por pixel con los de
+    /// `other`, que debe tener el mismo tamaño: un framebuffer se traza
+    /// mientras el otro se presenta. Las regiones que trazó `other` pasan a
+    /// ser las modificadas de este frame, junto con las líneas del frame
+    /// presentado, que la imagen nueva tapa.
+    pub fn swap_traced(&mut self, other: &mut Framebuffer) {
+        debug_assert_eq!((self.width, self.height), (other.width, other.height));
+        std::mem::swap(&mut self.hdr, &mut other.hdr);
+        std::mem::swap(&mut self.gbuffer, &mut other.gbuffer);
+        std::mem::swap(&mut self.sample_counts, &mut other.sample_counts);
+        self.dirty.append(&mut other.dirty);
+        self.dirty.append(&mut self.presented_overlays);
+    }
+
+    /// Limpia el buffer trasero y el HDR con el mismo color (el HDR recibe su valor lineal)
     #[inline]
     pub fn clear(&mut self, color: u32) {
         self.buffer.fill(color);
+        self.mark_all_dirty();
+        self.hdr.fill(color_to_vector3(u32_to_color(color)));
     }
 
     #[inline]
@@ -30,9 +281,306 @@ impl Framebuffer {
         }
     }
 
+    /// Lee un pixel del buffer de presentación (None fuera de los límites)
+    #[inline]
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<u32> {
+        if x < self.width && y < self.height {
+            Some(self.buffer[(y * self.width + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Como `set_pixel` pero acepta coordenadas negativas (se descartan)
+    #[inline]
+    fn set_pixel_clipped(&mut self, x: i32, y: i32, color: u32) {
+        if x >= 0 && y >= 0 {
+            self.set_pixel(x as u32, y as u32, color);
+        }
+    }
+
+    /// Línea de Bresenham recortada a los bordes del buffer. Los extremos
+    /// pueden estar fuera de pantalla (por ejemplo, esquinas proyectadas).
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: u32) {
+        let Some((x0, y0, x1, y1)) = self.clip_line(x0, y0, x1, y1) else {
+            return;
+        };
+        let (left, top) = (x0.min(x1) as u32, y0.min(y1) as u32);
+        let rect = DirtyRect::new(left, top, x0.abs_diff(x1) + 1, y0.abs_diff(y1) + 1);
+        self.mark_dirty(rect);
+        self.overlays.push(rect);
+
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+
+        loop {
+            self.set_pixel_clipped(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Recorte de Cohen-Sutherland contra el rectángulo del buffer, para no
+    /// recorrer pixeles fuera de pantalla en líneas muy largas
+    fn clip_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Option<(i32, i32, i32, i32)> {
+        const LEFT: u8 = 1;
+        const RIGHT: u8 = 2;
+        const TOP: u8 = 4;
+        const BOTTOM: u8 = 8;
+
+        let max_x = self.width as f64 - 1.0;
+        let max_y = self.height as f64 - 1.0;
+        if max_x < 0.0 || max_y < 0.0 {
+            return None;
+        }
+        let outcode = |x: f64, y: f64| {
+            let mut code = 0;
+            if x < 0.0 {
+                code |= LEFT;
+            } else if x > max_x {
+                code |= RIGHT;
+            }
+            if y < 0.0 {
+                code |= TOP;
+            } else if y > max_y {
+                code |= BOTTOM;
+            }
+            code
+        };
+
+        let (mut ax, mut ay, mut bx, mut by) = (x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+        let mut code_a = outcode(ax, ay);
+        let mut code_b = outcode(bx, by);
+
+        loop {
+            if code_a | code_b == 0 {
+                return Some((
+                    ax.round() as i32,
+                    ay.round() as i32,
+                    bx.round() as i32,
+                    by.round() as i32,
+                ));
+            }
+            if code_a & code_b != 0 {
+                return None;
+            }
+
+            let code = if code_a != 0 { code_a } else { code_b };
+            let (x, y) = if code & BOTTOM != 0 {
+                (ax + (bx - ax) * (max_y - ay) / (by - ay), max_y)
+            } else if code & TOP != 0 {
+                (ax + (bx - ax) * (0.0 - ay) / (by - ay), 0.0)
+            } else if code & RIGHT != 0 {
+                (max_x, ay + (by - ay) * (max_x - ax) / (bx - ax))
+            } else {
+                (0.0, ay + (by - ay) * (0.0 - ax) / (bx - ax))
+            };
+
+            if code == code_a {
+                (ax, ay) = (x, y);
+                code_a = outcode(ax, ay);
+            } else {
+                (bx, by) = (x, y);
+                code_b = outcode(bx, by);
+            }
+        }
+    }
+
+    /// Contorno de un rectángulo con esquina superior izquierda en (x, y)
+    pub fn draw_rect_outline(&mut self, x: i32, y: i32, width: i32, height: i32, color: u32) {
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let (x1, y1) = (x + width - 1, y + height - 1);
+        self.draw_line(x, y, x1, y, color);
+        self.draw_line(x, y1, x1, y1, color);
+        self.draw_line(x, y, x, y1, color);
+        self.draw_line(x1, y, x1, y1, color);
+    }
+
+    /// Cruz centrada en (cx, cy) con brazos de `size` pixeles
+    pub fn draw_cross(&mut self, cx: i32, cy: i32, size: i32, color: u32) {
+        self.draw_line(cx - size, cy, cx + size, cy, color);
+        self.draw_line(cx, cy - size, cx, cy + size, color);
+    }
+
+    /// Guarda el buffer como PPM binario (P6, maxval 255, sin alfa).
+    /// No depende de raylib ni de una ventana abierta.
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> Result<(), ExportError> {
+        write_ppm(path, self.width, self.height, &self.buffer)
+    }
+
+    /// Pixeles del buffer trasero (el frame en curso), fila por fila
+    #[inline]
+    pub fn as_slice(&self) -> &[u32] {
+        &self.buffer
+    }
+
+    /// Acceso al buffer de presentación (ya mapeado) para el post-procesado
+    #[inline]
+    pub fn buffer_mut(&mut self) -> &mut [u32] {
+        &mut self.buffer
+    }
+
+    /// Buffer HDR de solo lectura (medición de luminancia)
+    #[inline]
+    pub fn hdr(&self) -> &[Vector3] {
+        &self.hdr
+    }
+
+    /// Acceso al buffer HDR para el post-procesado
+    #[inline]
+    pub fn hdr_mut(&mut self) -> &mut [Vector3] {
+        &mut self.hdr
+    }
+
+    /// Buffer HDR para escribir junto con el G-buffer que lo guía (filtro
+    /// de ruido)
+    #[inline]
+    pub fn hdr_with_gbuffer(&mut self) -> (&mut [Vector3], &[GBufferSample]) {
+        (&mut self.hdr, &self.gbuffer)
+    }
+
+    /// Buffer de presentación junto con el G-buffer (contornos del modo
+    /// dibujo animado)
+    #[inline]
+    pub fn buffer_with_gbuffer(&mut self) -> (&mut [u32], &[GBufferSample]) {
+        (&mut self.buffer, &self.gbuffer)
+    }
+
+    /// Escribe un color lineal (sin recortar) en el buffer HDR
+    #[inline]
+    pub fn set_hdr_pixel(&mut self, x: u32, y: u32, color: Vector3) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            self.hdr[idx] = color;
+        }
+    }
+
+    /// Guarda la geometría del rayo primario de un pixel
+    #[inline]
+    pub fn set_gbuffer_sample(&mut self, x: u32, y: u32, sample: GBufferSample) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            self.gbuffer[idx] = sample;
+        }
+    }
+
+    /// Rayos por pixel del frame trazado: todos `samples` (muestreo fijo)
+    pub fn fill_sample_counts(&mut self, samples: u32) {
+        self.sample_counts.fill(samples);
+    }
+
+    /// Rayos por pixel del frame trazado
+    pub fn sample_counts(&self) -> &[u32] {
+        &self.sample_counts
+    }
+
+    /// Rayos por pixel del frame trazado, uno por pixel (muestreo adaptativo)
+    pub fn set_sample_counts(&mut self, counts: &[u32]) {
+        self.sample_counts.copy_from_slice(counts);
+    }
+
+    /// Rayos por pixel promedio del último frame final
+    pub fn average_samples(&self) -> f32 {
+        let total: u64 = self.sample_counts.iter().map(|&count| count as u64).sum();
+        total as f32 / self.sample_counts.len().max(1) as f32
+    }
+
+    /// Pinta el buffer de presentación con la vista de depuración indicada
+    /// en lugar de la imagen final. `render_settings` da las escalas del
+    /// costo de intersección y de las muestras por pixel.
+    pub fn resolve_debug_view(
+        &mut self,
+        mode: ViewMode,
+        depth_range: (f32, f32),
+        render_settings: &RenderSettings,
+    ) {
+        // La escala de colores depende de todo el frame (rango de
+        // profundidad, máximos): se vuelve a subir entera
+        self.mark_all_dirty();
+        self.look = None;
+        if mode == ViewMode::SampleCount {
+            let max_samples = render_settings
+                .adaptive
+                .max_spp
+                .max(render_settings.samples_per_pixel)
+                .max(2);
+            for (out, &count) in self.buffer.iter_mut().zip(&self.sample_counts) {
+                *out = color_to_u32(sample_count_color(count, max_samples));
+            }
+            return;
+        }
+        for (out, sample) in self.buffer.iter_mut().zip(&self.gbuffer) {
+            *out = color_to_u32(debug_color(
+                sample,
+                mode,
+                depth_range,
+                render_settings.cost_view_max,
+            ));
+        }
+    }
+
+    /// Pruebas de intersección por rayo primario del último frame en una
+    /// vista de depuración: (promedio, máximo)
+    pub fn intersection_cost(&self) -> (f32, u32) {
+        let total: u64 = self.gbuffer.iter().map(|sample| sample.tests as u64).sum();
+        let max = self
+            .gbuffer
+            .iter()
+            .map(|sample| sample.tests)
+            .max()
+            .unwrap_or(0);
+        (total as f32 / self.gbuffer.len().max(1) as f32, max)
+    }
+
+    /// Convierte el buffer HDR al buffer de presentación aplicando el mapeo tonal.
+    /// Lo que se dibuje con `set_pixel` después (overlays) no se pierde.
+    /// Con `dither` se aplica un tramado ordenado (Bayer 4x4) al cuantizar a
+    /// 8 bits, que rompe las bandas visibles en degradados suaves como el cielo.
+    pub fn resolve(&mut self, tone_map_settings: ToneMapSettings, dither: bool) {
+        if !dither {
+            for (out, &color) in self.buffer.iter_mut().zip(&self.hdr) {
+                *out = color_to_u32(vector3_to_color(tone_map(color, tone_map_settings)));
+            }
+            return;
+        }
+
+        let width = self.width as usize;
+        for (i, (out, &color)) in self.buffer.iter_mut().zip(&self.hdr).enumerate() {
+            let mapped = tone_map(color, tone_map_settings) * 255.0;
+            let threshold = BAYER_4X4[(i / width) % 4][(i % width) % 4];
+            let quantize = |v: f32| (v + threshold).clamp(0.0, 255.0) as u8;
+            *out = color_to_u32(Color::new(
+                quantize(mapped.x),
+                quantize(mapped.y),
+                quantize(mapped.z),
+                255,
+            ));
+        }
+    }
+
     fn ensure_texture(&mut self, d: &mut RaylibDrawHandle, thread: &RaylibThread) {
         if self.texture.is_none() {
-            let img = Image::gen_image_color(self.width as i32, self.height as i32, Color::BLACK);
+            // El buffer se sube tal cual con UpdateTexture: la textura debe ser
+            // R8G8B8A8, el mismo orden de bytes que produce `color_to_u32`.
+            let mut img =
+                Image::gen_image_color(self.width as i32, self.height as i32, Color::BLACK);
+            img.set_format(PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
             self.texture = Some(
                 d.load_texture_from_image(thread, &img)
                     .expect("No se pudo crear textura"),
@@ -40,30 +588,695 @@ impl Framebuffer {
         }
     }
 
+    /// Sube a la textura las regiones pendientes del buffer delantero y lo
+    /// dibuja escalado
     pub fn present_scaled(
-        &mut self, 
-        d: &mut RaylibDrawHandle, 
-        thread: &RaylibThread, 
-        source: Rectangle, 
-        dest: Rectangle
+        &mut self,
+        d: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        source: Rectangle,
+        dest: Rectangle,
     ) {
+        let new_texture = self.texture.is_none();
         self.ensure_texture(d, thread);
+        if new_texture {
+            self.front_dirty.clear();
+            self.front_dirty
+                .push(DirtyRect::new(0, 0, self.width, self.height));
+        }
+        self.upload_dirty();
+
+        if let Some(ref texture) = self.texture {
+            d.draw_texture_pro(texture, source, dest, Vector2::zero(), 0.0, Color::WHITE);
+        }
+    }
+
+    /// Sube solo las regiones modificadas con `UpdateTextureRec`; si cambió
+    /// más de la mitad del buffer conviene una única subida completa.
+    fn upload_dirty(&mut self) {
+        let Some(ref texture) = self.texture else {
+            return;
+        };
+        debug_assert_eq!(
+            texture.format,
+            PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32,
+            "la textura del framebuffer debe ser R8G8B8A8"
+        );
+
+        merge_dirty_rects(&mut self.front_dirty);
+        let dirty_area: u32 = self.front_dirty.iter().map(DirtyRect::area).sum();
+        if dirty_area == 0 {
+            return;
+        }
 
-        if let Some(ref mut texture) = self.texture {
+        if dirty_area * 2 > self.width * self.height {
             unsafe {
-                let raw = std::slice::from_raw_parts(
-                    self.buffer.as_ptr() as *const u8,
-                    self.buffer.len() * 4,
-                );
-                raylib::ffi::UpdateTexture(*texture.as_ref(), raw.as_ptr() as *const _);
+                raylib::ffi::UpdateTexture(*texture.as_ref(), self.front.as_ptr() as *const _);
             }
+        } else {
+            let width = self.width as usize;
+            let mut region = Vec::new();
+            for rect in &self.front_dirty {
+                // UpdateTextureRec espera los pixeles de la región contiguos
+                region.clear();
+                for y in rect.y..rect.y + rect.height {
+                    let start = y as usize * width + rect.x as usize;
+                    region.extend_from_slice(&self.front[start..start + rect.width as usize]);
+                }
+                let rec = raylib::ffi::Rectangle {
+                    x: rect.x as f32,
+                    y: rect.y as f32,
+                    width: rect.width as f32,
+                    height: rect.height as f32,
+                };
+                unsafe {
+                    raylib::ffi::UpdateTextureRec(
+                        *texture.as_ref(),
+                        rec,
+                        region.as_ptr() as *const _,
+                    );
+                }
+            }
+        }
+        self.front_dirty.clear();
+    }
+}
 
-            d.draw_texture_pro(texture, source, dest, Vector2::zero(), 0.0, Color::WHITE);
+/// Escribe pixeles empaquetados con `color_to_u32` como PPM binario (P6)
+pub fn write_ppm<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    pixels: &[u32],
+) -> Result<(), ExportError> {
+    let write = || -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path.as_ref())?);
+        encode_ppm(&mut out, width, height, pixels)?;
+        out.flush()
+    };
+    write().map_err(|source| ExportError::io(path, source))
+}
+
+/// Cabecera P6 y bytes R, G, B de cada pixel, fila por fila
+pub fn encode_ppm<W: Write>(
+    out: &mut W,
+    width: u32,
+    height: u32,
+    pixels: &[u32],
+) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+
+    let mut rgb = Vec::with_capacity(pixels.len() * 3);
+    for &pixel in pixels {
+        let c = u32_to_rgb(pixel);
+        rgb.extend_from_slice(&c);
+    }
+    out.write_all(&rgb)
+}
+
+/// Guarda pixeles RGB en el formato que indica la extensión: PPM con
+/// `write_ppm` y el resto (PNG, BMP...) con raylib, que tampoco necesita
+/// una ventana abierta para exportar.
+pub fn write_image(
+    path: &str,
+    width: u32,
+    height: u32,
+    pixels: &[u32],
+) -> Result<(), ExportError> {
+    let is_ppm = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ppm"));
+    if is_ppm {
+        return write_ppm(path, width, height, pixels);
+    }
+
+    let mut image = Image::gen_image_color(width as i32, height as i32, Color::BLACK);
+    for (i, &pixel) in pixels.iter().enumerate() {
+        let (x, y) = (i as u32 % width, i as u32 / width);
+        image.draw_pixel(x as i32, y as i32, u32_to_color(pixel));
+    }
+    if image.export_image(path) {
+        Ok(())
+    } else {
+        Err(ExportError::Encode(PathBuf::from(path)))
+    }
+}
+
+/// Cómo se escala el framebuffer a la ventana
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PresentMode {
+    /// Ocupa toda la ventana aunque se deforme
+    Stretch,
+    /// Conserva la relación de aspecto con bandas negras
+    #[default]
+    Fit,
+    /// Mayor múltiplo entero que cabe, centrado (pixeles nítidos y uniformes)
+    Integer,
+}
+
+impl PresentMode {
+    pub fn next(self) -> Self {
+        match self {
+            PresentMode::Stretch => PresentMode::Fit,
+            PresentMode::Fit => PresentMode::Integer,
+            PresentMode::Integer => PresentMode::Stretch,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PresentMode::Stretch => "Estirar",
+            PresentMode::Fit => "Ajustar",
+            PresentMode::Integer => "Entero",
         }
     }
+
+    /// Nombre usado en el archivo de preferencias
+    pub fn name(self) -> &'static str {
+        match self {
+            PresentMode::Stretch => "stretch",
+            PresentMode::Fit => "fit",
+            PresentMode::Integer => "integer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "stretch" => Some(PresentMode::Stretch),
+            "fit" => Some(PresentMode::Fit),
+            "integer" => Some(PresentMode::Integer),
+            _ => None,
+        }
+    }
+}
+
+/// Rectángulo destino en la ventana según el modo de presentación
+pub fn present_rect(
+    mode: PresentMode,
+    src_w: u32,
+    src_h: u32,
+    win_w: u32,
+    win_h: u32,
+) -> Rectangle {
+    match mode {
+        PresentMode::Stretch => Rectangle::new(0.0, 0.0, win_w as f32, win_h as f32),
+        PresentMode::Fit => fit_rect(src_w, src_h, win_w, win_h),
+        PresentMode::Integer => {
+            if src_w == 0 || src_h == 0 {
+                return Rectangle::new(0.0, 0.0, 0.0, 0.0);
+            }
+            let scale = (win_w / src_w).min(win_h / src_h);
+            // Si ni siquiera cabe a escala 1, se reduce conservando el aspecto
+            if scale == 0 {
+                return fit_rect(src_w, src_h, win_w, win_h);
+            }
+            let width = src_w * scale;
+            let height = src_h * scale;
+            Rectangle::new(
+                ((win_w - width) / 2) as f32,
+                ((win_h - height) / 2) as f32,
+                width as f32,
+                height as f32,
+            )
+        }
+    }
+}
+
+/// Rectángulo destino que encaja un buffer de `src_w`x`src_h` en la ventana
+/// conservando la relación de aspecto, centrado y con bandas negras.
+pub fn fit_rect(src_w: u32, src_h: u32, win_w: u32, win_h: u32) -> Rectangle {
+    if src_w == 0 || src_h == 0 || win_w == 0 || win_h == 0 {
+        return Rectangle::new(0.0, 0.0, 0.0, 0.0);
+    }
+    let scale = (win_w as f32 / src_w as f32).min(win_h as f32 / src_h as f32);
+    let width = src_w as f32 * scale;
+    let height = src_h as f32 * scale;
+    Rectangle::new(
+        (win_w as f32 - width) * 0.5,
+        (win_h as f32 - height) * 0.5,
+        width,
+        height,
+    )
+}
+
+/// Correspondencia entre la ventana y el framebuffer en el frame actual:
+/// tiene en cuenta la escala, el modo de presentación y las bandas negras.
+/// Todo lo que pasa del mouse al framebuffer (o dibuja alineado con sus
+/// pixeles en la ventana) usa esto.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenMapping {
+    /// Rectángulo de la ventana donde se dibuja la imagen
+    pub dest: Rectangle,
+    pub buffer_width: u32,
+    pub buffer_height: u32,
+}
+
+impl ScreenMapping {
+    pub fn new(
+        mode: PresentMode,
+        buffer_width: u32,
+        buffer_height: u32,
+        window_width: u32,
+        window_height: u32,
+    ) -> Self {
+        Self {
+            dest: present_rect(mode, buffer_width, buffer_height, window_width, window_height),
+            buffer_width,
+            buffer_height,
+        }
+    }
+
+    /// Pixeles de ventana por pixel del framebuffer, en x e y
+    pub fn scale(&self) -> (f32, f32) {
+        if self.buffer_width == 0 || self.buffer_height == 0 {
+            return (0.0, 0.0);
+        }
+        (
+            self.dest.width / self.buffer_width as f32,
+            self.dest.height / self.buffer_height as f32,
+        )
+    }
+
+    /// Pixel del framebuffer bajo un punto de la ventana. None si el punto
+    /// cae fuera de la imagen (bandas negras o fuera de la ventana).
+    pub fn window_to_buffer(&self, x: f32, y: f32) -> Option<(u32, u32)> {
+        let dest = self.dest;
+        if dest.width <= 0.0
+            || dest.height <= 0.0
+            || x < dest.x
+            || y < dest.y
+            || x >= dest.x + dest.width
+            || y >= dest.y + dest.height
+        {
+            return None;
+        }
+        let (scale_x, scale_y) = self.scale();
+        let buffer_x = (x - dest.x) / scale_x;
+        let buffer_y = (y - dest.y) / scale_y;
+        Some((
+            (buffer_x as u32).min(self.buffer_width.saturating_sub(1)),
+            (buffer_y as u32).min(self.buffer_height.saturating_sub(1)),
+        ))
+    }
+
+    /// Punto de la ventana que corresponde a una coordenada del framebuffer
+    /// (en pixeles, admite fracciones: 0.5 es el centro del primer pixel)
+    pub fn buffer_to_window(&self, x: f32, y: f32) -> Vector2 {
+        let (scale_x, scale_y) = self.scale();
+        Vector2::new(self.dest.x + x * scale_x, self.dest.y + y * scale_y)
+    }
 }
 
+/// Empaqueta un color para el framebuffer. En memoria el pixel queda como
+/// los bytes R, G, B, A (formato R8G8B8A8 de la textura) sin importar el
+/// endianness de la plataforma.
 #[inline]
 pub fn color_to_u32(c: Color) -> u32 {
-    ((c.a as u32) << 24) | ((c.b as u32) << 16) | ((c.g as u32) << 8) | (c.r as u32)
-}
\ No newline at end of file
+    u32::from_ne_bytes([c.r, c.g, c.b, c.a])
+}
+
+/// Desempaqueta un pixel de `color_to_u32` en bytes [r, g, b]
+#[inline]
+pub fn u32_to_rgb(c: u32) -> [u8; 3] {
+    let [r, g, b, _] = c.to_ne_bytes();
+    [r, g, b]
+}
+
+/// Inversa de `color_to_u32`
+#[inline]
+pub fn u32_to_color(c: u32) -> Color {
+    let [r, g, b, a] = c.to_ne_bytes();
+    Color::new(r, g, b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tonemap::ToneMapping;
+
+    #[test]
+    fn ppm_has_header_and_rgb_rows() {
+        let pixels = [
+            color_to_u32(Color::new(255, 0, 0, 255)),
+            color_to_u32(Color::new(0, 128, 0, 255)),
+            color_to_u32(Color::new(0, 0, 7, 0)),
+            color_to_u32(Color::new(10, 20, 30, 40)),
+            color_to_u32(Color::new(1, 2, 3, 4)),
+            color_to_u32(Color::new(250, 251, 252, 253)),
+        ];
+        let mut out = Vec::new();
+        encode_ppm(&mut out, 3, 2, &pixels).unwrap();
+
+        let header = b"P6\n3 2\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        // Sin alfa: tres bytes por pixel en el orden de la imagen
+        assert_eq!(
+            &out[header.len()..],
+            &[
+                255, 0, 0, 0, 128, 0, 0, 0, 7, //
+                10, 20, 30, 1, 2, 3, 250, 251, 252,
+            ]
+        );
+    }
+
+    #[test]
+    fn packed_pixels_round_trip_as_rgba_bytes() {
+        for (r, g, b, a) in [
+            (255, 0, 0, 255),
+            (0, 255, 0, 128),
+            (0, 0, 255, 0),
+            (12, 34, 56, 78),
+        ] {
+            let packed = color_to_u32(Color::new(r, g, b, a));
+            // El orden en memoria es el de la textura R8G8B8A8
+            assert_eq!(packed.to_ne_bytes(), [r, g, b, a]);
+            assert_eq!(u32_to_rgb(packed), [r, g, b]);
+            let color = u32_to_color(packed);
+            assert_eq!((color.r, color.g, color.b, color.a), (r, g, b, a));
+        }
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rectangle {
+        Rectangle::new(x, y, width, height)
+    }
+
+    #[test]
+    fn stretch_fills_the_window() {
+        let dest = present_rect(PresentMode::Stretch, 320, 180, 1000, 700);
+        assert_eq!(dest, rect(0.0, 0.0, 1000.0, 700.0));
+    }
+
+    #[test]
+    fn fit_keeps_aspect_with_bars() {
+        // Ventana más ancha: bandas a los costados
+        assert_eq!(
+            present_rect(PresentMode::Fit, 320, 180, 1000, 360),
+            rect(180.0, 0.0, 640.0, 360.0)
+        );
+        // Ventana más alta: bandas arriba y abajo
+        assert_eq!(
+            present_rect(PresentMode::Fit, 320, 180, 640, 600),
+            rect(0.0, 120.0, 640.0, 360.0)
+        );
+        assert_eq!(fit_rect(320, 180, 640, 360), rect(0.0, 0.0, 640.0, 360.0));
+    }
+
+    #[test]
+    fn integer_uses_largest_whole_scale_centered() {
+        assert_eq!(
+            present_rect(PresentMode::Integer, 320, 180, 1000, 700),
+            rect(20.0, 80.0, 960.0, 540.0)
+        );
+        assert_eq!(
+            present_rect(PresentMode::Integer, 320, 180, 320, 180),
+            rect(0.0, 0.0, 320.0, 180.0)
+        );
+        // Si no entra ni a escala 1 se comporta como `Fit`
+        assert_eq!(
+            present_rect(PresentMode::Integer, 320, 180, 160, 160),
+            fit_rect(320, 180, 160, 160)
+        );
+    }
+
+    #[test]
+    fn empty_sizes_give_an_empty_rect() {
+        let empty = rect(0.0, 0.0, 0.0, 0.0);
+        assert_eq!(fit_rect(0, 180, 640, 360), empty);
+        assert_eq!(fit_rect(320, 180, 0, 360), empty);
+        assert_eq!(present_rect(PresentMode::Integer, 320, 0, 640, 360), empty);
+        assert_eq!(present_rect(PresentMode::Fit, 320, 180, 640, 0), empty);
+    }
+
+    #[test]
+    fn mapping_finds_pixels_and_rejects_bars() {
+        // 320x180 en 800x600 ajustado: bandas arriba y abajo de 75 px
+        let fit = ScreenMapping::new(PresentMode::Fit, 320, 180, 800, 600);
+        assert_eq!(fit.scale(), (2.5, 2.5));
+        assert_eq!(fit.window_to_buffer(0.0, 75.0), Some((0, 0)));
+        assert_eq!(fit.window_to_buffer(799.9, 524.9), Some((319, 179)));
+        assert_eq!(fit.window_to_buffer(400.0, 300.0), Some((160, 90)));
+        assert_eq!(fit.window_to_buffer(400.0, 74.9), None);
+        assert_eq!(fit.window_to_buffer(400.0, 525.0), None);
+        assert_eq!(fit.window_to_buffer(-1.0, 300.0), None);
+
+        // Estirado: toda la ventana, con otra escala en cada eje
+        let stretch = ScreenMapping::new(PresentMode::Stretch, 320, 180, 640, 540);
+        assert_eq!(stretch.scale(), (2.0, 3.0));
+        assert_eq!(stretch.window_to_buffer(639.0, 539.0), Some((319, 179)));
+        assert_eq!(stretch.window_to_buffer(640.0, 0.0), None);
+
+        // Entero: escala 2 centrada en 700x400
+        let integer = ScreenMapping::new(PresentMode::Integer, 320, 180, 700, 400);
+        assert_eq!(integer.scale(), (2.0, 2.0));
+        assert_eq!(integer.window_to_buffer(30.0, 20.0), Some((0, 0)));
+        assert_eq!(integer.window_to_buffer(29.9, 20.0), None);
+        assert_eq!(integer.window_to_buffer(669.9, 379.9), Some((319, 179)));
+        assert_eq!(integer.window_to_buffer(670.0, 200.0), None);
+    }
+
+    #[test]
+    fn pixel_centers_round_trip_through_the_window() {
+        let windows = [
+            (320, 180),
+            (800, 600),
+            (1000, 400),
+            (333, 777),
+            (1920, 1080),
+        ];
+        for mode in [PresentMode::Stretch, PresentMode::Fit, PresentMode::Integer] {
+            for (window_width, window_height) in windows {
+                let mapping = ScreenMapping::new(mode, 32, 18, window_width, window_height);
+                for y in 0..18 {
+                    for x in 0..32 {
+                        let point = mapping.buffer_to_window(x as f32 + 0.5, y as f32 + 0.5);
+                        assert_eq!(
+                            mapping.window_to_buffer(point.x, point.y),
+                            Some((x, y)),
+                            "{:?} en {}x{}",
+                            mode,
+                            window_width,
+                            window_height
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_mapping_hits_nothing() {
+        let mapping = ScreenMapping::new(PresentMode::Fit, 320, 180, 0, 0);
+        assert_eq!(mapping.window_to_buffer(0.0, 0.0), None);
+        let mapping = ScreenMapping::new(PresentMode::Integer, 0, 0, 640, 360);
+        assert_eq!(mapping.scale(), (0.0, 0.0));
+        assert_eq!(mapping.window_to_buffer(10.0, 10.0), None);
+    }
+
+    const INK: u32 = 0xFFFF_FFFF;
+
+    /// Pixeles pintados con `INK`, fila por fila
+    fn inked(framebuffer: &Framebuffer) -> Vec<(u32, u32)> {
+        let width = framebuffer.width;
+        framebuffer
+            .as_slice()
+            .iter()
+            .enumerate()
+            .filter(|(_, pixel)| **pixel == INK)
+            .map(|(i, _)| (i as u32 % width, i as u32 / width))
+            .collect()
+    }
+
+    #[test]
+    fn line_fully_outside_draws_nothing() {
+        let mut framebuffer = Framebuffer::new(10, 8);
+        // A un costado, debajo y cruzando por fuera de la esquina (las dos
+        // puntas en zonas distintas, sin bits en común)
+        framebuffer.draw_line(-10, -5, -1, 20, INK);
+        framebuffer.draw_line(0, 8, 30, 12, INK);
+        framebuffer.draw_line(-5, 3, 3, -5, INK);
+        framebuffer.draw_line(i32::MIN / 2, 0, -1, 0, INK);
+        assert!(inked(&framebuffer).is_empty());
+    }
+
+    #[test]
+    fn line_partially_outside_is_clipped_to_the_buffer() {
+        let mut framebuffer = Framebuffer::new(10, 8);
+        framebuffer.draw_line(-5, 3, 20, 3, INK);
+        assert_eq!(
+            inked(&framebuffer),
+            (0..10).map(|x| (x, 3)).collect::<Vec<_>>()
+        );
+
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.draw_line(-3, -3, 12, 12, INK);
+        assert_eq!(
+            inked(&framebuffer),
+            (0..10).map(|i| (i, i)).collect::<Vec<_>>()
+        );
+
+        // Vertical desde muy arriba: termina en la punta dentro del buffer
+        let mut framebuffer = Framebuffer::new(10, 8);
+        framebuffer.draw_line(7, -1000, 7, 4, INK);
+        assert_eq!(
+            inked(&framebuffer),
+            (0..=4).map(|y| (7, y)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn degenerate_line_is_a_single_pixel() {
+        let mut framebuffer = Framebuffer::new(10, 8);
+        framebuffer.draw_line(4, 5, 4, 5, INK);
+        assert_eq!(inked(&framebuffer), vec![(4, 5)]);
+
+        let mut framebuffer = Framebuffer::new(10, 8);
+        framebuffer.draw_line(-1, -1, -1, -1, INK);
+        framebuffer.draw_line(10, 3, 10, 3, INK);
+        assert!(inked(&framebuffer).is_empty());
+
+        let mut framebuffer = Framebuffer::new(10, 8);
+        framebuffer.draw_rect_outline(2, 2, 0, 5, INK);
+        assert!(inked(&framebuffer).is_empty());
+    }
+
+    /// Framebuffer con todo el HDR en un gris entre dos niveles de 8 bits
+    fn flat_gray(level: f32) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(8, 8);
+        framebuffer.hdr_mut().fill(Vector3::one() * (level / 255.0));
+        framebuffer
+    }
+
+    fn clamp_settings() -> ToneMapSettings {
+        ToneMapSettings {
+            operator: ToneMapping::Clamp,
+            exposure_ev: 0.0,
+        }
+    }
+
+    #[test]
+    fn resolve_without_dither_quantizes_flat() {
+        let mut framebuffer = flat_gray(100.25);
+        framebuffer.resolve(clamp_settings(), false);
+        let pixels = framebuffer.as_slice();
+        assert!(pixels.iter().all(|&p| u32_to_rgb(p) == [100; 3]));
+    }
+
+    #[test]
+    fn bayer_dither_keeps_average_in_a_tiled_pattern() {
+        let mut framebuffer = flat_gray(100.25);
+        framebuffer.resolve(clamp_settings(), true);
+        let levels: Vec<u8> = framebuffer
+            .as_slice()
+            .iter()
+            .map(|&p| {
+                let [r, g, b] = u32_to_rgb(p);
+                assert!(r == g && g == b);
+                r
+            })
+            .collect();
+
+        // Solo los dos niveles vecinos, un cuarto de los pixeles en el de arriba
+        assert!(levels.iter().all(|&level| level == 100 || level == 101));
+        let high = levels.iter().filter(|&&level| level == 101).count();
+        assert_eq!(high, 64 / 4);
+
+        // El patrón se repite cada 4 pixeles en ambos ejes
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(levels[y * 8 + x], levels[(y % 4) * 8 + x % 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn dirty_rects_merge_overlapping_and_touching_tiles() {
+        let mut rects = vec![
+            // Dos tiles vecinos de una fila y uno que pisa a ambos
+            DirtyRect::new(0, 0, 16, 16),
+            DirtyRect::new(16, 0, 16, 16),
+            DirtyRect::new(8, 8, 16, 16),
+            // Uno suelto, lejos, y uno vacío que se descarta
+            DirtyRect::new(64, 64, 8, 8),
+            DirtyRect::new(40, 0, 0, 10),
+        ];
+        merge_dirty_rects(&mut rects);
+        rects.sort_by_key(|rect| (rect.y, rect.x));
+        assert_eq!(
+            rects,
+            vec![DirtyRect::new(0, 0, 32, 24), DirtyRect::new(64, 64, 8, 8)]
+        );
+
+        // Filas consecutivas de un render de un hilo: un solo rectángulo
+        let mut rows: Vec<_> = (0..10).map(|y| DirtyRect::new(0, y, 20, 1)).collect();
+        merge_dirty_rects(&mut rows);
+        assert_eq!(rows, vec![DirtyRect::new(0, 0, 20, 10)]);
+    }
+
+    /// Frame trazado que solo tocó `rect`
+    fn traced(rect: DirtyRect) -> Framebuffer {
+        let mut frame = Framebuffer::new(32, 32);
+        frame.mark_dirty(rect);
+        frame
+    }
+
+    fn look() -> ImageLook {
+        ImageLook {
+            tone_map: ToneMapSettings::default(),
+            dither: true,
+            post: PostProcess::default(),
+        }
+    }
+
+    #[test]
+    fn swapped_frame_brings_its_dirty_regions() {
+        let mut framebuffer = Framebuffer::new(32, 32);
+        framebuffer.set_look(look());
+        framebuffer.swap();
+        framebuffer.front_dirty.clear();
+
+        let tile = DirtyRect::new(8, 8, 8, 8);
+        let mut frame = traced(tile);
+        framebuffer.swap_traced(&mut frame);
+        framebuffer.set_look(look());
+        framebuffer.swap();
+        assert_eq!(framebuffer.front_dirty, vec![tile]);
+        assert!(frame.dirty.is_empty());
+    }
+
+    #[test]
+    fn previous_overlays_are_uploaded_again() {
+        let mut framebuffer = Framebuffer::new(32, 32);
+        framebuffer.set_look(look());
+        framebuffer.draw_line(0, 30, 9, 30, INK);
+        framebuffer.swap();
+        framebuffer.front_dirty.clear();
+
+        // El frame siguiente traza otra zona: la línea vieja también se sube
+        let tile = DirtyRect::new(8, 0, 8, 8);
+        framebuffer.swap_traced(&mut traced(tile));
+        framebuffer.set_look(look());
+        framebuffer.swap();
+        framebuffer.front_dirty.sort_by_key(|rect| rect.y);
+        assert_eq!(
+            framebuffer.front_dirty,
+            vec![tile, DirtyRect::new(0, 30, 10, 1)]
+        );
+    }
+
+    #[test]
+    fn changed_look_marks_whole_frame() {
+        let mut framebuffer = Framebuffer::new(32, 32);
+        framebuffer.set_look(look());
+        framebuffer.swap();
+        framebuffer.front_dirty.clear();
+
+        framebuffer.swap_traced(&mut traced(DirtyRect::new(0, 0, 4, 4)));
+        framebuffer.set_look(ImageLook {
+            dither: false,
+            ..look()
+        });
+        framebuffer.swap();
+        assert_eq!(framebuffer.front_dirty, vec![DirtyRect::new(0, 0, 32, 32)]);
+    }
+}
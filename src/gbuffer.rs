@@ -0,0 +1,224 @@
+// gbuffer.rs - Buffers auxiliares del rayo primario y vistas de depuración
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::render_settings::RenderSettings;
+use crate::scene::Scene;
+use crate::snell::{find_closest_intersection, intersection_tests};
+
+/// Qué se muestra en pantalla: la imagen final o alguno de los buffers auxiliares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    #[default]
+    Final,
+    Depth,
+    Normals,
+    Uv,
+    BlockId,
+    /// Pruebas de intersección del rayo primario: azul pocas, rojo muchas
+    IntersectionCost,
+    /// Rayos que recibió cada pixel (muestreo adaptativo): azul uno, rojo
+    /// el máximo
+    SampleCount,
+}
+
+impl ViewMode {
+    pub fn next(self) -> Self {
+        match self {
+            ViewMode::Final => ViewMode::Depth,
+            ViewMode::Depth => ViewMode::Normals,
+            ViewMode::Normals => ViewMode::Uv,
+            ViewMode::Uv => ViewMode::BlockId,
+            ViewMode::BlockId => ViewMode::IntersectionCost,
+            ViewMode::IntersectionCost => ViewMode::SampleCount,
+            ViewMode::SampleCount => ViewMode::Final,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewMode::Final => "Final",
+            ViewMode::Depth => "Profundidad",
+            ViewMode::Normals => "Normales",
+            ViewMode::Uv => "UV",
+            ViewMode::BlockId => "ID de bloque",
+            ViewMode::IntersectionCost => "Costo de intersección",
+            ViewMode::SampleCount => "Muestras por pixel",
+        }
+    }
+
+    /// Si la vista necesita el trazado completo (con sombreado) y no solo
+    /// el rayo primario
+    pub fn traces_color(self) -> bool {
+        matches!(self, ViewMode::Final | ViewMode::SampleCount)
+    }
+}
+
+/// Datos geométricos del primer impacto de un pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GBufferSample {
+    pub distance: f32,
+    pub normal: Vector3,
+    pub u: f32,
+    pub v: f32,
+    /// Índice del bloque en la escena (None si el rayo no golpeó nada)
+    pub block: Option<usize>,
+    /// Pruebas de intersección que hizo el rayo (también si no golpeó nada)
+    pub tests: u32,
+}
+
+impl GBufferSample {
+    pub const MISS: GBufferSample = GBufferSample {
+        distance: f32::INFINITY,
+        normal: Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        u: 0.0,
+        v: 0.0,
+        block: None,
+        tests: 0,
+    };
+
+    /// Lanza solo el rayo primario y guarda la geometría del impacto
+    pub fn from_primary_ray(
+        origin: &Vector3,
+        dir: &Vector3,
+        scene: &Scene,
+        render_settings: &RenderSettings,
+    ) -> Self {
+        let before = intersection_tests();
+        let hit = find_closest_intersection(origin, dir, scene, render_settings);
+        let tests = (intersection_tests() - before) as u32;
+        match hit {
+            Some((index, hit)) => GBufferSample {
+                distance: hit.distance,
+                normal: hit.normal,
+                u: hit.u,
+                v: hit.v,
+                block: Some(index),
+                tests,
+            },
+            None => GBufferSample {
+                tests,
+                ..GBufferSample::MISS
+            },
+        }
+    }
+}
+
+/// Rango cercano/lejano para normalizar la profundidad, a partir de la caja
+/// que envuelve toda la escena vista desde la cámara, hasta `max_distance`.
+pub fn depth_range(camera_pos: Vector3, scene: &[Block], max_distance: f32) -> (f32, f32) {
+    let Some(first) = scene.first() else {
+        return (0.0, max_distance);
+    };
+
+    let mut min = first.position;
+    let mut max = first.position;
+    for block in scene {
+        let (lo, hi) = block.bounds();
+        min = Vector3::new(min.x.min(lo.x), min.y.min(lo.y), min.z.min(lo.z));
+        max = Vector3::new(max.x.max(hi.x), max.y.max(hi.y), max.z.max(hi.z));
+    }
+
+    // Punto de la caja más cercano a la cámara (la cámara misma si está dentro)
+    let closest = Vector3::new(
+        camera_pos.x.clamp(min.x, max.x),
+        camera_pos.y.clamp(min.y, max.y),
+        camera_pos.z.clamp(min.z, max.z),
+    );
+    let near = camera_pos.distance_to(closest);
+
+    let mut far: f32 = 0.0;
+    for corner in 0..8 {
+        let point = Vector3::new(
+            if corner & 1 == 0 { min.x } else { max.x },
+            if corner & 2 == 0 { min.y } else { max.y },
+            if corner & 4 == 0 { min.z } else { max.z },
+        );
+        far = far.max(camera_pos.distance_to(point));
+    }
+
+    (near, far.min(max_distance).max(near + 1e-3))
+}
+
+/// Color de depuración de una muestra según el modo de vista. El costo de
+/// intersección se normaliza contra `max_tests` (lo que lo supera queda rojo).
+pub fn debug_color(
+    sample: &GBufferSample,
+    mode: ViewMode,
+    depth_range: (f32, f32),
+    max_tests: u32,
+) -> Color {
+    // El cielo también cuesta: el rayo probó todo antes de no golpear nada
+    if mode == ViewMode::IntersectionCost {
+        let t = (sample.tests as f32 / max_tests.max(1) as f32).min(1.0);
+        return depth_colormap(1.0 - t);
+    }
+    let Some(block) = sample.block else {
+        return Color::BLACK;
+    };
+
+    match mode {
+        ViewMode::Final | ViewMode::IntersectionCost | ViewMode::SampleCount => Color::MAGENTA,
+        ViewMode::Depth => {
+            let (near, far) = depth_range;
+            let t = ((sample.distance - near) / (far - near)).clamp(0.0, 1.0);
+            depth_colormap(t)
+        }
+        ViewMode::Normals => {
+            let n = sample.normal * 0.5 + Vector3::one() * 0.5;
+            unit_color(n.x, n.y, n.z)
+        }
+        ViewMode::Uv => unit_color(sample.u, sample.v, 0.0),
+        ViewMode::BlockId => {
+            // Hash entero simple para que bloques vecinos tengan colores distintos
+            let mut h = (block as u32).wrapping_mul(0x9E37_79B9);
+            h ^= h >> 15;
+            h = h.wrapping_mul(0x85EB_CA6B);
+            h ^= h >> 13;
+            Color::new(h as u8, (h >> 8) as u8, (h >> 16) as u8, 255)
+        }
+    }
+}
+
+/// Color de la vista de muestras por pixel: un rayo en azul, `max_samples`
+/// o más en rojo
+pub fn sample_count_color(samples: u32, max_samples: u32) -> Color {
+    let t = (samples.saturating_sub(1) as f32 / (max_samples - 1).max(1) as f32).min(1.0);
+    depth_colormap(1.0 - t)
+}
+
+#[inline]
+fn unit_color(r: f32, g: f32, b: f32) -> Color {
+    Color::new(
+        (r.clamp(0.0, 1.0) * 255.0) as u8,
+        (g.clamp(0.0, 1.0) * 255.0) as u8,
+        (b.clamp(0.0, 1.0) * 255.0) as u8,
+        255,
+    )
+}
+
+/// Rampa de color para la profundidad: cerca en rojo, lejos en azul (el
+/// costo de intersección y las muestras por pixel la usan invertida)
+pub fn depth_colormap(t: f32) -> Color {
+    const STOPS: [(f32, f32, f32); 5] = [
+        (1.0, 0.1, 0.1),
+        (1.0, 0.9, 0.1),
+        (0.2, 0.9, 0.2),
+        (0.1, 0.8, 0.9),
+        (0.1, 0.1, 0.6),
+    ];
+
+    let scaled = t * (STOPS.len() - 1) as f32;
+    let i = (scaled as usize).min(STOPS.len() - 2);
+    let f = scaled - i as f32;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    unit_color(
+        a.0 + (b.0 - a.0) * f,
+        a.1 + (b.1 - a.1) * f,
+        a.2 + (b.2 - a.2) * f,
+    )
+}
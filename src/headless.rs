@@ -0,0 +1,288 @@
+// headless.rs - Render sin ventana (--headless y --render-ppm) y sus opciones
+use log::info;
+use raylib::prelude::*;
+
+use crate::camera::CameraConfig;
+use crate::config::{CONFIG_PATH, Config, ConfigError};
+use crate::error::AppError;
+use crate::framebuffer::{ExportError, write_image, write_ppm};
+use crate::quality::QualityPreset;
+use crate::renderer::{Renderer, finish_frame, render_reference};
+use crate::scene::{
+    Scene, SceneKind, collect_lights, create_optimized_scene, default_lights,
+    load_minecraft_textures_cpu,
+};
+use crate::scene_file::load_scene;
+use crate::settings::{SETTINGS_PATH, Settings};
+use crate::textures::TextureManager;
+
+/// Ayuda de las opciones de `--headless`
+pub const HEADLESS_USAGE: &str = "Uso: --headless \
+    [--scene <isla|invierno|galeria|primitivas|archivo>] [--camera \"x,y,z,yaw,pitch\"] \
+    [--size ANCHOxALTO] [--preset fast|balanced|pretty] [--spp N] [--out archivo.png] \
+    [--quiet|--verbose]";
+
+/// De dónde salen los bloques del render
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneSource {
+    Builtin(SceneKind),
+    File(String),
+}
+
+/// Qué renderizar y dónde guardarlo. Yaw y pitch van en radianes, como la
+/// cámara del modo interactivo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessOptions {
+    pub scene: SceneSource,
+    pub camera_pos: Vector3,
+    pub camera_yaw: f32,
+    pub camera_pitch: f32,
+    pub width: u32,
+    pub height: u32,
+    /// Preset de calidad sobre `settings.toml`. La resolución la fija
+    /// `--size`, así que su `half_resolution` no se usa.
+    pub preset: Option<QualityPreset>,
+    /// Muestras por pixel; se promedian antes del mapeo tonal. Sin `--spp`
+    /// se usan las del preset.
+    pub samples_per_pixel: u32,
+    pub out: String,
+}
+
+impl Default for HeadlessOptions {
+    /// La isla desde la cámara inicial del modo interactivo
+    fn default() -> Self {
+        Self {
+            scene: SceneSource::Builtin(SceneKind::Island),
+            camera_pos: Vector3::new(0.0, 2.0, -6.0),
+            camera_yaw: 0.0,
+            camera_pitch: -0.2,
+            width: 400,
+            height: 300,
+            preset: None,
+            samples_per_pixel: 1,
+            out: "render.png".to_string(),
+        }
+    }
+}
+
+impl HeadlessOptions {
+    /// Lee las opciones que siguen a `--headless`. Cualquier opción
+    /// desconocida o mal escrita es un error.
+    pub fn parse(args: &[String]) -> Result<Self, ConfigError> {
+        let mut options = Self::default();
+        let mut samples_per_pixel = None;
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| ConfigError::MissingValue(flag.clone()))
+            };
+            match flag.as_str() {
+                "--scene" => options.scene = parse_scene(value()?),
+                "--camera" => {
+                    let camera = parse_camera(value()?)?;
+                    (options.camera_pos, options.camera_yaw, options.camera_pitch) = camera;
+                }
+                "--size" => (options.width, options.height) = parse_size(value()?)?,
+                "--preset" => {
+                    let name = value()?;
+                    options.preset = Some(
+                        QualityPreset::from_name(name)
+                            .ok_or_else(|| ConfigError::invalid("--preset", name))?,
+                    );
+                }
+                "--spp" => {
+                    let spp = value()?;
+                    samples_per_pixel = Some(
+                        spp.parse()
+                            .ok()
+                            .filter(|&n| n > 0)
+                            .ok_or_else(|| ConfigError::invalid("--spp", spp))?,
+                    );
+                }
+                "--out" => options.out = value()?.clone(),
+                // Nivel del log; lo aplica main antes de llegar acá
+                "--quiet" | "--verbose" => {}
+                _ => return Err(ConfigError::UnknownOption(flag.clone())),
+            }
+        }
+        options.samples_per_pixel = samples_per_pixel
+            .or(options.preset.map(QualityPreset::samples_per_pixel))
+            .unwrap_or(1);
+        Ok(options)
+    }
+}
+
+/// Nombre de una escena incluida o ruta a un archivo de escena
+pub(crate) fn parse_scene(value: &str) -> SceneSource {
+    match value.to_ascii_lowercase().as_str() {
+        "isla" | "island" => SceneSource::Builtin(SceneKind::Island),
+        "invierno" | "winter" => SceneSource::Builtin(SceneKind::Winter),
+        "galeria" | "galería" | "gallery" => SceneSource::Builtin(SceneKind::Gallery),
+        "primitivas" | "primitives" => SceneSource::Builtin(SceneKind::Primitives),
+        _ => SceneSource::File(value.to_string()),
+    }
+}
+
+/// "x,y,z,yaw,pitch" a posición y orientación
+pub(crate) fn parse_camera(value: &str) -> Result<(Vector3, f32, f32), ConfigError> {
+    let invalid = || ConfigError::invalid("--camera (x,y,z,yaw,pitch)", value);
+    let numbers: Vec<f32> = value
+        .split(',')
+        .map(|part| part.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid())?;
+    let [x, y, z, yaw, pitch] = numbers[..] else {
+        return Err(invalid());
+    };
+    Ok((Vector3::new(x, y, z), yaw, pitch))
+}
+
+/// "ANCHOxALTO" a dimensiones positivas
+pub(crate) fn parse_size(value: &str) -> Result<(u32, u32), ConfigError> {
+    value
+        .split_once(['x', 'X'])
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(|| ConfigError::invalid("el tamaño (ej: 1920x1080)", value))
+}
+
+/// Desplazamiento dentro del pixel de la muestra `index`, en [0, 1). Usa
+/// la secuencia de Halton (bases 2 y 3): cualquier cantidad de muestras
+/// queda repartida sin amontonarse y el resultado es determinista.
+pub fn sample_offset(index: u32) -> (f32, f32) {
+    if index == 0 {
+        // La primera muestra es el centro, igual que el modo interactivo
+        return (0.5, 0.5);
+    }
+    (halton(index, 2), halton(index, 3))
+}
+
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Renderiza un frame sin abrir ventana y lo guarda como PPM
+pub fn render_to_ppm(
+    scene: &Scene,
+    camera_config: &CameraConfig,
+    settings: &Settings,
+    path: &str,
+) -> Result<(), ExportError> {
+    let (width, height) = (camera_config.width as u32, camera_config.height as u32);
+    let pixels = render_reference(width, height, scene, camera_config, settings);
+    write_ppm(path, width, height, &pixels)
+}
+
+/// Render completo sin ventana: carga la escena y las texturas, traza
+/// `samples_per_pixel` pasadas multihilo con el rayo desplazado dentro del
+/// pixel, las promedia en HDR y guarda la imagen con el post-procesado de
+/// `settings.toml`. Imprime cuánto tardó cada etapa.
+pub fn run(options: &HeadlessOptions) -> Result<(), AppError> {
+    let start = std::time::Instant::now();
+    let scene = match &options.scene {
+        SceneSource::Builtin(kind) => kind.build()?,
+        SceneSource::File(path) => load_scene(path)?,
+    };
+    let mut settings = Settings::load(SETTINGS_PATH);
+    if let Some(preset) = options.preset {
+        preset.apply(&mut settings);
+    }
+    let mut texture_manager = TextureManager::new();
+    load_minecraft_textures_cpu(&mut texture_manager)?;
+    let lights = collect_lights(&scene, &settings.day.tint_lights(&default_lights()));
+    let load_time = start.elapsed();
+
+    let (width, height) = (options.width, options.height);
+    let camera_config = CameraConfig::new(
+        options.camera_pos,
+        options.camera_yaw,
+        options.camera_pitch,
+        width as usize,
+        height as usize,
+        std::f32::consts::FRAC_PI_3,
+        width as f32 / height as f32,
+    );
+    let mut renderer = Renderer::new(width, height, settings.render);
+    renderer.record_gbuffer = settings.needs_gbuffer();
+    // Solo los hilos vienen de config.toml; el resto son opciones de --headless
+    renderer.set_threads(Config::load(CONFIG_PATH).threads);
+    let mut accumulated = vec![Vector3::zero(); (width * height) as usize];
+    let mut rays = 0;
+
+    let render_start = std::time::Instant::now();
+    for sample in 0..options.samples_per_pixel {
+        renderer.seed.sample = sample;
+        let stats = renderer.trace(
+            &scene,
+            &lights,
+            &camera_config.with_jitter(sample_offset(sample)),
+            &texture_manager,
+        );
+        rays += stats.rays.total();
+        for (sum, color) in accumulated.iter_mut().zip(renderer.framebuffer.hdr()) {
+            *sum += *color;
+        }
+    }
+    let spp = options.samples_per_pixel as f32;
+    let framebuffer = &mut renderer.framebuffer;
+    for (pixel, sum) in framebuffer.hdr_mut().iter_mut().zip(&accumulated) {
+        *pixel = *sum / spp;
+    }
+    let render_time = render_start.elapsed();
+
+    // Sin frames previos la exposición automática se aplica de una vez
+    let mut tone_map = settings.tone_map;
+    if settings.auto_exposure.enabled {
+        tone_map.exposure_ev += settings.auto_exposure.target_ev(framebuffer.hdr());
+    }
+    if let Some(time) = finish_frame(framebuffer, &settings, tone_map) {
+        info!("Filtro de ruido {:.1?}", time);
+    }
+    write_image(&options.out, width, height, framebuffer.as_slice())?;
+
+    info!(
+        "{} guardado: {}x{}, {} spp, {} objetos, {} luces",
+        options.out,
+        width,
+        height,
+        options.samples_per_pixel,
+        scene.object_count(),
+        lights.len()
+    );
+    info!(
+        "Carga {:.1?}, render {:.1?} ({:.2} Mrayos/s), total {:.1?}",
+        load_time,
+        render_time,
+        rays as f64 / render_time.as_secs_f64().max(1e-9) / 1e6,
+        start.elapsed()
+    );
+    Ok(())
+}
+
+/// Frame de referencia de `--render-ppm`: la isla desde la cámara inicial,
+/// siempre con la resolución y el FOV por defecto para que sea comparable
+pub fn save_reference_frame(path: &str) -> Result<(), ExportError> {
+    let scene = create_optimized_scene();
+    let reference = Config::default();
+    let camera_config = CameraConfig::new(
+        Vector3::new(0.0, 2.0, -6.0),
+        0.0,
+        -0.2,
+        reference.render_width as usize,
+        reference.render_height as usize,
+        reference.fov(),
+        reference.render_width as f32 / reference.render_height as f32,
+    );
+    let start = std::time::Instant::now();
+    render_to_ppm(&scene, &camera_config, &Settings::load(SETTINGS_PATH), path)?;
+    info!("Frame guardado en {} ({:.1?})", path, start.elapsed());
+    Ok(())
+}
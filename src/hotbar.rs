@@ -0,0 +1,151 @@
+// hotbar.rs - Barra de bloques para elegir qué se coloca con clic derecho
+use raylib::prelude::*;
+
+use crate::block_registry::{BlockRegistry, RegisteredBlock};
+use crate::block_types::BlockType;
+use crate::material::vector3_to_color;
+use crate::textures::TextureManager;
+
+const SLOT_SIZE: i32 = 44;
+const SLOT_GAP: i32 = 4;
+const SWATCH_MARGIN: i32 = 6;
+
+/// Tipos de bloque de las nueve casillas fijas
+const DEFAULT_SLOTS: [BlockType; 9] = [
+    BlockType::Grass,
+    BlockType::Stone,
+    BlockType::Cobble,
+    BlockType::Glass,
+    BlockType::WoodLog,
+    BlockType::Leaves,
+    BlockType::Sand,
+    BlockType::Magma,
+    BlockType::Tnt,
+];
+
+/// Nueve casillas con tipos de bloque, más una por cada tipo propio del
+/// registro (se llega con la rueda); una está seleccionada
+pub struct Hotbar {
+    slots: Vec<RegisteredBlock>,
+    selected: usize,
+}
+
+impl Default for Hotbar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hotbar {
+    pub fn new() -> Self {
+        Self {
+            slots: DEFAULT_SLOTS.into_iter().map(RegisteredBlock::Builtin).collect(),
+            selected: 0,
+        }
+    }
+
+    /// Casillas fijas más los tipos propios del registro, en su orden
+    pub fn set_custom_types(&mut self, registry: &BlockRegistry) {
+        self.slots.truncate(DEFAULT_SLOTS.len());
+        let custom = registry.custom().map(|definition| Box::new(definition.clone()));
+        self.slots.extend(custom.map(RegisteredBlock::Custom));
+        if self.selected >= self.slots.len() {
+            self.selected = 0;
+        }
+    }
+
+    /// Tipo de bloque de la casilla activa
+    pub fn selected_type(&self) -> &RegisteredBlock {
+        &self.slots[self.selected]
+    }
+
+    /// Teclas 1-9 eligen casilla; la rueda del mouse avanza o retrocede
+    pub fn handle_input(&mut self, rl: &RaylibHandle) {
+        const KEYS: [KeyboardKey; 9] = [
+            KeyboardKey::KEY_ONE,
+            KeyboardKey::KEY_TWO,
+            KeyboardKey::KEY_THREE,
+            KeyboardKey::KEY_FOUR,
+            KeyboardKey::KEY_FIVE,
+            KeyboardKey::KEY_SIX,
+            KeyboardKey::KEY_SEVEN,
+            KeyboardKey::KEY_EIGHT,
+            KeyboardKey::KEY_NINE,
+        ];
+        for (i, key) in KEYS.iter().enumerate() {
+            if rl.is_key_pressed(*key) {
+                self.selected = i;
+            }
+        }
+
+        let wheel = rl.get_mouse_wheel_move();
+        let len = self.slots.len();
+        if wheel < 0.0 {
+            self.selected = (self.selected + 1) % len;
+        } else if wheel > 0.0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+
+    /// Dibuja la barra centrada en la parte inferior de la ventana
+    pub fn draw(
+        &self,
+        d: &mut RaylibDrawHandle,
+        texture_manager: &TextureManager,
+        window_width: i32,
+        window_height: i32,
+    ) {
+        let count = self.slots.len() as i32;
+        let total_width = count * SLOT_SIZE + (count - 1) * SLOT_GAP;
+        let x0 = (window_width - total_width) / 2;
+        let y = window_height - SLOT_SIZE - 10;
+
+        for (i, block_type) in self.slots.iter().enumerate() {
+            let x = x0 + i as i32 * (SLOT_SIZE + SLOT_GAP);
+            d.draw_rectangle(x, y, SLOT_SIZE, SLOT_SIZE, Color::new(0, 0, 0, 170));
+
+            // Miniatura: textura de GPU teñida con el difuso, o solo el color
+            let material = block_type.material();
+            let tint = vector3_to_color(material.diffuse);
+            let swatch = Rectangle::new(
+                (x + SWATCH_MARGIN) as f32,
+                (y + SWATCH_MARGIN) as f32,
+                (SLOT_SIZE - 2 * SWATCH_MARGIN) as f32,
+                (SLOT_SIZE - 2 * SWATCH_MARGIN) as f32,
+            );
+            match material
+                .texture
+                .as_deref()
+                .and_then(|path| texture_manager.get_gpu_texture(path))
+            {
+                Some(texture) => {
+                    let source =
+                        Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32);
+                    d.draw_texture_pro(texture, source, swatch, Vector2::zero(), 0.0, tint);
+                }
+                None => d.draw_rectangle_rec(swatch, tint),
+            }
+
+            let border = if i == self.selected {
+                Color::YELLOW
+            } else {
+                Color::GRAY
+            };
+            d.draw_rectangle_lines(x, y, SLOT_SIZE, SLOT_SIZE, border);
+            if i < DEFAULT_SLOTS.len() {
+                d.draw_text(&(i + 1).to_string(), x + 3, y + 2, 10, Color::WHITE);
+            }
+        }
+
+        // Nombre del bloque activo sobre la barra
+        let name = self.selected_type().display_name();
+        let text_width = d.measure_text(name, 18);
+        d.draw_text(
+            name,
+            (window_width - text_width) / 2,
+            y - 24,
+            18,
+            Color::WHITE,
+        );
+    }
+}
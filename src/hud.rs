@@ -0,0 +1,214 @@
+// hud.rs - Textos, paneles y menús que se dibujan sobre el frame presentado
+
+use raylib::prelude::*;
+
+use crate::block_inspector::BlockInspector;
+use crate::chest::ChestPanel;
+use crate::console::Console;
+use crate::debug_stats::DebugStats;
+use crate::framebuffer::{Framebuffer, ScreenMapping};
+use crate::gbuffer::ViewMode;
+use crate::hotbar::Hotbar;
+use crate::input_session::InputSession;
+use crate::keybinds::{Action, InputMap, key_name};
+use crate::light::Light;
+use crate::light_editor::LightEditor;
+use crate::material_editor::MaterialEditor;
+use crate::notifications::Notifications;
+use crate::pause_menu::PauseMenu;
+use crate::post_menu::PostProcessMenu;
+use crate::quality::quality_label;
+use crate::recorder::FrameRecorder;
+use crate::scene::Scene;
+use crate::settings::Settings;
+use crate::textures::TextureManager;
+use crate::tile_heatmap::{TileTime, draw_tile_heatmap};
+use crate::time_panel::TimePanel;
+
+/// Lo que dibuja el HUD sobre el frame ya presentado en la ventana
+pub struct Hud<'a> {
+    /// Dónde quedó el framebuffer en la ventana
+    pub mapping: &'a ScreenMapping,
+    pub framebuffer: &'a Framebuffer,
+    pub window_width: i32,
+    pub window_height: i32,
+    pub fps_text: &'a str,
+    pub pos_text: &'a str,
+    pub mode_text: &'a str,
+    pub render_time_text: &'a str,
+    /// Coordenadas del bloque bajo la mira
+    pub target_text: Option<&'a str>,
+    /// Tiempos por tile, si se mantiene la tecla del mapa de calor
+    pub tile_times: Option<&'a [TileTime]>,
+    pub scene: &'a Scene,
+    pub scene_label: &'a str,
+    pub view_mode: ViewMode,
+    pub settings: &'a Settings,
+    pub adapted_ev: f32,
+    pub input: &'a InputMap,
+    pub lights: &'a [Light],
+    pub material_editor: &'a MaterialEditor,
+    pub light_editor: &'a LightEditor,
+    pub post_menu: &'a PostProcessMenu,
+    pub hotbar: &'a Hotbar,
+    pub texture_manager: &'a TextureManager,
+    pub block_inspector: &'a BlockInspector,
+    pub time_panel: &'a TimePanel,
+    pub chest_panel: &'a ChestPanel,
+    pub recorder: &'a FrameRecorder,
+    pub session: &'a InputSession,
+    /// Panel de F3, si está abierto
+    pub debug_stats: Option<&'a DebugStats>,
+    pub notifications: &'a Notifications,
+    pub show_help: bool,
+    pub console: &'a Console,
+    pub pause_menu: &'a PauseMenu,
+}
+
+/// Dibuja el HUD (textos, paneles, menús) encima del frame que ya se
+/// presentó. Va separado de la presentación para que las capturas puedan
+/// tomar el frame con o sin él.
+pub fn draw_hud(d: &mut RaylibDrawHandle, hud: &Hud) {
+    let settings = hud.settings;
+    let (window_width, window_height) = (hud.window_width, hud.window_height);
+    // Coordenadas y distancia junto a la mira, en espacio de ventana
+    if let Some(text) = hud.target_text {
+        let center = hud.mapping.buffer_to_window(
+            hud.framebuffer.width as f32 * 0.5,
+            hud.framebuffer.height as f32 * 0.5,
+        );
+        let x = center.x as i32 + 12;
+        let y = center.y as i32 + 10;
+        d.draw_text(text, x + 1, y + 1, 16, Color::BLACK);
+        d.draw_text(text, x, y, 16, Color::WHITE);
+    }
+    if let Some(tile_times) = hud.tile_times {
+        draw_tile_heatmap(d, tile_times, hud.mapping);
+    }
+
+    d.draw_text(hud.fps_text, 10, 10, 20, Color::WHITE);
+    d.draw_text(hud.pos_text, 10, 35, 16, Color::WHITE);
+    d.draw_text(hud.mode_text, 10, 60, 16, Color::WHITE);
+    d.draw_text(hud.render_time_text, 10, 85, 16, Color::WHITE);
+    d.draw_text(
+        &format!(
+            "Bloques: {} | Vista: {}{} | Presentación: {}",
+            hud.scene.len(),
+            hud.view_mode.label(),
+            if hud.view_mode == ViewMode::IntersectionCost {
+                let (average, max) = hud.framebuffer.intersection_cost();
+                format!(" (media {:.1}, máx {})", average, max)
+            } else if hud.view_mode == ViewMode::SampleCount {
+                format!(" (media {:.1} spp)", hud.framebuffer.average_samples())
+            } else {
+                String::new()
+            },
+            settings.present_mode.label()
+        ),
+        10,
+        110,
+        16,
+        Color::WHITE,
+    );
+    d.draw_text(
+        &format!(
+            "Mapeo tonal: {} | Exposición: {} | Bloom: {} | FXAA: {}",
+            settings.tone_map.operator.label(),
+            if settings.auto_exposure.enabled {
+                format!(
+                    "auto {:+.2} EV ({:+.2})",
+                    hud.adapted_ev, settings.tone_map.exposure_ev
+                )
+            } else {
+                format!("{:+.2} EV", settings.tone_map.exposure_ev)
+            },
+            if settings.bloom.enabled { "ON" } else { "OFF" },
+            if settings.fxaa { "ON" } else { "OFF" }
+        ),
+        10,
+        135,
+        16,
+        Color::WHITE,
+    );
+    d.draw_text(
+        &format!(
+            "Calidad: {} | {}",
+            quality_label(settings),
+            settings.render.summary()
+        ),
+        10,
+        160,
+        16,
+        Color::WHITE,
+    );
+    d.draw_text(
+        &format!(
+            "{} - Ayuda | {} - Pausa",
+            key_name(hud.input.key_for(Action::ToggleHelp)),
+            key_name(hud.input.key_for(Action::Pause))
+        ),
+        10,
+        185,
+        14,
+        Color::LIGHTGRAY,
+    );
+
+    hud.material_editor
+        .draw(d, hud.scene, window_width - 270, 10);
+    hud.light_editor.draw(d, hud.lights, window_width - 270, 10);
+    hud.post_menu
+        .draw(d, &settings.post, window_width - 270, 200);
+    hud.hotbar
+        .draw(d, hud.texture_manager, window_width, window_height);
+    hud.block_inspector.draw(d, 10, window_height - 70);
+    hud.time_panel
+        .draw(d, &settings.day, window_width, window_height);
+    hud.chest_panel.draw(d, window_width, window_height);
+
+    // Indicador de grabación
+    if hud.recorder.is_recording() {
+        let x = window_width / 2 - 50;
+        let y = 10;
+        d.draw_circle(x, y + 8, 7.0, Color::RED);
+        let text = if hud.recorder.dropped() > 0 {
+            format!(
+                "REC {} (-{})",
+                hud.recorder.frame_count(),
+                hud.recorder.dropped()
+            )
+        } else {
+            format!("REC {}", hud.recorder.frame_count())
+        };
+        d.draw_text(&text, x + 14, y, 18, Color::RED);
+    }
+
+    if let Some(status) = hud.session.status() {
+        let width = d.measure_text(&status, 16);
+        d.draw_text(
+            &status,
+            window_width - width - 10,
+            window_height - 24,
+            16,
+            Color::ORANGE,
+        );
+    }
+
+    if let Some(debug_stats) = hud.debug_stats {
+        debug_stats.draw(d, 10, 210);
+    }
+
+    // Avisos recientes, sobre el estado de la sesión de entrada
+    hud.notifications.draw(d, window_width, window_height - 30);
+
+    if hud.show_help {
+        hud.input.draw_help_overlay(d, window_width, window_height);
+    }
+    hud.console.draw(d, window_width, window_height);
+    hud.pause_menu.draw(
+        d,
+        &settings.render,
+        hud.scene_label,
+        window_width,
+        window_height,
+    );
+}
@@ -0,0 +1,205 @@
+// input_session.rs - Grabación y reproducción de la entrada (--record, --replay)
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+use log::{error, info};
+use raylib::prelude::*;
+
+use crate::config::ConfigError;
+use crate::keybinds::{Action, ActionFrame, InputMap};
+use crate::notifications::Notifications;
+
+/// Primera línea de los archivos de sesión
+const SESSION_HEADER: &str = "# sesión de entrada: frame dt mantenidas disparadas";
+
+/// De dónde salen las acciones de cada frame. La sesión guarda un frame
+/// por línea con su duración, así que la reproducción avanza por número de
+/// frame y no por el reloj: dos reproducciones simulan exactamente lo mismo.
+pub enum InputSession {
+    /// Teclado y control, sin grabar
+    Live,
+    Recording {
+        path: String,
+        writer: BufWriter<File>,
+        frames: usize,
+    },
+    Replaying {
+        path: String,
+        frames: Vec<ActionFrame>,
+        next: usize,
+    },
+}
+
+impl InputSession {
+    /// `--record <archivo>` o `--replay <archivo>`; sin ninguno, entrada normal
+    pub fn from_args(args: &[String]) -> Result<Self, ConfigError> {
+        let value = |flag: &str| -> Result<Option<String>, ConfigError> {
+            match args.iter().position(|arg| arg == flag) {
+                Some(i) => args
+                    .get(i + 1)
+                    .cloned()
+                    .map(Some)
+                    .ok_or_else(|| ConfigError::MissingValue(flag.to_string())),
+                None => Ok(None),
+            }
+        };
+        match (value("--record")?, value("--replay")?) {
+            (Some(_), Some(_)) => Err(ConfigError::Conflict("--record", "--replay")),
+            (Some(path), None) => Self::record(path),
+            (None, Some(path)) => Self::replay(path),
+            (None, None) => Ok(InputSession::Live),
+        }
+    }
+
+    fn record(path: String) -> Result<Self, ConfigError> {
+        let file = File::create(&path).map_err(|err| ConfigError::io(&path, err))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", SESSION_HEADER).map_err(|err| ConfigError::io(&path, err))?;
+        info!("Grabando la entrada en {}", path);
+        Ok(InputSession::Recording {
+            path,
+            writer,
+            frames: 0,
+        })
+    }
+
+    fn replay(path: String) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(&path).map_err(|err| ConfigError::io(&path, err))?;
+        let frames = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| {
+                parse_frame(line).map_err(|message| ConfigError::Parse {
+                    path: path.clone().into(),
+                    line: i + 1,
+                    message,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        info!("Reproduciendo {} frames de {}", frames.len(), path);
+        Ok(InputSession::Replaying {
+            path,
+            frames,
+            next: 0,
+        })
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self, InputSession::Replaying { .. })
+    }
+
+    /// Prepara las acciones del frame que empieza: las lee del teclado (y
+    /// las graba) o las toma de la sesión. Al terminar la reproducción se
+    /// vuelve a la entrada normal.
+    pub fn update(
+        &mut self,
+        rl: &RaylibHandle,
+        input: &mut InputMap,
+        notifications: &mut Notifications,
+    ) {
+        match self {
+            InputSession::Live => input.poll(rl),
+            InputSession::Recording {
+                path,
+                writer,
+                frames,
+            } => {
+                input.poll(rl);
+                if let Err(err) = writeln!(writer, "{}", format_frame(*frames, input.frame())) {
+                    notifications.error(format!(
+                        "No se pudo grabar la entrada en {}: {}",
+                        path, err
+                    ));
+                    *self = InputSession::Live;
+                    return;
+                }
+                *frames += 1;
+            }
+            InputSession::Replaying { path, frames, next } => match frames.get(*next) {
+                Some(frame) => {
+                    input.set_frame(frame.clone());
+                    *next += 1;
+                }
+                None => {
+                    notifications.info(format!("Reproducción de {} terminada", path));
+                    *self = InputSession::Live;
+                    input.poll(rl);
+                }
+            },
+        }
+    }
+
+    /// Texto para el HUD mientras se graba o reproduce
+    pub fn status(&self) -> Option<String> {
+        match self {
+            InputSession::Live => None,
+            InputSession::Recording { frames, .. } => Some(format!("Sesión: grabando {}", frames)),
+            InputSession::Replaying { frames, next, .. } => {
+                Some(format!("Sesión: reproduciendo {}/{}", next, frames.len()))
+            }
+        }
+    }
+
+    /// Cierra la grabación (al salir del programa)
+    pub fn finish(&mut self) {
+        if let InputSession::Recording {
+            path,
+            writer,
+            frames,
+        } = self
+        {
+            match writer.flush() {
+                Ok(()) => info!("Sesión de {} frames guardada en {}", frames, path),
+                Err(err) => error!("No se pudo guardar {}: {}", path, err),
+            }
+        }
+        *self = InputSession::Live;
+    }
+}
+
+/// Una línea: número de frame, dt y las acciones separadas por comas ("-"
+/// si no hay ninguna), ej: `42 0.016667 move_forward,turn_left toggle_bloom`
+fn format_frame(index: usize, frame: &ActionFrame) -> String {
+    let names = |actions: &[Action]| {
+        if actions.is_empty() {
+            "-".to_string()
+        } else {
+            actions
+                .iter()
+                .map(|action| action.name())
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+    };
+    format!(
+        "{} {} {} {}",
+        index,
+        frame.dt,
+        names(&frame.down),
+        names(&frame.pressed)
+    )
+}
+
+fn parse_frame(line: &str) -> Result<ActionFrame, String> {
+    let [_, dt, down, pressed] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err("se esperaba: frame dt mantenidas disparadas".to_string());
+    };
+    let actions = |list: &str| -> Result<Vec<Action>, String> {
+        if list == "-" {
+            return Ok(Vec::new());
+        }
+        list.split(',')
+            .map(|name| {
+                Action::from_name(name).ok_or_else(|| format!("acción desconocida '{}'", name))
+            })
+            .collect()
+    };
+    Ok(ActionFrame {
+        dt: dt
+            .parse()
+            .map_err(|_| format!("duración inválida '{}'", dt))?,
+        down: actions(down)?,
+        pressed: actions(pressed)?,
+    })
+}
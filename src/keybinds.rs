@@ -0,0 +1,762 @@
+// keybinds.rs - Tabla de teclas configurable: la usan la entrada y la ayuda (H)
+use std::fs;
+use std::path::Path;
+
+use log::warn;
+use raylib::prelude::*;
+
+use crate::config::ConfigError;
+use crate::events::GAMEPAD;
+use crate::settings::parse_key_values;
+
+/// Archivo con las teclas del usuario
+pub const KEYBINDS_PATH: &str = "keybinds.toml";
+
+/// Acciones globales que se disparan con una tecla
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    MoveUp,
+    MoveDown,
+    TurnLeft,
+    TurnRight,
+    LookUp,
+    LookDown,
+    ToggleMultithread,
+    ToggleShadows,
+    ToggleReflections,
+    ToggleRefractions,
+    ToggleAmbientOcclusion,
+    CycleToneMapping,
+    ExposureUp,
+    ExposureDown,
+    ToggleAutoExposure,
+    ToggleBloom,
+    ToggleFxaa,
+    ToggleDenoise,
+    ToggleToon,
+    ToggleDither,
+    CycleQualityPreset,
+    CyclePresentMode,
+    ToggleRecording,
+    TogglePostMenu,
+    CycleViewMode,
+    ToggleMaterialEditor,
+    ToggleLightEditor,
+    ToggleTimePanel,
+    ToggleConsole,
+    Screenshot,
+    CycleFpsCap,
+    ToggleHelp,
+    ToggleDebugStats,
+    ShowTileHeatmap,
+    ToggleWireframe,
+    Detonate,
+    Pause,
+}
+
+impl Action {
+    /// Nombre de la acción en `keybinds.toml`
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBack => "move_back",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::LookUp => "look_up",
+            Action::LookDown => "look_down",
+            Action::ToggleMultithread => "toggle_multithread",
+            Action::ToggleShadows => "toggle_shadows",
+            Action::ToggleReflections => "toggle_reflections",
+            Action::ToggleRefractions => "toggle_refractions",
+            Action::ToggleAmbientOcclusion => "toggle_ambient_occlusion",
+            Action::CycleToneMapping => "cycle_tone_mapping",
+            Action::ExposureUp => "exposure_up",
+            Action::ExposureDown => "exposure_down",
+            Action::ToggleAutoExposure => "toggle_auto_exposure",
+            Action::ToggleBloom => "toggle_bloom",
+            Action::ToggleFxaa => "toggle_fxaa",
+            Action::ToggleDenoise => "toggle_denoise",
+            Action::ToggleToon => "toggle_toon",
+            Action::ToggleDither => "toggle_dither",
+            Action::CycleQualityPreset => "cycle_quality_preset",
+            Action::CyclePresentMode => "cycle_present_mode",
+            Action::ToggleRecording => "toggle_recording",
+            Action::TogglePostMenu => "toggle_post_menu",
+            Action::CycleViewMode => "cycle_view_mode",
+            Action::ToggleMaterialEditor => "toggle_material_editor",
+            Action::ToggleLightEditor => "toggle_light_editor",
+            Action::ToggleTimePanel => "toggle_time_panel",
+            Action::ToggleConsole => "toggle_console",
+            Action::Screenshot => "screenshot",
+            Action::CycleFpsCap => "cycle_fps_cap",
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleDebugStats => "toggle_debug_stats",
+            Action::ShowTileHeatmap => "show_tile_heatmap",
+            Action::ToggleWireframe => "toggle_wireframe",
+            Action::Detonate => "detonate",
+            Action::Pause => "pause",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        DEFAULT_KEYBINDINGS
+            .iter()
+            .map(|binding| binding.action)
+            .find(|action| action.name() == name)
+    }
+}
+
+/// Una tecla asociada a una acción, con su descripción para la ayuda
+#[derive(Debug, Clone, Copy)]
+pub struct Keybinding {
+    pub key: KeyboardKey,
+    pub action: Action,
+    pub description: &'static str,
+}
+
+const fn bind(key: KeyboardKey, action: Action, description: &'static str) -> Keybinding {
+    Keybinding {
+        key,
+        action,
+        description,
+    }
+}
+
+/// Teclas por defecto de todas las acciones globales. El código de entrada
+/// solo consulta acciones, así que una tecla nueva no funciona hasta que se
+/// agrega aquí.
+pub const DEFAULT_KEYBINDINGS: &[Keybinding] = &[
+    bind(KeyboardKey::KEY_W, Action::MoveForward, "Avanzar"),
+    bind(KeyboardKey::KEY_S, Action::MoveBack, "Retroceder"),
+    bind(
+        KeyboardKey::KEY_A,
+        Action::StrafeLeft,
+        "Mover a la izquierda",
+    ),
+    bind(
+        KeyboardKey::KEY_D,
+        Action::StrafeRight,
+        "Mover a la derecha",
+    ),
+    bind(KeyboardKey::KEY_SPACE, Action::MoveUp, "Subir"),
+    bind(KeyboardKey::KEY_LEFT_CONTROL, Action::MoveDown, "Bajar"),
+    bind(
+        KeyboardKey::KEY_LEFT,
+        Action::TurnLeft,
+        "Girar a la izquierda",
+    ),
+    bind(
+        KeyboardKey::KEY_RIGHT,
+        Action::TurnRight,
+        "Girar a la derecha",
+    ),
+    bind(KeyboardKey::KEY_UP, Action::LookUp, "Mirar arriba"),
+    bind(KeyboardKey::KEY_DOWN, Action::LookDown, "Mirar abajo"),
+    bind(KeyboardKey::KEY_T, Action::ToggleMultithread, "Multihilo"),
+    bind(KeyboardKey::KEY_F1, Action::ToggleShadows, "Sombras"),
+    bind(KeyboardKey::KEY_F2, Action::ToggleReflections, "Reflejos"),
+    bind(KeyboardKey::KEY_F9, Action::ToggleRefractions, "Refracción"),
+    bind(
+        KeyboardKey::KEY_F4,
+        Action::ToggleAmbientOcclusion,
+        "Oclusión ambiental",
+    ),
+    bind(KeyboardKey::KEY_O, Action::CycleToneMapping, "Mapeo tonal"),
+    bind(
+        KeyboardKey::KEY_PAGE_UP,
+        Action::ExposureUp,
+        "Subir exposición",
+    ),
+    bind(
+        KeyboardKey::KEY_PAGE_DOWN,
+        Action::ExposureDown,
+        "Bajar exposición",
+    ),
+    bind(
+        KeyboardKey::KEY_F5,
+        Action::ToggleAutoExposure,
+        "Exposición automática",
+    ),
+    bind(KeyboardKey::KEY_B, Action::ToggleBloom, "Bloom"),
+    bind(KeyboardKey::KEY_F, Action::ToggleFxaa, "FXAA"),
+    bind(KeyboardKey::KEY_J, Action::ToggleDenoise, "Filtro de ruido"),
+    bind(KeyboardKey::KEY_U, Action::ToggleToon, "Modo dibujo"),
+    bind(KeyboardKey::KEY_F6, Action::ToggleDither, "Tramado"),
+    bind(KeyboardKey::KEY_F8, Action::CycleQualityPreset, "Calidad"),
+    bind(
+        KeyboardKey::KEY_F7,
+        Action::CyclePresentMode,
+        "Presentación",
+    ),
+    bind(
+        KeyboardKey::KEY_F10,
+        Action::ToggleRecording,
+        "Grabar frames",
+    ),
+    bind(KeyboardKey::KEY_V, Action::TogglePostMenu, "Post-procesado"),
+    bind(
+        KeyboardKey::KEY_G,
+        Action::CycleViewMode,
+        "Vista de depuración",
+    ),
+    bind(
+        KeyboardKey::KEY_M,
+        Action::ToggleMaterialEditor,
+        "Editar material",
+    ),
+    bind(KeyboardKey::KEY_L, Action::ToggleLightEditor, "Editar luces"),
+    bind(KeyboardKey::KEY_N, Action::ToggleTimePanel, "Hora del día"),
+    bind(KeyboardKey::KEY_GRAVE, Action::ToggleConsole, "Consola"),
+    bind(KeyboardKey::KEY_F11, Action::Screenshot, "Captura"),
+    bind(KeyboardKey::KEY_F12, Action::CycleFpsCap, "Límite de FPS"),
+    bind(KeyboardKey::KEY_H, Action::ToggleHelp, "Ayuda"),
+    bind(KeyboardKey::KEY_F3, Action::ToggleDebugStats, "Depuración"),
+    bind(KeyboardKey::KEY_K, Action::ShowTileHeatmap, "Tiempo por tile (mantener)"),
+    bind(KeyboardKey::KEY_Y, Action::ToggleWireframe, "Alambre: bloques o grilla"),
+    bind(KeyboardKey::KEY_X, Action::Detonate, "Encender la TNT bajo la mira"),
+    bind(KeyboardKey::KEY_ESCAPE, Action::Pause, "Pausa"),
+];
+
+/// Botones del control que disparan acciones, además de su tecla
+pub const GAMEPAD_BINDINGS: &[(GamepadButton, Action)] = &[
+    (
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+        Action::ToggleShadows,
+    ),
+    (
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+        Action::ToggleReflections,
+    ),
+    (
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+        Action::ToggleAmbientOcclusion,
+    ),
+    (
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP,
+        Action::CycleViewMode,
+    ),
+    (
+        GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+        Action::ToggleBloom,
+    ),
+    (
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+        Action::ToggleMultithread,
+    ),
+    (
+        GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT,
+        Action::ToggleHelp,
+    ),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT, Action::Pause),
+];
+
+/// Nombre del botón con la distribución de Xbox
+fn gamepad_button_name(button: GamepadButton) -> &'static str {
+    match button {
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN => "A",
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT => "B",
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT => "X",
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP => "Y",
+        GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1 => "LB",
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1 => "RB",
+        GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT => "Select",
+        GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT => "Start",
+        _ => "?",
+    }
+}
+
+/// Controles fuera de la tabla (mouse y teclas propias de un panel)
+pub const OTHER_CONTROLS: &[(&str, &str)] = &[
+    ("Clic izq/der", "Quitar/poner bloque (clic izq enciende la TNT)"),
+    ("Clic central", "Inspeccionar bloque"),
+    ("Shift+Captura", "Captura HDR (.hdr o -2/0/+2 EV)"),
+    ("1-9/Rueda", "Elegir bloque"),
+    ("Sticks", "Control: mover y mirar"),
+    ("Gatillos", "Control: subir/bajar"),
+];
+
+/// Nombres de tecla aceptados en `keybinds.toml` (los de raylib sin `KEY_`)
+const KEY_NAMES: &[(&str, KeyboardKey)] = &[
+    ("A", KeyboardKey::KEY_A),
+    ("B", KeyboardKey::KEY_B),
+    ("C", KeyboardKey::KEY_C),
+    ("D", KeyboardKey::KEY_D),
+    ("E", KeyboardKey::KEY_E),
+    ("F", KeyboardKey::KEY_F),
+    ("G", KeyboardKey::KEY_G),
+    ("H", KeyboardKey::KEY_H),
+    ("I", KeyboardKey::KEY_I),
+    ("J", KeyboardKey::KEY_J),
+    ("K", KeyboardKey::KEY_K),
+    ("L", KeyboardKey::KEY_L),
+    ("M", KeyboardKey::KEY_M),
+    ("N", KeyboardKey::KEY_N),
+    ("O", KeyboardKey::KEY_O),
+    ("P", KeyboardKey::KEY_P),
+    ("Q", KeyboardKey::KEY_Q),
+    ("R", KeyboardKey::KEY_R),
+    ("S", KeyboardKey::KEY_S),
+    ("T", KeyboardKey::KEY_T),
+    ("U", KeyboardKey::KEY_U),
+    ("V", KeyboardKey::KEY_V),
+    ("W", KeyboardKey::KEY_W),
+    ("X", KeyboardKey::KEY_X),
+    ("Y", KeyboardKey::KEY_Y),
+    ("Z", KeyboardKey::KEY_Z),
+    ("ZERO", KeyboardKey::KEY_ZERO),
+    ("ONE", KeyboardKey::KEY_ONE),
+    ("TWO", KeyboardKey::KEY_TWO),
+    ("THREE", KeyboardKey::KEY_THREE),
+    ("FOUR", KeyboardKey::KEY_FOUR),
+    ("FIVE", KeyboardKey::KEY_FIVE),
+    ("SIX", KeyboardKey::KEY_SIX),
+    ("SEVEN", KeyboardKey::KEY_SEVEN),
+    ("EIGHT", KeyboardKey::KEY_EIGHT),
+    ("NINE", KeyboardKey::KEY_NINE),
+    ("F1", KeyboardKey::KEY_F1),
+    ("F2", KeyboardKey::KEY_F2),
+    ("F3", KeyboardKey::KEY_F3),
+    ("F4", KeyboardKey::KEY_F4),
+    ("F5", KeyboardKey::KEY_F5),
+    ("F6", KeyboardKey::KEY_F6),
+    ("F7", KeyboardKey::KEY_F7),
+    ("F8", KeyboardKey::KEY_F8),
+    ("F9", KeyboardKey::KEY_F9),
+    ("F10", KeyboardKey::KEY_F10),
+    ("F11", KeyboardKey::KEY_F11),
+    ("F12", KeyboardKey::KEY_F12),
+    ("SPACE", KeyboardKey::KEY_SPACE),
+    ("ESCAPE", KeyboardKey::KEY_ESCAPE),
+    ("ENTER", KeyboardKey::KEY_ENTER),
+    ("TAB", KeyboardKey::KEY_TAB),
+    ("BACKSPACE", KeyboardKey::KEY_BACKSPACE),
+    ("INSERT", KeyboardKey::KEY_INSERT),
+    ("DELETE", KeyboardKey::KEY_DELETE),
+    ("HOME", KeyboardKey::KEY_HOME),
+    ("END", KeyboardKey::KEY_END),
+    ("PAGE_UP", KeyboardKey::KEY_PAGE_UP),
+    ("PAGE_DOWN", KeyboardKey::KEY_PAGE_DOWN),
+    ("RIGHT", KeyboardKey::KEY_RIGHT),
+    ("LEFT", KeyboardKey::KEY_LEFT),
+    ("DOWN", KeyboardKey::KEY_DOWN),
+    ("UP", KeyboardKey::KEY_UP),
+    ("LEFT_SHIFT", KeyboardKey::KEY_LEFT_SHIFT),
+    ("LEFT_CONTROL", KeyboardKey::KEY_LEFT_CONTROL),
+    ("LEFT_ALT", KeyboardKey::KEY_LEFT_ALT),
+    ("RIGHT_SHIFT", KeyboardKey::KEY_RIGHT_SHIFT),
+    ("RIGHT_CONTROL", KeyboardKey::KEY_RIGHT_CONTROL),
+    ("RIGHT_ALT", KeyboardKey::KEY_RIGHT_ALT),
+    ("APOSTROPHE", KeyboardKey::KEY_APOSTROPHE),
+    ("COMMA", KeyboardKey::KEY_COMMA),
+    ("MINUS", KeyboardKey::KEY_MINUS),
+    ("PERIOD", KeyboardKey::KEY_PERIOD),
+    ("SLASH", KeyboardKey::KEY_SLASH),
+    ("SEMICOLON", KeyboardKey::KEY_SEMICOLON),
+    ("EQUAL", KeyboardKey::KEY_EQUAL),
+    ("LEFT_BRACKET", KeyboardKey::KEY_LEFT_BRACKET),
+    ("BACKSLASH", KeyboardKey::KEY_BACKSLASH),
+    ("RIGHT_BRACKET", KeyboardKey::KEY_RIGHT_BRACKET),
+    ("GRAVE", KeyboardKey::KEY_GRAVE),
+    ("KP_0", KeyboardKey::KEY_KP_0),
+    ("KP_1", KeyboardKey::KEY_KP_1),
+    ("KP_2", KeyboardKey::KEY_KP_2),
+    ("KP_3", KeyboardKey::KEY_KP_3),
+    ("KP_4", KeyboardKey::KEY_KP_4),
+    ("KP_5", KeyboardKey::KEY_KP_5),
+    ("KP_6", KeyboardKey::KEY_KP_6),
+    ("KP_7", KeyboardKey::KEY_KP_7),
+    ("KP_8", KeyboardKey::KEY_KP_8),
+    ("KP_9", KeyboardKey::KEY_KP_9),
+];
+
+/// Busca una tecla por nombre, sin distinguir mayúsculas
+fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|&(_, key)| key)
+}
+
+/// Nombre de la tecla tal como se escribe en `keybinds.toml`
+fn key_config_name(key: KeyboardKey) -> &'static str {
+    KEY_NAMES
+        .iter()
+        .find(|&&(_, k)| k == key)
+        .map_or("?", |&(name, _)| name)
+}
+
+/// Estado de las acciones en un frame. Sale del teclado y el control
+/// (`InputMap::poll`) o de una sesión grabada; todo el código consulta
+/// este estado, así que reproducir una sesión es solo reemplazarlo.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionFrame {
+    /// Segundos que dura el frame (el tiempo de la simulación)
+    pub dt: f32,
+    /// Acciones mantenidas
+    pub down: Vec<Action>,
+    /// Acciones que se dispararon en este frame
+    pub pressed: Vec<Action>,
+}
+
+/// Teclas asignadas a cada acción
+#[derive(Debug, Clone)]
+pub struct InputMap {
+    bindings: Vec<Keybinding>,
+    /// Con la consola abierta ninguna acción responde
+    enabled: bool,
+    frame: ActionFrame,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_KEYBINDINGS.to_vec(),
+            enabled: true,
+            frame: ActionFrame::default(),
+        }
+    }
+}
+
+impl InputMap {
+    /// Carga las teclas del archivo; las acciones que no aparecen mantienen
+    /// su tecla por defecto. Si el archivo no existe se escribe con los
+    /// valores por defecto. Las teclas repetidas solo generan advertencias.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut input = InputMap::default();
+        let Ok(text) = fs::read_to_string(path.as_ref()) else {
+            if let Err(err) = input.save(path.as_ref()) {
+                warn!("No se pudieron escribir las teclas: {}", err);
+            }
+            return Ok(input);
+        };
+
+        for (line_number, name, value) in parse_key_values(&text) {
+            let error = |message: String| ConfigError::Parse {
+                path: path.as_ref().to_path_buf(),
+                line: line_number,
+                message,
+            };
+            let Some(action) = Action::from_name(&name) else {
+                let valid: Vec<&str> = DEFAULT_KEYBINDINGS
+                    .iter()
+                    .map(|binding| binding.action.name())
+                    .collect();
+                return Err(error(format!(
+                    "acción desconocida '{}'. Acciones válidas: {}",
+                    name,
+                    valid.join(", ")
+                )));
+            };
+            let Some(key) = key_from_name(&value) else {
+                let valid: Vec<&str> = KEY_NAMES.iter().map(|(name, _)| *name).collect();
+                return Err(error(format!(
+                    "tecla desconocida '{}'. Teclas válidas: {}",
+                    value,
+                    valid.join(", ")
+                )));
+            };
+            if let Some(binding) = input.bindings.iter_mut().find(|b| b.action == action) {
+                binding.key = key;
+            }
+        }
+
+        for (key, first, second) in input.conflicts() {
+            warn!(
+                "{} está asignada a '{}' y a '{}'",
+                key_config_name(key),
+                first.name(),
+                second.name()
+            );
+        }
+
+        Ok(input)
+    }
+
+    /// Pares de acciones que comparten tecla, en el orden de la tabla
+    pub fn conflicts(&self) -> Vec<(KeyboardKey, Action, Action)> {
+        let mut conflicts = Vec::new();
+        for (i, binding) in self.bindings.iter().enumerate() {
+            for other in &self.bindings[i + 1..] {
+                if binding.key == other.key {
+                    conflicts.push((binding.key, binding.action, other.action));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Guarda las teclas en formato `acción = "TECLA"`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let mut text = String::from("# Teclas del raytracer (nombres de raylib sin KEY_)\n");
+        for binding in &self.bindings {
+            text.push_str(&format!(
+                "{} = \"{}\"  # {}\n",
+                binding.action.name(),
+                key_config_name(binding.key),
+                binding.description
+            ));
+        }
+        fs::write(path.as_ref(), text).map_err(|err| ConfigError::io(path, err))
+    }
+
+    /// Tecla asignada a una acción
+    pub fn key_for(&self, action: Action) -> KeyboardKey {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.key)
+            .expect("toda acción tiene una entrada en DEFAULT_KEYBINDINGS")
+    }
+
+    fn description(&self, action: Action) -> &'static str {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map_or("", |binding| binding.description)
+    }
+
+    /// Activa o desactiva todas las acciones (ej: mientras se escribe)
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Lee el teclado y el control para el frame que empieza. Una acción se
+    /// dispara con su tecla o, si hay un control conectado, con su botón.
+    pub fn poll(&mut self, rl: &RaylibHandle) {
+        let gamepad = rl.is_gamepad_available(GAMEPAD);
+        let mut frame = ActionFrame {
+            dt: rl.get_frame_time(),
+            ..ActionFrame::default()
+        };
+        for binding in &self.bindings {
+            let action = binding.action;
+            if rl.is_key_down(binding.key) {
+                frame.down.push(action);
+            }
+            let button_pressed = gamepad
+                && GAMEPAD_BINDINGS.iter().any(|&(button, bound)| {
+                    bound == action && rl.is_gamepad_button_pressed(GAMEPAD, button)
+                });
+            if rl.is_key_pressed(binding.key) || button_pressed {
+                frame.pressed.push(action);
+            }
+        }
+        self.frame = frame;
+    }
+
+    /// Reemplaza el frame leído (reproducción de una sesión)
+    pub fn set_frame(&mut self, frame: ActionFrame) {
+        self.frame = frame;
+    }
+
+    pub fn frame(&self) -> &ActionFrame {
+        &self.frame
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.enabled && self.frame.pressed.contains(&action)
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        self.enabled && self.frame.down.contains(&action)
+    }
+
+    /// Lista de controles para la terminal
+    pub fn controls_text(&self) -> String {
+        self.bindings
+            .iter()
+            .map(|binding| format!("{} - {}", key_name(binding.key), binding.description))
+            .chain(
+                OTHER_CONTROLS
+                    .iter()
+                    .map(|(keys, description)| format!("{} - {}", keys, description)),
+            )
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// Panel de ayuda en dos columnas sobre un rectángulo oscuro translúcido
+    pub fn draw_help_overlay(
+        &self,
+        d: &mut RaylibDrawHandle,
+        window_width: i32,
+        window_height: i32,
+    ) {
+        let lines: Vec<(String, &str)> = self
+            .bindings
+            .iter()
+            .map(|binding| (key_name(binding.key), binding.description))
+            .chain(GAMEPAD_BINDINGS.iter().map(|&(button, action)| {
+                (
+                    format!("Pad {}", gamepad_button_name(button)),
+                    self.description(action),
+                )
+            }))
+            .chain(
+                OTHER_CONTROLS
+                    .iter()
+                    .map(|(keys, description)| (keys.to_string(), *description)),
+            )
+            .collect();
+
+        let line_height = 18;
+        let column_width = 260;
+        let rows = lines.len().div_ceil(2) as i32;
+        let width = column_width * 2 + 20;
+        let height = line_height * rows + 50;
+        let x = (window_width - width) / 2;
+        let y = ((window_height - height) / 2).max(0);
+
+        d.draw_rectangle(x, y, width, height, Color::new(0, 0, 0, 190));
+        d.draw_text(
+            &format!(
+                "Controles ({}: cerrar)",
+                key_name(self.key_for(Action::ToggleHelp))
+            ),
+            x + 10,
+            y + 10,
+            20,
+            Color::YELLOW,
+        );
+
+        for (i, (key, description)) in lines.iter().enumerate() {
+            let column = i as i32 / rows;
+            let row = i as i32 % rows;
+            let line_x = x + 10 + column * column_width;
+            let line_y = y + 40 + row * line_height;
+            d.draw_text(key, line_x, line_y, 16, Color::YELLOW);
+            d.draw_text(description, line_x + 80, line_y, 16, Color::WHITE);
+        }
+    }
+}
+
+/// Nombre corto de la tecla para mostrar
+pub fn key_name(key: KeyboardKey) -> String {
+    match key {
+        KeyboardKey::KEY_LEFT_CONTROL => "Ctrl".to_string(),
+        KeyboardKey::KEY_PAGE_UP => "RePág".to_string(),
+        KeyboardKey::KEY_PAGE_DOWN => "AvPág".to_string(),
+        KeyboardKey::KEY_ESCAPE => "ESC".to_string(),
+        KeyboardKey::KEY_SPACE => "Espacio".to_string(),
+        KeyboardKey::KEY_LEFT => "Izq".to_string(),
+        KeyboardKey::KEY_RIGHT => "Der".to_string(),
+        KeyboardKey::KEY_UP => "Arriba".to_string(),
+        KeyboardKey::KEY_DOWN => "Abajo".to_string(),
+        _ => key_config_name(key).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Carga `text` como archivo de teclas
+    fn load_text(name: &str, text: &str) -> Result<InputMap, ConfigError> {
+        let path = std::env::temp_dir().join(format!(
+            "raytracer_keybinds_{}_{}.toml",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, text).unwrap();
+        let result = InputMap::load(&path);
+        let _ = fs::remove_file(&path);
+        result
+    }
+
+    #[test]
+    fn defaults_have_no_conflicts_and_unique_names() {
+        assert!(InputMap::default().conflicts().is_empty());
+        for binding in DEFAULT_KEYBINDINGS {
+            assert_eq!(
+                Action::from_name(binding.action.name()),
+                Some(binding.action)
+            );
+        }
+    }
+
+    #[test]
+    fn file_overrides_only_listed_actions() {
+        let input = load_text(
+            "overrides",
+            "# teclas\n\
+             move_forward = \"I\"\n\
+             screenshot = \"p\"\n",
+        )
+        .unwrap();
+        assert_eq!(input.key_for(Action::MoveForward), KeyboardKey::KEY_I);
+        // Los nombres de tecla no distinguen mayúsculas
+        assert_eq!(input.key_for(Action::Screenshot), KeyboardKey::KEY_P);
+        assert_eq!(input.key_for(Action::MoveBack), KeyboardKey::KEY_S);
+        assert!(input.conflicts().is_empty());
+    }
+
+    #[test]
+    fn screenshot_is_a_bindable_action() {
+        // Un archivo de antes de la acción sigue capturando con la tecla
+        // por defecto
+        let input = load_text("no_screenshot", "pause = \"P\"\n").unwrap();
+        assert_eq!(input.key_for(Action::Screenshot), KeyboardKey::KEY_F11);
+
+        let err = load_text("listed", "captura = \"F11\"\n").unwrap_err();
+        let ConfigError::Parse { message, .. } = err else {
+            panic!("se esperaba un error de lectura: {:?}", err);
+        };
+        assert!(message.contains("screenshot"), "{}", message);
+    }
+
+    #[test]
+    fn shared_key_is_reported_as_conflict() {
+        let input = load_text("conflict", "toggle_bloom = \"W\"\n").unwrap();
+        assert_eq!(
+            input.conflicts(),
+            vec![(KeyboardKey::KEY_W, Action::MoveForward, Action::ToggleBloom)]
+        );
+    }
+
+    #[test]
+    fn unknown_action_or_key_fails_with_line() {
+        let err = load_text("bad_action", "\nvolar = \"W\"\n").unwrap_err();
+        assert!(
+            matches!(err, ConfigError::Parse { line: 2, .. }),
+            "{:?}",
+            err
+        );
+
+        let err = load_text("bad_key", "pause = \"P\"\npause = \"NOPE\"\n").unwrap_err();
+        assert!(
+            matches!(err, ConfigError::Parse { line: 2, .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn saved_bindings_load_back() {
+        let mut input = InputMap::default();
+        input.bindings[0].key = KeyboardKey::KEY_I;
+        let path = std::env::temp_dir().join(format!(
+            "raytracer_keybinds_saved_{}.toml",
+            std::process::id()
+        ));
+        input.save(&path).unwrap();
+        let loaded = InputMap::load(&path);
+        let _ = fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        for binding in &input.bindings {
+            assert_eq!(
+                loaded.key_for(binding.action),
+                binding.key,
+                "{:?}",
+                binding.action
+            );
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// lib.rs - Raytracer como biblioteca; el binario elige el modo y corre la ventana
+pub mod adaptive;
+pub mod animation;
+pub mod beacon;
+pub mod block;
+pub mod block_inspector;
+pub mod block_registry;
+pub mod block_shape;
+pub mod block_types;
+pub mod bvh;
+pub mod camera;
+pub mod caustics;
+pub mod chest;
+pub mod config;
+pub mod console;
+pub mod console_commands;
+pub mod day_cycle;
+pub mod debug_stats;
+pub mod denoise;
+pub mod error;
+pub mod events;
+pub mod fog;
+pub mod frame_limiter;
+pub mod framebuffer;
+pub mod gbuffer;
+pub mod headless;
+pub mod hotbar;
+pub mod hud;
+pub mod input_session;
+pub mod keybinds;
+pub mod light;
+pub mod light_editor;
+pub mod logger;
+pub mod material;
+pub mod material_editor;
+pub mod motion_blur;
+pub mod notifications;
+pub mod ore;
+pub mod orientation;
+pub mod pause_menu;
+pub mod plant;
+pub mod portal;
+pub mod post;
+pub mod post_menu;
+pub mod primitive;
+pub mod quality;
+pub mod radiance;
+pub mod ray_intersect;
+pub mod recorder;
+pub mod render_context;
+pub mod render_settings;
+pub mod render_thread;
+pub mod renderer;
+pub mod rng;
+pub mod scene;
+pub mod scene_file;
+pub mod screenshot;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod settings;
+pub mod shared_scene;
+pub mod snell;
+pub mod textures;
+pub mod tile_heatmap;
+pub mod time_panel;
+pub mod tnt;
+pub mod tonemap;
+pub mod toon;
+pub mod view_file;
+pub mod voxel_grid;
+pub mod wireframe;
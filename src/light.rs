@@ -3,20 +3,54 @@ use raylib::prelude::*;
 
 /// Representa una luz puntual en la escena.
 /// Se define por su posición, color e intensidad.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Light {
     /// Posición de la luz en el espacio 3D
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
     pub position: Vector3,
     /// Color de la luz (RGB en rango 0.0 - 1.0)
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
     pub color: Vector3,
     /// Intensidad de la luz (factor multiplicador)
     pub intensity: f32,
+    /// Coeficiente propio de la atenuación cuadrática (luces chicas que
+    /// alcanzan poco, como una antorcha). None usa el de `RenderSettings`.
+    pub attenuation: Option<f32>,
+    /// Cono al que se limita la luz (un foco). None ilumina en todas las
+    /// direcciones.
+    pub spot: Option<Spot>,
+}
+
+/// Cono de un foco: la luz se apaga suavemente fuera de `angle` radianes
+/// alrededor de `direction`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spot {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub direction: Vector3,
+    pub angle: f32,
+}
+
+impl Spot {
+    pub fn new(direction: Vector3, angle: f32) -> Self {
+        Self { direction: direction.normalized(), angle }
+    }
+
+    /// Cuánta luz sale hacia `dir` (normalizado, desde la luz): 1.0 en el
+    /// centro del cono, bajando hasta 0.0 en el borde
+    pub fn factor(&self, dir: &Vector3) -> f32 {
+        let cos_edge = self.angle.cos();
+        let t = (self.direction.dot(*dir) - cos_edge) / (1.0 - cos_edge).max(1e-6);
+        let t = t.clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
 }
 
 impl Light {
     /// Crea una nueva luz con parámetros personalizados.
     pub fn new(position: Vector3, color: Vector3, intensity: f32) -> Self {
-        Self { position, color, intensity }
+        Self { position, color, intensity, attenuation: None, spot: None }
     }
 
     /// Devuelve el color de la luz como `raylib::Color` (clamp de 0-255).
@@ -37,6 +71,8 @@ impl Default for Light {
             position: Vector3::zero(),
             color: Vector3::one(),
             intensity: 1.0,
+            attenuation: None,
+            spot: None,
         }
     }
 }
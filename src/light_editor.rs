@@ -0,0 +1,295 @@
+// light_editor.rs - Panel con sliders para ajustar las luces en vivo
+use raylib::prelude::*;
+
+use crate::light::Light;
+use crate::notifications::Notifications;
+
+/// Parámetros de una luz editables con un slider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Parameter {
+    Intensity,
+    Red,
+    Green,
+    Blue,
+    X,
+    Y,
+    Z,
+}
+
+const PARAMETERS: [Parameter; 7] = [
+    Parameter::Intensity,
+    Parameter::Red,
+    Parameter::Green,
+    Parameter::Blue,
+    Parameter::X,
+    Parameter::Y,
+    Parameter::Z,
+];
+
+impl Parameter {
+    fn label(self) -> &'static str {
+        match self {
+            Parameter::Intensity => "Intensidad",
+            Parameter::Red => "Rojo",
+            Parameter::Green => "Verde",
+            Parameter::Blue => "Azul",
+            Parameter::X => "Pos X",
+            Parameter::Y => "Pos Y",
+            Parameter::Z => "Pos Z",
+        }
+    }
+
+    /// Rango del slider
+    fn range(self) -> (f32, f32) {
+        match self {
+            Parameter::Intensity => (0.0, 10.0),
+            Parameter::Red | Parameter::Green | Parameter::Blue => (0.0, 1.0),
+            Parameter::X | Parameter::Y | Parameter::Z => (-15.0, 15.0),
+        }
+    }
+
+    /// Incremento de las flechas izquierda/derecha
+    fn step(self) -> f32 {
+        match self {
+            Parameter::Intensity => 0.1,
+            Parameter::Red | Parameter::Green | Parameter::Blue => 0.05,
+            Parameter::X | Parameter::Y | Parameter::Z => 0.25,
+        }
+    }
+
+    fn value(self, light: &Light) -> f32 {
+        match self {
+            Parameter::Intensity => light.intensity,
+            Parameter::Red => light.color.x,
+            Parameter::Green => light.color.y,
+            Parameter::Blue => light.color.z,
+            Parameter::X => light.position.x,
+            Parameter::Y => light.position.y,
+            Parameter::Z => light.position.z,
+        }
+    }
+
+    fn set(self, light: &mut Light, value: f32) {
+        let (min, max) = self.range();
+        let value = value.clamp(min, max);
+        match self {
+            Parameter::Intensity => light.intensity = value,
+            Parameter::Red => light.color.x = value,
+            Parameter::Green => light.color.y = value,
+            Parameter::Blue => light.color.z = value,
+            Parameter::X => light.position.x = value,
+            Parameter::Y => light.position.y = value,
+            Parameter::Z => light.position.z = value,
+        }
+    }
+}
+
+const PANEL_WIDTH: i32 = 260;
+const ROW_HEIGHT: i32 = 24;
+const HEADER_HEIGHT: i32 = 28;
+const LABEL_WIDTH: i32 = 90;
+const VALUE_WIDTH: i32 = 50;
+
+/// Estado del editor: luz elegida, slider seleccionado y arrastre del mouse
+#[derive(Default)]
+pub struct LightEditor {
+    open: bool,
+    light: usize,
+    selected: usize,
+    dragging: Option<usize>,
+}
+
+impl LightEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.dragging = None;
+    }
+
+    /// Barra del slider `index` dentro del panel en (x, y)
+    fn slider_rect(index: usize, x: i32, y: i32) -> Rectangle {
+        Rectangle::new(
+            (x + 8 + LABEL_WIDTH) as f32,
+            (y + HEADER_HEIGHT + ROW_HEIGHT * index as i32 + 6) as f32,
+            (PANEL_WIDTH - 16 - LABEL_WIDTH - VALUE_WIDTH) as f32,
+            10.0,
+        )
+    }
+
+    /// Tab cambia de luz, flechas eligen y ajustan, el mouse arrastra los
+    /// sliders del panel en (x, y) y P imprime las luces como código.
+    /// Devuelve true si alguna luz cambió.
+    pub fn handle_input(
+        &mut self,
+        rl: &RaylibHandle,
+        lights: &mut [Light],
+        x: i32,
+        y: i32,
+        notifications: &mut Notifications,
+    ) -> bool {
+        if !self.open || lights.is_empty() {
+            return false;
+        }
+        self.light = self.light.min(lights.len() - 1);
+
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            self.light = (self.light + 1) % lights.len();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1) % PARAMETERS.len();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = (self.selected + PARAMETERS.len() - 1) % PARAMETERS.len();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+            println!("{}", lights_to_code(lights));
+            notifications.info("Código de las luces impreso en la terminal");
+        }
+
+        let light = &mut lights[self.light];
+        let mut changed = false;
+
+        let mut steps = 0.0;
+        if rl.is_key_pressed_repeat(KeyboardKey::KEY_RIGHT)
+            || rl.is_key_pressed(KeyboardKey::KEY_RIGHT)
+        {
+            steps += 1.0;
+        }
+        if rl.is_key_pressed_repeat(KeyboardKey::KEY_LEFT)
+            || rl.is_key_pressed(KeyboardKey::KEY_LEFT)
+        {
+            steps -= 1.0;
+        }
+        if steps != 0.0 {
+            let parameter = PARAMETERS[self.selected];
+            parameter.set(light, parameter.value(light) + parameter.step() * steps);
+            changed = true;
+        }
+
+        // Arrastre: empieza al hacer clic sobre una barra y sigue mientras
+        // el botón esté presionado, aunque el mouse salga de ella
+        let mouse = rl.get_mouse_position();
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.dragging = (0..PARAMETERS.len()).find(|&i| {
+                let mut rect = Self::slider_rect(i, x, y);
+                // Área de clic algo más alta que la barra
+                rect.y -= 6.0;
+                rect.height += 12.0;
+                rect.check_collision_point_rec(mouse)
+            });
+        }
+        if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.dragging = None;
+        }
+        if let Some(index) = self.dragging {
+            self.selected = index;
+            let rect = Self::slider_rect(index, x, y);
+            let t = ((mouse.x - rect.x) / rect.width).clamp(0.0, 1.0);
+            let parameter = PARAMETERS[index];
+            let (min, max) = parameter.range();
+            let value = min + (max - min) * t;
+            if value != parameter.value(light) {
+                parameter.set(light, value);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    fn height() -> i32 {
+        HEADER_HEIGHT + ROW_HEIGHT * PARAMETERS.len() as i32 + 40
+    }
+
+    /// Dibuja el panel con los sliders de la luz elegida
+    pub fn draw(&self, d: &mut RaylibDrawHandle, lights: &[Light], x: i32, y: i32) {
+        if !self.open {
+            return;
+        }
+        let Some(light) = lights.get(self.light.min(lights.len().saturating_sub(1))) else {
+            return;
+        };
+
+        d.draw_rectangle(x, y, PANEL_WIDTH, Self::height(), Color::new(0, 0, 0, 170));
+        d.draw_text(
+            &format!("Luz {}/{}", self.light + 1, lights.len()),
+            x + 8,
+            y + 6,
+            16,
+            Color::YELLOW,
+        );
+        d.draw_rectangle(x + PANEL_WIDTH - 28, y + 6, 18, 16, light.as_color());
+
+        for (i, parameter) in PARAMETERS.iter().enumerate() {
+            let row_y = y + HEADER_HEIGHT + ROW_HEIGHT * i as i32;
+            let color = if i == self.selected {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            d.draw_text(parameter.label(), x + 8, row_y + 4, 14, color);
+
+            let rect = Self::slider_rect(i, x, y);
+            let (min, max) = parameter.range();
+            let t = ((parameter.value(light) - min) / (max - min)).clamp(0.0, 1.0);
+            d.draw_rectangle_rec(rect, Color::new(70, 70, 70, 255));
+            d.draw_rectangle(
+                rect.x as i32,
+                rect.y as i32,
+                (rect.width * t) as i32,
+                rect.height as i32,
+                color,
+            );
+            d.draw_text(
+                &format!("{:.2}", parameter.value(light)),
+                (rect.x + rect.width) as i32 + 6,
+                row_y + 4,
+                14,
+                color,
+            );
+        }
+
+        let help_y = y + HEADER_HEIGHT + ROW_HEIGHT * PARAMETERS.len() as i32 + 2;
+        d.draw_text(
+            "Tab: luz | Flechas/mouse: ajustar",
+            x + 8,
+            help_y,
+            14,
+            Color::LIGHTGRAY,
+        );
+        d.draw_text(
+            "P: imprimir código | L: cerrar",
+            x + 8,
+            help_y + 18,
+            14,
+            Color::LIGHTGRAY,
+        );
+    }
+}
+
+/// Genera las líneas `Light::new(...)` listas para pegar en el código
+pub fn lights_to_code(lights: &[Light]) -> String {
+    lights
+        .iter()
+        .map(|light| {
+            format!(
+                "Light::new(Vector3::new({:.2}, {:.2}, {:.2}), Vector3::new({:.2}, {:.2}, {:.2}), {:.2}),",
+                light.position.x,
+                light.position.y,
+                light.position.z,
+                light.color.x,
+                light.color.y,
+                light.color.z,
+                light.intensity
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,90 @@
+// logger.rs - Logger mínimo para el facade `log`: nivel, tiempo y módulo
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Variable de entorno con el nivel (off, error, warn, info, debug, trace)
+pub const LOG_ENV_VAR: &str = "RAYTRACER_LOG";
+/// Target de los mensajes que ya salen de `Notifications`: se escriben en la
+/// terminal pero no se vuelven a mandar al HUD
+pub const NOTIFICATION_TARGET: &str = "hud";
+/// Avisos pendientes para el HUD; si nadie los lee (sin ventana) se
+/// descartan los más viejos
+const MAX_PENDING_NOTICES: usize = 32;
+
+struct Logger {
+    start: Instant,
+    /// Advertencias y errores que todavía no pasaron al HUD
+    notices: Mutex<VecDeque<(Level, String)>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let target = record.target();
+        let module = target.strip_prefix("project2_graphics::").unwrap_or(target);
+        eprintln!(
+            "[{:>8.3}s {:<5} {}] {}",
+            self.start.elapsed().as_secs_f32(),
+            record.level(),
+            module,
+            record.args()
+        );
+
+        if record.level() <= Level::Warn && target != NOTIFICATION_TARGET {
+            let mut notices = self.notices.lock().unwrap_or_else(|e| e.into_inner());
+            if notices.len() == MAX_PENDING_NOTICES {
+                notices.pop_front();
+            }
+            notices.push_back((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Nivel según los argumentos y el entorno: `--quiet` deja solo los errores,
+/// `--verbose` agrega los de depuración y si no hay ninguno se usa
+/// `RAYTRACER_LOG` (por defecto info)
+pub fn level_from_args(args: &[String]) -> LevelFilter {
+    if args.iter().any(|arg| arg == "--quiet") {
+        LevelFilter::Error
+    } else if args.iter().any(|arg| arg == "--verbose") {
+        LevelFilter::Debug
+    } else {
+        std::env::var(LOG_ENV_VAR)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(LevelFilter::Info)
+    }
+}
+
+/// Instala el logger. Llamarlo más de una vez solo cambia el nivel.
+pub fn init(level: LevelFilter) {
+    let logger = LOGGER.get_or_init(|| Logger {
+        start: Instant::now(),
+        notices: Mutex::new(VecDeque::new()),
+    });
+    // Falla solo si ya estaba instalado
+    let _ = log::set_logger(logger);
+    log::set_max_level(level);
+}
+
+/// Advertencias y errores registrados desde la última llamada, para
+/// mostrarlos en el HUD
+pub fn take_notices() -> Vec<(Level, String)> {
+    LOGGER.get().map_or_else(Vec::new, |logger| {
+        let mut notices = logger.notices.lock().unwrap_or_else(|e| e.into_inner());
+        notices.drain(..).collect()
+    })
+}
@@ -1,11 +1,15 @@
 // material.rs
 use raylib::prelude::*;
 
+use crate::ore::Specks;
+
 /// Define las propiedades físicas y visuales de un material.
 /// Se usa para calcular cómo interactúa la luz con la superficie.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Material {
     /// Color base difuso (en espacio RGB normalizado 0.0–1.0).
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
     pub diffuse: Vector3,
 
     /// Coeficientes de mezcla entre el color propio y la luz:
@@ -33,14 +37,70 @@ pub struct Material {
     pub normal_map_id: Option<String>,
 
     /// Ruta opcional para el color del halo de luz
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::option_vec3"))]
     pub emission_color: Option<Vector3>,
 
     /// Intensidad de la luz emitida
     pub emission_strength: f32, 
+
+    /// Translucidez (subsurface falso): cuánta luz pasa desde la cara trasera.
+    /// 0.0 = desactivado.
+    pub translucency: f32,
+
+    /// Si es `true`, el material ignora las luces de la escena y solo
+    /// muestra su emisión (para fuentes de luz como el sol).
+    pub unlit: bool,
+
+    /// Índices de refracción por canal (R, G, B) para dispersión tipo prisma.
+    /// `None` usa `refractive_index` para los tres canales.
+    pub refractive_index_rgb: Option<[f32; 3]>,
+
+    /// Absorción por unidad de distancia dentro del medio (Beer-Lambert),
+    /// por canal. Cero = la luz que lo atraviesa no se tiñe.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub absorption: Vector3,
+
+    /// Amplitud de las olas animadas en la cara superior (perturban la
+    /// normal con el tiempo). 0.0 = superficie quieta.
+    pub waves: f32,
+
+    /// Aspereza de la superficie: dispersa el rayo reflejado alrededor de
+    /// la dirección del espejo. 0.0 = reflejo nítido.
+    pub roughness: f32,
+
+    /// Escarcha: aclara el color hacia blanco cerca de los bordes de cada
+    /// cara (según la distancia de las UV al borde). 0.0 = sin escarcha.
+    pub frost: f32,
+
+    /// Vetas de mineral: puntos de otro color (que pueden brillar) en un
+    /// patrón distinto por bloque (ver `ore::speck_at`). None = sin vetas.
+    pub specks: Option<Specks>,
+
+    /// Aberración cromática de la refracción: separa un poco los canales
+    /// R, G y B del rayo refractado (dispersión barata, sin tres índices).
+    /// 0.0 = vidrio limpio.
+    pub aberration_strength: f32,
+
+    /// Distorsión de barril en el espacio UV de la cara: tuerce el rayo
+    /// refractado más cuanto más lejos del centro ("vidrio deformado").
+    /// 0.0 = sin distorsión.
+    pub distortion_strength: f32,
+
+    /// Proyecta cáusticas: la luz que lo atraviesa hacia una superficie se
+    /// reparte en ondas brillantes animadas (ver `caustics::caustic_pattern`)
+    /// en vez de solo atenuarse.
+    pub caustics: bool,
+
+    /// Refleja lo que indica Fresnel aunque su reflectividad sea cero (el
+    /// agua refleja el cielo en ángulos rasantes). Sin esto un material
+    /// transparente solo refleja si es reflectivo.
+    pub fresnel_reflection: bool,
 }
 
 impl Material {
-    /// Crea un nuevo material con los parámetros especificados.
+    /// Crea un nuevo material con los parámetros especificados. Los
+    /// argumentos siguen el orden de los campos; el resto arranca apagado.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         diffuse: Vector3,
         albedo: [f32; 2],
@@ -62,9 +122,23 @@ impl Material {
             normal_map_id,
             emission_color: None,
             emission_strength: 0.0,
+            translucency: 0.0,
+            unlit: false,
+            refractive_index_rgb: None,
+            absorption: Vector3::zero(),
+            waves: 0.0,
+            roughness: 0.0,
+            frost: 0.0,
+            specks: None,
+            aberration_strength: 0.0,
+            distortion_strength: 0.0,
+            caustics: false,
+            fresnel_reflection: false,
         }
     }
 
+    /// Como `new`, con la emisión
+    #[allow(clippy::too_many_arguments)]
     pub fn new_emissive(
         diffuse: Vector3,
         albedo: [f32; 2],
@@ -88,9 +162,88 @@ impl Material {
             normal_map_id,
             emission_color,
             emission_strength,
+            translucency: 0.0,
+            unlit: false,
+            refractive_index_rgb: None,
+            absorption: Vector3::zero(),
+            waves: 0.0,
+            roughness: 0.0,
+            frost: 0.0,
+            specks: None,
+            aberration_strength: 0.0,
+            distortion_strength: 0.0,
+            caustics: false,
+            fresnel_reflection: false,
+        }
+    }
+
+    /// Interpola entre dos materiales (t en [0, 1]).
+    /// Los campos numéricos se mezclan linealmente; los no numéricos
+    /// (texturas, flags) se toman de `a` si t < 0.5 y de `b` en otro caso.
+    pub fn lerp(a: &Material, b: &Material, t: f32) -> Material {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |x: f32, y: f32| x + (y - x) * t;
+        let pick_b = t >= 0.5;
+
+        // Si solo uno tiene color de emisión, el otro usa su difuso (igual que el shader)
+        let emission_color = match (a.emission_color, b.emission_color) {
+            (None, None) => None,
+            (ea, eb) => Some(
+                ea.unwrap_or(a.diffuse)
+                    .lerp(eb.unwrap_or(b.diffuse), t),
+            ),
+        };
+        let refractive_index_rgb = match (a.refractive_index_rgb, b.refractive_index_rgb) {
+            (None, None) => None,
+            (ra, rb) => {
+                let ra = ra.unwrap_or([a.refractive_index; 3]);
+                let rb = rb.unwrap_or([b.refractive_index; 3]);
+                Some([mix(ra[0], rb[0]), mix(ra[1], rb[1]), mix(ra[2], rb[2])])
+            }
+        };
+
+        Material {
+            diffuse: a.diffuse.lerp(b.diffuse, t),
+            albedo: [mix(a.albedo[0], b.albedo[0]), mix(a.albedo[1], b.albedo[1])],
+            specular: mix(a.specular, b.specular),
+            reflectivity: mix(a.reflectivity, b.reflectivity),
+            transparency: mix(a.transparency, b.transparency),
+            refractive_index: mix(a.refractive_index, b.refractive_index),
+            texture: if pick_b { b.texture.clone() } else { a.texture.clone() },
+            normal_map_id: if pick_b { b.normal_map_id.clone() } else { a.normal_map_id.clone() },
+            emission_color,
+            emission_strength: mix(a.emission_strength, b.emission_strength),
+            translucency: mix(a.translucency, b.translucency),
+            unlit: if pick_b { b.unlit } else { a.unlit },
+            refractive_index_rgb,
+            absorption: a.absorption.lerp(b.absorption, t),
+            waves: mix(a.waves, b.waves),
+            roughness: mix(a.roughness, b.roughness),
+            frost: mix(a.frost, b.frost),
+            specks: if pick_b { b.specks } else { a.specks },
+            aberration_strength: mix(a.aberration_strength, b.aberration_strength),
+            distortion_strength: mix(a.distortion_strength, b.distortion_strength),
+            caustics: if pick_b { b.caustics } else { a.caustics },
+            fresnel_reflection: if pick_b {
+                b.fresnel_reflection
+            } else {
+                a.fresnel_reflection
+            },
         }
     }
 
+    /// Indica si dos materiales transparentes forman el mismo medio óptico,
+    /// de modo que la cara compartida entre ellos no debe refractar.
+    pub fn same_medium(&self, other: &Material) -> bool {
+        self.transparency > 0.01
+            && other.transparency > 0.01
+            && self.refractive_index == other.refractive_index
+            && self.refractive_index_rgb == other.refractive_index_rgb
+            && self.transparency == other.transparency
+            && self.texture == other.texture
+            && self.absorption == other.absorption
+    }
+
     /// Material negro por defecto (sin interacción con la luz).
     pub fn black() -> Self {
         Self {
@@ -104,10 +257,158 @@ impl Material {
             normal_map_id: None,
             emission_color: None,
             emission_strength: 0.0,
+            translucency: 0.0,
+            unlit: false,
+            refractive_index_rgb: None,
+            absorption: Vector3::zero(),
+            waves: 0.0,
+            roughness: 0.0,
+            frost: 0.0,
+            specks: None,
+            aberration_strength: 0.0,
+            distortion_strength: 0.0,
+            caustics: false,
+            fresnel_reflection: false,
+        }
+    }
+}
+
+/// Problemas detectados al validar un material.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaterialWarning {
+    /// Un componente de albedo fuera de [0, 1].
+    AlbedoOutOfRange { index: usize, value: f32 },
+    /// La suma de los albedos supera 1.0 (el material "crea" energía).
+    AlbedoSumExceeded(f32),
+    /// reflectividad + transparencia supera 1.0.
+    EnergyExceeded { reflectivity: f32, transparency: f32 },
+    /// Índice de refracción menor que el del aire.
+    RefractiveIndexBelowOne(f32),
+    /// Un campo numérico negativo.
+    NegativeValue { field: &'static str, value: f32 },
+    /// Un campo que es una fracción (o un color) por encima de su máximo.
+    AboveMaximum { field: &'static str, value: f32, max: f32 },
+    /// Tiene textura pero es unlit sin emisión: nunca se verá.
+    UnlitWithoutEmission,
+}
+
+impl std::fmt::Display for MaterialWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaterialWarning::AlbedoOutOfRange { index, value } => {
+                write!(f, "albedo[{}] = {} fuera de [0, 1]", index, value)
+            }
+            MaterialWarning::AlbedoSumExceeded(sum) => {
+                write!(f, "la suma de albedos ({}) supera 1.0", sum)
+            }
+            MaterialWarning::EnergyExceeded { reflectivity, transparency } => write!(
+                f,
+                "reflectividad ({}) + transparencia ({}) supera 1.0",
+                reflectivity, transparency
+            ),
+            MaterialWarning::RefractiveIndexBelowOne(ior) => {
+                write!(f, "índice de refracción {} menor que 1.0", ior)
+            }
+            MaterialWarning::NegativeValue { field, value } => {
+                write!(f, "{} negativo ({})", field, value)
+            }
+            MaterialWarning::AboveMaximum { field, value, max } => {
+                write!(f, "{} = {} supera el máximo {}", field, value, max)
+            }
+            MaterialWarning::UnlitWithoutEmission => {
+                write!(f, "material unlit con textura pero sin emisión")
+            }
         }
     }
 }
 
+impl Material {
+    /// Revisa que el material sea físicamente razonable.
+    /// Devuelve una lista vacía si no hay problemas.
+    pub fn validate(&self) -> Vec<MaterialWarning> {
+        let mut warnings = Vec::new();
+
+        for (index, &value) in self.albedo.iter().enumerate() {
+            if !(0.0..=1.0).contains(&value) {
+                warnings.push(MaterialWarning::AlbedoOutOfRange { index, value });
+            }
+        }
+        let albedo_sum = self.albedo[0] + self.albedo[1];
+        if albedo_sum > 1.0 + 1e-3 {
+            warnings.push(MaterialWarning::AlbedoSumExceeded(albedo_sum));
+        }
+
+        if self.reflectivity + self.transparency > 1.0 + 1e-3 {
+            warnings.push(MaterialWarning::EnergyExceeded {
+                reflectivity: self.reflectivity,
+                transparency: self.transparency,
+            });
+        }
+
+        if self.refractive_index < 1.0 {
+            warnings.push(MaterialWarning::RefractiveIndexBelowOne(self.refractive_index));
+        }
+        if let Some(iors) = self.refractive_index_rgb {
+            for ior in iors.into_iter().filter(|&ior| ior < 1.0) {
+                warnings.push(MaterialWarning::RefractiveIndexBelowOne(ior));
+            }
+        }
+
+        let numeric_fields = [
+            ("diffuse.r", self.diffuse.x),
+            ("diffuse.g", self.diffuse.y),
+            ("diffuse.b", self.diffuse.z),
+            ("specular", self.specular),
+            ("reflectivity", self.reflectivity),
+            ("transparency", self.transparency),
+            ("emission_strength", self.emission_strength),
+            ("translucency", self.translucency),
+            ("absorption.r", self.absorption.x),
+            ("absorption.g", self.absorption.y),
+            ("absorption.b", self.absorption.z),
+            ("waves", self.waves),
+            ("roughness", self.roughness),
+            ("frost", self.frost),
+            ("aberration_strength", self.aberration_strength),
+            ("distortion_strength", self.distortion_strength),
+            ("specks.glow", self.specks.map_or(0.0, |specks| specks.glow)),
+        ];
+        for (field, value) in numeric_fields {
+            if value < 0.0 {
+                warnings.push(MaterialWarning::NegativeValue { field, value });
+            }
+        }
+
+        // Fracciones y colores: por encima de 1 el material devuelve más luz
+        // de la que recibe
+        let bounded_fields = [
+            ("diffuse.r", self.diffuse.x),
+            ("diffuse.g", self.diffuse.y),
+            ("diffuse.b", self.diffuse.z),
+            ("reflectivity", self.reflectivity),
+            ("transparency", self.transparency),
+            ("translucency", self.translucency),
+            ("roughness", self.roughness),
+            ("frost", self.frost),
+        ];
+        for (field, value) in bounded_fields {
+            if value > 1.0 {
+                warnings.push(MaterialWarning::AboveMaximum {
+                    field,
+                    value,
+                    max: 1.0,
+                });
+            }
+        }
+
+        if self.texture.is_some() && self.unlit && self.emission_strength <= 0.0 {
+            warnings.push(MaterialWarning::UnlitWithoutEmission);
+        }
+
+        warnings
+    }
+}
+
 /// Convierte un `Vector3` (0.0–1.0) en un `Color` de Raylib (0–255).
 pub fn vector3_to_color(v: Vector3) -> Color {
     Color::new(
@@ -126,3 +427,203 @@ pub fn color_to_vector3(color: Color) -> Vector3 {
         color.b as f32 / 255.0,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Material válido sobre el que cada prueba rompe un campo
+    fn plain() -> Material {
+        Material::new(
+            Vector3::new(0.5, 0.5, 0.5),
+            [0.8, 0.2],
+            10.0,
+            0.1,
+            0.0,
+            1.0,
+            None,
+            None,
+        )
+    }
+
+    /// Material caliente (emisivo y con textura) hacia el que se interpola
+    fn hot() -> Material {
+        let mut material = Material::new_emissive(
+            Vector3::new(1.0, 0.3, 0.1),
+            [0.6, 0.4],
+            30.0,
+            0.5,
+            0.2,
+            1.4,
+            Some("textures/magma.png".to_string()),
+            None,
+            Some(Vector3::new(1.0, 0.5, 0.0)),
+            4.0,
+        );
+        material.unlit = true;
+        material
+    }
+
+    /// Igualdad con margen para lo que pasa por `a + (b - a) * t`
+    fn assert_close(actual: Vector3, expected: Vector3) {
+        assert!(
+            (actual - expected).length() < 1e-5,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn lerp_endpoints_match_inputs() {
+        let (a, b) = (plain(), hot());
+        let start = Material::lerp(&a, &b, 0.0);
+        assert_close(start.diffuse, a.diffuse);
+        assert_eq!(start.specular, a.specular);
+        assert_eq!(start.emission_strength, 0.0);
+        assert_eq!(start.texture, None);
+        assert!(!start.unlit);
+
+        let end = Material::lerp(&a, &b, 1.0);
+        assert_close(end.diffuse, b.diffuse);
+        assert!((end.reflectivity - b.reflectivity).abs() < 1e-5);
+        assert!((end.refractive_index - b.refractive_index).abs() < 1e-5);
+        assert_close(end.emission_color.unwrap(), b.emission_color.unwrap());
+        assert!((end.emission_strength - b.emission_strength).abs() < 1e-5);
+        assert_eq!(end.texture, b.texture);
+        assert!(end.unlit);
+    }
+
+    #[test]
+    fn lerp_midpoint_mixes_numbers_and_picks_b() {
+        let (a, b) = (plain(), hot());
+        let mid = Material::lerp(&a, &b, 0.5);
+        assert_close(mid.diffuse, Vector3::new(0.75, 0.4, 0.3));
+        assert_close(
+            Vector3::new(mid.albedo[0], mid.albedo[1], mid.specular),
+            Vector3::new(0.7, 0.3, 20.0),
+        );
+        assert!((mid.emission_strength - 2.0).abs() < 1e-5);
+        // Sin color de emisión, `a` aporta su difuso
+        assert_close(mid.emission_color.unwrap(), Vector3::new(0.75, 0.5, 0.25));
+        // Texturas y flags: de `b` desde t = 0.5
+        assert_eq!(mid.texture, b.texture);
+        assert!(mid.unlit);
+    }
+
+    #[test]
+    fn lerp_clamps_t_outside_unit_range() {
+        let (a, b) = (plain(), hot());
+        let below = Material::lerp(&a, &b, -2.0);
+        assert_close(below.diffuse, a.diffuse);
+        assert_eq!(below.emission_strength, 0.0);
+        let above = Material::lerp(&a, &b, 3.0);
+        assert_close(above.diffuse, b.diffuse);
+        assert!((above.emission_strength - b.emission_strength).abs() < 1e-5);
+    }
+
+    #[test]
+    fn plain_material_has_no_warnings() {
+        assert!(plain().validate().is_empty());
+    }
+
+    #[test]
+    fn albedo_out_of_range() {
+        let mut material = plain();
+        material.albedo = [1.5, -0.5];
+        let warnings = material.validate();
+        assert!(warnings.contains(&MaterialWarning::AlbedoOutOfRange {
+            index: 0,
+            value: 1.5
+        }));
+        assert!(warnings.contains(&MaterialWarning::AlbedoOutOfRange {
+            index: 1,
+            value: -0.5
+        }));
+    }
+
+    #[test]
+    fn albedo_sum_exceeded() {
+        let mut material = plain();
+        material.albedo = [0.7, 0.6];
+        assert_eq!(
+            material.validate(),
+            vec![MaterialWarning::AlbedoSumExceeded(1.3)]
+        );
+    }
+
+    #[test]
+    fn reflectivity_plus_transparency_exceeded() {
+        let mut material = plain();
+        material.reflectivity = 0.6;
+        material.transparency = 0.6;
+        assert_eq!(
+            material.validate(),
+            vec![MaterialWarning::EnergyExceeded {
+                reflectivity: 0.6,
+                transparency: 0.6
+            }]
+        );
+    }
+
+    #[test]
+    fn refractive_index_below_one() {
+        let mut material = plain();
+        material.refractive_index = 0.9;
+        material.refractive_index_rgb = Some([1.5, 0.8, 1.52]);
+        assert_eq!(
+            material.validate(),
+            vec![
+                MaterialWarning::RefractiveIndexBelowOne(0.9),
+                MaterialWarning::RefractiveIndexBelowOne(0.8),
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_value() {
+        let mut material = plain();
+        material.absorption = Vector3::new(0.0, -0.2, 0.0);
+        material.roughness = -1.0;
+        let warnings = material.validate();
+        assert!(warnings.contains(&MaterialWarning::NegativeValue {
+            field: "absorption.g",
+            value: -0.2
+        }));
+        assert!(warnings.contains(&MaterialWarning::NegativeValue {
+            field: "roughness",
+            value: -1.0
+        }));
+    }
+
+    #[test]
+    fn above_maximum() {
+        let mut material = plain();
+        material.frost = 1.5;
+        material.diffuse.x = 2.0;
+        let warnings = material.validate();
+        assert!(warnings.contains(&MaterialWarning::AboveMaximum {
+            field: "frost",
+            value: 1.5,
+            max: 1.0
+        }));
+        assert!(warnings.contains(&MaterialWarning::AboveMaximum {
+            field: "diffuse.r",
+            value: 2.0,
+            max: 1.0
+        }));
+    }
+
+    #[test]
+    fn unlit_texture_without_emission() {
+        let mut material = plain();
+        material.unlit = true;
+        material.texture = Some("textures/stone.jpg".to_string());
+        assert_eq!(
+            material.validate(),
+            vec![MaterialWarning::UnlitWithoutEmission]
+        );
+        material.emission_strength = 1.0;
+        assert!(material.validate().is_empty());
+    }
+}
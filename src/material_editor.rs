@@ -0,0 +1,291 @@
+// material_editor.rs - Editor de materiales en vivo sobre el bloque bajo la mira
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::material::Material;
+use crate::notifications::Notifications;
+use crate::shared_scene::SharedScene;
+
+/// Propiedades del material que se pueden ajustar desde el editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Property {
+    Reflectivity,
+    Transparency,
+    RefractiveIndex,
+    Specular,
+    Translucency,
+    EmissionStrength,
+}
+
+const PROPERTIES: [Property; 6] = [
+    Property::Reflectivity,
+    Property::Transparency,
+    Property::RefractiveIndex,
+    Property::Specular,
+    Property::Translucency,
+    Property::EmissionStrength,
+];
+
+impl Property {
+    fn label(self) -> &'static str {
+        match self {
+            Property::Reflectivity => "Reflectividad",
+            Property::Transparency => "Transparencia",
+            Property::RefractiveIndex => "Índice refracción",
+            Property::Specular => "Especular",
+            Property::Translucency => "Translucidez",
+            Property::EmissionStrength => "Emisión",
+        }
+    }
+
+    fn value(self, material: &Material) -> f32 {
+        match self {
+            Property::Reflectivity => material.reflectivity,
+            Property::Transparency => material.transparency,
+            Property::RefractiveIndex => material.refractive_index,
+            Property::Specular => material.specular,
+            Property::Translucency => material.translucency,
+            Property::EmissionStrength => material.emission_strength,
+        }
+    }
+
+    /// Suma `steps` incrementos a la propiedad, respetando su rango válido
+    fn adjust(self, material: &mut Material, steps: f32) {
+        match self {
+            Property::Reflectivity => {
+                material.reflectivity = (material.reflectivity + 0.05 * steps).clamp(0.0, 1.0)
+            }
+            Property::Transparency => {
+                material.transparency = (material.transparency + 0.05 * steps).clamp(0.0, 1.0)
+            }
+            Property::RefractiveIndex => {
+                material.refractive_index =
+                    (material.refractive_index + 0.01 * steps).clamp(1.0, 3.0)
+            }
+            Property::Specular => material.specular = (material.specular + 5.0 * steps).max(0.0),
+            Property::Translucency => {
+                material.translucency = (material.translucency + 0.05 * steps).clamp(0.0, 1.0)
+            }
+            Property::EmissionStrength => {
+                material.emission_strength = (material.emission_strength + 0.1 * steps).max(0.0)
+            }
+        }
+    }
+}
+
+/// Estado del editor: qué bloque se edita y qué propiedad está seleccionada
+#[derive(Default)]
+pub struct MaterialEditor {
+    block_index: Option<usize>,
+    selected: usize,
+}
+
+impl MaterialEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.block_index.is_some()
+    }
+
+    /// Bloque que se está editando
+    pub fn block_index(&self) -> Option<usize> {
+        self.block_index
+    }
+
+    pub fn open(&mut self, block_index: usize) {
+        self.block_index = Some(block_index);
+        self.selected = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.block_index = None;
+    }
+
+    /// Flechas arriba/abajo eligen propiedad, +/- la ajustan y P imprime el material.
+    /// El cambio llega al render en el próximo trabajo (ver `SharedScene`).
+    /// Devuelve true si se modificó el material.
+    pub fn handle_input(
+        &mut self,
+        rl: &RaylibHandle,
+        scene: &mut SharedScene,
+        notifications: &mut Notifications,
+    ) -> bool {
+        let Some(index) = self.block_index else {
+            return false;
+        };
+
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1) % PROPERTIES.len();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = (self.selected + PROPERTIES.len() - 1) % PROPERTIES.len();
+        }
+
+        let mut steps = 0.0;
+        if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) || rl.is_key_pressed(KeyboardKey::KEY_KP_ADD) {
+            steps += 1.0;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_MINUS)
+            || rl.is_key_pressed(KeyboardKey::KEY_KP_SUBTRACT)
+        {
+            steps -= 1.0;
+        }
+        let mut edited = false;
+        if steps != 0.0
+            && let Some(block) = scene.get(index)
+        {
+            let mut block = block.clone();
+            PROPERTIES[self.selected].adjust(&mut block.material, steps);
+            scene.replace_block(index, block);
+            edited = true;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_P)
+            && let Some(block) = scene.get(index)
+        {
+            println!("{}", material_to_code(&block.material));
+            notifications.info("Código del material impreso en la terminal");
+        }
+        edited
+    }
+
+    /// Dibuja el panel con los valores actuales
+    pub fn draw(&self, d: &mut RaylibDrawHandle, scene: &[Block], x: i32, y: i32) {
+        let Some(block) = self.block_index.and_then(|index| scene.get(index)) else {
+            return;
+        };
+
+        let line_height = 18;
+        let height = line_height * (PROPERTIES.len() as i32 + 3) + 10;
+        d.draw_rectangle(x, y, 260, height, Color::new(0, 0, 0, 170));
+
+        let pos = block.position;
+        d.draw_text(
+            &format!("Material en ({:.0}, {:.0}, {:.0})", pos.x, pos.y, pos.z),
+            x + 8,
+            y + 6,
+            16,
+            Color::YELLOW,
+        );
+
+        for (i, property) in PROPERTIES.iter().enumerate() {
+            let color = if i == self.selected {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            let marker = if i == self.selected { ">" } else { " " };
+            d.draw_text(
+                &format!(
+                    "{} {}: {:.2}",
+                    marker,
+                    property.label(),
+                    property.value(&block.material)
+                ),
+                x + 8,
+                y + 6 + line_height * (i as i32 + 1),
+                16,
+                color,
+            );
+        }
+
+        d.draw_text(
+            "Flechas: elegir | +/-: ajustar",
+            x + 8,
+            y + 6 + line_height * (PROPERTIES.len() as i32 + 1),
+            14,
+            Color::LIGHTGRAY,
+        );
+        d.draw_text(
+            "P: imprimir código | M: cerrar",
+            x + 8,
+            y + 6 + line_height * (PROPERTIES.len() as i32 + 2),
+            14,
+            Color::LIGHTGRAY,
+        );
+    }
+}
+
+fn vec3_code(v: Vector3) -> String {
+    format!("Vector3::new({:.3}, {:.3}, {:.3})", v.x, v.y, v.z)
+}
+
+/// Genera el literal de Rust del material, listo para pegar en block_types.rs
+pub fn material_to_code(material: &Material) -> String {
+    let texture = match &material.texture {
+        Some(path) => format!("Some(\"{}\".to_string())", path),
+        None => "None".to_string(),
+    };
+    let normal_map = match &material.normal_map_id {
+        Some(path) => format!("Some(\"{}\".to_string())", path),
+        None => "None".to_string(),
+    };
+    let emission_color = match material.emission_color {
+        Some(color) => format!("Some({})", vec3_code(color)),
+        None => "None".to_string(),
+    };
+    let refractive_index_rgb = match material.refractive_index_rgb {
+        Some([r, g, b]) => format!("Some([{:.3}, {:.3}, {:.3}])", r, g, b),
+        None => "None".to_string(),
+    };
+    let specks = match material.specks {
+        Some(specks) => format!(
+            "Some(Specks::new({}, {:.3}))",
+            vec3_code(specks.color),
+            specks.glow
+        ),
+        None => "None".to_string(),
+    };
+
+    format!(
+        "Material {{
+    diffuse: {},
+    albedo: [{:.3}, {:.3}],
+    specular: {:.1},
+    reflectivity: {:.3},
+    transparency: {:.3},
+    refractive_index: {:.3},
+    texture: {},
+    normal_map_id: {},
+    emission_color: {},
+    emission_strength: {:.3},
+    translucency: {:.3},
+    unlit: {},
+    refractive_index_rgb: {},
+    absorption: {},
+    waves: {:.3},
+    roughness: {:.3},
+    frost: {:.3},
+    specks: {},
+    aberration_strength: {:.3},
+    distortion_strength: {:.3},
+    caustics: {},
+    fresnel_reflection: {},
+}}",
+        vec3_code(material.diffuse),
+        material.albedo[0],
+        material.albedo[1],
+        material.specular,
+        material.reflectivity,
+        material.transparency,
+        material.refractive_index,
+        texture,
+        normal_map,
+        emission_color,
+        material.emission_strength,
+        material.translucency,
+        material.unlit,
+        refractive_index_rgb,
+        vec3_code(material.absorption),
+        material.waves,
+        material.roughness,
+        material.frost,
+        specks,
+        material.aberration_strength,
+        material.distortion_strength,
+        material.caustics,
+        material.fresnel_reflection,
+    )
+}
@@ -0,0 +1,35 @@
+// motion_blur.rs - Desenfoque de movimiento de la cámara
+/// Desenfoque por movimiento de la cámara: el frame promedia varias pasadas
+/// con la cámara interpolada entre la pose del frame anterior y la actual.
+/// Con la cámara quieta no se gasta nada (una sola pasada).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MotionBlur {
+    pub enabled: bool,
+    /// Fracción del recorrido desde la pose anterior que cubre el
+    /// obturador: 1 = todo el camino, 0.5 = la mitad más reciente
+    pub shutter: f32,
+    /// Pasadas por frame (poses a lo largo del recorrido)
+    pub samples: u32,
+}
+
+impl Default for MotionBlur {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shutter: 0.5,
+            samples: 4,
+        }
+    }
+}
+
+impl MotionBlur {
+    /// Instante de la pasada `sample` entre la pose anterior (0) y la actual
+    /// (1). La pasada 0 es la pose actual; las demás retroceden en pasos
+    /// iguales hasta cubrir `shutter`.
+    pub fn sample_time(&self, sample: u32) -> f32 {
+        let samples = self.samples.max(1) as f32;
+        1.0 - self.shutter.clamp(0.0, 1.0) * sample as f32 / samples
+    }
+}
@@ -0,0 +1,139 @@
+// notifications.rs - Avisos breves en el HUD (captura guardada, escena, errores...)
+use std::collections::VecDeque;
+
+use raylib::prelude::*;
+
+use crate::logger::NOTIFICATION_TARGET;
+
+/// Avisos guardados; los más viejos se descartan para que una ráfaga no
+/// haga crecer la cola
+const MAX_NOTIFICATIONS: usize = 16;
+/// Avisos que se dibujan a la vez (los más nuevos)
+const MAX_VISIBLE: usize = 5;
+/// Segundos por defecto en pantalla
+const DEFAULT_DURATION: f32 = 3.0;
+/// Segundos finales en los que el aviso se desvanece
+const FADE_SECONDS: f32 = 1.0;
+const FONT_SIZE: i32 = 16;
+const LINE_HEIGHT: i32 = 22;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::WHITE,
+            Severity::Warning => Color::YELLOW,
+            Severity::Error => Color::RED,
+        }
+    }
+}
+
+struct Notification {
+    text: String,
+    severity: Severity,
+    duration: f32,
+    /// Segundos que le quedan en pantalla
+    remaining: f32,
+}
+
+/// Cola de avisos del HUD
+#[derive(Default)]
+pub struct Notifications {
+    queue: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Agrega un aviso por `duration` segundos. También queda en el log con
+    /// el nivel que corresponde a la severidad.
+    pub fn notify(&mut self, text: impl Into<String>, severity: Severity, duration: f32) {
+        let text = text.into();
+        let level = match severity {
+            Severity::Info => log::Level::Info,
+            Severity::Warning => log::Level::Warn,
+            Severity::Error => log::Level::Error,
+        };
+        log::log!(target: NOTIFICATION_TARGET, level, "{}", text);
+        self.show(text, severity, duration);
+    }
+
+    /// Agrega un aviso sin registrarlo (ya viene del log)
+    pub fn show(&mut self, text: String, severity: Severity, duration: f32) {
+        if self.queue.len() == MAX_NOTIFICATIONS {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(Notification {
+            text,
+            severity,
+            duration,
+            remaining: duration,
+        });
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.notify(text, Severity::Info, DEFAULT_DURATION);
+    }
+
+    pub fn warning(&mut self, text: impl Into<String>) {
+        self.notify(text, Severity::Warning, DEFAULT_DURATION * 1.5);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.notify(text, Severity::Error, DEFAULT_DURATION * 2.0);
+    }
+
+    /// Muestra las advertencias y errores que se registraron con `log` fuera
+    /// de los avisos (texturas, archivos de configuración, grabación...)
+    pub fn show_logged(&mut self) {
+        for (level, text) in crate::logger::take_notices() {
+            if level == log::Level::Error {
+                self.show(text, Severity::Error, DEFAULT_DURATION * 2.0);
+            } else {
+                self.show(text, Severity::Warning, DEFAULT_DURATION * 1.5);
+            }
+        }
+    }
+
+    /// Descuenta el tiempo del frame y quita los avisos vencidos
+    pub fn update(&mut self, dt: f32) {
+        for notification in &mut self.queue {
+            notification.remaining -= dt;
+        }
+        self.queue.retain(|notification| notification.remaining > 0.0);
+    }
+
+    /// Dibuja los últimos avisos apilados hacia arriba desde la esquina
+    /// inferior derecha; `bottom` es la y de la base de la pila
+    pub fn draw(&self, d: &mut RaylibDrawHandle, window_width: i32, bottom: i32) {
+        for (i, notification) in self.queue.iter().rev().take(MAX_VISIBLE).enumerate() {
+            let fade = FADE_SECONDS.min(notification.duration);
+            let alpha = (notification.remaining / fade).clamp(0.0, 1.0);
+            let width = d.measure_text(&notification.text, FONT_SIZE);
+            let x = window_width - width - 16;
+            let y = bottom - LINE_HEIGHT * (i as i32 + 1);
+            d.draw_rectangle(
+                x - 6,
+                y - 3,
+                width + 12,
+                LINE_HEIGHT - 2,
+                Color::new(0, 0, 0, (170.0 * alpha) as u8),
+            );
+            d.draw_text(
+                &notification.text,
+                x,
+                y,
+                FONT_SIZE,
+                notification.severity.color().fade(alpha),
+            );
+        }
+    }
+}
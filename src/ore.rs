@@ -0,0 +1,134 @@
+// ore.rs - Vetas de mineral: puntos de color (y brillo) sobre la piedra
+use raylib::prelude::*;
+
+use crate::rng::splitmix64;
+
+/// Puntos por lado de cada cara (la grilla de la textura de piedra)
+pub const SPECK_GRID: u32 = 8;
+
+/// Fracción de los puntos de la grilla que son mineral
+pub const SPECK_COVERAGE: f32 = 0.15;
+
+/// Vetas de un material: el color de los puntos y cuánto brillan en la
+/// oscuridad (0.0 = no brillan)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Specks {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub color: Vector3,
+    pub glow: f32,
+}
+
+impl Specks {
+    pub fn new(color: Vector3, glow: f32) -> Self {
+        Self { color, glow }
+    }
+}
+
+/// Si (u, v) cae en un punto de mineral de la cara de normal `normal` de
+/// la celda `cell`. El patrón sale de un hash de la celda, la cara y el
+/// punto de la grilla: es siempre el mismo para un bloque y distinto entre
+/// bloques, sin texturas extra.
+pub fn is_speck(cell: (i32, i32, i32), normal: Vector3, u: f32, v: f32) -> bool {
+    let grid = SPECK_GRID as f32;
+    let column = (u.clamp(0.0, 1.0) * grid).min(grid - 1.0) as u64;
+    let row = (v.clamp(0.0, 1.0) * grid).min(grid - 1.0) as u64;
+    // Una de las 27 combinaciones de signos de la normal
+    let face = normal.x.round() as i64 * 9 + normal.y.round() as i64 * 3 + normal.z.round() as i64;
+
+    let (x, y, z) = cell;
+    let mut hash = splitmix64((x as u32 as u64) | ((y as u32 as u64) << 32));
+    hash = splitmix64(hash ^ (z as u32 as u64) ^ ((face as u64) << 32));
+    hash = splitmix64(hash ^ (row * SPECK_GRID as u64 + column));
+    // Los 24 bits altos como número en [0, 1)
+    ((hash >> 40) as f32 / (1u64 << 24) as f32) < SPECK_COVERAGE
+}
+
+/// `is_speck` para un punto sobre la cara de un bloque de lado 1: la celda
+/// es la del centro del bloque, medio lado hacia adentro de la cara
+pub fn speck_at(point: Vector3, normal: Vector3, u: f32, v: f32) -> bool {
+    let center = point - normal * 0.5;
+    let cell = (
+        center.x.round() as i32,
+        center.y.round() as i32,
+        center.z.round() as i32,
+    );
+    is_speck(cell, normal, u, v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Centros de los puntos de la grilla de una cara
+    fn grid_uvs() -> impl Iterator<Item = (f32, f32)> {
+        let grid = SPECK_GRID as f32;
+        (0..SPECK_GRID * SPECK_GRID).map(move |i| {
+            let (column, row) = ((i % SPECK_GRID) as f32, (i / SPECK_GRID) as f32);
+            ((column + 0.5) / grid, (row + 0.5) / grid)
+        })
+    }
+
+    #[test]
+    fn pattern_is_deterministic_and_constant_inside_a_speck() {
+        let normal = Vector3::new(0.0, 0.0, -1.0);
+        let half = 0.45 / SPECK_GRID as f32;
+        for cell in [(0, 0, 0), (3, -2, 7), (-40, 5, 12)] {
+            for (u, v) in grid_uvs() {
+                let speck = is_speck(cell, normal, u, v);
+                assert_eq!(is_speck(cell, normal, u, v), speck);
+                // Todo el punto de la grilla es del mismo color
+                assert_eq!(is_speck(cell, normal, u - half, v + half), speck);
+                assert_eq!(is_speck(cell, normal, u + half, v - half), speck);
+            }
+        }
+    }
+
+    #[test]
+    fn coverage_matches_the_speck_fraction() {
+        let mut specks = 0;
+        let mut total = 0;
+        for x in -10..10 {
+            for z in -10..10 {
+                for normal in [Vector3::new(0.0, 1.0, 0.0), Vector3::new(-1.0, 0.0, 0.0)] {
+                    for (u, v) in grid_uvs() {
+                        specks += is_speck((x, -3, z), normal, u, v) as u32;
+                        total += 1;
+                    }
+                }
+            }
+        }
+        let coverage = specks as f32 / total as f32;
+        assert!(
+            (coverage - SPECK_COVERAGE).abs() < 0.02,
+            "cobertura {} (esperada {})",
+            coverage,
+            SPECK_COVERAGE
+        );
+    }
+
+    #[test]
+    fn neighbouring_blocks_and_faces_differ() {
+        let pattern = |cell, normal| {
+            grid_uvs()
+                .map(|(u, v)| is_speck(cell, normal, u, v))
+                .collect::<Vec<_>>()
+        };
+        let top = Vector3::new(0.0, 1.0, 0.0);
+        let base = pattern((0, 0, 0), top);
+        assert_ne!(base, pattern((1, 0, 0), top));
+        assert_ne!(base, pattern((0, 0, 1), top));
+        assert_ne!(base, pattern((0, 0, 0), Vector3::new(0.0, -1.0, 0.0)));
+    }
+
+    #[test]
+    fn speck_at_uses_the_cell_behind_the_face() {
+        let normal = Vector3::new(1.0, 0.0, 0.0);
+        for (u, v) in grid_uvs() {
+            assert_eq!(
+                speck_at(Vector3::new(2.5, -1.0, 4.0), normal, u, v),
+                is_speck((2, -1, 4), normal, u, v)
+            );
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// orientation.rs - Rotaciones de bloque que llevan ejes a ejes
+use raylib::prelude::*;
+
+use crate::block::Face;
+
+/// Orientación de un bloque: la cara del mundo hacia la que apunta su
+/// arriba local y cuántos cuartos de vuelta gira alrededor de ese eje.
+/// Cubre las 24 rotaciones que llevan ejes a ejes; como las cajas siguen
+/// alineadas, girar un bloque es solo reinterpretar normales y UV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orientation {
+    pub up: Face,
+    pub turns: u8,
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self::UPRIGHT
+    }
+}
+
+impl Orientation {
+    /// Sin rotar: el arriba local es +Y
+    pub const UPRIGHT: Orientation = Orientation {
+        up: Face::Top,
+        turns: 0,
+    };
+
+    pub fn new(up: Face, turns: u8) -> Self {
+        Self {
+            up,
+            turns: turns % 4,
+        }
+    }
+
+    pub fn is_upright(self) -> bool {
+        self == Self::UPRIGHT
+    }
+
+    /// Lleva un vector de coordenadas del bloque a coordenadas del mundo
+    pub fn to_world(self, v: Vector3) -> Vector3 {
+        // Primero el giro alrededor del eje Y local...
+        let v = match self.turns % 4 {
+            0 => v,
+            1 => Vector3::new(v.z, v.y, -v.x),
+            2 => Vector3::new(-v.x, v.y, -v.z),
+            _ => Vector3::new(-v.z, v.y, v.x),
+        };
+        // ...y después el arriba local se lleva a la cara `up`
+        match self.up {
+            Face::Top => v,
+            Face::Bottom => Vector3::new(v.x, -v.y, -v.z),
+            Face::East => Vector3::new(v.y, -v.x, v.z),
+            Face::West => Vector3::new(-v.y, v.x, v.z),
+            Face::South => Vector3::new(v.x, -v.z, v.y),
+            Face::North => Vector3::new(v.x, v.z, -v.y),
+        }
+    }
+
+    /// Inversa de `to_world`: la rotación es ortonormal, así que cada
+    /// componente local es la proyección sobre el eje local girado
+    pub fn to_local(self, v: Vector3) -> Vector3 {
+        Vector3::new(
+            v.dot(self.to_world(Vector3::new(1.0, 0.0, 0.0))),
+            v.dot(self.to_world(Vector3::new(0.0, 1.0, 0.0))),
+            v.dot(self.to_world(Vector3::new(0.0, 0.0, 1.0))),
+        )
+    }
+
+    /// Caja (mínimo, máximo) girada: sigue alineada a los ejes
+    pub fn rotate_box(self, min: Vector3, max: Vector3) -> (Vector3, Vector3) {
+        let (a, b) = (self.to_world(min), self.to_world(max));
+        (
+            Vector3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            Vector3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        )
+    }
+}
@@ -0,0 +1,249 @@
+// pause_menu.rs - Menú de pausa (ESC): reanudar, escenas, ajustes y salir
+use raylib::prelude::*;
+
+use crate::keybinds::{Action, InputMap};
+use crate::render_settings::RenderSettings;
+
+/// Acciones que el loop principal debe ejecutar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseAction {
+    SwitchScene,
+    SaveScene,
+    /// Guardar `settings.toml` y `config.toml` sin salir
+    SaveSettings,
+    /// Guardar la escena y, si se pudo, salir
+    SaveAndQuit,
+    Quit,
+}
+
+/// Página visible del menú
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Page {
+    #[default]
+    Main,
+    RenderSettings,
+    /// Confirmación de salida con cambios sin guardar
+    ConfirmQuit,
+}
+
+const MAIN_ITEMS: [&str; 6] = [
+    "Reanudar",
+    "Cambiar escena",
+    "Ajustes de render",
+    "Guardar escena",
+    "Guardar ajustes",
+    "Salir",
+];
+
+const RENDER_ITEMS: [&str; 5] = [
+    "Sombras",
+    "Reflejos",
+    "Refracción",
+    "Oclusión ambiental",
+    "Volver",
+];
+
+const CONFIRM_ITEMS: [&str; 3] = ["Guardar y salir", "Salir sin guardar", "Cancelar"];
+
+const PANEL_WIDTH: i32 = 300;
+const ITEM_HEIGHT: i32 = 30;
+const TITLE_HEIGHT: i32 = 40;
+
+/// Estado del menú de pausa
+#[derive(Default)]
+pub struct PauseMenu {
+    open: bool,
+    page: Page,
+    selected: usize,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Abre el menú en la página principal, o lo cierra
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.page = Page::Main;
+        self.selected = 0;
+    }
+
+    /// ESC vuelve atrás en las subpáginas y cierra desde la principal
+    fn back(&mut self) {
+        if self.page == Page::Main {
+            self.open = false;
+        } else {
+            self.page = Page::Main;
+            self.selected = 0;
+        }
+    }
+
+    fn items(&self) -> &'static [&'static str] {
+        match self.page {
+            Page::Main => &MAIN_ITEMS,
+            Page::RenderSettings => &RENDER_ITEMS,
+            Page::ConfirmQuit => &CONFIRM_ITEMS,
+        }
+    }
+
+    /// Flechas y Enter o el mouse navegan. Devuelve la acción elegida, si hay
+    /// alguna que no se resuelve dentro del menú. `scene_dirty` decide si
+    /// salir pide confirmación.
+    pub fn handle_input(
+        &mut self,
+        rl: &RaylibHandle,
+        input: &InputMap,
+        render: &mut RenderSettings,
+        scene_dirty: bool,
+    ) -> Option<PauseAction> {
+        if input.is_pressed(Action::Pause) {
+            if self.open {
+                self.back();
+            } else {
+                self.toggle();
+            }
+            return None;
+        }
+        if !self.open {
+            return None;
+        }
+
+        let count = self.items().len();
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1) % count;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = (self.selected + count - 1) % count;
+        }
+
+        let mut activated = rl.is_key_pressed(KeyboardKey::KEY_ENTER);
+        // El mouse solo cambia la selección al moverse, para no pisar el teclado
+        let mouse = rl.get_mouse_position();
+        let delta = rl.get_mouse_delta();
+        let clicked = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
+        let (width, height) = (rl.get_screen_width(), rl.get_screen_height());
+        for i in 0..count {
+            if self
+                .item_rect(i, width, height)
+                .check_collision_point_rec(mouse)
+            {
+                if delta.x != 0.0 || delta.y != 0.0 || clicked {
+                    self.selected = i;
+                }
+                activated |= clicked;
+            }
+        }
+        if !activated {
+            return None;
+        }
+
+        match (self.page, self.selected) {
+            (Page::Main, 0) => self.open = false,
+            (Page::Main, 1) => return Some(PauseAction::SwitchScene),
+            (Page::Main, 2) => {
+                self.page = Page::RenderSettings;
+                self.selected = 0;
+            }
+            (Page::Main, 3) => return Some(PauseAction::SaveScene),
+            (Page::Main, 4) => return Some(PauseAction::SaveSettings),
+            (Page::Main, _) if scene_dirty => {
+                self.page = Page::ConfirmQuit;
+                self.selected = 0;
+            }
+            (Page::Main, _) => return Some(PauseAction::Quit),
+            (Page::RenderSettings, 0) => render.shadows = !render.shadows,
+            (Page::RenderSettings, 1) => render.reflections = !render.reflections,
+            (Page::RenderSettings, 2) => render.refractions = !render.refractions,
+            (Page::RenderSettings, 3) => render.ambient_occlusion = !render.ambient_occlusion,
+            (Page::RenderSettings, _) => self.back(),
+            (Page::ConfirmQuit, 0) => return Some(PauseAction::SaveAndQuit),
+            (Page::ConfirmQuit, 1) => return Some(PauseAction::Quit),
+            (Page::ConfirmQuit, _) => self.back(),
+        }
+        None
+    }
+
+    /// Rectángulo en pantalla de la opción `index`, centrado en la ventana
+    fn item_rect(&self, index: usize, window_width: i32, window_height: i32) -> Rectangle {
+        let (x, y) = self.panel_origin(window_width, window_height);
+        Rectangle::new(
+            (x + 10) as f32,
+            (y + TITLE_HEIGHT + ITEM_HEIGHT * index as i32) as f32,
+            (PANEL_WIDTH - 20) as f32,
+            (ITEM_HEIGHT - 4) as f32,
+        )
+    }
+
+    fn panel_height(&self) -> i32 {
+        TITLE_HEIGHT + ITEM_HEIGHT * self.items().len() as i32 + 10
+    }
+
+    fn panel_origin(&self, window_width: i32, window_height: i32) -> (i32, i32) {
+        (
+            (window_width - PANEL_WIDTH) / 2,
+            (window_height - self.panel_height()) / 2,
+        )
+    }
+
+    /// Dibuja el menú sobre la imagen (oscurecida) y el nombre de la escena
+    pub fn draw(
+        &self,
+        d: &mut RaylibDrawHandle,
+        render: &RenderSettings,
+        scene_label: &str,
+        window_width: i32,
+        window_height: i32,
+    ) {
+        if !self.open {
+            return;
+        }
+
+        d.draw_rectangle(0, 0, window_width, window_height, Color::new(0, 0, 0, 120));
+        let (x, y) = self.panel_origin(window_width, window_height);
+        d.draw_rectangle(
+            x,
+            y,
+            PANEL_WIDTH,
+            self.panel_height(),
+            Color::new(20, 20, 20, 220),
+        );
+
+        let title = match self.page {
+            Page::Main => "Pausa",
+            Page::RenderSettings => "Ajustes de render",
+            Page::ConfirmQuit => "Hay cambios sin guardar",
+        };
+        d.draw_text(title, x + 12, y + 12, 20, Color::YELLOW);
+
+        let toggles = [
+            render.shadows,
+            render.reflections,
+            render.refractions,
+            render.ambient_occlusion,
+        ];
+        for (i, item) in self.items().iter().enumerate() {
+            let rect = self.item_rect(i, window_width, window_height);
+            if i == self.selected {
+                d.draw_rectangle_rec(rect, Color::new(255, 255, 255, 40));
+            }
+            let text = match (self.page, i) {
+                (Page::Main, 1) => format!("{}: {}", item, scene_label),
+                (Page::RenderSettings, i) if i < toggles.len() => {
+                    format!("{}: {}", item, if toggles[i] { "ON" } else { "OFF" })
+                }
+                _ => item.to_string(),
+            };
+            let color = if i == self.selected {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            d.draw_text(&text, rect.x as i32 + 8, rect.y as i32 + 5, 18, color);
+        }
+    }
+}
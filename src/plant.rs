@@ -0,0 +1,167 @@
+// plant.rs - Plantas de dos láminas cruzadas (flores y pasto alto)
+use raylib::prelude::*;
+
+use crate::textures::TextureManager;
+
+/// Lado en texels de las texturas de plantas
+const PLANT_TEXTURE_SIZE: u32 = 16;
+
+/// Distancia mínima de un impacto, para que un rayo que sale de la lámina
+/// no vuelva a chocar con ella
+const MIN_HIT_DISTANCE: f32 = 1e-4;
+
+/// Tipo de planta: define la silueta (qué texels son transparentes) y el
+/// color de su textura procedural
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Plant {
+    Flower,
+    TallGrass,
+}
+
+impl Plant {
+    pub const ALL: [Plant; 2] = [Plant::Flower, Plant::TallGrass];
+
+    /// Nombre usado en los archivos de escena
+    pub fn name(self) -> &'static str {
+        match self {
+            Plant::Flower => "flower",
+            Plant::TallGrass => "tall_grass",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|plant| plant.name() == name)
+    }
+
+    /// Ruta con la que los materiales usan la textura procedural
+    pub fn texture_name(self) -> &'static str {
+        match self {
+            Plant::Flower => "procedural/flower",
+            Plant::TallGrass => "procedural/tall_grass",
+        }
+    }
+
+    /// Altura de las láminas (fracción de la celda)
+    pub fn height(self) -> f32 {
+        match self {
+            Plant::Flower => 0.7,
+            Plant::TallGrass => 0.9,
+        }
+    }
+
+    /// Color del texel (x, y), con y = 0 arriba. None si es transparente.
+    fn texel(self, x: u32, y: u32) -> Option<Vector3> {
+        let size = PLANT_TEXTURE_SIZE;
+        match self {
+            Plant::Flower => {
+                // Corola redonda arriba y tallo con dos hojas abajo
+                let (dx, dy) = (x as f32 - 7.5, y as f32 - 4.5);
+                let petal = dx * dx + dy * dy;
+                if petal < 2.5 {
+                    Some(Vector3::new(1.0, 0.85, 0.2))
+                } else if petal < 14.0 {
+                    Some(Vector3::new(0.85, 0.15, 0.2))
+                } else if (x == 7 || x == 8) && y > 7 {
+                    Some(Vector3::new(0.25, 0.55, 0.15))
+                } else if (y == 11 && (4..7).contains(&x)) || (y == 10 && (9..12).contains(&x)) {
+                    Some(Vector3::new(0.3, 0.6, 0.18))
+                } else {
+                    None
+                }
+            }
+            Plant::TallGrass => {
+                // Hojas de distinta altura en columnas salteadas; el gris
+                // (teñido por el difuso) se aclara hacia la punta
+                let blade_height = match x {
+                    1 | 14 => 7,
+                    3 | 11 => 12,
+                    4 | 9 => 15,
+                    6 | 13 => 10,
+                    7 => 16,
+                    _ => 0,
+                };
+                if y < size - blade_height {
+                    return None;
+                }
+                let shade = 0.6 + 0.4 * (size - y) as f32 / size as f32;
+                Some(Vector3::one() * shade)
+            }
+        }
+    }
+
+    /// Color de los texels transparentes: el de las hojas, para que el
+    /// filtrado bilineal no oscurezca los bordes
+    fn fill_color(self) -> Vector3 {
+        match self {
+            Plant::Flower => Vector3::new(0.3, 0.6, 0.18),
+            Plant::TallGrass => Vector3::one() * 0.8,
+        }
+    }
+
+    /// Si la textura es opaca en (u, v): lo que no cubre no se golpea ni
+    /// hace sombra
+    pub fn covers(self, u: f32, v: f32) -> bool {
+        let size = PLANT_TEXTURE_SIZE;
+        let x = ((u * size as f32) as u32).min(size - 1);
+        let y = ((v * size as f32) as u32).min(size - 1);
+        self.texel(x, y).is_some()
+    }
+}
+
+/// Registra las texturas procedurales de todas las plantas
+pub fn insert_plant_textures(tex_mgr: &mut TextureManager) {
+    for plant in Plant::ALL {
+        let size = PLANT_TEXTURE_SIZE;
+        tex_mgr.insert_procedural(plant.texture_name(), size, size, |x, y| {
+            plant.texel(x, y).unwrap_or(plant.fill_color())
+        });
+    }
+}
+
+/// Corta el rayo con las dos láminas verticales de una planta, cruzadas en
+/// el centro de la celda (`center`, largo `extents` en cada eje). Las dos
+/// caras de cada lámina se ven: la normal apunta siempre contra el rayo.
+/// Retorna la distancia, la normal y las UV del impacto más cercano que cae
+/// en una zona opaca de la textura.
+pub fn intersect_cross(
+    plant: Plant,
+    center: &Vector3,
+    extents: Vector3,
+    origin: &Vector3,
+    dir: &Vector3,
+) -> Option<(f32, Vector3, f32, f32)> {
+    let bottom = center.y - extents.y * 0.5;
+    let height = plant.height() * extents.y;
+    let mut nearest: Option<(f32, Vector3, f32, f32)> = None;
+
+    for normal in [Vector3::new(0.0, 0.0, 1.0), Vector3::new(1.0, 0.0, 0.0)] {
+        let denom = normal.dot(*dir);
+        if denom.abs() < 1e-8 {
+            continue;
+        }
+        let distance = (*center - *origin).dot(normal) / denom;
+        if distance < MIN_HIT_DISTANCE || nearest.is_some_and(|(best, ..)| distance >= best) {
+            continue;
+        }
+
+        let point = *origin + *dir * distance;
+        // La lámina de normal Z se extiende en X y la de normal X en Z
+        let across = if normal.z != 0.0 {
+            (point.x - center.x) / extents.x
+        } else {
+            (point.z - center.z) / extents.z
+        };
+        let up = (point.y - bottom) / height;
+        if across.abs() > 0.5 || !(0.0..=1.0).contains(&up) {
+            continue;
+        }
+
+        let (u, v) = (across + 0.5, 1.0 - up);
+        if plant.covers(u, v) {
+            nearest = Some((distance, normal * -denom.signum(), u, v));
+        }
+    }
+
+    nearest
+}
@@ -0,0 +1,91 @@
+// portal.rs - Portales de luz: ventanas y puertas por las que entra la luz
+// del cielo a los interiores
+use raylib::prelude::*;
+
+use crate::block_types::BlockType;
+use crate::scene::Scene;
+
+/// Distancia máxima desde un punto a los portales que lo iluminan
+pub const PORTAL_RADIUS: f32 = 8.0;
+/// Portales más cercanos que se muestrean en cada punto
+pub const MAX_PORTALS_PER_POINT: usize = 4;
+
+/// Abertura por la que se ve el cielo: una caja alineada a los ejes (la del
+/// vidrio, o la celda marcada con `portal` en el archivo de escena)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightPortal {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl LightPortal {
+    pub fn center(&self) -> Vector3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Punto de la caja para `(a, b, c)` en [0, 1)
+    pub fn point_at(&self, a: f32, b: f32, c: f32) -> Vector3 {
+        let size = self.max - self.min;
+        self.min + Vector3::new(size.x * a, size.y * b, size.z * c)
+    }
+
+    /// Área aproximada que la abertura le muestra a quien la mira: la de
+    /// su cara más grande (el vidrio de un panel, la celda de una puerta)
+    pub fn area(&self) -> f32 {
+        let size = self.max - self.min;
+        (size.x * size.y).max(size.y * size.z).max(size.x * size.z)
+    }
+}
+
+/// Portales de la escena: los bloques de vidrio más las celdas marcadas. Se
+/// arma una vez por frame, como los haces de los faros, así sigue a la
+/// escena cuando se edita.
+pub fn light_portals(scene: &Scene) -> Vec<LightPortal> {
+    let glass = scene
+        .iter()
+        .filter(|block| block.emission.is_none() && block.material.transparency > 0.5)
+        .filter(|block| {
+            matches!(
+                BlockType::identify(block),
+                Some(BlockType::Glass | BlockType::GlassPane | BlockType::WarpedGlass)
+            )
+        })
+        .map(|block| {
+            let (min, max) = block.bounds();
+            LightPortal { min, max }
+        });
+    let cells = scene.portal_cells.iter().map(|&cell| LightPortal {
+        min: cell - Vector3::one() * 0.5,
+        max: cell + Vector3::one() * 0.5,
+    });
+    glass.chain(cells).collect()
+}
+
+/// Hasta `MAX_PORTALS_PER_POINT` portales a menos de `PORTAL_RADIUS` de
+/// `point` y del lado de la normal, del más cercano al más lejano. Se
+/// llama en cada punto sombreado: no reserva memoria.
+pub fn nearby_portals<'a>(
+    portals: &'a [LightPortal],
+    point: &Vector3,
+    normal: &Vector3,
+) -> impl Iterator<Item = &'a LightPortal> {
+    let mut nearest: [Option<(f32, &LightPortal)>; MAX_PORTALS_PER_POINT] =
+        [None; MAX_PORTALS_PER_POINT];
+    for portal in portals {
+        let offset = portal.center() - *point;
+        let distance = offset.length();
+        if distance >= PORTAL_RADIUS || offset.dot(*normal) <= 0.0 {
+            continue;
+        }
+        // Inserción ordenada: el más lejano se cae del final
+        let Some(slot) = nearest
+            .iter()
+            .position(|entry| entry.is_none_or(|(other, _)| distance < other))
+        else {
+            continue;
+        };
+        nearest[slot..].rotate_right(1);
+        nearest[slot] = Some((distance, portal));
+    }
+    nearest.into_iter().flatten().map(|(_, portal)| portal)
+}
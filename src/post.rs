@@ -0,0 +1,448 @@
+// post.rs - Post-procesado en CPU sobre el buffer HDR
+use raylib::prelude::*;
+
+use crate::framebuffer::{Framebuffer, color_to_u32, u32_to_color, u32_to_rgb};
+
+/// Factor de reducción por eje del buffer de bloom (2 = un cuarto de pixeles)
+const BLOOM_DOWNSAMPLE: usize = 2;
+
+/// Pesos del kernel gaussiano (sigma ≈ 2) desde el centro hacia afuera
+const BLOOM_KERNEL: [f32; 5] = [0.2042, 0.1802, 0.1238, 0.0663, 0.0276];
+
+/// Parámetros del bloom (resplandor alrededor de zonas muy brillantes)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BloomSettings {
+    pub enabled: bool,
+    /// Luminancia a partir de la cual un pixel aporta al bloom
+    pub threshold: f32,
+    /// Cuánto del resplandor se suma a la imagen (0 = nada)
+    pub strength: f32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            strength: 0.6,
+        }
+    }
+}
+
+#[inline]
+pub fn luminance(c: Vector3) -> f32 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+/// Aplica bloom al buffer HDR: filtro de brillo, reducción, desenfoque
+/// gaussiano separable (horizontal + vertical) y suma sobre la imagen.
+pub fn apply_bloom(framebuffer: &mut Framebuffer, settings: &BloomSettings) {
+    if !settings.enabled || settings.strength <= 0.0 {
+        return;
+    }
+
+    let width = framebuffer.width as usize;
+    let height = framebuffer.height as usize;
+    let low_w = width.div_ceil(BLOOM_DOWNSAMPLE);
+    let low_h = height.div_ceil(BLOOM_DOWNSAMPLE);
+    let hdr = framebuffer.hdr_mut();
+
+    // 1) Filtro de brillo + reducción: promedio de cada bloque de pixeles
+    let mut bright = vec![Vector3::zero(); low_w * low_h];
+    for ly in 0..low_h {
+        for lx in 0..low_w {
+            let mut sum = Vector3::zero();
+            let mut count = 0.0;
+            for y in (ly * BLOOM_DOWNSAMPLE)..((ly + 1) * BLOOM_DOWNSAMPLE).min(height) {
+                for x in (lx * BLOOM_DOWNSAMPLE)..((lx + 1) * BLOOM_DOWNSAMPLE).min(width) {
+                    let c = hdr[y * width + x];
+                    let lum = luminance(c);
+                    if lum > settings.threshold {
+                        // Solo la parte que excede el umbral, conservando el tono
+                        sum += c * ((lum - settings.threshold) / lum);
+                    }
+                    count += 1.0;
+                }
+            }
+            bright[ly * low_w + lx] = sum / count;
+        }
+    }
+
+    // 2) Desenfoque separable
+    let blurred_h = blur_pass(&bright, low_w, low_h, true);
+    let blurred = blur_pass(&blurred_h, low_w, low_h, false);
+
+    // 3) Sumar el resplandor a la imagen original
+    for y in 0..height {
+        for x in 0..width {
+            let glow = blurred[(y / BLOOM_DOWNSAMPLE) * low_w + x / BLOOM_DOWNSAMPLE];
+            hdr[y * width + x] += glow * settings.strength;
+        }
+    }
+}
+
+/// Una pasada 1D del kernel gaussiano, con los bordes repetidos
+fn blur_pass(src: &[Vector3], width: usize, height: usize, horizontal: bool) -> Vec<Vector3> {
+    let mut dst = vec![Vector3::zero(); src.len()];
+    let radius = BLOOM_KERNEL.len() as isize - 1;
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = Vector3::zero();
+            for offset in -radius..=radius {
+                let weight = BLOOM_KERNEL[offset.unsigned_abs()];
+                let (sx, sy) = if horizontal {
+                    (
+                        (x as isize + offset).clamp(0, width as isize - 1) as usize,
+                        y,
+                    )
+                } else {
+                    (
+                        x,
+                        (y as isize + offset).clamp(0, height as isize - 1) as usize,
+                    )
+                };
+                sum += src[sy * width + sx] * weight;
+            }
+            dst[y * width + x] = sum;
+        }
+    }
+
+    dst
+}
+
+// === FXAA ===
+
+/// Contraste local mínimo relativo para considerar un pixel como borde
+const FXAA_EDGE_THRESHOLD: f32 = 0.166;
+/// Contraste mínimo absoluto (evita trabajar en zonas oscuras)
+const FXAA_EDGE_THRESHOLD_MIN: f32 = 0.0833;
+/// Cuánto suavizado sub-pixel se permite (0 = nada, 1 = máximo)
+const FXAA_SUBPIX: f32 = 0.75;
+/// Pasos de búsqueda a lo largo del borde, como en el preset de calidad 3.11
+const FXAA_SEARCH_STEPS: [f32; 12] = [1.0, 1.0, 1.0, 1.0, 1.0, 1.5, 2.0, 2.0, 2.0, 2.0, 4.0, 8.0];
+
+#[inline]
+fn luma_u32(c: u32) -> f32 {
+    let [r, g, b] = u32_to_rgb(c);
+    (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) / 255.0
+}
+
+/// Mezcla dos colores empaquetados canal por canal
+fn mix_u32(a: u32, b: u32, t: f32) -> u32 {
+    let ca = u32_to_color(a);
+    let cb = u32_to_color(b);
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    color_to_u32(Color::new(
+        mix(ca.r, cb.r),
+        mix(ca.g, cb.g),
+        mix(ca.b, cb.b),
+        mix(ca.a, cb.a),
+    ))
+}
+
+/// Suavizado de bordes estilo FXAA 3.11 (preset de calidad) sobre el buffer
+/// LDR ya mapeado. Las zonas planas no se modifican.
+pub fn apply_fxaa(buffer: &mut [u32], width: u32, height: u32) {
+    let w = width as i32;
+    let h = height as i32;
+    if w < 3 || h < 3 {
+        return;
+    }
+
+    let src = buffer.to_vec();
+    let luma: Vec<f32> = src.iter().map(|&c| luma_u32(c)).collect();
+    let at = |x: i32, y: i32| (y.clamp(0, h - 1) * w + x.clamp(0, w - 1)) as usize;
+    let luma_at = |x: i32, y: i32| luma[at(x, y)];
+
+    for y in 0..h {
+        for x in 0..w {
+            let m = luma_at(x, y);
+            let n = luma_at(x, y - 1);
+            let s = luma_at(x, y + 1);
+            let e = luma_at(x + 1, y);
+            let west = luma_at(x - 1, y);
+
+            let luma_max = m.max(n).max(s).max(e).max(west);
+            let luma_min = m.min(n).min(s).min(e).min(west);
+            let range = luma_max - luma_min;
+            if range < FXAA_EDGE_THRESHOLD_MIN.max(luma_max * FXAA_EDGE_THRESHOLD) {
+                continue;
+            }
+
+            let nw = luma_at(x - 1, y - 1);
+            let ne = luma_at(x + 1, y - 1);
+            let sw = luma_at(x - 1, y + 1);
+            let se = luma_at(x + 1, y + 1);
+
+            // Suavizado sub-pixel según el contraste con el promedio de vecinos
+            let average = (2.0 * (n + s + e + west) + nw + ne + sw + se) / 12.0;
+            let subpix_a = ((average - m).abs() / range).clamp(0.0, 1.0);
+            let subpix_b = (-2.0 * subpix_a + 3.0) * subpix_a * subpix_a;
+            let subpix = subpix_b * subpix_b * FXAA_SUBPIX;
+
+            // Orientación del borde
+            let edge_horz = (-2.0 * west + nw + sw).abs()
+                + 2.0 * (-2.0 * m + n + s).abs()
+                + (-2.0 * e + ne + se).abs();
+            let edge_vert = (-2.0 * n + nw + ne).abs()
+                + 2.0 * (-2.0 * m + west + e).abs()
+                + (-2.0 * s + sw + se).abs();
+            let horizontal = edge_horz >= edge_vert;
+
+            // Lado del borde con mayor gradiente
+            let (luma1, luma2) = if horizontal { (n, s) } else { (west, e) };
+            let gradient1 = luma1 - m;
+            let gradient2 = luma2 - m;
+            let (step, luma_neighbor) = if gradient1.abs() >= gradient2.abs() {
+                (-1, luma1)
+            } else {
+                (1, luma2)
+            };
+            let gradient_scaled = 0.25 * gradient1.abs().max(gradient2.abs());
+            let local_average = 0.5 * (luma_neighbor + m);
+
+            // Luma en el punto medio entre el pixel y su vecino a distancia `t`
+            // a lo largo del borde
+            let edge_luma = |t: f32| {
+                let offset = t.round() as i32;
+                let (ax, ay, bx, by) = if horizontal {
+                    (x + offset, y, x + offset, y + step)
+                } else {
+                    (x, y + offset, x + step, y + offset)
+                };
+                0.5 * (luma_at(ax, ay) + luma_at(bx, by)) - local_average
+            };
+
+            // Buscar los extremos del borde en ambas direcciones
+            let search = |sign: f32| {
+                let mut distance = 0.0;
+                let mut end_luma = 0.0;
+                for &stride in &FXAA_SEARCH_STEPS {
+                    distance += stride;
+                    end_luma = edge_luma(sign * distance);
+                    if end_luma.abs() >= gradient_scaled {
+                        break;
+                    }
+                }
+                (distance, end_luma)
+            };
+            let (dist_neg, end_neg) = search(-1.0);
+            let (dist_pos, end_pos) = search(1.0);
+
+            let (distance, end_luma) = if dist_neg < dist_pos {
+                (dist_neg, end_neg)
+            } else {
+                (dist_pos, end_pos)
+            };
+            let pixel_offset = 0.5 - distance / (dist_neg + dist_pos);
+
+            // Solo se desplaza si el extremo más cercano tiene la variación correcta
+            let center_smaller = m < local_average;
+            let edge_offset = if (end_luma < 0.0) != center_smaller {
+                pixel_offset
+            } else {
+                0.0
+            };
+
+            let offset = edge_offset.max(subpix);
+            if offset <= 0.0 {
+                continue;
+            }
+
+            let neighbor = if horizontal {
+                src[at(x, y + step)]
+            } else {
+                src[at(x + step, y)]
+            };
+            buffer[at(x, y)] = mix_u32(src[at(x, y)], neighbor, offset);
+        }
+    }
+}
+
+// === Gradación de color ===
+
+/// Ajustes finales sobre la imagen ya mapeada. Cada efecto en su valor
+/// neutro (viñeta 0, saturación 1, contraste 1) no toca el buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PostProcess {
+    /// Oscurecimiento máximo en las esquinas (0 = sin viñeta)
+    pub vignette_strength: f32,
+    /// Distancia al centro (0 centro, 1 esquina) donde empieza la viñeta
+    pub vignette_radius: f32,
+    /// 0 = blanco y negro, 1 = original, >1 = más saturado
+    pub saturation: f32,
+    /// Pendiente de la curva alrededor del gris medio (1 = original)
+    pub contrast: f32,
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        Self {
+            vignette_strength: 0.0,
+            vignette_radius: 0.5,
+            saturation: 1.0,
+            contrast: 1.0,
+        }
+    }
+}
+
+impl PostProcess {
+    pub fn is_neutral(&self) -> bool {
+        self.vignette_strength == 0.0 && self.saturation == 1.0 && self.contrast == 1.0
+    }
+
+    /// Aplica contraste, saturación y viñeta al buffer LDR
+    pub fn apply(&self, buffer: &mut [u32], width: u32, height: u32) {
+        if self.is_neutral() {
+            return;
+        }
+
+        let cx = width as f32 * 0.5;
+        let cy = height as f32 * 0.5;
+        let max_dist = (cx * cx + cy * cy).sqrt().max(1.0);
+        let fade = (1.0 - self.vignette_radius).max(1e-3);
+
+        for y in 0..height {
+            for x in 0..width {
+                let idx = (y * width + x) as usize;
+                let c = u32_to_color(buffer[idx]);
+                let mut rgb = [c.r as f32 / 255.0, c.g as f32 / 255.0, c.b as f32 / 255.0];
+
+                if self.contrast != 1.0 {
+                    for channel in &mut rgb {
+                        *channel = (*channel - 0.5) * self.contrast + 0.5;
+                    }
+                }
+
+                if self.saturation != 1.0 {
+                    let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+                    for channel in &mut rgb {
+                        *channel = luma + (*channel - luma) * self.saturation;
+                    }
+                }
+
+                if self.vignette_strength != 0.0 {
+                    let dx = x as f32 + 0.5 - cx;
+                    let dy = y as f32 + 0.5 - cy;
+                    let dist = (dx * dx + dy * dy).sqrt() / max_dist;
+                    let t = ((dist - self.vignette_radius) / fade).clamp(0.0, 1.0);
+                    let factor = 1.0 - self.vignette_strength * t * t;
+                    for channel in &mut rgb {
+                        *channel *= factor;
+                    }
+                }
+
+                let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+                buffer[idx] =
+                    color_to_u32(Color::new(to_u8(rgb[0]), to_u8(rgb[1]), to_u8(rgb[2]), c.a));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(value: u8) -> u32 {
+        color_to_u32(Color::new(value, value, value, 255))
+    }
+
+    /// Imagen de prueba con un borde diagonal en escalera: blanco sobre la
+    /// diagonal, negro debajo
+    fn staircase(size: u32) -> Vec<u32> {
+        (0..size * size)
+            .map(|i| {
+                if i % size > i / size {
+                    gray(255)
+                } else {
+                    gray(0)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fxaa_blends_edges_and_keeps_flat_regions() {
+        const SIZE: u32 = 16;
+        let source = staircase(SIZE);
+        let mut buffer = source.clone();
+        apply_fxaa(&mut buffer, SIZE, SIZE);
+
+        let s = SIZE as i32;
+        let at = |x: i32, y: i32| (y * s + x) as usize;
+        for y in 0..s {
+            for x in 0..s {
+                let index = at(x, y);
+                let neighbors: Vec<u32> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                    .iter()
+                    .map(|(dx, dy)| ((x + dx).clamp(0, s - 1), (y + dy).clamp(0, s - 1)))
+                    .map(|(nx, ny)| source[at(nx, ny)])
+                    .collect();
+                if neighbors.iter().all(|&n| n == source[index]) {
+                    // Zona plana (en cruz): no se toca
+                    assert_eq!(buffer[index], source[index], "({}, {})", x, y);
+                } else {
+                    let [r, g, b] = u32_to_rgb(buffer[index]);
+                    assert!(r > 0 && r < 255, "({}, {}) sin mezclar: {}", x, y, r);
+                    assert!(r == g && g == b);
+                }
+            }
+        }
+        assert_ne!(buffer, source);
+    }
+
+    #[test]
+    fn fxaa_ignores_flat_image_and_tiny_buffers() {
+        let mut flat = vec![gray(90); 8 * 8];
+        apply_fxaa(&mut flat, 8, 8);
+        assert!(flat.iter().all(|&c| c == gray(90)));
+
+        let checker = vec![gray(0), gray(255), gray(255), gray(0)];
+        let mut tiny = checker.clone();
+        apply_fxaa(&mut tiny, 2, 2);
+        assert_eq!(tiny, checker);
+    }
+
+    /// Buffer con colores variados (incluido el alfa)
+    fn varied(width: u32, height: u32) -> Vec<u32> {
+        (0..width * height)
+            .map(|i| i.wrapping_mul(2654435761))
+            .collect()
+    }
+
+    #[test]
+    fn neutral_grading_leaves_buffer_identical() {
+        let source = varied(32, 18);
+        for radius in [0.0, 0.5, 1.0] {
+            let grading = PostProcess {
+                vignette_radius: radius,
+                ..PostProcess::default()
+            };
+            assert!(grading.is_neutral());
+            let mut buffer = source.clone();
+            grading.apply(&mut buffer, 32, 18);
+            assert_eq!(buffer, source);
+        }
+    }
+
+    #[test]
+    fn vignette_outside_its_radius_is_a_no_op() {
+        // Con el radio en la esquina el factor es 1 en toda la imagen: el
+        // paso por f32 y de vuelta a u8 tiene que ser exacto
+        let source = varied(32, 18);
+        let grading = PostProcess {
+            vignette_strength: 0.8,
+            vignette_radius: 1.0,
+            ..PostProcess::default()
+        };
+        let mut buffer = source.clone();
+        grading.apply(&mut buffer, 32, 18);
+        assert_eq!(buffer, source);
+    }
+}
@@ -0,0 +1,145 @@
+// post_menu.rs - Menú en pantalla para la gradación de color
+use raylib::prelude::*;
+
+use crate::post::PostProcess;
+
+/// Parámetros de `PostProcess` editables desde el menú
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Parameter {
+    VignetteStrength,
+    VignetteRadius,
+    Saturation,
+    Contrast,
+}
+
+const PARAMETERS: [Parameter; 4] = [
+    Parameter::VignetteStrength,
+    Parameter::VignetteRadius,
+    Parameter::Saturation,
+    Parameter::Contrast,
+];
+
+impl Parameter {
+    fn label(self) -> &'static str {
+        match self {
+            Parameter::VignetteStrength => "Viñeta",
+            Parameter::VignetteRadius => "Radio viñeta",
+            Parameter::Saturation => "Saturación",
+            Parameter::Contrast => "Contraste",
+        }
+    }
+
+    fn value(self, post: &PostProcess) -> f32 {
+        match self {
+            Parameter::VignetteStrength => post.vignette_strength,
+            Parameter::VignetteRadius => post.vignette_radius,
+            Parameter::Saturation => post.saturation,
+            Parameter::Contrast => post.contrast,
+        }
+    }
+
+    /// Suma `steps` incrementos de 0.05 dentro del rango válido. Se redondea
+    /// a centésimas para poder volver exactamente al valor neutro.
+    fn adjust(self, post: &mut PostProcess, steps: f32) {
+        let step = |value: f32, min: f32, max: f32| {
+            ((value + 0.05 * steps).clamp(min, max) * 100.0).round() / 100.0
+        };
+        match self {
+            Parameter::VignetteStrength => {
+                post.vignette_strength = step(post.vignette_strength, 0.0, 1.0)
+            }
+            Parameter::VignetteRadius => {
+                post.vignette_radius = step(post.vignette_radius, 0.0, 1.0)
+            }
+            Parameter::Saturation => post.saturation = step(post.saturation, 0.0, 2.0),
+            Parameter::Contrast => post.contrast = step(post.contrast, 0.5, 2.0),
+        }
+    }
+}
+
+/// Estado del menú de post-procesado
+#[derive(Default)]
+pub struct PostProcessMenu {
+    open: bool,
+    selected: usize,
+}
+
+impl PostProcessMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Flechas arriba/abajo eligen parámetro, izquierda/derecha lo ajustan
+    pub fn handle_input(&mut self, rl: &RaylibHandle, post: &mut PostProcess) {
+        if !self.open {
+            return;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1) % PARAMETERS.len();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = (self.selected + PARAMETERS.len() - 1) % PARAMETERS.len();
+        }
+
+        let mut steps = 0.0;
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            steps += 1.0;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            steps -= 1.0;
+        }
+        if steps != 0.0 {
+            PARAMETERS[self.selected].adjust(post, steps);
+        }
+    }
+
+    /// Dibuja el panel con los valores actuales
+    pub fn draw(&self, d: &mut RaylibDrawHandle, post: &PostProcess, x: i32, y: i32) {
+        if !self.open {
+            return;
+        }
+
+        let line_height = 18;
+        let height = line_height * (PARAMETERS.len() as i32 + 2) + 10;
+        d.draw_rectangle(x, y, 260, height, Color::new(0, 0, 0, 170));
+        d.draw_text("Post-procesado", x + 8, y + 6, 16, Color::YELLOW);
+
+        for (i, parameter) in PARAMETERS.iter().enumerate() {
+            let color = if i == self.selected {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            let marker = if i == self.selected { ">" } else { " " };
+            d.draw_text(
+                &format!(
+                    "{} {}: {:.2}",
+                    marker,
+                    parameter.label(),
+                    parameter.value(post)
+                ),
+                x + 8,
+                y + 6 + line_height * (i as i32 + 1),
+                16,
+                color,
+            );
+        }
+
+        d.draw_text(
+            "Flechas: elegir/ajustar | V: cerrar",
+            x + 8,
+            y + 6 + line_height * (PARAMETERS.len() as i32 + 1),
+            14,
+            Color::LIGHTGRAY,
+        );
+    }
+}
@@ -0,0 +1,263 @@
+// primitive.rs - Primitivas fuera de la grilla de bloques: planos y esferas
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::light::Light;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, RayIntersect};
+
+/// Distancia mínima de un impacto, para que un rayo que sale de la
+/// superficie no vuelva a chocar con ella
+const MIN_HIT_DISTANCE: f32 = 1e-4;
+
+/// Plano infinito. La normal indica la cara visible (la de atrás también se
+/// golpea, con la normal tal cual, como la cara interior de un bloque).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub point: Vector3,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub normal: Vector3,
+    pub material: Material,
+    /// Lado del cuadro que ocupa una repetición de la textura
+    pub uv_scale: f32,
+}
+
+impl Plane {
+    pub fn new(point: Vector3, normal: Vector3, material: Material) -> Self {
+        Self {
+            point,
+            normal: normal.normalized(),
+            material,
+            uv_scale: 1.0,
+        }
+    }
+
+    /// Base del plano para las UV: dos ejes perpendiculares a la normal
+    fn tangents(&self) -> (Vector3, Vector3) {
+        let helper = if self.normal.y.abs() < 0.9 {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            Vector3::new(1.0, 0.0, 0.0)
+        };
+        let tangent = helper.cross(self.normal).normalized();
+        (tangent, self.normal.cross(tangent))
+    }
+}
+
+impl<'a> RayIntersect<'a> for Plane {
+    fn ray_intersect(&'a self, origin: &Vector3, dir: &Vector3) -> Intersect<'a> {
+        let denom = self.normal.dot(*dir);
+        if denom.abs() < 1e-6 {
+            return Intersect::empty();
+        }
+        let distance = (self.point - *origin).dot(self.normal) / denom;
+        if distance < MIN_HIT_DISTANCE {
+            return Intersect::empty();
+        }
+
+        let point = *origin + *dir * distance;
+        let (tangent, bitangent) = self.tangents();
+        let local = (point - self.point) / self.uv_scale;
+        let u = local.dot(tangent).rem_euclid(1.0);
+        let v = local.dot(bitangent).rem_euclid(1.0);
+        Intersect::new(&self.material, distance, self.normal, point, u, v)
+    }
+}
+
+/// Esfera con UV de longitud (u) y latitud (v, 0 en el polo norte)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub center: Vector3,
+    pub radius: f32,
+    pub material: Material,
+    /// Luz puntual en el centro, como la de un bloque emisivo
+    pub emission: Option<Light>,
+}
+
+impl Sphere {
+    pub fn new(center: Vector3, radius: f32, material: Material) -> Self {
+        Self {
+            center,
+            radius,
+            material,
+            emission: None,
+        }
+    }
+
+    pub fn with_emission(mut self, color: Vector3, intensity: f32) -> Self {
+        self.emission = Some(Light::new(self.center, color, intensity));
+        self
+    }
+}
+
+impl<'a> RayIntersect<'a> for Sphere {
+    fn ray_intersect(&'a self, origin: &Vector3, dir: &Vector3) -> Intersect<'a> {
+        // |o + t d - c|² = r²  =>  a t² + 2 b t + c = 0
+        let oc = *origin - self.center;
+        let a = dir.dot(*dir);
+        let b = oc.dot(*dir);
+        let c = oc.dot(oc) - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 || a <= 0.0 {
+            return Intersect::empty();
+        }
+
+        // La más cercana por delante; desde adentro, la salida
+        let root = discriminant.sqrt();
+        let near = (-b - root) / a;
+        let far = (-b + root) / a;
+        let distance = if near >= MIN_HIT_DISTANCE {
+            near
+        } else if far >= MIN_HIT_DISTANCE {
+            far
+        } else {
+            return Intersect::empty();
+        };
+
+        let point = *origin + *dir * distance;
+        let normal = (point - self.center) / self.radius;
+        let u = 0.5 + normal.z.atan2(normal.x) / std::f32::consts::TAU;
+        let v = normal.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+        Intersect::new(&self.material, distance, normal, point, u, v)
+    }
+}
+
+/// Cualquier objeto de la escena que no está en la lista de bloques. Un
+/// `Block` acá es una caja suelta: se traza igual pero no se edita, no se
+/// anima ni se une con sus vecinos.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Primitive {
+    Block(Block),
+    Plane(Plane),
+    Sphere(Sphere),
+}
+
+impl Primitive {
+    /// Material principal (el de las caras sin override en las cajas)
+    pub fn material(&self) -> &Material {
+        match self {
+            Primitive::Block(block) => &block.material,
+            Primitive::Plane(plane) => &plane.material,
+            Primitive::Sphere(sphere) => &sphere.material,
+        }
+    }
+
+    /// Luz que aporta a la escena, si es emisiva
+    pub fn emission(&self) -> Option<&Light> {
+        match self {
+            Primitive::Block(block) => block.emission.as_ref(),
+            Primitive::Plane(_) => None,
+            Primitive::Sphere(sphere) => sphere.emission.as_ref(),
+        }
+    }
+
+    /// Caja (mínimo, máximo) que la contiene; None para el plano infinito
+    pub fn bounds(&self) -> Option<(Vector3, Vector3)> {
+        match self {
+            Primitive::Block(block) => Some(block.bounds()),
+            Primitive::Plane(_) => None,
+            Primitive::Sphere(sphere) => {
+                let radius = Vector3::one() * sphere.radius.abs();
+                Some((sphere.center - radius, sphere.center + radius))
+            }
+        }
+    }
+}
+
+impl<'a> RayIntersect<'a> for Primitive {
+    fn ray_intersect(&'a self, origin: &Vector3, dir: &Vector3) -> Intersect<'a> {
+        match self {
+            Primitive::Block(block) => block.ray_intersect(origin, dir),
+            Primitive::Plane(plane) => plane.ray_intersect(origin, dir),
+            Primitive::Sphere(sphere) => sphere.ray_intersect(origin, dir),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_types::BlockType;
+
+    fn sphere() -> Sphere {
+        Sphere::new(
+            Vector3::new(1.0, 2.0, 3.0),
+            2.0,
+            BlockType::Glass.material(),
+        )
+    }
+
+    #[test]
+    fn ray_from_outside_hits_the_near_side() {
+        let sphere = sphere();
+        let hit = sphere.ray_intersect(&Vector3::new(1.0, 2.0, -7.0), &Vector3::new(0.0, 0.0, 1.0));
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - 8.0).abs() < 1e-4);
+        assert!((hit.normal - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn ray_from_inside_hits_the_exit() {
+        let sphere = sphere();
+        let dir = Vector3::new(1.0, 1.0, 0.0).normalized();
+        let hit = sphere.ray_intersect(&sphere.center, &dir);
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - sphere.radius).abs() < 1e-4);
+        // La normal sigue apuntando hacia afuera (a favor del rayo)
+        assert!((hit.normal - dir).length() < 1e-4);
+    }
+
+    #[test]
+    fn tangent_ray_touches_once_and_a_wider_one_misses() {
+        let sphere = sphere();
+        let dir = Vector3::new(1.0, 0.0, 0.0);
+        let top = sphere.center + Vector3::new(0.0, sphere.radius, 0.0);
+        let hit = sphere.ray_intersect(&(top - dir * 5.0), &dir);
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - 5.0).abs() < 1e-3);
+        assert!((hit.point - top).length() < 1e-3);
+        assert!((hit.normal - Vector3::new(0.0, 1.0, 0.0)).length() < 1e-3);
+
+        let above = top + Vector3::new(0.0, 1e-3, 0.0);
+        assert!(
+            !sphere
+                .ray_intersect(&(above - dir * 5.0), &dir)
+                .is_intersecting
+        );
+    }
+
+    #[test]
+    fn uv_is_continuous_at_the_poles() {
+        let sphere = sphere();
+        // Rayos hacia el centro desde cerca de cada polo, a distintas
+        // longitudes: la latitud tiende a 0 (norte) o 1 (sur) sin saltos
+        for (pole, expected_v) in [(1.0, 0.0), (-1.0, 1.0)] {
+            let mut previous_v: Option<f32> = None;
+            for step in 0..16 {
+                let angle = step as f32 * std::f32::consts::TAU / 16.0;
+                let offset = Vector3::new(angle.cos() * 1e-3, pole, angle.sin() * 1e-3);
+                let origin = sphere.center + offset.normalized() * 5.0;
+                let hit = sphere.ray_intersect(&origin, &(sphere.center - origin).normalized());
+                assert!(hit.is_intersecting);
+                assert!((0.0..=1.0).contains(&hit.u), "u {}", hit.u);
+                assert!((hit.v - expected_v).abs() < 1e-3, "v {} en el polo", hit.v);
+                if let Some(previous) = previous_v {
+                    assert!((hit.v - previous).abs() < 1e-3);
+                }
+                previous_v = Some(hit.v);
+            }
+        }
+        // En el ecuador la latitud es 0.5 y la longitud da la vuelta entera
+        let hit = sphere.ray_intersect(
+            &(sphere.center + Vector3::new(-5.0, 0.0, 0.0)),
+            &Vector3::new(1.0, 0.0, 0.0),
+        );
+        assert!((hit.v - 0.5).abs() < 1e-4);
+        assert!(hit.u < 1e-4 || hit.u > 1.0 - 1e-4, "u {}", hit.u);
+    }
+}
@@ -0,0 +1,145 @@
+// quality.rs - Presets de calidad (F8): Rápida, Equilibrada y Bonita
+use crate::render_settings::RenderSettings;
+use crate::settings::Settings;
+
+/// Combinación completa de opciones de calidad. Elegir un preset reemplaza
+/// todas juntas; cambiar después una opción suelta deja la configuración
+/// como "Personalizada" (no coincide con ningún preset).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityPreset {
+    Fast,
+    Balanced,
+    Pretty,
+}
+
+impl QualityPreset {
+    pub const ALL: [QualityPreset; 3] = [
+        QualityPreset::Fast,
+        QualityPreset::Balanced,
+        QualityPreset::Pretty,
+    ];
+
+    pub fn next(self) -> Self {
+        match self {
+            QualityPreset::Fast => QualityPreset::Balanced,
+            QualityPreset::Balanced => QualityPreset::Pretty,
+            QualityPreset::Pretty => QualityPreset::Fast,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QualityPreset::Fast => "Rápida",
+            QualityPreset::Balanced => "Equilibrada",
+            QualityPreset::Pretty => "Bonita",
+        }
+    }
+
+    /// Nombre para la línea de comandos y la consola
+    pub fn name(self) -> &'static str {
+        match self {
+            QualityPreset::Fast => "fast",
+            QualityPreset::Balanced => "balanced",
+            QualityPreset::Pretty => "pretty",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Opciones del raytracer del preset. El cielo y los parámetros finos
+    /// del trazado no son de calidad y se conservan de `current`.
+    pub fn render_settings(self, current: &RenderSettings) -> RenderSettings {
+        let base = RenderSettings {
+            sky: current.sky,
+            sky_model: current.sky_model,
+            turbidity: current.turbidity,
+            star_density: current.star_density,
+            star_brightness: current.star_brightness,
+            time: current.time,
+            ao_jitter: current.ao_jitter,
+            light_radius: current.light_radius,
+            portal_strength: current.portal_strength,
+            russian_roulette: current.russian_roulette,
+            min_reflectivity: current.min_reflectivity,
+            max_distance: current.max_distance,
+            ray_epsilon: current.ray_epsilon,
+            min_specular: current.min_specular,
+            ambient_strength: current.ambient_strength,
+            light_attenuation: current.light_attenuation,
+            tile_size: current.tile_size,
+            samples_per_pixel: current.samples_per_pixel,
+            adaptive: current.adaptive,
+            motion_blur: current.motion_blur,
+            god_rays: current.god_rays,
+            god_ray_steps: current.god_ray_steps,
+            god_ray_density: current.god_ray_density,
+            god_ray_distance: current.god_ray_distance,
+            fog: current.fog,
+            toon: current.toon,
+            cost_view_max: current.cost_view_max,
+            ..RenderSettings::default()
+        };
+        match self {
+            QualityPreset::Fast => RenderSettings {
+                shadows: false,
+                ambient_occlusion: false,
+                max_depth: 1,
+                bilinear_filtering: false,
+                half_resolution: true,
+                ..base
+            },
+            QualityPreset::Balanced => RenderSettings {
+                shadows: true,
+                ambient_occlusion: false,
+                max_depth: 2,
+                ..base
+            },
+            QualityPreset::Pretty => RenderSettings {
+                shadows: true,
+                soft_shadows: true,
+                accumulate: true,
+                ambient_occlusion: true,
+                light_portals: true,
+                max_depth: 4,
+                ..base
+            },
+        }
+    }
+
+    pub fn bloom(self) -> bool {
+        self == QualityPreset::Pretty
+    }
+
+    /// Frames que promedia el render sin ventana, cada uno con los rayos
+    /// corridos dentro del pixel (aparte de `RenderSettings::samples_per_pixel`,
+    /// que se elige a mano y vale también en el modo interactivo)
+    pub fn samples_per_pixel(self) -> u32 {
+        match self {
+            QualityPreset::Pretty => 4,
+            _ => 1,
+        }
+    }
+
+    /// Aplica todas las opciones del preset de una vez
+    pub fn apply(self, settings: &mut Settings) {
+        settings.render = self.render_settings(&settings.render);
+        settings.bloom.enabled = self.bloom();
+    }
+
+    /// Preset que coincide con las opciones actuales, si hay alguno
+    pub fn detect(settings: &Settings) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| {
+            settings.render == preset.render_settings(&settings.render)
+                && settings.bloom.enabled == preset.bloom()
+        })
+    }
+}
+
+/// Nombre de la calidad actual para el HUD
+pub fn quality_label(settings: &Settings) -> &'static str {
+    QualityPreset::detect(settings).map_or("Personalizada", QualityPreset::label)
+}
@@ -0,0 +1,268 @@
+// radiance.rs - Escritura de imágenes HDR en formato Radiance (.hdr, RGBE)
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::framebuffer::ExportError;
+
+/// Largo máximo de una corrida (y de un tramo literal) del RLE de Radiance
+const MAX_RUN: usize = 127;
+/// Iguales seguidos a partir de los cuales conviene cortar el literal
+const MIN_RUN: usize = 4;
+
+/// Escribe `pixels` (radiancia lineal, sin mapeo tonal) como archivo
+/// Radiance: cabecera de texto y una línea RGBE por fila, de arriba hacia
+/// abajo. Las filas de 8 a 32767 pixeles van con el RLE por canal; las
+/// demás, planas.
+pub fn write_radiance_hdr<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    pixels: &[Vector3],
+) -> Result<(), ExportError> {
+    let write = || -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path.as_ref())?);
+        out.write_all(&radiance_header(width, height))?;
+        for row in pixels.chunks(width.max(1) as usize).take(height as usize) {
+            let rgbe: Vec<[u8; 4]> = row.iter().map(|&color| to_rgbe(color)).collect();
+            out.write_all(&encode_scanline(&rgbe))?;
+        }
+        out.flush()
+    };
+    write().map_err(|source| ExportError::io(path, source))
+}
+
+/// Cabecera del archivo: firma, formato y resolución (`-Y` = filas de
+/// arriba hacia abajo)
+pub fn radiance_header(width: u32, height: u32) -> Vec<u8> {
+    format!(
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        height, width
+    )
+    .into_bytes()
+}
+
+/// Color a RGBE: mantisas de 8 bits por canal con el exponente común del
+/// mayor. Lo que no llega a ~1e-32 (o es negativo) queda en negro.
+pub fn to_rgbe(color: Vector3) -> [u8; 4] {
+    let max = color.x.max(color.y).max(color.z);
+    if max.is_nan() || max < 1e-32 {
+        return [0; 4];
+    }
+    // max = mantisa * 2^exponent, con la mantisa en [0.5, 1)
+    let mut exponent = max.log2().floor() as i32 + 1;
+    if max / (exponent as f32).exp2() >= 1.0 {
+        exponent += 1;
+    }
+    let scale = 256.0 / (exponent as f32).exp2();
+    let channel = |value: f32| (value.max(0.0) * scale).min(255.0) as u8;
+    [
+        channel(color.x),
+        channel(color.y),
+        channel(color.z),
+        (exponent + 128).clamp(0, 255) as u8,
+    ]
+}
+
+/// Una fila en el formato de Radiance. Con RLE: marca `2 2` y el ancho, y
+/// después cada canal (R, G, B, E) por separado en tramos que son una
+/// corrida (`128 + n`, valor) o un literal (`n`, n valores).
+pub fn encode_scanline(rgbe: &[[u8; 4]]) -> Vec<u8> {
+    let width = rgbe.len();
+    if !(8..=0x7fff).contains(&width) {
+        return rgbe.iter().flatten().copied().collect();
+    }
+
+    let mut out = Vec::with_capacity(width * 4 + 4);
+    out.extend_from_slice(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+    for channel in 0..4 {
+        let values: Vec<u8> = rgbe.iter().map(|pixel| pixel[channel]).collect();
+        encode_channel(&values, &mut out);
+    }
+    out
+}
+
+/// RLE de los valores de un canal
+fn encode_channel(values: &[u8], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < values.len() {
+        // Dónde empieza la próxima corrida que valga la pena
+        let mut run_start = i;
+        let mut run_len = 0;
+        while run_start < values.len() {
+            run_len = values[run_start..]
+                .iter()
+                .take(MAX_RUN)
+                .take_while(|&&value| value == values[run_start])
+                .count();
+            if run_len >= MIN_RUN {
+                break;
+            }
+            run_start += run_len;
+        }
+        if run_len < MIN_RUN {
+            run_start = values.len();
+        }
+
+        // Literales hasta la corrida, de a tramos de MAX_RUN
+        for chunk in values[i..run_start].chunks(MAX_RUN) {
+            out.push(chunk.len() as u8);
+            out.extend_from_slice(chunk);
+        }
+        if run_start < values.len() {
+            out.extend_from_slice(&[128 + run_len as u8, values[run_start]]);
+        }
+        i = (run_start + run_len).min(values.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// RGBE a color, como lo lee cualquier visor de Radiance
+    fn from_rgbe([r, g, b, e]: [u8; 4]) -> Vector3 {
+        if e == 0 {
+            return Vector3::zero();
+        }
+        let scale = (e as i32 - 128 - 8) as f32;
+        Vector3::new(r as f32, g as f32, b as f32) * scale.exp2()
+    }
+
+    /// Lee una fila de `width` pixeles desde `bytes`; devuelve la fila y
+    /// los bytes que consumió
+    fn decode_scanline(bytes: &[u8], width: usize) -> (Vec<[u8; 4]>, usize) {
+        if !(8..=0x7fff).contains(&width) {
+            let row = bytes[..width * 4]
+                .chunks_exact(4)
+                .map(|p| [p[0], p[1], p[2], p[3]])
+                .collect();
+            return (row, width * 4);
+        }
+        assert_eq!(&bytes[..2], &[2, 2], "falta la marca de RLE");
+        assert_eq!(((bytes[2] as usize) << 8) | bytes[3] as usize, width);
+        let mut row = vec![[0u8; 4]; width];
+        let mut i = 4;
+        for channel in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = bytes[i] as usize;
+                if count > 128 {
+                    for pixel in &mut row[x..x + count - 128] {
+                        pixel[channel] = bytes[i + 1];
+                    }
+                    x += count - 128;
+                    i += 2;
+                } else {
+                    assert!(count > 0, "tramo vacío");
+                    for (pixel, &value) in row[x..x + count].iter_mut().zip(&bytes[i + 1..]) {
+                        pixel[channel] = value;
+                    }
+                    x += count;
+                    i += 1 + count;
+                }
+            }
+            assert_eq!(x, width, "el canal {} se pasa del ancho", channel);
+        }
+        (row, i)
+    }
+
+    fn assert_round_trip(color: Vector3) {
+        let back = from_rgbe(to_rgbe(color));
+        // Ocho bits de mantisa sobre el canal mayor
+        let tolerance = color.x.max(color.y).max(color.z) / 128.0;
+        assert!(
+            (back - color).length() <= tolerance * 2.0,
+            "{:?} volvió como {:?}",
+            color,
+            back
+        );
+    }
+
+    #[test]
+    fn header_bytes_are_the_radiance_ones() {
+        assert_eq!(
+            radiance_header(640, 480),
+            b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 480 +X 640\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn rgbe_round_trips_within_the_mantissa() {
+        assert_eq!(to_rgbe(Vector3::zero()), [0; 4]);
+        assert_eq!(to_rgbe(Vector3::new(-1.0, 0.0, 0.0)), [0; 4]);
+        assert_eq!(to_rgbe(Vector3::one()), [128, 128, 128, 129]);
+        for color in [
+            Vector3::new(0.5, 0.25, 0.125),
+            Vector3::new(1e-6, 2e-6, 0.0),
+            Vector3::new(3000.0, 10.0, 0.001),
+            Vector3::new(0.999, 0.999, 0.999),
+        ] {
+            assert_round_trip(color);
+        }
+        let mut rng = Rng::new(7, 3);
+        for _ in 0..1000 {
+            let scale = (rng.next_f32() * 20.0 - 10.0).exp2();
+            assert_round_trip(Vector3::new(rng.next_f32(), rng.next_f32(), rng.next_f32()) * scale);
+        }
+    }
+
+    #[test]
+    fn scanlines_decode_back_to_the_same_rgbe() {
+        let mut rng = Rng::new(11, 5);
+        for width in [1, 7, 8, 127, 128, 300, 1000] {
+            // Tramos lisos (corridas) mezclados con ruido (literales)
+            let row: Vec<[u8; 4]> = (0..width)
+                .map(|x| {
+                    if (x / 50) % 2 == 0 {
+                        [10, 20, 30, 128]
+                    } else {
+                        let mut value = || (rng.next_f32() * 255.0) as u8;
+                        [value(), value(), value(), 130]
+                    }
+                })
+                .collect();
+            let encoded = encode_scanline(&row);
+            let (decoded, used) = decode_scanline(&encoded, width);
+            assert_eq!(used, encoded.len(), "ancho {}", width);
+            assert_eq!(decoded, row, "ancho {}", width);
+        }
+        // Una fila lisa se comprime
+        let flat = vec![[1, 2, 3, 128]; 300];
+        assert!(encode_scanline(&flat).len() < 40);
+    }
+
+    #[test]
+    fn written_file_reads_back_within_tolerance() {
+        let (width, height) = (37, 5);
+        let pixels: Vec<Vector3> = (0..width * height)
+            .map(|i| Vector3::new(i as f32 * 0.1, (i % 7) as f32, 1.0 / (1.0 + i as f32)))
+            .collect();
+        let path = std::env::temp_dir().join(format!("raytracer_hdr_{}.hdr", std::process::id()));
+        write_radiance_hdr(&path, width, height, &pixels).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let header = radiance_header(width, height);
+        assert!(bytes.starts_with(&header));
+        let mut rest = &bytes[header.len()..];
+        for row in pixels.chunks(width as usize) {
+            let (decoded, used) = decode_scanline(rest, width as usize);
+            for (&color, &rgbe) in row.iter().zip(&decoded) {
+                let back = from_rgbe(rgbe);
+                let tolerance = color.x.max(color.y).max(color.z) / 64.0;
+                assert!(
+                    (back - color).length() <= tolerance,
+                    "{:?} -> {:?}",
+                    color,
+                    back
+                );
+            }
+            rest = &rest[used..];
+        }
+        assert!(rest.is_empty());
+    }
+}
@@ -24,6 +24,14 @@ pub struct Intersect<'a> {
     /// Coordenadas UV (0..1) si aplica
     pub u: f32,
     pub v: f32,
+
+    /// La cara golpeada es interior (pegada a otro bloque del mismo medio
+    /// transparente) y el rayo debe atravesarla sin refractar.
+    pub interior_face: bool,
+
+    /// Índice del bloque golpeado en `Scene::blocks` (ver `Scene::block`).
+    /// `ray_intersect` lo deja en None; lo completa `find_closest_intersection`.
+    pub block_id: Option<u32>,
 }
 
 impl<'a> Intersect<'a> {
@@ -37,6 +45,8 @@ impl<'a> Intersect<'a> {
             point,
             u,
             v,
+            interior_face: false,
+            block_id: None,
         }
     }
 
@@ -50,6 +60,8 @@ impl<'a> Intersect<'a> {
             point: Vector3::zero(),
             u: 0.0,
             v: 0.0,
+            interior_face: false,
+            block_id: None,
         }
     }
 }
@@ -0,0 +1,155 @@
+// recorder.rs - Grabación de secuencias de frames a disco (para armar videos)
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+
+use log::error;
+
+use crate::framebuffer::{ExportError, Framebuffer, write_ppm};
+
+/// Frames que pueden esperar en cola antes de empezar a descartar
+const QUEUE_CAPACITY: usize = 8;
+
+/// Copia de un frame presentado, lista para escribirse en el hilo de disco
+struct Frame {
+    index: u32,
+    width: u32,
+    height: u32,
+    pixels: Vec<u32>,
+}
+
+/// Resumen de una grabación terminada
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecordingReport {
+    pub written: u32,
+    pub dropped: u32,
+    pub failed: u32,
+}
+
+/// Graba cada N frames presentados como PPM numerados. La escritura ocurre en
+/// un hilo aparte alimentado por un canal acotado; si el disco no da abasto,
+/// los frames se descartan en lugar de frenar el render.
+pub struct FrameRecorder {
+    output_dir: PathBuf,
+    every_nth: u32,
+    sender: Option<SyncSender<Frame>>,
+    writer: Option<JoinHandle<(u32, u32)>>,
+    presented: u32,
+    queued: u32,
+    dropped: u32,
+}
+
+impl FrameRecorder {
+    pub fn new(output_dir: impl Into<PathBuf>, every_nth: u32) -> Self {
+        Self {
+            output_dir: output_dir.into(),
+            every_nth: every_nth.max(1),
+            sender: None,
+            writer: None,
+            presented: 0,
+            queued: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Frames enviados al hilo de escritura en la grabación actual
+    pub fn frame_count(&self) -> u32 {
+        self.queued
+    }
+
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Empieza una grabación nueva en el directorio de salida
+    pub fn start(&mut self) -> Result<(), ExportError> {
+        if self.is_recording() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.output_dir)
+            .map_err(|err| ExportError::io(&self.output_dir, err))?;
+
+        let (sender, receiver) = mpsc::sync_channel::<Frame>(QUEUE_CAPACITY);
+        let output_dir = self.output_dir.clone();
+        let writer = thread::spawn(move || {
+            let mut written = 0;
+            let mut failed = 0;
+            for frame in receiver {
+                let path = output_dir.join(format!("frame_{:05}.ppm", frame.index));
+                match write_ppm(&path, frame.width, frame.height, &frame.pixels) {
+                    Ok(()) => written += 1,
+                    Err(e) => {
+                        error!("No se pudo escribir {}", e);
+                        failed += 1;
+                    }
+                }
+            }
+            (written, failed)
+        });
+
+        self.sender = Some(sender);
+        self.writer = Some(writer);
+        self.presented = 0;
+        self.queued = 0;
+        self.dropped = 0;
+        Ok(())
+    }
+
+    /// Termina la grabación esperando a que se escriban los frames en cola
+    pub fn stop(&mut self) -> Option<RecordingReport> {
+        // Cerrar el canal hace que el hilo termine al vaciar la cola
+        self.sender.take()?;
+        let (written, failed) = self
+            .writer
+            .take()
+            .and_then(|writer| writer.join().ok())
+            .unwrap_or((0, self.queued));
+
+        Some(RecordingReport {
+            written,
+            dropped: self.dropped,
+            failed,
+        })
+    }
+
+    /// Llamar una vez por frame presentado; encola una copia si toca grabarlo
+    pub fn capture(&mut self, framebuffer: &Framebuffer) {
+        self.capture_pixels(
+            framebuffer.width,
+            framebuffer.height,
+            framebuffer.as_slice(),
+        );
+    }
+
+    /// Como `capture` con pixeles RGBA sueltos (ej: la ventana con el HUD)
+    pub fn capture_pixels(&mut self, width: u32, height: u32, pixels: &[u32]) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        self.presented += 1;
+        if !(self.presented - 1).is_multiple_of(self.every_nth) {
+            return;
+        }
+
+        let frame = Frame {
+            index: self.queued,
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        };
+        match sender.try_send(frame) {
+            Ok(()) => self.queued += 1,
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            Err(TrySendError::Disconnected(_)) => {
+                error!("El hilo de grabación terminó inesperadamente");
+                self.stop();
+            }
+        }
+    }
+}
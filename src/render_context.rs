@@ -0,0 +1,97 @@
+// render_context.rs - Lo que el trazado de un frame consulta en cada rayo
+use crate::animation::AnimatedMaterials;
+use crate::beacon::Beam;
+use crate::light::Light;
+use crate::portal::LightPortal;
+use crate::render_settings::RenderSettings;
+use crate::scene::Scene;
+use crate::textures::TextureManager;
+
+/// Lo que hay para trazar: la escena, sus luces y texturas, y lo que se
+/// deriva de ella una vez por frame (haces de los faros, portales de luz,
+/// materiales animados)
+#[derive(Clone, Copy)]
+pub struct SceneRefs<'a> {
+    pub scene: &'a Scene,
+    pub lights: &'a [Light],
+    pub beams: &'a [Beam],
+    pub portals: &'a [LightPortal],
+    pub textures: &'a TextureManager,
+    pub animated: &'a AnimatedMaterials,
+}
+
+/// Contexto de un frame: se pasa por referencia a cada rayo y a cada
+/// función de sombreado en vez de una lista creciente de argumentos. El
+/// generador de números de cada pixel va aparte porque es mutable y propio
+/// del hilo, igual que los contadores de rayos (ver `snell::take_ray_counts`).
+#[derive(Clone, Copy)]
+pub struct RenderContext<'a> {
+    pub refs: SceneRefs<'a>,
+    pub settings: &'a RenderSettings,
+    /// Tiempo de la simulación en segundos (`settings.time`): olas,
+    /// cáusticas y demás materiales animados
+    pub time: f32,
+    /// Frames trazados por el renderer, contando este
+    pub frame: u64,
+}
+
+impl<'a> RenderContext<'a> {
+    pub fn new(refs: SceneRefs<'a>, settings: &'a RenderSettings, frame: u64) -> Self {
+        Self {
+            refs,
+            settings,
+            time: settings.time,
+            frame,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raylib::prelude::*;
+
+    use crate::block_types::BlockType;
+    use crate::rng::Rng;
+    use crate::snell::trace_ray_multi_light;
+
+    /// Color de un rayo que baja en diagonal sobre un bloque de agua, con
+    /// el contexto armado para `time` y `frame`
+    fn water_color(time: f32, frame: u64) -> Vector3 {
+        let scene = Scene::new(vec![BlockType::Water.to_block(Vector3::zero(), 1.0)]);
+        let textures = TextureManager::new();
+        let animated = AnimatedMaterials::default();
+        let refs = SceneRefs {
+            scene: &scene,
+            lights: &[],
+            beams: &[],
+            portals: &[],
+            textures: &textures,
+            animated: &animated,
+        };
+        let settings = RenderSettings {
+            time,
+            ..RenderSettings::default()
+        };
+        let context = RenderContext::new(refs, &settings, frame);
+        assert_eq!(context.time, time);
+        assert_eq!(context.frame, frame);
+        let origin = Vector3::new(-0.3, 3.0, -2.0);
+        let dir = (Vector3::new(0.1, 0.5, 0.2) - origin).normalized();
+        trace_ray_multi_light(origin, dir, 0, 1.0, &context, &mut Rng::new(1, 1))
+    }
+
+    #[test]
+    fn shading_reads_the_time_from_the_context() {
+        // Mismo tiempo, mismo color; las olas se mueven con el tiempo
+        assert_eq!(water_color(1.5, 1), water_color(1.5, 1));
+        assert_ne!(water_color(1.5, 1), water_color(2.75, 1));
+    }
+
+    #[test]
+    fn frame_index_does_not_change_a_deterministic_ray() {
+        // El número de frame es para quien lo pida: el mismo rayo con el
+        // mismo generador da lo mismo en cualquier frame
+        assert_eq!(water_color(0.5, 1), water_color(0.5, 900));
+    }
+}
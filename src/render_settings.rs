@@ -0,0 +1,202 @@
+// render_settings.rs - Opciones de calidad del raytracer ajustables en ejecución
+use crate::adaptive::AdaptiveSampling;
+use crate::day_cycle::{Sky, SkyModel};
+use crate::fog::Fog;
+use crate::motion_blur::MotionBlur;
+use crate::snell::MAX_DISTANCE;
+use crate::toon::ToonShading;
+
+/// Muestras por pixel que acepta `RenderSettings::samples_per_pixel`
+pub const SAMPLES_PER_PIXEL_OPTIONS: [u32; 4] = [1, 2, 4, 8];
+
+/// Efectos que se pueden activar o desactivar sin recompilar, más los
+/// límites de la recursión. Se pasa por referencia a todo el trazado.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RenderSettings {
+    /// Rayos de sombra hacia cada luz
+    pub shadows: bool,
+    /// Sombras suaves: cada rayo de sombra apunta a un punto al azar de la
+    /// luz, una esfera de `light_radius`. La penumbra sale del promedio de
+    /// varios frames (`accumulate`) o de varias muestras por pixel.
+    pub soft_shadows: bool,
+    /// Radio de las luces para las sombras suaves
+    pub light_radius: f32,
+    /// Rayos reflejados en materiales reflectivos
+    pub reflections: bool,
+    /// Rayos refractados en materiales transparentes
+    pub refractions: bool,
+    /// Oclusión ambiental: rayos cortos que oscurecen rincones y uniones
+    pub ambient_occlusion: bool,
+    /// Gira el patrón de la oclusión ambiental en cada pixel: cambia las
+    /// bandas de las direcciones fijas por ruido fino
+    pub ao_jitter: bool,
+    /// Portales de luz: las ventanas y puertas cercanas suman la luz del
+    /// cielo que dejan pasar (un rayo de sombra por portal), así los
+    /// interiores no quedan planos con solo el ambiente
+    pub light_portals: bool,
+    /// Multiplica la luz que entra por los portales
+    pub portal_strength: f32,
+    /// Profundidad máxima de rebotes (reflexión/refracción)
+    pub max_depth: u32,
+    /// Ruleta rusa: los rebotes que aportarían poco al pixel se cortan al
+    /// azar y los que siguen pesan más, así el promedio de varias pasadas
+    /// no cambia. Ahorra rayos con `max_depth` alto a cambio de ruido.
+    pub russian_roulette: bool,
+    /// Reflectividad mínima para lanzar un rayo reflejado
+    pub min_reflectivity: f32,
+    /// Interpolación bilinear de texturas; apagada usa el texel más cercano
+    pub bilinear_filtering: bool,
+    /// Renderiza a la mitad de la resolución interna (el modo interactivo
+    /// escala el framebuffer igual a la ventana)
+    pub half_resolution: bool,
+    /// Distancia máxima de los rayos; más allá se ve el cielo
+    pub max_distance: f32,
+    /// Desplazamiento del origen de los rayos secundarios para no volver a
+    /// chocar con la superficie de la que salen
+    pub ray_epsilon: f32,
+    /// Exponente especular a partir del cual se calcula el brillo
+    pub min_specular: f32,
+    /// Intensidad de la luz ambiente (se multiplica por la del cielo)
+    pub ambient_strength: f32,
+    /// Coeficiente de la atenuación cuadrática de las luces
+    pub light_attenuation: f32,
+    /// Lado de los tiles que se reparten entre los hilos
+    pub tile_size: u32,
+    /// Rayos por pixel (uno de `SAMPLES_PER_PIXEL_OPTIONS`), repartidos
+    /// dentro del pixel y promediados antes del mapeo tonal: suaviza los
+    /// bordes a cambio de multiplicar el costo
+    pub samples_per_pixel: u32,
+    /// Acumulación: mientras no cambian la cámara, la escena ni los
+    /// ajustes, cada frame nuevo se promedia con los anteriores y el ruido
+    /// (sombras suaves, oclusión ambiental) se va limpiando
+    pub accumulate: bool,
+    /// Muestreo adaptativo: si está activo manda sobre `samples_per_pixel`
+    /// y reparte los rayos extra según la varianza de cada vecindario
+    pub adaptive: AdaptiveSampling,
+    /// Desenfoque de movimiento de la cámara (varias pasadas por frame
+    /// mientras se mueve)
+    pub motion_blur: MotionBlur,
+    /// Rayos de luz volumétricos: los rayos primarios avanzan por el aire y
+    /// suman la luz del sol que ven en cada paso. Es caro (un rayo de sombra
+    /// por paso); conviene usarlo con varias pasadas acumuladas (`--headless
+    /// --samples`), que promedian el ruido del inicio aleatorio.
+    pub god_rays: bool,
+    /// Pasos de la marcha de cada rayo primario
+    pub god_ray_steps: u32,
+    /// Luz dispersada por unidad de distancia
+    pub god_ray_density: f32,
+    /// Distancia máxima de la marcha desde la cámara
+    pub god_ray_distance: f32,
+    /// Niebla por distancia sobre las superficies (el cielo no la lleva).
+    /// La de la escena, si su archivo trae una, manda sobre esta.
+    pub fog: Fog,
+    /// Cielo analítico o el gradiente original
+    pub sky_model: SkyModel,
+    /// Turbidez del aire del cielo analítico: con más, el horizonte es más
+    /// blanco y el halo del sol más ancho (2 = limpio, 10 = neblinoso)
+    pub turbidity: f32,
+    /// Fracción de las celdas del cielo con una estrella (0 = sin estrellas)
+    pub star_density: f32,
+    /// Brillo de las estrellas más brillantes
+    pub star_brightness: f32,
+    /// Modo dibujo animado: luz difusa en bandas, brillos recortados y
+    /// contornos (estos los dibuja `finish_frame`)
+    pub toon: ToonShading,
+    /// Pruebas de intersección que se ven rojas en la vista de costo
+    pub cost_view_max: u32,
+    /// Cielo y luz ambiente de la hora actual (lo calcula `DayCycle`)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub sky: Sky,
+    /// Tiempo de la simulación en segundos, para los materiales animados
+    /// en el shader (olas del agua)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub time: f32,
+}
+
+impl Default for RenderSettings {
+    /// Sombras y oclusión ambiental vienen apagadas para conservar el
+    /// aspecto original de la escena
+    fn default() -> Self {
+        Self {
+            shadows: false,
+            soft_shadows: false,
+            light_radius: 0.3,
+            reflections: true,
+            refractions: true,
+            ambient_occlusion: false,
+            ao_jitter: false,
+            light_portals: false,
+            portal_strength: 2.0,
+            max_depth: 2,
+            russian_roulette: false,
+            min_reflectivity: 0.05,
+            bilinear_filtering: true,
+            half_resolution: false,
+            max_distance: MAX_DISTANCE,
+            ray_epsilon: 1e-4,
+            min_specular: 5.0,
+            ambient_strength: 0.08,
+            light_attenuation: 0.01,
+            tile_size: 16,
+            samples_per_pixel: 1,
+            accumulate: false,
+            adaptive: AdaptiveSampling::default(),
+            motion_blur: MotionBlur::default(),
+            god_rays: false,
+            god_ray_steps: 16,
+            god_ray_density: 0.008,
+            god_ray_distance: 30.0,
+            fog: Fog::default(),
+            sky_model: SkyModel::Procedural,
+            turbidity: 3.0,
+            star_density: 0.08,
+            star_brightness: 1.5,
+            toon: ToonShading::default(),
+            cost_view_max: 256,
+            sky: Sky::default(),
+            time: 0.0,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Resumen para el HUD, ej: "Sombras ON | Reflejos ON | ... | 1 spp"
+    pub fn summary(&self) -> String {
+        let on_off = |enabled: bool| if enabled { "ON" } else { "OFF" };
+        format!(
+            "Sombras {} | Reflejos {} | Refracción {} | AO {} | {} spp",
+            on_off(self.shadows),
+            on_off(self.reflections),
+            on_off(self.refractions),
+            on_off(self.ambient_occlusion),
+            self.samples_per_pixel
+        )
+    }
+
+    /// Si `samples` es una cantidad de muestras por pixel válida
+    pub fn is_valid_samples_per_pixel(samples: u32) -> bool {
+        SAMPLES_PER_PIXEL_OPTIONS.contains(&samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_keep_the_old_constants() {
+        // Valores de las constantes que reemplazó `RenderSettings`; si
+        // cambian, cambia la imagen por defecto (ver tests/golden.rs)
+        let settings = RenderSettings::default();
+        assert_eq!(settings.max_distance, 50.0);
+        assert_eq!(settings.ray_epsilon, 1e-4);
+        assert_eq!(settings.min_reflectivity, 0.05);
+        assert_eq!(settings.min_specular, 5.0);
+        assert_eq!(settings.max_depth, 2);
+        assert_eq!(settings.ambient_strength, 0.08);
+        assert_eq!(settings.light_attenuation, 0.01);
+        assert_eq!(settings.tile_size, 16);
+    }
+}
@@ -0,0 +1,350 @@
+// render_thread.rs - Raytracer en un hilo propio, separado del loop de la interfaz
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::error;
+use raylib::prelude::*;
+
+use crate::animation::MaterialAnimation;
+use crate::camera::CameraConfig;
+use crate::debug_stats::RenderStats;
+use crate::framebuffer::Framebuffer;
+use crate::gbuffer::ViewMode;
+use crate::headless::sample_offset;
+use crate::light::Light;
+use crate::render_settings::RenderSettings;
+use crate::renderer::Renderer;
+use crate::scene::Scene;
+use crate::textures::TextureManager;
+
+/// Todo lo que necesita el hilo para trazar un frame. La escena es la copia
+/// de `SharedScene::snapshot`: las ediciones de la interfaz mientras tanto
+/// quedan en cola y el frame en curso termina con la versión anterior.
+pub struct RenderJob {
+    pub scene: Arc<Scene>,
+    pub lights: Vec<Light>,
+    pub camera: CameraConfig,
+    /// Cámara del trabajo anterior, para el desenfoque de movimiento
+    pub previous_camera: Option<CameraConfig>,
+    pub settings: RenderSettings,
+    pub view_mode: ViewMode,
+    pub multithreaded: bool,
+    /// Guardar el G-buffer en la vista final (para el filtro de ruido)
+    pub record_gbuffer: bool,
+    /// Animaciones de material, evaluadas con `settings.time` al trazar
+    pub animations: Vec<MaterialAnimation>,
+}
+
+/// Frame trazado (HDR o G-buffer, sin resolver) con la cámara que lo generó
+pub struct RenderedFrame {
+    pub framebuffer: Framebuffer,
+    pub camera: CameraConfig,
+    pub view_mode: ViewMode,
+    pub stats: RenderStats,
+    /// Tiempo de trazado en el hilo de render
+    pub elapsed: Duration,
+    /// Frames promediados en este (1 sin acumulación)
+    pub accumulated_frames: u32,
+}
+
+/// Suma de los frames trazados con la misma cámara, escena, luces y
+/// ajustes (`RenderSettings::accumulate`). Cada frame nuevo corre los rayos
+/// dentro del pixel y cambia la secuencia aleatoria, así el promedio limpia
+/// el ruido y suaviza los bordes.
+#[derive(Default)]
+struct Accumulation {
+    sum: Vec<Vector3>,
+    frames: u32,
+    /// Escena del primer frame; `Weak` para no obligar a la interfaz a
+    /// clonarla al editarla. Cualquier edición la reemplaza.
+    scene: Weak<Scene>,
+    lights: Vec<Light>,
+    camera: Option<CameraConfig>,
+    settings: Option<RenderSettings>,
+}
+
+impl Accumulation {
+    /// Si el frame de `job` se puede promediar con los anteriores
+    fn continues(&self, job: &RenderJob) -> bool {
+        let (Some(camera), Some(settings)) = (&self.camera, &self.settings) else {
+            return false;
+        };
+        // El tiempo avanza en cada frame pero solo importa si algún
+        // material se anima con él
+        let same_settings = if job.scene.has_animated_materials() || !job.animations.is_empty() {
+            *settings == job.settings
+        } else {
+            RenderSettings {
+                time: job.settings.time,
+                ..*settings
+            } == job.settings
+        };
+        std::ptr::eq(self.scene.as_ptr(), Arc::as_ptr(&job.scene))
+            && (camera.width, camera.height) == (job.camera.width, job.camera.height)
+            && !job.camera.moved_from(camera)
+            && self.lights == job.lights
+            && same_settings
+    }
+
+    /// Empieza un promedio nuevo con el frame de `job`
+    fn restart(&mut self, job: &RenderJob) {
+        self.sum.clear();
+        self.sum
+            .resize(job.camera.width * job.camera.height, Vector3::zero());
+        self.frames = 0;
+        self.scene = Arc::downgrade(&job.scene);
+        self.lights.clone_from(&job.lights);
+        self.camera = Some(job.camera.clone());
+        self.settings = Some(job.settings);
+    }
+
+    /// Suma el frame recién trazado y lo reemplaza por el promedio
+    fn add(&mut self, hdr: &mut [Vector3]) {
+        self.frames += 1;
+        let scale = 1.0 / self.frames as f32;
+        for (sum, pixel) in self.sum.iter_mut().zip(hdr) {
+            *sum += *pixel;
+            *pixel = *sum * scale;
+        }
+    }
+}
+
+/// Hilo de render con un trabajo a la vez. La interfaz manda la pose y los
+/// ajustes del momento cuando el hilo queda libre y presenta el último frame
+/// terminado; mientras tanto sigue atendiendo la entrada al ritmo de la
+/// pantalla. Los framebuffers ya presentados vuelven al hilo para no
+/// reservar memoria en cada frame.
+pub struct RenderThread {
+    jobs: Option<Sender<(RenderJob, Option<Framebuffer>)>>,
+    frames: Receiver<RenderedFrame>,
+    worker: Option<JoinHandle<()>>,
+    busy: bool,
+    spare: Option<Framebuffer>,
+}
+
+impl RenderThread {
+    /// Arranca el hilo; `threads` es la cantidad de hilos del render
+    /// multihilo (0 = los que tenga la máquina)
+    pub fn spawn(textures: Arc<TextureManager>, threads: usize) -> Self {
+        let (job_sender, jobs) = mpsc::channel::<(RenderJob, Option<Framebuffer>)>();
+        let (frame_sender, frames) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            let mut renderer = Renderer::new(0, 0, RenderSettings::default());
+            renderer.set_threads(threads);
+            let mut accumulation = Accumulation::default();
+            // Termina cuando la interfaz cierra el canal
+            while let Ok((job, spare)) = jobs.recv() {
+                if let Some(spare) = spare {
+                    renderer.framebuffer = spare;
+                }
+                renderer.resize(job.camera.width as u32, job.camera.height as u32);
+                renderer.settings = job.settings;
+                renderer.view_mode = job.view_mode;
+                renderer.multithreaded = job.multithreaded;
+                renderer.record_gbuffer = job.record_gbuffer;
+                renderer.animations.clone_from(&job.animations);
+
+                // Acumulación: cada frame del promedio con otro corrimiento
+                // dentro del pixel y otra secuencia aleatoria
+                let accumulate = job.settings.accumulate && job.view_mode == ViewMode::Final;
+                if accumulate && !accumulation.continues(&job) {
+                    accumulation.restart(&job);
+                }
+                let sample = if accumulate { accumulation.frames } else { 0 };
+                renderer.seed.sample = sample;
+                let camera = job.camera.with_jitter(sample_offset(sample));
+                renderer.previous_camera = job.previous_camera;
+
+                let start = Instant::now();
+                let stats = renderer.trace(&job.scene, &job.lights, &camera, &textures);
+                let accumulated_frames = if accumulate {
+                    accumulation.add(renderer.framebuffer.hdr_mut());
+                    accumulation.frames
+                } else {
+                    1
+                };
+                // Suelta la escena antes de entregar el frame, así la
+                // interfaz la encuentra libre y la edita sin clonarla
+                drop(job.scene);
+                let frame = RenderedFrame {
+                    framebuffer: std::mem::replace(
+                        &mut renderer.framebuffer,
+                        Framebuffer::new(0, 0),
+                    ),
+                    camera: job.camera,
+                    view_mode: job.view_mode,
+                    stats,
+                    elapsed: start.elapsed(),
+                    accumulated_frames,
+                };
+                if frame_sender.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            jobs: Some(job_sender),
+            frames,
+            worker: Some(worker),
+            busy: false,
+            spare: None,
+        }
+    }
+
+    /// Hay un frame trazándose
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    /// Manda a trazar un frame si el hilo está libre. Devuelve si se envió.
+    pub fn submit(&mut self, job: RenderJob) -> bool {
+        if self.busy {
+            return false;
+        }
+        let Some(jobs) = &self.jobs else {
+            return false;
+        };
+        if jobs.send((job, self.spare.take())).is_err() {
+            error!("El hilo de render terminó inesperadamente");
+            self.shutdown();
+            return false;
+        }
+        self.busy = true;
+        true
+    }
+
+    /// Frame terminado desde la última llamada, si hay
+    pub fn poll(&mut self) -> Option<RenderedFrame> {
+        match self.frames.try_recv() {
+            Ok(frame) => {
+                self.busy = false;
+                Some(frame)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                if self.jobs.is_some() {
+                    error!("El hilo de render terminó inesperadamente");
+                    self.shutdown();
+                }
+                None
+            }
+        }
+    }
+
+    /// Devuelve el framebuffer de un frame ya presentado para el próximo trabajo
+    pub fn recycle(&mut self, framebuffer: Framebuffer) {
+        self.spare = Some(framebuffer);
+    }
+
+    /// Cierra el canal y espera a que termine el frame en curso
+    pub fn shutdown(&mut self) {
+        self.jobs.take();
+        if let Some(worker) = self.worker.take()
+            && worker.join().is_err()
+        {
+            error!("El hilo de render terminó con un pánico");
+        }
+        self.busy = false;
+    }
+}
+
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_types::BlockType;
+    use crate::shared_scene::SharedScene;
+
+    fn job(scene: &Arc<Scene>, camera_pos: Vector3) -> RenderJob {
+        RenderJob {
+            scene: Arc::clone(scene),
+            lights: vec![Light::default()],
+            camera: CameraConfig::new(camera_pos, 0.0, 0.0, 4, 2, 1.0, 2.0),
+            previous_camera: None,
+            settings: RenderSettings {
+                accumulate: true,
+                ..RenderSettings::default()
+            },
+            view_mode: ViewMode::Final,
+            multithreaded: false,
+            record_gbuffer: false,
+            animations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn accumulation_averages_until_something_changes() {
+        let mut shared = SharedScene::new(Scene::new(vec![
+            BlockType::Stone.to_block(Vector3::zero(), 1.0),
+        ]));
+        let scene = shared.snapshot();
+        let first = job(&scene, Vector3::zero());
+        let mut accumulation = Accumulation::default();
+        assert!(!accumulation.continues(&first));
+        accumulation.restart(&first);
+
+        let mut hdr = vec![Vector3::one(); 8];
+        accumulation.add(&mut hdr);
+        let mut hdr = vec![Vector3::new(3.0, 3.0, 3.0); 8];
+        accumulation.add(&mut hdr);
+        assert_eq!(accumulation.frames, 2);
+        assert!(
+            hdr.iter()
+                .all(|pixel| *pixel == Vector3::new(2.0, 2.0, 2.0))
+        );
+
+        // El tiempo no cuenta si ningún material se anima
+        let mut later = job(&scene, Vector3::zero());
+        later.settings.time = 5.0;
+        assert!(accumulation.continues(&later));
+
+        assert!(!accumulation.continues(&job(&scene, Vector3::new(0.0, 1.0, 0.0))));
+        let mut lights = job(&scene, Vector3::zero());
+        lights.lights[0].intensity = 2.0;
+        assert!(!accumulation.continues(&lights));
+        let mut settings = job(&scene, Vector3::zero());
+        settings.settings.shadows = true;
+        assert!(!accumulation.continues(&settings));
+
+        // Editar la escena la reemplaza aunque nadie más la tenga
+        drop((first, later, lights, settings, scene));
+        let mut moved = shared[0].clone();
+        moved.position.y = 1.0;
+        shared.replace_block(0, moved);
+        assert!(!accumulation.continues(&job(&shared.snapshot(), Vector3::zero())));
+    }
+
+    #[test]
+    fn animated_materials_restart_with_time() {
+        let scene = Arc::new(Scene::new(vec![
+            BlockType::Water.to_block(Vector3::zero(), 1.0),
+        ]));
+        assert!(scene.has_animated_materials());
+        let mut accumulation = Accumulation::default();
+        accumulation.restart(&job(&scene, Vector3::zero()));
+        let mut later = job(&scene, Vector3::zero());
+        later.settings.time = 5.0;
+        assert!(!accumulation.continues(&later));
+
+        // Lo mismo con una animación de material sobre un bloque quieto
+        let scene = Arc::new(Scene::new(vec![
+            BlockType::Stone.to_block(Vector3::zero(), 1.0),
+        ]));
+        let stone = BlockType::Stone.material();
+        let mut first = job(&scene, Vector3::zero());
+        first.animations = vec![MaterialAnimation::new(0, stone.clone(), stone, 2.0)];
+        accumulation.restart(&first);
+        let mut later = job(&scene, Vector3::zero());
+        later.animations.clone_from(&first.animations);
+        later.settings.time = 5.0;
+        assert!(!accumulation.continues(&later));
+    }
+}
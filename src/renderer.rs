@@ -0,0 +1,868 @@
+// renderer.rs - Render de un frame sobre el framebuffer, sin ventana
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use raylib::prelude::*;
+
+use crate::adaptive::sample_targets;
+use crate::animation::{AnimatedMaterials, MaterialAnimation};
+use crate::beacon::beacon_beams;
+use crate::camera::CameraConfig;
+use crate::debug_stats::RenderStats;
+use crate::denoise::apply_denoise;
+use crate::framebuffer::{DirtyRect, Framebuffer, ImageLook};
+use crate::gbuffer::{GBufferSample, ViewMode, depth_range};
+use crate::light::Light;
+use crate::portal::light_portals;
+use crate::post::{apply_bloom, apply_fxaa};
+use crate::render_context::{RenderContext, SceneRefs};
+use crate::render_settings::RenderSettings;
+use crate::rng::{Rng, SampleSeed};
+use crate::scene::{Scene, collect_lights, default_lights, insert_procedural_textures};
+use crate::settings::Settings;
+use crate::snell::{RayCounts, take_ray_counts, trace_ray_multi_light};
+use crate::textures::TextureManager;
+use crate::tile_heatmap::TileTime;
+use crate::tonemap::ToneMapSettings;
+use crate::toon::apply_outlines;
+
+/// Lado mínimo de un tile achicado por las muestras por pixel
+const MIN_TILE_SIZE: usize = 4;
+
+/// Dueño del framebuffer y de las opciones del raytracer. No depende de la
+/// ventana de raylib: sirve para el modo interactivo, el render sin ventana
+/// y las pruebas.
+pub struct Renderer {
+    pub framebuffer: Framebuffer,
+    pub settings: RenderSettings,
+    pub view_mode: ViewMode,
+    /// Mapeo tonal de `render`; el loop interactivo aplica el suyo con
+    /// `finish_frame`
+    pub tone_map: ToneMapSettings,
+    /// Reparte los tiles entre `threads` hilos; si no, un solo hilo
+    pub multithreaded: bool,
+    /// Semilla de los efectos estocásticos; el render con varias muestras
+    /// por pixel cambia `sample` en cada pasada
+    pub seed: SampleSeed,
+    /// Guarda el G-buffer también en la vista final (lo usa el filtro de
+    /// ruido); cuesta un rayo primario más por pixel
+    pub record_gbuffer: bool,
+    /// Cámara del frame anterior, para el desenfoque de movimiento. None
+    /// en el primer frame (o después de cambiar la resolución): sin
+    /// desenfoque.
+    pub previous_camera: Option<CameraConfig>,
+    /// Frames trazados hasta ahora (`RenderContext::frame`)
+    pub frame: u64,
+    /// Animaciones de material; se evalúan al empezar cada frame con
+    /// `settings.time` (ver `AnimatedMaterials`)
+    pub animations: Vec<MaterialAnimation>,
+    threads: usize,
+}
+
+impl Renderer {
+    pub fn new(width: u32, height: u32, settings: RenderSettings) -> Self {
+        Self {
+            framebuffer: Framebuffer::new(width, height),
+            settings,
+            view_mode: ViewMode::Final,
+            tone_map: ToneMapSettings::default(),
+            multithreaded: true,
+            seed: SampleSeed::default(),
+            record_gbuffer: false,
+            previous_camera: None,
+            frame: 0,
+            animations: Vec::new(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Hilos del render multihilo; 0 vuelve a los que tenga la máquina
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = if threads == 0 {
+            thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            threads
+        };
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.framebuffer.resize(width, height);
+    }
+
+    /// Traza el frame en el buffer HDR (o el G-buffer en las vistas de
+    /// depuración) sin resolverlo. La cámara debe tener la resolución del
+    /// framebuffer.
+    pub fn trace(
+        &mut self,
+        scene: &Scene,
+        lights: &[Light],
+        camera: &CameraConfig,
+        textures: &TextureManager,
+    ) -> RenderStats {
+        debug_assert_eq!(
+            (camera.width as u32, camera.height as u32),
+            (self.framebuffer.width, self.framebuffer.height)
+        );
+        self.frame += 1;
+        // Los haces de los faros y los portales de luz se buscan una vez por
+        // frame, no por rayo
+        let beams = beacon_beams(scene);
+        let portals = light_portals(scene);
+        let animated = AnimatedMaterials::evaluate(&self.animations, self.settings.time);
+        let refs = SceneRefs {
+            scene,
+            lights,
+            beams: &beams,
+            portals: &portals,
+            textures,
+            animated: &animated,
+        };
+        // La niebla y el sol del archivo de escena mandan sobre los ajustes
+        let settings = RenderSettings {
+            fog: scene.fog.unwrap_or(self.settings.fog),
+            sky: match scene.sun {
+                Some(sun) => self.settings.sky.with_sun_direction(sun),
+                None => self.settings.sky,
+            },
+            ..self.settings
+        };
+
+        // Desenfoque de movimiento: solo si hay pose anterior y la cámara
+        // se movió; si no, una pasada como siempre
+        let blur_from = self.previous_camera.clone().filter(|previous| {
+            settings.motion_blur.enabled
+                && settings.motion_blur.samples > 1
+                && self.view_mode.traces_color()
+                && (previous.width, previous.height) == (camera.width, camera.height)
+                && camera.moved_from(previous)
+        });
+        let Some(previous) = blur_from else {
+            return self.trace_pose(refs, camera, &settings);
+        };
+
+        let seed = self.seed;
+        let passes = settings.motion_blur.samples;
+        let mut stats = RenderStats::default();
+        let mut sum = vec![Vector3::zero(); camera.width * camera.height];
+        let mut sample_counts = vec![0; camera.width * camera.height];
+        // De la pose más vieja a la actual: el G-buffer queda con la última
+        for pass in (0..passes).rev() {
+            let pose = previous.lerp_pose(camera, settings.motion_blur.sample_time(pass));
+            self.seed = seed.motion_pass(pass);
+            let pass_stats = self.trace_pose(refs, &pose, &settings);
+            add_pass(&mut stats, pass_stats);
+            for (total, &color) in sum.iter_mut().zip(self.framebuffer.hdr()) {
+                *total += color;
+            }
+            for (total, &count) in sample_counts
+                .iter_mut()
+                .zip(self.framebuffer.sample_counts())
+            {
+                *total += count;
+            }
+        }
+        self.seed = seed;
+
+        let scale = 1.0 / passes as f32;
+        for (out, total) in self.framebuffer.hdr_mut().iter_mut().zip(sum) {
+            *out = total * scale;
+        }
+        self.framebuffer.set_sample_counts(&sample_counts);
+        stats
+    }
+
+    /// Traza el frame con una sola pose de la cámara
+    fn trace_pose(
+        &mut self,
+        refs: SceneRefs,
+        camera: &CameraConfig,
+        settings: &RenderSettings,
+    ) -> RenderStats {
+        // Muestreo adaptativo: primero un rayo por pixel para medir dónde
+        // varía la imagen
+        let adaptive = settings.adaptive.enabled && self.view_mode.traces_color();
+        let first_pass = RenderSettings {
+            samples_per_pixel: if adaptive {
+                1
+            } else {
+                settings.samples_per_pixel
+            },
+            ..*settings
+        };
+        let context = RenderContext::new(refs, &first_pass, self.frame);
+        let mut stats = if self.multithreaded {
+            self.trace_multithreaded(&context, camera)
+        } else {
+            self.trace_single_threaded(&context, camera)
+        };
+        if !self.view_mode.traces_color() {
+            return stats;
+        }
+        if !adaptive {
+            self.framebuffer
+                .fill_sample_counts(settings.samples_per_pixel);
+            return stats;
+        }
+
+        let targets = sample_targets(
+            self.framebuffer.hdr(),
+            camera.width,
+            camera.height,
+            &settings.adaptive,
+        );
+        let refined = self.refine(&context, camera, &targets);
+        add_pass(&mut stats, refined);
+        self.framebuffer.set_sample_counts(&targets);
+        stats
+    }
+
+    /// Traza y resuelve el frame con `tone_map` (sin bloom ni post-procesado)
+    /// y devuelve los pixeles RGBA
+    pub fn render(
+        &mut self,
+        scene: &Scene,
+        lights: &[Light],
+        camera: &CameraConfig,
+        textures: &TextureManager,
+    ) -> &[u32] {
+        self.trace(scene, lights, camera, textures);
+        if self.view_mode == ViewMode::Final {
+            self.framebuffer.resolve(self.tone_map, false);
+        } else {
+            self.framebuffer.resolve_debug_view(
+                self.view_mode,
+                depth_range(camera.pos, scene, self.settings.max_distance),
+                &self.settings,
+            );
+        }
+        self.framebuffer.as_slice()
+    }
+
+    fn trace_single_threaded(
+        &mut self,
+        context: &RenderContext,
+        camera: &CameraConfig,
+    ) -> RenderStats {
+        let (scene, render_settings) = (context.refs.scene, context.settings);
+        take_ray_counts();
+        let spp = render_settings.samples_per_pixel;
+        for y in 0..camera.height {
+            for x in 0..camera.width {
+                let ray_dir = camera.get_ray_direction(x, y);
+
+                // Vistas de depuración: solo el rayo primario, sin sombreado
+                if !self.view_mode.traces_color() {
+                    let sample = GBufferSample::from_primary_ray(
+                        &camera.pos,
+                        &ray_dir,
+                        scene,
+                        render_settings,
+                    );
+                    self.framebuffer
+                        .set_gbuffer_sample(x as u32, y as u32, sample);
+                    continue;
+                }
+
+                let mut rng = self.seed.rng(x, y);
+                let color_vec = supersample(camera, x, y, spp, &mut rng, |ray_dir, rng| {
+                    trace_ray_multi_light(camera.pos, ray_dir, 0, 1.0, context, rng)
+                });
+
+                self.framebuffer
+                    .set_hdr_pixel(x as u32, y as u32, color_vec);
+                if self.record_gbuffer {
+                    let sample = GBufferSample::from_primary_ray(
+                        &camera.pos,
+                        &ray_dir,
+                        scene,
+                        render_settings,
+                    );
+                    self.framebuffer
+                        .set_gbuffer_sample(x as u32, y as u32, sample);
+                }
+            }
+            self.framebuffer
+                .mark_dirty(DirtyRect::new(0, y as u32, camera.width as u32, 1));
+        }
+        RenderStats {
+            rays: take_ray_counts(),
+            ..RenderStats::default()
+        }
+    }
+
+    fn trace_multithreaded(
+        &mut self,
+        context: &RenderContext,
+        camera: &CameraConfig,
+    ) -> RenderStats {
+        let num_threads = self.threads;
+        let view_mode = self.view_mode;
+        let record_gbuffer = self.record_gbuffer;
+        let seed = self.seed;
+
+        // Cada muestra por pixel multiplica el costo de un tile: el lado se
+        // achica en la raíz de las muestras para que cada uno cueste como
+        // con una sola y el reparto entre hilos siga parejo
+        let tiles = make_tiles(camera, context.settings, context.settings.samples_per_pixel);
+        let results = run_tiles(num_threads, &tiles, |tile, output| {
+            trace_tile(
+                tile,
+                context,
+                camera,
+                view_mode,
+                record_gbuffer,
+                seed,
+                output,
+            )
+        });
+        self.collect_tiles(num_threads, &tiles, results)
+    }
+
+    /// Segunda pasada del muestreo adaptativo: muestras extra en los pixeles
+    /// cuyo objetivo en `targets` pasa de uno, promediadas con la primera
+    fn refine(
+        &mut self,
+        context: &RenderContext,
+        camera: &CameraConfig,
+        targets: &[u32],
+    ) -> RenderStats {
+        let first = self.framebuffer.hdr().to_vec();
+        let seed = self.seed.refinement();
+        let trace_extra = |tile, output: &mut TileOutput| {
+            refine_tile(tile, &first, targets, context, camera, seed, output)
+        };
+
+        if !self.multithreaded {
+            take_ray_counts();
+            let mut output = TileOutput::default();
+            trace_extra((0, 0, camera.width, camera.height), &mut output);
+            for (x, y, color) in output.pixels {
+                self.framebuffer.set_hdr_pixel(x as u32, y as u32, color);
+            }
+            return RenderStats {
+                rays: take_ray_counts(),
+                ..RenderStats::default()
+            };
+        }
+
+        // Los tiles del mismo tamaño que la primera pasada (un rayo por
+        // pixel), así los tiempos se suman tile a tile
+        let tiles = make_tiles(camera, context.settings, 1);
+        let results = run_tiles(self.threads, &tiles, trace_extra);
+        self.collect_tiles(self.threads, &tiles, results)
+    }
+
+    /// Escribe lo que trazaron los hilos y junta sus estadísticas
+    fn collect_tiles(
+        &mut self,
+        num_threads: usize,
+        tiles: &[Tile],
+        results: Vec<TileOutput>,
+    ) -> RenderStats {
+        let mut stats = RenderStats {
+            threads: num_threads,
+            tiles: tiles.len(),
+            ..RenderStats::default()
+        };
+        for output in results {
+            stats.rays.add(&output.rays);
+            stats.tile_times.extend(output.times);
+            for (x, y, c) in output.pixels {
+                self.framebuffer.set_hdr_pixel(x as u32, y as u32, c);
+            }
+            for (x, y, sample) in output.samples {
+                self.framebuffer
+                    .set_gbuffer_sample(x as u32, y as u32, sample);
+            }
+        }
+
+        // Cada tile renderizado es una región modificada
+        for &(x1, y1, x2, y2) in tiles {
+            self.framebuffer.mark_dirty(DirtyRect::new(
+                x1 as u32,
+                y1 as u32,
+                (x2 - x1) as u32,
+                (y2 - y1) as u32,
+            ));
+        }
+        stats
+    }
+}
+
+/// Suma a `stats` los rayos y tiempos de otra pasada sobre el mismo frame.
+/// El tiempo de cada tile se suma al del tile con la misma esquina.
+fn add_pass(stats: &mut RenderStats, pass: RenderStats) {
+    stats.rays.add(&pass.rays);
+    stats.threads = stats.threads.max(pass.threads);
+    stats.tiles = stats.tiles.max(pass.tiles);
+    let tile_index: HashMap<(u32, u32), usize> = stats
+        .tile_times
+        .iter()
+        .enumerate()
+        .map(|(i, tile)| ((tile.x, tile.y), i))
+        .collect();
+    for time in pass.tile_times {
+        match tile_index.get(&(time.x, time.y)) {
+            Some(&i) => stats.tile_times[i].seconds += time.seconds,
+            None => stats.tile_times.push(time),
+        }
+    }
+}
+
+/// Región `(x1, y1, x2, y2)` de la imagen, sin incluir x2 ni y2
+type Tile = (usize, usize, usize, usize);
+
+/// Parte la imagen en tiles para `samples` rayos por pixel
+fn make_tiles(camera: &CameraConfig, render_settings: &RenderSettings, samples: u32) -> Vec<Tile> {
+    let spp = samples.max(1) as f32;
+    let base_size = (render_settings.tile_size as usize).max(1);
+    let tile_size = ((base_size as f32 / spp.sqrt()) as usize).max(MIN_TILE_SIZE.min(base_size));
+    let mut tiles = Vec::new();
+    for ty in (0..camera.height).step_by(tile_size) {
+        for tx in (0..camera.width).step_by(tile_size) {
+            let x2 = (tx + tile_size).min(camera.width);
+            let y2 = (ty + tile_size).min(camera.height);
+            tiles.push((tx, ty, x2, y2));
+        }
+    }
+    tiles
+}
+
+/// Lo que un hilo devuelve de sus tiles
+#[derive(Default)]
+struct TileOutput {
+    pixels: Vec<(usize, usize, Vector3)>,
+    samples: Vec<(usize, usize, GBufferSample)>,
+    times: Vec<TileTime>,
+    rays: RayCounts,
+}
+
+/// Reparte `tiles` entre `num_threads` hilos. Cada hilo toma el siguiente
+/// tile libre de una cola compartida en vez de un bloque fijo, así los
+/// tiles caros (bordes con muchas muestras adaptativas, vidrio) no dejan a
+/// los demás hilos esperando.
+fn run_tiles(
+    num_threads: usize,
+    tiles: &[Tile],
+    trace_tile: impl Fn(Tile, &mut TileOutput) + Sync,
+) -> Vec<TileOutput> {
+    let next = AtomicUsize::new(0);
+    let workers = num_threads.clamp(1, tiles.len().max(1));
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut output = TileOutput::default();
+                    while let Some(&tile) = tiles.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let tile_start = Instant::now();
+                        trace_tile(tile, &mut output);
+                        let (x1, y1, x2, y2) = tile;
+                        output.times.push(TileTime {
+                            x: x1 as u32,
+                            y: y1 as u32,
+                            width: (x2 - x1) as u32,
+                            height: (y2 - y1) as u32,
+                            seconds: tile_start.elapsed().as_secs_f32(),
+                        });
+                    }
+                    output.rays = take_ray_counts();
+                    output
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
+    })
+}
+
+/// Traza un tile de la primera pasada
+fn trace_tile(
+    (x1, y1, x2, y2): Tile,
+    context: &RenderContext,
+    camera: &CameraConfig,
+    view_mode: ViewMode,
+    record_gbuffer: bool,
+    seed: SampleSeed,
+    output: &mut TileOutput,
+) {
+    let (scene, render_settings) = (context.refs.scene, context.settings);
+    let spp = render_settings.samples_per_pixel;
+    for y in y1..y2 {
+        for x in x1..x2 {
+            let ray_dir = camera.get_ray_direction(x, y);
+
+            if !view_mode.traces_color() {
+                let sample =
+                    GBufferSample::from_primary_ray(&camera.pos, &ray_dir, scene, render_settings);
+                output.samples.push((x, y, sample));
+                continue;
+            }
+
+            let mut rng = seed.rng(x, y);
+            let color_vec = supersample(camera, x, y, spp, &mut rng, |ray_dir, rng| {
+                trace_ray_multi_light(camera.pos, ray_dir, 0, 1.0, context, rng)
+            });
+
+            output.pixels.push((x, y, color_vec));
+            if record_gbuffer {
+                let sample =
+                    GBufferSample::from_primary_ray(&camera.pos, &ray_dir, scene, render_settings);
+                output.samples.push((x, y, sample));
+            }
+        }
+    }
+}
+
+/// Muestras extra de los pixeles de un tile según `targets` (rayos totales
+/// por pixel). Caen en puntos al azar del pixel y se promedian con el color
+/// de la primera pasada, `first`.
+fn refine_tile(
+    (x1, y1, x2, y2): Tile,
+    first: &[Vector3],
+    targets: &[u32],
+    context: &RenderContext,
+    camera: &CameraConfig,
+    seed: SampleSeed,
+    output: &mut TileOutput,
+) {
+    for y in y1..y2 {
+        for x in x1..x2 {
+            let index = y * camera.width + x;
+            let target = targets[index];
+            if target <= 1 {
+                continue;
+            }
+
+            let mut rng = seed.rng(x, y);
+            let mut sum = first[index];
+            for _ in 1..target {
+                let jitter = (rng.next_f32(), rng.next_f32());
+                sum += trace_ray_multi_light(
+                    camera.pos,
+                    camera.get_jittered_ray_direction(x, y, jitter),
+                    0,
+                    1.0,
+                    context,
+                    &mut rng,
+                );
+            }
+            output.pixels.push((x, y, sum / target as f32));
+        }
+    }
+}
+
+/// Color de un pixel con `samples` rayos. Con uno pasa por el `jitter` de
+/// la cámara (igual que sin supersampling); con más, el pixel se divide en
+/// una grilla de estratos con un rayo en un punto al azar de cada uno, y
+/// los colores se promedian en lineal, antes del mapeo tonal.
+fn supersample(
+    camera: &CameraConfig,
+    x: usize,
+    y: usize,
+    samples: u32,
+    rng: &mut Rng,
+    mut trace: impl FnMut(Vector3, &mut Rng) -> Vector3,
+) -> Vector3 {
+    if samples <= 1 {
+        return trace(camera.get_ray_direction(x, y), rng);
+    }
+    // Filas: el divisor más grande que no pasa la raíz (8 = 2 x 4)
+    let rows = (1..=samples)
+        .take_while(|rows| rows * rows <= samples)
+        .filter(|rows| samples.is_multiple_of(*rows))
+        .last()
+        .unwrap_or(1);
+    let columns = samples / rows;
+
+    let mut sum = Vector3::zero();
+    for row in 0..rows {
+        for column in 0..columns {
+            let jitter = (
+                (column as f32 + rng.next_f32()) / columns as f32,
+                (row as f32 + rng.next_f32()) / rows as f32,
+            );
+            sum += trace(camera.get_jittered_ray_direction(x, y, jitter), rng);
+        }
+    }
+    sum / samples as f32
+}
+
+/// Post-procesado de la imagen final: filtro de ruido y bloom en HDR, mapeo
+/// tonal y luego contornos del modo dibujo, FXAA y gradación de color sobre
+/// el buffer ya mapeado.
+/// `tone_map` incluye la exposición efectiva (manual más la automática si
+/// está activa). Devuelve cuánto tardó el filtro de ruido, si está activo.
+pub fn finish_frame(
+    framebuffer: &mut Framebuffer,
+    settings: &Settings,
+    tone_map: ToneMapSettings,
+) -> Option<Duration> {
+    // Las pasadas que mezclan pixeles vecinos cambian también fuera de lo
+    // que se trazó: la imagen se vuelve a subir entera
+    if settings.denoise.enabled
+        || settings.bloom.enabled
+        || settings.fxaa
+        || settings.render.toon.enabled
+    {
+        framebuffer.mark_all_dirty();
+    }
+    framebuffer.set_look(ImageLook {
+        tone_map,
+        dither: settings.dither,
+        post: settings.post,
+    });
+    let denoise_time = apply_denoise(framebuffer, &settings.denoise);
+    apply_bloom(framebuffer, &settings.bloom);
+    framebuffer.resolve(tone_map, settings.dither);
+    apply_outlines(framebuffer, &settings.render.toon);
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    if settings.fxaa {
+        apply_fxaa(framebuffer.buffer_mut(), width, height);
+    }
+    settings.post.apply(framebuffer.buffer_mut(), width, height);
+    denoise_time
+}
+
+/// Frame determinista para comparar contra imágenes de referencia: camino de
+/// un solo hilo, solo texturas procedurales (las de disco quedan en el color
+/// base del material) y con las luces derivadas de la escena.
+pub fn render_reference(
+    width: u32,
+    height: u32,
+    scene: &Scene,
+    camera: &CameraConfig,
+    settings: &Settings,
+) -> Vec<u32> {
+    let camera_config = camera.with_size(width as usize, height as usize);
+    let lights = collect_lights(scene, &settings.day.tint_lights(&default_lights()));
+    let mut textures = TextureManager::new();
+    insert_procedural_textures(&mut textures);
+    let mut renderer = Renderer::new(width, height, settings.render);
+    renderer.multithreaded = false;
+    renderer.record_gbuffer = settings.needs_gbuffer();
+    renderer.trace(scene, &lights, &camera_config, &textures);
+    finish_frame(&mut renderer.framebuffer, settings, settings.tone_map);
+    renderer.framebuffer.as_slice().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_types::BlockType;
+
+    /// Un bloque de piedra frente a la cámara, 8x6 pixeles
+    fn tiny_frame(multithreaded: bool) -> Vec<u32> {
+        tiny_frame_with(RenderSettings::default(), multithreaded, 2)
+    }
+
+    fn tiny_frame_with(settings: RenderSettings, multithreaded: bool, threads: usize) -> Vec<u32> {
+        let scene = Scene::new(vec![BlockType::Stone.to_block(Vector3::zero(), 1.0)]);
+        let camera = CameraConfig::new(
+            Vector3::new(0.0, 0.0, -4.0),
+            std::f32::consts::FRAC_PI_2,
+            0.0,
+            8,
+            6,
+            std::f32::consts::FRAC_PI_3,
+            8.0 / 6.0,
+        );
+        let lights = collect_lights(&scene, &default_lights());
+        let mut renderer = Renderer::new(8, 6, settings);
+        renderer.multithreaded = multithreaded;
+        renderer.set_threads(threads);
+        renderer
+            .render(&scene, &lights, &camera, &TextureManager::new())
+            .to_vec()
+    }
+
+    #[test]
+    fn renders_tiny_scene_without_a_window() {
+        let single = tiny_frame(false);
+        assert_eq!(single.len(), 8 * 6);
+        // Los tiles de varios hilos dan exactamente los mismos pixeles
+        assert_eq!(single, tiny_frame(true));
+        // El centro ve el bloque y la esquina el cielo
+        let center = single[3 * 8 + 4];
+        let corner = single[0];
+        assert_ne!(center, corner);
+        assert_ne!(center & 0x00ff_ffff, 0);
+    }
+
+    /// Un bloque sin sombrear visto de costado contra un cielo negro: cada
+    /// pixel es el color del bloque, el del cielo o una mezcla de ambos
+    fn silhouette_frame(samples_per_pixel: u32) -> Vec<u32> {
+        use crate::day_cycle::{Sky, SkyModel};
+
+        let scene = Scene::new(vec![BlockType::Sun.to_block(Vector3::zero(), 1.0)]);
+        // Mirando en diagonal a una esquina: los bordes quedan inclinados
+        let camera = CameraConfig::new(
+            Vector3::new(-2.5, 2.0, -2.5),
+            std::f32::consts::FRAC_PI_4,
+            -0.5,
+            24,
+            18,
+            std::f32::consts::FRAC_PI_3,
+            24.0 / 18.0,
+        );
+        let settings = RenderSettings {
+            samples_per_pixel,
+            sky_model: SkyModel::Gradient,
+            star_density: 0.0,
+            sky: Sky {
+                horizon: Vector3::zero(),
+                zenith: Vector3::zero(),
+                sun_color: Vector3::zero(),
+                moon_color: Vector3::zero(),
+                ..Sky::default()
+            },
+            ..RenderSettings::default()
+        };
+        let mut renderer = Renderer::new(24, 18, settings);
+        renderer.multithreaded = false;
+        renderer
+            .render(&scene, &[], &camera, &TextureManager::new())
+            .to_vec()
+    }
+
+    #[test]
+    fn supersampling_blends_the_block_edges() {
+        let hard = silhouette_frame(1);
+        let sky = hard[0] & 0x00ff_ffff;
+        let block = hard[9 * 24 + 12] & 0x00ff_ffff;
+        assert_ne!(sky, block);
+        let blended = |frame: &[u32]| {
+            frame
+                .iter()
+                .map(|pixel| pixel & 0x00ff_ffff)
+                .filter(|&pixel| pixel != sky && pixel != block)
+                .count()
+        };
+        // Con una muestra el borde es un escalón: solo bloque o cielo
+        assert_eq!(blended(&hard), 0);
+
+        let smooth = silhouette_frame(4);
+        assert!(
+            blended(&smooth) > 5,
+            "{} pixeles mezclados",
+            blended(&smooth)
+        );
+        // Las mezclas quedan entre los dos colores en cada canal
+        for pixel in smooth {
+            for shift in [0, 8, 16] {
+                let channel = (pixel >> shift) & 0xff;
+                let (a, b) = ((sky >> shift) & 0xff, (block >> shift) & 0xff);
+                assert!(channel >= a.min(b) && channel <= a.max(b), "{:06x}", pixel);
+            }
+        }
+    }
+
+    /// Isla por defecto de 128x96: el HDR promediado de `frames` frames y los
+    /// rayos primarios de cada uno
+    fn island_hdr(settings: RenderSettings, frames: usize) -> (Vec<Vector3>, u64) {
+        let scene = crate::scene::create_optimized_scene();
+        let camera = CameraConfig::new(
+            Vector3::new(0.0, 4.0, -10.0),
+            std::f32::consts::FRAC_PI_2,
+            -0.3,
+            128,
+            96,
+            std::f32::consts::FRAC_PI_3,
+            128.0 / 96.0,
+        );
+        let lights = collect_lights(&scene, &default_lights());
+        let mut textures = TextureManager::new();
+        insert_procedural_textures(&mut textures);
+        let mut renderer = Renderer::new(128, 96, settings);
+        let mut sum = vec![Vector3::zero(); 128 * 96];
+        let mut primary = 0;
+        for _ in 0..frames {
+            primary += renderer
+                .trace(&scene, &lights, &camera, &textures)
+                .rays
+                .primary;
+            for (total, &color) in sum.iter_mut().zip(renderer.framebuffer.hdr()) {
+                *total += color;
+            }
+        }
+        let average = sum.into_iter().map(|color| color / frames as f32).collect();
+        (average, primary / frames as u64)
+    }
+
+    /// Error cuadrático medio entre dos frames, con el brillo comprimido a
+    /// [0, 1) como lo ve el umbral adaptativo
+    fn rmse(a: &[Vector3], b: &[Vector3]) -> f32 {
+        let compress =
+            |c: Vector3| Vector3::new(c.x / (1.0 + c.x), c.y / (1.0 + c.y), c.z / (1.0 + c.z));
+        let sum: f32 = a
+            .iter()
+            .zip(b)
+            .map(|(&a, &b)| (compress(a) - compress(b)).length_sqr() / 3.0)
+            .sum();
+        (sum / a.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn adaptive_sampling_matches_four_spp_with_half_the_rays() {
+        let base = RenderSettings::default();
+        let (reference, _) = island_hdr(
+            RenderSettings {
+                samples_per_pixel: 8,
+                ..base
+            },
+            2,
+        );
+        let (uniform, uniform_rays) = island_hdr(
+            RenderSettings {
+                samples_per_pixel: 4,
+                ..base
+            },
+            1,
+        );
+        let mut settings = base;
+        settings.adaptive.enabled = true;
+        let (adaptive, adaptive_rays) = island_hdr(settings, 1);
+
+        let (uniform_error, adaptive_error) =
+            (rmse(&uniform, &reference), rmse(&adaptive, &reference));
+        assert!(
+            adaptive_rays * 2 <= uniform_rays,
+            "{} rayos contra {}",
+            adaptive_rays,
+            uniform_rays
+        );
+        assert!(
+            adaptive_error <= uniform_error * 1.1,
+            "error {} contra {}",
+            adaptive_error,
+            uniform_error
+        );
+    }
+
+    #[test]
+    fn noisy_frame_is_identical_across_runs_and_thread_counts() {
+        // Sombras suaves, oclusión con jitter y varias muestras: todo lo
+        // aleatorio sale del generador de cada pixel
+        let settings = RenderSettings {
+            shadows: true,
+            soft_shadows: true,
+            ambient_occlusion: true,
+            ao_jitter: true,
+            samples_per_pixel: 4,
+            ..RenderSettings::default()
+        };
+        let reference = tiny_frame_with(settings, false, 1);
+        assert_eq!(reference, tiny_frame_with(settings, false, 1));
+        for threads in [1, 2, 3, 8] {
+            assert_eq!(
+                reference,
+                tiny_frame_with(settings, true, threads),
+                "{} hilos",
+                threads
+            );
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// rng.rs - Números pseudoaleatorios deterministas (PCG32) para los efectos estocásticos
+use std::f32::consts::TAU;
+
+use raylib::prelude::*;
+
+/// Semilla de una pasada de render. Cada pixel arma su propio generador a
+/// partir de esto y sus coordenadas, así el resultado no depende de cómo se
+/// repartan los tiles entre los hilos.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SampleSeed {
+    /// Semilla del frame; cambiarla cambia el patrón de ruido
+    pub frame: u64,
+    /// Índice de la muestra cuando se promedian varias pasadas por pixel
+    pub sample: u32,
+}
+
+impl SampleSeed {
+    /// Generador del pixel `(x, y)` en esta pasada
+    pub fn rng(self, x: usize, y: usize) -> Rng {
+        Rng::for_pixel(self.frame, x as u32, y as u32, self.sample)
+    }
+
+    /// Semilla de la pasada `pass` del desenfoque de movimiento (la 0 es la
+    /// de este frame)
+    pub fn motion_pass(self, pass: u32) -> Self {
+        if pass == 0 {
+            return self;
+        }
+        Self {
+            frame: splitmix64(self.frame.wrapping_add(pass as u64)),
+            ..self
+        }
+    }
+
+    /// Semilla de una segunda pasada sobre los mismos pixeles (las muestras
+    /// extra del muestreo adaptativo) que no repite los números de esta
+    pub fn refinement(self) -> Self {
+        Self {
+            frame: splitmix64(self.frame ^ 0xA5A5_5A5A_C3C3_3C3C),
+            ..self
+        }
+    }
+}
+
+/// PCG32 (XSH RR): 64 bits de estado, salida de 32 bits. Es chico, rápido y
+/// con mucha mejor calidad que un LCG simple.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+}
+
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+
+impl Rng {
+    /// Generador con una semilla y un número de secuencia; secuencias
+    /// distintas con la misma semilla no se solapan
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// Generador de un pixel: la semilla mezcla el frame y las coordenadas y
+    /// cada muestra usa su propia secuencia
+    pub fn for_pixel(frame_seed: u64, x: u32, y: u32, sample_index: u32) -> Self {
+        let pixel = ((y as u64) << 32) | x as u64;
+        Self::new(
+            splitmix64(frame_seed ^ splitmix64(pixel)),
+            sample_index as u64,
+        )
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(PCG_MULTIPLIER)
+            .wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// Número uniforme en [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        // 24 bits: todos los valores son exactos en un f32
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Punto uniforme en el disco de radio 1
+    pub fn in_unit_disk(&mut self) -> (f32, f32) {
+        let radius = self.next_f32().sqrt();
+        let angle = self.next_f32() * TAU;
+        (radius * angle.cos(), radius * angle.sin())
+    }
+
+    /// Punto uniforme dentro de la esfera de radio 1 (por rechazo: en
+    /// promedio se descartan menos de la mitad de los intentos)
+    pub fn in_unit_sphere(&mut self) -> Vector3 {
+        loop {
+            let point = Vector3::new(
+                self.next_f32() * 2.0 - 1.0,
+                self.next_f32() * 2.0 - 1.0,
+                self.next_f32() * 2.0 - 1.0,
+            );
+            if point.length_sqr() <= 1.0 {
+                return point;
+            }
+        }
+    }
+
+    /// Dirección del hemisferio de `normal` con densidad proporcional al
+    /// coseno (proyecta un punto del disco sobre la semiesfera)
+    pub fn cosine_hemisphere(&mut self, normal: &Vector3) -> Vector3 {
+        let (x, y) = self.in_unit_disk();
+        let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+        let (tangent, bitangent) = orthonormal_basis(normal);
+        (tangent * x + bitangent * y + *normal * z).normalized()
+    }
+}
+
+/// Mezclador de bits de SplitMix64, para que semillas parecidas (pixeles
+/// vecinos) den estados sin relación
+pub(crate) fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Tangente y bitangente perpendiculares a `normal` (unitaria)
+fn orthonormal_basis(normal: &Vector3) -> (Vector3, Vector3) {
+    let helper = if normal.y.abs() < 0.9 {
+        Vector3::new(0.0, 1.0, 0.0)
+    } else {
+        Vector3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = normal.cross(helper).normalized();
+    (tangent, normal.cross(tangent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_reference_pcg32() {
+        // Primeras salidas de pcg32_srandom_r(42, 54) en la implementación
+        // de referencia (pcg32-demo)
+        let mut rng = Rng::new(42, 54);
+        let expected = [
+            0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e,
+        ];
+        for value in expected {
+            assert_eq!(rng.next_u32(), value);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let mut a = Rng::for_pixel(7, 12, 34, 2);
+        let mut b = SampleSeed {
+            frame: 7,
+            sample: 2,
+        }
+        .rng(12, 34);
+        for _ in 0..1000 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+        // Otra muestra del mismo pixel es otra secuencia
+        let mut a = Rng::for_pixel(7, 12, 34, 2);
+        let mut c = Rng::for_pixel(7, 12, 34, 3);
+        assert!((0..16).any(|_| a.next_u32() != c.next_u32()));
+    }
+
+    #[test]
+    fn neighbouring_pixels_are_decorrelated() {
+        // Correlación entre el primer número de cada pixel y el de su
+        // vecino de la derecha, a lo largo de una fila
+        let first = |x: u32| Rng::for_pixel(1, x, 5, 0).next_f32();
+        let n = 4096;
+        let pairs: Vec<(f32, f32)> = (0..n).map(|x| (first(x), first(x + 1))).collect();
+        let mean = |values: &mut dyn Iterator<Item = f32>| values.sum::<f32>() / n as f32;
+        let (mean_a, mean_b) = (
+            mean(&mut pairs.iter().map(|p| p.0)),
+            mean(&mut pairs.iter().map(|p| p.1)),
+        );
+        let covariance = mean(&mut pairs.iter().map(|(a, b)| (a - mean_a) * (b - mean_b)));
+        let variance = mean(&mut pairs.iter().map(|(a, _)| (a - mean_a).powi(2)));
+        let correlation = covariance / variance;
+        assert!(correlation.abs() < 0.05, "correlación {}", correlation);
+        assert!((mean_a - 0.5).abs() < 0.02, "media {}", mean_a);
+    }
+
+    #[test]
+    fn helpers_stay_in_their_domain() {
+        let mut rng = Rng::new(3, 4);
+        let normal = Vector3::new(0.0, 0.6, 0.8);
+        for _ in 0..1000 {
+            let value = rng.next_f32();
+            assert!((0.0..1.0).contains(&value));
+            let (x, y) = rng.in_unit_disk();
+            assert!(x * x + y * y <= 1.0 + 1e-6);
+            assert!(rng.in_unit_sphere().length_sqr() <= 1.0);
+            let dir = rng.cosine_hemisphere(&normal);
+            assert!((dir.length() - 1.0).abs() < 1e-4);
+            assert!(dir.dot(normal) >= -1e-6);
+        }
+    }
+}
@@ -1,37 +1,425 @@
 // scene.rs - Isla flotante con casa, jardín, árbol y lago
-use crate::block::{self, Block};
-use crate::block_types::BlockType;
-use crate::textures::TextureManager;
+use crate::animation::MaterialAnimation;
+use crate::block::{Block, Face};
+use crate::block_registry::BlockDefinition;
+use crate::block_shape::{Attachment, Axis, BlockShape, Direction};
+use std::collections::HashMap;
+use std::ops::Deref;
+use crate::block_types::{BlockType, insert_log_textures};
+use crate::bvh::{Aabb, Bvh, BvhCounters, BvhItem};
+use crate::chest::insert_chest_textures;
+use crate::fog::Fog;
+use crate::headless::{SceneSource, parse_scene};
+use crate::light::Light;
+use crate::plant::insert_plant_textures;
+use crate::primitive::{Plane, Primitive, Sphere};
+use crate::scene_file::{SCENE_PATH, SceneError, load_scene};
+use crate::textures::{TextureError, TextureManager};
+use crate::tnt::insert_tnt_textures;
+use crate::voxel_grid::{GridCounters, VoxelGrid};
+use log::{debug, info};
 use raylib::prelude::*;
 
-/// Carga las texturas que vamos a usar en los bloques estilo Minecraft
+/// Texturas de bloques que usa la escena
+const MINECRAFT_TEXTURES: [&str; 10] = [
+    "textures/grass_top.jpg",
+    "textures/dirt.jpg",
+    "textures/stone.jpg",
+    "textures/cobble.png",
+    "textures/cherry_log.png",
+    "textures/cherry_leaves.png",
+    "textures/leaves_oak.jpg",
+    "textures/glass.png",
+    "textures/sand.png",
+    "textures/magma.png",
+];
+
+/// Contenido de la escena. Los bloques de la grilla son el camino rápido: se
+/// editan, se animan y se guardan uno por uno, y el resto del programa lee
+/// `Scene` directamente como su lista (`Deref` a `Vec<Block>`). Planos,
+/// esferas y cajas sueltas van en `primitives`. Los índices de objeto
+/// numeran primero los bloques y después las primitivas.
+///
+/// Para trazar, los bloques llevan al lado una `VoxelGrid`, y los que no
+/// entran en una celda junto con las primitivas una `Bvh`. Los bloques se
+/// cambian solo con `place_block`, `remove_block` y `replace_block`, que
+/// actualizan las dos en el lugar y recalculan los vecinos de la celda
+/// tocada. `invalidate_grid` las deja viejas a propósito (los rayos prueban
+/// todo) hasta `refresh_grid`.
+#[derive(Debug, Clone, Default)]
+pub struct Scene {
+    blocks: Vec<Block>,
+    primitives: Vec<Primitive>,
+    grid: VoxelGrid,
+    bvh: Bvh,
+    /// La grilla y la BVH no siguen a las listas hasta `refresh_grid`
+    stale: bool,
+    /// Tipos de bloque propios que usa la escena; se guardan con ella para
+    /// que cargue sin `blocks.txt`
+    pub palette: Vec<BlockDefinition>,
+    /// Niebla propia de la escena; si está, manda sobre la de los ajustes
+    pub fog: Option<Fog>,
+    /// Dirección fija del sol; si está, reemplaza la de la hora del día
+    pub sun: Option<Vector3>,
+    /// Celdas abiertas (puertas, huecos) que iluminan el interior con el
+    /// cielo, además de los vidrios (ver `portal::light_portals`)
+    pub portal_cells: Vec<Vector3>,
+}
+
+impl Scene {
+    pub fn new(blocks: Vec<Block>) -> Self {
+        Self::default().with_blocks(blocks)
+    }
+
+    /// Suma (o reemplaza) un tipo propio en la paleta
+    pub fn add_to_palette(&mut self, definition: &BlockDefinition) {
+        match self.palette.iter_mut().find(|entry| entry.name == definition.name) {
+            Some(entry) => *entry = definition.clone(),
+            None => self.palette.push(definition.clone()),
+        }
+    }
+
+    /// La misma escena con otros bloques (ya con sus vecinos calculados)
+    pub fn with_blocks(mut self, blocks: Vec<Block>) -> Self {
+        self.blocks = blocks;
+        self.build_index();
+        self
+    }
+
+    pub fn with_primitives(mut self, primitives: Vec<Primitive>) -> Self {
+        self.primitives = primitives;
+        self.build_index();
+        self
+    }
+
+    pub fn primitives(&self) -> &[Primitive] {
+        &self.primitives
+    }
+
+    /// Bloques más primitivas
+    pub fn object_count(&self) -> usize {
+        self.blocks.len() + self.primitives.len()
+    }
+
+    /// Bloque de la grilla con ese índice de objeto
+    pub fn block(&self, index: usize) -> Option<&Block> {
+        self.blocks.get(index)
+    }
+
+    /// Primitiva con ese índice de objeto (los que siguen a los bloques)
+    pub fn primitive(&self, index: usize) -> Option<&Primitive> {
+        index
+            .checked_sub(self.blocks.len())
+            .and_then(|i| self.primitives.get(i))
+    }
+
+    /// Grilla de los bloques, si está al día con la lista
+    pub fn grid(&self) -> Option<&VoxelGrid> {
+        (!self.stale).then_some(&self.grid)
+    }
+
+    /// BVH de los bloques que no entran en la grilla y de las primitivas, si
+    /// está al día
+    pub fn bvh(&self) -> Option<&Bvh> {
+        (!self.stale).then_some(&self.bvh)
+    }
+
+    /// Ediciones en el lugar y reconstrucciones de la grilla hasta ahora
+    pub fn grid_counters(&self) -> GridCounters {
+        self.grid.counters()
+    }
+
+    /// Reajustes y reconstrucciones de la BVH hasta ahora
+    pub fn bvh_counters(&self) -> BvhCounters {
+        self.bvh.counters()
+    }
+
+    /// Deja la grilla y la BVH viejas: los rayos prueban todos los objetos
+    /// y las ediciones recalculan los vecinos de toda la escena, hasta
+    /// `refresh_grid` (ej: para comparar con el camino lento)
+    pub fn invalidate_grid(&mut self) {
+        self.stale = true;
+    }
+
+    /// Rearma la grilla y la BVH si `invalidate_grid` las dejó viejas
+    pub fn refresh_grid(&mut self) {
+        if self.stale {
+            self.grid.rebuild(&self.blocks);
+            self.bvh.rebuild_with(self.bvh_items());
+            self.stale = false;
+        }
+    }
+
+    /// Agrega un bloque en una celda libre. Devuelve su índice, o None si la
+    /// celda ya estaba ocupada por un bloque que se pisa con él (dos losas,
+    /// una arriba y otra abajo, comparten la celda).
+    pub fn place_block(&mut self, block: Block) -> Option<usize> {
+        let key = grid_key(block.position);
+        if self
+            .blocks_with_key(key)
+            .into_iter()
+            .any(|other| collides(&self.blocks[other], &block))
+        {
+            return None;
+        }
+        let spot = (block.position, block.extents);
+        let index = self.blocks.len();
+        self.blocks.push(block);
+        if !self.stale && self.grid.insert(index, &self.blocks[index]).is_none() {
+            let bounds = block_box(&self.blocks[index]);
+            self.bvh.insert(BvhItem::Block(index as u32), Some(bounds));
+        }
+        self.neighbors_changed_at(&[spot]);
+        Some(index)
+    }
+
+    /// Quita un bloque como `Vec::swap_remove`: el último pasa a ocupar su
+    /// índice y los demás no cambian (ver `index_after_removal`). Recalcula
+    /// los vecinos de la celda que quedó libre.
+    pub fn remove_block(&mut self, index: usize) -> Block {
+        let last = self.blocks.len() - 1;
+        if !self.stale {
+            let removed_outside = self.grid.block_cell(index).is_none();
+            let moved_outside = index != last && self.grid.block_cell(last).is_none();
+            self.grid.remove(index);
+            if removed_outside {
+                self.bvh.remove(BvhItem::Block(index as u32));
+            }
+            if moved_outside {
+                self.bvh
+                    .rename(BvhItem::Block(last as u32), BvhItem::Block(index as u32));
+            }
+        }
+        let removed = self.blocks.swap_remove(index);
+        self.neighbors_changed_at(&[(removed.position, removed.extents)]);
+        removed
+    }
+
+    /// Cambia el bloque `index` por `block` (abrir un cofre, la mecha de una
+    /// TNT, una capa más de nieve, otro material) sin mover los demás.
+    /// Devuelve el que estaba.
+    pub fn replace_block(&mut self, index: usize, block: Block) -> Block {
+        let previous = std::mem::replace(&mut self.blocks[index], block);
+        if !self.stale {
+            let was_outside = self.grid.block_cell(index).is_none();
+            let block = &self.blocks[index];
+            let inside = self.grid.update(index, block).is_some();
+            let item = BvhItem::Block(index as u32);
+            match (was_outside, inside) {
+                (true, true) => self.bvh.remove(item),
+                (false, false) => self.bvh.insert(item, Some(block_box(block))),
+                (true, false) if block_box(block) != block_box(&previous) => {
+                    self.bvh.update(item, Some(block_box(block)))
+                }
+                _ => {}
+            }
+        }
+        let block = &self.blocks[index];
+        self.neighbors_changed_at(&[
+            (previous.position, previous.extents),
+            (block.position, block.extents),
+        ]);
+        previous
+    }
+
+    /// Luces de las primitivas emisivas, en orden
+    pub fn primitive_lights(&self) -> impl Iterator<Item = &Light> {
+        self.primitives.iter().filter_map(Primitive::emission)
+    }
+
+    /// Si algún material cambia con `RenderSettings::time` (olas, cáusticas)
+    pub fn has_animated_materials(&self) -> bool {
+        self.blocks
+            .iter()
+            .map(|block| &block.material)
+            .chain(self.primitives.iter().map(Primitive::material))
+            .any(|material| material.waves > 0.0 || material.caustics)
+    }
+
+    /// Arma la grilla y la BVH de cero, con los contadores en cero
+    fn build_index(&mut self) {
+        self.grid = VoxelGrid::build(&self.blocks);
+        self.bvh = Bvh::build(self.bvh_items());
+        self.stale = false;
+    }
+
+    /// Lo que va en la BVH: los bloques que no entran en la grilla y las
+    /// primitivas, con su caja
+    fn bvh_items(&self) -> Vec<(BvhItem, Option<Aabb>)> {
+        let blocks = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.grid.block_cell(*index).is_none())
+            .map(|(index, block)| (BvhItem::Block(index as u32), Some(block_box(block))));
+        let primitives = self
+            .primitives
+            .iter()
+            .enumerate()
+            .map(|(index, primitive)| (BvhItem::Primitive(index as u32), primitive.bounds()));
+        blocks.chain(primitives).collect()
+    }
+
+    /// Índices de los bloques con esa clave de `grid_key`, en orden: con la
+    /// grilla y la BVH al día se buscan en las celdas de alrededor, si no en
+    /// toda la lista
+    fn blocks_with_key(&self, key: (i32, i32, i32)) -> Vec<usize> {
+        if self.stale {
+            return (0..self.blocks.len())
+                .filter(|&index| grid_key(self.blocks[index].position) == key)
+                .collect();
+        }
+        let center = Vector3::new(key.0 as f32, key.1 as f32, key.2 as f32) * 0.5;
+        let (min, max) = (
+            center - Vector3::one() * 0.25,
+            center + Vector3::one() * 0.25,
+        );
+        let mut found = Vec::new();
+        let mut keep = |index: usize| {
+            if grid_key(self.blocks[index].position) == key {
+                found.push(index);
+            }
+        };
+        self.grid.blocks_near(min, max, |id| keep(id as usize));
+        self.bvh.visit_box(min, max, |item| {
+            if let BvhItem::Block(id) = item {
+                keep(id as usize);
+            }
+        });
+        found.sort_unstable();
+        found
+    }
+
+    /// El último bloque con esa clave cuya forma cumple `wanted` (el mismo
+    /// que gana en los `HashMap` de `mark_interior_faces` y `connect_fences`)
+    fn last_block_with(
+        &self,
+        key: (i32, i32, i32),
+        wanted: impl Fn(&BlockShape) -> bool,
+    ) -> Option<usize> {
+        self.blocks_with_key(key)
+            .into_iter()
+            .rfind(|&index| wanted(&self.blocks[index].shape))
+    }
+
+    /// Recalcula las caras interiores y los travesaños de los bloques que
+    /// pueden ver la celda de cada `(posición, tamaño)` como vecina: los de
+    /// esa celda y los de las seis de al lado. Un vecino de otro tamaño no
+    /// une caras, así que alcanza con mirar a un tamaño de distancia. Con la
+    /// grilla vieja se recalcula toda la escena.
+    fn neighbors_changed_at(&mut self, spots: &[(Vector3, Vector3)]) {
+        if self.stale {
+            neighbors_changed(&mut self.blocks);
+            return;
+        }
+        let mut touched = Vec::new();
+        for &(position, extents) in spots {
+            touched.extend(self.blocks_with_key(grid_key(position)));
+            for (_, normal) in face_normals() {
+                touched.extend(self.blocks_with_key(grid_key(position - normal * extents)));
+            }
+        }
+        touched.sort_unstable();
+        touched.dedup();
+
+        for &index in &touched {
+            let rails = fence_rails(&self.blocks[index], |key| {
+                self.last_block_with(key, |shape| matches!(shape, BlockShape::Fence { .. }))
+                    .map(|index| self.blocks[index].extents)
+            });
+            if let Some(rails) = rails
+                && let BlockShape::Fence { rails: current } = &mut self.blocks[index].shape
+            {
+                *current = rails;
+            }
+        }
+        for &index in &touched {
+            let mask = interior_mask(&self.blocks[index], |key| {
+                self.last_block_with(key, covers_face)
+                    .map(|index| &self.blocks[index])
+            });
+            self.blocks[index].interior_faces = mask;
+        }
+    }
+}
+
+/// Dónde queda un índice de bloque después de `Scene::remove_block(removed)`,
+/// con `last` el índice que tenía el último bloque: el quitado desaparece y
+/// el último pasa a `removed`
+pub fn index_after_removal(index: usize, removed: usize, last: usize) -> Option<usize> {
+    if index == removed {
+        None
+    } else if index == last {
+        Some(removed)
+    } else {
+        Some(index)
+    }
+}
+
+/// La grilla y la BVH se derivan de los objetos: no cuentan para comparar
+/// escenas
+impl PartialEq for Scene {
+    fn eq(&self, other: &Self) -> bool {
+        self.blocks == other.blocks
+            && self.primitives == other.primitives
+            && self.palette == other.palette
+            && self.fog == other.fog
+            && self.sun == other.sun
+            && self.portal_cells == other.portal_cells
+    }
+}
+
+impl From<Vec<Block>> for Scene {
+    fn from(blocks: Vec<Block>) -> Self {
+        Self::new(blocks)
+    }
+}
+
+impl Deref for Scene {
+    type Target = Vec<Block>;
+
+    fn deref(&self) -> &Vec<Block> {
+        &self.blocks
+    }
+}
+
+/// Carga las texturas que vamos a usar en los bloques estilo Minecraft (más
+/// las procedurales de plantas, tapas de tronco, TNT y cofre). Una que falla no
+/// corta la carga: se devuelven todas las que no se pudieron leer.
 pub fn load_minecraft_textures(
     rl: &mut RaylibHandle,
     thread: &RaylibThread,
     tex_mgr: &mut TextureManager,
-) -> Result<(), String> {
-    let textures = vec![
-        "textures/grass_top.jpg",
-        "textures/dirt.jpg",
-        "textures/stone.jpg",
-        "textures/cobble.png",
-        "textures/cherry_log.png",
-        "textures/cherry_leaves.png",
-        "textures/leaves_oak.jpg",
-        "textures/glass.png",
-        "textures/sand.png",
-        "textures/magma.png",
-    ];
+) -> Result<(), Vec<TextureError>> {
+    insert_procedural_textures(tex_mgr);
+    collect_texture_errors(MINECRAFT_TEXTURES.map(|path| tex_mgr.load_texture(rl, thread, path)))
+}
 
-    for path in textures {
-        tex_mgr.load_texture(rl, thread, path)?;
-    }
+/// Como `load_minecraft_textures` pero sin ventana: solo la copia en CPU
+pub fn load_minecraft_textures_cpu(tex_mgr: &mut TextureManager) -> Result<(), Vec<TextureError>> {
+    insert_procedural_textures(tex_mgr);
+    collect_texture_errors(MINECRAFT_TEXTURES.map(|path| tex_mgr.load_cpu_texture(path)))
+}
+
+/// Registra las texturas que se generan en código (plantas, tronco, TNT y
+/// cofre); no necesitan archivos ni ventana
+pub fn insert_procedural_textures(tex_mgr: &mut TextureManager) {
+    insert_plant_textures(tex_mgr);
+    insert_log_textures(tex_mgr);
+    insert_tnt_textures(tex_mgr);
+    insert_chest_textures(tex_mgr);
+}
 
-    Ok(())
+/// Se intentan todas las texturas y se devuelven todas las que fallaron
+fn collect_texture_errors(
+    results: impl IntoIterator<Item = Result<(), TextureError>>,
+) -> Result<(), Vec<TextureError>> {
+    let errors: Vec<_> = results.into_iter().filter_map(Result::err).collect();
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
 }
 
 /// Crea una isla flotante estilo Minecraft con casa, jardín, árbol y lago
-pub fn create_optimized_scene() -> Vec<Block> {
+pub fn create_optimized_scene() -> Scene {
     let mut blocks = Vec::new();
 
     // === CAPA BASE DE LA ISLA (césped y tierra) ===
@@ -42,7 +430,7 @@ pub fn create_optimized_scene() -> Vec<Block> {
         }
     }
 
-    // === CASA (3x3 con ventanas) ===
+    // === CASA (3x3 con ventanas de panel de vidrio) ===
     let house_x = -2.0;
     let house_z = -2.0;
 
@@ -52,7 +440,7 @@ pub fn create_optimized_scene() -> Vec<Block> {
             let pos = Vector3::new(house_x + x as f32, y as f32, house_z);
 
             // puerta de 2 bloques (x=1, y=1 y y=2 libres)
-            if !(x == 1) {
+            if x != 1 {
                 blocks.push(BlockType::Cobble.to_block(pos, 1.0));
             }
         }
@@ -63,7 +451,11 @@ pub fn create_optimized_scene() -> Vec<Block> {
 
             if x == 1 && y == 2 {
                 // ventana sur
-                blocks.push(BlockType::Glass.to_block(pos, 1.0));
+                blocks.push(
+                    BlockType::GlassPane
+                        .to_block(pos, 1.0)
+                        .with_shape(BlockShape::Pane { axis: Axis::Z }),
+                );
             } else {
                 blocks.push(BlockType::Cobble.to_block(pos, 1.0));
             }
@@ -75,7 +467,11 @@ pub fn create_optimized_scene() -> Vec<Block> {
 
             if z == 1 && y == 2 {
                 // ventana oeste
-                blocks.push(BlockType::Glass.to_block(pos, 1.0));
+                blocks.push(
+                    BlockType::GlassPane
+                        .to_block(pos, 1.0)
+                        .with_shape(BlockShape::Pane { axis: Axis::X }),
+                );
             } else {
                 blocks.push(BlockType::Cobble.to_block(pos, 1.0));
             }
@@ -87,7 +483,11 @@ pub fn create_optimized_scene() -> Vec<Block> {
 
             if z == 1 && y == 2 {
                 // ventana este
-                blocks.push(BlockType::Glass.to_block(pos, 1.0));
+                blocks.push(
+                    BlockType::GlassPane
+                        .to_block(pos, 1.0)
+                        .with_shape(BlockShape::Pane { axis: Axis::X }),
+                );
             } else {
                 blocks.push(BlockType::Cobble.to_block(pos, 1.0));
             }
@@ -106,6 +506,38 @@ pub fn create_optimized_scene() -> Vec<Block> {
 
     blocks.push(BlockType::Cobble.to_block(Vector3::new(-2.0, 4.0, 0.0), 1.0)); // Chimenea
 
+    // Cofre contra la pared del fondo, con el frente hacia la puerta
+    blocks.push(BlockType::Chest.to_block(Vector3::new(house_x + 1.0, 1.0, house_z + 1.0), 1.0));
+
+    // Antorchas: una en la pared del fondo, sobre el cofre (se ve por la
+    // puerta), y otra junto a la puerta, por fuera
+    let wall_torch = BlockShape::Torch(Attachment::South);
+    blocks.push(
+        BlockType::Torch
+            .to_block(Vector3::new(house_x + 1.0, 2.0, house_z + 1.0), 1.0)
+            .with_shape(wall_torch),
+    );
+    blocks.push(
+        BlockType::Torch
+            .to_block(Vector3::new(house_x, 2.0, house_z - 1.0), 1.0)
+            .with_shape(wall_torch),
+    );
+
+    // Escalinata que sube desde afuera de la isla hasta la puerta, en vez
+    // del borde de un bloque de alto
+    for step in 1..=2 {
+        blocks.push(
+            BlockType::CobbleStairs
+                .to_block(
+                    Vector3::new(house_x + 1.0, 1.0 - step as f32, house_z - 1.0 - step as f32),
+                    1.0,
+                )
+                .with_shape(BlockShape::Stair {
+                    facing: Direction::South,
+                }),
+        );
+    }
+
     // === ÁRBOL EN EL JARDÍN ===
     let tree_x = 2.0;
     let tree_z = -1.0;
@@ -132,19 +564,22 @@ pub fn create_optimized_scene() -> Vec<Block> {
     // Coordenadas relativas de un lago 2x2
     let lake_coords = vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
 
-    // Agua (Glass) en el nivel 0 y -1
+    // Agua en el nivel 0 y -1, con fondo de arena en -2
     for (dx, dz) in &lake_coords {
         let lx = lake_center_x + dx;
         let lz = lake_center_z + dz;
 
-        // Superficie del agua
-        replace_block(
-            &mut blocks,
-            BlockType::Reflect.to_block(Vector3::new(lx, 0.0, lz), 1.0),
-        );
+        for y in [0.0, -1.0] {
+            replace_block(
+                &mut blocks,
+                BlockType::Water.to_block(Vector3::new(lx, y, lz), 1.0),
+            );
+        }
+        blocks.push(BlockType::Sand.to_block(Vector3::new(lx, -2.0, lz), 1.0));
     }
 
-    // Arena alrededor (un anillo de 4x4 menos el lago central)
+    // Arena alrededor (un anillo de 4x4 menos el lago central), con una
+    // segunda capa que hace de pared del lago
     for x in -1..=2 {
         for z in -1..=2 {
             let sx = lake_center_x + x as f32;
@@ -159,6 +594,7 @@ pub fn create_optimized_scene() -> Vec<Block> {
                     &mut blocks,
                     BlockType::Sand.to_block(Vector3::new(sx, 0.0, sz), 1.0),
                 );
+                blocks.push(BlockType::Sand.to_block(Vector3::new(sx, -1.0, sz), 1.0));
             }
         }
     }
@@ -205,24 +641,522 @@ pub fn create_optimized_scene() -> Vec<Block> {
         );
     }
 
+    // === FLORES Y PASTO ALTO sobre el césped libre ===
+    let plants = [
+        (BlockType::Flower, -3.0, -3.0),
+        (BlockType::Flower, 1.0, -3.0),
+        (BlockType::Flower, -2.0, 2.0),
+        (BlockType::Flower, -1.0, 1.0),
+        (BlockType::TallGrass, -3.0, -1.0),
+        (BlockType::TallGrass, -3.0, 0.0),
+        (BlockType::TallGrass, 2.0, -3.0),
+        (BlockType::TallGrass, -2.0, 1.0),
+        (BlockType::TallGrass, -2.0, 3.0),
+    ];
+    for (plant, px, pz) in plants {
+        blocks.push(plant.to_block(Vector3::new(px, 1.0, pz), 1.0));
+    }
+
+    // === PEDESTAL con tapa brillante frente a la puerta ===
+    blocks.push(
+        BlockType::Stone
+            .to_block(Vector3::new(0.0, 1.0, -3.0), 1.0)
+            .with_face_material(Face::Top, BlockType::Magma.material()),
+    );
+
+    // === PRISMA junto al lago (dispersa la luz del sol) ===
+    blocks.push(BlockType::Prism.to_block(Vector3::new(3.0, 1.0, 3.0), 1.0));
+
+    // === FARO en la esquina, su haz se ve desde toda la isla ===
+    blocks.push(BlockType::Beacon.to_block(Vector3::new(3.0, 1.0, -3.0), 1.0));
+
+    // === PANZA DE PIEDRA bajo la isla, cada capa más chica, con algunos
+    // minerales que se ven al volar por debajo ===
+    for (y, reach) in [(-1, 2), (-2, 1)] {
+        for x in -reach..=reach {
+            for z in -reach..=reach {
+                let pos = Vector3::new(x as f32, y as f32, z as f32);
+                if block_at(&blocks, pos).is_none() {
+                    blocks.push(BlockType::Stone.to_block(pos, 1.0));
+                }
+            }
+        }
+    }
+    let ores = [
+        (BlockType::RedstoneOre, -2.0, -1.0, -2.0),
+        (BlockType::DiamondOre, 2.0, -1.0, -1.0),
+        (BlockType::DiamondOre, -2.0, -1.0, 1.0),
+        (BlockType::RedstoneOre, 1.0, -2.0, 0.0),
+        (BlockType::DiamondOre, -1.0, -2.0, -1.0),
+    ];
+    for (ore, ox, oy, oz) in ores {
+        replace_block(&mut blocks, ore.to_block(Vector3::new(ox, oy, oz), 1.0));
+    }
+
+    // === CERCA del jardín: cierra el lado del lago y el de las piedras ===
+    for (fx, fz) in [(0.0, 1.0), (0.0, 2.0), (0.0, 3.0), (0.0, 4.0), (-1.0, 4.0)] {
+        blocks.push(BlockType::WoodFence.to_block(Vector3::new(fx, 1.0, fz), 1.0));
+    }
+
+    neighbors_changed(&mut blocks);
+
     // === SOL EMISIVO (fuente de luz visual) ===
-    blocks.push(BlockType::Sun.to_block(Vector3::new(8.0, 10.0, -8.0), 2.0));
+    // Esfera en vez de cubo para que el brillo se vea redondo
+    let sun = Sphere::new(Vector3::new(8.0, 10.0, -8.0), 1.0, BlockType::Sun.material())
+        .with_emission(Vector3::new(1.0, 0.9, 0.9), 8.0);
+
+    info!("Isla flotante creada con {} bloques", blocks.len());
+    debug!("- Casa: 3x3 con ventanas, techo, antorchas y escalinata");
+    debug!("- Árbol: 3 bloques de altura con copa");
+    debug!("- Flores y pasto alto sobre el césped");
+    debug!("- Lago: 2x2 de agua, 2 de profundidad, con arena alrededor");
+    debug!("- Base: isla flotante cónica");
+    debug!("- Sol: esfera emisiva");
+
+    let mut scene = Scene::new(blocks).with_primitives(vec![Primitive::Sphere(sun)]);
+    // La puerta también deja entrar el cielo
+    scene.portal_cells = vec![
+        Vector3::new(house_x + 1.0, 1.0, house_z),
+        Vector3::new(house_x + 1.0, 2.0, house_z),
+    ];
+    scene
+}
 
-    println!("Isla flotante creada con {} bloques", blocks.len());
-    println!("- Casa: 3x3 con ventanas y techo");
-    println!("- Árbol: 3 bloques de altura con copa");
-    println!("- Lago: 3x3 con arena alrededor");
-    println!("- Base: isla flotante cónica");
+/// Galería de materiales: cada tipo de bloque sobre un piso de piedra,
+/// iluminado por un sol encima de la fila
+pub fn create_material_gallery() -> Vec<Block> {
+    let mut blocks = Vec::new();
 
+    for x in -7..=7 {
+        for z in -2..=2 {
+            blocks.push(BlockType::Stone.to_block(Vector3::new(x as f32, 0.0, z as f32), 1.0));
+        }
+    }
+
+    let exhibits = [
+        BlockType::Grass,
+        BlockType::Dirt,
+        BlockType::Cobble,
+        BlockType::WoodLog,
+        BlockType::Leaves,
+        BlockType::CherryLeaves,
+        BlockType::Sand,
+        BlockType::Glass,
+        BlockType::Reflect,
+        BlockType::Water,
+        BlockType::Magma,
+        BlockType::Prism,
+        BlockType::WarpedGlass,
+    ];
+    let start = -(exhibits.len() as i32 / 2);
+    for (i, block_type) in exhibits.iter().enumerate() {
+        let x = (start + i as i32) as f32;
+        blocks.push(block_type.to_block(Vector3::new(x, 1.0, 0.0), 1.0));
+    }
+
+    blocks.push(BlockType::Sun.to_block(Vector3::new(0.0, 6.0, -3.0), 1.0));
+
+    // Escalera de losas al fondo: cada escalón sube media celda, así las
+    // celdas impares llevan una losa abajo y otra arriba
+    for step in 0..4 {
+        let x = 3.0 + step as f32;
+        for half in 0..=step {
+            let position = Vector3::new(x, 1.0 + (half / 2) as f32, 2.0);
+            let block_type = if half == step {
+                BlockType::WoodPlankSlab
+            } else {
+                BlockType::StoneSlab
+            };
+            let shape = BlockShape::Slab { top: half % 2 == 1 };
+            blocks.push(block_type.to_block(position, 1.0).with_shape(shape));
+        }
+    }
+
+    // Pileta de lava hundida en el piso, delante de la fila: de noche
+    // ilumina la piedra de alrededor
+    for x in -1..=1 {
+        replace_block(
+            &mut blocks,
+            BlockType::Lava.to_block(Vector3::new(x as f32, 0.0, -1.0), 1.0),
+        );
+    }
+
+    mark_interior_faces(&mut blocks);
     blocks
 }
 
-pub fn replace_block(blocks: &mut Vec<Block>, new_block: Block) {
-    let pos = new_block.position;
+/// Capas de nieve acumuladas contra las paredes de piedra
+const DRIFT_LAYERS: u8 = 3;
+
+/// La isla en invierno: el borde del lago se congeló, las plantas quedaron
+/// bajo una capa de nieve y contra las paredes de la casa se juntó más
+pub fn create_winter_scene() -> Scene {
+    let island = create_optimized_scene();
+    let mut blocks = island.blocks.clone();
+    blocks.retain(|b| !matches!(b.shape, BlockShape::CrossPlant(_)));
+    for x in [1.0, 2.0] {
+        replace_block(
+            &mut blocks,
+            BlockType::Ice.to_block(Vector3::new(x, 0.0, 2.0), 1.0),
+        );
+    }
+    let snow = snow_cover(&blocks);
+    blocks.extend(snow);
+    neighbors_changed(&mut blocks);
+    island.with_blocks(blocks)
+}
+
+/// Nieve sobre el bloque más alto de cada columna, si es un cubo opaco y
+/// la celda de arriba está libre. Al lado de una pared de piedra se junta
+/// más: `DRIFT_LAYERS` capas.
+fn snow_cover(blocks: &[Block]) -> Vec<Block> {
+    let occupied: HashMap<(i32, i32, i32), &Block> =
+        blocks.iter().map(|b| (grid_key(b.position), b)).collect();
+    let is_wall = |key: (i32, i32, i32)| {
+        occupied
+            .get(&key)
+            .is_some_and(|b| BlockType::identify(b) == Some(BlockType::Cobble))
+    };
+
+    let mut tops: HashMap<(i32, i32), &Block> = HashMap::new();
+    for block in blocks {
+        let solid = block.shape.is_full()
+            && block.material.transparency == 0.0
+            && block.emission.is_none();
+        if !solid {
+            continue;
+        }
+        let (x, _, z) = grid_key(block.position);
+        let top = tops.entry((x, z)).or_insert(block);
+        if block.position.y > top.position.y {
+            *top = block;
+        }
+    }
+
+    let mut snow = Vec::new();
+    for top in tops.values() {
+        let position = top.position + Vector3::new(0.0, top.extents.y, 0.0);
+        let key = grid_key(position);
+        if occupied.contains_key(&key) {
+            continue;
+        }
+        let (x, y, z) = key;
+        let against_wall = [(2, 0), (-2, 0), (0, 2), (0, -2)]
+            .iter()
+            .any(|(dx, dz)| is_wall((x + dx, y, z + dz)));
+        let layers = if against_wall { DRIFT_LAYERS } else { 1 };
+        snow.push(
+            BlockType::Snow
+                .to_block(position, top.extents.y)
+                .with_shape(BlockShape::SnowLayer { layers }),
+        );
+    }
+    // Orden fijo: el de un HashMap cambia entre ejecuciones
+    snow.sort_by_key(|b| grid_key(b.position));
+    snow
+}
+
+/// Muestra de primitivas: bloques de la grilla sobre un plano infinito, una
+/// esfera de vidrio, una de espejo, una caja suelta y un sol esférico
+pub fn create_primitive_showcase() -> Scene {
+    let mut blocks = Vec::new();
+    for x in -1..=1 {
+        for z in -1..=1 {
+            blocks.push(BlockType::Cobble.to_block(Vector3::new(x as f32, 0.0, z as f32), 1.0));
+        }
+    }
+    blocks.push(BlockType::Glass.to_block(Vector3::new(-1.0, 1.0, 1.0), 1.0));
+    mark_interior_faces(&mut blocks);
+
+    let sun = BlockType::Sun.material();
+    let primitives = vec![
+        Primitive::Plane(Plane::new(
+            Vector3::new(0.0, -0.5, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            BlockType::Sand.material(),
+        )),
+        Primitive::Sphere(Sphere::new(
+            Vector3::new(0.0, 1.3, 0.0),
+            0.8,
+            BlockType::Glass.material(),
+        )),
+        Primitive::Sphere(Sphere::new(
+            Vector3::new(2.5, 0.5, 1.0),
+            1.0,
+            BlockType::Reflect.material(),
+        )),
+        Primitive::Block(BlockType::Magma.to_block(Vector3::new(-2.6, 0.25, -1.3), 1.5)),
+        Primitive::Sphere(
+            Sphere::new(Vector3::new(4.0, 7.0, -5.0), 1.2, sun)
+                .with_emission(Vector3::new(1.0, 0.9, 0.9), 8.0),
+        ),
+    ];
+
+    Scene::new(blocks).with_primitives(primitives)
+}
+
+/// Luces fijas de la escena, que no pertenecen a ningún bloque
+pub fn default_lights() -> Vec<Light> {
+    vec![
+        Light::new(
+            Vector3::new(-5.0, 6.0, 5.0), // Luz secundaria
+            Vector3::new(0.6, 0.7, 1.0),  // Fría/azulada
+            3.0,
+        ),
+        Light::new(
+            Vector3::new(0.0, 6.0, 0.0), // Luz cenital
+            Vector3::new(1.0, 1.0, 0.9), // Blanca suave
+            2.6,
+        ),
+    ]
+}
+
+/// Recolecta las luces de los bloques emisivos (en el orden de la escena),
+/// luego las de las primitivas emisivas y al final las luces fijas
+pub fn collect_lights(scene: &Scene, fixed_lights: &[Light]) -> Vec<Light> {
+    let mut lights = Vec::new();
+    for block in scene.iter() {
+        if let Some(light) = &block.emission {
+            lights.push(*light);
+        }
+    }
+    lights.extend(scene.primitive_lights());
+    lights.extend_from_slice(fixed_lights);
+    lights
+}
+
+/// Escenas disponibles desde el menú de pausa
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneKind {
+    Island,
+    Winter,
+    Gallery,
+    Primitives,
+    /// La última escena guardada en `SCENE_PATH`
+    Saved,
+}
+
+impl SceneKind {
+    pub fn next(self) -> Self {
+        match self {
+            SceneKind::Island => SceneKind::Winter,
+            SceneKind::Winter => SceneKind::Gallery,
+            SceneKind::Gallery => SceneKind::Primitives,
+            SceneKind::Primitives => SceneKind::Saved,
+            SceneKind::Saved => SceneKind::Island,
+        }
+    }
+
+    /// Escena de un nombre de `--scene`; un archivo cuenta como la guardada
+    pub fn from_name(name: &str) -> Self {
+        match parse_scene(name) {
+            SceneSource::Builtin(kind) => kind,
+            SceneSource::File(_) => SceneKind::Saved,
+        }
+    }
+
+    /// Nombre con el que la acepta `--scene` (la guardada, por su archivo)
+    pub fn name(self) -> &'static str {
+        match self {
+            SceneKind::Island => "island",
+            SceneKind::Winter => "winter",
+            SceneKind::Gallery => "gallery",
+            SceneKind::Primitives => "primitives",
+            SceneKind::Saved => SCENE_PATH,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SceneKind::Island => "Isla flotante",
+            SceneKind::Winter => "Isla en invierno",
+            SceneKind::Gallery => "Galería de materiales",
+            SceneKind::Primitives => "Planos y esferas",
+            SceneKind::Saved => "Escena guardada",
+        }
+    }
+
+    /// Construye la escena (la guardada puede fallar al leerse)
+    pub fn build(self) -> Result<Scene, SceneError> {
+        match self {
+            SceneKind::Island => Ok(create_optimized_scene()),
+            SceneKind::Winter => Ok(create_winter_scene()),
+            SceneKind::Gallery => Ok(create_material_gallery().into()),
+            SceneKind::Primitives => Ok(create_primitive_showcase()),
+            SceneKind::Saved => load_scene(SCENE_PATH),
+        }
+    }
+}
+
+/// Animaciones de la isla: un magma junto al lago late entre caliente y
+/// frío. En cualquier escena la lava palpita, cada bloque a su ritmo.
+pub fn create_scene_animations(blocks: &[Block]) -> Vec<MaterialAnimation> {
+    let mut animations = Vec::new();
+
+    let hot = BlockType::Magma.material();
+    let mut cold = hot.clone();
+    cold.diffuse = Vector3::new(0.35, 0.12, 0.05);
+    cold.emission_strength = 0.3;
+
+    let pulse_pos = Vector3::new(0.0, 0.0, 3.0);
+    if let Some(index) = blocks.iter().position(|b| b.position == pulse_pos) {
+        animations.push(MaterialAnimation::new(index, cold, hot, 3.0));
+    }
+
+    let bright = BlockType::Lava.material();
+    let mut dim = bright.clone();
+    dim.emission_strength = 1.6;
+    for (index, block) in blocks.iter().enumerate() {
+        if BlockType::identify(block) == Some(BlockType::Lava) {
+            let period = 2.2 + 0.45 * (index % 4) as f32;
+            animations.push(MaterialAnimation::new(index, dim.clone(), bright.clone(), period));
+        }
+    }
+
+    animations
+}
+
+/// Clave de grilla para buscar vecinos (admite posiciones en medios bloques).
+/// Un bloque más chico que una celda cae en la de su centro.
+fn grid_key(pos: Vector3) -> (i32, i32, i32) {
+    (
+        (pos.x * 2.0).round() as i32,
+        (pos.y * 2.0).round() as i32,
+        (pos.z * 2.0).round() as i32,
+    )
+}
+
+/// Caras de un bloque con su normal
+fn face_normals() -> [(Face, Vector3); 6] {
+    [
+        (Face::East, Vector3::new(1.0, 0.0, 0.0)),
+        (Face::West, Vector3::new(-1.0, 0.0, 0.0)),
+        (Face::Top, Vector3::new(0.0, 1.0, 0.0)),
+        (Face::Bottom, Vector3::new(0.0, -1.0, 0.0)),
+        (Face::South, Vector3::new(0.0, 0.0, 1.0)),
+        (Face::North, Vector3::new(0.0, 0.0, -1.0)),
+    ]
+}
+
+/// Solo los cubos y los paneles tapan una cara vecina entera
+fn covers_face(shape: &BlockShape) -> bool {
+    matches!(shape, BlockShape::Cube | BlockShape::Pane { .. })
+}
 
-    // Quitar cualquier bloque existente en esa posición
-    blocks.retain(|b| b.position != pos);
+/// Marca las caras compartidas entre cubos vecinos (o paneles paralelos que
+/// se continúan) del mismo material transparente, para que el rayo no
+/// refracte dos veces dentro del vidrio: una fila de paneles se comporta
+/// como un solo panel largo.
+/// En los líquidos (materiales con absorción) también se marcan las caras
+/// pegadas a un bloque opaco: desde adentro el rayo llega directo al fondo.
+pub fn mark_interior_faces(blocks: &mut [Block]) {
+    let index_by_pos: HashMap<(i32, i32, i32), usize> = blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| covers_face(&b.shape))
+        .map(|(i, b)| (grid_key(b.position), i))
+        .collect();
+
+    for i in 0..blocks.len() {
+        let mask = interior_mask(&blocks[i], |key| {
+            index_by_pos.get(&key).map(|&j| &blocks[j])
+        });
+        blocks[i].interior_faces = mask;
+    }
+}
+
+/// Caras interiores de `block` (ver `mark_interior_faces`); `cover_at` da
+/// el bloque que tapa cada celda vecina, si hay
+fn interior_mask<'a>(block: &Block, cover_at: impl Fn((i32, i32, i32)) -> Option<&'a Block>) -> u8 {
+    let mut mask = 0u8;
+    if block.material.transparency > 0.01 {
+        for (face, normal) in face_normals() {
+            let neighbor_pos = block.position + normal * block.extents;
+            if let Some(neighbor) = cover_at(grid_key(neighbor_pos)) {
+                let liquid_against_solid = block.material.absorption != Vector3::zero()
+                    && neighbor.material.transparency <= 0.01;
+                if neighbor.extents == block.extents
+                    && block.shape.joins(neighbor.shape, &normal)
+                    && (neighbor.material.same_medium(&block.material) || liquid_against_solid)
+                {
+                    mask |= 1 << face.index();
+                }
+            }
+        }
+    }
+    mask
+}
+
+/// Une cada cerca con las cercas vecinas de la misma medida: un travesaño
+/// por cada lado que tiene una al lado
+pub fn connect_fences(blocks: &mut [Block]) {
+    let fences: HashMap<(i32, i32, i32), Vector3> = blocks
+        .iter()
+        .filter(|b| matches!(b.shape, BlockShape::Fence { .. }))
+        .map(|b| (grid_key(b.position), b.extents))
+        .collect();
+    if fences.is_empty() {
+        return;
+    }
+
+    for block in blocks.iter_mut() {
+        if let Some(rails) = fence_rails(block, |key| fences.get(&key).copied())
+            && let BlockShape::Fence { rails: current } = &mut block.shape
+        {
+            *current = rails;
+        }
+    }
+}
+
+/// Travesaños de una cerca (ver `connect_fences`); `fence_at` da la medida
+/// de la cerca de cada celda vecina, si hay. None si no es una cerca.
+fn fence_rails(block: &Block, fence_at: impl Fn((i32, i32, i32)) -> Option<Vector3>) -> Option<u8> {
+    if !matches!(block.shape, BlockShape::Fence { .. }) {
+        return None;
+    }
+    let mut rails = 0;
+    for (i, direction) in Direction::ALL.into_iter().enumerate() {
+        let neighbor = block.position + direction.vector() * block.extents;
+        if fence_at(grid_key(neighbor)) == Some(block.extents) {
+            rails |= 1 << i;
+        }
+    }
+    Some(rails)
+}
+
+/// Gancho para después de agregar o quitar bloques: recalcula todo lo que
+/// depende de los vecinos (caras interiores y travesaños de las cercas)
+pub fn neighbors_changed(blocks: &mut [Block]) {
+    connect_fences(blocks);
+    mark_interior_faces(blocks);
+}
+
+/// Índice del bloque que ocupa la celda de `position`, si hay alguno
+pub fn block_at(blocks: &[Block], position: Vector3) -> Option<usize> {
+    let key = grid_key(position);
+    blocks.iter().position(|b| grid_key(b.position) == key)
+}
+
+pub fn replace_block(blocks: &mut Vec<Block>, new_block: Block) {
+    // Quitar los bloques de esa celda que ocupan el mismo lugar
+    blocks.retain(|b| !collides(b, &new_block));
 
     // Insertar el nuevo
     blocks.push(new_block);
 }
+
+/// Si dos bloques están en la misma celda y sus formas se pisan
+fn collides(a: &Block, b: &Block) -> bool {
+    grid_key(a.position) == grid_key(b.position) && a.shape.overlaps(b.shape)
+}
+
+/// Caja de un bloque para la BVH: la de su forma, estirada hasta su
+/// posición para que `Scene::blocks_with_key` lo encuentre por su celda
+fn block_box(block: &Block) -> Aabb {
+    let (min, max) = block.bounds();
+    let p = block.position;
+    (
+        Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+        Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+    )
+}
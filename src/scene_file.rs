@@ -0,0 +1,1071 @@
+// scene_file.rs - Guardado y carga de escenas en texto plano
+//
+// Formato (una directiva por línea, `#` inicia un comentario):
+//   block <x> <y> <z> <tamaño> <material>  (tamaño: el lado o `x,y,z` por eje)
+//   block <x> <y> <z> <tamaño> <nombre>  (bloque de un tipo incorporado o propio)
+//   blockdef <nombre> <material>         (tipo de bloque propio: las directivas
+//                                        siguientes completan su modelo)
+//   box <x> <y> <z> <tamaño> <material>       (caja suelta, fuera de la grilla)
+//   plane <x,y,z> <nx,ny,nz> <escala uv> <material>
+//   sphere <x> <y> <z> <radio> <material>
+//   shape torch <floor|north|south|east|west> | shape slab <top|bottom>
+//   shape stair <north|south|east|west>  (hacia donde sube la escalera)
+//   shape plant <flower|tall_grass> | shape pane <x|z>  (eje perpendicular)
+//   shape snow <capas 1-8> | shape fence  (los travesaños salen de los vecinos)
+//   shape chest <open|closed>            (frente al norte; `orient` lo gira)
+//                                       (forma del bloque o caja anterior)
+//   orient <east|west|top|bottom|south|north> <cuartos de vuelta 0-3>
+//                                       (hacia dónde apunta el arriba del bloque
+//                                        o caja anterior y su giro en ese eje)
+//   emission <r> <g> <b> <intensidad> [dx,dy,dz] [atenuación]
+//                                       (luz del bloque, caja o esfera anterior,
+//                                        opcionalmente desplazada de su centro)
+//   spot <dx,dy,dz> <ángulo>             (cono de la luz anterior, en radianes)
+//   medium <absorción r,g,b> <olas>      (medio del material del objeto anterior)
+//   surface <aspereza> <escarcha>        (superficie del material del objeto anterior)
+//   specks <r,g,b> <brillo>              (vetas de mineral del material del objeto anterior)
+//   warp <aberración> <distorsión>       (refracción deformada del material del objeto anterior)
+//   caustics                             (el material del objeto anterior proyecta cáusticas)
+//   fresnel                              (el material del objeto anterior refleja según
+//                                        Fresnel aunque no sea reflectivo)
+//   face <cara 0-5> <material>          (override del bloque o caja anterior)
+//   fog <densidad> <inicio> <r,g,b>      (niebla de la escena; `-` como color usa
+//                                        el horizonte del cielo)
+//   sun <dx,dy,dz>                       (sol fijo de la escena, en vez de la hora)
+//   portal <x> <y> <z>                   (celda abierta por la que entra la luz
+//                                        del cielo: puerta o hueco sin vidrio)
+//
+// Las definiciones (`blockdef`) van al principio, así los bloques que usan
+// el nombre ya lo encuentran. `blocks.txt` usa el mismo formato, solo con
+// definiciones.
+//
+// El material se escribe como campos separados por espacios en el orden de
+// `material_to_line`; los vectores van como `r,g,b` y `-` indica None.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use raylib::prelude::*;
+
+use crate::block::{Block, Face};
+use crate::block_registry::{BlockDefinition, BlockRegistry};
+use crate::block_shape::{Attachment, Axis, BlockShape, Direction, MAX_SNOW_LAYERS};
+use crate::block_types::{BlockType, normalize_name};
+use crate::fog::Fog;
+use crate::light::{Light, Spot};
+use crate::material::{Material, MaterialWarning};
+use crate::ore::Specks;
+use crate::orientation::Orientation;
+use crate::plant::Plant;
+use crate::primitive::{Plane, Primitive, Sphere};
+use crate::scene::{Scene, neighbors_changed};
+
+/// Archivo donde el menú de pausa guarda la escena
+pub const SCENE_PATH: &str = "scene.txt";
+
+/// Cantidad de campos que ocupa un material en una línea
+const MATERIAL_FIELDS: usize = 14;
+
+/// Por qué no se pudo leer o guardar una escena
+#[derive(Debug)]
+pub enum SceneError {
+    /// No se pudo leer o escribir el archivo
+    Io { path: PathBuf, source: io::Error },
+    /// Línea con una directiva o un valor inválido (numeradas desde 1)
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SceneError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            SceneError::Parse { .. } => None,
+        }
+    }
+}
+
+/// Advertencia de `Material::validate` en el material de una entrada del
+/// archivo (un `blockdef`, un bloque o primitiva con material propio, o una
+/// cara con `face`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterialIssue {
+    /// Línea de la entrada (numeradas desde 1)
+    pub line: usize,
+    /// La entrada como se lee en el archivo, ej: `blockdef cristal`
+    pub entry: String,
+    pub warning: MaterialWarning,
+}
+
+impl fmt::Display for MaterialIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: material de '{}': {}",
+            self.line, self.entry, self.warning
+        )
+    }
+}
+
+/// Guarda los tipos propios, los bloques y las primitivas de la escena
+pub fn save_scene<P: AsRef<Path>>(path: P, scene: &Scene) -> Result<(), SceneError> {
+    let mut text = String::from("# Escena del raytracer\n");
+    if let Some(fog) = &scene.fog {
+        text.push_str(&format!(
+            "fog {} {} {}\n",
+            fog.density,
+            fog.start,
+            fog.color.map_or("-".to_string(), vec3_to_field)
+        ));
+    }
+    if let Some(sun) = scene.sun {
+        text.push_str(&format!("sun {}\n", vec3_to_field(sun)));
+    }
+    for cell in &scene.portal_cells {
+        text.push_str(&format!("portal {} {} {}\n", cell.x, cell.y, cell.z));
+    }
+    for definition in &scene.palette {
+        text.push_str(&format!(
+            "blockdef {} {}\n",
+            definition.name,
+            material_to_line(&definition.template.material)
+        ));
+        push_block_details(&mut text, &definition.template);
+    }
+    for block in scene.iter() {
+        push_block(&mut text, "block", block);
+    }
+    for primitive in scene.primitives() {
+        match primitive {
+            Primitive::Block(block) => push_block(&mut text, "box", block),
+            Primitive::Plane(plane) => {
+                text.push_str(&format!(
+                    "plane {} {} {} {}\n",
+                    vec3_to_field(plane.point),
+                    vec3_to_field(plane.normal),
+                    plane.uv_scale,
+                    material_to_line(&plane.material)
+                ));
+                push_medium(&mut text, &plane.material);
+            }
+            Primitive::Sphere(sphere) => {
+                text.push_str(&format!(
+                    "sphere {} {} {} {} {}\n",
+                    sphere.center.x,
+                    sphere.center.y,
+                    sphere.center.z,
+                    sphere.radius,
+                    material_to_line(&sphere.material)
+                ));
+                push_medium(&mut text, &sphere.material);
+                push_emission(&mut text, sphere.emission.as_ref(), sphere.center);
+            }
+        }
+    }
+    fs::write(path.as_ref(), text).map_err(|source| SceneError::Io {
+        path: path.as_ref().to_path_buf(),
+        source,
+    })
+}
+
+/// Línea `block`/`box` con su emisión y las caras con otro material
+fn push_block(text: &mut String, directive: &str, block: &Block) {
+    text.push_str(&format!(
+        "{} {} {} {} {} {}\n",
+        directive,
+        block.position.x,
+        block.position.y,
+        block.position.z,
+        extents_to_field(block.extents),
+        material_to_line(&block.material)
+    ));
+    push_block_details(text, block);
+}
+
+/// Lo que sigue a la línea de un bloque: medio, forma, orientación, luz y
+/// caras con otro material
+fn push_block_details(text: &mut String, block: &Block) {
+    push_medium(text, &block.material);
+    match block.shape {
+        BlockShape::Cube => {}
+        BlockShape::Torch(attachment) => {
+            text.push_str(&format!("shape torch {}\n", attachment.name()));
+        }
+        BlockShape::Slab { top } => {
+            text.push_str(&format!("shape slab {}\n", if top { "top" } else { "bottom" }));
+        }
+        BlockShape::Stair { facing } => {
+            text.push_str(&format!("shape stair {}\n", facing.name()));
+        }
+        BlockShape::CrossPlant(plant) => {
+            text.push_str(&format!("shape plant {}\n", plant.name()));
+        }
+        BlockShape::Pane { axis } => {
+            text.push_str(&format!("shape pane {}\n", axis.name()));
+        }
+        BlockShape::SnowLayer { layers } => {
+            text.push_str(&format!("shape snow {}\n", layers));
+        }
+        // Los travesaños se recalculan al cargar
+        BlockShape::Fence { .. } => text.push_str("shape fence\n"),
+        BlockShape::Chest { open } => {
+            text.push_str(&format!("shape chest {}\n", if open { "open" } else { "closed" }));
+        }
+    }
+    if !block.orientation.is_upright() {
+        text.push_str(&format!(
+            "orient {} {}\n",
+            block.orientation.up.name(),
+            block.orientation.turns
+        ));
+    }
+    push_emission(text, block.emission.as_ref(), block.position);
+    if let Some(faces) = &block.face_overrides {
+        for (face, material) in faces.iter().enumerate() {
+            if let Some(material) = material {
+                text.push_str(&format!("face {} {}\n", face, material_to_line(material)));
+            }
+        }
+    }
+}
+
+/// `center` es la posición del objeto: solo se escribe el desplazamiento de
+/// la luz si no está en el centro (ej: lava) o si hace falta para llegar a
+/// la atenuación propia
+fn push_emission(text: &mut String, light: Option<&Light>, center: Vector3) {
+    if let Some(light) = light {
+        text.push_str(&format!(
+            "emission {} {} {} {}",
+            light.color.x, light.color.y, light.color.z, light.intensity
+        ));
+        if light.position != center || light.attenuation.is_some() {
+            text.push_str(&format!(" {}", vec3_to_field(light.position - center)));
+        }
+        if let Some(attenuation) = light.attenuation {
+            text.push_str(&format!(" {}", attenuation));
+        }
+        text.push('\n');
+        if let Some(spot) = light.spot {
+            text.push_str(&format!("spot {} {}\n", vec3_to_field(spot.direction), spot.angle));
+        }
+    }
+}
+
+/// Absorción y olas (y aspereza y escarcha, vetas, deformación de la
+/// refracción y cáusticas) van en su propia línea, así las escenas guardadas antes de que existieran siguen cargando
+fn push_medium(text: &mut String, material: &Material) {
+    if material.absorption != Vector3::zero() || material.waves != 0.0 {
+        text.push_str(&format!(
+            "medium {} {}\n",
+            vec3_to_field(material.absorption),
+            material.waves
+        ));
+    }
+    if material.roughness != 0.0 || material.frost != 0.0 {
+        text.push_str(&format!("surface {} {}\n", material.roughness, material.frost));
+    }
+    if let Some(specks) = material.specks {
+        text.push_str(&format!("specks {} {}\n", vec3_to_field(specks.color), specks.glow));
+    }
+    if material.aberration_strength != 0.0 || material.distortion_strength != 0.0 {
+        text.push_str(&format!(
+            "warp {} {}\n",
+            material.aberration_strength, material.distortion_strength
+        ));
+    }
+    if material.caustics {
+        text.push_str("caustics\n");
+    }
+    if material.fresnel_reflection {
+        text.push_str("fresnel\n");
+    }
+}
+
+/// Escena a medio leer: los bloques y las primitivas se juntan aparte y la
+/// `Scene` los recibe al final, con sus vecinos ya calculados
+#[derive(Default)]
+struct Draft {
+    blocks: Vec<Block>,
+    primitives: Vec<Primitive>,
+    scene: Scene,
+}
+
+/// Objeto al que se aplican las directivas `emission`, `medium`, `surface`,
+/// `specks`, `warp`, `caustics`, `fresnel`, `shape`, `orient` y `face`
+#[derive(Clone, Copy)]
+enum LastObject {
+    Block(usize),
+    Primitive(usize),
+    /// Modelo de un tipo propio de la paleta
+    Definition(usize),
+}
+
+/// Posición y luz del último objeto que puede emitir (bloque, caja, esfera
+/// o modelo de un tipo propio)
+fn last_emission<'a>(
+    draft: &'a mut Draft,
+    last: Option<LastObject>,
+    directive: &str,
+) -> Result<(Vector3, &'a mut Option<Light>), String> {
+    match last {
+        Some(LastObject::Block(index)) => {
+            let block = &mut draft.blocks[index];
+            Ok((block.position, &mut block.emission))
+        }
+        Some(LastObject::Definition(index)) => {
+            let block = &mut draft.scene.palette[index].template;
+            Ok((block.position, &mut block.emission))
+        }
+        Some(LastObject::Primitive(index)) => match &mut draft.primitives[index] {
+            Primitive::Block(block) => Ok((block.position, &mut block.emission)),
+            Primitive::Sphere(sphere) => Ok((sphere.center, &mut sphere.emission)),
+            Primitive::Plane(_) => Err("un plano no puede emitir luz".to_string()),
+        },
+        None => Err(format!("'{}' sin objeto previo", directive)),
+    }
+}
+
+/// Bloque del último objeto (de la grilla, caja suelta o modelo de un tipo
+/// propio), o el error de `directive` si no hay o no es un bloque
+fn last_block<'a>(
+    draft: &'a mut Draft,
+    last: Option<LastObject>,
+    directive: &str,
+) -> Result<&'a mut Block, String> {
+    match last {
+        Some(LastObject::Block(index)) => Ok(&mut draft.blocks[index]),
+        Some(LastObject::Definition(index)) => Ok(&mut draft.scene.palette[index].template),
+        Some(LastObject::Primitive(index)) => match &mut draft.primitives[index] {
+            Primitive::Block(block) => Ok(block),
+            _ => Err(format!("'{}' solo aplica a bloques y cajas", directive)),
+        },
+        None => Err(format!("'{}' sin bloque previo", directive)),
+    }
+}
+
+/// Material de una entrada, para validarlo cuando el archivo terminó de
+/// leerse (`medium`, `surface`... lo completan en las líneas siguientes)
+#[derive(Clone, Copy)]
+enum MaterialSlot {
+    Object(LastObject),
+    Face(LastObject, usize),
+}
+
+/// Material de la entrada `slot` en la escena ya leída
+fn slot_material(draft: &Draft, slot: MaterialSlot) -> Option<&Material> {
+    let (object, face) = match slot {
+        MaterialSlot::Object(object) => (object, None),
+        MaterialSlot::Face(object, face) => (object, Some(face)),
+    };
+    let block = match object {
+        LastObject::Block(index) => &draft.blocks[index],
+        LastObject::Definition(index) => &draft.scene.palette[index].template,
+        LastObject::Primitive(index) => match &draft.primitives[index] {
+            Primitive::Block(block) => block,
+            Primitive::Plane(plane) => return face.is_none().then_some(&plane.material),
+            Primitive::Sphere(sphere) => return face.is_none().then_some(&sphere.material),
+        },
+    };
+    match face {
+        None => Some(&block.material),
+        Some(face) => block.face_overrides.as_ref()?[face].as_ref(),
+    }
+}
+
+/// Material principal del último objeto leído
+fn last_material(draft: &mut Draft, last: Option<LastObject>) -> Option<&mut Material> {
+    Some(match last? {
+        LastObject::Block(index) => &mut draft.blocks[index].material,
+        LastObject::Definition(index) => &mut draft.scene.palette[index].template.material,
+        LastObject::Primitive(index) => match &mut draft.primitives[index] {
+            Primitive::Block(block) => &mut block.material,
+            Primitive::Plane(plane) => &mut plane.material,
+            Primitive::Sphere(sphere) => &mut sphere.material,
+        },
+    })
+}
+
+/// Carga una escena guardada con `save_scene`. Los errores indican la línea.
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+    load_scene_with(path, &BlockRegistry::new())
+}
+
+/// Registra los tipos propios de un archivo como `blocks.txt` (las
+/// directivas que no son definiciones se leen pero se ignoran). Devuelve
+/// cuántos se registraron.
+pub fn load_block_definitions<P: AsRef<Path>>(
+    path: P,
+    registry: &mut BlockRegistry,
+) -> Result<usize, SceneError> {
+    let scene = load_scene_with(path, registry)?;
+    for definition in &scene.palette {
+        // `blockdef` ya rechaza los nombres incorporados
+        let _ = registry.register(definition.clone());
+    }
+    Ok(scene.palette.len())
+}
+
+/// Como `load_scene`, resolviendo los bloques por nombre con `registry`
+/// además de la paleta del archivo. Los materiales dudosos se avisan en el
+/// log con su línea (ver `load_scene_checked`).
+pub fn load_scene_with<P: AsRef<Path>>(
+    path: P,
+    registry: &BlockRegistry,
+) -> Result<Scene, SceneError> {
+    let (scene, issues) = load_scene_checked(path.as_ref(), registry)?;
+    for issue in issues {
+        warn!("{}:{}", path.as_ref().display(), issue);
+    }
+    Ok(scene)
+}
+
+/// Como `load_scene_with`, devolviendo las advertencias de material de cada
+/// entrada en vez de registrarlas (`--strict` las convierte en error)
+pub fn load_scene_checked<P: AsRef<Path>>(
+    path: P,
+    registry: &BlockRegistry,
+) -> Result<(Scene, Vec<MaterialIssue>), SceneError> {
+    let text = fs::read_to_string(path.as_ref()).map_err(|source| SceneError::Io {
+        path: path.as_ref().to_path_buf(),
+        source,
+    })?;
+
+    let mut draft = Draft::default();
+    let mut last: Option<LastObject> = None;
+    // Entradas con material propio: línea, cómo se llaman y dónde quedó
+    let mut material_entries: Vec<(usize, String, MaterialSlot)> = Vec::new();
+    let mut last_entry = String::new();
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut tokens = line.split_whitespace();
+        let Some(directive) = tokens.next() else {
+            continue;
+        };
+        let fields: Vec<&str> = tokens.collect();
+        let error = |message: String| SceneError::Parse {
+            path: path.as_ref().to_path_buf(),
+            line: line_number,
+            message,
+        };
+
+        match directive {
+            "block" if fields.len() == 5 => {
+                let numbers = parse_floats(&fields[..3]).map_err(error)?;
+                let position = Vector3::new(numbers[0], numbers[1], numbers[2]);
+                let extents = extents_from_field(fields[3]).map_err(error)?;
+                let name = fields[4];
+                // La paleta del archivo pisa al registro
+                let wanted = normalize_name(name);
+                let definition = draft
+                    .scene
+                    .palette
+                    .iter()
+                    .find(|d| normalize_name(&d.name) == wanted);
+                let mut block = match definition {
+                    Some(definition) => definition.to_block(position, extents.x),
+                    None => registry
+                        .get(name)
+                        .ok_or_else(|| error(format!("tipo de bloque desconocido '{}'", name)))?
+                        .to_block(position, extents.x),
+                };
+                if !block.is_cubic() || block.extents != extents {
+                    block = block.with_extents(extents);
+                }
+                draft.blocks.push(block);
+                last = Some(LastObject::Block(draft.blocks.len() - 1));
+                // El material es el del tipo: se valida en su `blockdef`
+                last_entry = format!("block {} {}", fields[..3].join(" "), name);
+            }
+            "blockdef" => {
+                if fields.len() != 1 + MATERIAL_FIELDS {
+                    return Err(error(format!(
+                        "se esperaban {} campos en 'blockdef'",
+                        1 + MATERIAL_FIELDS
+                    )));
+                }
+                let name = fields[0];
+                if BlockType::from_name(name).is_some() {
+                    return Err(error(format!("'{}' es un tipo de bloque incorporado", name)));
+                }
+                let material = material_from_fields(&fields[1..]).map_err(error)?;
+                draft
+                    .scene
+                    .add_to_palette(&BlockDefinition::new(name, material));
+                let index = draft
+                    .scene
+                    .palette
+                    .iter()
+                    .position(|d| d.name == name)
+                    .unwrap_or(0);
+                last = Some(LastObject::Definition(index));
+                last_entry = format!("blockdef {}", name);
+                material_entries.push((
+                    line_number,
+                    last_entry.clone(),
+                    MaterialSlot::Object(LastObject::Definition(index)),
+                ));
+            }
+            "block" | "box" => {
+                if fields.len() != 4 + MATERIAL_FIELDS {
+                    return Err(error(format!(
+                        "se esperaban {} campos en '{}'",
+                        4 + MATERIAL_FIELDS,
+                        directive
+                    )));
+                }
+                let numbers = parse_floats(&fields[..3]).map_err(error)?;
+                let position = Vector3::new(numbers[0], numbers[1], numbers[2]);
+                let extents = extents_from_field(fields[3]).map_err(error)?;
+                let material = material_from_fields(&fields[4..]).map_err(error)?;
+                let block = Block::new(position, extents, material);
+                let object = if directive == "block" {
+                    draft.blocks.push(block);
+                    LastObject::Block(draft.blocks.len() - 1)
+                } else {
+                    draft.primitives.push(Primitive::Block(block));
+                    LastObject::Primitive(draft.primitives.len() - 1)
+                };
+                last = Some(object);
+                last_entry = format!("{} {}", directive, fields[..3].join(" "));
+                material_entries.push((
+                    line_number,
+                    last_entry.clone(),
+                    MaterialSlot::Object(object),
+                ));
+            }
+            "plane" => {
+                if fields.len() != 3 + MATERIAL_FIELDS {
+                    return Err(error(format!(
+                        "se esperaban {} campos en 'plane'",
+                        3 + MATERIAL_FIELDS
+                    )));
+                }
+                let point = vec3_from_field(fields[0]).map_err(error)?;
+                let normal = vec3_from_field(fields[1]).map_err(error)?;
+                if normal.length() == 0.0 {
+                    return Err(error("la normal del plano no puede ser cero".to_string()));
+                }
+                let uv_scale = parse_floats(&fields[2..3]).map_err(error)?[0];
+                let material = material_from_fields(&fields[3..]).map_err(error)?;
+                let mut plane = Plane::new(point, normal, material);
+                plane.uv_scale = uv_scale;
+                draft.primitives.push(Primitive::Plane(plane));
+                let object = LastObject::Primitive(draft.primitives.len() - 1);
+                last = Some(object);
+                last_entry = format!("plane {}", fields[0]);
+                material_entries.push((
+                    line_number,
+                    last_entry.clone(),
+                    MaterialSlot::Object(object),
+                ));
+            }
+            "sphere" => {
+                if fields.len() != 4 + MATERIAL_FIELDS {
+                    return Err(error(format!(
+                        "se esperaban {} campos en 'sphere'",
+                        4 + MATERIAL_FIELDS
+                    )));
+                }
+                let numbers = parse_floats(&fields[..4]).map_err(error)?;
+                let center = Vector3::new(numbers[0], numbers[1], numbers[2]);
+                let material = material_from_fields(&fields[4..]).map_err(error)?;
+                draft
+                    .primitives
+                    .push(Primitive::Sphere(Sphere::new(center, numbers[3], material)));
+                let object = LastObject::Primitive(draft.primitives.len() - 1);
+                last = Some(object);
+                last_entry = format!("sphere {}", fields[..3].join(" "));
+                material_entries.push((
+                    line_number,
+                    last_entry.clone(),
+                    MaterialSlot::Object(object),
+                ));
+            }
+            "emission" => {
+                if !(4..=6).contains(&fields.len()) {
+                    return Err(error("se esperaban de 4 a 6 campos en 'emission'".to_string()));
+                }
+                let numbers = parse_floats(&fields[..4]).map_err(error)?;
+                let offset = match fields.get(4) {
+                    Some(field) => vec3_from_field(field).map_err(error)?,
+                    None => Vector3::zero(),
+                };
+                let color = Vector3::new(numbers[0], numbers[1], numbers[2]);
+                let (position, emission) =
+                    last_emission(&mut draft, last, "emission").map_err(error)?;
+                let mut light = Light::new(position + offset, color, numbers[3]);
+                if let Some(field) = fields.get(5) {
+                    light.attenuation = Some(parse_floats(&[field]).map_err(error)?[0]);
+                }
+                *emission = Some(light);
+            }
+            "spot" => {
+                if fields.len() != 2 {
+                    return Err(error("se esperaban 2 campos en 'spot'".to_string()));
+                }
+                let direction = vec3_from_field(fields[0]).map_err(error)?;
+                let angle = parse_floats(&fields[1..]).map_err(error)?[0];
+                let (_, emission) = last_emission(&mut draft, last, "spot").map_err(error)?;
+                let light = emission
+                    .as_mut()
+                    .ok_or_else(|| error("'spot' sin 'emission' previo".to_string()))?;
+                light.spot = Some(Spot::new(direction, angle));
+            }
+            "shape" => {
+                let block = last_block(&mut draft, last, "shape").map_err(error)?;
+                // La luz ya guardada no se mueve: `emission` va después
+                block.shape = match fields.as_slice() {
+                    ["torch", attachment] => BlockShape::Torch(
+                        Attachment::from_name(attachment)
+                            .ok_or_else(|| error(format!("apoyo inválido '{}'", attachment)))?,
+                    ),
+                    ["slab", "top"] => BlockShape::Slab { top: true },
+                    ["slab", "bottom"] => BlockShape::Slab { top: false },
+                    ["stair", facing] => BlockShape::Stair {
+                        facing: Direction::from_name(facing)
+                            .ok_or_else(|| error(format!("orientación inválida '{}'", facing)))?,
+                    },
+                    ["plant", plant] => BlockShape::CrossPlant(
+                        Plant::from_name(plant)
+                            .ok_or_else(|| error(format!("planta inválida '{}'", plant)))?,
+                    ),
+                    ["pane", axis] => BlockShape::Pane {
+                        axis: Axis::from_name(axis)
+                            .ok_or_else(|| error(format!("eje inválido '{}'", axis)))?,
+                    },
+                    ["snow", layers] => BlockShape::SnowLayer {
+                        layers: layers
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|layers| (1..=MAX_SNOW_LAYERS).contains(layers))
+                            .ok_or_else(|| error(format!("capas inválidas '{}'", layers)))?,
+                    },
+                    ["fence"] => BlockShape::Fence { rails: 0 },
+                    ["chest", "open"] => BlockShape::Chest { open: true },
+                    ["chest", "closed"] => BlockShape::Chest { open: false },
+                    _ => return Err(error(format!("forma inválida '{}'", fields.join(" ")))),
+                };
+            }
+            "orient" => {
+                let block = last_block(&mut draft, last, "orient").map_err(error)?;
+                let [up, turns] = fields.as_slice() else {
+                    return Err(error("se esperaban 2 campos en 'orient'".to_string()));
+                };
+                let up = Face::from_name(up)
+                    .ok_or_else(|| error(format!("cara inválida '{}'", up)))?;
+                let turns = turns
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&turns| turns < 4)
+                    .ok_or_else(|| error(format!("giro inválido '{}'", turns)))?;
+                block.orientation = Orientation::new(up, turns);
+            }
+            "medium" => {
+                if fields.len() != 2 {
+                    return Err(error("se esperaban 2 campos en 'medium'".to_string()));
+                }
+                let absorption = vec3_from_field(fields[0]).map_err(error)?;
+                let waves = parse_floats(&fields[1..]).map_err(error)?[0];
+                let material = last_material(&mut draft, last)
+                    .ok_or_else(|| error("'medium' sin objeto previo".to_string()))?;
+                material.absorption = absorption;
+                material.waves = waves;
+            }
+            "surface" => {
+                if fields.len() != 2 {
+                    return Err(error("se esperaban 2 campos en 'surface'".to_string()));
+                }
+                let values = parse_floats(&fields).map_err(error)?;
+                let material = last_material(&mut draft, last)
+                    .ok_or_else(|| error("'surface' sin objeto previo".to_string()))?;
+                material.roughness = values[0];
+                material.frost = values[1];
+            }
+            "specks" => {
+                if fields.len() != 2 {
+                    return Err(error("se esperaban 2 campos en 'specks'".to_string()));
+                }
+                let color = vec3_from_field(fields[0]).map_err(error)?;
+                let glow = parse_floats(&fields[1..]).map_err(error)?[0];
+                let material = last_material(&mut draft, last)
+                    .ok_or_else(|| error("'specks' sin objeto previo".to_string()))?;
+                material.specks = Some(Specks::new(color, glow));
+            }
+            "warp" => {
+                if fields.len() != 2 {
+                    return Err(error("se esperaban 2 campos en 'warp'".to_string()));
+                }
+                let values = parse_floats(&fields).map_err(error)?;
+                let material = last_material(&mut draft, last)
+                    .ok_or_else(|| error("'warp' sin objeto previo".to_string()))?;
+                material.aberration_strength = values[0];
+                material.distortion_strength = values[1];
+            }
+            "caustics" => {
+                if !fields.is_empty() {
+                    return Err(error("'caustics' no lleva campos".to_string()));
+                }
+                let material = last_material(&mut draft, last)
+                    .ok_or_else(|| error("'caustics' sin objeto previo".to_string()))?;
+                material.caustics = true;
+            }
+            "fresnel" => {
+                if !fields.is_empty() {
+                    return Err(error("'fresnel' no lleva campos".to_string()));
+                }
+                let material = last_material(&mut draft, last)
+                    .ok_or_else(|| error("'fresnel' sin objeto previo".to_string()))?;
+                material.fresnel_reflection = true;
+            }
+            "face" => {
+                let block = last_block(&mut draft, last, "face").map_err(error)?;
+                if fields.len() != 1 + MATERIAL_FIELDS {
+                    return Err(error(format!(
+                        "se esperaban {} campos en 'face'",
+                        1 + MATERIAL_FIELDS
+                    )));
+                }
+                let face = fields[0]
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&face| face < 6)
+                    .ok_or_else(|| error(format!("cara inválida '{}'", fields[0])))?;
+                let material = material_from_fields(&fields[1..]).map_err(error)?;
+                block.face_overrides.get_or_insert_with(Default::default)[face] = Some(material);
+                if let Some(object) = last {
+                    material_entries.push((
+                        line_number,
+                        format!("face {} de {}", face, last_entry),
+                        MaterialSlot::Face(object, face),
+                    ));
+                }
+            }
+            "fog" => {
+                if fields.len() != 3 {
+                    return Err(error("se esperaban 3 campos en 'fog'".to_string()));
+                }
+                let numbers = parse_floats(&fields[..2]).map_err(error)?;
+                let color = match fields[2] {
+                    "-" => None,
+                    field => Some(vec3_from_field(field).map_err(error)?),
+                };
+                draft.scene.fog = Some(Fog::new(numbers[0], numbers[1], color));
+            }
+            "sun" => {
+                if fields.len() != 1 {
+                    return Err(error("se esperaba 1 campo en 'sun'".to_string()));
+                }
+                let direction = vec3_from_field(fields[0]).map_err(error)?;
+                if direction.length() == 0.0 {
+                    return Err(error("la dirección del sol no puede ser cero".to_string()));
+                }
+                draft.scene.sun = Some(direction.normalized());
+            }
+            "portal" => {
+                if fields.len() != 3 {
+                    return Err(error("se esperaban 3 campos en 'portal'".to_string()));
+                }
+                let position = parse_floats(&fields).map_err(error)?;
+                draft
+                    .scene
+                    .portal_cells
+                    .push(Vector3::new(position[0], position[1], position[2]));
+            }
+            other => return Err(error(format!("directiva desconocida '{}'", other))),
+        }
+    }
+
+    neighbors_changed(&mut draft.blocks);
+    let issues = material_entries
+        .into_iter()
+        .flat_map(|(line, entry, slot)| {
+            let warnings = slot_material(&draft, slot).map(Material::validate);
+            warnings
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |warning| MaterialIssue {
+                    line,
+                    entry: entry.clone(),
+                    warning,
+                })
+        })
+        .collect();
+    let scene = draft
+        .scene
+        .with_blocks(draft.blocks)
+        .with_primitives(draft.primitives);
+    Ok((scene, issues))
+}
+
+/// Campos del material en el orden que espera `material_from_fields`
+fn material_to_line(material: &Material) -> String {
+    let optional_text = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    format!(
+        "{} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+        vec3_to_field(material.diffuse),
+        material.albedo[0],
+        material.albedo[1],
+        material.specular,
+        material.reflectivity,
+        material.transparency,
+        material.refractive_index,
+        material.translucency,
+        material.unlit,
+        optional_text(&material.texture),
+        optional_text(&material.normal_map_id),
+        material
+            .emission_color
+            .map_or("-".to_string(), vec3_to_field),
+        material.emission_strength,
+        material
+            .refractive_index_rgb
+            .map_or("-".to_string(), |[r, g, b]| format!("{},{},{}", r, g, b)),
+    )
+}
+
+fn material_from_fields(fields: &[&str]) -> Result<Material, String> {
+    let optional_text = |field: &str| (field != "-").then(|| field.to_string());
+    let numbers = parse_floats(&[
+        fields[1], fields[2], fields[3], fields[4], fields[5], fields[6], fields[7], fields[12],
+    ])?;
+    let unlit = fields[8]
+        .parse::<bool>()
+        .map_err(|_| format!("valor booleano inválido '{}'", fields[8]))?;
+    let emission_color = match fields[11] {
+        "-" => None,
+        field => Some(vec3_from_field(field)?),
+    };
+    let refractive_index_rgb = match fields[13] {
+        "-" => None,
+        field => {
+            let v = vec3_from_field(field)?;
+            Some([v.x, v.y, v.z])
+        }
+    };
+
+    let mut material = Material::new_emissive(
+        vec3_from_field(fields[0])?,
+        [numbers[0], numbers[1]],
+        numbers[2],
+        numbers[3],
+        numbers[4],
+        numbers[5],
+        optional_text(fields[9]),
+        optional_text(fields[10]),
+        emission_color,
+        numbers[7],
+    );
+    material.translucency = numbers[6];
+    material.unlit = unlit;
+    material.refractive_index_rgb = refractive_index_rgb;
+    Ok(material)
+}
+
+fn vec3_to_field(v: Vector3) -> String {
+    format!("{},{},{}", v.x, v.y, v.z)
+}
+
+/// El lado solo si el bloque es un cubo, si no `x,y,z`
+fn extents_to_field(extents: Vector3) -> String {
+    if extents.x == extents.y && extents.y == extents.z {
+        extents.x.to_string()
+    } else {
+        vec3_to_field(extents)
+    }
+}
+
+fn extents_from_field(field: &str) -> Result<Vector3, String> {
+    if field.contains(',') {
+        vec3_from_field(field)
+    } else {
+        Ok(Vector3::one() * parse_floats(&[field])?[0])
+    }
+}
+
+fn vec3_from_field(field: &str) -> Result<Vector3, String> {
+    let parts: Vec<&str> = field.split(',').collect();
+    if parts.len() != 3 {
+        return Err(format!("vector inválido '{}'", field));
+    }
+    let v = parse_floats(&parts)?;
+    Ok(Vector3::new(v[0], v[1], v[2]))
+}
+
+fn parse_floats(fields: &[&str]) -> Result<Vec<f32>, String> {
+    fields
+        .iter()
+        .map(|field| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("número inválido '{}'", field))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::SceneKind;
+
+    /// Archivo propio de la prueba en el directorio temporal
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("raytracer_{}_{}.txt", name, std::process::id()))
+    }
+
+    /// Carga `text` como archivo de escena
+    fn load_text(name: &str, text: &str) -> Result<(Scene, Vec<MaterialIssue>), SceneError> {
+        let path = temp_path(name);
+        fs::write(&path, text).unwrap();
+        let result = load_scene_checked(&path, &BlockRegistry::new());
+        let _ = fs::remove_file(&path);
+        result
+    }
+
+    /// Material válido en el formato de línea
+    const PLAIN: &str = "0.5,0.5,0.5 0.8 0.2 10 0.1 0 1 0 false - - - 0 -";
+
+    /// Guarda `scene`, la vuelve a cargar y devuelve lo leído
+    fn round_trip(name: &str, scene: &Scene) -> Scene {
+        let path = temp_path(name);
+        save_scene(&path, scene).unwrap();
+        let loaded = load_scene_with(&path, &BlockRegistry::new());
+        let _ = fs::remove_file(&path);
+        loaded.unwrap()
+    }
+
+    #[test]
+    fn builtin_scenes_survive_save_and_load() {
+        for kind in [
+            SceneKind::Island,
+            SceneKind::Winter,
+            SceneKind::Gallery,
+            SceneKind::Primitives,
+        ] {
+            let scene = kind.build().unwrap();
+            assert_eq!(
+                round_trip(kind.name(), &scene),
+                scene,
+                "escena {}",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn scene_extras_survive_save_and_load() {
+        let mut material = BlockType::Stone.material();
+        material.diffuse = Vector3::new(0.3, 0.6, 0.9);
+        let definition = BlockDefinition::new("piedra_azul", material);
+        let mut placed = definition.template.clone();
+        placed.position = Vector3::new(1.0, 0.0, 0.0);
+
+        let mut scene = Scene::new(vec![
+            placed,
+            BlockType::Torch.to_block(Vector3::new(0.0, 1.0, 0.0), 1.0),
+        ]);
+        scene.add_to_palette(&definition);
+        scene.fog = Some(Fog {
+            density: 0.05,
+            start: 4.0,
+            color: Some(Vector3::new(0.7, 0.75, 0.8)),
+        });
+        scene.sun = Some(Vector3::new(0.6, 0.8, 0.0));
+        scene.portal_cells = vec![Vector3::new(2.0, 0.0, 0.0)];
+
+        assert_eq!(round_trip("extras", &scene), scene);
+    }
+
+    #[test]
+    fn material_issues_name_entry_and_line() {
+        let text = format!(
+            "# materiales\n\
+             blockdef bueno {PLAIN}\n\
+             blockdef brillante 0.5,0.5,0.5 0.8 0.2 10 0.8 0.8 1.5 0 false - - - 0 -\n\
+             block 1 0 0 1 {PLAIN}\n\
+             surface 2 0\n\
+             face 3 0.5,0.5,0.5 0.8 0.2 10 0.1 0 0.5 0 false - - - 0 -\n"
+        );
+        let (_, issues) = load_text("material_issues", &text).unwrap();
+        assert_eq!(
+            issues,
+            vec![
+                MaterialIssue {
+                    line: 3,
+                    entry: "blockdef brillante".to_string(),
+                    warning: MaterialWarning::EnergyExceeded {
+                        reflectivity: 0.8,
+                        transparency: 0.8
+                    },
+                },
+                MaterialIssue {
+                    line: 4,
+                    entry: "block 1 0 0".to_string(),
+                    warning: MaterialWarning::AboveMaximum {
+                        field: "roughness",
+                        value: 2.0,
+                        max: 1.0
+                    },
+                },
+                MaterialIssue {
+                    line: 6,
+                    entry: "face 3 de block 1 0 0".to_string(),
+                    warning: MaterialWarning::RefractiveIndexBelowOne(0.5),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_file_is_io_error() {
+        let path = temp_path("no_existe");
+        let err = load_scene(&path).unwrap_err();
+        match &err {
+            SceneError::Io {
+                path: err_path,
+                source,
+            } => {
+                assert_eq!(err_path, &path);
+                assert_eq!(source.kind(), io::ErrorKind::NotFound);
+            }
+            other => panic!("se esperaba Io: {:?}", other),
+        }
+        assert_eq!(crate::error::AppError::from(err).exit_code(), 3);
+    }
+
+    #[test]
+    fn malformed_line_is_parse_error_with_its_number() {
+        let text = format!(
+            "# comentario\n\
+             \n\
+             block 0 0 0 1 {PLAIN}\n\
+             block 1 0 x 1 {PLAIN}\n\
+             block 2 0 0 1 {PLAIN}\n"
+        );
+        let err = load_text("malformed", &text).map(|_| ()).unwrap_err();
+        assert!(
+            matches!(err, SceneError::Parse { line: 4, .. }),
+            "se esperaba Parse en la línea 4: {:?}",
+            err
+        );
+        assert!(err.to_string().contains(":4: "), "{}", err);
+    }
+}
@@ -0,0 +1,206 @@
+// screenshot.rs - Capturas del frame renderizado
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::*;
+
+use crate::framebuffer::{ExportError, Framebuffer, color_to_u32, write_image, write_ppm};
+use crate::material::vector3_to_color;
+use crate::post::luminance;
+use crate::radiance::write_radiance_hdr;
+use crate::tonemap::{ToneMapSettings, tone_map};
+
+/// Directorio donde se guardan las capturas
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Guarda el frame como PPM numerado en `SCREENSHOT_DIR`, sin pisar capturas
+/// anteriores. Devuelve la ruta escrita.
+pub fn save_screenshot(framebuffer: &Framebuffer) -> Result<PathBuf, ExportError> {
+    save_screenshot_pixels(
+        framebuffer.width,
+        framebuffer.height,
+        framebuffer.as_slice(),
+    )
+}
+
+/// Como `save_screenshot` con pixeles RGBA sueltos (ej: los de `read_window`)
+pub fn save_screenshot_pixels(
+    width: u32,
+    height: u32,
+    pixels: &[u32],
+) -> Result<PathBuf, ExportError> {
+    save_screenshot_in(Path::new(SCREENSHOT_DIR), width, height, pixels)
+}
+
+/// Como `save_screenshot_pixels` en el directorio `dir`
+fn save_screenshot_in(
+    dir: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[u32],
+) -> Result<PathBuf, ExportError> {
+    fs::create_dir_all(dir).map_err(|err| ExportError::io(dir, err))?;
+    let path = next_free_path(dir, "screenshot", "ppm");
+    write_ppm(&path, width, height, pixels)?;
+    Ok(path)
+}
+
+/// Lee la ventana tal como se ve: el frame presentado más los overlays y el
+/// HUD. Se llama dentro de `begin_drawing`, después de dibujar todo.
+/// Devuelve (ancho, alto, pixeles RGBA).
+pub fn read_window(d: &mut RaylibDrawHandle, thread: &RaylibThread) -> (u32, u32, Vec<u32>) {
+    // Lo que sigue en el lote de raylib todavía no llegó a la pantalla
+    unsafe {
+        raylib::ffi::rlDrawRenderBatchActive();
+    }
+    let image = d.load_image_from_screen(thread);
+    let pixels = image
+        .get_image_data()
+        .iter()
+        .map(|&color| color_to_u32(color))
+        .collect();
+    (image.width as u32, image.height as u32, pixels)
+}
+
+/// Exposiciones de la captura con `HdrCaptureFormat::Brackets`, en EV
+/// respecto de la del mapeo tonal actual
+pub const BRACKET_EVS: [f32; 3] = [-2.0, 0.0, 2.0];
+
+/// Qué guarda la captura HDR (Shift + tecla de captura)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HdrCaptureFormat {
+    /// Un archivo Radiance (.hdr) con la radiancia sin mapear
+    #[default]
+    Radiance,
+    /// Tres PNG mapeados a -2, 0 y +2 EV
+    Brackets,
+}
+
+impl HdrCaptureFormat {
+    /// Nombre usado en el archivo de preferencias
+    pub fn name(self) -> &'static str {
+        match self {
+            HdrCaptureFormat::Radiance => "hdr",
+            HdrCaptureFormat::Brackets => "brackets",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hdr" => Some(HdrCaptureFormat::Radiance),
+            "brackets" => Some(HdrCaptureFormat::Brackets),
+            _ => None,
+        }
+    }
+}
+
+/// Resultado de una captura HDR
+#[derive(Debug, Clone)]
+pub struct HdrCapture {
+    pub paths: Vec<PathBuf>,
+    /// Luminancia del pixel más brillante del buffer capturado
+    pub peak_luminance: f32,
+}
+
+/// Guarda el buffer HDR del frame (antes del post-procesado) en
+/// `SCREENSHOT_DIR`. Las exposiciones de `Brackets` parten de `tone_map`.
+pub fn save_hdr_capture(
+    framebuffer: &Framebuffer,
+    format: HdrCaptureFormat,
+    tone_map_settings: ToneMapSettings,
+) -> Result<HdrCapture, ExportError> {
+    fs::create_dir_all(SCREENSHOT_DIR).map_err(|err| ExportError::io(SCREENSHOT_DIR, err))?;
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let hdr = framebuffer.hdr();
+    let peak_luminance = hdr
+        .iter()
+        .map(|&color| luminance(color))
+        .fold(0.0, f32::max);
+
+    let paths = match format {
+        HdrCaptureFormat::Radiance => {
+            let path = next_free_path(Path::new(SCREENSHOT_DIR), "hdr", "hdr");
+            write_radiance_hdr(&path, width, height, hdr)?;
+            vec![path]
+        }
+        HdrCaptureFormat::Brackets => {
+            // La de 0 EV lleva el nombre base, así reserva el número
+            let base = next_free_path(Path::new(SCREENSHOT_DIR), "bracket", "png");
+            let stem = base.with_extension("");
+            let mut paths = Vec::new();
+            for ev in BRACKET_EVS {
+                let path = if ev == 0.0 {
+                    base.clone()
+                } else {
+                    PathBuf::from(format!("{}_{:+}ev.png", stem.display(), ev))
+                };
+                let settings = ToneMapSettings {
+                    exposure_ev: tone_map_settings.exposure_ev + ev,
+                    ..tone_map_settings
+                };
+                let pixels: Vec<u32> = hdr
+                    .iter()
+                    .map(|&color| color_to_u32(vector3_to_color(tone_map(color, settings))))
+                    .collect();
+                write_image(&path.to_string_lossy(), width, height, &pixels)?;
+                paths.push(path);
+            }
+            paths
+        }
+    };
+    Ok(HdrCapture {
+        paths,
+        peak_luminance,
+    })
+}
+
+/// Primera ruta `<prefijo>_NNNN.<ext>` que todavía no existe
+fn next_free_path(dir: &Path, prefix: &str, extension: &str) -> PathBuf {
+    (1..)
+        .map(|n| dir.join(format!("{}_{:04}.{}", prefix, n, extension)))
+        .find(|path| !path.exists())
+        .expect("rango infinito")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_capture_has_no_hud_pixels() {
+        // El frame de un clear liso, como queda antes de dibujar el HUD
+        let background = Color::new(20, 40, 90, 255);
+        let mut framebuffer = Framebuffer::new(320, 240);
+        framebuffer.clear(color_to_u32(background));
+
+        let dir = std::env::temp_dir().join(format!("raytracer_capture_{}", std::process::id()));
+        let path = save_screenshot_in(
+            &dir,
+            framebuffer.width,
+            framebuffer.height,
+            framebuffer.as_slice(),
+        )
+        .unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let header = b"P6\n320 240\n255\n";
+        assert!(bytes.starts_with(header));
+        let pixels = &bytes[header.len()..];
+        assert_eq!(pixels.len(), 320 * 240 * 3);
+        // Zona del HUD: las líneas de texto de arriba a la izquierda
+        for y in 0..120 {
+            for x in 0..320 {
+                let i = (y * 320 + x) * 3;
+                assert_eq!(
+                    &pixels[i..i + 3],
+                    &[background.r, background.g, background.b],
+                    "pixel ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// serialization.rs - Adaptadores de serde para tipos externos (feature "serde")
+use raylib::prelude::Vector3;
+use serde::{Deserialize, Serialize};
+
+/// Definición remota de `Vector3` de raylib, que no implementa serde.
+/// Uso: `#[serde(with = "crate::serialization::Vec3Def")]`.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Vector3")]
+pub struct Vec3Def {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Igual que `Vec3Def` pero para campos `Option<Vector3>`.
+/// Uso: `#[serde(with = "crate::serialization::option_vec3")]`.
+pub mod option_vec3 {
+    use super::Vec3Def;
+    use raylib::prelude::Vector3;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "Vec3Def")] Vector3);
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Vector3>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vector3>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(v)| v))
+    }
+}
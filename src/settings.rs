@@ -0,0 +1,705 @@
+// settings.rs - Preferencias persistentes: RON con la feature "serde", si no
+// un archivo `clave = valor`
+use std::fs;
+use std::path::Path;
+
+use log::warn;
+
+use crate::config::ConfigError;
+use crate::day_cycle::{DayCycle, SkyModel};
+use crate::denoise::DenoiseSettings;
+use crate::events::GamepadSettings;
+use crate::fog::{fog_color_to_string, parse_fog_color};
+use crate::framebuffer::PresentMode;
+use crate::post::{BloomSettings, PostProcess};
+use crate::render_settings::RenderSettings;
+use crate::screenshot::HdrCaptureFormat;
+use crate::tnt::DEFAULT_BLAST_RADIUS;
+use crate::tonemap::{AutoExposure, ToneMapSettings, ToneMapping};
+
+/// Archivo donde se guardan las preferencias entre ejecuciones
+#[cfg(feature = "serde")]
+pub const SETTINGS_PATH: &str = "settings.ron";
+#[cfg(not(feature = "serde"))]
+pub const SETTINGS_PATH: &str = "settings.toml";
+
+/// Preferencias del usuario que sobreviven entre ejecuciones
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Settings {
+    pub render: RenderSettings,
+    pub tone_map: ToneMapSettings,
+    pub auto_exposure: AutoExposure,
+    pub bloom: BloomSettings,
+    pub denoise: DenoiseSettings,
+    pub fxaa: bool,
+    pub dither: bool,
+    pub post: PostProcess,
+    pub present_mode: PresentMode,
+    /// Directorio donde F10 graba la secuencia de frames
+    pub record_dir: String,
+    /// Se graba uno de cada N frames presentados
+    pub record_every_nth: u32,
+    /// Las capturas y grabaciones toman la ventana con el HUD encima; por
+    /// defecto guardan solo el frame post-procesado
+    pub capture_hud: bool,
+    /// Qué guarda la captura HDR (Shift + F11)
+    pub hdr_capture: HdrCaptureFormat,
+    pub gamepad: GamepadSettings,
+    pub day: DayCycle,
+    /// Radio de la explosión de la TNT, en bloques
+    pub tnt_radius: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            render: RenderSettings::default(),
+            tone_map: ToneMapSettings::default(),
+            auto_exposure: AutoExposure::default(),
+            bloom: BloomSettings::default(),
+            denoise: DenoiseSettings::default(),
+            fxaa: true,
+            dither: true,
+            post: PostProcess::default(),
+            present_mode: PresentMode::default(),
+            record_dir: "recordings".to_string(),
+            record_every_nth: 1,
+            capture_hud: false,
+            hdr_capture: HdrCaptureFormat::default(),
+            gamepad: GamepadSettings::default(),
+            day: DayCycle::default(),
+            tnt_radius: DEFAULT_BLAST_RADIUS,
+        }
+    }
+}
+
+impl Settings {
+    /// Carga las preferencias; si el archivo no existe usa los valores por defecto.
+    /// Claves desconocidas o valores inválidos se ignoran con una advertencia.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let Ok(text) = fs::read_to_string(path.as_ref()) else {
+            return Settings::default();
+        };
+
+        let mut settings = Self::from_file_text(path.as_ref(), &text);
+        settings.render.sky = settings.day.sky();
+        settings
+    }
+
+    /// Lee el archivo en RON. Los campos que faltan toman el valor por
+    /// defecto y los desconocidos se ignoran; si no se puede leer, se
+    /// avisa y se usan todas las preferencias por defecto.
+    #[cfg(feature = "serde")]
+    fn from_file_text(path: &Path, text: &str) -> Self {
+        match ron::from_str::<Settings>(text) {
+            Ok(mut settings) => {
+                settings.sanitize();
+                settings
+            }
+            Err(err) => {
+                warn!(
+                    "{}: {}; se usan los valores por defecto",
+                    path.display(),
+                    err
+                );
+                Settings::default()
+            }
+        }
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn from_file_text(path: &Path, text: &str) -> Self {
+        let mut settings = Settings::default();
+        for (line_number, key, value) in parse_key_values(text) {
+            if !settings.apply(&key, &value) {
+                warn!(
+                    "{}:{}: ignorando '{} = {}'",
+                    path.display(),
+                    line_number,
+                    key,
+                    value
+                );
+            }
+        }
+        settings
+    }
+
+    /// Lleva a su rango los valores que `apply` validaría uno por uno
+    #[cfg(feature = "serde")]
+    pub(crate) fn sanitize(&mut self) {
+        let defaults = RenderSettings::default();
+        if !RenderSettings::is_valid_samples_per_pixel(self.render.samples_per_pixel) {
+            self.render.samples_per_pixel = defaults.samples_per_pixel;
+        }
+        self.render.tile_size = self.render.tile_size.max(1);
+        self.record_every_nth = self.record_every_nth.max(1);
+        self.gamepad.dead_zone = self.gamepad.dead_zone.clamp(0.0, 0.9);
+        self.day.set_hour(self.day.hour);
+        self.tnt_radius = self.tnt_radius.clamp(0.5, 16.0);
+    }
+
+    /// Aplica una línea `clave = valor` de las preferencias. Devuelve false
+    /// si la clave no existe o el valor no es válido.
+    pub fn apply(&mut self, key: &str, value: &str) -> bool {
+        let settings = self;
+        match key {
+            "shadows" => value
+                .parse::<bool>()
+                .map(|on| settings.render.shadows = on)
+                .is_ok(),
+            "soft_shadows" => value
+                .parse::<bool>()
+                .map(|on| settings.render.soft_shadows = on)
+                .is_ok(),
+            "light_radius" => value
+                .parse::<f32>()
+                .ok()
+                .filter(|&radius| radius >= 0.0)
+                .map(|radius| settings.render.light_radius = radius)
+                .is_some(),
+            "reflections" => value
+                .parse::<bool>()
+                .map(|on| settings.render.reflections = on)
+                .is_ok(),
+            "refractions" => value
+                .parse::<bool>()
+                .map(|on| settings.render.refractions = on)
+                .is_ok(),
+            "ambient_occlusion" => value
+                .parse::<bool>()
+                .map(|on| settings.render.ambient_occlusion = on)
+                .is_ok(),
+            "ao_jitter" => value
+                .parse::<bool>()
+                .map(|on| settings.render.ao_jitter = on)
+                .is_ok(),
+            "light_portals" => value
+                .parse::<bool>()
+                .map(|on| settings.render.light_portals = on)
+                .is_ok(),
+            "portal_strength" => value
+                .parse::<f32>()
+                .map(|strength| settings.render.portal_strength = strength)
+                .is_ok(),
+            "max_depth" => value
+                .parse::<u32>()
+                .map(|depth| settings.render.max_depth = depth)
+                .is_ok(),
+            "russian_roulette" => value
+                .parse::<bool>()
+                .map(|on| settings.render.russian_roulette = on)
+                .is_ok(),
+            "bilinear_filtering" => value
+                .parse::<bool>()
+                .map(|on| settings.render.bilinear_filtering = on)
+                .is_ok(),
+            "half_resolution" => value
+                .parse::<bool>()
+                .map(|on| settings.render.half_resolution = on)
+                .is_ok(),
+            "max_distance" => value
+                .parse::<f32>()
+                .map(|d| settings.render.max_distance = d)
+                .is_ok(),
+            "ray_epsilon" => value
+                .parse::<f32>()
+                .map(|e| settings.render.ray_epsilon = e)
+                .is_ok(),
+            "min_specular" => value
+                .parse::<f32>()
+                .map(|s| settings.render.min_specular = s)
+                .is_ok(),
+            "ambient_strength" => value
+                .parse::<f32>()
+                .map(|a| settings.render.ambient_strength = a)
+                .is_ok(),
+            "light_attenuation" => value
+                .parse::<f32>()
+                .map(|a| settings.render.light_attenuation = a)
+                .is_ok(),
+            "tile_size" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&size| size > 0)
+                .map(|size| settings.render.tile_size = size)
+                .is_some(),
+            "samples_per_pixel" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&samples| RenderSettings::is_valid_samples_per_pixel(samples))
+                .map(|samples| settings.render.samples_per_pixel = samples)
+                .is_some(),
+            "accumulate" => value
+                .parse::<bool>()
+                .map(|on| settings.render.accumulate = on)
+                .is_ok(),
+            "adaptive_sampling" => value
+                .parse::<bool>()
+                .map(|on| settings.render.adaptive.enabled = on)
+                .is_ok(),
+            "adaptive_threshold" => value
+                .parse::<f32>()
+                .ok()
+                .filter(|&threshold| threshold > 0.0)
+                .map(|threshold| settings.render.adaptive.threshold = threshold)
+                .is_some(),
+            "adaptive_max_spp" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&samples| samples > 0)
+                .map(|samples| settings.render.adaptive.max_spp = samples)
+                .is_some(),
+            "motion_blur" => value
+                .parse::<bool>()
+                .map(|on| settings.render.motion_blur.enabled = on)
+                .is_ok(),
+            "motion_blur_shutter" => value
+                .parse::<f32>()
+                .ok()
+                .filter(|shutter| (0.0..=1.0).contains(shutter))
+                .map(|shutter| settings.render.motion_blur.shutter = shutter)
+                .is_some(),
+            "motion_blur_samples" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&samples| samples > 0)
+                .map(|samples| settings.render.motion_blur.samples = samples)
+                .is_some(),
+            "god_rays" => value
+                .parse::<bool>()
+                .map(|on| settings.render.god_rays = on)
+                .is_ok(),
+            "god_ray_steps" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&steps| steps > 0)
+                .map(|steps| settings.render.god_ray_steps = steps)
+                .is_some(),
+            "god_ray_density" => value
+                .parse::<f32>()
+                .map(|density| settings.render.god_ray_density = density)
+                .is_ok(),
+            "god_ray_distance" => value
+                .parse::<f32>()
+                .map(|distance| settings.render.god_ray_distance = distance)
+                .is_ok(),
+            "sky_model" => SkyModel::from_name(value)
+                .map(|model| settings.render.sky_model = model)
+                .is_some(),
+            "turbidity" => value
+                .parse::<f32>()
+                .ok()
+                .filter(|&turbidity| turbidity > 0.0)
+                .map(|turbidity| settings.render.turbidity = turbidity)
+                .is_some(),
+            "star_density" => value
+                .parse::<f32>()
+                .ok()
+                .filter(|density| (0.0..=1.0).contains(density))
+                .map(|density| settings.render.star_density = density)
+                .is_some(),
+            "star_brightness" => value
+                .parse::<f32>()
+                .map(|brightness| settings.render.star_brightness = brightness)
+                .is_ok(),
+            "toon" => value
+                .parse::<bool>()
+                .map(|on| settings.render.toon.enabled = on)
+                .is_ok(),
+            "toon_bands" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&bands| bands > 0)
+                .map(|bands| settings.render.toon.bands = bands)
+                .is_some(),
+            "toon_outline" => value
+                .parse::<u32>()
+                .map(|thickness| settings.render.toon.outline_thickness = thickness)
+                .is_ok(),
+            "cost_view_max" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&max| max > 0)
+                .map(|max| settings.render.cost_view_max = max)
+                .is_some(),
+            "fog_density" => value
+                .parse::<f32>()
+                .ok()
+                .filter(|&density| density >= 0.0)
+                .map(|density| settings.render.fog.density = density)
+                .is_some(),
+            "fog_start" => value
+                .parse::<f32>()
+                .map(|start| settings.render.fog.start = start)
+                .is_ok(),
+            "fog_color" => parse_fog_color(value)
+                .map(|color| settings.render.fog.color = color)
+                .is_some(),
+            "tone_mapping" => ToneMapping::from_name(value)
+                .map(|op| settings.tone_map.operator = op)
+                .is_some(),
+            "exposure_ev" => value
+                .parse::<f32>()
+                .map(|ev| settings.tone_map.exposure_ev = ev)
+                .is_ok(),
+            "auto_exposure" => value
+                .parse::<bool>()
+                .map(|on| settings.auto_exposure.enabled = on)
+                .is_ok(),
+            "auto_exposure_target" => value
+                .parse::<f32>()
+                .map(|t| settings.auto_exposure.target_luminance = t)
+                .is_ok(),
+            "auto_exposure_min_ev" => value
+                .parse::<f32>()
+                .map(|ev| settings.auto_exposure.min_ev = ev)
+                .is_ok(),
+            "auto_exposure_max_ev" => value
+                .parse::<f32>()
+                .map(|ev| settings.auto_exposure.max_ev = ev)
+                .is_ok(),
+            "auto_exposure_speed_up" => value
+                .parse::<f32>()
+                .map(|s| settings.auto_exposure.speed_up = s)
+                .is_ok(),
+            "auto_exposure_speed_down" => value
+                .parse::<f32>()
+                .map(|s| settings.auto_exposure.speed_down = s)
+                .is_ok(),
+            "bloom_enabled" => value
+                .parse::<bool>()
+                .map(|on| settings.bloom.enabled = on)
+                .is_ok(),
+            "bloom_threshold" => value
+                .parse::<f32>()
+                .map(|t| settings.bloom.threshold = t)
+                .is_ok(),
+            "bloom_strength" => value
+                .parse::<f32>()
+                .map(|s| settings.bloom.strength = s)
+                .is_ok(),
+            "denoise" => value
+                .parse::<bool>()
+                .map(|on| settings.denoise.enabled = on)
+                .is_ok(),
+            "denoise_strength" => value
+                .parse::<f32>()
+                .map(|strength| settings.denoise.strength = strength)
+                .is_ok(),
+            "denoise_passes" => value
+                .parse::<u32>()
+                .map(|passes| settings.denoise.passes = passes)
+                .is_ok(),
+            "fxaa" => value.parse::<bool>().map(|on| settings.fxaa = on).is_ok(),
+            "dither" => value.parse::<bool>().map(|on| settings.dither = on).is_ok(),
+            "vignette_strength" => value
+                .parse::<f32>()
+                .map(|v| settings.post.vignette_strength = v)
+                .is_ok(),
+            "vignette_radius" => value
+                .parse::<f32>()
+                .map(|v| settings.post.vignette_radius = v)
+                .is_ok(),
+            "saturation" => value
+                .parse::<f32>()
+                .map(|v| settings.post.saturation = v)
+                .is_ok(),
+            "present_mode" => PresentMode::from_name(value)
+                .map(|mode| settings.present_mode = mode)
+                .is_some(),
+            "record_dir" => {
+                settings.record_dir = value.to_string();
+                true
+            }
+            "record_every_nth" => value
+                .parse::<u32>()
+                .map(|n| settings.record_every_nth = n.max(1))
+                .is_ok(),
+            "capture_hud" => value
+                .parse::<bool>()
+                .map(|on| settings.capture_hud = on)
+                .is_ok(),
+            "hdr_capture" => HdrCaptureFormat::from_name(value)
+                .map(|format| settings.hdr_capture = format)
+                .is_some(),
+            "contrast" => value
+                .parse::<f32>()
+                .map(|v| settings.post.contrast = v)
+                .is_ok(),
+            "gamepad_dead_zone" => value
+                .parse::<f32>()
+                .map(|v| settings.gamepad.dead_zone = v.clamp(0.0, 0.9))
+                .is_ok(),
+            "gamepad_response_curve" => value
+                .parse::<f32>()
+                .map(|v| settings.gamepad.response_curve = v)
+                .is_ok(),
+            "gamepad_move_speed" => value
+                .parse::<f32>()
+                .map(|v| settings.gamepad.move_speed = v)
+                .is_ok(),
+            "gamepad_look_speed" => value
+                .parse::<f32>()
+                .map(|v| settings.gamepad.look_speed = v)
+                .is_ok(),
+            "time_of_day" => value
+                .parse::<f32>()
+                .map(|h| settings.day.set_hour(h))
+                .is_ok(),
+            "day_speed" => value
+                .parse::<f32>()
+                .ok()
+                .filter(|speed| *speed > 0.0)
+                .map(|speed| settings.day.speed = speed)
+                .is_some(),
+            "day_paused" => value
+                .parse::<bool>()
+                .map(|paused| settings.day.paused = paused)
+                .is_ok(),
+            "tnt_radius" => value
+                .parse::<f32>()
+                .map(|radius| settings.tnt_radius = radius.clamp(0.5, 16.0))
+                .is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Guarda las preferencias en RON
+    #[cfg(feature = "serde")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| ConfigError::invalid("preferencias serializables", err.to_string()))?;
+        fs::write(path.as_ref(), text).map_err(|err| ConfigError::io(path, err))
+    }
+
+    /// Guarda las preferencias en formato `clave = valor`
+    #[cfg(not(feature = "serde"))]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        fs::write(path.as_ref(), self.to_text()).map_err(|err| ConfigError::io(path, err))
+    }
+
+    /// Las preferencias en el formato de `settings.toml`
+    pub fn to_text(&self) -> String {
+        format!(
+            "# Preferencias del raytracer\n\
+             shadows = {}\n\
+             soft_shadows = {}\n\
+             light_radius = {}\n\
+             reflections = {}\n\
+             refractions = {}\n\
+             ambient_occlusion = {}\n\
+             ao_jitter = {}\n\
+             light_portals = {}\n\
+             portal_strength = {}\n\
+             max_depth = {}\n\
+             russian_roulette = {}\n\
+             bilinear_filtering = {}\n\
+             half_resolution = {}\n\
+             max_distance = {}\n\
+             ray_epsilon = {}\n\
+             min_specular = {}\n\
+             ambient_strength = {}\n\
+             light_attenuation = {}\n\
+             tile_size = {}\n\
+             samples_per_pixel = {}\n\
+             accumulate = {}\n\
+             adaptive_sampling = {}\n\
+             adaptive_threshold = {}\n\
+             adaptive_max_spp = {}\n\
+             motion_blur = {}\n\
+             motion_blur_shutter = {}\n\
+             motion_blur_samples = {}\n\
+             god_rays = {}\n\
+             god_ray_steps = {}\n\
+             god_ray_density = {}\n\
+             god_ray_distance = {}\n\
+             sky_model = \"{}\"\n\
+             turbidity = {}\n\
+             star_density = {}\n\
+             star_brightness = {}\n\
+             toon = {}\n\
+             toon_bands = {}\n\
+             toon_outline = {}\n\
+             cost_view_max = {}\n\
+             fog_density = {}\n\
+             fog_start = {}\n\
+             fog_color = \"{}\"\n\
+             tone_mapping = \"{}\"\n\
+             exposure_ev = {}\n\
+             auto_exposure = {}\n\
+             auto_exposure_target = {}\n\
+             auto_exposure_min_ev = {}\n\
+             auto_exposure_max_ev = {}\n\
+             auto_exposure_speed_up = {}\n\
+             auto_exposure_speed_down = {}\n\
+             bloom_enabled = {}\n\
+             bloom_threshold = {}\n\
+             bloom_strength = {}\n\
+             denoise = {}\n\
+             denoise_strength = {}\n\
+             denoise_passes = {}\n\
+             fxaa = {}\n\
+             dither = {}\n\
+             vignette_strength = {}\n\
+             vignette_radius = {}\n\
+             saturation = {}\n\
+             contrast = {}\n\
+             present_mode = \"{}\"\n\
+             record_dir = \"{}\"\n\
+             record_every_nth = {}\n\
+             capture_hud = {}\n\
+             hdr_capture = \"{}\"\n\
+             gamepad_dead_zone = {}\n\
+             gamepad_response_curve = {}\n\
+             gamepad_move_speed = {}\n\
+             gamepad_look_speed = {}\n\
+             time_of_day = {}\n\
+             day_speed = {}\n\
+             day_paused = {}\n\
+             tnt_radius = {}\n",
+            self.render.shadows,
+            self.render.soft_shadows,
+            self.render.light_radius,
+            self.render.reflections,
+            self.render.refractions,
+            self.render.ambient_occlusion,
+            self.render.ao_jitter,
+            self.render.light_portals,
+            self.render.portal_strength,
+            self.render.max_depth,
+            self.render.russian_roulette,
+            self.render.bilinear_filtering,
+            self.render.half_resolution,
+            self.render.max_distance,
+            self.render.ray_epsilon,
+            self.render.min_specular,
+            self.render.ambient_strength,
+            self.render.light_attenuation,
+            self.render.tile_size,
+            self.render.samples_per_pixel,
+            self.render.accumulate,
+            self.render.adaptive.enabled,
+            self.render.adaptive.threshold,
+            self.render.adaptive.max_spp,
+            self.render.motion_blur.enabled,
+            self.render.motion_blur.shutter,
+            self.render.motion_blur.samples,
+            self.render.god_rays,
+            self.render.god_ray_steps,
+            self.render.god_ray_density,
+            self.render.god_ray_distance,
+            self.render.sky_model.name(),
+            self.render.turbidity,
+            self.render.star_density,
+            self.render.star_brightness,
+            self.render.toon.enabled,
+            self.render.toon.bands,
+            self.render.toon.outline_thickness,
+            self.render.cost_view_max,
+            self.render.fog.density,
+            self.render.fog.start,
+            fog_color_to_string(self.render.fog.color),
+            self.tone_map.operator.name(),
+            self.tone_map.exposure_ev,
+            self.auto_exposure.enabled,
+            self.auto_exposure.target_luminance,
+            self.auto_exposure.min_ev,
+            self.auto_exposure.max_ev,
+            self.auto_exposure.speed_up,
+            self.auto_exposure.speed_down,
+            self.bloom.enabled,
+            self.bloom.threshold,
+            self.bloom.strength,
+            self.denoise.enabled,
+            self.denoise.strength,
+            self.denoise.passes,
+            self.fxaa,
+            self.dither,
+            self.post.vignette_strength,
+            self.post.vignette_radius,
+            self.post.saturation,
+            self.post.contrast,
+            self.present_mode.name(),
+            self.record_dir,
+            self.record_every_nth,
+            self.capture_hud,
+            self.hdr_capture.name(),
+            self.gamepad.dead_zone,
+            self.gamepad.response_curve,
+            self.gamepad.move_speed,
+            self.gamepad.look_speed,
+            self.day.hour,
+            self.day.speed,
+            self.day.paused,
+            self.tnt_radius,
+        )
+    }
+
+    /// Si la vista final necesita el G-buffer (filtro de ruido o contornos
+    /// del modo dibujo); el renderer lo guarda solo entonces
+    pub fn needs_gbuffer(&self) -> bool {
+        self.denoise.enabled || self.render.toon.enabled
+    }
+}
+
+/// Lee líneas `clave = valor` (subconjunto de TOML): ignora comentarios `#`
+/// y líneas vacías, y quita las comillas de los valores de texto.
+/// Devuelve (número de línea, clave, valor).
+pub fn parse_key_values(text: &str) -> Vec<(usize, String, String)> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().trim_matches('"');
+            Some((i + 1, key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Preferencias con varios valores fuera de los de por defecto
+    fn tweaked() -> Settings {
+        let mut settings = Settings::default();
+        settings.render.shadows = true;
+        settings.render.samples_per_pixel = 4;
+        settings.render.fog.color = Some(raylib::prelude::Vector3::new(0.5, 0.6, 0.7));
+        settings.tone_map.operator = ToneMapping::Reinhard;
+        settings.present_mode = PresentMode::Integer;
+        settings.record_dir = "capturas".to_string();
+        settings.day.set_hour(6.5);
+        settings
+    }
+
+    #[test]
+    fn saved_settings_load_back() {
+        let path = std::env::temp_dir().join(format!("raytracer_settings_{}", std::process::id()));
+        let settings = tweaked();
+        settings.save(&path).unwrap();
+        let loaded = Settings::load(&path);
+        let _ = fs::remove_file(&path);
+        let expected = Settings {
+            render: RenderSettings {
+                sky: settings.day.sky(),
+                ..settings.render
+            },
+            ..settings
+        };
+        assert_eq!(loaded, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ron_fills_missing_fields_and_ignores_unknown_ones() {
+        let settings: Settings =
+            ron::from_str("(fxaa: false, future_option: 3, render: (shadows: true))").unwrap();
+        assert!(!settings.fxaa);
+        assert!(settings.render.shadows);
+        assert_eq!(settings.bloom, BloomSettings::default());
+    }
+}
@@ -0,0 +1,161 @@
+// shared_scene.rs - Escena que edita la interfaz y copia que traza el hilo de render
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::block::Block;
+use crate::block_registry::BlockDefinition;
+use crate::scene::Scene;
+
+/// Edición ya hecha en la escena de la interfaz y pendiente en la copia
+#[derive(Debug, Clone)]
+enum SceneEdit {
+    Place(Block),
+    Remove(usize),
+    Replace(usize, Block),
+    AddToPalette(BlockDefinition),
+}
+
+impl SceneEdit {
+    fn apply(self, scene: &mut Scene) {
+        match self {
+            SceneEdit::Place(block) => {
+                scene.place_block(block);
+            }
+            SceneEdit::Remove(index) => {
+                scene.remove_block(index);
+            }
+            SceneEdit::Replace(index, block) => {
+                scene.replace_block(index, block);
+            }
+            SceneEdit::AddToPalette(definition) => scene.add_to_palette(&definition),
+        }
+    }
+}
+
+/// Escena de la interfaz junto a la copia que se comparte con el hilo de
+/// render. Las ediciones cambian enseguida la de la interfaz y quedan en
+/// cola; `snapshot` las repite sobre la copia cuando el hilo ya la soltó,
+/// así editar mientras se traza un frame no clona la escena entera. Solo se
+/// clona si la copia sigue compartida al pedirla (un frame en curso).
+#[derive(Debug)]
+pub struct SharedScene {
+    scene: Scene,
+    snapshot: Arc<Scene>,
+    pending: Vec<SceneEdit>,
+}
+
+impl SharedScene {
+    pub fn new(scene: Scene) -> Self {
+        Self {
+            snapshot: Arc::new(scene.clone()),
+            scene,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Cambia la escena entera (otra escena, una carga desde archivo)
+    pub fn replace(&mut self, scene: Scene) {
+        *self = Self::new(scene);
+    }
+
+    /// Ver `Scene::place_block`
+    pub fn place_block(&mut self, block: Block) -> Option<usize> {
+        let index = self.scene.place_block(block.clone())?;
+        self.pending.push(SceneEdit::Place(block));
+        Some(index)
+    }
+
+    /// Ver `Scene::remove_block`
+    pub fn remove_block(&mut self, index: usize) -> Block {
+        let removed = self.scene.remove_block(index);
+        self.pending.push(SceneEdit::Remove(index));
+        removed
+    }
+
+    /// Ver `Scene::replace_block`
+    pub fn replace_block(&mut self, index: usize, block: Block) -> Block {
+        let previous = self.scene.replace_block(index, block.clone());
+        self.pending.push(SceneEdit::Replace(index, block));
+        previous
+    }
+
+    /// Ver `Scene::add_to_palette`
+    pub fn add_to_palette(&mut self, definition: &BlockDefinition) {
+        self.scene.add_to_palette(definition);
+        self.pending
+            .push(SceneEdit::AddToPalette(definition.clone()));
+    }
+
+    /// Copia para un trabajo de render, al día con las ediciones. El hilo
+    /// suelta la del trabajo anterior antes de devolver el frame, así que
+    /// entre frames la copia es única y las ediciones se aplican en el lugar.
+    pub fn snapshot(&mut self) -> Arc<Scene> {
+        if !self.pending.is_empty() {
+            if Arc::strong_count(&self.snapshot) == 1 {
+                // Con solo referencias `Weak` (la acumulación) `make_mut`
+                // mueve la escena sin clonarla
+                let snapshot = Arc::make_mut(&mut self.snapshot);
+                for edit in self.pending.drain(..) {
+                    edit.apply(snapshot);
+                }
+            } else {
+                self.snapshot = Arc::new(self.scene.clone());
+                self.pending.clear();
+            }
+        }
+        Arc::clone(&self.snapshot)
+    }
+}
+
+impl Deref for SharedScene {
+    type Target = Scene;
+
+    fn deref(&self) -> &Scene {
+        &self.scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_types::BlockType;
+    use raylib::prelude::Vector3;
+
+    fn stone(x: f32) -> Block {
+        BlockType::Stone.to_block(Vector3::new(x, 0.0, 0.0), 1.0)
+    }
+
+    #[test]
+    fn edits_during_a_frame_reach_the_next_snapshot_in_place() {
+        let mut shared = SharedScene::new(Scene::new((0..4).map(|x| stone(x as f32)).collect()));
+        let rendering = shared.snapshot();
+
+        assert!(shared.place_block(stone(6.0)).is_some());
+        shared.remove_block(1);
+        shared.replace_block(0, BlockType::Glass.to_block(Vector3::zero(), 1.0));
+        // El frame en curso sigue con la escena de antes
+        assert_eq!(rendering.len(), 4);
+        assert_eq!(shared.len(), 4);
+        assert_ne!(*rendering, *shared);
+
+        // Con el frame entregado la copia es única: se edita sin clonarla
+        let address = Arc::as_ptr(&rendering);
+        drop(rendering);
+        let next = shared.snapshot();
+        assert_eq!(Arc::as_ptr(&next), address);
+        assert_eq!(*next, *shared);
+    }
+
+    #[test]
+    fn a_shared_snapshot_is_copied_once() {
+        let mut shared = SharedScene::new(Scene::new(vec![stone(0.0)]));
+        let rendering = shared.snapshot();
+        shared.place_block(stone(1.0));
+        let next = shared.snapshot();
+        assert!(!Arc::ptr_eq(&rendering, &next));
+        assert_eq!(*next, *shared);
+        assert_eq!(rendering.len(), 1);
+        // Sin ediciones nuevas no hay otra copia
+        assert!(Arc::ptr_eq(&next, &shared.snapshot()));
+    }
+}
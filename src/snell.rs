@@ -1,15 +1,123 @@
 // snell.rs - Módulo de raytracing optimizado y reorganizado
+use crate::beacon::beam_glow;
 use crate::block::Block;
+use crate::bvh::BvhItem;
+use crate::caustics::caustic_factor;
+use crate::day_cycle::SkyModel;
 use crate::light::Light;
+use crate::material::Material;
+use crate::ore::speck_at;
+use crate::portal::nearby_portals;
 use crate::ray_intersect::{Intersect, RayIntersect};
-use crate::textures::TextureManager;
+use crate::render_context::RenderContext;
+use crate::render_settings::RenderSettings;
+use crate::rng::{Rng, splitmix64};
+use crate::scene::Scene;
+use crate::toon::ToonShading;
 use raylib::prelude::*;
 
 // === CONSTANTES ===
-const MAX_DISTANCE: f32 = 50.0;
-const EPSILON: f32 = 1e-4;
-const MIN_REFLECTION_THRESHOLD: f32 = 0.05;
-const MIN_SPECULAR_THRESHOLD: f32 = 5.0;
+/// Alcance por defecto de los rayos (`RenderSettings::max_distance`) y de la
+/// selección de bloques
+pub const MAX_DISTANCE: f32 = 50.0;
+const MAX_INTERIOR_SKIPS: usize = 16;
+/// Diferencia de distancia bajo la cual dos caras se consideran la misma
+/// (una cara interior pegada a la de un bloque opaco)
+const COINCIDENT_DISTANCE: f32 = 1e-3;
+/// Alcance de los rayos de oclusión ambiental
+const AO_DISTANCE: f32 = 1.0;
+/// Un impacto más cerca que esto corta la búsqueda del más cercano
+const NEAR_HIT_DISTANCE: f32 = 0.1;
+/// Pasado el impacto más cercano (o `NEAR_HIT_DISTANCE`), cuánto más se
+/// sigue recorriendo la grilla: dos veces la ventana de caras coincidentes
+/// y un margen para los redondeos. Un bloque más lejos ya no puede cambiar
+/// qué impacto gana, así que la grilla da lo mismo que probar todos.
+const GRID_STOP_MARGIN: f32 = 2.0 * COINCIDENT_DISTANCE + 1e-3;
+/// Ancho (en UV) de la franja de escarcha junto al borde de una cara
+const FROST_WIDTH: f32 = 0.3;
+
+// === ESTADÍSTICAS ===
+
+/// Rayos trazados por categoría
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RayCounts {
+    pub primary: u64,
+    pub shadow: u64,
+    pub reflection: u64,
+    pub refraction: u64,
+    pub ambient_occlusion: u64,
+    /// Caminos terminados (rayos que no siguieron rebotando) y la suma de
+    /// la profundidad a la que terminó cada uno
+    pub path_ends: u64,
+    pub path_depth: u64,
+    /// Rebotes cortados por la ruleta rusa
+    pub roulette: u64,
+    /// Pruebas de intersección rayo-objeto de la búsqueda del impacto más
+    /// cercano (no son rayos: no cuentan en `total`)
+    pub intersection_tests: u64,
+}
+
+impl RayCounts {
+    pub fn total(&self) -> u64 {
+        self.primary + self.shadow + self.reflection + self.refraction + self.ambient_occlusion
+    }
+
+    pub fn add(&mut self, other: &RayCounts) {
+        self.primary += other.primary;
+        self.shadow += other.shadow;
+        self.reflection += other.reflection;
+        self.refraction += other.refraction;
+        self.ambient_occlusion += other.ambient_occlusion;
+        self.path_ends += other.path_ends;
+        self.path_depth += other.path_depth;
+        self.roulette += other.roulette;
+        self.intersection_tests += other.intersection_tests;
+    }
+
+    /// Profundidad media a la que terminan los caminos (0 = sin rebotes)
+    pub fn average_bounce_depth(&self) -> f32 {
+        if self.path_ends == 0 {
+            0.0
+        } else {
+            self.path_depth as f32 / self.path_ends as f32
+        }
+    }
+}
+
+thread_local! {
+    /// Cada hilo cuenta sin sincronizarse; el renderer junta los totales
+    static RAY_COUNTS: std::cell::Cell<RayCounts> = std::cell::Cell::new(RayCounts::default());
+}
+
+#[inline]
+fn count_rays(update: impl FnOnce(&mut RayCounts)) {
+    RAY_COUNTS.with(|counts| {
+        let mut value = counts.get();
+        update(&mut value);
+        counts.set(value);
+    });
+}
+
+/// Cuenta un camino que termina a profundidad `depth`
+#[inline]
+fn count_path_end(depth: u32) {
+    count_rays(|counts| {
+        counts.path_ends += 1;
+        counts.path_depth += depth as u64;
+    });
+}
+
+/// Devuelve los rayos contados en el hilo actual y reinicia el contador
+pub fn take_ray_counts() -> RayCounts {
+    RAY_COUNTS.with(|counts| counts.replace(RayCounts::default()))
+}
+
+/// Pruebas de intersección contadas hasta ahora en el hilo actual, sin
+/// reiniciar; la diferencia entre dos lecturas es el costo de lo trazado
+/// en el medio
+pub fn intersection_tests() -> u64 {
+    RAY_COUNTS.with(|counts| counts.get().intersection_tests)
+}
 
 // === FUNCIONES DE FÍSICA ÓPTICA ===
 
@@ -47,27 +155,210 @@ pub fn refract(incident: &Vector3, normal: &Vector3, refractive_index: f32) -> V
 
 // === FUNCIONES DE INTERSECCIÓN ===
 
-/// Encuentra la intersección más cercana en la escena
+/// Encuentra la intersección más cercana en la escena.
+/// Las caras interiores entre bloques del mismo medio transparente se
+/// atraviesan sin detenerse, así dos vidrios juntos se comportan como uno solo.
+/// Devuelve también el índice de objeto golpeado (ver `Scene`); si es un
+/// bloque, el impacto lleva además su `block_id`. La distancia del impacto
+/// se mide desde `origin`, sumando los tramos de las caras saltadas.
 #[inline]
-fn find_closest_intersection<'a>(
+pub fn find_closest_intersection<'a>(
     origin: &Vector3,
     dir: &Vector3,
-    scene: &'a [Block],
-) -> Option<Intersect<'a>> {
-    let mut closest: Option<Intersect<'a>> = None;
-    let mut min_distance = MAX_DISTANCE;
+    scene: &'a Scene,
+    render_settings: &RenderSettings,
+) -> Option<(usize, Intersect<'a>)> {
+    let mut origin = *origin;
+    let mut travelled = 0.0;
 
-    for block in scene {
-        let hit = block.ray_intersect(origin, dir);
-        if hit.is_intersecting && hit.distance < min_distance {
+    for _ in 0..MAX_INTERIOR_SKIPS {
+        let remaining = render_settings.max_distance - travelled;
+        let (index, mut hit) = find_closest_surface(&origin, dir, scene, remaining)?;
+        if !hit.interior_face {
+            hit.distance += travelled;
+            return Some((index, hit));
+        }
+        origin = hit.point + *dir * render_settings.ray_epsilon;
+        travelled += hit.distance + render_settings.ray_epsilon;
+    }
+
+    None
+}
+
+/// Intersección más cercana sin considerar caras interiores
+#[inline]
+fn find_closest_surface<'a>(
+    origin: &Vector3,
+    dir: &Vector3,
+    scene: &'a Scene,
+    max_distance: f32,
+) -> Option<(usize, Intersect<'a>)> {
+    let mut closest: Option<(usize, Intersect<'a>)> = None;
+    let mut min_distance = max_distance;
+    let mut tests = 0;
+    let outside = bvh_candidates(origin, dir, scene, max_distance + GRID_STOP_MARGIN);
+    let first_primitive = outside.partition_point(|&index| index < scene.len());
+    let (outside_blocks, primitives) = outside.split_at(first_primitive);
+
+    for (index, mut hit) in block_hits(origin, dir, scene, max_distance, outside_blocks, &mut tests)
+    {
+        // Si una cara interior coincide con la de otro bloque, gana la otra:
+        // saltar la interior dejaría el origen dentro del vecino
+        let hides_interior = closest.as_ref().is_some_and(|(_, other)| {
+            other.interior_face
+                && !hit.interior_face
+                && hit.distance - other.distance < COINCIDENT_DISTANCE
+        });
+        if hit.distance < min_distance || hides_interior {
             min_distance = hit.distance;
+            hit.block_id = Some(index as u32);
 
             // Early termination para objetos muy cercanos
-            if hit.distance < 0.1 {
-                return Some(hit);
+            if hit.distance < NEAR_HIT_DISTANCE && !hit.interior_face {
+                count_rays(|counts| counts.intersection_tests += tests);
+                return Some((index, hit));
             }
 
-            closest = Some(hit);
+            closest = Some((index, hit));
+        }
+    }
+
+    for &index in primitives {
+        tests += 1;
+        let hit = scene
+            .primitive(index)
+            .expect("índice de primitiva")
+            .ray_intersect(origin, dir);
+        if hit.is_intersecting && hit.distance < min_distance {
+            min_distance = hit.distance;
+            closest = Some((index, hit));
+        }
+    }
+
+    count_rays(|counts| counts.intersection_tests += tests);
+    closest
+}
+
+/// Impactos del rayo con los bloques, en orden de índice. Con la grilla al
+/// día se prueban `outside` (los bloques fuera de la grilla que encontró la
+/// BVH) y los de las celdas que cruza el rayo hasta `GRID_STOP_MARGIN`
+/// después del impacto más cercano; si no, todos.
+fn block_hits<'a>(
+    origin: &Vector3,
+    dir: &Vector3,
+    scene: &'a Scene,
+    max_distance: f32,
+    outside: &[usize],
+    tests: &mut u64,
+) -> Vec<(usize, Intersect<'a>)> {
+    let blocks: &'a [Block] = scene.as_slice();
+    let mut hits = Vec::new();
+    let Some(grid) = scene.grid() else {
+        *tests += blocks.len() as u64;
+        for (index, block) in blocks.iter().enumerate() {
+            let hit = block.ray_intersect(origin, dir);
+            if hit.is_intersecting {
+                hits.push((index, hit));
+            }
+        }
+        return hits;
+    };
+
+    let mut test = |id: u32| {
+        *tests += 1;
+        let hit = blocks[id as usize].ray_intersect(origin, dir);
+        hit.is_intersecting.then_some((id as usize, hit))
+    };
+    hits.extend(outside.iter().filter_map(|&index| test(index as u32)));
+    let mut nearest = hits
+        .iter()
+        .map(|(_, hit)| hit.distance)
+        .fold(f32::INFINITY, f32::min);
+    let reach = max_distance + GRID_STOP_MARGIN;
+    grid.walk(origin, dir, reach, |t_enter, ids| {
+        if t_enter > nearest.max(NEAR_HIT_DISTANCE) + GRID_STOP_MARGIN {
+            return false;
+        }
+        for (index, hit) in ids.iter().filter_map(|&id| test(id)) {
+            nearest = nearest.min(hit.distance);
+            hits.push((index, hit));
+        }
+        true
+    });
+    // Una celda puede repetirse si el rayo pasa por una arista
+    hits.sort_by_key(|(index, _)| *index);
+    hits.dedup_by_key(|(index, _)| *index);
+    hits
+}
+
+/// Objetos que el rayo puede tocar antes de `max_distance`, por índice de
+/// objeto y en orden: los bloques de las celdas que cruza y lo que encuentra
+/// la BVH, o todos si no están al día
+fn object_candidates(
+    origin: &Vector3,
+    dir: &Vector3,
+    scene: &Scene,
+    max_distance: f32,
+) -> Vec<usize> {
+    let Some(grid) = scene.grid() else {
+        return (0..scene.object_count()).collect();
+    };
+    let reach = max_distance + GRID_STOP_MARGIN;
+    let mut candidates = bvh_candidates(origin, dir, scene, reach);
+    grid.walk(origin, dir, reach, |_, ids| {
+        candidates.extend(ids.iter().map(|&id| id as usize));
+        true
+    });
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+/// Objetos fuera de la grilla que el rayo puede tocar antes de `reach`, por
+/// índice de objeto y en orden: los bloques que no entran en una celda y
+/// las primitivas que encuentra la BVH. Sin BVH al día, todas las
+/// primitivas (los bloques los prueba uno por uno `block_hits`).
+fn bvh_candidates(origin: &Vector3, dir: &Vector3, scene: &Scene, reach: f32) -> Vec<usize> {
+    let first_primitive = scene.len();
+    let Some(bvh) = scene.bvh() else {
+        return (first_primitive..scene.object_count()).collect();
+    };
+    let mut candidates = Vec::new();
+    bvh.visit(origin, dir, reach, |item| {
+        candidates.push(match item {
+            BvhItem::Block(id) => id as usize,
+            BvhItem::Primitive(i) => first_primitive + i as usize,
+        })
+    });
+    candidates.sort_unstable();
+    candidates
+}
+
+/// Bloque seleccionado por un rayo de la cámara
+#[derive(Debug, Clone, Copy)]
+pub struct BlockPick {
+    pub index: usize,
+    pub distance: f32,
+    /// Normal de la cara golpeada (hacia afuera del bloque)
+    pub normal: Vector3,
+}
+
+/// Devuelve el bloque más cercano que toca el rayo, su distancia y la cara.
+/// Se usa para seleccionar bloques (ej: el que está bajo la mira).
+pub fn pick_block(origin: &Vector3, dir: &Vector3, scene: &[Block]) -> Option<BlockPick> {
+    let mut closest: Option<BlockPick> = None;
+
+    for (index, block) in scene.iter().enumerate() {
+        let hit = block.ray_intersect(origin, dir);
+        if hit.is_intersecting
+            && hit.distance < MAX_DISTANCE
+            && closest.is_none_or(|pick| hit.distance < pick.distance)
+        {
+            closest = Some(BlockPick {
+                index,
+                distance: hit.distance,
+                normal: hit.normal,
+            });
         }
     }
 
@@ -82,7 +373,10 @@ fn calculate_light_contribution<'a>(
     light: &Light,
     base_color: &Vector3,
     view_dir: &Vector3,
+    context: &RenderContext,
+    rng: &mut Rng,
 ) -> Vector3 {
+    let render_settings = context.settings;
     // Verificar que el material existe
     let material = match intersect.material {
         Some(mat) => mat,
@@ -92,31 +386,430 @@ fn calculate_light_contribution<'a>(
     let light_dir = (light.position - intersect.point).normalized();
     let light_distance = (light.position - intersect.point).length();
 
+    // Foco: fuera de su cono no ilumina (ni hace falta el rayo de sombra)
+    let cone = light.spot.map_or(1.0, |spot| spot.factor(&-light_dir));
+    if cone <= 0.0 {
+        return Vector3::zero();
+    }
+
+    // De espaldas a la luz solo llega lo que se filtra por la superficie
+    let n_dot_l_raw = intersect.normal.dot(light_dir);
+    let back_lit = n_dot_l_raw < 0.0;
+    if back_lit && material.translucency <= 0.0 {
+        return Vector3::zero();
+    }
+
+    // Sombra: cuánta luz llega sin ser bloqueada. La que entra por detrás
+    // atraviesa el propio bloque: el rayo sale del otro lado y no lo cuenta.
+    let visibility = if render_settings.shadows {
+        let (side, own_block) = if back_lit {
+            (-1.0, intersect.block_id)
+        } else {
+            (1.0, None)
+        };
+        let shadow_origin =
+            intersect.point + intersect.normal * (render_settings.ray_epsilon * 10.0 * side);
+        // Sombras suaves: el rayo apunta a un punto al azar de la esfera de
+        // la luz; el sombreado sigue usando el centro
+        let (shadow_dir, shadow_distance) =
+            if render_settings.soft_shadows && render_settings.light_radius > 0.0 {
+                let target = light.position + rng.in_unit_sphere() * render_settings.light_radius;
+                let to_target = target - shadow_origin;
+                (to_target.normalized(), to_target.length())
+            } else {
+                (light_dir, light_distance)
+            };
+        light_visibility(
+            &shadow_origin,
+            &shadow_dir,
+            shadow_distance,
+            own_block,
+            context,
+        )
+    } else {
+        1.0
+    };
+    if visibility <= 0.0 {
+        return Vector3::zero();
+    }
+
     // Atenuación cuadrática por distancia
-    let attenuation = 1.0 / (1.0 + 0.01 * light_distance * light_distance);
+    let falloff = light.attenuation.unwrap_or(render_settings.light_attenuation);
+    let attenuation = cone * visibility / (1.0 + falloff * light_distance * light_distance);
 
     // Componente difusa (Lambert)
-    let n_dot_l = intersect.normal.dot(light_dir).max(0.0);
+    let mut n_dot_l = n_dot_l_raw.max(0.0);
+    // Modo dibujo: la luz cae en bandas planas en vez de un degradado
+    if render_settings.toon.enabled {
+        n_dot_l = render_settings.toon.quantize_diffuse(n_dot_l);
+    }
     let diffuse_intensity = n_dot_l * light.intensity * attenuation;
 
     let mut color = *base_color * light.color * diffuse_intensity * material.albedo[0];
 
+    // Translucidez: la luz que llega por detrás se filtra a través de la superficie
+    if n_dot_l_raw < 0.0 && material.translucency > 0.0 {
+        color += *base_color * light.color * (-n_dot_l_raw) * material.translucency * attenuation;
+    }
+
     // Componente especular (Blinn-Phong) solo si es significativo
-    if material.specular > MIN_SPECULAR_THRESHOLD && diffuse_intensity > 0.1 {
+    if material.specular > render_settings.min_specular && diffuse_intensity > 0.1 {
         let view_direction = (-*view_dir).normalized();
         let half_vector = (light_dir + view_direction).normalized();
         let n_dot_h = intersect.normal.dot(half_vector).max(0.0);
-        let spec = n_dot_h.powf(material.specular);
+        let mut spec = n_dot_h.powf(material.specular);
+        if render_settings.toon.enabled {
+            spec = ToonShading::hard_highlight(spec);
+        }
 
-        color = color + light.color * spec * material.albedo[1] * attenuation;
+        color += light.color * spec * material.albedo[1] * attenuation;
     }
 
     color
 }
 
+/// Fracción de luz que llega desde `origin` hasta una luz a `distance`.
+/// Los objetos transparentes dejan pasar su transparencia; los emisivos no
+/// hacen sombra (la luz vive dentro de ellos), y tampoco `skip_block`. El
+/// primer material con cáusticas que se cruza reparte además la luz en
+/// ondas según el tiempo.
+fn light_visibility(
+    origin: &Vector3,
+    dir: &Vector3,
+    distance: f32,
+    skip_block: Option<u32>,
+    context: &RenderContext,
+) -> f32 {
+    count_rays(|counts| counts.shadow += 1);
+    let scene = context.refs.scene;
+    let mut visibility = 1.0;
+    // Un bloque de agua de varios de alto no multiplica el patrón
+    let mut caustics_done = false;
+    let mut pass_through = |material: &Material| {
+        let mut factor = material.transparency;
+        if material.caustics && !caustics_done {
+            caustics_done = true;
+            factor *= caustic_factor(material, origin, dir, context.time);
+        }
+        factor
+    };
+
+    // Primero los bloques y después las primitivas, cada uno en su orden
+    for index in object_candidates(origin, dir, scene, distance) {
+        let (hit, material) = match scene.block(index) {
+            Some(block) => {
+                if block.emission.is_some()
+                    || block.material.emission_strength > 0.0
+                    || skip_block == Some(index as u32)
+                {
+                    continue;
+                }
+                (block.ray_intersect(origin, dir), &block.material)
+            }
+            None => {
+                let primitive = scene.primitive(index).expect("índice de primitiva");
+                let material = primitive.material();
+                if primitive.emission().is_some() || material.emission_strength > 0.0 {
+                    continue;
+                }
+                (primitive.ray_intersect(origin, dir), material)
+            }
+        };
+        if hit.is_intersecting && hit.distance < distance {
+            visibility *= pass_through(material);
+            if visibility < 0.01 {
+                return 0.0;
+            }
+        }
+    }
+
+    visibility
+}
+
+/// Coseno del radio angular del disco del sol del cielo analítico y del
+/// borde de su transición suave
+const SUN_DISK_COS: f32 = 0.99975;
+const SUN_DISK_EDGE_COS: f32 = 0.9995;
+
+/// Colores del cielo analítico a pleno día
+const DAY_ZENITH: Vector3 = Vector3 { x: 0.12, y: 0.32, z: 0.78 };
+const DAY_HORIZON: Vector3 = Vector3 { x: 0.68, y: 0.8, z: 0.95 };
+/// Horizonte del lado del sol cuando está bajo
+const SUNSET_HORIZON: Vector3 = Vector3 { x: 1.0, y: 0.45, z: 0.2 };
+/// Bruma que blanquea el horizonte con la turbidez
+const HAZE: Vector3 = Vector3 { x: 0.9, y: 0.9, z: 0.88 };
+/// Piso del cielo de noche (las estrellas y la luna van aparte)
+const NIGHT_SKY: Vector3 = Vector3 { x: 0.01, y: 0.015, z: 0.04 };
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Cielo analítico para un rayo `dir` con el sol en `sun_dir` (ambos
+/// normalizados). Inspirado en Rayleigh: azul profundo en el cenit y claro
+/// en el horizonte, más oscuro y cálido cuanto más bajo está el sol, con un
+/// halo (Mie) alrededor del disco que se abre con la `turbidity`.
+pub fn sky_color(dir: &Vector3, sun_dir: &Vector3, turbidity: f32) -> Vector3 {
+    let elevation = sun_dir.y;
+    // Día pleno con el sol a más de ~15°; se apaga pasado el crepúsculo
+    let daylight = smoothstep(-0.15, 0.25, elevation);
+    // Qué tan bajo está el sol: 1 en el horizonte, 0 desde ~25°
+    let low_sun = 1.0 - smoothstep(0.0, 0.45, elevation.abs());
+    let haze = ((turbidity - 2.0) / 8.0).clamp(0.0, 1.0);
+
+    // Del lado del sol el horizonte se entibia al amanecer y al atardecer
+    let flat_dir = Vector3::new(dir.x, 0.0, dir.z);
+    let flat_sun = Vector3::new(sun_dir.x, 0.0, sun_dir.z);
+    let toward_sun = if flat_dir.length() > 1e-4 && flat_sun.length() > 1e-4 {
+        flat_dir.normalized().dot(flat_sun.normalized()) * 0.5 + 0.5
+    } else {
+        0.5
+    };
+    let horizon = DAY_HORIZON
+        .lerp(HAZE, haze)
+        .lerp(SUNSET_HORIZON, low_sun * (0.3 + 0.7 * toward_sun * toward_sun));
+    let zenith = DAY_ZENITH.lerp(HAZE, haze * 0.3) * (1.0 - 0.5 * low_sun);
+
+    // Bajo el horizonte se ve el mismo horizonte, algo más oscuro
+    let up = dir.y.max(0.0);
+    let t = (1.0 - up).powi(4);
+    let below = 1.0 - 0.4 * (-dir.y).max(0.0);
+    let mut color = (zenith * (1.0 - t) + horizon * t) * below * daylight;
+
+    // Halo y disco del sol, solo mientras está sobre el horizonte
+    let sun_visible = smoothstep(-0.02, 0.02, elevation);
+    let sun_tint = Vector3::new(1.0, 0.95, 0.85).lerp(SUNSET_HORIZON, low_sun * 0.6);
+    let cos_theta = dir.dot(*sun_dir);
+    let g = 0.8 + 0.15 * (1.0 - haze);
+    let mie = (1.0 - g * g) / (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5);
+    color += sun_tint * (mie * 0.004 * (0.5 + haze) * sun_visible);
+    let disk = smoothstep(SUN_DISK_EDGE_COS, SUN_DISK_COS, cos_theta);
+    color += sun_tint * (disk * 8.0 * sun_visible);
+
+    color + NIGHT_SKY * (1.0 - daylight)
+}
+
+/// Celdas por lado de cada cara del cubo sobre el que se reparten las
+/// estrellas (una como mucho por celda)
+const STAR_GRID: f32 = 100.0;
+/// Radio de una estrella en fracciones de celda; unas pocas son más grandes
+const STAR_RADIUS: f32 = 0.15;
+const BIG_STAR_RADIUS: f32 = 0.25;
+const BIG_STAR_CHANCE: f32 = 0.06;
+
+/// Campo de estrellas fijo para la dirección `dir` (normalizada). La esfera
+/// se proyecta sobre un cubo cuyas caras se dividen en una grilla; cada
+/// celda tiene o no una estrella según un hash de la celda, en una posición
+/// corrida al azar dentro de ella. Como depende solo de la celda, las
+/// estrellas no titilan al girar la cámara. `density` es la fracción de
+/// celdas con estrella.
+pub fn star_field(dir: &Vector3, density: f32, brightness: f32) -> Vector3 {
+    if density <= 0.0 || brightness <= 0.0 {
+        return Vector3::zero();
+    }
+
+    // Cara del cubo según el eje dominante y (u, v) en [-1, 1] sobre ella
+    let (ax, ay, az) = (dir.x.abs(), dir.y.abs(), dir.z.abs());
+    let (face, u, v) = if ax >= ay && ax >= az {
+        (if dir.x > 0.0 { 0 } else { 1 }, dir.y / ax, dir.z / ax)
+    } else if ay >= az {
+        (if dir.y > 0.0 { 2 } else { 3 }, dir.x / ay, dir.z / ay)
+    } else {
+        (if dir.z > 0.0 { 4 } else { 5 }, dir.x / az, dir.y / az)
+    };
+    let grid_u = (u * 0.5 + 0.5) * STAR_GRID;
+    let grid_v = (v * 0.5 + 0.5) * STAR_GRID;
+    let cell_u = grid_u.floor().clamp(0.0, STAR_GRID - 1.0);
+    let cell_v = grid_v.floor().clamp(0.0, STAR_GRID - 1.0);
+
+    let mut hash = splitmix64(((face as u64) << 40) | ((cell_u as u64) << 20) | cell_v as u64);
+    // Los 24 bits altos de cada paso del hash como número en [0, 1)
+    let mut next = || {
+        hash = splitmix64(hash);
+        (hash >> 40) as f32 / (1u64 << 24) as f32
+    };
+    if next() >= density {
+        return Vector3::zero();
+    }
+
+    // Centro lejos del borde de la celda, así la estrella no se corta
+    let center_u = 0.25 + 0.5 * next();
+    let center_v = 0.25 + 0.5 * next();
+    let big = next() < BIG_STAR_CHANCE;
+    let radius = if big { BIG_STAR_RADIUS } else { STAR_RADIUS };
+    let du = grid_u - cell_u - center_u;
+    let dv = grid_v - cell_v - center_v;
+    let distance = (du * du + dv * dv).sqrt();
+    if distance >= radius {
+        return Vector3::zero();
+    }
+
+    let magnitude = (0.3 + 0.7 * next()) * if big { 2.0 } else { 1.0 };
+    // Entre blanco azulado y amarillento
+    let tint = Vector3::new(0.85, 0.9, 1.0).lerp(Vector3::new(1.0, 0.9, 0.7), next());
+    tint * (brightness * magnitude * (1.0 - distance / radius))
+}
+
+/// Color de un rayo que no chocó con nada, según el modelo de cielo, con
+/// las estrellas de noche (se apagan mientras sale el sol)
+fn background(dir: &Vector3, render_settings: &RenderSettings) -> Vector3 {
+    let sky = &render_settings.sky;
+    let color = match render_settings.sky_model {
+        SkyModel::Gradient => sky.color(dir),
+        SkyModel::Procedural => {
+            sky_color(dir, &sky.sun_direction, render_settings.turbidity) + sky.moon(dir)
+        }
+    };
+    let night = 1.0 - smoothstep(-0.15, 0.05, sky.sun_direction.y);
+    if night > 0.0 {
+        color + star_field(dir, render_settings.star_density, render_settings.star_brightness)
+            * night
+    } else {
+        color
+    }
+}
+
+/// Asimetría de la dispersión de los rayos volumétricos (Henyey-Greenstein):
+/// 0 dispersa igual hacia todos lados; cerca de 1, sobre todo hacia adelante,
+/// así los haces se ven más mirando hacia el sol
+const GOD_RAY_ANISOTROPY: f32 = 0.5;
+
+/// Luz del sol dispersada por el aire a lo largo de un rayo primario, hasta
+/// `max_t` o `god_ray_distance`. Avanza en pasos fijos y en cada uno mira si
+/// el sol se ve; el inicio se corre al azar dentro del primer paso para
+/// cambiar las bandas por ruido.
+fn god_rays(
+    origin: &Vector3,
+    dir: &Vector3,
+    max_t: f32,
+    context: &RenderContext,
+    rng: &mut Rng,
+) -> Vector3 {
+    let render_settings = context.settings;
+    let sky = &render_settings.sky;
+    let sun = sky.sun_direction;
+    if sun.y <= 0.0 || sky.sun_color == Vector3::zero() {
+        return Vector3::zero();
+    }
+
+    let steps = render_settings.god_ray_steps.max(1);
+    let step = max_t.min(render_settings.god_ray_distance) / steps as f32;
+    let start = rng.next_f32();
+    let mut lit = 0.0;
+    for i in 0..steps {
+        let point = *origin + *dir * ((i as f32 + start) * step);
+        lit += light_visibility(&point, &sun, render_settings.max_distance, None, context);
+    }
+
+    // Henyey-Greenstein escalada para que la dispersión uniforme valga 1
+    let g = GOD_RAY_ANISOTROPY;
+    let cos_theta = dir.dot(sun);
+    let phase = (1.0 - g * g) / (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5);
+
+    sky.sun_color * (lit * step * render_settings.god_ray_density * phase)
+}
+
+/// Oclusión ambiental: fracción de rayos cortos del hemisferio de la normal
+/// que no chocan con nada (1 = despejado, 0 = totalmente ocluido).
+/// Usa direcciones fijas para que la imagen no tenga ruido; con
+/// `ao_jitter` el patrón se gira al azar en cada punto.
+fn ambient_occlusion(
+    point: &Vector3,
+    normal: &Vector3,
+    context: &RenderContext,
+    rng: &mut Rng,
+) -> f32 {
+    const SAMPLES: usize = 8;
+    let (scene, render_settings) = (context.refs.scene, context.settings);
+
+    // Base ortonormal alrededor de la normal
+    let helper = if normal.y.abs() < 0.9 {
+        Vector3::new(0.0, 1.0, 0.0)
+    } else {
+        Vector3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = normal.cross(helper).normalized();
+    let bitangent = normal.cross(tangent);
+
+    let origin = *point + *normal * render_settings.ray_epsilon * 10.0;
+    count_rays(|counts| counts.ambient_occlusion += SAMPLES as u64);
+    let step = std::f32::consts::TAU / SAMPLES as f32;
+    let rotation = if render_settings.ao_jitter {
+        rng.next_f32() * step
+    } else {
+        0.0
+    };
+    let mut open = 0;
+    for i in 0..SAMPLES {
+        let phi = i as f32 * step + rotation;
+        // Inclinación alternada para cubrir el hemisferio
+        let elevation: f32 = if i % 2 == 0 { 0.35 } else { 0.8 };
+        let spread = (1.0 - elevation * elevation).sqrt();
+        let dir = (tangent * (phi.cos() * spread)
+            + bitangent * (phi.sin() * spread)
+            + *normal * elevation)
+            .normalized();
+
+        let blocked = object_candidates(&origin, &dir, scene, AO_DISTANCE)
+            .into_iter()
+            .any(|index| {
+                let (hit, material) = match scene.block(index) {
+                    Some(block) => (block.ray_intersect(&origin, &dir), &block.material),
+                    None => {
+                        let primitive = scene.primitive(index).expect("índice de primitiva");
+                        (primitive.ray_intersect(&origin, &dir), primitive.material())
+                    }
+                };
+                hit.is_intersecting && hit.distance < AO_DISTANCE && material.transparency < 0.5
+            });
+        if !blocked {
+            open += 1;
+        }
+    }
+
+    open as f32 / SAMPLES as f32
+}
+
+/// Luz del cielo que entra por los portales cercanos (ventanas y puertas)
+/// hasta `point`. Por cada portal, un rayo de sombra hacia un punto al azar
+/// de la abertura que sigue de largo: si sale al cielo, suma el color del
+/// cielo en esa dirección pesado por el ángulo sólido aproximado de la
+/// abertura y el coseno con la normal.
+fn portal_light(
+    point: &Vector3,
+    normal: &Vector3,
+    context: &RenderContext,
+    rng: &mut Rng,
+) -> Vector3 {
+    let render_settings = context.settings;
+    let origin = *point + *normal * render_settings.ray_epsilon * 10.0;
+    let mut light = Vector3::zero();
+    for portal in nearby_portals(context.refs.portals, point, normal) {
+        let target = portal.point_at(rng.next_f32(), rng.next_f32(), rng.next_f32());
+        let offset = target - origin;
+        let dir = offset.normalized();
+        let cos_theta = dir.dot(*normal);
+        if cos_theta <= 0.0 {
+            continue;
+        }
+        // Más allá de la abertura: tiene que verse el cielo, no otra pared
+        let visibility =
+            light_visibility(&origin, &dir, render_settings.max_distance, None, context);
+        if visibility <= 0.0 {
+            continue;
+        }
+        // Pegado al portal el ángulo sólido no pasa de una media esfera
+        let solid_angle = portal.area() / offset.dot(offset).max(1.0);
+        light += render_settings.sky.gradient(&dir)
+            * (visibility * cos_theta * solid_angle / std::f32::consts::PI);
+    }
+    light * render_settings.portal_strength
+}
+
 /// Obtiene el color base del material, aplicando texturas si existen
 #[inline]
-fn get_material_color<'a>(intersect: &Intersect<'a>, texture_manager: &TextureManager) -> Vector3 {
+fn get_material_color<'a>(intersect: &Intersect<'a>, context: &RenderContext) -> Vector3 {
     // Verificar que el material existe
     let material = match intersect.material {
         Some(mat) => mat,
@@ -127,61 +820,133 @@ fn get_material_color<'a>(intersect: &Intersect<'a>, texture_manager: &TextureMa
 
     // Aplicar textura si existe
     if let Some(texture_path) = &material.texture {
-        let texture_color = texture_manager.sample_texture(texture_path, intersect.u, intersect.v);
-        base_color = base_color * texture_color;
+        let texture_color = sample_material_texture(texture_path, intersect, context);
+        base_color *= texture_color;
+    }
+
+    if material.frost > 0.0 {
+        let frost = (material.frost * edge_frost(intersect.u, intersect.v)).min(1.0);
+        base_color = base_color.lerp(Vector3::one(), frost);
+    }
+
+    // Los puntos de mineral tapan la piedra
+    if let Some(specks) = material.specks
+        && speck_at(intersect.point, intersect.normal, intersect.u, intersect.v)
+    {
+        base_color = specks.color;
     }
 
     base_color
 }
 
-/// Color del cielo con gradiente basado en la dirección del rayo
-#[inline]
-fn sky_color(dir: &Vector3) -> Vector3 {
-    let t = (dir.y * 0.5 + 0.5).clamp(0.0, 1.0); // Mapear [-1,1] a [0,1]
-
-    // Gradiente de horizonte (naranja) a cenit (azul)
-    let horizon_color = Vector3::new(0.98, 0.92, 0.88); // Casi blanco con tono cálido
-    let zenith_color = Vector3::new(0.2, 0.4, 0.8);
+/// Cuánta escarcha hay en (u, v): 1 en el borde de la cara, baja suave
+/// hasta 0 a `FROST_WIDTH` de él (y es 0 en el centro)
+pub fn edge_frost(u: f32, v: f32) -> f32 {
+    let border = u.min(1.0 - u).min(v).min(1.0 - v).max(0.0);
+    let t = (1.0 - border / FROST_WIDTH).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
 
-    horizon_color * (1.0 - t) + zenith_color * t
+/// Color de la textura en el punto de impacto con el filtro elegido
+#[inline]
+fn sample_material_texture(path: &str, intersect: &Intersect, context: &RenderContext) -> Vector3 {
+    let texture_manager = context.refs.textures;
+    if context.settings.bilinear_filtering {
+        texture_manager.sample_texture(path, intersect.u, intersect.v)
+    } else {
+        texture_manager.sample_texture_nearest(path, intersect.u, intersect.v)
+    }
 }
 
 // === FUNCIONES PRINCIPALES DE RAYTRACING ===
 
-/// Raytracer principal con múltiples luces, reflexiones y transparencia + fake glow
+/// Raytracer principal con múltiples luces, reflexiones y transparencia + fake glow.
+/// `throughput` es el peso con que el color del rayo llega al pixel (1.0 en
+/// los primarios); con la ruleta rusa activa, los rebotes de poco peso se
+/// cortan al azar. La escena, las luces y los ajustes vienen en `context`,
+/// el mismo para todos los rayos del frame.
 pub fn trace_ray_multi_light(
     origin: Vector3,
     dir: Vector3,
     depth: u32,
-    max_depth: u32,
-    scene: &[Block],
-    lights: &[Light],
-    texture_manager: &TextureManager,
+    throughput: f32,
+    context: &RenderContext,
+    rng: &mut Rng,
 ) -> Vector3 {
+    let (scene, render_settings) = (context.refs.scene, context.settings);
+    if depth == 0 {
+        count_rays(|counts| counts.primary += 1);
+    }
+    let max_depth = render_settings.max_depth;
     if depth > max_depth {
-        return sky_color(&dir);
+        count_path_end(depth);
+        return background(&dir, render_settings);
+    }
+
+    let hit = find_closest_intersection(&origin, &dir, scene, render_settings);
+
+    // Haces de los faros (y en los rayos primarios, la luz volumétrica del
+    // sol) entre el origen y el impacto o el cielo
+    let max_t = hit.as_ref().map_or(render_settings.max_distance, |(_, hit)| hit.distance);
+    let mut glow = beam_glow(&origin, &dir, max_t, context.refs.beams);
+    if depth == 0 && render_settings.god_rays {
+        glow += god_rays(&origin, &dir, max_t, context, rng);
     }
 
-    let intersect = match find_closest_intersection(&origin, &dir, scene) {
-        Some(hit) => hit,
-        None => return sky_color(&dir),
+    let mut intersect = match hit {
+        Some((_, hit)) => hit,
+        None => {
+            count_path_end(depth);
+            return background(&dir, render_settings) + glow;
+        }
     };
+    // Los bloques animados se sombrean con el material de este frame
+    if let Some(id) = intersect.block_id
+        && let Some(animated) = context.refs.animated.get(id as usize)
+    {
+        intersect.material = Some(animated);
+    }
 
     let material = match intersect.material {
         Some(mat) => mat,
-        None => return sky_color(&dir),
+        None => {
+            count_path_end(depth);
+            return background(&dir, render_settings) + glow;
+        }
     };
 
-    let base_color = get_material_color(&intersect, texture_manager);
+    // Niebla sobre la distancia de este tramo: los rayos reflejados y
+    // refractados la suman sobre su propio recorrido al volver
+    let fog = render_settings.fog;
+
+    // Olas: solo la superficie (cara superior) se mueve
+    if material.waves > 0.0 && intersect.normal.y > 0.5 {
+        intersect.normal = ripple_normal(&intersect.point, material.waves, context.time);
+    }
+
+    let base_color = get_material_color(&intersect, context);
+
+    // Materiales sin iluminación: solo emisión, sin sombreado ni rebotes
+    if material.unlit {
+        let emission = material.emission_color.unwrap_or(material.diffuse);
+        let texture_color = match &material.texture {
+            Some(path) => sample_material_texture(path, &intersect, context),
+            None => Vector3::one(),
+        };
+        let unlit_color = emission * texture_color * material.emission_strength;
+        count_path_end(depth);
+        return fog.apply(unlit_color, intersect.distance, &render_settings.sky) + glow;
+    }
 
     // === iluminación directa ===
     let mut final_color = Vector3::zero();
+    let lights = context.refs.lights;
     for light in lights {
-        final_color =
-            final_color + calculate_light_contribution(&intersect, light, &base_color, &dir);
+        final_color +=
+            calculate_light_contribution(&intersect, light, &base_color, &dir, context, rng);
     }
     if !lights.is_empty() {
-        final_color = final_color / lights.len() as f32;
+        final_color /= lights.len() as f32;
     }
 
     // === Emisión basada en textura o diffuse ===
@@ -196,7 +961,7 @@ pub fn trace_ray_multi_light(
         };
 
         // Emisión directa
-        final_color = final_color + emission_base * material.emission_strength;
+        final_color += emission_base * material.emission_strength;
 
         // --- Fake glow extra ---
         let glow_strength = material.emission_strength;
@@ -205,85 +970,377 @@ pub fn trace_ray_multi_light(
         let dist = (intersect.point - origin).length();
         let dist_factor = 1.0 / (1.0 + 0.15 * dist);
 
-        final_color =
-            final_color + emission_base * glow_strength * angle_factor * dist_factor * 2.0;
+        final_color += emission_base * glow_strength * angle_factor * dist_factor * 2.0;
+    }
+
+    // Brillo propio de los puntos de mineral: se ven también a oscuras
+    if let Some(specks) = material.specks
+        && specks.glow > 0.0
+        && speck_at(intersect.point, intersect.normal, intersect.u, intersect.v)
+    {
+        final_color += specks.color * specks.glow;
     }
 
-    final_color = final_color + base_color * 0.08; // ambiente sutil
+    // Ambiente sutil (más tenue de noche), atenuado en rincones si la
+    // oclusión ambiental está activa
+    let ambient_occlusion = if render_settings.ambient_occlusion {
+        ambient_occlusion(&intersect.point, &intersect.normal, context, rng)
+    } else {
+        1.0
+    };
+    let ambient = render_settings.ambient_strength * render_settings.sky.ambient;
+    final_color += base_color * ambient * ambient_occlusion;
+
+    // Cielo que entra por ventanas y puertas cercanas
+    if render_settings.light_portals && !context.refs.portals.is_empty() {
+        final_color += base_color * portal_light(&intersect.point, &intersect.normal, context, rng);
+    }
 
     // === reflexión y refracción ===
     let mut reflection_color = Vector3::zero();
     let mut refraction_color = Vector3::zero();
     let mut fresnel = 0.0;
+    let mut spawned = false;
 
-    // Reflexión
-    if material.reflectivity > MIN_REFLECTION_THRESHOLD && depth < max_depth {
-        let reflected_dir = reflect(&dir, &intersect.normal).normalized();
-        let reflect_origin = intersect.point + intersect.normal * EPSILON;
+    // Con un efecto desactivado el material se combina como si no lo tuviera
+    let reflective =
+        render_settings.reflections && material.reflectivity > render_settings.min_reflectivity;
+    let transparent = render_settings.refractions && material.transparency > 0.01;
+
+    // Fresnel (Schlick) antes de trazar: también decide cuánto pesa cada
+    // rayo que sigue
+    let refracted_dir = if transparent {
+        refract(&dir, &intersect.normal, material.refractive_index)
+    } else {
+        Vector3::zero()
+    };
+    let refracts = refracted_dir.dot(refracted_dir) > 1e-6;
+    if transparent && depth < max_depth {
+        fresnel = if refracts {
+            let cos_i = (-dir.dot(intersect.normal)).abs().clamp(0.0, 1.0);
+            calculate_fresnel(cos_i, material.refractive_index)
+        } else {
+            1.0 // reflexión interna total
+        };
+    }
+
+    // Reflexión (los transparentes con `fresnel_reflection` reflejan lo que
+    // indica Fresnel aunque no sean reflectivos)
+    let fresnel_reflection =
+        render_settings.reflections && transparent && material.fresnel_reflection;
+    let reflection_weight = match (transparent, reflective) {
+        (true, true) => fresnel * material.reflectivity,
+        (true, false) => fresnel,
+        _ => material.reflectivity,
+    };
+    if (reflective || fresnel_reflection)
+        && depth < max_depth
+        && let Some(survival) = roulette(throughput * reflection_weight, render_settings, rng)
+    {
+        let mut reflected_dir = reflect(&dir, &intersect.normal).normalized();
+        // Superficie áspera: el reflejo se abre hacia un lóbulo alrededor
+        // del espejo, sin cruzar la cara
+        if material.roughness > 0.0 {
+            let scattered = rng.cosine_hemisphere(&reflected_dir);
+            let rough = reflected_dir.lerp(scattered, material.roughness).normalized();
+            if rough.dot(intersect.normal) * reflected_dir.dot(intersect.normal) > 0.0 {
+                reflected_dir = rough;
+            }
+        }
+        let reflect_origin =
+            intersect.point + intersect.normal * render_settings.ray_epsilon;
+        count_rays(|counts| counts.reflection += 1);
+        spawned = true;
         reflection_color = trace_ray_multi_light(
             reflect_origin,
             reflected_dir,
             depth + 1,
-            max_depth,
-            scene,
-            lights,
-            texture_manager,
-        );
+            throughput * reflection_weight / survival,
+            context,
+            rng,
+        ) / survival;
     }
 
     // Refracción
-    if material.transparency > 0.01 && depth < max_depth {
-        let refracted_dir = refract(&dir, &intersect.normal, material.refractive_index);
-        if refracted_dir.dot(refracted_dir) > 1e-6 {
-            let refract_origin = if dir.dot(intersect.normal) < 0.0 {
-                intersect.point - intersect.normal * EPSILON
-            } else {
-                intersect.point + intersect.normal * EPSILON
-            };
-            refraction_color = trace_ray_multi_light(
+    let refraction_weight = material.transparency * (1.0 - fresnel);
+    if transparent
+        && depth < max_depth
+        && refracts
+        && let Some(survival) = roulette(throughput * refraction_weight, render_settings, rng)
+    {
+        let child_throughput = throughput * refraction_weight / survival;
+        let entering = dir.dot(intersect.normal) < 0.0;
+        let refract_origin = if entering {
+            intersect.point - intersect.normal * render_settings.ray_epsilon
+        } else {
+            intersect.point + intersect.normal * render_settings.ray_epsilon
+        };
+        spawned = true;
+        // Vidrio deformado: el rayo se tuerce según dónde cruza la cara
+        let warped_dir = if material.distortion_strength > 0.0 {
+            distort_refraction(
+                &refracted_dir.normalized(),
+                &intersect.normal,
+                intersect.u,
+                intersect.v,
+                material.distortion_strength,
+            )
+        } else {
+            refracted_dir.normalized()
+        };
+        refraction_color = match material.refractive_index_rgb {
+            Some(iors) => trace_channels(
                 refract_origin,
-                refracted_dir.normalized(),
-                depth + 1,
-                max_depth,
-                scene,
-                lights,
-                texture_manager,
-            );
+                dispersed_dirs(&dir, &intersect.normal, iors),
+                depth,
+                child_throughput,
+                context,
+                rng,
+            ),
+            None if material.aberration_strength > 0.0 => trace_channels(
+                refract_origin,
+                aberrated_dirs(&warped_dir, &intersect.normal, material.aberration_strength),
+                depth,
+                child_throughput,
+                context,
+                rng,
+            ),
+            None => {
+                count_rays(|counts| counts.refraction += 1);
+                trace_ray_multi_light(
+                    refract_origin,
+                    warped_dir,
+                    depth + 1,
+                    child_throughput,
+                    context,
+                    rng,
+                )
+            }
+        } / survival;
 
-            // Fresnel (Schlick)
-            let cos_i = (-dir.dot(intersect.normal)).abs().clamp(0.0, 1.0);
-            fresnel = calculate_fresnel(cos_i, material.refractive_index);
-        } else {
-            fresnel = 1.0; // reflexión interna total
+        // Beer-Lambert: el medio tiñe la luz según el camino recorrido
+        // hasta la siguiente superficie (el fondo o la salida)
+        if entering && material.absorption != Vector3::zero() {
+            refraction_color *= absorption_factor(
+                &refract_origin,
+                &refracted_dir.normalized(),
+                material.absorption,
+                context,
+            );
         }
     }
+    if !spawned {
+        count_path_end(depth);
+    }
 
     // === combinación final ===
-    if material.transparency > 0.01 && material.reflectivity > MIN_REFLECTION_THRESHOLD {
+    let lit_surface = final_color;
+    if transparent && reflective {
         // Caso 3: Material con transparencia + reflectividad (vidrio espejado)
         let direct = final_color * (1.0 - material.transparency) * (1.0 - material.reflectivity);
         let reflect = reflection_color * fresnel * material.reflectivity;
         let refract = refraction_color * material.transparency * (1.0 - fresnel);
         final_color = direct + reflect + refract;
-    } else if material.transparency > 0.01 {
+    } else if transparent {
         // Caso 1: Solo transparente
         let direct = final_color * (1.0 - material.transparency);
         let reflect = reflection_color * fresnel;
         let refract = refraction_color * material.transparency * (1.0 - fresnel);
         final_color = direct + reflect + refract;
-    } else if material.reflectivity > MIN_REFLECTION_THRESHOLD {
+    } else if reflective {
         // Caso 2: Solo reflectivo
         final_color =
             final_color * (1.0 - material.reflectivity) + reflection_color * material.reflectivity;
     }
 
+    // La escarcha es opaca: donde la hay se ve solo la superficie iluminada
+    if material.frost > 0.0 {
+        let frost = (material.frost * edge_frost(intersect.u, intersect.v)).min(1.0);
+        final_color = final_color.lerp(lit_surface, frost);
+    }
+    final_color = fog.apply(final_color, intersect.distance, &render_settings.sky) + glow;
+
+    // Color lineal sin recortar: el mapeo tonal se aplica por pixel (ver tonemap.rs)
+    Vector3::new(
+        final_color.x.max(0.0),
+        final_color.y.max(0.0),
+        final_color.z.max(0.0),
+    )
+}
+
+/// Normal de una superficie de agua con olas: suma de senos que viajan en
+/// distintas direcciones sobre el plano XZ. `amplitude` es la pendiente
+/// máxima de cada onda.
+fn ripple_normal(point: &Vector3, amplitude: f32, time: f32) -> Vector3 {
+    // (dirección x, dirección z, número de onda, velocidad)
+    const WAVES: [(f32, f32, f32, f32); 3] = [
+        (1.0, 0.0, 2.1, 1.3),
+        (0.6, 0.8, 3.4, 1.9),
+        (-0.7, 0.7, 5.3, 2.7),
+    ];
+
+    let (mut slope_x, mut slope_z) = (0.0, 0.0);
+    for (dx, dz, k, speed) in WAVES {
+        let phase = k * (point.x * dx + point.z * dz) + speed * time;
+        // Derivada de sin(phase) / k: pendiente igual para todas las ondas
+        let slope = amplitude * phase.cos();
+        slope_x += slope * dx;
+        slope_z += slope * dz;
+    }
+    Vector3::new(-slope_x, 1.0, -slope_z).normalized()
+}
+
+/// Fracción de luz por canal que sobrevive al atravesar un medio
+/// absorbente desde `origin` hasta la siguiente superficie
+fn absorption_factor(
+    origin: &Vector3,
+    dir: &Vector3,
+    absorption: Vector3,
+    context: &RenderContext,
+) -> Vector3 {
+    let hit = find_closest_intersection(origin, dir, context.refs.scene, context.settings);
+    let distance = match hit {
+        Some((_, hit)) => hit.distance,
+        None => return Vector3::one(), // no hay salida: no se tiñe
+    };
     Vector3::new(
-        final_color.x.clamp(0.0, 1.0),
-        final_color.y.clamp(0.0, 1.0),
-        final_color.z.clamp(0.0, 1.0),
+        (-absorption.x * distance).exp(),
+        (-absorption.y * distance).exp(),
+        (-absorption.z * distance).exp(),
     )
 }
 
+/// Dispersión: un rayo refractado por canal (R, G, B) con su propio índice,
+/// para `trace_channels`. Cuesta el triple que la refracción normal, por eso
+/// es opcional por material.
+fn dispersed_dirs(dir: &Vector3, normal: &Vector3, iors: [f32; 3]) -> [Vector3; 3] {
+    // Un vector nulo marca reflexión interna total en ese canal
+    iors.map(|ior| refract(dir, normal, ior))
+}
+
+/// Traza un rayo por canal (R, G, B) y se queda con ese canal de cada uno.
+/// Las direcciones nulas no se trazan (el canal queda negro).
+fn trace_channels(
+    origin: Vector3,
+    dirs: [Vector3; 3],
+    depth: u32,
+    throughput: f32,
+    context: &RenderContext,
+    rng: &mut Rng,
+) -> Vector3 {
+    let mut channels = [0.0; 3];
+
+    for (channel, dir) in dirs.into_iter().enumerate() {
+        if dir.dot(dir) <= 1e-6 {
+            continue;
+        }
+
+        count_rays(|counts| counts.refraction += 1);
+        let color = trace_ray_multi_light(
+            origin,
+            dir.normalized(),
+            depth + 1,
+            throughput,
+            context,
+            rng,
+        );
+        channels[channel] = match channel {
+            0 => color.x,
+            1 => color.y,
+            _ => color.z,
+        };
+    }
+
+    Vector3::new(channels[0], channels[1], channels[2])
+}
+
+/// Separación entre canales con `aberration_strength` 1
+const ABERRATION_SCALE: f32 = 0.03;
+/// Desvío en el borde de la cara con `distortion_strength` 1
+const DISTORTION_SCALE: f32 = 0.25;
+
+/// Aberración cromática falsa: en vez de un índice por canal, el rayo
+/// refractado `refracted` (unitario) se corre un poco de costado para el
+/// rojo y para el azul. El corrimiento va en el plano de incidencia, como
+/// en la dispersión real: el azul se dobla más hacia la normal.
+fn aberrated_dirs(refracted: &Vector3, normal: &Vector3, strength: f32) -> [Vector3; 3] {
+    let tangential = *refracted - *normal * refracted.dot(*normal);
+    // De frente no hay plano de incidencia: cualquier eje de la cara sirve
+    let side = if tangential.dot(tangential) > 1e-6 {
+        tangential.normalized()
+    } else {
+        face_uv_axes(normal).0
+    };
+    let offset = side * (strength * ABERRATION_SCALE);
+    [
+        (*refracted + offset).normalized(),
+        *refracted,
+        (*refracted - offset).normalized(),
+    ]
+}
+
+/// Distorsión de barril del espacio UV: el rayo refractado `refracted`
+/// (unitario) se abre hacia afuera cuanto más lejos del centro de la cara
+/// cruza, con la forma `r·(1 + k·r²)` de una lente. Si el desvío lo sacara
+/// del otro lado de la cara se deja sin distorsionar.
+fn distort_refraction(
+    refracted: &Vector3,
+    normal: &Vector3,
+    u: f32,
+    v: f32,
+    strength: f32,
+) -> Vector3 {
+    // Centro de la cara en 0, punto medio de los bordes en ±1
+    let (cu, cv) = (u * 2.0 - 1.0, v * 2.0 - 1.0);
+    let r2 = cu * cu + cv * cv;
+    let (axis_u, axis_v) = face_uv_axes(normal);
+    let offset = (axis_u * cu + axis_v * cv) * (strength * DISTORTION_SCALE * r2);
+    let warped = (*refracted + offset).normalized();
+    if warped.dot(*normal) * refracted.dot(*normal) > 0.0 {
+        warped
+    } else {
+        *refracted
+    }
+}
+
+/// Ejes en los que crecen la u y la v sobre una cara alineada a los ejes
+/// (los mismos que usa `Block::calc_uv`). En otras superficies es una
+/// aproximación, alcanza para los efectos que solo necesitan un sentido.
+fn face_uv_axes(normal: &Vector3) -> (Vector3, Vector3) {
+    if normal.x.abs() > 0.9 {
+        (Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, -1.0, 0.0))
+    } else if normal.y.abs() > 0.9 {
+        let v_sign = if normal.y > 0.0 { 1.0 } else { -1.0 };
+        (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, v_sign))
+    } else {
+        let u_sign = if normal.z > 0.0 { -1.0 } else { 1.0 };
+        (Vector3::new(u_sign, 0.0, 0.0), Vector3::new(0.0, -1.0, 0.0))
+    }
+}
+
+/// Peso por debajo del cual un rebote entra en la ruleta rusa
+const ROULETTE_THRESHOLD: f32 = 0.1;
+/// Probabilidad mínima de seguir: acota cuánto se amplifica un rebote que
+/// sobrevive
+const ROULETTE_MIN_SURVIVAL: f32 = 0.1;
+
+/// Ruleta rusa para un rebote que llegaría al pixel con peso `throughput`.
+/// Devuelve la probabilidad con que sobrevivió (el color que trae se divide
+/// por ella para que el promedio no cambie), o None si se corta. Sin la
+/// ruleta o con peso suficiente sigue siempre.
+fn roulette(throughput: f32, render_settings: &RenderSettings, rng: &mut Rng) -> Option<f32> {
+    if !render_settings.russian_roulette || throughput >= ROULETTE_THRESHOLD {
+        return Some(1.0);
+    }
+    let survival = (throughput / ROULETTE_THRESHOLD).max(ROULETTE_MIN_SURVIVAL);
+    if rng.next_f32() < survival {
+        Some(survival)
+    } else {
+        count_rays(|counts| counts.roulette += 1);
+        None
+    }
+}
+
 /// Calcula el coeficiente de reflexión de Fresnel
 fn calculate_fresnel(cos_i: f32, refractive_index: f32) -> f32 {
     let n1 = 1.0;
@@ -291,3 +1348,385 @@ fn calculate_fresnel(cos_i: f32, refractive_index: f32) -> f32 {
     let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
     r0 + (1.0 - r0) * (1.0 - cos_i).powi(5) // Schlick
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{AnimatedMaterials, MaterialAnimation};
+    use crate::block_types::BlockType;
+    use crate::camera::CameraConfig;
+    use crate::fog::Fog;
+    use crate::render_context::SceneRefs;
+    use crate::scene::mark_interior_faces;
+    use crate::textures::TextureManager;
+
+    /// Color del rayo que sale de `origin` hacia `dir` en `scene`
+    fn trace(scene: &Scene, lights: &[Light], origin: Vector3, dir: Vector3) -> Vector3 {
+        trace_with(&RenderSettings::default(), scene, lights, origin, dir)
+    }
+
+    fn trace_with(
+        settings: &RenderSettings,
+        scene: &Scene,
+        lights: &[Light],
+        origin: Vector3,
+        dir: Vector3,
+    ) -> Vector3 {
+        let textures = TextureManager::new();
+        let refs = SceneRefs {
+            scene,
+            lights,
+            beams: &[],
+            portals: &[],
+            textures: &textures,
+            animated: &AnimatedMaterials::default(),
+        };
+        let context = RenderContext::new(refs, settings, 0);
+        trace_ray_multi_light(origin, dir, 0, 1.0, &context, &mut Rng::new(1, 1))
+    }
+
+    #[test]
+    fn center_pixel_picks_the_block_in_front() {
+        // Tres bloques en fila frente a la cámara y uno tapado detrás del
+        // del medio; el centro de la imagen tiene que dar el del medio
+        let scene = Scene::new(vec![
+            BlockType::Stone.to_block(Vector3::new(-2.0, 0.0, 0.0), 1.0),
+            BlockType::Dirt.to_block(Vector3::new(0.0, 0.0, 0.0), 1.0),
+            BlockType::Stone.to_block(Vector3::new(2.0, 0.0, 0.0), 1.0),
+            BlockType::Stone.to_block(Vector3::new(0.0, 0.0, 3.0), 1.0),
+        ]);
+        let camera = CameraConfig::new(
+            Vector3::new(0.0, 0.0, -6.0),
+            std::f32::consts::FRAC_PI_2,
+            0.0,
+            9,
+            7,
+            std::f32::consts::FRAC_PI_3,
+            9.0 / 7.0,
+        );
+        let dir = camera.get_ray_direction(4, 3);
+
+        let pick = pick_block(&camera.pos, &dir, &scene).unwrap();
+        assert_eq!(pick.index, 1);
+        assert!((pick.distance - 5.5).abs() < 1e-4);
+        assert_eq!(pick.normal, Vector3::new(0.0, 0.0, -1.0));
+
+        // El trazado llega al mismo bloque por `block_id`
+        let (_, hit) =
+            find_closest_intersection(&camera.pos, &dir, &scene, &RenderSettings::default())
+                .unwrap();
+        assert_eq!(hit.block_id, Some(1));
+        let block = scene.block(hit.block_id.unwrap() as usize).unwrap();
+        assert_eq!(BlockType::identify(block), Some(BlockType::Dirt));
+    }
+
+    #[test]
+    fn animated_block_is_shaded_with_the_frame_material() {
+        // La escena guarda piedra; la animación la cambia por el sol (sin
+        // luces, así que solo brilla si se usa el material animado)
+        let scene = Scene::new(vec![BlockType::Stone.to_block(Vector3::zero(), 1.0)]);
+        let sun = BlockType::Sun.material();
+        let expected = sun.emission_color.unwrap() * sun.emission_strength;
+        let animations = [MaterialAnimation::new(0, sun.clone(), sun, 2.0)];
+        let animated = AnimatedMaterials::evaluate(&animations, 0.5);
+        let textures = TextureManager::new();
+        let settings = RenderSettings::default();
+        let refs = SceneRefs {
+            scene: &scene,
+            lights: &[],
+            beams: &[],
+            portals: &[],
+            textures: &textures,
+            animated: &animated,
+        };
+        let context = RenderContext::new(refs, &settings, 0);
+        let (origin, dir) = (Vector3::new(0.0, 0.0, -3.0), Vector3::new(0.0, 0.0, 1.0));
+        let color = trace_ray_multi_light(origin, dir, 0, 1.0, &context, &mut Rng::new(1, 1));
+        assert!(
+            (color - expected).length() < 1e-4,
+            "{:?} != {:?}",
+            color,
+            expected
+        );
+        assert!((trace(&scene, &[], origin, dir) - expected).length() > 0.1);
+    }
+
+    #[test]
+    fn only_fresnel_materials_trace_a_reflection_without_reflectivity() {
+        let origin = Vector3::new(0.0, 0.0, -3.0);
+        let dir = Vector3::new(0.0, 0.0, 1.0);
+        let reflections = |block_type: BlockType| {
+            let scene = Scene::new(vec![block_type.to_block(Vector3::zero(), 1.0)]);
+            take_ray_counts();
+            trace(&scene, &[], origin, dir);
+            take_ray_counts().reflection
+        };
+        // El vidrio no es reflectivo: se ve como antes del agua, sin rayo
+        // de reflexión; el agua refleja lo que indica Fresnel
+        assert!(!BlockType::Glass.material().fresnel_reflection);
+        assert_eq!(reflections(BlockType::Glass), 0);
+        assert!(BlockType::Water.material().fresnel_reflection);
+        assert!(reflections(BlockType::Water) > 0);
+    }
+
+    #[test]
+    fn edge_frost_is_zero_in_the_center_and_full_at_the_borders() {
+        assert_eq!(edge_frost(0.5, 0.5), 0.0);
+        for (u, v) in [
+            (0.0, 0.5),
+            (1.0, 0.5),
+            (0.5, 0.0),
+            (0.5, 1.0),
+            (0.0, 0.0),
+            (1.0, 1.0),
+        ] {
+            assert_eq!(edge_frost(u, v), 1.0, "borde ({}, {})", u, v);
+        }
+        // Baja sin saltos desde el borde hasta FROST_WIDTH
+        let mut previous = edge_frost(0.0, 0.5);
+        for step in 1..=20 {
+            let frost = edge_frost(FROST_WIDTH * step as f32 / 20.0, 0.5);
+            assert!(frost <= previous && previous - frost < 0.2);
+            previous = frost;
+        }
+        assert_eq!(previous, 0.0);
+
+        let ice = BlockType::Ice.material();
+        assert!(ice.frost > 0.0 && ice.roughness > 0.0);
+        assert!((ice.refractive_index - 1.31).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fog_fades_far_hits_and_leaves_the_sky_alone() {
+        let red = Vector3::new(1.0, 0.0, 0.0);
+        let foggy = RenderSettings {
+            fog: Fog::new(0.2, 2.0, Some(red)),
+            ..RenderSettings::default()
+        };
+        let origin = Vector3::zero();
+        let dir = Vector3::new(0.0, 0.0, 1.0);
+
+        // Sin impacto: el cielo no se toca
+        let empty = Scene::new(Vec::new());
+        assert_eq!(
+            trace_with(&foggy, &empty, &[], origin, dir),
+            trace(&empty, &[], origin, dir)
+        );
+
+        // Antes del inicio no hay niebla; lejos casi todo es niebla
+        let near = Scene::new(vec![
+            BlockType::Stone.to_block(Vector3::new(0.0, 0.0, 2.0), 1.0),
+        ]);
+        assert_eq!(
+            trace_with(&foggy, &near, &[], origin, dir),
+            trace(&near, &[], origin, dir)
+        );
+        let far = Scene::new(vec![
+            BlockType::Stone.to_block(Vector3::new(0.0, 0.0, 40.0), 1.0),
+        ]);
+        let color = trace_with(&foggy, &far, &[], origin, dir);
+        assert!((color - red).length() < 0.01, "{:?}", color);
+    }
+
+    #[test]
+    fn unlit_block_ignores_scene_lights() {
+        let scene = Scene::new(vec![BlockType::Sun.to_block(Vector3::zero(), 1.0)]);
+        let material = BlockType::Sun.material();
+        let expected = material.emission_color.unwrap() * material.emission_strength;
+        let origin = Vector3::new(0.0, 0.0, -3.0);
+        let dir = Vector3::new(0.0, 0.0, 1.0);
+
+        let dark = trace(&scene, &[], origin, dir);
+        let lit = trace(
+            &scene,
+            &[
+                Light::new(Vector3::new(0.0, 3.0, -3.0), Vector3::one(), 5.0),
+                Light::new(
+                    Vector3::new(2.0, -1.0, -2.0),
+                    Vector3::new(1.0, 0.2, 0.2),
+                    3.0,
+                ),
+            ],
+            origin,
+            dir,
+        );
+        assert_eq!(dark, lit);
+        assert!(
+            (dark - expected).length() < 1e-4,
+            "{:?} != {:?}",
+            dark,
+            expected
+        );
+    }
+
+    #[test]
+    fn distance_after_interior_faces_counts_from_origin() {
+        // Tres vidrios en fila: desde dentro del primero, el rayo salta dos
+        // pares de caras interiores y sale por el fondo del tercero
+        let mut blocks: Vec<Block> = (0..3)
+            .map(|z| BlockType::Glass.to_block(Vector3::new(0.0, 0.0, z as f32), 1.0))
+            .collect();
+        mark_interior_faces(&mut blocks);
+        let scene = Scene::new(blocks);
+        let origin = Vector3::new(0.1, 0.2, 0.0);
+        let dir = Vector3::new(0.0, 0.0, 1.0);
+
+        let (_, hit) =
+            find_closest_intersection(&origin, &dir, &scene, &RenderSettings::default()).unwrap();
+        assert!(!hit.interior_face);
+        assert!((hit.point.z - 2.5).abs() < 1e-4, "{:?}", hit.point);
+        assert!((hit.distance - 2.5).abs() < 1e-3, "{}", hit.distance);
+        assert!((hit.distance - (hit.point - origin).length()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn back_lit_leaves_glow_with_shadows_on() {
+        let scene = Scene::new(vec![BlockType::Leaves.to_block(Vector3::zero(), 1.0)]);
+        // La luz detrás del bloque, la cámara delante
+        let behind = [Light::new(Vector3::new(0.0, 0.0, 4.0), Vector3::one(), 5.0)];
+        let origin = Vector3::new(0.0, 0.0, -3.0);
+        let dir = Vector3::new(0.0, 0.0, 1.0);
+
+        let mut settings = RenderSettings::default();
+        let unshadowed = trace_with(&settings, &scene, &behind, origin, dir);
+        settings.shadows = true;
+        let dark = trace_with(&settings, &scene, &[], origin, dir);
+        let lit = trace_with(&settings, &scene, &behind, origin, dir);
+
+        assert!(lit.x > dark.x && lit.y > dark.y, "{:?} <= {:?}", lit, dark);
+        // Nada más tapa la luz: la sombra no le quita nada
+        assert!(
+            (lit - unshadowed).length() < 1e-5,
+            "{:?} != {:?}",
+            lit,
+            unshadowed
+        );
+    }
+
+    #[test]
+    fn opaque_back_face_gets_no_light() {
+        let scene = Scene::new(vec![BlockType::Stone.to_block(Vector3::zero(), 1.0)]);
+        let behind = [Light::new(Vector3::new(0.0, 0.0, 4.0), Vector3::one(), 5.0)];
+        let origin = Vector3::new(0.0, 0.0, -3.0);
+        let dir = Vector3::new(0.0, 0.0, 1.0);
+
+        let settings = RenderSettings {
+            shadows: true,
+            ..RenderSettings::default()
+        };
+        let dark = trace_with(&settings, &scene, &[], origin, dir);
+        let lit = trace_with(&settings, &scene, &behind, origin, dir);
+        assert_eq!(lit, dark);
+    }
+
+    #[test]
+    fn soft_shadows_leave_penumbra_at_hard_edge() {
+        // Piso de tres bloques y uno encima que tapa la luz; el punto mirado
+        // queda justo dentro del borde de la sombra dura
+        let mut blocks: Vec<Block> = (-1..=1)
+            .map(|x| BlockType::Stone.to_block(Vector3::new(x as f32, 0.0, 0.0), 1.0))
+            .collect();
+        blocks.push(BlockType::Stone.to_block(Vector3::new(0.0, 2.0, 0.0), 1.0));
+        let scene = Scene::new(blocks);
+        let lights = [Light::new(Vector3::new(0.0, 6.0, 0.0), Vector3::one(), 5.0)];
+        let target = Vector3::new(0.75, 0.5, 0.0);
+        let origin = Vector3::new(0.75, 3.0, -3.0);
+        let dir = (target - origin).normalized();
+
+        let hard = RenderSettings {
+            shadows: true,
+            ..RenderSettings::default()
+        };
+        let soft = RenderSettings {
+            soft_shadows: true,
+            light_radius: 0.5,
+            ..hard
+        };
+        let unshadowed = trace_with(&RenderSettings::default(), &scene, &lights, origin, dir);
+        let dark = trace_with(&hard, &scene, &[], origin, dir);
+        assert_eq!(trace_with(&hard, &scene, &lights, origin, dir), dark);
+
+        // Promedio de muchos rayos de sombra, como la acumulación
+        let textures = TextureManager::new();
+        let refs = SceneRefs {
+            scene: &scene,
+            lights: &lights,
+            beams: &[],
+            portals: &[],
+            textures: &textures,
+            animated: &AnimatedMaterials::default(),
+        };
+        let context = RenderContext::new(refs, &soft, 0);
+        let samples = 256;
+        let average = (0..samples)
+            .map(|i| trace_ray_multi_light(origin, dir, 0, 1.0, &context, &mut Rng::new(i, 7)))
+            .fold(Vector3::zero(), |sum, color| sum + color)
+            / samples as f32;
+        assert!(
+            average.x > dark.x + 1e-3 && average.x < unshadowed.x - 1e-3,
+            "{} no está entre {} y {}",
+            average.x,
+            dark.x,
+            unshadowed.x
+        );
+    }
+
+    /// La grilla y la BVH tienen que dar el mismo impacto que probar todos
+    /// los objetos, también desde adentro de la isla y rozando caras
+    fn assert_same_hits(scene: &Scene, min_hits: usize) {
+        let mut linear = scene.clone();
+        linear.invalidate_grid();
+        assert!(scene.grid().is_some() && linear.grid().is_none());
+
+        let settings = RenderSettings::default();
+        let mut rng = Rng::new(3, 0);
+        let mut hits = 0;
+        for i in 0..20_000 {
+            let mut coord = |range: f32| (rng.next_f32() - 0.5) * range;
+            let mut origin = Vector3::new(coord(20.0), coord(12.0) + 2.0, coord(20.0));
+            // La mitad sale de un centro o un borde de celda exacto
+            if i % 2 == 0 {
+                origin = Vector3::new(origin.x.round(), origin.y.round() + 0.5, origin.z.round());
+            }
+            // Y un tercio corre paralelo al piso
+            let rise = if i % 3 == 0 { 0.0 } else { coord(2.0) };
+            let dir = Vector3::new(coord(2.0), rise, coord(2.0)).normalized();
+            let with_grid = find_closest_intersection(&origin, &dir, scene, &settings);
+            let full_scan = find_closest_intersection(&origin, &dir, &linear, &settings);
+            let summary = |hit: Option<(usize, Intersect)>| {
+                hit.map(|(index, hit)| (index, hit.distance, hit.normal, hit.block_id))
+            };
+            hits += with_grid.is_some() as usize;
+            assert_eq!(
+                summary(with_grid),
+                summary(full_scan),
+                "rayo {} desde {:?}",
+                i,
+                origin
+            );
+        }
+        assert!(hits > min_hits, "solo {} rayos tocaron algo", hits);
+    }
+
+    #[test]
+    fn grid_and_full_scan_find_the_same_hits() {
+        assert_same_hits(&crate::scene::create_winter_scene(), 2_000);
+    }
+
+    /// Primitivas y bloques fuera de la grilla salen de la BVH, también
+    /// después de editarla en el lugar
+    #[test]
+    fn bvh_and_full_scan_find_the_same_hits() {
+        let mut scene = crate::scene::create_primitive_showcase();
+        // Bloques corridos media celda: no entran en la grilla
+        for x in 0..6 {
+            let position = Vector3::new(x as f32 - 2.5, 1.5, 2.5);
+            scene.place_block(BlockType::Glass.to_block(position, 1.0));
+        }
+        scene.remove_block(3);
+        let moved = scene[2].clone().with_extents(Vector3::one() * 1.5);
+        scene.replace_block(2, moved);
+        assert_eq!(scene.bvh_counters().rebuilds, 0);
+        assert_same_hits(&scene, 2_000);
+    }
+}
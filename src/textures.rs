@@ -1,6 +1,51 @@
 // textures.rs - Versión mejorada
+use log::debug;
 use raylib::prelude::*;
 use std::collections::HashMap;
+use std::path::Path;
+
+/// Por qué no se pudo cargar una textura; cada variante lleva la ruta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureError {
+    /// El archivo no existe
+    NotFound(String),
+    /// Existe pero raylib no pudo leerlo como imagen
+    DecodeFailed(String),
+    /// La imagen se leyó pero no se pudo subir a la GPU
+    GpuUploadFailed(String),
+}
+
+impl TextureError {
+    pub fn path(&self) -> &str {
+        match self {
+            TextureError::NotFound(path)
+            | TextureError::DecodeFailed(path)
+            | TextureError::GpuUploadFailed(path) => path,
+        }
+    }
+}
+
+impl std::fmt::Display for TextureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextureError::NotFound(path) => write!(f, "no existe la textura {}", path),
+            TextureError::DecodeFailed(path) => write!(f, "no se pudo leer la imagen {}", path),
+            TextureError::GpuUploadFailed(path) => {
+                write!(f, "no se pudo subir la textura {} a la GPU", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// Lee la imagen de `path` distinguiendo un archivo que falta de uno ilegible
+fn load_image(path: &str) -> Result<Image, TextureError> {
+    if !Path::new(path).is_file() {
+        return Err(TextureError::NotFound(path.to_string()));
+    }
+    Image::load_image(path).map_err(|_| TextureError::DecodeFailed(path.to_string()))
+}
 
 /// Textura cargada en memoria de CPU con interpolación bilinear
 struct CpuTexture {
@@ -60,6 +105,13 @@ impl CpuTexture {
         c0 + (c1 - c0) * fy
     }
 
+    /// Pixel más cercano, sin interpolar (bordes duros, más barato)
+    fn sample_nearest(&self, u: f32, v: f32) -> Vector3 {
+        let x = (u.clamp(0.0, 1.0) * (self.width - 1) as f32).round() as i32;
+        let y = (v.clamp(0.0, 1.0) * (self.height - 1) as f32).round() as i32;
+        self.get_pixel_clamped(x, y)
+    }
+
     /// Obtiene pixel con clamping a bordes
     fn get_pixel_clamped(&self, x: i32, y: i32) -> Vector3 {
         let x = x.clamp(0, self.width - 1);
@@ -81,6 +133,7 @@ impl CpuTexture {
 }
 
 /// Gestor de texturas mejorado
+#[derive(Default)]
 pub struct TextureManager {
     cpu_textures: HashMap<String, CpuTexture>,
     gpu_textures: HashMap<String, Texture2D>,
@@ -92,24 +145,63 @@ impl TextureManager {
     }
 
     /// Carga una textura desde archivo
-    pub fn load_texture(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &str) -> Result<(), String> {
+    pub fn load_texture(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &str) -> Result<(), TextureError> {
         if self.gpu_textures.contains_key(path) {
             return Ok(()); // Ya está cargada
         }
 
-        let image = Image::load_image(path)
-            .map_err(|_| format!("No se pudo cargar la imagen: {}", path))?;
+        let image = load_image(path)?;
 
         let texture = rl
             .load_texture_from_image(thread, &image)
-            .map_err(|_| format!("No se pudo crear la textura: {}", path))?;
+            .map_err(|_| TextureError::GpuUploadFailed(path.to_string()))?;
 
         self.cpu_textures.insert(path.to_string(), CpuTexture::from_image(&image));
         self.gpu_textures.insert(path.to_string(), texture);
-        
+        debug!("Textura cargada: {} ({}x{})", path, image.width, image.height);
+
         Ok(())
     }
 
+    /// Carga solo la copia en CPU que usa el raytracer. No necesita ventana
+    /// (render sin interfaz).
+    pub fn load_cpu_texture(&mut self, path: &str) -> Result<(), TextureError> {
+        if self.cpu_textures.contains_key(path) {
+            return Ok(());
+        }
+
+        let image = load_image(path)?;
+        self.cpu_textures.insert(path.to_string(), CpuTexture::from_image(&image));
+        debug!("Textura cargada en CPU: {} ({}x{})", path, image.width, image.height);
+
+        Ok(())
+    }
+
+    /// Crea una textura en CPU calculando cada texel con `texel(x, y)`
+    /// (colores en [0,1]). Sirve para texturas procedurales sin archivo: los
+    /// materiales la usan con `name` como ruta.
+    pub fn insert_procedural(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        texel: impl Fn(u32, u32) -> Vector3,
+    ) {
+        let (width, height) = (width.max(1), height.max(1));
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| texel(x, y))
+            .collect();
+        self.cpu_textures.insert(
+            name.to_string(),
+            CpuTexture {
+                width: width as i32,
+                height: height as i32,
+                pixels,
+            },
+        );
+    }
+
     /// Obtiene color con interpolación bilinear (para raytracer)
     pub fn sample_texture(&self, path: &str, u: f32, v: f32) -> Vector3 {
         self.cpu_textures
@@ -118,6 +210,14 @@ impl TextureManager {
             .unwrap_or(Vector3::one()) // Color blanco por defecto
     }
 
+    /// Como `sample_texture` pero con el pixel más cercano
+    pub fn sample_texture_nearest(&self, path: &str, u: f32, v: f32) -> Vector3 {
+        self.cpu_textures
+            .get(path)
+            .map(|tex| tex.sample_nearest(u, v))
+            .unwrap_or(Vector3::one())
+    }
+
     /// Obtiene normal desde normal map
     pub fn sample_normal_map(&self, path: &str, u: f32, v: f32) -> Vector3 {
         self.cpu_textures
@@ -141,6 +241,22 @@ impl TextureManager {
         }
     }
 
+    /// Memoria usada en bytes: (copia de CPU en flotantes, textura de GPU en RGBA8)
+    pub fn memory_usage(&self) -> (usize, usize) {
+        let cpu = self
+            .cpu_textures
+            .values()
+            .map(|tex| tex.pixels.len() * std::mem::size_of::<Vector3>())
+            .sum();
+        let gpu = self
+            .cpu_textures
+            .iter()
+            .filter(|(path, _)| self.gpu_textures.contains_key(*path))
+            .map(|(_, tex)| tex.pixels.len() * 4)
+            .sum();
+        (cpu, gpu)
+    }
+
     /// devuelve (width,height) o (0,0) si no existe
     pub fn size_of(&self, path: &str) -> Option<(u32,u32)> {
         self.cpu_textures.get(path).map(|t| (t.width as u32, t.height as u32))
@@ -155,12 +271,28 @@ impl TextureManager {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
 
-impl Default for TextureManager {
-    fn default() -> Self {
-        Self {
-            cpu_textures: HashMap::new(),
-            gpu_textures: HashMap::new(),
-        }
+    #[test]
+    fn missing_file_is_not_found() {
+        let path = std::env::temp_dir()
+            .join(format!("no_existe_{}.png", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let mut manager = TextureManager::new();
+        let err = manager.load_cpu_texture(&path).unwrap_err();
+        assert_eq!(err, TextureError::NotFound(path.clone()));
+        assert_eq!(err.path(), path);
+        assert!(err.to_string().contains(&path));
+        // No queda registrada a medias
+        assert_eq!(manager.size_of(&path), None);
+
+        // El modo sin ventana sale con el código de las texturas
+        let app_error = AppError::Textures(vec![err]);
+        assert_eq!(app_error.exit_code(), 4);
+        assert!(app_error.to_string().contains(&path));
     }
-}
\ No newline at end of file
+}
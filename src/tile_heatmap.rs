@@ -0,0 +1,65 @@
+// tile_heatmap.rs - Mapa de calor del tiempo de cada tile (mantener K)
+use raylib::prelude::*;
+
+use crate::framebuffer::ScreenMapping;
+
+/// Tiempo de pared que tardó un tile del render multihilo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileTime {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub seconds: f32,
+}
+
+/// Verde (rápido), amarillo y rojo (lento) según la fracción del tile más lento
+fn heat_color(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let (r, g) = if t < 0.5 {
+        (t * 2.0, 1.0)
+    } else {
+        (1.0, (1.0 - t) * 2.0)
+    };
+    Color::new((r * 255.0) as u8, (g * 255.0) as u8, 0, 110)
+}
+
+/// Dibuja cada tile como un rectángulo translúcido sobre la imagen
+/// presentada. Los tiempos se normalizan contra el tile más lento del frame.
+pub fn draw_tile_heatmap(d: &mut RaylibDrawHandle, tiles: &[TileTime], mapping: &ScreenMapping) {
+    let max = tiles.iter().map(|tile| tile.seconds).fold(0.0, f32::max);
+    if max <= 0.0 {
+        return;
+    }
+    let (scale_x, scale_y) = mapping.scale();
+    for tile in tiles {
+        let corner = mapping.buffer_to_window(tile.x as f32, tile.y as f32);
+        let rect = Rectangle::new(
+            corner.x,
+            corner.y,
+            tile.width as f32 * scale_x,
+            tile.height as f32 * scale_y,
+        );
+        d.draw_rectangle_rec(rect, heat_color(tile.seconds / max));
+        d.draw_rectangle_lines_ex(rect, 1.0, Color::new(0, 0, 0, 60));
+    }
+
+    let total: f32 = tiles.iter().map(|tile| tile.seconds).sum();
+    let text = format!(
+        "Tiles: {} | máx {:.2}ms | media {:.2}ms",
+        tiles.len(),
+        max * 1000.0,
+        total / tiles.len() as f32 * 1000.0
+    );
+    let dest = mapping.dest;
+    let x = dest.x as i32 + 6;
+    let y = (dest.y + dest.height) as i32 - 22;
+    d.draw_rectangle(
+        x - 4,
+        y - 2,
+        d.measure_text(&text, 16) + 8,
+        20,
+        Color::new(0, 0, 0, 170),
+    );
+    d.draw_text(&text, x, y, 16, Color::WHITE);
+}
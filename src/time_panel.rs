@@ -0,0 +1,175 @@
+// time_panel.rs - Panel de hora del día (N): slider de 0h a 24h, pausa y velocidad
+use raylib::prelude::*;
+
+use crate::day_cycle::{DAY_SPEEDS, DayCycle};
+
+const PANEL_WIDTH: i32 = 420;
+const PANEL_HEIGHT: i32 = 92;
+const TRACK_HEIGHT: i32 = 8;
+const BUTTON_WIDTH: i32 = 56;
+const BUTTON_HEIGHT: i32 = 22;
+/// Espacio que deja libre abajo para la hotbar
+const BOTTOM_MARGIN: i32 = 64;
+
+/// Estado del panel: visible o no y si se está arrastrando el slider
+#[derive(Default)]
+pub struct TimePanel {
+    open: bool,
+    dragging: bool,
+}
+
+impl TimePanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.dragging = false;
+    }
+
+    /// Esquina superior izquierda del panel, centrado sobre la hotbar
+    fn origin(window_width: i32, window_height: i32) -> (i32, i32) {
+        (
+            (window_width - PANEL_WIDTH) / 2,
+            window_height - BOTTOM_MARGIN - PANEL_HEIGHT,
+        )
+    }
+
+    fn track_rect(x: i32, y: i32) -> Rectangle {
+        Rectangle::new(
+            (x + 12) as f32,
+            (y + 36) as f32,
+            (PANEL_WIDTH - 24) as f32,
+            TRACK_HEIGHT as f32,
+        )
+    }
+
+    /// Botón de pausa seguido de uno por velocidad
+    fn button_rect(index: usize, x: i32, y: i32) -> Rectangle {
+        Rectangle::new(
+            (x + 12 + (BUTTON_WIDTH + 8) * index as i32) as f32,
+            (y + PANEL_HEIGHT - BUTTON_HEIGHT - 10) as f32,
+            BUTTON_WIDTH as f32,
+            BUTTON_HEIGHT as f32,
+        )
+    }
+
+    /// Arrastrar sobre la barra cambia la hora; los botones pausan el ciclo
+    /// o eligen la velocidad
+    pub fn handle_input(
+        &mut self,
+        rl: &RaylibHandle,
+        day: &mut DayCycle,
+        window_width: i32,
+        window_height: i32,
+    ) {
+        if !self.open {
+            return;
+        }
+        let (x, y) = Self::origin(window_width, window_height);
+        let mouse = rl.get_mouse_position();
+        let track = Self::track_rect(x, y);
+
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            // Área de clic algo más alta que la barra
+            let mut grab = track;
+            grab.y -= 8.0;
+            grab.height += 16.0;
+            self.dragging = grab.check_collision_point_rec(mouse);
+
+            if Self::button_rect(0, x, y).check_collision_point_rec(mouse) {
+                day.paused = !day.paused;
+            }
+            for (i, speed) in DAY_SPEEDS.iter().enumerate() {
+                if Self::button_rect(i + 1, x, y).check_collision_point_rec(mouse) {
+                    day.speed = *speed;
+                }
+            }
+        }
+        if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.dragging = false;
+        }
+
+        if self.dragging {
+            let t = ((mouse.x - track.x) / track.width).clamp(0.0, 1.0);
+            // 24h es la misma hora que 0h; se deja al final de la barra
+            day.hour = (24.0 * t).min(23.99);
+        }
+    }
+
+    /// Dibuja la barra con marcas cada 6 horas, la hora actual y los botones
+    pub fn draw(
+        &self,
+        d: &mut RaylibDrawHandle,
+        day: &DayCycle,
+        window_width: i32,
+        window_height: i32,
+    ) {
+        if !self.open {
+            return;
+        }
+        let (x, y) = Self::origin(window_width, window_height);
+        d.draw_rectangle(x, y, PANEL_WIDTH, PANEL_HEIGHT, Color::new(0, 0, 0, 170));
+
+        let state = if day.paused { "en pausa" } else { "avanzando" };
+        d.draw_text(
+            &format!("Hora {} ({}, {}x)", day.clock(), state, day.speed),
+            x + 12,
+            y + 8,
+            16,
+            Color::YELLOW,
+        );
+
+        let track = Self::track_rect(x, y);
+        d.draw_rectangle_rec(track, Color::new(70, 70, 70, 255));
+        for mark in 0..=4 {
+            let mark_x = track.x as i32 + (track.width * mark as f32 / 4.0) as i32;
+            d.draw_line(
+                mark_x,
+                track.y as i32 - 3,
+                mark_x,
+                (track.y + track.height) as i32 + 3,
+                Color::LIGHTGRAY,
+            );
+        }
+        let knob_x = track.x + track.width * day.hour / 24.0;
+        d.draw_circle(
+            knob_x as i32,
+            (track.y + track.height * 0.5) as i32,
+            7.0,
+            if self.dragging {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            },
+        );
+
+        let labels = std::iter::once(if day.paused { "Seguir" } else { "Pausa" }.to_string())
+            .chain(DAY_SPEEDS.iter().map(|speed| format!("{}x", speed)));
+        for (i, label) in labels.enumerate() {
+            let rect = Self::button_rect(i, x, y);
+            let active = i > 0 && DAY_SPEEDS[i - 1] == day.speed;
+            d.draw_rectangle_rec(
+                rect,
+                if active {
+                    Color::new(255, 255, 255, 70)
+                } else {
+                    Color::new(255, 255, 255, 30)
+                },
+            );
+            let width = d.measure_text(&label, 14);
+            d.draw_text(
+                &label,
+                rect.x as i32 + (BUTTON_WIDTH - width) / 2,
+                rect.y as i32 + 4,
+                14,
+                if active { Color::YELLOW } else { Color::WHITE },
+            );
+        }
+    }
+}
@@ -0,0 +1,360 @@
+// tnt.rs - TNT: mecha, explosión que quita bloques, destello y sacudida
+use std::f32::consts::TAU;
+
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::block_types::BlockType;
+use crate::light::Light;
+use crate::material::Material;
+use crate::rng::Rng;
+use crate::scene::index_after_removal;
+use crate::textures::TextureManager;
+
+/// Texturas procedurales del costado y de las tapas
+pub const TNT_SIDE_TEXTURE: &str = "procedural/tnt_side";
+pub const TNT_TOP_TEXTURE: &str = "procedural/tnt_top";
+
+/// Lado en texels de las texturas de TNT
+const TNT_TEXTURE_SIZE: u32 = 16;
+
+/// Segundos de mecha al encenderla a mano
+pub const FUSE_SECONDS: f32 = 2.0;
+/// Mecha de una TNT alcanzada por otra explosión: más corta, con algo de
+/// azar para que la cadena no explote toda en el mismo frame
+const CHAIN_FUSE_SECONDS: f32 = 0.4;
+const CHAIN_FUSE_JITTER: f32 = 0.3;
+
+/// Radio de la explosión por defecto (en bloques)
+pub const DEFAULT_BLAST_RADIUS: f32 = 3.0;
+
+/// Parpadeos por segundo del brillo de la mecha e intensidad máxima
+const FUSE_BLINK_RATE: f32 = 4.0;
+const FUSE_GLOW: f32 = 2.0;
+
+/// Destello de luz que deja cada explosión
+const FLASH_SECONDS: f32 = 0.35;
+const FLASH_INTENSITY: f32 = 12.0;
+
+/// Sacudida de la cámara: duración y desplazamiento máximo por bloque de radio
+const SHAKE_SECONDS: f32 = 0.5;
+const SHAKE_PER_RADIUS: f32 = 0.04;
+
+/// TNT encendida: el bloque y el material que tenía, para que el brillo de la
+/// mecha parpadee sobre el original
+#[derive(Debug, Clone)]
+struct Fuse {
+    block_index: usize,
+    remaining: f32,
+    elapsed: f32,
+    base: Block,
+}
+
+/// Luz breve en el centro de una explosión
+#[derive(Debug, Clone, Copy)]
+struct Flash {
+    position: Vector3,
+    remaining: f32,
+}
+
+/// Una explosión resuelta en este frame
+#[derive(Debug, Clone)]
+pub struct Blast {
+    pub center: Vector3,
+    /// Bloques que vuelan, por su índice en la escena de antes del paso
+    pub removed: Vec<usize>,
+}
+
+/// Cambios de un paso de `Explosives::update`, para aplicar a la escena
+#[derive(Debug, Clone, Default)]
+pub struct ExplosionStep {
+    /// Bloques con la mecha encendida, con el brillo de este paso
+    pub glowing: Vec<(usize, Block)>,
+    pub blasts: Vec<Blast>,
+}
+
+impl ExplosionStep {
+    /// Índices de todos los bloques que vuelan, de mayor a menor. Quitados
+    /// en este orden con `Scene::remove_block` los que faltan no se mueven,
+    /// y es el orden con el que `Explosives` ya corrigió sus mechas.
+    pub fn removal_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = self
+            .blasts
+            .iter()
+            .flat_map(|blast| blast.removed.iter().copied())
+            .collect();
+        order.sort_unstable_by(|a, b| b.cmp(a));
+        order
+    }
+}
+
+/// Estado de las TNT de la escena: mechas encendidas, destellos y sacudida.
+/// Avanza con el dt simulado y un generador propio, así una sesión
+/// reproducida explota igual.
+#[derive(Debug, Clone)]
+pub struct Explosives {
+    fuses: Vec<Fuse>,
+    flashes: Vec<Flash>,
+    shake: f32,
+    shake_strength: f32,
+    rng: Rng,
+}
+
+impl Default for Explosives {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Explosives {
+    pub fn new() -> Self {
+        Self {
+            fuses: Vec::new(),
+            flashes: Vec::new(),
+            shake: 0.0,
+            shake_strength: 0.0,
+            rng: Rng::new(0x7417, 0),
+        }
+    }
+
+    /// Si el bloque es una TNT
+    pub fn is_tnt(block: &Block) -> bool {
+        BlockType::identify(block) == Some(BlockType::Tnt)
+    }
+
+    /// Enciende la mecha del bloque `index`. Devuelve false si no es TNT o
+    /// ya estaba encendida.
+    pub fn ignite(&mut self, blocks: &[Block], index: usize) -> bool {
+        self.light_fuse(blocks, index, FUSE_SECONDS)
+    }
+
+    fn light_fuse(&mut self, blocks: &[Block], index: usize, seconds: f32) -> bool {
+        let Some(block) = blocks.get(index) else {
+            return false;
+        };
+        if !Self::is_tnt(block) || self.fuses.iter().any(|fuse| fuse.block_index == index) {
+            return false;
+        }
+        self.fuses.push(Fuse {
+            block_index: index,
+            remaining: seconds,
+            elapsed: 0.0,
+            base: block.clone(),
+        });
+        true
+    }
+
+    /// Si hay algo en curso (mechas, destellos o sacudida)
+    pub fn is_active(&self) -> bool {
+        !self.fuses.is_empty() || !self.flashes.is_empty() || self.shake > 0.0
+    }
+
+    /// Olvida todo (al cambiar de escena)
+    pub fn clear(&mut self) {
+        self.fuses.clear();
+        self.flashes.clear();
+        self.shake = 0.0;
+    }
+
+    /// Ajusta los índices tras quitar el bloque `removed` por otro medio,
+    /// con `last` el índice que tenía el último (ver `index_after_removal`);
+    /// si era una TNT encendida, su mecha se apaga
+    pub fn on_block_removed(&mut self, removed: usize, last: usize) {
+        self.fuses.retain_mut(
+            |fuse| match index_after_removal(fuse.block_index, removed, last) {
+                Some(index) => {
+                    fuse.block_index = index;
+                    true
+                }
+                None => false,
+            },
+        );
+    }
+
+    /// Avanza `dt` segundos: parpadean las mechas, explotan las que llegan a
+    /// cero (marcando los bloques dentro de `radius`) y se apagan destellos
+    /// y sacudida. La escena no se toca: el paso devuelve el brillo de las
+    /// mechas y los bloques que vuelan, que se quitan en el orden de
+    /// `ExplosionStep::removal_order`.
+    pub fn update(&mut self, blocks: &[Block], dt: f32, radius: f32) -> ExplosionStep {
+        self.shake = (self.shake - dt).max(0.0);
+        self.flashes.retain_mut(|flash| {
+            flash.remaining -= dt;
+            flash.remaining > 0.0
+        });
+
+        let mut step = ExplosionStep::default();
+        for fuse in &mut self.fuses {
+            fuse.remaining -= dt;
+            fuse.elapsed += dt;
+            if let Some(block) = blocks.get(fuse.block_index) {
+                let pulse = 0.5 - 0.5 * (fuse.elapsed * TAU * FUSE_BLINK_RATE).cos();
+                let mut block = block.clone();
+                glow(&mut block, &fuse.base, pulse * FUSE_GLOW);
+                step.glowing.push((fuse.block_index, block));
+            }
+        }
+
+        // Las de la cadena se suman a `fuses` y explotan en pasos siguientes
+        let mut gone = vec![false; blocks.len()];
+        while let Some(i) = self.fuses.iter().position(|fuse| fuse.remaining <= 0.0) {
+            let fuse = self.fuses.remove(i);
+            step.blasts
+                .push(self.detonate(blocks, &mut gone, fuse.block_index, radius));
+        }
+        let mut len = blocks.len();
+        for removed in step.removal_order() {
+            len -= 1;
+            self.on_block_removed(removed, len);
+        }
+        step
+    }
+
+    /// Marca la TNT y los bloques alrededor que no marcó otra explosión del
+    /// paso (`gone`): seguro en la mitad interior del radio y con
+    /// probabilidad que cae hasta cero en el borde. Las TNT alcanzadas no
+    /// vuelan sino que se encienden (reacción en cadena).
+    fn detonate(
+        &mut self,
+        blocks: &[Block],
+        gone: &mut [bool],
+        index: usize,
+        radius: f32,
+    ) -> Blast {
+        let center = blocks[index].position;
+        let mut doomed = Vec::new();
+        let mut chained = Vec::new();
+        for (i, block) in blocks.iter().enumerate() {
+            if gone[i] {
+                continue;
+            }
+            if i == index {
+                doomed.push(i);
+                continue;
+            }
+            let distance = (block.position - center).length();
+            if distance > radius {
+                continue;
+            }
+            if Self::is_tnt(block) {
+                chained.push(i);
+                continue;
+            }
+            let chance = ((radius - distance) / (radius * 0.5)).clamp(0.0, 1.0);
+            if self.rng.next_f32() < chance {
+                doomed.push(i);
+            }
+        }
+
+        for i in chained {
+            let seconds = CHAIN_FUSE_SECONDS + self.rng.next_f32() * CHAIN_FUSE_JITTER;
+            self.light_fuse(blocks, i, seconds);
+        }
+
+        for &i in &doomed {
+            gone[i] = true;
+        }
+
+        self.flashes.push(Flash {
+            position: center,
+            remaining: FLASH_SECONDS,
+        });
+        self.shake = SHAKE_SECONDS;
+        self.shake_strength = radius * SHAKE_PER_RADIUS;
+
+        Blast {
+            center,
+            removed: doomed,
+        }
+    }
+
+    /// Luces de los destellos, más débiles a medida que se apagan
+    pub fn flash_lights(&self) -> impl Iterator<Item = Light> + '_ {
+        self.flashes.iter().map(|flash| {
+            let fade = flash.remaining / FLASH_SECONDS;
+            Light::new(
+                flash.position,
+                Vector3::new(1.0, 0.8, 0.5),
+                FLASH_INTENSITY * fade * fade,
+            )
+        })
+    }
+
+    /// Desplazamiento de la cámara por la sacudida (cero si no hay). Sale
+    /// del tiempo que le queda y no del generador, así no cambia qué bloques
+    /// vuelan según cuántos frames se rendericen.
+    pub fn camera_offset(&self) -> Vector3 {
+        if self.shake <= 0.0 {
+            return Vector3::zero();
+        }
+        let amount = self.shake_strength * self.shake / SHAKE_SECONDS;
+        let t = self.shake * 60.0;
+        Vector3::new(
+            (t * 1.3).sin() * amount,
+            (t * 1.7 + 1.0).sin() * amount,
+            (t * 1.1 + 2.0).sin() * amount,
+        )
+    }
+}
+
+/// Materiales del bloque con el brillo blanco de la mecha encima de los
+/// originales
+fn glow(block: &mut Block, base: &Block, strength: f32) {
+    block.material = with_glow(&base.material, strength);
+    if let (Some(faces), Some(base_faces)) = (&mut block.face_overrides, &base.face_overrides) {
+        for (face, base_face) in faces.iter_mut().zip(base_faces.iter()) {
+            *face = base_face.as_ref().map(|material| with_glow(material, strength));
+        }
+    }
+}
+
+fn with_glow(material: &Material, strength: f32) -> Material {
+    let mut material = material.clone();
+    material.emission_color = Some(Vector3::one());
+    material.emission_strength = strength;
+    material
+}
+
+/// Registra las texturas procedurales de la TNT: costado rojo con una franja
+/// blanca y las letras, tapas con la mecha en el centro
+pub fn insert_tnt_textures(tex_mgr: &mut TextureManager) {
+    let size = TNT_TEXTURE_SIZE;
+    let red = Vector3::new(0.8, 0.15, 0.1);
+    let dark_red = Vector3::new(0.6, 0.1, 0.07);
+    let white = Vector3::new(0.92, 0.92, 0.88);
+    let ink = Vector3::new(0.15, 0.12, 0.1);
+
+    tex_mgr.insert_procedural(TNT_SIDE_TEXTURE, size, size, |x, y| {
+        if !(5..11).contains(&y) {
+            // Cartuchos: rayas verticales más oscuras
+            return if x % 4 == 3 { dark_red } else { red };
+        }
+        // Letras de 3x5 en las filas 6..11: T, N, T
+        let (row, letter_x) = (y.wrapping_sub(6), x.wrapping_sub(3));
+        if row < 5 && letter_x < 11 && letter_x % 4 < 3 {
+            let column = letter_x % 4;
+            let is_n = letter_x / 4 == 1;
+            let inked = if is_n {
+                column != 1 || row == 1 || row == 2
+            } else {
+                row == 0 || column == 1
+            };
+            if inked {
+                return ink;
+            }
+        }
+        white
+    });
+
+    tex_mgr.insert_procedural(TNT_TOP_TEXTURE, size, size, |x, y| {
+        let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+        let fuse = (7..9).contains(&x) && (7..9).contains(&y);
+        if border {
+            red
+        } else if fuse {
+            ink
+        } else {
+            Vector3::new(0.75, 0.7, 0.65)
+        }
+    });
+}
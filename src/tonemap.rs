@@ -0,0 +1,317 @@
+// tonemap.rs - Conversión del color lineal del raytracer a rango mostrable
+use raylib::prelude::*;
+
+use crate::post::luminance;
+
+/// Punto a partir del cual la compresión suave empieza a actuar.
+/// Por debajo el color queda intacto, así la escena normal casi no cambia.
+const ROLLOVER_KNEE: f32 = 0.8;
+
+/// Gamma de salida para los operadores que trabajan en espacio lineal
+const GAMMA: f32 = 2.2;
+
+/// Límites y paso del ajuste de exposición (en EV)
+pub const MIN_EXPOSURE_EV: f32 = -4.0;
+pub const MAX_EXPOSURE_EV: f32 = 4.0;
+pub const EXPOSURE_STEP_EV: f32 = 0.25;
+
+/// Operador de mapeo tonal aplicado a cada pixel antes de cuantizar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ToneMapping {
+    /// Sin operador: recorte duro por canal, sin gamma (comportamiento original).
+    Clamp,
+    /// Compresión suave por encima de `ROLLOVER_KNEE`: los emisores fuertes
+    /// conservan un núcleo brillante con caída natural en vez de saturar.
+    SoftRollover,
+    /// Reinhard clásico x/(1+x) seguido de gamma 2.2.
+    Reinhard,
+    /// Aproximación de la curva ACES (Narkowicz) seguida de gamma 2.2.
+    Aces,
+}
+
+impl ToneMapping {
+    /// Siguiente operador (para alternar con una tecla)
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapping::Clamp => ToneMapping::SoftRollover,
+            ToneMapping::SoftRollover => ToneMapping::Reinhard,
+            ToneMapping::Reinhard => ToneMapping::Aces,
+            ToneMapping::Aces => ToneMapping::Clamp,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ToneMapping::Clamp => "Ninguno",
+            ToneMapping::SoftRollover => "Suave",
+            ToneMapping::Reinhard => "Reinhard",
+            ToneMapping::Aces => "ACES",
+        }
+    }
+
+    /// Nombre usado en el archivo de configuración
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMapping::Clamp => "none",
+            ToneMapping::SoftRollover => "soft",
+            ToneMapping::Reinhard => "reinhard",
+            ToneMapping::Aces => "aces",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "clamp" => Some(ToneMapping::Clamp),
+            "soft" => Some(ToneMapping::SoftRollover),
+            "reinhard" => Some(ToneMapping::Reinhard),
+            "aces" => Some(ToneMapping::Aces),
+            _ => None,
+        }
+    }
+
+    /// Reinhard y ACES comprimen en lineal y necesitan codificar con gamma
+    fn uses_gamma(self) -> bool {
+        matches!(self, ToneMapping::Reinhard | ToneMapping::Aces)
+    }
+}
+
+/// Operador + exposición: todo lo necesario para pasar de lineal a pantalla.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ToneMapSettings {
+    pub operator: ToneMapping,
+    /// Exposición en pasos EV (0 = sin cambio, +1 = doble de luz)
+    pub exposure_ev: f32,
+}
+
+impl Default for ToneMapSettings {
+    fn default() -> Self {
+        Self {
+            operator: ToneMapping::SoftRollover,
+            exposure_ev: 0.0,
+        }
+    }
+}
+
+impl ToneMapSettings {
+    /// Ajusta la exposición en `steps` pasos, dentro de los límites
+    pub fn adjust_exposure(&mut self, steps: f32) {
+        self.exposure_ev =
+            (self.exposure_ev + steps * EXPOSURE_STEP_EV).clamp(MIN_EXPOSURE_EV, MAX_EXPOSURE_EV);
+    }
+}
+
+/// Cada cuántos pixeles se toma una muestra para medir la luminancia media
+/// (número primo para no alinearse con filas ni tiles)
+const LUMINANCE_SAMPLE_STRIDE: usize = 7;
+
+/// Exposición automática: mide la luminancia media logarítmica del buffer
+/// HDR y adapta la exposición hacia un gris medio, como el ojo al entrar en
+/// un lugar oscuro. La exposición manual se suma como compensación.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AutoExposure {
+    pub enabled: bool,
+    /// Luminancia a la que se lleva la media de la escena
+    pub target_luminance: f32,
+    /// Límites de la exposición automática (en EV)
+    pub min_ev: f32,
+    pub max_ev: f32,
+    /// Velocidad de adaptación al aclarar (escena oscura) y al oscurecer
+    /// (escena brillante), en 1/segundos
+    pub speed_up: f32,
+    pub speed_down: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            // Media de la escena original con exposición 0, así el aspecto
+            // por defecto casi no cambia al activarla
+            target_luminance: 0.3,
+            min_ev: -2.0,
+            max_ev: 4.0,
+            speed_up: 1.5,
+            speed_down: 3.0,
+        }
+    }
+}
+
+impl AutoExposure {
+    /// Exposición que llevaría la luminancia media del buffer al objetivo
+    pub fn target_ev(&self, hdr: &[Vector3]) -> f32 {
+        let average = log_average_luminance(hdr);
+        if average <= 0.0 {
+            return self.max_ev;
+        }
+        (self.target_luminance / average)
+            .log2()
+            .clamp(self.min_ev, self.max_ev)
+    }
+
+    /// Acerca `current_ev` a `target_ev` de forma exponencial; con las
+    /// velocidades por defecto la adaptación tarda alrededor de un segundo
+    pub fn adapt(&self, current_ev: f32, target_ev: f32, dt: f32) -> f32 {
+        let speed = if target_ev > current_ev {
+            self.speed_up
+        } else {
+            self.speed_down
+        };
+        current_ev + (target_ev - current_ev) * (1.0 - (-dt * speed).exp())
+    }
+}
+
+/// Media geométrica de la luminancia sobre una muestra del buffer
+pub fn log_average_luminance(hdr: &[Vector3]) -> f32 {
+    const DELTA: f32 = 1e-4;
+    let mut sum = 0.0;
+    let mut count = 0;
+    for &color in hdr.iter().step_by(LUMINANCE_SAMPLE_STRIDE) {
+        sum += (luminance(color).max(0.0) + DELTA).ln();
+        count += 1;
+    }
+    if count == 0 {
+        return 0.0;
+    }
+    (sum / count as f32).exp()
+}
+
+/// Compresión suave de un canal: identidad hasta la rodilla y luego
+/// una curva tipo x/(1+x) que tiende a 1.0 sin llegar a recortar.
+#[inline]
+fn soft_rollover(x: f32) -> f32 {
+    if x <= ROLLOVER_KNEE {
+        return x;
+    }
+    let range = 1.0 - ROLLOVER_KNEE;
+    let excess = x - ROLLOVER_KNEE;
+    ROLLOVER_KNEE + range * excess / (range + excess)
+}
+
+#[inline]
+fn reinhard(x: f32) -> f32 {
+    x / (1.0 + x)
+}
+
+/// Ajuste analítico de la curva ACES (Krzysztof Narkowicz)
+#[inline]
+fn aces(x: f32) -> f32 {
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+/// Aplica exposición, operador y gamma; el resultado queda en [0, 1]
+#[inline]
+pub fn tone_map(color: Vector3, settings: ToneMapSettings) -> Vector3 {
+    let exposed = if settings.exposure_ev == 0.0 {
+        color
+    } else {
+        color * settings.exposure_ev.exp2()
+    };
+
+    let curve: fn(f32) -> f32 = match settings.operator {
+        ToneMapping::Clamp => |x| x,
+        ToneMapping::SoftRollover => soft_rollover,
+        ToneMapping::Reinhard => reinhard,
+        ToneMapping::Aces => aces,
+    };
+    let encode = |x: f32| {
+        let mapped = curve(x.max(0.0)).clamp(0.0, 1.0);
+        if settings.operator.uses_gamma() {
+            mapped.powf(1.0 / GAMMA)
+        } else {
+            mapped
+        }
+    };
+
+    Vector3::new(encode(exposed.x), encode(exposed.y), encode(exposed.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATORS: [ToneMapping; 4] = [
+        ToneMapping::Clamp,
+        ToneMapping::SoftRollover,
+        ToneMapping::Reinhard,
+        ToneMapping::Aces,
+    ];
+
+    fn settings(operator: ToneMapping, exposure_ev: f32) -> ToneMapSettings {
+        ToneMapSettings {
+            operator,
+            exposure_ev,
+        }
+    }
+
+    #[test]
+    fn every_operator_is_monotonic_and_in_range() {
+        for operator in OPERATORS {
+            for exposure_ev in [MIN_EXPOSURE_EV, 0.0, MAX_EXPOSURE_EV] {
+                let mut previous = 0.0;
+                // De 0 a 50 en pasos finos, pasando la rodilla de `SoftRollover`
+                for i in 0..=5000 {
+                    let x = i as f32 * 0.01;
+                    let mapped = tone_map(Vector3::new(x, x, x), settings(operator, exposure_ev));
+                    // Un ulp de redondeo cerca de 1.0 no llega a los 8 bits
+                    assert!(
+                        mapped.x >= previous - 1e-6,
+                        "{:?} a {} EV baja en {}: {} < {}",
+                        operator,
+                        exposure_ev,
+                        x,
+                        mapped.x,
+                        previous
+                    );
+                    assert!((0.0..=1.0).contains(&mapped.x));
+                    previous = mapped.x;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn more_exposure_never_darkens() {
+        let color = Vector3::new(0.05, 0.4, 3.0);
+        for operator in OPERATORS {
+            let mut previous = Vector3::zero();
+            let mut ev = MIN_EXPOSURE_EV;
+            while ev <= MAX_EXPOSURE_EV {
+                let mapped = tone_map(color, settings(operator, ev));
+                assert!(
+                    mapped.x >= previous.x - 1e-6
+                        && mapped.y >= previous.y - 1e-6
+                        && mapped.z >= previous.z - 1e-6,
+                    "{:?} a {} EV: {:?} < {:?}",
+                    operator,
+                    ev,
+                    mapped,
+                    previous
+                );
+                previous = mapped;
+                ev += EXPOSURE_STEP_EV;
+            }
+        }
+    }
+
+    #[test]
+    fn clamp_at_zero_exposure_matches_plain_clamp() {
+        for color in [
+            Vector3::new(-0.5, 0.0, 0.25),
+            Vector3::new(0.5, 0.999, 1.0),
+            Vector3::new(1.5, 40.0, 0.8),
+        ] {
+            let mapped = tone_map(color, settings(ToneMapping::Clamp, 0.0));
+            let clamped = Vector3::new(
+                color.x.clamp(0.0, 1.0),
+                color.y.clamp(0.0, 1.0),
+                color.z.clamp(0.0, 1.0),
+            );
+            assert_eq!(mapped, clamped);
+        }
+    }
+}
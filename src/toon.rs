@@ -0,0 +1,109 @@
+// toon.rs - Modo dibujo animado: luz en bandas y contornos oscuros
+use raylib::prelude::*;
+
+use crate::framebuffer::{Framebuffer, color_to_u32};
+use crate::gbuffer::GBufferSample;
+
+/// Salto de profundidad entre pixeles vecinos, relativo a la distancia del
+/// más cercano, a partir del cual hay un contorno
+const OUTLINE_DEPTH_JUMP: f32 = 0.1;
+/// Coseno entre normales vecinas por debajo del cual hay un contorno (las
+/// caras de un bloque siempre lo cruzan)
+const OUTLINE_NORMAL_COS: f32 = 0.8;
+/// Brillo del especular duro: lo que supera el umbral queda en 1
+const HIGHLIGHT_THRESHOLD: f32 = 0.5;
+
+/// Sombreado de dibujo animado. No es una opción de calidad: los presets
+/// lo conservan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ToonShading {
+    pub enabled: bool,
+    /// Niveles en que se corta la luz difusa
+    pub bands: u32,
+    /// Grosor de los contornos en pixeles; 0 = sin contornos
+    pub outline_thickness: u32,
+}
+
+impl Default for ToonShading {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bands: 4,
+            outline_thickness: 1,
+        }
+    }
+}
+
+impl ToonShading {
+    /// Corta el término difuso (`n·l` en [0, 1]) en `bands` niveles. Redondea
+    /// hacia arriba: solo lo que no recibe luz queda en la banda oscura.
+    pub fn quantize_diffuse(&self, n_dot_l: f32) -> f32 {
+        let bands = self.bands.max(1) as f32;
+        (n_dot_l * bands).ceil().min(bands) / bands
+    }
+
+    /// Especular sin degradado: un brillo recortado o nada
+    pub fn hard_highlight(spec: f32) -> f32 {
+        if spec > HIGHLIGHT_THRESHOLD { 1.0 } else { 0.0 }
+    }
+}
+
+/// Dibuja contornos negros sobre la imagen ya mapeada donde la profundidad o
+/// la normal de pixeles vecinos cambian de golpe (bordes de bloques y
+/// siluetas contra el cielo). Usa el G-buffer del frame, que el renderer
+/// guarda si el modo está activo (`record_gbuffer`).
+pub fn apply_outlines(framebuffer: &mut Framebuffer, toon: &ToonShading) {
+    if !toon.enabled || toon.outline_thickness == 0 {
+        return;
+    }
+    let width = framebuffer.width as usize;
+    let height = framebuffer.height as usize;
+    let (buffer, gbuffer) = framebuffer.buffer_with_gbuffer();
+
+    // Cada pixel se compara con el de la derecha y el de abajo: el borde
+    // queda de un pixel de ancho
+    let mut edges = vec![false; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let center = &gbuffer[index];
+            let right = (x + 1 < width).then(|| &gbuffer[index + 1]);
+            let below = (y + 1 < height).then(|| &gbuffer[index + width]);
+            edges[index] = [right, below]
+                .into_iter()
+                .flatten()
+                .any(|neighbor| is_edge(center, neighbor));
+        }
+    }
+
+    // Engrosar: cada pixel a menos de `outline_thickness` de un borde
+    let radius = toon.outline_thickness as usize - 1;
+    let outline = color_to_u32(Color::BLACK);
+    for y in 0..height {
+        for x in 0..width {
+            let near_edge = (y.saturating_sub(radius)..=(y + radius).min(height - 1)).any(|ny| {
+                (x.saturating_sub(radius)..=(x + radius).min(width - 1))
+                    .any(|nx| edges[ny * width + nx])
+            });
+            if near_edge {
+                buffer[y * width + x] = outline;
+            }
+        }
+    }
+}
+
+/// Si entre dos pixeles vecinos hay un contorno
+fn is_edge(a: &GBufferSample, b: &GBufferSample) -> bool {
+    match (a.distance.is_finite(), b.distance.is_finite()) {
+        (false, false) => false,
+        // Silueta contra el cielo
+        (true, false) | (false, true) => true,
+        (true, true) => {
+            let nearest = a.distance.min(b.distance);
+            (a.distance - b.distance).abs() > OUTLINE_DEPTH_JUMP * nearest
+                || a.normal.dot(b.normal) < OUTLINE_NORMAL_COS
+        }
+    }
+}
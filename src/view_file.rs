@@ -0,0 +1,322 @@
+// view_file.rs - Vista de una captura: cámara, escena y ajustes guardados
+// junto a la imagen para poder volver a armarla. En RON con la feature
+// "serde", si no en el formato `clave = valor` de las preferencias.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use raylib::prelude::*;
+
+use crate::config::{Config, ConfigError};
+#[cfg(not(feature = "serde"))]
+use crate::headless::parse_camera;
+use crate::headless::{SceneSource, parse_scene};
+use crate::scene::{Scene, SceneKind};
+use crate::scene_file::{SceneError, load_scene};
+use crate::settings::Settings;
+#[cfg(not(feature = "serde"))]
+use crate::settings::parse_key_values;
+
+/// Extensión del archivo de vista, la misma que la de las preferencias
+#[cfg(feature = "serde")]
+const VIEW_EXTENSION: &str = "ron";
+#[cfg(not(feature = "serde"))]
+const VIEW_EXTENSION: &str = "toml";
+
+/// Lo necesario para repetir una captura. Yaw y pitch van en radianes,
+/// como la cámara del modo interactivo.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedView {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::Vec3Def"))]
+    pub camera_pos: Vector3,
+    pub camera_yaw: f32,
+    pub camera_pitch: f32,
+    /// FOV vertical en grados, como en `config.toml`
+    #[cfg_attr(feature = "serde", serde(default = "default_fov"))]
+    pub fov_degrees: f32,
+    /// Escena como la acepta `--scene`: una incorporada o un archivo
+    pub scene: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub settings: Settings,
+}
+
+fn default_fov() -> f32 {
+    Config::default().fov_degrees
+}
+
+impl SavedView {
+    /// Construye la escena de la vista
+    pub fn build_scene(&self) -> Result<(SceneKind, Scene), SceneError> {
+        match parse_scene(&self.scene) {
+            SceneSource::Builtin(kind) => kind.build().map(|scene| (kind, scene)),
+            SceneSource::File(path) => load_scene(&path).map(|scene| (SceneKind::Saved, scene)),
+        }
+    }
+}
+
+/// Archivo de vista de la captura `capture`: el mismo nombre con `.ron`
+/// (`.toml` sin la feature "serde")
+pub fn view_path(capture: &Path) -> PathBuf {
+    capture.with_extension(VIEW_EXTENSION)
+}
+
+/// Guarda la vista en RON: la cámara y la escena primero, después todas
+/// las preferencias
+#[cfg(feature = "serde")]
+pub fn save_view<P: AsRef<Path>>(path: P, view: &SavedView) -> Result<(), ConfigError> {
+    let path = path.as_ref();
+    let text = ron::ser::to_string_pretty(view, ron::ser::PrettyConfig::default())
+        .map_err(|err| ConfigError::invalid("vista serializable", err.to_string()))?;
+    let text = format!(
+        "// Vista de una captura: --from-screenshot o `view load` la restauran\n{}\n",
+        text
+    );
+    fs::write(path, text).map_err(|err| ConfigError::io(path, err))
+}
+
+/// Guarda la vista en el formato de `settings.toml`: la cámara y la escena
+/// primero, después todas las preferencias
+#[cfg(not(feature = "serde"))]
+pub fn save_view<P: AsRef<Path>>(path: P, view: &SavedView) -> Result<(), ConfigError> {
+    let path = path.as_ref();
+    let text = format!(
+        "# Vista de una captura: --from-screenshot o `view load` la restauran\n\
+         camera = \"{},{},{},{},{}\"\n\
+         fov = {}\n\
+         scene = \"{}\"\n\
+         \n\
+         {}",
+        view.camera_pos.x,
+        view.camera_pos.y,
+        view.camera_pos.z,
+        view.camera_yaw,
+        view.camera_pitch,
+        view.fov_degrees,
+        view.scene,
+        view.settings.to_text(),
+    );
+    fs::write(path, text).map_err(|err| ConfigError::io(path, err))
+}
+
+/// Lee una vista guardada con `save_view`. Los campos que no se conocen (de
+/// una versión más nueva) se ignoran con un aviso; la cámara y la escena
+/// son obligatorias, y una escena de archivo tiene que seguir existiendo.
+#[cfg(feature = "serde")]
+pub fn load_view<P: AsRef<Path>>(path: P) -> Result<SavedView, ConfigError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|err| ConfigError::io(path, err))?;
+    let parse_error = |err: ron::error::SpannedError| ConfigError::Parse {
+        path: path.to_path_buf(),
+        line: err.position.line,
+        message: err.code.to_string(),
+    };
+
+    let found: ron::Value = ron::from_str(&text).map_err(parse_error)?;
+    for field in unknown_fields(&found) {
+        warn!(
+            "{}: se ignora '{}' (opción desconocida)",
+            path.display(),
+            field
+        );
+    }
+    let mut view: SavedView = ron::from_str(&text).map_err(parse_error)?;
+    view.settings.sanitize();
+    if !(view.fov_degrees > 0.0 && view.fov_degrees < 180.0) {
+        return Err(ConfigError::invalid(
+            "el FOV de la vista",
+            view.fov_degrees.to_string(),
+        ));
+    }
+    finish_view(view)
+}
+
+/// Campos de una vista leída como `ron::Value` que no existen en esta
+/// versión, con su camino (ej: `settings.render.foo`)
+#[cfg(feature = "serde")]
+fn unknown_fields(found: &ron::Value) -> Vec<String> {
+    fn collect(found: &ron::Value, known: &ron::Value, prefix: &str, out: &mut Vec<String>) {
+        let (ron::Value::Map(found), ron::Value::Map(known)) = (found, known) else {
+            return;
+        };
+        for (key, value) in found.iter() {
+            let ron::Value::String(name) = key else {
+                continue;
+            };
+            let field = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            match known.iter().find(|(known_key, _)| *known_key == key) {
+                Some((_, known_value)) => collect(value, known_value, &field, out),
+                None => out.push(field),
+            }
+        }
+    }
+
+    // Los campos que existen salen de una vista cualquiera serializada
+    let template = SavedView {
+        camera_pos: Vector3::zero(),
+        camera_yaw: 0.0,
+        camera_pitch: 0.0,
+        fov_degrees: default_fov(),
+        scene: String::new(),
+        settings: Settings::default(),
+    };
+    let known = ron::to_string(&template)
+        .ok()
+        .and_then(|text| ron::from_str::<ron::Value>(&text).ok());
+    let mut unknown = Vec::new();
+    if let Some(known) = known {
+        collect(found, &known, "", &mut unknown);
+    }
+    unknown
+}
+
+/// Lee una vista guardada con `save_view`. Las claves que no se conocen (de
+/// una versión más nueva) se ignoran con un aviso; la cámara y la escena
+/// son obligatorias, y una escena de archivo tiene que seguir existiendo.
+#[cfg(not(feature = "serde"))]
+pub fn load_view<P: AsRef<Path>>(path: P) -> Result<SavedView, ConfigError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|err| ConfigError::io(path, err))?;
+
+    let mut camera = None;
+    let mut fov_degrees = None;
+    let mut scene = None;
+    let mut settings = Settings::default();
+    for (line, key, value) in parse_key_values(&text) {
+        let parse_error = |message: String| ConfigError::Parse {
+            path: path.to_path_buf(),
+            line,
+            message,
+        };
+        match key.as_str() {
+            "camera" => {
+                camera = Some(parse_camera(&value).map_err(|err| parse_error(err.to_string()))?)
+            }
+            "fov" => {
+                let fov = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|fov| *fov > 0.0 && *fov < 180.0)
+                    .ok_or_else(|| parse_error(format!("FOV inválido '{}'", value)))?;
+                fov_degrees = Some(fov);
+            }
+            "scene" => scene = Some(value),
+            _ => {
+                if !settings.apply(&key, &value) {
+                    warn!(
+                        "{}:{}: se ignora '{}' (opción desconocida o valor inválido)",
+                        path.display(),
+                        line,
+                        key
+                    );
+                }
+            }
+        }
+    }
+    let missing = |key: &str| ConfigError::MissingValue(format!("'{}' en {}", key, path.display()));
+    let (camera_pos, camera_yaw, camera_pitch) = camera.ok_or_else(|| missing("camera"))?;
+    finish_view(SavedView {
+        camera_pos,
+        camera_yaw,
+        camera_pitch,
+        fov_degrees: fov_degrees.unwrap_or(default_fov()),
+        scene: scene.ok_or_else(|| missing("scene"))?,
+        settings,
+    })
+}
+
+/// Lo común a los dos formatos: el cielo sale de la hora del día, como al
+/// leer las preferencias, y una escena de archivo tiene que existir
+fn finish_view(mut view: SavedView) -> Result<SavedView, ConfigError> {
+    view.settings.render.sky = view.settings.day.sky();
+    if let SceneSource::File(scene_path) = parse_scene(&view.scene)
+        && !Path::new(&scene_path).exists()
+    {
+        return Err(ConfigError::invalid(
+            "la escena de la vista (no existe)",
+            scene_path,
+        ));
+    }
+    Ok(view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_view(name: &str) -> PathBuf {
+        let capture = std::env::temp_dir().join(format!(
+            "raytracer_view_{}_{}.png",
+            name,
+            std::process::id()
+        ));
+        view_path(&capture)
+    }
+
+    fn sample_view() -> SavedView {
+        let mut settings = Settings {
+            fxaa: false,
+            ..Settings::default()
+        };
+        settings.render.samples_per_pixel = 4;
+        settings.day.set_hour(19.5);
+        settings.render.sky = settings.day.sky();
+        SavedView {
+            camera_pos: Vector3::new(1.5, 4.0, -10.25),
+            camera_yaw: 1.2,
+            camera_pitch: -0.3,
+            fov_degrees: 75.0,
+            scene: "winter".to_string(),
+            settings,
+        }
+    }
+
+    #[test]
+    fn saved_view_round_trips() {
+        let path = temp_view("round_trip");
+        #[cfg(feature = "serde")]
+        assert_eq!(path.extension().unwrap(), "ron");
+        let view = sample_view();
+        save_view(&path, &view).unwrap();
+        let loaded = load_view(&path);
+        fs::remove_file(&path).ok();
+        assert_eq!(loaded.unwrap(), view);
+    }
+
+    #[test]
+    fn a_missing_scene_file_is_an_error() {
+        let path = temp_view("missing_scene");
+        let view = SavedView {
+            scene: "no_existe_esta_escena.txt".to_string(),
+            ..sample_view()
+        };
+        save_view(&path, &view).unwrap();
+        let loaded = load_view(&path);
+        fs::remove_file(&path).ok();
+        assert!(loaded.is_err(), "{:?}", loaded);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn unknown_fields_are_reported_with_their_path() {
+        let text = "(camera_pos: (x: 0, y: 1, z: 2), camera_yaw: 0, camera_pitch: 0, \
+                    scene: \"island\", lens: \"fisheye\", \
+                    settings: (fxaa: false, render: (shadows: true, new_knob: 2)))";
+        let found: ron::Value = ron::from_str(text).unwrap();
+        assert_eq!(unknown_fields(&found), ["lens", "settings.render.new_knob"]);
+
+        // Y la vista se lee igual, con el FOV por defecto
+        let path = temp_view("unknown_fields");
+        fs::write(&path, text).unwrap();
+        let loaded = load_view(&path);
+        fs::remove_file(&path).ok();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.camera_pos, Vector3::new(0.0, 1.0, 2.0));
+        assert_eq!(loaded.fov_degrees, default_fov());
+        assert!(!loaded.settings.fxaa);
+    }
+}
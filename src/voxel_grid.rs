@@ -0,0 +1,596 @@
+// voxel_grid.rs - Grilla de celdas de lado 1 sobre los bloques de la escena:
+// un rayo prueba solo los bloques de las celdas que cruza, y poner o quitar
+// un bloque la actualiza en el lugar sin armarla de nuevo
+use std::collections::HashMap;
+
+use raylib::prelude::*;
+
+use crate::block::Block;
+
+/// Celda de la grilla: la posición del bloque redondeada
+pub type Cell = (i32, i32, i32);
+
+/// Cuánto puede salirse una caja de su celda y seguir contando como
+/// adentro (redondeos al sumar posición y tamaño)
+const FIT_TOLERANCE: f32 = 1e-4;
+
+/// Si el rayo cruza dos bordes a menos de esto, pasa por una arista y se
+/// visitan también las celdas de costado
+const EDGE_EPSILON: f32 = 1e-4;
+
+/// Cuántas veces cambió la grilla, para la pantalla de depuración
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GridCounters {
+    /// Bloques puestos, quitados o cambiados en el lugar
+    pub updates: u64,
+    /// Celdas escritas por esas ediciones: a lo sumo dos por edición, sin
+    /// importar el tamaño de la escena
+    pub cells_touched: u64,
+    /// Veces que se armó de cero
+    pub rebuilds: u64,
+}
+
+/// Índices de bloque por celda, siempre ordenados. Los bloques que no
+/// entran en una celda (más grandes, corridos media celda, la tapa abierta
+/// de un cofre) quedan afuera y los lleva la BVH de la escena (`bvh.rs`).
+#[derive(Debug, Clone, Default)]
+pub struct VoxelGrid {
+    cells: HashMap<Cell, Vec<u32>>,
+    /// Celda de cada bloque por índice (None si no entra en una)
+    block_cells: Vec<Option<Cell>>,
+    /// Celdas extremas que tuvieron algún bloque; solo crece
+    bounds: Option<(Cell, Cell)>,
+    counters: GridCounters,
+}
+
+/// Dos grillas son iguales si reparten igual los bloques; los contadores y
+/// el recorte (que no se achica al quitar) no cuentan
+impl PartialEq for VoxelGrid {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells && self.block_cells == other.block_cells
+    }
+}
+
+impl VoxelGrid {
+    pub fn build(blocks: &[Block]) -> Self {
+        let mut grid = Self::default();
+        for (index, block) in blocks.iter().enumerate() {
+            let cell = cell_of(block);
+            grid.block_cells.push(cell);
+            if let Some(cell) = cell {
+                grid.add(index as u32, cell);
+            }
+        }
+        grid.counters = GridCounters::default();
+        grid
+    }
+
+    /// Arma la grilla de cero conservando los contadores
+    pub fn rebuild(&mut self, blocks: &[Block]) {
+        let counters = self.counters;
+        *self = Self::build(blocks);
+        self.counters = GridCounters {
+            rebuilds: counters.rebuilds + 1,
+            ..counters
+        };
+    }
+
+    /// Bloque nuevo al final de la lista (`block_id` es la cantidad de
+    /// bloques que había). Devuelve su celda, o None si no entra en una.
+    pub fn insert(&mut self, block_id: usize, block: &Block) -> Option<Cell> {
+        debug_assert_eq!(block_id, self.block_cells.len());
+        let cell = cell_of(block);
+        self.block_cells.push(cell);
+        if let Some(cell) = cell {
+            self.add(block_id as u32, cell);
+        }
+        self.counters.updates += 1;
+        cell
+    }
+
+    /// Saca el bloque `block_id` como `Vec::swap_remove`: el último pasa a
+    /// ese índice y ningún otro cambia. Toca a lo sumo dos celdas, la del
+    /// bloque y la del que se mudó. Devuelve la celda que tenía.
+    pub fn remove(&mut self, block_id: usize) -> Option<Cell> {
+        let last = self.block_cells.len() - 1;
+        let cell = self.block_cells.swap_remove(block_id);
+        if let Some(cell) = cell {
+            self.take(block_id as u32, cell);
+        }
+        if block_id != last
+            && let Some(moved) = self.block_cells[block_id]
+            && let Some(ids) = self.cells.get_mut(&moved)
+        {
+            for id in ids.iter_mut().filter(|id| **id == last as u32) {
+                *id = block_id as u32;
+            }
+            ids.sort_unstable();
+            self.counters.cells_touched += 1;
+        }
+        self.counters.updates += 1;
+        cell
+    }
+
+    /// El bloque `block_id` cambió (forma, posición): pasa a su celda nueva
+    /// si hace falta. Devuelve esa celda, o None si ya no entra en una.
+    pub fn update(&mut self, block_id: usize, block: &Block) -> Option<Cell> {
+        let cell = cell_of(block);
+        let previous = std::mem::replace(&mut self.block_cells[block_id], cell);
+        if previous != cell {
+            if let Some(previous) = previous {
+                self.take(block_id as u32, previous);
+            }
+            if let Some(cell) = cell {
+                self.add(block_id as u32, cell);
+            }
+        }
+        self.counters.updates += 1;
+        cell
+    }
+
+    /// Celda del bloque `block_id`, None si no entra en una
+    pub fn block_cell(&self, block_id: usize) -> Option<Cell> {
+        self.block_cells.get(block_id).copied().flatten()
+    }
+
+    pub fn counters(&self) -> GridCounters {
+        self.counters
+    }
+
+    /// Bloques de las celdas que tocan la caja `min`-`max`, celda por celda
+    /// (a lo sumo dos por eje si la caja es más chica que una celda)
+    pub fn blocks_near(&self, min: Vector3, max: Vector3, mut visit: impl FnMut(u32)) {
+        let range = |min: f32, max: f32| (min - 0.5).ceil() as i32..=(max + 0.5).floor() as i32;
+        for x in range(min.x, max.x) {
+            for y in range(min.y, max.y) {
+                for z in range(min.z, max.z) {
+                    if let Some(ids) = self.cells.get(&(x, y, z)) {
+                        ids.iter().copied().for_each(&mut visit);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Celdas ocupadas con sus bloques, en cualquier orden
+    pub fn cells(&self) -> impl Iterator<Item = (Cell, &[u32])> {
+        self.cells.iter().map(|(cell, ids)| (*cell, ids.as_slice()))
+    }
+
+    /// Recorre en orden las celdas con bloques que cruza el rayo hasta
+    /// `max_t`, pasando a `visit` la distancia a la que entra en cada una y
+    /// sus bloques. Si el rayo pasa justo por una arista o por un borde se
+    /// visitan también las celdas del otro lado, así que una celda puede
+    /// aparecer dos veces. `visit` devuelve false para cortar el recorrido.
+    pub fn walk(
+        &self,
+        origin: &Vector3,
+        dir: &Vector3,
+        max_t: f32,
+        mut visit: impl FnMut(f32, &[u32]) -> bool,
+    ) {
+        let Some((lo, hi)) = self.bounds else {
+            return;
+        };
+        let (lo, hi) = ([lo.0, lo.1, lo.2], [hi.0, hi.1, hi.2]);
+        let o = [origin.x, origin.y, origin.z];
+        let d = [dir.x, dir.y, dir.z];
+
+        // Tramo del rayo dentro de la caja de la grilla
+        let (mut t_start, mut t_end) = (0.0f32, max_t);
+        for axis in 0..3 {
+            let (min, max) = (lo[axis] as f32 - 0.5, hi[axis] as f32 + 0.5);
+            if d[axis] == 0.0 {
+                if o[axis] < min || o[axis] > max {
+                    return;
+                }
+                continue;
+            }
+            let (t0, t1) = ((min - o[axis]) / d[axis], (max - o[axis]) / d[axis]);
+            t_start = t_start.max(t0.min(t1));
+            t_end = t_end.min(t0.max(t1));
+        }
+        if t_start > t_end {
+            return;
+        }
+
+        let step = d.map(|c| {
+            if c > 0.0 {
+                1
+            } else if c < 0.0 {
+                -1
+            } else {
+                0
+            }
+        });
+        let entry = [0, 1, 2].map(|a| o[a] + d[a] * t_start);
+        let mut cell = [0, 1, 2].map(|a| (entry[a].round() as i32).clamp(lo[a], hi[a]));
+        // Si el rayo sale de un borde de celda puede tocar la cara del
+        // vecino en el primer paso; si además corre paralelo a ese borde, en
+        // todo el recorrido. Esas celdas de al lado se visitan también.
+        let borders: Vec<(usize, i32)> = (0..3)
+            .filter_map(|a| {
+                let from_center = entry[a] - cell[a] as f32;
+                ((from_center.abs() - 0.5).abs() < EDGE_EPSILON)
+                    .then_some((a, if from_center > 0.0 { 1 } else { -1 }))
+            })
+            .collect();
+        let start_offsets = side_offsets(&borders);
+        let lane: Vec<_> = borders.into_iter().filter(|(a, _)| step[*a] == 0).collect();
+        let lane_offsets = side_offsets(&lane);
+
+        // Distancia al borde de salida de la celda en cada eje
+        let exit = |cell: [i32; 3], a: usize| {
+            if step[a] == 0 {
+                f32::INFINITY
+            } else {
+                (cell[a] as f32 + 0.5 * step[a] as f32 - o[a]) / d[a]
+            }
+        };
+        let mut t_enter = t_start;
+        if !self.visit_cells(cell, &start_offsets, t_enter, &mut visit) {
+            return;
+        }
+        loop {
+            let t_exit = [0, 1, 2].map(|a| exit(cell, a));
+            let axis = (0..3)
+                .min_by(|a, b| t_exit[*a].total_cmp(&t_exit[*b]))
+                .unwrap();
+            let t = t_exit[axis];
+            if t > t_end {
+                return;
+            }
+            for other in (0..3).filter(|other| *other != axis) {
+                if t_exit[other] - t < EDGE_EPSILON {
+                    let mut side = cell;
+                    side[other] += step[other];
+                    if !self.visit_cells(side, &lane_offsets, t_enter, &mut visit) {
+                        return;
+                    }
+                }
+            }
+            cell[axis] += step[axis];
+            if cell[axis] < lo[axis] || cell[axis] > hi[axis] {
+                return;
+            }
+            t_enter = t_enter.max(t);
+            if !self.visit_cells(cell, &lane_offsets, t_enter, &mut visit) {
+                return;
+            }
+        }
+    }
+
+    /// Visita `cell` corrida en cada uno de `offsets`
+    fn visit_cells(
+        &self,
+        cell: [i32; 3],
+        offsets: &[[i32; 3]],
+        t_enter: f32,
+        visit: &mut impl FnMut(f32, &[u32]) -> bool,
+    ) -> bool {
+        offsets.iter().all(|offset| {
+            let key = (
+                cell[0] + offset[0],
+                cell[1] + offset[1],
+                cell[2] + offset[2],
+            );
+            match self.cells.get(&key) {
+                Some(ids) => visit(t_enter, ids),
+                None => true,
+            }
+        })
+    }
+
+    /// Suma `id` a su celda, en orden
+    fn add(&mut self, id: u32, cell: Cell) {
+        let ids = self.cells.entry(cell).or_default();
+        let at = ids.partition_point(|other| *other < id);
+        ids.insert(at, id);
+        self.counters.cells_touched += 1;
+        self.bounds = Some(match self.bounds {
+            Some((lo, hi)) => (
+                (lo.0.min(cell.0), lo.1.min(cell.1), lo.2.min(cell.2)),
+                (hi.0.max(cell.0), hi.1.max(cell.1), hi.2.max(cell.2)),
+            ),
+            None => (cell, cell),
+        });
+    }
+
+    /// Saca `id` de su celda; una celda vacía se borra
+    fn take(&mut self, id: u32, cell: Cell) {
+        if let Some(ids) = self.cells.get_mut(&cell) {
+            ids.retain(|other| *other != id);
+            if ids.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+        self.counters.cells_touched += 1;
+    }
+}
+
+/// Caja (mínimo, máximo) de la celda
+pub fn cell_bounds(cell: Cell) -> (Vector3, Vector3) {
+    let center = Vector3::new(cell.0 as f32, cell.1 as f32, cell.2 as f32);
+    (center - Vector3::one() * 0.5, center + Vector3::one() * 0.5)
+}
+
+/// Corrimientos a las celdas vecinas por los bordes `(eje, lado)`, en todas
+/// las combinaciones (el primero es la celda misma)
+fn side_offsets(borders: &[(usize, i32)]) -> Vec<[i32; 3]> {
+    let mut offsets = vec![[0; 3]];
+    for &(axis, side) in borders {
+        for i in 0..offsets.len() {
+            let mut offset = offsets[i];
+            offset[axis] = side;
+            offsets.push(offset);
+        }
+    }
+    offsets
+}
+
+/// Celda del bloque, si su caja entra en ella
+fn cell_of(block: &Block) -> Option<Cell> {
+    let p = block.position;
+    let cell = (p.x.round() as i32, p.y.round() as i32, p.z.round() as i32);
+    let (min, max) = block.bounds();
+    let fits = |center: i32, min: f32, max: f32| {
+        min >= center as f32 - 0.5 - FIT_TOLERANCE && max <= center as f32 + 0.5 + FIT_TOLERANCE
+    };
+    (fits(cell.0, min.x, max.x) && fits(cell.1, min.y, max.y) && fits(cell.2, min.z, max.z))
+        .then_some(cell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_shape::BlockShape;
+    use crate::block_types::BlockType;
+    use crate::camera::CameraConfig;
+    use crate::renderer::render_reference;
+    use crate::rng::Rng;
+    use crate::scene::{Scene, create_optimized_scene, neighbors_changed};
+    use crate::settings::Settings;
+    use std::time::{Duration, Instant};
+
+    fn stone(x: f32, y: f32, z: f32) -> Block {
+        BlockType::Stone.to_block(Vector3::new(x, y, z), 1.0)
+    }
+
+    /// Celdas que devuelve `walk`, con la distancia de entrada
+    fn walked(grid: &VoxelGrid, origin: Vector3, dir: Vector3) -> Vec<(f32, Vec<u32>)> {
+        let mut visited = Vec::new();
+        grid.walk(&origin, &dir, f32::INFINITY, |t, ids| {
+            visited.push((t, ids.to_vec()));
+            true
+        });
+        visited
+    }
+
+    #[test]
+    fn walk_visits_the_crossed_cells_in_order() {
+        let blocks: Vec<Block> = (0..6).map(|x| stone(x as f32, 0.0, 0.0)).collect();
+        let grid = VoxelGrid::build(&blocks);
+
+        // A lo largo de la fila, de un extremo al otro
+        let visited = walked(
+            &grid,
+            Vector3::new(-3.0, 0.2, 0.1),
+            Vector3::new(1.0, 0.0, 0.0),
+        );
+        let ids: Vec<u32> = visited.iter().flat_map(|(_, ids)| ids.clone()).collect();
+        assert_eq!(ids, [0, 1, 2, 3, 4, 5]);
+        for (i, (t, _)) in visited.iter().enumerate() {
+            assert!(
+                (t - (2.5 + i as f32)).abs() < 1e-5,
+                "entrada a la celda {}: {}",
+                i,
+                t
+            );
+        }
+
+        // Al revés, y cortando a mitad de camino
+        let mut seen = Vec::new();
+        grid.walk(
+            &Vector3::new(9.0, 0.0, 0.0),
+            &Vector3::new(-1.0, 0.0, 0.0),
+            5.0,
+            |_, ids| {
+                seen.extend_from_slice(ids);
+                seen.len() < 2
+            },
+        );
+        assert_eq!(seen, [5, 4]);
+
+        // Un rayo que pasa por arriba no toca nada
+        assert!(
+            walked(
+                &grid,
+                Vector3::new(-3.0, 2.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0)
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn walk_through_an_edge_also_visits_the_side_cells() {
+        let blocks = vec![
+            stone(1.0, 0.0, 0.0),
+            stone(0.0, 1.0, 0.0),
+            stone(1.0, 1.0, 0.0),
+        ];
+        let grid = VoxelGrid::build(&blocks);
+        // Diagonal exacta por la arista entre las cuatro celdas
+        let dir = Vector3::new(1.0, 1.0, 0.0).normalized();
+        let ids: Vec<u32> = walked(&grid, Vector3::new(-0.5, -0.5, 0.0), dir)
+            .into_iter()
+            .flat_map(|(_, ids)| ids)
+            .collect();
+        for id in 0..3 {
+            assert!(ids.contains(&id), "falta el bloque {} en {:?}", id, ids);
+        }
+    }
+
+    #[test]
+    fn blocks_that_leave_their_cell_are_kept_apart() {
+        let blocks = vec![
+            stone(0.0, 0.0, 0.0),
+            // Corrido media celda
+            stone(0.5, 1.0, 0.0),
+            // Más grande que una celda
+            BlockType::Stone.to_block(Vector3::new(3.0, 0.0, 0.0), 2.0),
+            // Una losa sigue entrando en su celda
+            stone(0.0, 2.0, 0.0).with_shape(BlockShape::Slab { top: true }),
+        ];
+        let grid = VoxelGrid::build(&blocks);
+        assert_eq!(grid.block_cell(0), Some((0, 0, 0)));
+        assert_eq!(grid.block_cell(1), None);
+        assert_eq!(grid.block_cell(2), None);
+        assert_eq!(grid.block_cell(3), Some((0, 2, 0)));
+        let ids: Vec<u32> = grid.cells().flat_map(|(_, ids)| ids.to_vec()).collect();
+        assert!(!ids.contains(&1) && !ids.contains(&2));
+    }
+
+    #[test]
+    fn edits_in_place_match_a_fresh_build() {
+        let mut blocks: Vec<Block> = (0..20)
+            .map(|i| stone((i % 5) as f32, 0.0, (i / 5) as f32))
+            .collect();
+        // Dos losas en la misma celda
+        blocks.push(stone(9.0, 0.0, 0.0).with_shape(BlockShape::Slab { top: false }));
+        blocks.push(stone(9.0, 0.0, 0.0).with_shape(BlockShape::Slab { top: true }));
+        blocks.push(stone(0.5, 3.0, 0.0));
+        let mut grid = VoxelGrid::build(&blocks);
+
+        let mut rng = Rng::new(7, 0);
+        for i in 0..300 {
+            let pick = |rng: &mut Rng, len: usize| (rng.next_f32() * len as f32) as usize % len;
+            let roll = rng.next_f32();
+            if roll < 0.4 && !blocks.is_empty() {
+                let index = pick(&mut rng, blocks.len());
+                blocks.swap_remove(index);
+                grid.remove(index);
+            } else if roll < 0.7 && !blocks.is_empty() {
+                // Se mueve a otra celda, o media celda afuera de la grilla
+                let index = pick(&mut rng, blocks.len());
+                let shift = if i % 4 == 0 { 0.5 } else { 1.0 };
+                blocks[index].position.x += shift;
+                grid.update(index, &blocks[index]);
+            } else {
+                let block = stone((i % 7) as f32, (i % 3) as f32, 0.0);
+                grid.insert(blocks.len(), &block);
+                blocks.push(block);
+            }
+            assert_eq!(
+                grid,
+                VoxelGrid::build(&blocks),
+                "después de la edición {}",
+                i
+            );
+        }
+        let counters = grid.counters();
+        assert_eq!((counters.updates, counters.rebuilds), (300, 0));
+        assert!(
+            counters.cells_touched <= 2 * counters.updates,
+            "{} celdas en {} ediciones",
+            counters.cells_touched,
+            counters.updates
+        );
+    }
+
+    /// Cámara de las imágenes de referencia de la isla, 64x48
+    fn island_camera() -> CameraConfig {
+        CameraConfig::new(
+            Vector3::new(0.0, 4.0, -10.0),
+            std::f32::consts::FRAC_PI_2,
+            -0.3,
+            64,
+            48,
+            std::f32::consts::FRAC_PI_3,
+            64.0 / 48.0,
+        )
+    }
+
+    /// Pone y quita bloques de piedra al azar hasta hacer `count` ediciones.
+    /// Devuelve cuánto tardaron las ediciones.
+    fn random_edits(scene: &mut Scene, count: u32) -> Duration {
+        let mut rng = Rng::new(42, 0);
+        let mut elapsed = Duration::ZERO;
+        let mut edits = 0;
+        while edits < count {
+            let start = Instant::now();
+            if edits % 2 == 0 {
+                let cell =
+                    |rng: &mut Rng, range: f32| (rng.next_f32() * range).floor() - range / 2.0;
+                let position = Vector3::new(
+                    cell(&mut rng, 16.0),
+                    1.0 + cell(&mut rng, 6.0).abs(),
+                    cell(&mut rng, 16.0),
+                );
+                if scene
+                    .place_block(stone(position.x, position.y, position.z))
+                    .is_none()
+                {
+                    continue;
+                }
+            } else {
+                let index = (rng.next_f32() * scene.len() as f32) as usize % scene.len();
+                scene.remove_block(index);
+            }
+            elapsed += start.elapsed();
+            edits += 1;
+        }
+        elapsed
+    }
+
+    #[test]
+    fn thousand_edits_stay_in_place_and_render_like_a_fresh_scene() {
+        let mut scene = create_optimized_scene();
+        random_edits(&mut scene, 1000);
+        // Cada edición toca su celda y a lo sumo la del bloque que se mudó
+        let counters = scene.grid_counters();
+        assert_eq!((counters.updates, counters.rebuilds), (1000, 0));
+        assert!(counters.cells_touched <= 2000, "{:?}", counters);
+        assert_eq!(scene.bvh_counters().rebuilds, 0);
+        assert_eq!(scene.grid(), Some(&VoxelGrid::build(&scene)));
+        // Los vecinos recalculados celda por celda dan lo mismo que toda la escena
+        let mut blocks = scene.to_vec();
+        neighbors_changed(&mut blocks);
+        assert!(*scene == blocks, "caras interiores o cercas distintas");
+
+        let settings = Settings::default();
+        let render = |scene: &Scene| render_reference(64, 48, scene, &island_camera(), &settings);
+        let edited = render(&scene);
+        // La misma escena con la grilla armada de cero
+        let mut fresh = scene.clone();
+        fresh.invalidate_grid();
+        fresh.refresh_grid();
+        assert_eq!(fresh.grid_counters().rebuilds, 1);
+        assert!(
+            edited == render(&fresh),
+            "distinta de la grilla armada de cero"
+        );
+        // Con la grilla vieja se prueban todos los objetos: mismo resultado
+        let mut linear = scene.clone();
+        linear.invalidate_grid();
+        assert!(linear.grid().is_none());
+        assert!(
+            edited == render(&linear),
+            "distinta de probar todos los objetos"
+        );
+    }
+
+    /// Medición de tiempo: depende de la máquina, se corre a mano con
+    /// `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn thousand_edits_stay_fast() {
+        let mut scene = create_optimized_scene();
+        let per_edit = random_edits(&mut scene, 1000) / 1000;
+        assert!(
+            per_edit < Duration::from_millis(1),
+            "{:?} por edición",
+            per_edit
+        );
+    }
+}
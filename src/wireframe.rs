@@ -0,0 +1,209 @@
+// wireframe.rs - Cajas de alambre proyectadas sobre el frame (selección y
+// vista de depuración de la escena)
+use raylib::prelude::*;
+
+use crate::block::Block;
+use crate::bvh::Bvh;
+use crate::camera::CameraConfig;
+use crate::framebuffer::{Framebuffer, color_to_u32};
+use crate::gbuffer::depth_colormap;
+use crate::voxel_grid::{VoxelGrid, cell_bounds};
+
+/// Qué cajas de alambre se dibujan sobre el frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireframeMode {
+    #[default]
+    Off,
+    /// La caja de cada bloque, coloreada por distancia
+    Blocks,
+    /// Las celdas ocupadas de la grilla de aceleración, coloreadas por
+    /// cuántos bloques tienen
+    Grid,
+}
+
+impl WireframeMode {
+    pub fn next(self) -> Self {
+        match self {
+            WireframeMode::Off => WireframeMode::Blocks,
+            WireframeMode::Blocks => WireframeMode::Grid,
+            WireframeMode::Grid => WireframeMode::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WireframeMode::Off => "OFF",
+            WireframeMode::Blocks => "bloques",
+            WireframeMode::Grid => "grilla",
+        }
+    }
+}
+
+/// Colores de la vista de la grilla: celdas con un bloque, con dos (losas
+/// compartidas) o más, y hojas de la BVH (bloques que no entran en una
+/// celda y primitivas)
+const ONE_BLOCK_CELL: Color = Color::GREEN;
+const SHARED_CELL: Color = Color::ORANGE;
+const BVH_LEAF: Color = Color::MAGENTA;
+
+/// Dibuja en el framebuffer las aristas de la caja `min`-`max` proyectadas
+/// por la cámara
+pub fn draw_box_outline(
+    framebuffer: &mut Framebuffer,
+    camera: &CameraConfig,
+    min: Vector3,
+    max: Vector3,
+    color: u32,
+) {
+    let corners: Vec<Vector3> = (0..8)
+        .map(|i| {
+            Vector3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        })
+        .collect();
+
+    // Aristas: pares de esquinas que difieren en un solo eje. Las que cruzan
+    // por detrás de la cámara se recortan contra el plano cercano.
+    for a in 0..8usize {
+        for axis in [1usize, 2, 4] {
+            let b = a | axis;
+            if b == a {
+                continue;
+            }
+            let Some((start, end)) = camera.clip_to_near(corners[a], corners[b]) else {
+                continue;
+            };
+            if let (Some((x0, y0)), Some((x1, y1))) = (camera.project(start), camera.project(end)) {
+                framebuffer.draw_line(x0 as i32, y0 as i32, x1 as i32, y1 as i32, color);
+            }
+        }
+    }
+}
+
+/// Vista de alambre: la caja de cada bloque de la escena, con el color de
+/// la vista de profundidad según su distancia en `depth_range` (cerca en
+/// rojo, lejos en azul)
+pub fn draw_block_boxes(
+    framebuffer: &mut Framebuffer,
+    camera: &CameraConfig,
+    blocks: &[Block],
+    depth_range: (f32, f32),
+) {
+    let (near, far) = depth_range;
+    let boxes = far_to_near(camera, blocks.iter().map(|block| (block.bounds(), ())));
+    for (distance, (min, max), ()) in boxes {
+        let t = ((distance - near) / (far - near).max(f32::EPSILON)).clamp(0.0, 1.0);
+        let color = color_to_u32(depth_colormap(t));
+        draw_box_outline(framebuffer, camera, min, max, color);
+    }
+}
+
+/// Vista de las estructuras de aceleración: cada celda ocupada de la
+/// grilla con el color de su ocupación, más las hojas de la BVH
+pub fn draw_grid_cells(
+    framebuffer: &mut Framebuffer,
+    camera: &CameraConfig,
+    grid: &VoxelGrid,
+    bvh: &Bvh,
+) {
+    let cells = grid.cells().map(|(cell, ids)| {
+        let color = match ids.len() {
+            1 => ONE_BLOCK_CELL,
+            _ => SHARED_CELL,
+        };
+        (cell_bounds(cell), color)
+    });
+    let leaves = bvh.leaf_bounds().map(|bounds| (bounds, BVH_LEAF));
+    for (_, (min, max), color) in far_to_near(camera, cells.chain(leaves)) {
+        draw_box_outline(framebuffer, camera, min, max, color_to_u32(color));
+    }
+}
+
+/// Cajas con su distancia a la cámara, de lejos a cerca para que las
+/// cercanas queden encima. Las que quedan enteras detrás de la cámara se
+/// descartan: no hay nada que dibujar.
+fn far_to_near<T>(
+    camera: &CameraConfig,
+    boxes: impl Iterator<Item = ((Vector3, Vector3), T)>,
+) -> Vec<(f32, (Vector3, Vector3), T)> {
+    let mut visible: Vec<_> = boxes
+        .filter_map(|((min, max), extra)| {
+            let center = (min + max) * 0.5;
+            let radius = (max - min).length() * 0.5;
+            if (center - camera.pos).dot(camera.forward) < -radius {
+                return None;
+            }
+            Some(((center - camera.pos).length(), (min, max), extra))
+        })
+        .collect();
+    visible.sort_by(|a, b| b.0.total_cmp(&a.0));
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_shape::BlockShape;
+    use crate::block_types::BlockType;
+    use crate::scene::Scene;
+
+    #[test]
+    fn grid_view_colors_cells_by_occupancy() {
+        let stone = |x: f32| BlockType::Stone.to_block(Vector3::new(x, 0.0, 0.0), 1.0);
+        let blocks = vec![
+            stone(-2.0),
+            stone(0.0).with_shape(BlockShape::Slab { top: false }),
+            stone(0.0).with_shape(BlockShape::Slab { top: true }),
+            // Corrido media celda: fuera de la grilla, en la BVH
+            stone(2.5),
+        ];
+        let scene = Scene::new(blocks);
+        let (grid, bvh) = (scene.grid().unwrap(), scene.bvh().unwrap());
+        let camera = CameraConfig::new(
+            Vector3::new(0.0, 1.0, -8.0),
+            std::f32::consts::FRAC_PI_2,
+            -0.1,
+            80,
+            60,
+            std::f32::consts::FRAC_PI_3,
+            80.0 / 60.0,
+        );
+        let mut framebuffer = Framebuffer::new(80, 60);
+        framebuffer.clear(0);
+        draw_grid_cells(&mut framebuffer, &camera, grid, bvh);
+
+        let count = |color: Color| {
+            let color = color_to_u32(color);
+            framebuffer
+                .as_slice()
+                .iter()
+                .filter(|p| **p == color)
+                .count()
+        };
+        for color in [ONE_BLOCK_CELL, SHARED_CELL, BVH_LEAF] {
+            assert!(count(color) > 10, "falta el color {:?}", color);
+        }
+        let drawn = framebuffer.as_slice().iter().filter(|p| **p != 0).count();
+        assert_eq!(
+            drawn,
+            count(ONE_BLOCK_CELL) + count(SHARED_CELL) + count(BVH_LEAF)
+        );
+
+        // Detrás de la cámara no se dibuja nada
+        let behind = CameraConfig::new(
+            Vector3::new(0.0, 1.0, 8.0),
+            std::f32::consts::FRAC_PI_2,
+            0.0,
+            80,
+            60,
+            std::f32::consts::FRAC_PI_3,
+            80.0 / 60.0,
+        );
+        framebuffer.clear(0);
+        draw_grid_cells(&mut framebuffer, &behind, grid, bvh);
+        assert!(framebuffer.as_slice().iter().all(|p| *p == 0));
+    }
+}
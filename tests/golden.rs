@@ -0,0 +1,219 @@
+// golden.rs - Imágenes de referencia: frames chicos y deterministas que se
+// comparan contra los PPM guardados en tests/golden/ para detectar cambios
+// en el sombreado.
+//
+// Para regenerarlas después de un cambio visual intencional:
+//     RAYTRACER_UPDATE_GOLDEN=1 cargo test --test golden
+use std::f32::consts::FRAC_PI_2;
+use std::fs;
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use project2_graphics::camera::CameraConfig;
+use project2_graphics::fog::Fog;
+use project2_graphics::framebuffer::{u32_to_rgb, write_ppm};
+use project2_graphics::renderer::render_reference;
+use project2_graphics::scene::{
+    Scene, create_material_gallery, create_optimized_scene, create_primitive_showcase,
+};
+use project2_graphics::settings::Settings;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// Variable de entorno que reescribe las referencias en vez de compararlas
+const UPDATE_VAR: &str = "RAYTRACER_UPDATE_GOLDEN";
+
+/// Diferencia máxima por canal que se considera ruido de punto flotante
+const CHANNEL_TOLERANCE: u8 = 3;
+
+/// Pixeles que pueden pasarse de la tolerancia (bordes que caen justo en
+/// otro bloque por un redondeo)
+const MAX_DIFFERING_PIXELS: usize = 8;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.ppm", name))
+}
+
+/// Lee un PPM binario (P6, maxval 255) como el que escribe `write_ppm`
+fn read_ppm(path: &PathBuf) -> (u32, u32, Vec<[u8; 3]>) {
+    let bytes = fs::read(path).unwrap_or_else(|err| {
+        panic!(
+            "no se pudo leer {} ({}); generala con {}=1",
+            path.display(),
+            err,
+            UPDATE_VAR
+        )
+    });
+    // Cabecera: cuatro campos separados por espacios y un único separador
+    // antes de los datos
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while fields.len() < 4 {
+        if bytes[i].is_ascii_whitespace() {
+            if i > start {
+                fields.push(String::from_utf8_lossy(&bytes[start..i]).into_owned());
+            }
+            start = i + 1;
+        }
+        i += 1;
+    }
+    assert_eq!(fields[0], "P6", "{} no es un PPM binario", path.display());
+    assert_eq!(fields[3], "255");
+    let width: u32 = fields[1].parse().unwrap();
+    let height: u32 = fields[2].parse().unwrap();
+    let pixels = bytes[i..]
+        .chunks_exact(3)
+        .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+        .collect::<Vec<_>>();
+    assert_eq!(pixels.len(), (width * height) as usize);
+    (width, height, pixels)
+}
+
+/// Ajustes fijos: los valores por defecto, sin leer `settings.toml`
+fn reference_settings() -> Settings {
+    Settings::default()
+}
+
+fn camera(pos: Vector3, yaw: f32, pitch: f32) -> CameraConfig {
+    CameraConfig::new(
+        pos,
+        yaw,
+        pitch,
+        WIDTH as usize,
+        HEIGHT as usize,
+        std::f32::consts::FRAC_PI_3,
+        WIDTH as f32 / HEIGHT as f32,
+    )
+}
+
+/// Renderiza `scene` y la compara con su referencia (o la reescribe)
+fn check_golden(name: &str, scene: &Scene, camera: &CameraConfig) {
+    check_golden_within(
+        name,
+        scene,
+        camera,
+        &reference_settings(),
+        CHANNEL_TOLERANCE,
+        MAX_DIFFERING_PIXELS,
+    );
+}
+
+/// Como `check_golden`, con los ajustes, la tolerancia por canal y la
+/// cantidad de pixeles que pueden pasarla explícitas (0 y 0: idéntica bit
+/// a bit)
+fn check_golden_within(
+    name: &str,
+    scene: &Scene,
+    camera: &CameraConfig,
+    settings: &Settings,
+    channel_tolerance: u8,
+    max_differing_pixels: usize,
+) {
+    let pixels = render_reference(WIDTH, HEIGHT, scene, camera, settings);
+    let path = golden_path(name);
+
+    if std::env::var_os(UPDATE_VAR).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        write_ppm(&path, WIDTH, HEIGHT, &pixels).unwrap();
+        return;
+    }
+
+    let (width, height, expected) = read_ppm(&path);
+    assert_eq!((width, height), (WIDTH, HEIGHT), "tamaño de {}", name);
+
+    let mut differing = 0;
+    let mut worst = (0, 0, 0u8);
+    for (i, (&actual, reference)) in pixels.iter().zip(&expected).enumerate() {
+        let actual = u32_to_rgb(actual);
+        let diff = (0..3)
+            .map(|c| actual[c].abs_diff(reference[c]))
+            .max()
+            .unwrap();
+        if diff > channel_tolerance {
+            differing += 1;
+        }
+        if diff > worst.2 {
+            worst = (i as u32 % WIDTH, i as u32 / WIDTH, diff);
+        }
+    }
+    assert!(
+        differing <= max_differing_pixels,
+        "{}: {} pixeles difieren más de {} (peor: {} en ({}, {})); si el cambio es \
+         intencional, regenerá con {}=1",
+        name,
+        differing,
+        channel_tolerance,
+        worst.2,
+        worst.0,
+        worst.1,
+        UPDATE_VAR
+    );
+}
+
+#[test]
+fn island_matches_golden() {
+    let scene = create_optimized_scene();
+    check_golden(
+        "island",
+        &scene,
+        &camera(Vector3::new(0.0, 4.0, -10.0), FRAC_PI_2, -0.3),
+    );
+}
+
+/// Los ajustes por defecto reproducen la referencia bit a bit: el paso de
+/// las constantes sueltas a `RenderSettings` no cambió ni un pixel, y así
+/// tiene que seguir mientras nadie regenere la imagen a propósito
+#[test]
+fn default_island_is_bit_identical() {
+    let scene = create_optimized_scene();
+    check_golden_within(
+        "island",
+        &scene,
+        &camera(Vector3::new(0.0, 4.0, -10.0), FRAC_PI_2, -0.3),
+        &reference_settings(),
+        0,
+        0,
+    );
+}
+
+/// Niebla con densidad 0: no cambia nada aunque tenga inicio y color
+#[test]
+fn zero_density_fog_is_bit_identical() {
+    let scene = create_optimized_scene();
+    let mut settings = reference_settings();
+    settings.render.fog = Fog::new(0.0, 5.0, Some(Vector3::new(1.0, 0.0, 1.0)));
+    check_golden_within(
+        "island",
+        &scene,
+        &camera(Vector3::new(0.0, 4.0, -10.0), FRAC_PI_2, -0.3),
+        &settings,
+        0,
+        0,
+    );
+}
+
+#[test]
+fn primitives_match_golden() {
+    let scene = create_primitive_showcase();
+    check_golden(
+        "primitives",
+        &scene,
+        &camera(Vector3::new(0.0, 3.0, -7.0), FRAC_PI_2, -0.3),
+    );
+}
+
+#[test]
+fn material_gallery_matches_golden() {
+    let scene: Scene = create_material_gallery().into();
+    check_golden(
+        "gallery",
+        &scene,
+        &camera(Vector3::new(0.0, 3.0, -9.0), FRAC_PI_2, -0.25),
+    );
+}